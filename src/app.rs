@@ -2,14 +2,28 @@
 
 use eframe::egui::{self, CentralPanel, Color32, Context, FontData, FontDefinitions, FontFamily, Frame, Margin, RichText, Rounding, TopBottomPanel};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::{ProcessesToUpdate, System};
 
-use crate::system::{CpuInfo, ProcessManager};
-use crate::ui::{CpuMonitorPanel, ProcessListPanel, SchedulerPanel};
-use crate::utils::CpuHistory;
+use crate::system::{
+    close_action, core_mismatch_category, default_script_path, dispatch_hotkey, preset_changes, in_quiet_hours, inactive_scheduled_rules, is_hybrid, poll_tray, process_categories, publish_snapshot, read_cpu_pressure, scheduling_health, serve_snapshots, spawn_tray, ApplySource, AutoRule, CloseAction, CoreTopology, CpuInfo, CustomCategory,
+    DropInWatcher, FocusBoostConfig, FocusBooster, GameList, GlobalHotkeys, HotkeyAction, HotkeyBinding, InstanceCommand, KeyChord, InstanceGuard, Limitations, LocalTime, PinnedProcess, ProcessManager, Profile, Psi, RuleEngine, RuleResolution, RunDelaySampler, SchedulePreset,
+    QuietHours, RuleSchedule, SessionJournal, SnapshotSlot, SystemProbe, ThermalConfig, ThermalController, TrayEvent, UnderclockRule, UsageSource, unix_timestamp, IDLE_P95_PERCENT,
+};
+use crate::ui::conflict::{ApplyGuard, ManualApply};
+use crate::ui::cpu_monitor::{CellDisplay, DEFAULT_MAX_COMPARED_CORES};
+use crate::ui::theme::{CcdColor, CcdPalette};
+use crate::ui::{
+    CpuMonitorPanel, DebugPanel, DeferredEdit, LimitationsWindow, OnboardingOverlay, ProfileAction, ProcessListPanel, SchedulerPanel, SettingsPanel,
+    WatchlistPanel,
+};
+use crate::startup::{StartupData, StartupLoader};
+use crate::utils::{Clock, CpuHistory};
 
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +36,111 @@ pub struct AppConfig {
     pub window_width: f32,
     /// 窗口高度
     pub window_height: f32,
+    /// 进程级 GPU 使用率监控（需要 nvidia-smi 或 amdgpu 驱动）
+    #[serde(default)]
+    pub gpu_monitoring: bool,
+    /// 进程详情中显示延迟统计（需要内核开启 delay accounting）
+    #[serde(default)]
+    pub delay_accounting: bool,
+    /// 核心使用率动画的平滑系数 (0-1，越大越快)
+    #[serde(default = "default_animation_smoothing")]
+    pub animation_smoothing: f32,
+    /// 高温时自动降温
+    #[serde(default)]
+    pub thermal: ThermalConfig,
+    /// 最近启用的调优档案
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// 核心使用率的数据来源
+    #[serde(default)]
+    pub usage_source: UsageSource,
+    /// 焦点窗口所属进程临时加速
+    #[serde(default)]
+    pub focus_boost: FocusBoostConfig,
+    /// 编辑器切换调度策略时预填的优先级（按策略短名称）
+    #[serde(default = "default_policy_defaults")]
+    pub policy_defaults: HashMap<String, i32>,
+    /// 核心对比图同时显示的最多核心数
+    #[serde(default = "default_max_compared_cores")]
+    pub max_compared_cores: usize,
+    /// 核心网格单元格显示的数值
+    #[serde(default)]
+    pub cell_display: CellDisplay,
+    /// 核心网格"只显示繁忙核心"的使用率阈值 (%)，默认与节能建议的空闲阈值相同
+    #[serde(default = "default_idle_core_threshold")]
+    pub idle_core_threshold: f32,
+    /// 各 CCD 的强调色（为空或与检测到的 CCD 不一致时使用默认颜色）
+    #[serde(default)]
+    pub ccd_colors: Vec<CcdColor>,
+    /// 定时规则：在指定时间段内启用的自动规则
+    #[serde(default)]
+    pub rule_schedules: Vec<RuleSchedule>,
+    /// 免打扰时间段：时间段内不显示后台提示
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHours>,
+    /// 保留给系统的核心：预设和规则的符号亲和性目标不包含这些核心
+    #[serde(default)]
+    pub reserved_cores: Vec<usize>,
+    /// 显示调试工具标签页（负载生成器）
+    #[serde(default)]
+    pub debug_tools: bool,
+    /// 自定义进程分类（与内置分类同名时扩展内置分类）
+    #[serde(default)]
+    pub custom_categories: Vec<CustomCategory>,
+    /// 进程列表启用的分类
+    #[serde(default)]
+    pub active_categories: Vec<String>,
+    /// 已显示过首次运行的使用说明
+    #[serde(default)]
+    pub onboarding_shown: bool,
+    /// 监视列表中固定的进程（按进程名和启动时间识别）
+    #[serde(default)]
+    pub pinned_processes: Vec<PinnedProcess>,
+    /// 进程表密度
+    #[serde(default)]
+    pub table_density: TableDensity,
+    /// 关闭窗口时改为最小化（需要托盘图标）
+    #[serde(default = "default_close_to_tray")]
+    pub close_to_tray: bool,
+    /// 已确认过关闭窗口时的行为
+    #[serde(default)]
+    pub close_to_tray_confirmed: bool,
+    /// 快捷键绑定
+    #[serde(default)]
+    pub hotkeys: Vec<HotkeyBinding>,
+    /// 核心持续低于标称频率的检测规则
+    #[serde(default)]
+    pub underclock: UnderclockRule,
+    /// 每隔多少个进程刷新周期完整刷新一次，其余周期只刷新活跃、选中、监视中和匹配搜索的进程；1 表示每次都完整刷新
+    #[serde(default = "default_full_process_refresh_cycles")]
+    pub full_process_refresh_cycles: u32,
+}
+
+fn default_animation_smoothing() -> f32 {
+    0.3
+}
+
+fn default_idle_core_threshold() -> f32 {
+    IDLE_P95_PERCENT
+}
+
+fn default_max_compared_cores() -> usize {
+    DEFAULT_MAX_COMPARED_CORES
+}
+
+fn default_close_to_tray() -> bool {
+    true
+}
+
+fn default_full_process_refresh_cycles() -> u32 {
+    5
+}
+
+fn default_policy_defaults() -> HashMap<String, i32> {
+    [("OTHER", 0), ("BATCH", 5), ("IDLE", 0), ("FIFO", 50), ("RR", 50)]
+        .into_iter()
+        .map(|(name, priority)| (name.to_string(), priority))
+        .collect()
 }
 
 impl Default for AppConfig {
@@ -31,6 +150,32 @@ impl Default for AppConfig {
             history_length: 120, // 60 秒 @ 500ms
             window_width: 1000.0,
             window_height: 700.0,
+            gpu_monitoring: false,
+            delay_accounting: false,
+            animation_smoothing: default_animation_smoothing(),
+            thermal: ThermalConfig::default(),
+            active_profile: None,
+            usage_source: UsageSource::default(),
+            focus_boost: FocusBoostConfig::default(),
+            policy_defaults: default_policy_defaults(),
+            max_compared_cores: default_max_compared_cores(),
+            cell_display: CellDisplay::default(),
+            idle_core_threshold: default_idle_core_threshold(),
+            ccd_colors: Vec::new(),
+            rule_schedules: Vec::new(),
+            quiet_hours: Vec::new(),
+            reserved_cores: Vec::new(),
+            debug_tools: false,
+            custom_categories: Vec::new(),
+            active_categories: Vec::new(),
+            onboarding_shown: false,
+            pinned_processes: Vec::new(),
+            table_density: TableDensity::default(),
+            close_to_tray: default_close_to_tray(),
+            close_to_tray_confirmed: false,
+            hotkeys: Vec::new(),
+            underclock: UnderclockRule::default(),
+            full_process_refresh_cycles: default_full_process_refresh_cycles(),
         }
     }
 }
@@ -66,14 +211,88 @@ impl AppConfig {
     }
 }
 
+/// 进程表密度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TableDensity {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl TableDensity {
+    pub const ALL: [TableDensity; 2] = [TableDensity::Comfortable, TableDensity::Compact];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TableDensity::Comfortable => "舒适",
+            TableDensity::Compact => "紧凑",
+        }
+    }
+}
+
+/// 等待确认的批量还原
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MassRestore {
+    /// 还原为修改前的设置
+    Revert,
+    /// 恢复为默认调度设置
+    Defaults,
+}
+
 /// 当前标签页
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     CpuMonitor,
     ProcessList,
     Scheduler,
+    Settings,
+    Debug,
+}
+
+impl Tab {
+    /// 标签栏中的顺序
+    pub const ALL: [Tab; 5] = [Tab::CpuMonitor, Tab::ProcessList, Tab::Scheduler, Tab::Settings, Tab::Debug];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tab::CpuMonitor => "CPU 监控",
+            Tab::ProcessList => "进程管理",
+            Tab::Scheduler => "调度策略",
+            Tab::Settings => "设置",
+            Tab::Debug => "调试",
+        }
+    }
+
+    /// 按命令行名称（--tab）查找
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(Tab::CpuMonitor),
+            "processes" => Some(Tab::ProcessList),
+            "scheduler" => Some(Tab::Scheduler),
+            "settings" => Some(Tab::Settings),
+            "debug" => Some(Tab::Debug),
+            _ => None,
+        }
+    }
+
+    /// 是否需要完整的进程数据
+    fn needs_process_details(&self) -> bool {
+        matches!(self, Tab::ProcessList | Tab::Scheduler)
+    }
 }
 
+/// 进程相关标签页激活时的进程刷新间隔
+const PROCESS_REFRESH_INTERVAL: Duration = Duration::from_millis(1000);
+/// 其他标签页激活时的轻量保活刷新间隔
+const PROCESS_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+/// 数据超过此时长未更新时标记为严重过期
+const STALE_DATA_THRESHOLD: Duration = Duration::from_secs(10);
+/// 设置停止修改多久后写入配置文件
+const CONFIG_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+/// 设置页"暂停告警"的时长
+const ALERT_PAUSE_DURATION: Duration = Duration::from_secs(3600);
+
 /// 主应用
 pub struct HexinApp {
     /// 应用配置
@@ -84,8 +303,24 @@ pub struct HexinApp {
     cpu_info: CpuInfo,
     /// CPU 历史数据
     cpu_history: CpuHistory,
+    /// CPU 压力 (PSI)，内核不支持时为 None
+    cpu_pressure: Option<Psi>,
+    /// 全系统运行延迟采样
+    run_delay: RunDelaySampler,
     /// 进程管理器
     process_manager: ProcessManager,
+    /// 本次会话的调度更改记录
+    session: SessionJournal,
+    /// 自动规则引擎（由进程生命周期回调驱动）
+    rule_engine: Arc<Mutex<RuleEngine>>,
+    /// profiles.d 目录监视（无法确定配置目录时为 None）
+    dropin_watcher: Option<DropInWatcher>,
+    /// 后台启动检测（完成后为 None）
+    startup: Option<StartupLoader>,
+    /// 一键还原的结果
+    session_message: Option<String>,
+    /// 录制脚本的保存路径（正在输入时为 Some）
+    script_path: Option<String>,
     /// 当前标签页
     current_tab: Tab,
     /// CPU 监控面板
@@ -94,12 +329,72 @@ pub struct HexinApp {
     process_list_panel: ProcessListPanel,
     /// 调度策略面板
     scheduler_panel: SchedulerPanel,
+    /// 设置面板
+    settings_panel: SettingsPanel,
+    /// 监视列表条
+    watchlist_panel: WatchlistPanel,
+    /// 手动应用前的检查状态（监视列表和快捷键共用）
+    apply_guard: ApplyGuard,
+    /// apply_guard 中规则快照的版本
+    rule_guard_revision: Option<u64>,
+    /// 已推送给调度面板的 apply_guard 规则版本（拓扑变化时重置）
+    scheduler_revision: Option<u64>,
+    /// 已推送给进程列表的 apply_guard 规则版本（拓扑变化时重置）
+    process_list_revision: Option<u64>,
+    /// 已推送给设置面板（规则名称和矛盾检测）的规则引擎版本
+    settings_revision: Option<u64>,
+    /// 调试面板
+    debug_panel: DebugPanel,
+    /// 使用说明浮层
+    onboarding: OnboardingOverlay,
+    /// 权限与限制清单
+    limitations: Limitations,
+    /// 权限与限制窗口
+    limitations_window: LimitationsWindow,
+    /// 高温降温自动化
+    thermal: ThermalController,
+    /// 前台加速
+    focus_booster: FocusBooster,
+    /// 全局快捷键
+    global_hotkeys: GlobalHotkeys,
+    /// 托盘菜单事件（未启用托盘或托盘服务已结束时为 None，此时关闭窗口直接退出）
+    tray: Option<Receiver<usize>>,
+    /// 显示首次关闭窗口的确认对话框
+    close_prompt: bool,
+    /// 等待确认的批量还原
+    restore_prompt: Option<MassRestore>,
+    /// 正在退出（不再拦截关闭请求）
+    quitting: bool,
+    /// 网页快照服务（未指定 --serve 或启动失败时为 None）
+    web_snapshot: Option<SnapshotSlot>,
+    /// 单实例 socket 和其他实例转发的命令
+    instance: Option<(InstanceGuard, Receiver<InstanceCommand>)>,
+    /// 待应用的命令（启动参数或其他实例转发），启动检测完成后应用
+    pending_command: Option<InstanceCommand>,
     /// 上次 CPU 更新时间
     last_cpu_update: Instant,
+    /// 上次降温自动化检查时间
+    last_thermal_update: Instant,
     /// 上次进程更新时间
     last_process_update: Instant,
+    /// 下一帧立即进行完整进程刷新（切换到进程相关标签页时）
+    force_process_refresh: bool,
+    /// 进程刷新次数（决定分级刷新中哪些周期完整刷新）
+    process_cycle: u64,
+    /// hexin 自身的 CPU 使用率
+    self_cpu_usage: f32,
+    /// 是否暂停数据刷新
+    paused: bool,
+    /// 设置页最近一次修改的时间，停止修改一段时间后写入配置文件（防抖）
+    config_save_pending: Option<Instant>,
+    /// 后台提示暂停到此时刻（None 表示未暂停）
+    alerts_paused_until: Option<Instant>,
+    /// 时间来源（刷新节奏和历史时间戳）
+    clock: Box<dyn Clock>,
     /// 启动时间（用于历史图表的时间戳）
     start_time: Instant,
+    /// 启动时的系统时间（历史图表切换为系统时间轴时使用）
+    start_wall_time: SystemTime,
 }
 
 impl HexinApp {
@@ -130,76 +425,936 @@ impl HexinApp {
     }
 
     /// 创建新应用
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    /// `serve` 为网页快照服务的监听地址（--serve），`clock` 为刷新节奏和历史时间戳的时间来源
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        serve: Option<&str>,
+        instance: Option<InstanceGuard>,
+        command: InstanceCommand,
+        clock: Box<dyn Clock>,
+    ) -> Self {
         // 配置中文字体
         Self::setup_fonts(&cc.egui_ctx);
 
         let config = AppConfig::load();
-        let mut sys = System::new_all();
-        sys.refresh_all();
-
-        let cpu_info = CpuInfo::detect();
-        let logical_cores = cpu_info.logical_cores;
-        let vcache_cores = cpu_info.vcache_cores();
+        // 拓扑检测和首次进程扫描在后台进行，窗口先显示占位界面
+        let startup = StartupLoader::spawn(&config);
 
-        let cpu_history = CpuHistory::new(logical_cores, config.history_length);
-        let mut process_manager = ProcessManager::new(logical_cores);
-
-        // 初始化时加载进程列表
-        process_manager.update(&sys);
+        let mut cpu_monitor_panel = CpuMonitorPanel::new(config.animation_smoothing);
+        cpu_monitor_panel.set_max_compared_cores(config.max_compared_cores);
+        cpu_monitor_panel.set_cell_display(config.cell_display);
+        cpu_monitor_panel.set_idle_threshold(config.idle_core_threshold);
+        cpu_monitor_panel.set_underclock_rule(config.underclock);
+        let mut process_list_panel = ProcessListPanel::new();
+        process_list_panel.set_delay_accounting(config.delay_accounting);
+        process_list_panel.set_density(config.table_density);
+        let onboarding = OnboardingOverlay::new(!config.onboarding_shown);
+        let watchlist_panel = WatchlistPanel::new(config.pinned_processes.clone());
+        // 托盘事件在窗口最小化时也要处理，到达时唤醒 UI
+        let egui_ctx = cc.egui_ctx.clone();
+        let tray = spawn_tray(move || egui_ctx.request_repaint())
+            .map_err(|e| tracing::info!("{}", e))
+            .ok();
+        let instance = instance.map(|mut guard| {
+            let egui_ctx = cc.egui_ctx.clone();
+            let commands = guard.listen(move || egui_ctx.request_repaint());
+            (guard, commands)
+        });
+        let web_snapshot = serve.and_then(|addr| serve_snapshots(addr).map_err(|e| tracing::warn!("{}", e)).ok());
 
         Self {
+            cpu_history: CpuHistory::new(0, config.history_length),
             config,
-            sys,
-            cpu_info,
-            cpu_history,
-            process_manager,
+            sys: System::new(),
+            cpu_info: CpuInfo::empty(),
+            cpu_pressure: read_cpu_pressure(),
+            run_delay: RunDelaySampler::default(),
+            process_manager: ProcessManager::new(0),
+            session: SessionJournal::load(),
+            rule_engine: Arc::new(Mutex::new(RuleEngine::new(Vec::new(), 0))),
+            dropin_watcher: DropInWatcher::default_dir().map(DropInWatcher::new),
+            startup: Some(startup),
+            session_message: None,
+            script_path: None,
             current_tab: Tab::CpuMonitor,
-            cpu_monitor_panel: CpuMonitorPanel::new(),
-            process_list_panel: ProcessListPanel::new(),
-            scheduler_panel: SchedulerPanel::new(&vcache_cores, logical_cores),
-            last_cpu_update: Instant::now(),
-            last_process_update: Instant::now(),
-            start_time: Instant::now(),
+            cpu_monitor_panel,
+            process_list_panel,
+            scheduler_panel: SchedulerPanel::new(&CoreTopology::default()),
+            settings_panel: SettingsPanel::new(),
+            watchlist_panel,
+            apply_guard: ApplyGuard { game_list: GameList::load(), ..Default::default() },
+            rule_guard_revision: None,
+            scheduler_revision: None,
+            process_list_revision: None,
+            settings_revision: None,
+            debug_panel: DebugPanel::new(),
+            onboarding,
+            limitations: Limitations::default(),
+            limitations_window: LimitationsWindow::default(),
+            thermal: ThermalController::new(),
+            focus_booster: FocusBooster::new(),
+            global_hotkeys: GlobalHotkeys::default(),
+            tray,
+            close_prompt: false,
+            restore_prompt: None,
+            quitting: false,
+            web_snapshot,
+            instance,
+            pending_command: Some(command),
+            last_cpu_update: clock.now(),
+            last_thermal_update: clock.now(),
+            last_process_update: clock.now(),
+            force_process_refresh: false,
+            process_cycle: 0,
+            self_cpu_usage: 0.0,
+            paused: false,
+            config_save_pending: None,
+            alerts_paused_until: None,
+            start_time: clock.now(),
+            clock,
+            start_wall_time: SystemTime::now(),
+        }
+    }
+
+    /// 换入后台检测的结果
+    fn finish_startup(&mut self, data: StartupData) {
+        self.sys = data.sys;
+        self.cpu_info = data.cpu_info;
+        self.cpu_history = CpuHistory::new(self.cpu_info.logical_cores, self.config.history_length);
+        self.process_manager = data.process_manager;
+        let mut categories = process_categories(&self.config.custom_categories);
+        if is_hybrid(&self.cpu_info.cores) {
+            self.process_manager.set_core_types(self.cpu_info.cores.iter().map(|c| c.core_type).collect());
+            categories.push(core_mismatch_category());
+        }
+        self.process_manager.set_categories(categories, &self.config.active_categories);
+        self.process_manager.set_tiered_refresh(self.config.full_process_refresh_cycles > 1);
+        self.rule_engine = data.rule_engine;
+        self.rule_guard_revision = None;
+        self.scheduler_revision = None;
+        self.settings_revision = None;
+        self.apply_topology();
+        self.reload_dropin_rules();
+        self.record_rule_applications();
+        self.watchlist_panel.sync(&mut self.process_manager, true);
+        self.recheck_limitations();
+    }
+
+    /// 重新检查权限与功能限制
+    fn recheck_limitations(&mut self) {
+        let probe = SystemProbe::read(self.cpu_info.vendor);
+        self.limitations.recheck(&probe, self.config.delay_accounting);
+    }
+
+    /// 把操作失败中的权限问题汇总到权限与限制清单
+    fn collect_limitations(&mut self) {
+        for (change, error) in self.session.take_failures() {
+            self.limitations.observe_failure(&change, &error);
+        }
+        for error in self.cpu_monitor_panel.take_sysfs_failures() {
+            self.limitations.observe_sysfs_failure(&error);
+        }
+    }
+
+    /// 把拓扑相关的设置（CCD 颜色、保留核心）推送到各面板
+    fn apply_topology(&mut self) {
+        let ccd_palette = CcdPalette::resolve(&self.config.ccd_colors, &self.cpu_info.l3_caches);
+        self.cpu_monitor_panel.set_ccd_palette(ccd_palette.clone());
+        self.process_list_panel.set_ccd_palette(ccd_palette.clone(), &self.cpu_info.l3_caches);
+        self.watchlist_panel.set_ccd_palette(ccd_palette, &self.cpu_info.l3_caches);
+        let topology = self.topology();
+        if self.scheduler_panel.set_topology(&topology) {
+            // profiles.d 的符号亲和性目标需要按新的保留核心重新解析
+            if let Some(watcher) = self.dropin_watcher.as_mut() {
+                watcher.invalidate();
+            }
+            self.reload_dropin_rules();
+        }
+        self.cpu_monitor_panel.set_reserved_cores(topology.reserved_cores.clone());
+        self.process_list_panel.set_reserved_cores(topology.reserved_cores.clone());
+        self.apply_guard.vcache_cores = topology.vcache_cores.clone();
+        self.apply_guard.reserved_cores = topology.reserved_cores.clone();
+        self.process_list_revision = None;
+        self.scheduler_revision = None;
+        self.debug_panel.set_reserved_cores(topology.reserved_cores);
+    }
+
+    /// 核心拓扑（含设置中的保留核心）
+    fn topology(&self) -> CoreTopology {
+        self.cpu_info.topology().with_reserved(&self.config.reserved_cores)
+    }
+
+    /// profiles.d 有变化时重新加载其中的规则，无法解析的文件只提示不中断
+    fn reload_dropin_rules(&mut self) {
+        let topology = self.topology();
+        let Some(watcher) = self.dropin_watcher.as_mut() else {
+            return;
+        };
+        let Some(load) = watcher.poll(&topology) else {
+            return;
+        };
+        for warning in &load.warnings {
+            tracing::warn!("profiles.d: {}", warning);
+        }
+        if !load.warnings.is_empty() {
+            self.session_message = Some(format!("profiles.d: {}", load.warnings.join("; ")));
+        }
+        if let Ok(mut engine) = self.rule_engine.lock() {
+            // 新增或修改的规则重新评估已在运行的进程
+            let changed = engine.set_dropin_rules(load.rules);
+            if !changed.is_empty() {
+                tracing::info!("profiles.d 规则更新: {}", changed.join(", "));
+                engine.apply_to_running(&changed, self.process_manager.processes().map(|p| (p.pid, p.name.as_str())));
+            }
+        }
+    }
+
+    /// 按当前本地时间切换定时规则，刚生效的规则应用到已在运行的匹配进程
+    fn update_rule_schedules(&mut self) {
+        let inactive = inactive_scheduled_rules(&self.config.rule_schedules, LocalTime::now());
+        let Ok(mut engine) = self.rule_engine.lock() else {
+            return;
+        };
+        let activated = engine.set_scheduled_inactive(inactive);
+        if !activated.is_empty() {
+            tracing::info!("定时规则生效: {}", activated.join(", "));
+            engine.apply_to_running(&activated, self.process_manager.processes().map(|p| (p.pid, p.name.as_str())));
         }
     }
 
+    /// 处理到期的延迟规则，并把自动规则的应用结果记入会话，失败时显示提示
+    fn record_rule_applications(&mut self) {
+        let (applications, expired) = match self.rule_engine.lock() {
+            Ok(mut engine) => {
+                engine.process_pending(self.clock.now());
+                let applications = engine.take_applications();
+                let expired = engine.expire_deferred(unix_timestamp());
+                // 一次性动作触发或过期后已从列表删除
+                if !expired.is_empty() || applications.iter().any(|a| a.deferred) {
+                    engine.save();
+                }
+                (applications, expired)
+            }
+            Err(_) => return,
+        };
+        let mut errors = Vec::new();
+        let mut notices: Vec<String> = expired
+            .iter()
+            .map(|action| format!("一次性动作 '{}' → {} 已过期", action.pattern, action.preset.name))
+            .collect();
+        for application in applications {
+            if application.deferred && application.report.is_success() {
+                notices.push(format!(
+                    "已对 {} ({}) 应用{}",
+                    application.process_name, application.pid, application.rule_name
+                ));
+            }
+            self.session.record_application(
+                application.pid,
+                &application.changes,
+                &application.report,
+                ApplySource::Rule(application.rule_name.clone()),
+            );
+            if !application.report.is_success() {
+                errors.push(format!(
+                    "规则 '{}' 应用到 {} ({}) 失败: {}",
+                    application.rule_name,
+                    application.process_name,
+                    application.pid,
+                    application.report.summary()
+                ));
+            }
+        }
+        notices.extend(errors);
+        if !notices.is_empty() {
+            let message = notices.join("; ");
+            tracing::info!("自动规则提示: {}", message);
+            if !self.alerts_silenced() {
+                self.session_message = Some(message);
+            }
+        }
+    }
+
+    /// 后台提示暂停的剩余时间，暂停已结束时清除
+    fn alert_pause_remaining(&mut self) -> Option<Duration> {
+        let remaining = self.alerts_paused_until?.saturating_duration_since(self.clock.now());
+        if remaining.is_zero() {
+            self.alerts_paused_until = None;
+            return None;
+        }
+        Some(remaining)
+    }
+
+    /// 是否处于免打扰时间段或暂停期间
+    fn alerts_silenced(&mut self) -> bool {
+        self.alert_pause_remaining().is_some() || in_quiet_hours(&self.config.quiet_hours, LocalTime::now())
+    }
+
+    /// 把调度面板中对一次性延后动作的修改交给规则引擎并保存
+    fn apply_deferred_edits(&mut self) {
+        let edits = self.scheduler_panel.take_deferred_edits();
+        if edits.is_empty() {
+            return;
+        }
+        let Ok(mut engine) = self.rule_engine.lock() else {
+            return;
+        };
+        for edit in edits {
+            match edit {
+                DeferredEdit::Add(action) => {
+                    engine.add_deferred(action);
+                }
+                DeferredEdit::Remove(id) => {
+                    engine.remove_deferred(id);
+                }
+            }
+        }
+        engine.save();
+    }
+
+    /// 规则有变化时更新手动应用检查用的规则快照
+    fn refresh_rule_guard(&mut self) {
+        let Ok(engine) = self.rule_engine.lock() else {
+            return;
+        };
+        if self.rule_guard_revision != Some(engine.revision()) {
+            self.rule_guard_revision = Some(engine.revision());
+            self.apply_guard.rules = engine.guard();
+        }
+    }
+
+    /// 用户在应用前的提醒中选择不再提示 V-Cache 时关闭提示并保存游戏列表
+    fn apply_vcache_opt_out(&mut self, opt_out: bool) {
+        if !opt_out {
+            return;
+        }
+        self.apply_guard.game_list.warn_vcache = false;
+        self.apply_guard.game_list.save();
+        self.process_list_revision = None;
+        self.scheduler_revision = None;
+    }
+
+    /// 执行手动应用冲突的处理（覆盖一次或更新规则）
+    fn resolve_rule_conflicts(&mut self, resolutions: Vec<RuleResolution>) {
+        if resolutions.is_empty() {
+            return;
+        }
+        let Ok(mut engine) = self.rule_engine.lock() else {
+            return;
+        };
+        let mut updated = false;
+        for resolution in resolutions {
+            updated |= matches!(resolution, RuleResolution::UpdateRule { .. });
+            if let Err(e) = engine.resolve(resolution) {
+                self.session_message = Some(e);
+            }
+        }
+        if updated {
+            engine.save();
+        }
+    }
+
+    /// 加入从进程列表创建的亲和性模板规则并保存
+    fn add_affinity_rules(&mut self, rules: Vec<AutoRule>) {
+        if rules.is_empty() {
+            return;
+        }
+        let Ok(mut engine) = self.rule_engine.lock() else {
+            return;
+        };
+        let messages: Vec<String> = rules.into_iter().map(|rule| engine.add_affinity_rule(rule)).collect();
+        engine.save();
+        self.session_message = Some(messages.join("; "));
+    }
+
+    /// 启用或导出调优档案
+    fn handle_profile_action(&mut self, action: ProfileAction) {
+        let result = match action {
+            ProfileAction::Activate { profile, warnings } => {
+                let name = profile.metadata.name.clone();
+                self.scheduler_panel.set_custom_presets(profile.presets);
+                if let Ok(mut engine) = self.rule_engine.lock() {
+                    engine.set_rules(profile.rules);
+                    engine.save();
+                }
+                self.config.active_profile = Some(name.clone());
+                self.config.save();
+                let mut msg = format!("已启用档案 '{}'", name);
+                if !warnings.is_empty() {
+                    msg.push_str(&format!("（注意: {}）", warnings.join("；")));
+                }
+                Ok(msg)
+            }
+            ProfileAction::Export { metadata, path } => {
+                let rules = self
+                    .rule_engine
+                    .lock()
+                    .map(|engine| engine.rules().to_vec())
+                    .unwrap_or_default();
+                let profile = Profile::capture(metadata, self.scheduler_panel.custom_presets(), rules);
+                let exported = if path.as_os_str().is_empty() {
+                    Ok(())
+                } else {
+                    profile.export(&path)
+                };
+                exported.and_then(|()| profile.save_to_gallery()).map(|saved| {
+                    let target = if path.as_os_str().is_empty() { saved } else { path };
+                    format!("已导出档案 '{}' 到 {}（已加入档案库）", profile.metadata.name, target.display())
+                })
+            }
+        };
+        self.settings_panel.set_profile_result(result);
+    }
+
+    /// 绘制刷新状态：暂停按钮，以及数据未实时更新时的过期提示
+    fn draw_refresh_status(&mut self, ui: &mut egui::Ui) {
+        let pause_label = if self.paused { "▶ 继续" } else { "⏸ 暂停" };
+        if ui.small_button(pause_label).on_hover_text("暂停 / 继续监控数据刷新；自动规则、前台加速和降温自动化照常运行").clicked() {
+            self.paused = !self.paused;
+        }
+
+        // 超过两个刷新周期未更新即视为非实时数据
+        let age = self.clock.now().saturating_duration_since(self.last_cpu_update);
+        let expected = Duration::from_millis(self.config.refresh_interval_ms) * 2;
+        if !self.paused && age < expected {
+            return;
+        }
+
+        let text = if self.paused {
+            format!("数据已暂停 · {} 秒前", age.as_secs())
+        } else {
+            format!("数据未更新 · {} 秒前", age.as_secs())
+        };
+        let (fill, color) = if age >= STALE_DATA_THRESHOLD {
+            (Color32::from_rgb(110, 30, 30), Color32::from_rgb(255, 180, 180))
+        } else {
+            (Color32::from_rgb(90, 75, 30), Color32::from_rgb(255, 220, 140))
+        };
+        ui.add_space(8.0);
+        Frame::none()
+            .fill(fill)
+            .rounding(Rounding::same(4.0))
+            .inner_margin(Margin::symmetric(8.0, 2.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new(text).size(12.0).color(color));
+            })
+            .response
+            .on_hover_text("当前显示的不是实时数据");
+        ui.add_space(12.0);
+    }
+
+    /// 立即刷新刚被修改或还原的进程，不等待下一次完整刷新
+    fn refresh_touched_processes(&mut self) {
+        for pid in self.session.take_touched() {
+            self.process_manager.refresh_pid(pid);
+        }
+    }
+
+    /// 刷新监视列表中的进程（只读取这几个 PID），已退出的进程移出列表
+    fn refresh_watched_processes(&mut self) {
+        if self.process_manager.watched().is_empty() {
+            return;
+        }
+        let pids: Vec<sysinfo::Pid> = self.process_manager.watched().iter().map(|&pid| sysinfo::Pid::from_u32(pid)).collect();
+        self.sys.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+        let exited = self.process_manager.refresh_watched(&self.sys);
+        self.watchlist_panel.add_exited(exited);
+    }
+
     /// 更新系统数据
     fn update_data(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
+
+        // 焦点变化时切换加速的进程；全局快捷键应用预设时也需要知道焦点窗口
+        let hotkeys_need_focus = self
+            .config
+            .hotkeys
+            .iter()
+            .any(|b| b.global && matches!(b.action, HotkeyAction::ApplyPreset(_)));
+        self.focus_booster.poll(
+            &self.config.focus_boost,
+            hotkeys_need_focus,
+            self.scheduler_panel.presets(),
+            &mut self.session,
+            self.cpu_info.logical_cores,
+        );
 
-        // CPU 更新 (每 500ms)
-        let cpu_elapsed = now.duration_since(self.last_cpu_update);
-        if cpu_elapsed >= Duration::from_millis(self.config.refresh_interval_ms) {
+        // CPU 更新 (每 500ms)，暂停时停止
+        let refresh_interval = Duration::from_millis(self.config.refresh_interval_ms);
+        if !self.paused && now.duration_since(self.last_cpu_update) >= refresh_interval {
             self.last_cpu_update = now;
 
             // 刷新 CPU 信息
             self.sys.refresh_cpu_all();
-            self.cpu_info.update(&self.sys);
+            self.cpu_info.update(&self.sys, self.config.usage_source);
+            if self.config.cell_display.needs_temperature() {
+                self.cpu_info.update_temperatures();
+            } else {
+                self.cpu_info.mark_temperatures_stale();
+            }
 
             // 记录历史数据
-            let core_usages: Vec<f32> = self.cpu_info.cores.iter().map(|c| c.usage_percent).collect();
-            let timestamp = now.duration_since(self.start_time).as_secs_f64();
-            self.cpu_history.push(&core_usages, self.cpu_info.total_usage_percent, timestamp);
+            let core_samples: Vec<(f32, u64)> =
+                self.cpu_info.cores.iter().map(|c| (c.usage_percent, c.frequency_mhz)).collect();
+            let (sample_time, history_time) = self.cpu_history.record(
+                self.clock.as_ref(),
+                self.start_time,
+                &core_samples,
+                self.cpu_info.total_usage_percent,
+            );
+            let timestamp = sample_time.monotonic;
+
+            // 选中进程所在核心（只轮询这一个 PID）
+            self.process_manager.sample_tracked_cpu();
+            self.refresh_watched_processes();
+
+            // 记录 CPU 压力
+            self.cpu_pressure = read_cpu_pressure();
+            if let Some(psi) = self.cpu_pressure {
+                self.cpu_history.push_pressure(psi.some.avg10, history_time);
+            }
+            if let Some(breakdown) = self.cpu_info.total_breakdown {
+                self.cpu_history.push_breakdown(breakdown.system, breakdown.iowait, history_time);
+            }
+            let run_delay_ms = self.run_delay.sample(timestamp);
+            let (score, factors) =
+                scheduling_health(&self.cpu_info, &self.process_manager, self.cpu_pressure, run_delay_ms);
+            self.cpu_monitor_panel.set_scheduling_health(score, factors);
+
+            if let Some(slot) = &self.web_snapshot {
+                publish_snapshot(slot, &self.cpu_info, &self.process_manager);
+            }
         }
 
-        // 进程更新 (每 1000ms)
+        // 高温降温自动化（暂停时照常运行）
+        if now.duration_since(self.last_thermal_update) >= refresh_interval {
+            self.last_thermal_update = now;
+            let processes: Vec<_> = self.process_manager.processes().collect();
+            self.thermal.tick(
+                &self.config.thermal,
+                &processes,
+                &mut self.session,
+                self.cpu_info.logical_cores,
+                self.clock.seconds_since(self.start_time),
+            );
+        }
+
+        // 进程更新：进程相关标签页每秒刷新，其他标签页和暂停时仅低频保活（新进程仍会触发自动规则）
+        // 分级刷新时每隔若干周期才完整刷新（发现新进程），其余周期只刷新活跃进程
+        let detailed = !self.paused && self.current_tab.needs_process_details();
+        let interval = if detailed { PROCESS_REFRESH_INTERVAL } else { PROCESS_KEEPALIVE_INTERVAL };
         let process_elapsed = now.duration_since(self.last_process_update);
-        if process_elapsed >= Duration::from_millis(1000) {
+        if self.force_process_refresh || process_elapsed >= interval {
+            let cycles = u64::from(self.config.full_process_refresh_cycles.max(1));
+            let full = self.force_process_refresh || !detailed || self.process_cycle.is_multiple_of(cycles);
+            self.force_process_refresh = false;
             self.last_process_update = now;
-            self.sys.refresh_processes(ProcessesToUpdate::All, true);
-            self.process_manager.update(&self.sys);
+            self.process_cycle += 1;
+            let started = Instant::now();
+            let mut open = self.process_list_panel.detail_window_pids();
+            open.extend(self.scheduler_panel.selected_pid());
+            self.process_manager.set_open_pids(open);
+            self.process_manager.set_track_faults(self.process_list_panel.shows_faults());
+            if full {
+                self.sys.refresh_processes(ProcessesToUpdate::All, true);
+                self.reload_dropin_rules();
+                self.process_manager.update(&self.sys, detailed);
+            } else {
+                let hot = self.process_manager.hot_pids();
+                let pids: Vec<sysinfo::Pid> = hot.iter().map(|&pid| sysinfo::Pid::from_u32(pid)).collect();
+                self.sys.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+                self.process_manager.update_hot(&self.sys, &hot, detailed);
+            }
+            tracing::debug!("进程{}刷新耗时 {:?}", if full { "完整" } else { "分级" }, started.elapsed());
+            for (pid, old_name) in self.process_manager.take_execs() {
+                if let Some(process) = self.process_manager.process(pid) {
+                    self.session.process_exec(pid, &old_name, &process.name);
+                }
+            }
+            // 只有完整刷新后的调度信息才能用来判断外部修改
+            if detailed {
+                let process_manager = &self.process_manager;
+                self.session.forget_modified(|pid| process_manager.process(pid), self.cpu_info.logical_cores);
+                self.session.save_applied_by();
+            }
+            self.update_rule_schedules();
+            self.record_rule_applications();
+            self.watchlist_panel.sync(&mut self.process_manager, true);
+            // 分级刷新时 ProcessManager 中的使用率按各进程自身的刷新间隔计算
+            self.self_cpu_usage = self.process_manager.process(std::process::id()).map_or(0.0, |p| p.cpu_usage);
+        }
+        self.refresh_touched_processes();
+        for diff in self.session.take_affinity_diffs() {
+            self.cpu_monitor_panel.flash_affinity_change(&diff);
+        }
+    }
+
+    /// 录制脚本的保存路径输入框
+    fn draw_script_prompt(&mut self, ui: &mut egui::Ui) {
+        let Some(path) = self.script_path.as_mut() else {
+            return;
+        };
+
+        let mut save = false;
+        let mut discard = false;
+        let mut cancel = false;
+        Frame::none()
+            .fill(Color32::from_gray(40))
+            .inner_margin(Margin::same(8.0))
+            .rounding(Rounding::same(4.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("保存 {} 步录制到:", self.session.recorder().steps().len()));
+                    let response = ui.add(egui::TextEdit::singleline(path).desired_width(360.0));
+                    save = ui.button("保存").clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                    discard = ui.button("清空录制").on_hover_text("丢弃已录制的步骤").clicked();
+                    cancel = ui.button("取消").clicked();
+                });
+            });
+        ui.add_space(8.0);
+
+        if save {
+            let path = PathBuf::from(path.trim());
+            let recorder = self.session.recorder();
+            self.session_message = Some(match recorder.save(&path) {
+                Ok(()) => format!("已保存 {} 步到 {}，运行 sh {} 重现这些调整", recorder.steps().len(), path.display(), path.display()),
+                Err(e) => e,
+            });
+            self.script_path = None;
+        } else if discard {
+            self.session.recorder_mut().clear();
+            self.script_path = None;
+        } else if cancel {
+            self.script_path = None;
+        }
+    }
+
+    /// 处理托盘事件和窗口关闭请求：有托盘时关闭窗口改为最小化（首次关闭时先确认）
+    fn handle_window_lifecycle(&mut self, ctx: &Context) {
+        while let Some(event) = self.tray.as_ref().and_then(poll_tray) {
+            match event {
+                TrayEvent::Show => show_window(ctx),
+                TrayEvent::Quit => {
+                    self.quitting = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                TrayEvent::Closed => {
+                    // 托盘消失后最小化的窗口只能从任务栏恢复，重新显示并改为直接退出
+                    self.tray = None;
+                    self.close_prompt = false;
+                    show_window(ctx);
+                }
+            }
+        }
+
+        // 再次启动 hexin 时：显示已有窗口，标签页和过滤在启动检测完成后应用
+        while let Some(command) = self.instance.as_ref().and_then(|(_, commands)| commands.try_recv().ok()) {
+            show_window(ctx);
+            self.pending_command = Some(command);
+        }
+
+        if ctx.input(|i| i.viewport().close_requested()) {
+            let config = &self.config;
+            match close_action(self.quitting, self.tray.is_some(), config.close_to_tray_confirmed, config.close_to_tray) {
+                CloseAction::Close => {}
+                CloseAction::Prompt => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    self.close_prompt = true;
+                }
+                CloseAction::Minimize => {
+                    // 不隐藏窗口：隐藏后界面停止刷新，定期任务也随之停止
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+                }
+            }
+        }
+
+        if self.close_prompt {
+            self.draw_close_prompt(ctx);
+        }
+        if self.restore_prompt.is_some() {
+            self.draw_restore_prompt(ctx);
+        }
+    }
+
+    /// 应用启动参数或其他实例转发的 --tab / --filter
+    fn apply_instance_command(&mut self, command: InstanceCommand) {
+        if let Some(tab) = command.tab.as_deref().and_then(Tab::from_name) {
+            self.switch_tab(tab);
+        }
+        if let Some(filter) = command.filter {
+            self.scheduler_panel.set_process_search(filter.clone());
+            self.process_manager.set_filter(filter);
+        }
+    }
+
+    /// 切换标签页（调试页未启用时忽略）
+    fn switch_tab(&mut self, tab: Tab) {
+        if (tab != Tab::Debug || self.config.debug_tools) && self.current_tab != tab {
+            self.current_tab = tab;
+            self.force_process_refresh = tab.needs_process_details();
+        }
+    }
+
+    /// 当前标签页中选中的进程（没有时取另一个进程相关标签页的选择）
+    fn selected_pid(&self) -> Option<u32> {
+        let (list, scheduler) = (self.process_list_panel.selected_pid(), self.scheduler_panel.selected_pid());
+        match self.current_tab {
+            Tab::Scheduler => scheduler.or(list),
+            _ => list.or(scheduler),
+        }
+    }
+
+    /// 处理应用内快捷键（输入框获得焦点时只响应带 Ctrl / Alt 的组合）和全局快捷键
+    fn handle_hotkeys(&mut self, ctx: &Context) {
+        let mut actions = Vec::new();
+        if !self.settings_panel.is_capturing_hotkey() {
+            let typing = ctx.wants_keyboard_input();
+            let chords: Vec<KeyChord> = ctx.input(|i| {
+                i.events
+                    .iter()
+                    .filter_map(|event| match event {
+                        egui::Event::Key { key, pressed: true, repeat: false, modifiers, .. } => Some(KeyChord {
+                            ctrl: modifiers.ctrl,
+                            alt: modifiers.alt,
+                            shift: modifiers.shift,
+                            key: key.name().to_string(),
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            });
+            for chord in chords.iter().filter(|chord| !typing || chord.has_command_modifier()) {
+                actions.extend(dispatch_hotkey(&self.config.hotkeys, chord).map(|action| (action.clone(), false)));
+            }
+        }
+
+        let egui_ctx = ctx.clone();
+        for chord in self.global_hotkeys.poll(&self.config.hotkeys, move || egui_ctx.request_repaint()) {
+            actions.extend(dispatch_hotkey(&self.config.hotkeys, &chord).map(|action| (action.clone(), true)));
+        }
+
+        for (action, global) in actions {
+            self.run_hotkey(action, global);
+        }
+    }
+
+    /// 执行快捷键操作；全局快捷键应用预设时优先作用于焦点窗口所属进程
+    fn run_hotkey(&mut self, action: HotkeyAction, global: bool) {
+        match action {
+            HotkeyAction::ApplyPreset(name) => {
+                // 全局快捷键只作用于焦点窗口所属进程，应用内快捷键作用于选中的进程
+                let target = if global {
+                    self.focus_booster.focused_pid().ok_or_else(|| {
+                        self.focus_booster.tracker_error().unwrap_or("没有焦点窗口（或焦点在 hexin 上）").to_string()
+                    })
+                } else {
+                    self.selected_pid().ok_or_else(|| "没有选中的进程".to_string())
+                };
+                let pid = match target {
+                    Ok(pid) => pid,
+                    Err(reason) => {
+                        self.session_message = Some(format!("快捷键: {}，无法应用预设 '{}'", reason, name));
+                        return;
+                    }
+                };
+                let Some(preset) = self.scheduler_panel.presets().iter().find(|p| p.name == name).cloned() else {
+                    self.session_message = Some(format!("快捷键: 找不到预设 '{}'", name));
+                    return;
+                };
+                self.session_message = Some(format!("快捷键: {}", self.apply_hotkey_preset(pid, preset)));
+            }
+            HotkeyAction::ApplyRule(name) => {
+                let result = match self.rule_engine.lock() {
+                    Ok(mut engine) if engine.rule_names().contains(&name) => {
+                        let rules = [name.clone()];
+                        Some(engine.apply_to_running(&rules, self.process_manager.processes().map(|p| (p.pid, p.name.as_str()))))
+                    }
+                    _ => None,
+                };
+                self.record_rule_applications();
+                // 失败的详情已记录到会话日志
+                self.session_message = Some(match result {
+                    None => format!("快捷键: 找不到规则 '{}'", name),
+                    Some((0, 0)) => format!("快捷键: 规则 '{}' 未启用或没有可应用的运行中进程", name),
+                    Some((applied, 0)) => format!("快捷键: 规则 '{}' 已应用到 {} 个进程", name, applied),
+                    Some((applied, failed)) => {
+                        format!("快捷键: 规则 '{}' 已应用到 {} 个进程，{} 个失败", name, applied, failed)
+                    }
+                });
+            }
+            HotkeyAction::SwitchTab(name) => {
+                if let Some(tab) = Tab::from_name(&name) {
+                    self.switch_tab(tab);
+                }
+            }
+            HotkeyAction::TogglePause => self.paused = !self.paused,
+            HotkeyAction::FocusSearch => {
+                if self.current_tab == Tab::Scheduler {
+                    self.scheduler_panel.focus_search();
+                } else {
+                    self.switch_tab(Tab::ProcessList);
+                    self.process_list_panel.focus_search();
+                }
+            }
+        }
+    }
+
+    /// 快捷键应用预设：与进程列表相同的检查，需要确认或有冲突时不应用，返回结果说明
+    fn apply_hotkey_preset(&mut self, pid: u32, preset: SchedulePreset) -> String {
+        self.refresh_rule_guard();
+        let Some(process) = self.process_manager.process(pid) else {
+            return format!("进程 {} 已退出", pid);
+        };
+        let changes = preset_changes(&preset);
+        match self.apply_guard.warnings(process, &changes) {
+            Err(e) => return e,
+            Ok(warnings) if !warnings.is_empty() => {
+                return format!("未应用预设 '{}'：{}，请在窗口中确认后应用", preset.name, warnings.join("；"));
+            }
+            Ok(_) => {}
+        }
+        if let Some(conflict) = self.apply_guard.rules.conflict(pid, &process.name, &changes) {
+            return format!("未应用预设 '{}'：与规则 '{}' 冲突，请在窗口中应用", preset.name, conflict.rule_name);
+        }
+        let apply = ManualApply { pid, changes, preset: Some(preset) };
+        match apply.run(&mut self.session, self.cpu_info.logical_cores) {
+            Ok(message) | Err(message) => message,
+        }
+    }
+
+    /// 批量还原前的确认对话框
+    fn draw_restore_prompt(&mut self, ctx: &Context) {
+        let Some(kind) = self.restore_prompt else {
+            return;
+        };
+        let (title, description) = match kind {
+            MassRestore::Revert => ("还原全部更改", "恢复为 hexin 修改前的调度设置"),
+            MassRestore::Defaults => ("恢复全部默认", "恢复为 SCHED_OTHER、nice 0 和全部核心（记录了修改前的值时恢复为该值）"),
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                ui.label(format!("本次会话修改过的 {} 个进程将{}。", self.session.entries().len(), description));
+                ui.label(RichText::new("已退出的进程和受保护的进程（hexin 自身、init、内核线程）会被跳过")
+                    .size(12.0).color(Color32::from_gray(150)));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    confirmed = ui.button(title).clicked();
+                    cancelled = ui.button("取消").clicked();
+                });
+            });
+
+        if cancelled {
+            self.restore_prompt = None;
+        } else if confirmed {
+            self.restore_prompt = None;
+            let summary = match kind {
+                MassRestore::Revert => self.session.revert_all_session_changes(),
+                MassRestore::Defaults => self.session.restore_all_defaults(self.cpu_info.logical_cores),
+            };
+            self.session_message = Some(summary.summary());
+        }
+    }
+
+    /// 首次关闭窗口时的确认对话框，选择会保存到配置
+    fn draw_close_prompt(&mut self, ctx: &Context) {
+        let mut choice = None;
+        egui::Window::new("关闭窗口")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                ui.label("关闭窗口时改为最小化，hexin 不退出。自动规则、前台加速和监视列表随界面刷新运行，部分桌面会在窗口最小化时降低刷新频率。");
+                ui.label("点击托盘图标恢复窗口，在托盘菜单中选择\"退出\"完全退出 hexin。");
+                ui.add_space(4.0);
+                ui.label(RichText::new("此选择会被记住，之后可以在设置中更改").size(12.0).color(Color32::from_gray(150)));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("最小化").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("退出").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("取消").clicked() {
+                        self.close_prompt = false;
+                    }
+                });
+            });
+
+        let Some(to_tray) = choice else {
+            return;
+        };
+        self.close_prompt = false;
+        self.config.close_to_tray = to_tray;
+        self.config.close_to_tray_confirmed = true;
+        self.config.save();
+        if to_tray {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        } else {
+            self.quitting = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
         }
     }
 }
 
+/// 从托盘恢复窗口
+fn show_window(ctx: &Context) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+}
+
 impl eframe::App for HexinApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        // 更新数据
+        self.handle_window_lifecycle(ctx);
+
+        // 启动检测完成前只显示占位界面，面板（包括应用按钮）都不绘制
+        if let Some(startup) = self.startup.as_mut() {
+            startup.mark_frame();
+            match startup.poll(&self.config) {
+                Some(data) => {
+                    self.startup = None;
+                    self.finish_startup(data);
+                }
+                None => {
+                    CentralPanel::default().show(ctx, |ui| {
+                        ui.add_space(ui.available_height() / 2.0 - 24.0);
+                        ui.vertical_centered(|ui| {
+                            ui.spinner();
+                            ui.label(RichText::new("正在检测 CPU 拓扑…").size(16.0).color(Color32::from_gray(180)));
+                        });
+                    });
+                    ctx.request_repaint_after(Duration::from_millis(50));
+                    return;
+                }
+            }
+        }
+
+        if let Some(command) = self.pending_command.take() {
+            self.apply_instance_command(command);
+        }
+        self.handle_hotkeys(ctx);
+
+        // 更新数据（C-state 分布只在 CPU 监控页可见时按刷新间隔采样）
+        let minimized = ctx.input(|i| i.viewport().minimized == Some(true));
+        self.cpu_info.set_cstate_visible(self.current_tab == Tab::CpuMonitor && !minimized);
         self.update_data();
 
-        // 请求持续重绘
-        ctx.request_repaint_after(Duration::from_millis(self.config.refresh_interval_ms));
+        // 请求持续重绘（暂停时仍每秒重绘以更新数据年龄）
+        let repaint_interval = if self.paused {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_millis(self.config.refresh_interval_ms)
+        };
+        ctx.request_repaint_after(repaint_interval);
 
         // 顶部标签栏
         TopBottomPanel::top("tabs")
@@ -213,13 +1368,11 @@ impl eframe::App for HexinApp {
                     ui.add_space(24.0);
 
                     // 标签按钮
-                    let tabs = [
-                        (Tab::CpuMonitor, "CPU 监控"),
-                        (Tab::ProcessList, "进程管理"),
-                        (Tab::Scheduler, "调度策略"),
-                    ];
-
-                    for (tab, label) in tabs {
+                    for tab in Tab::ALL {
+                        let label = tab.label();
+                        if tab == Tab::Debug && !self.config.debug_tools {
+                            continue;
+                        }
                         let is_selected = self.current_tab == tab;
                         let text_color = if is_selected {
                             Color32::WHITE
@@ -234,8 +1387,9 @@ impl eframe::App for HexinApp {
                             .show(ui, |ui| {
                                 if ui.add(egui::Label::new(
                                     RichText::new(label).color(text_color).size(13.0)
-                                ).sense(egui::Sense::click())).clicked() {
+                                ).sense(egui::Sense::click())).clicked() && self.current_tab != tab {
                                     self.current_tab = tab;
+                                    self.force_process_refresh = tab.needs_process_details();
                                 }
                             });
 
@@ -244,6 +1398,20 @@ impl eframe::App for HexinApp {
 
                     // 右侧状态信息
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("?").on_hover_text("使用说明").clicked() {
+                            self.onboarding.open();
+                        }
+                        let limitation_count = self.limitations.entries().len();
+                        if limitation_count > 0
+                            && ui.small_button(RichText::new(format!("⚠ {}", limitation_count)).color(Color32::from_rgb(255, 200, 100)))
+                                .on_hover_text("权限与限制：被阻止的操作和解决办法")
+                                .clicked()
+                        {
+                            self.limitations_window.toggle();
+                        }
+                        ui.add_space(8.0);
+                        self.draw_refresh_status(ui);
+
                         let usage_color = if self.cpu_info.total_usage_percent > 80.0 {
                             Color32::from_rgb(255, 100, 100)
                         } else if self.cpu_info.total_usage_percent > 50.0 {
@@ -252,42 +1420,278 @@ impl eframe::App for HexinApp {
                             Color32::from_rgb(100, 200, 100)
                         };
 
+                        // 一键还原本次会话的所有更改
+                        if !self.session.is_empty() {
+                            let count = self.session.entries().len();
+                            if ui.button(RichText::new(format!("还原全部更改 ({})", count))
+                                .size(12.0).color(Color32::from_rgb(255, 150, 100)))
+                                .on_hover_text("将 hexin 本次修改过的所有进程恢复为修改前的调度设置")
+                                .clicked()
+                            {
+                                self.restore_prompt = Some(MassRestore::Revert);
+                            }
+                            if ui.button(RichText::new("恢复全部默认").size(12.0).color(Color32::from_rgb(255, 150, 100)))
+                                .on_hover_text("将 hexin 本次修改过的所有进程恢复为 SCHED_OTHER、nice 0 和全部核心\n（hexin 记录了修改前的值时恢复为该值）")
+                                .clicked()
+                            {
+                                self.restore_prompt = Some(MassRestore::Defaults);
+                            }
+                            ui.add_space(12.0);
+                        }
+
+                        // 录制手动调整为可重放的脚本
+                        let steps = self.session.recorder().steps().len();
+                        if steps > 0 && ui.button(RichText::new(format!("保存脚本 ({})", steps)).size(12.0))
+                            .on_hover_text("保存为 shell 脚本，之后或在另一台机器上运行以重现这些调整")
+                            .clicked()
+                        {
+                            self.script_path = Some(default_script_path(unix_timestamp()).to_string_lossy().into_owned());
+                        }
+                        let recording = self.session.recorder().is_recording();
+                        let (label, color) = if recording {
+                            ("⏺ 录制中", Color32::from_rgb(255, 100, 100))
+                        } else {
+                            ("⏺ 录制", Color32::from_gray(180))
+                        };
+                        if ui.button(RichText::new(label).size(12.0).color(color))
+                            .on_hover_text("录制手动修改和手动应用的预设（按进程名匹配，生成 chrt / renice / ionice / taskset 命令）\n自动规则、前台加速和高温降温不录制")
+                            .clicked()
+                        {
+                            self.session.recorder_mut().set_recording(!recording);
+                        }
+                        ui.add_space(12.0);
+
                         ui.label(RichText::new(format!("核心: {}", self.cpu_info.logical_cores))
                             .size(12.0).color(Color32::from_gray(140)));
                         ui.add_space(12.0);
                         ui.label(RichText::new(format!("CPU: {:.1}%", self.cpu_info.total_usage_percent))
-                            .size(12.0).color(usage_color));
+                            .size(12.0).color(usage_color))
+                            .on_hover_text(format!(
+                                "hexin 自身: {:.1}%\n最近 60 秒平均: {:.1}%",
+                                self.self_cpu_usage,
+                                self.cpu_history.average_over(60.0).unwrap_or(0.0)
+                            ));
+
+                        // CPU 压力 (PSI)，不支持时隐藏
+                        if let Some(psi) = self.cpu_pressure {
+                            let pressure_color = match psi.severity() {
+                                0 => Color32::from_rgb(100, 200, 100),
+                                1 => Color32::from_rgb(255, 200, 100),
+                                _ => Color32::from_rgb(255, 100, 100),
+                            };
+                            let mut tooltip = format!(
+                                "CPU 压力 (PSI some)\n10 秒: {:.2}%\n60 秒: {:.2}%\n300 秒: {:.2}%",
+                                psi.some.avg10, psi.some.avg60, psi.some.avg300
+                            );
+                            if let Some(full) = psi.full {
+                                tooltip.push_str(&format!("\nfull 10 秒: {:.2}%", full.avg10));
+                            }
+                            ui.add_space(12.0);
+                            ui.label(RichText::new(format!("压力: {:.1}%", psi.some.avg10))
+                                .size(12.0).color(pressure_color))
+                                .on_hover_text(tooltip);
+                        }
+
+                        // 安全指示：未启用 seccomp 的用户态进程数
+                        let unsecured = self.process_manager.unsecured_processes().len();
+                        ui.add_space(12.0);
+                        ui.label(RichText::new(format!("无 seccomp: {}", unsecured))
+                            .size(12.0).color(Color32::from_gray(140)))
+                            .on_hover_text("未启用 seccomp 沙箱的用户态进程数（不含内核线程）");
                     });
                 });
             });
 
+        // 底部监视列表（所有标签页可见）
+        self.watchlist_panel.sync(&mut self.process_manager, false);
+        if let Some(pins) = self.watchlist_panel.take_pins_changed() {
+            self.config.pinned_processes = pins;
+            self.config.save();
+        }
+        if self.watchlist_panel.is_visible(&self.process_manager) {
+            self.refresh_rule_guard();
+            TopBottomPanel::bottom("watchlist")
+                .frame(Frame::none()
+                    .fill(Color32::from_gray(30))
+                    .inner_margin(Margin::symmetric(16.0, 6.0)))
+                .show(ctx, |ui| {
+                    self.watchlist_panel.ui(
+                        ui,
+                        &mut self.process_manager,
+                        &mut self.session,
+                        &self.apply_guard,
+                        self.scheduler_panel.presets(),
+                        self.cpu_info.logical_cores,
+                    );
+                });
+            let resolutions = self.watchlist_panel.take_rule_resolutions();
+            self.resolve_rule_conflicts(resolutions);
+            let opt_out = self.watchlist_panel.take_vcache_opt_out();
+            self.apply_vcache_opt_out(opt_out);
+        }
+
         // 主内容区域
         CentralPanel::default().show(ctx, |ui| {
+            let mut clear_message = false;
+            if let Some(ref msg) = self.session_message {
+                Frame::none()
+                    .fill(Color32::from_gray(40))
+                    .inner_margin(Margin::same(8.0))
+                    .rounding(Rounding::same(4.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(msg.as_str()).color(Color32::from_gray(220)));
+                            if ui.small_button("✕").clicked() {
+                                clear_message = true;
+                            }
+                        });
+                    });
+            }
+            if clear_message {
+                self.session_message = None;
+            }
+            self.draw_script_prompt(ui);
+
+            let viewport_height = ui.available_height();
             egui::ScrollArea::vertical().show(ui, |ui| {
                 match self.current_tab {
                     Tab::CpuMonitor => {
-                        self.cpu_monitor_panel.ui(ui, &self.cpu_info, &self.cpu_history);
+                        self.cpu_monitor_panel.ui(
+                            ui,
+                            &self.cpu_info,
+                            &mut self.cpu_history,
+                            self.start_wall_time,
+                            self.process_manager.tracked_history(),
+                        );
+                        if let Some(preset) = self.cpu_monitor_panel.take_created_preset() {
+                            let name = preset.name.clone();
+                            let result = self.scheduler_panel.add_preset(preset);
+                            if result.is_ok() {
+                                self.session_message = Some(format!("已创建预设 '{}'，可在调度设置中应用", name));
+                            }
+                            self.cpu_monitor_panel.set_preset_result(result);
+                        }
                     }
                     Tab::ProcessList => {
+                        self.refresh_rule_guard();
+                        if self.process_list_revision != self.rule_guard_revision {
+                            self.process_list_revision = self.rule_guard_revision;
+                            self.process_list_panel.set_apply_guard(self.apply_guard.clone());
+                        }
                         self.process_list_panel.ui(
                             ui,
                             &mut self.process_manager,
-                            self.cpu_info.logical_cores,
+                            &mut self.session,
+                            self.scheduler_panel.presets(),
+                            &self.cpu_info,
+                            viewport_height,
                         );
+                        let resolutions = self.process_list_panel.take_rule_resolutions();
+                        self.resolve_rule_conflicts(resolutions);
+                        let opt_out = self.process_list_panel.take_vcache_opt_out();
+                        self.apply_vcache_opt_out(opt_out);
+                        let affinity_rules = self.process_list_panel.take_affinity_rules();
+                        self.add_affinity_rules(affinity_rules);
+                        if self.process_manager.active_categories() != self.config.active_categories.as_slice() {
+                            self.config.active_categories = self.process_manager.active_categories().to_vec();
+                            self.config.save();
+                        }
                     }
                     Tab::Scheduler => {
+                        self.refresh_rule_guard();
+                        if self.scheduler_revision != self.rule_guard_revision {
+                            self.scheduler_revision = self.rule_guard_revision;
+                            self.scheduler_panel.set_apply_guard(self.apply_guard.clone());
+                            if let Ok(engine) = self.rule_engine.lock() {
+                                self.scheduler_panel.set_deferred_actions(engine.deferred_actions().to_vec());
+                            }
+                        }
                         self.scheduler_panel.ui(
                             ui,
                             &self.process_manager,
+                            &mut self.session,
                             self.cpu_info.logical_cores,
+                            &self.config.policy_defaults,
                         );
+                        if let Some(pid) = self.scheduler_panel.take_watch_toggle() {
+                            self.process_manager.toggle_watch(pid);
+                        }
+                        let resolutions = self.scheduler_panel.take_rule_resolutions();
+                        self.resolve_rule_conflicts(resolutions);
+                        let opt_out = self.scheduler_panel.take_vcache_opt_out();
+                        self.apply_vcache_opt_out(opt_out);
+                        self.apply_deferred_edits();
+                    }
+                    Tab::Settings => {
+                        if let Ok(engine) = self.rule_engine.lock() {
+                            if self.settings_revision != Some(engine.revision()) {
+                                self.settings_revision = Some(engine.revision());
+                                self.settings_panel.set_rule_names(engine.rule_names());
+                                self.settings_panel.set_rule_overlaps(engine.detect_conflicts());
+                            }
+                        }
+                        self.settings_panel.set_global_hotkey_error(self.global_hotkeys.error().map(str::to_string));
+                        let remaining = self.alert_pause_remaining();
+                        self.settings_panel.set_alert_pause(remaining);
+                        if self.settings_panel.ui(
+                            ui,
+                            &mut self.config,
+                            &self.thermal,
+                            &self.focus_booster,
+                            self.scheduler_panel.presets(),
+                            &self.cpu_info,
+                        ) {
+                            self.cpu_monitor_panel.set_max_compared_cores(self.config.max_compared_cores);
+                            self.cpu_monitor_panel.set_cell_display(self.config.cell_display);
+                            self.cpu_monitor_panel.set_idle_threshold(self.config.idle_core_threshold);
+                            self.cpu_monitor_panel.set_underclock_rule(self.config.underclock);
+                            self.process_list_panel.set_density(self.config.table_density);
+                            self.process_manager.set_tiered_refresh(self.config.full_process_refresh_cycles > 1);
+                            self.apply_topology();
+                            if !self.config.debug_tools {
+                                self.debug_panel.stop();
+                            }
+                            self.config_save_pending = Some(self.clock.now());
+                        }
+                        if let Some(action) = self.settings_panel.take_profile_action() {
+                            self.handle_profile_action(action);
+                        }
+                        if let Some(pause) = self.settings_panel.take_alert_pause_request() {
+                            self.alerts_paused_until = pause.then(|| self.clock.now() + ALERT_PAUSE_DURATION);
+                        }
+                    }
+                    Tab::Debug => {
+                        self.debug_panel.ui(ui, &self.cpu_info);
                     }
                 }
             });
         });
+
+        self.collect_limitations();
+        if self.limitations_window.ui(ctx, &mut self.limitations) {
+            self.recheck_limitations();
+        }
+        if self.onboarding.ui(ctx) && !self.config.onboarding_shown {
+            self.config.onboarding_shown = true;
+            self.config.save();
+        }
+        if let Some(changed_at) = self.config_save_pending {
+            let elapsed = self.clock.now().saturating_duration_since(changed_at);
+            if elapsed >= CONFIG_SAVE_DEBOUNCE {
+                self.config.save();
+                self.config_save_pending = None;
+            } else {
+                ctx.request_repaint_after(CONFIG_SAVE_DEBOUNCE - elapsed);
+            }
+        }
+        self.refresh_touched_processes();
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.debug_panel.stop();
+        self.focus_booster.restore_all();
+        self.thermal.restore_all();
+        self.session.save_applied_by();
         self.config.save();
     }
 }
@@ -0,0 +1,31 @@
+//! 无界面模式 - 不显示窗口，只运行网页快照服务 (--serve <端口> --no-gui)
+//! 仅监视：不加载自动规则，也不修改任何进程的调度
+
+use std::thread;
+use std::time::Duration;
+use sysinfo::{ProcessesToUpdate, System};
+
+use crate::app::AppConfig;
+use crate::system::{publish_snapshot, serve_snapshots, CpuInfo, ProcessManager};
+
+/// 数据刷新间隔（与网页的轮询间隔相同）
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 启动服务并持续刷新快照，直到进程被终止；服务无法启动时返回错误
+pub fn run(addr: &str, config: &AppConfig) -> Result<(), String> {
+    let slot = serve_snapshots(addr)?;
+
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    let mut cpu_info = CpuInfo::detect();
+    let mut process_manager = ProcessManager::new(cpu_info.logical_cores);
+    loop {
+        // 使用率需要两次采样之间的间隔
+        thread::sleep(REFRESH_INTERVAL);
+        sys.refresh_cpu_all();
+        cpu_info.update(&sys, config.usage_source);
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        process_manager.update(&sys, true);
+        publish_snapshot(&slot, &cpu_info, &process_manager);
+    }
+}
@@ -3,19 +3,58 @@
 //! 支持 AMD/Intel CPU 的核心拓扑检测、进程管理和调度策略配置
 
 mod app;
+mod headless;
+mod startup;
 mod system;
 mod ui;
 mod utils;
 
 use app::{AppConfig, HexinApp};
 use eframe::egui;
+use system::{claim_instance, Instance, InstanceCommand};
+use utils::{parse_args, SystemClock, USAGE};
 
 fn main() -> eframe::Result<()> {
     // 初始化日志
     tracing_subscriber::fmt::init();
 
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
     let config = AppConfig::load();
 
+    // 无界面模式：只运行网页快照服务
+    if let (true, Some(addr)) = (args.no_gui, args.serve.as_deref()) {
+        if let Err(e) = headless::run(addr, &config) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // 单实例：已有实例在运行时把 --tab / --filter 转发过去并退出
+    let command = InstanceCommand { tab: args.tab.clone(), filter: args.filter.clone() };
+    let instance = match claim_instance(&command) {
+        Ok(Instance::Forwarded) => {
+            println!("hexin 已在运行，已切换到已有窗口");
+            return Ok(());
+        }
+        Ok(Instance::Primary(guard)) => Some(guard),
+        Err(e) => {
+            tracing::warn!("单实例检测失败，继续启动: {}", e);
+            None
+        }
+    };
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([config.window_width, config.window_height])
@@ -27,6 +66,6 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "hexin",
         options,
-        Box::new(|cc| Ok(Box::new(HexinApp::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(HexinApp::new(cc, args.serve.as_deref(), instance, command, Box::new(SystemClock))))),
     )
 }
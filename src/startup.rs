@@ -0,0 +1,120 @@
+//! 启动检测 - CPU 拓扑检测和首次进程扫描在后台线程进行，窗口先显示占位界面
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+use crate::app::AppConfig;
+use crate::system::{inactive_scheduled_rules, CpuInfo, LocalTime, ProcessManager, RuleEngine};
+
+/// 后台检测的结果
+pub struct StartupData {
+    pub sys: System,
+    pub cpu_info: CpuInfo,
+    /// 已完成首次扫描、注册了规则回调的进程管理器
+    pub process_manager: ProcessManager,
+    pub rule_engine: Arc<Mutex<RuleEngine>>,
+}
+
+impl StartupData {
+    /// 检测 CPU 拓扑并扫描进程（耗时，在后台线程调用）
+    fn detect(config: &AppConfig) -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        let cpu_info = CpuInfo::detect();
+        let logical_cores = cpu_info.logical_cores;
+        let mut process_manager = ProcessManager::new(logical_cores);
+        process_manager.set_gpu_monitoring(config.gpu_monitoring);
+
+        // 新进程出现时应用匹配的自动规则
+        let mut engine = RuleEngine::load(logical_cores);
+        // 启动时已在时间段内的定时规则直接生效（包括 hexin 未运行时错过的启用）
+        engine.set_scheduled_inactive(inactive_scheduled_rules(&config.rule_schedules, LocalTime::now()));
+        let rule_engine = Arc::new(Mutex::new(engine));
+        let engine = rule_engine.clone();
+        process_manager.on_process_appeared(move |process| {
+            if let Ok(mut engine) = engine.lock() {
+                engine.process_appeared(process);
+            }
+        });
+        let engine = rule_engine.clone();
+        process_manager.on_process_disappeared(move |pid| {
+            if let Ok(mut engine) = engine.lock() {
+                engine.process_disappeared(pid);
+            }
+        });
+        // 启动器 exec 成真正的程序后按新名称重新匹配
+        let engine = rule_engine.clone();
+        process_manager.on_process_exec(move |process, _| {
+            if let Ok(mut engine) = engine.lock() {
+                engine.process_exec(process);
+            }
+        });
+
+        // 首次扫描：已在运行的进程也会触发规则
+        process_manager.update(&sys, true);
+
+        Self {
+            sys,
+            cpu_info,
+            process_manager,
+            rule_engine,
+        }
+    }
+}
+
+/// 等待后台检测完成
+pub struct StartupLoader {
+    rx: Receiver<StartupData>,
+    /// 应用创建时间
+    started: Instant,
+    /// 首帧耗时（用于启动日志）
+    first_frame: Option<Duration>,
+}
+
+impl StartupLoader {
+    /// 启动后台检测
+    pub fn spawn(config: &AppConfig) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let config = config.clone();
+        thread::spawn(move || {
+            // 窗口可能已关闭，忽略发送失败
+            let _ = tx.send(StartupData::detect(&config));
+        });
+        Self {
+            rx,
+            started: Instant::now(),
+            first_frame: None,
+        }
+    }
+
+    /// 记录首帧时间
+    pub fn mark_frame(&mut self) {
+        if self.first_frame.is_none() {
+            let elapsed = self.started.elapsed();
+            self.first_frame = Some(elapsed);
+            tracing::info!("首帧: 启动后 {:?}", elapsed);
+        }
+    }
+
+    /// 检测完成时返回结果；后台线程异常退出时直接在当前线程检测
+    pub fn poll(&mut self, config: &AppConfig) -> Option<StartupData> {
+        let data = match self.rx.try_recv() {
+            Ok(data) => data,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                tracing::warn!("启动检测线程异常退出，改为同步检测");
+                StartupData::detect(config)
+            }
+        };
+        tracing::info!(
+            "启动检测完成: 启动后 {:?}（首帧 {:?}）",
+            self.started.elapsed(),
+            self.first_frame.unwrap_or_default()
+        );
+        Some(data)
+    }
+}
@@ -0,0 +1,365 @@
+//! 调度设置的事务式应用与回滚
+//! 每一步应用前记录原值，后续步骤失败时按相反顺序恢复已应用的步骤
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    get_io_priority, get_oom_score_adj, get_process_affinity, get_process_nice, get_rt_priority,
+    get_scheduler_info, set_io_priority, set_oom_score_adj, set_process_affinity, set_process_nice,
+    set_scheduler, IoPriority, SchedulePolicy, SchedulePreset,
+};
+
+/// 单项可回滚的调度设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchedChange {
+    /// 调度策略和实时优先级，reset_on_fork 为 true 时子进程不继承
+    Policy { policy: SchedulePolicy, priority: i32, reset_on_fork: bool },
+    /// nice 值
+    Nice(i32),
+    /// I/O 优先级
+    IoPriority(IoPriority),
+    /// OOM 分数调整值
+    OomScoreAdj(i32),
+    /// CPU 亲和性
+    Affinity(Vec<usize>),
+}
+
+impl SchedChange {
+    /// 设置项名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            SchedChange::Policy { .. } => "调度策略",
+            SchedChange::Nice(_) => "nice 值",
+            SchedChange::IoPriority(_) => "I/O 优先级",
+            SchedChange::OomScoreAdj(_) => "OOM 调整",
+            SchedChange::Affinity(_) => "亲和性",
+        }
+    }
+
+    /// 应用到进程
+    pub fn apply(&self, pid: i32) -> Result<(), String> {
+        match self {
+            SchedChange::Policy { policy, priority, reset_on_fork } => {
+                // 不覆盖无法识别（因而也无法回滚）的策略
+                let (current, _, _) = get_scheduler_info(pid);
+                if current.is_unrecognized() {
+                    return Err(format!("进程当前使用未识别的调度策略 {}，已跳过修改", current.display_name()));
+                }
+                set_scheduler(pid, *policy, *priority, *reset_on_fork)
+            }
+            SchedChange::Nice(nice) => set_process_nice(pid, *nice),
+            SchedChange::IoPriority(prio) => set_io_priority(pid, *prio),
+            SchedChange::OomScoreAdj(value) => set_oom_score_adj(pid, *value),
+            SchedChange::Affinity(cores) => set_process_affinity(pid, cores),
+        }
+    }
+
+    /// 读取进程中同类设置的当前值（用于回滚），无法读取时返回 None
+    pub fn capture(&self, pid: i32, logical_cores: usize) -> Option<SchedChange> {
+        match self {
+            SchedChange::Policy { .. } => {
+                let (policy, _, attr) = get_scheduler_info(pid);
+                if let SchedulePolicy::Unknown(_) = policy {
+                    return None;
+                }
+                let priority = get_rt_priority(pid).unwrap_or(0);
+                Some(SchedChange::Policy { policy, priority, reset_on_fork: attr.reset_on_fork() })
+            }
+            SchedChange::Nice(_) => Some(SchedChange::Nice(get_process_nice(pid))),
+            SchedChange::IoPriority(_) => get_io_priority(pid).ok().map(SchedChange::IoPriority),
+            SchedChange::OomScoreAdj(_) => get_oom_score_adj(pid).ok().map(SchedChange::OomScoreAdj),
+            SchedChange::Affinity(_) => Some(SchedChange::Affinity(get_process_affinity(pid, logical_cores))),
+        }
+    }
+
+    /// 取值是否相同（调度策略不比较 reset-on-fork 标志）
+    fn same_value(&self, other: &SchedChange) -> bool {
+        match (self, other) {
+            (
+                SchedChange::Policy { policy, priority, .. },
+                SchedChange::Policy { policy: other_policy, priority: other_priority, .. },
+            ) => policy == other_policy && priority == other_priority,
+            _ => self == other,
+        }
+    }
+}
+
+/// `changes` 中与 `other` 同一设置项但取值不同的项（只在一方出现的设置项不算）
+pub fn differing_changes(changes: &[SchedChange], other: &[SchedChange]) -> Vec<&'static str> {
+    changes
+        .iter()
+        .filter(|change| other.iter().any(|o| o.label() == change.label() && !change.same_value(o)))
+        .map(|change| change.label())
+        .collect()
+}
+
+/// 把设置写入预设（用手动修改的值更新规则）
+pub fn merge_into_preset(preset: &mut SchedulePreset, changes: &[SchedChange]) {
+    for change in changes {
+        match change {
+            SchedChange::Policy { policy, priority, .. } => {
+                // 实时优先级和 nice 共用 priority 字段，策略类别变化时旧值失去意义
+                if policy.is_realtime() {
+                    preset.priority = *priority;
+                } else if preset.policy.is_realtime() {
+                    preset.priority = 0;
+                }
+                preset.policy = *policy;
+            }
+            SchedChange::Nice(nice) => {
+                if !preset.policy.is_realtime() {
+                    preset.priority = *nice;
+                }
+            }
+            SchedChange::IoPriority(prio) => preset.io_priority = Some(*prio),
+            SchedChange::OomScoreAdj(value) => preset.oom_score_adj = Some(*value),
+            SchedChange::Affinity(cores) => preset.affinity_cores = Some(cores.clone()),
+        }
+    }
+}
+
+/// 亲和性修改前后允许运行的核心差异
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AffinityDiff {
+    /// 新允许的核心
+    pub added: Vec<usize>,
+    /// 不再允许的核心
+    pub removed: Vec<usize>,
+}
+
+impl AffinityDiff {
+    /// 核心集合是否没有变化
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 比较修改前后的亲和性（结果按核心编号排序）
+pub fn affinity_diff(old: &[usize], new: &[usize]) -> AffinityDiff {
+    let mut added: Vec<usize> = new.iter().copied().filter(|core| !old.contains(core)).collect();
+    let mut removed: Vec<usize> = old.iter().copied().filter(|core| !new.contains(core)).collect();
+    added.sort_unstable();
+    added.dedup();
+    removed.sort_unstable();
+    removed.dedup();
+    AffinityDiff { added, removed }
+}
+
+/// 事务式应用的结果
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    /// 成功应用的设置项
+    pub applied: Vec<&'static str>,
+    /// 失败的设置项及错误
+    pub failure: Option<(&'static str, String)>,
+    /// 失败后已回滚的设置项
+    pub rolled_back: Vec<&'static str>,
+    /// 回滚过程中的错误
+    pub rollback_errors: Vec<String>,
+    /// 成功应用项的原始值（按应用顺序，可用于撤销）
+    pub previous: Vec<SchedChange>,
+}
+
+impl ApplyReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// 成功应用的亲和性步骤使核心集合发生的变化（`changes` 为应用时传入的设置）
+    pub fn affinity_diff(&self, changes: &[SchedChange]) -> Option<AffinityDiff> {
+        if !self.is_success() {
+            return None;
+        }
+        let new = changes.iter().find_map(|c| match c {
+            SchedChange::Affinity(cores) => Some(cores),
+            _ => None,
+        })?;
+        let old = self.previous.iter().find_map(|c| match c {
+            SchedChange::Affinity(cores) => Some(cores),
+            _ => None,
+        })?;
+        Some(affinity_diff(old, new)).filter(|diff| !diff.is_empty())
+    }
+
+    /// 结果摘要
+    pub fn summary(&self) -> String {
+        match &self.failure {
+            None => format!("已应用: {}", self.applied.join(", ")),
+            Some((label, err)) => {
+                let mut msg = format!("{}失败: {}", label, err);
+                if !self.rolled_back.is_empty() {
+                    msg.push_str(&format!("；已回滚: {}", self.rolled_back.join(", ")));
+                }
+                if !self.rollback_errors.is_empty() {
+                    msg.push_str(&format!("；回滚失败: {}", self.rollback_errors.join("; ")));
+                }
+                msg
+            }
+        }
+    }
+}
+
+/// 依次应用设置，某项失败时继续应用其余各项，返回 (已应用项, 错误)
+pub fn apply_each<'a>(pid: i32, changes: impl IntoIterator<Item = &'a SchedChange>) -> (Vec<&'static str>, Vec<String>) {
+    let mut applied = Vec::new();
+    let mut errors = Vec::new();
+    for change in changes {
+        match change.apply(pid) {
+            Ok(()) => applied.push(change.label()),
+            Err(e) => errors.push(format!("{}: {}", change.label(), e)),
+        }
+    }
+    (applied, errors)
+}
+
+/// 按相反顺序恢复原始值，返回 (已恢复项, 错误)
+pub fn rollback(pid: i32, previous: &[SchedChange]) -> (Vec<&'static str>, Vec<String>) {
+    apply_each(pid, previous.iter().rev())
+}
+
+/// 依次应用设置，任一步失败时回滚之前已应用的步骤
+pub fn apply_changes(pid: i32, changes: &[SchedChange], logical_cores: usize) -> ApplyReport {
+    let mut report = ApplyReport::default();
+
+    for change in changes {
+        let previous = change.capture(pid, logical_cores);
+        match change.apply(pid) {
+            Ok(()) => {
+                report.applied.push(change.label());
+                if let Some(previous) = previous {
+                    report.previous.push(previous);
+                }
+            }
+            Err(e) => {
+                report.failure = Some((change.label(), e));
+                let (restored, errors) = rollback(pid, &report.previous);
+                report.rolled_back = restored;
+                report.rollback_errors = errors;
+                break;
+            }
+        }
+    }
+
+    report
+}
+
+/// 设置调度策略步骤的 reset-on-fork 标志
+pub fn set_reset_on_fork(changes: &mut [SchedChange], enabled: bool) {
+    for change in changes {
+        if let SchedChange::Policy { reset_on_fork, .. } = change {
+            *reset_on_fork = enabled;
+        }
+    }
+}
+
+/// 将预设展开为设置步骤
+pub fn preset_changes(preset: &SchedulePreset) -> Vec<SchedChange> {
+    let is_realtime = preset.policy.is_realtime();
+    let mut changes = vec![SchedChange::Policy {
+        policy: preset.policy,
+        priority: if is_realtime { preset.priority } else { 0 },
+        reset_on_fork: false,
+    }];
+
+    if !is_realtime && preset.priority != 0 {
+        changes.push(SchedChange::Nice(preset.priority));
+    }
+    if let Some(io_priority) = preset.io_priority {
+        changes.push(SchedChange::IoPriority(io_priority));
+    }
+    if let Some(oom) = preset.oom_score_adj {
+        changes.push(SchedChange::OomScoreAdj(oom));
+    }
+    if let Some(ref cores) = preset.affinity_cores {
+        changes.push(SchedChange::Affinity(cores.clone()));
+    }
+
+    changes
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::system::{CoreTopology, IoClass};
+    use std::process::{Child, Command};
+
+    fn spawn_child() -> Child {
+        Command::new("sleep").arg("30").spawn().expect("无法启动子进程")
+    }
+
+    #[test]
+    fn test_apply_changes_success() {
+        let mut child = spawn_child();
+        let pid = child.id() as i32;
+
+        let report = apply_changes(pid, &[SchedChange::OomScoreAdj(200)], 1);
+        assert!(report.is_success());
+        assert_eq!(report.applied, vec!["OOM 调整"]);
+        assert_eq!(get_oom_score_adj(pid), Ok(200));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_apply_changes_rolls_back_on_failure() {
+        let mut child = spawn_child();
+        let pid = child.id() as i32;
+        let original_oom = get_oom_score_adj(pid).unwrap();
+        let original_io = get_io_priority(pid).unwrap();
+
+        let changes = [
+            SchedChange::IoPriority(IoPriority { class: IoClass::BestEffort, level: 7 }),
+            SchedChange::OomScoreAdj(original_oom + 100),
+            // 空亲和性必然失败 (EINVAL)
+            SchedChange::Affinity(Vec::new()),
+        ];
+        let report = apply_changes(pid, &changes, 1);
+
+        assert!(!report.is_success());
+        assert_eq!(report.applied, vec!["I/O 优先级", "OOM 调整"]);
+        assert_eq!(report.failure.as_ref().map(|f| f.0), Some("亲和性"));
+        assert_eq!(report.rolled_back, vec!["OOM 调整", "I/O 优先级"]);
+        assert!(report.rollback_errors.is_empty());
+        assert_eq!(get_oom_score_adj(pid), Ok(original_oom));
+        assert_eq!(get_io_priority(pid), Ok(original_io));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_affinity_diff() {
+        let diff = affinity_diff(&[0, 1, 2, 3], &[3, 2, 5, 4, 4]);
+        assert_eq!(diff.added, vec![4, 5]);
+        assert_eq!(diff.removed, vec![0, 1]);
+        assert!(affinity_diff(&[0, 1], &[1, 0]).is_empty());
+
+        let changes = [SchedChange::Nice(5), SchedChange::Affinity(vec![0, 1])];
+        let mut report = ApplyReport {
+            applied: vec!["nice 值", "亲和性"],
+            previous: vec![SchedChange::Nice(0), SchedChange::Affinity(vec![0, 1, 2, 3])],
+            ..Default::default()
+        };
+        assert_eq!(report.affinity_diff(&changes), Some(AffinityDiff { added: Vec::new(), removed: vec![2, 3] }));
+        // 核心集合没有变化或应用失败时不提示
+        assert_eq!(report.affinity_diff(&[SchedChange::Affinity(vec![3, 2, 1, 0])]), None);
+        report.failure = Some(("亲和性", "EINVAL".to_string()));
+        assert_eq!(report.affinity_diff(&changes), None);
+    }
+
+    #[test]
+    fn test_preset_changes() {
+        let presets = SchedulePreset::builtin_presets(&CoreTopology::single_package(4, &[0, 1]));
+        let background = presets.iter().find(|p| p.name == "后台任务").unwrap();
+        let changes = preset_changes(background);
+        assert!(changes.contains(&SchedChange::OomScoreAdj(500)));
+        assert!(changes.contains(&SchedChange::IoPriority(IoPriority { class: IoClass::Idle, level: 0 })));
+
+        let game = presets.iter().find(|p| p.name.starts_with("游戏模式")).unwrap();
+        let changes = preset_changes(game);
+        assert_eq!(changes.last(), Some(&SchedChange::Affinity(vec![0, 1])));
+        assert!(changes.contains(&SchedChange::OomScoreAdj(-300)));
+    }
+}
@@ -0,0 +1,218 @@
+//! 进程分类 - 进程列表上方的快速过滤条件
+//! 内置分类之外，可以在 config.toml 的 custom_categories 中按进程名模式添加分类或扩展同名的内置分类
+
+use serde::{Deserialize, Serialize};
+
+use super::{wildcard_match, ProcessInfo};
+
+/// 高 CPU 分类的使用率阈值 (%)
+pub const HIGH_CPU_PERCENT: f32 = 25.0;
+
+/// 分类条件
+#[derive(Debug, Clone, PartialEq)]
+pub enum CategoryPredicate {
+    /// 进程名匹配任一模式（不区分大小写，支持 * 通配符）
+    NamePatterns(Vec<String>),
+    /// 命令行包含任一关键词（不区分大小写）
+    CmdContains(Vec<String>),
+    /// 运行在容器中
+    Container,
+    /// 实时调度策略 (FIFO / RR)
+    Realtime,
+    /// CPU 使用率高于阈值 (%)
+    CpuAbove(f32),
+    /// 混合架构上运行的核心类型与优先级不符
+    CoreMismatch,
+    /// 满足任一条件
+    AnyOf(Vec<CategoryPredicate>),
+}
+
+impl CategoryPredicate {
+    fn names(patterns: &[&str]) -> Self {
+        CategoryPredicate::NamePatterns(patterns.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// 进程是否满足条件
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            CategoryPredicate::NamePatterns(patterns) => {
+                let name = process.name.to_lowercase();
+                patterns.iter().any(|pattern| wildcard_match(&pattern.to_lowercase(), &name))
+            }
+            CategoryPredicate::CmdContains(keywords) => {
+                let cmd = process.cmd.to_lowercase();
+                keywords.iter().any(|keyword| cmd.contains(&keyword.to_lowercase()))
+            }
+            CategoryPredicate::Container => process.container.is_some(),
+            CategoryPredicate::Realtime => process.sched_policy.is_realtime(),
+            CategoryPredicate::CpuAbove(threshold) => process.cpu_usage > *threshold,
+            CategoryPredicate::CoreMismatch => process.core_mismatch.is_some(),
+            CategoryPredicate::AnyOf(predicates) => predicates.iter().any(|p| p.matches(process)),
+        }
+    }
+}
+
+/// 一个进程分类（过滤条件）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessCategory {
+    /// 显示名称，也是配置中引用分类的键
+    pub name: String,
+    pub predicate: CategoryPredicate,
+}
+
+/// 配置中的自定义分类
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomCategory {
+    pub name: String,
+    /// 进程名模式（不区分大小写，支持 * 通配符）
+    pub patterns: Vec<String>,
+}
+
+/// 内置分类
+pub fn builtin_categories() -> Vec<ProcessCategory> {
+    let category = |name: &str, predicate| ProcessCategory {
+        name: name.to_string(),
+        predicate,
+    };
+    vec![
+        // Wine / Proton 运行的 Windows 程序
+        category(
+            "游戏",
+            CategoryPredicate::AnyOf(vec![
+                CategoryPredicate::names(&["*.exe", "wine*", "*proton*"]),
+                CategoryPredicate::CmdContains(vec!["/proton".to_string(), "/wine".to_string()]),
+            ]),
+        ),
+        // Firefox 的内容进程名被截断为 15 个字符
+        category(
+            "浏览器",
+            CategoryPredicate::names(&[
+                "chrome", "chrome_*", "chromium*", "brave*", "vivaldi*", "msedge", "opera*", "firefox*",
+                "librewolf*", "isolated web co", "web content", "webkit*", "epiphany*",
+            ]),
+        ),
+        category(
+            "编译",
+            CategoryPredicate::names(&[
+                "cc1", "cc1plus", "cc1obj", "rustc", "ld", "ld.*", "mold", "cargo", "ninja", "make", "gcc", "g++",
+                "clang*", "build-script-*",
+            ]),
+        ),
+        category("容器", CategoryPredicate::Container),
+        category("实时任务", CategoryPredicate::Realtime),
+        category("高 CPU", CategoryPredicate::CpuAbove(HIGH_CPU_PERCENT)),
+    ]
+}
+
+/// 混合架构专用的分类：运行在与优先级不符的核心类型上的进程
+pub fn core_mismatch_category() -> ProcessCategory {
+    ProcessCategory {
+        name: "核心错配".to_string(),
+        predicate: CategoryPredicate::CoreMismatch,
+    }
+}
+
+/// 内置分类加上配置中的自定义分类；与内置分类同名的自定义分类会扩展该分类
+pub fn process_categories(custom: &[CustomCategory]) -> Vec<ProcessCategory> {
+    let mut categories = builtin_categories();
+    for extra in custom.iter().filter(|c| !c.name.trim().is_empty() && !c.patterns.is_empty()) {
+        let predicate = CategoryPredicate::NamePatterns(extra.patterns.clone());
+        match categories.iter_mut().find(|c| c.name == extra.name) {
+            Some(existing) => {
+                let builtin = std::mem::replace(&mut existing.predicate, CategoryPredicate::AnyOf(Vec::new()));
+                existing.predicate = CategoryPredicate::AnyOf(vec![builtin, predicate]);
+            }
+            None => categories.push(ProcessCategory {
+                name: extra.name.clone(),
+                predicate,
+            }),
+        }
+    }
+    categories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+    use crate::system::{ContainerInfo, ContainerRuntime, CoreMismatch, SchedulePolicy};
+
+    fn category(name: &str) -> ProcessCategory {
+        builtin_categories().into_iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_game_category() {
+        let game = category("游戏");
+        assert!(game.predicate.matches(&process(1, "EldenRing.exe", 0.0, 0)));
+        assert!(game.predicate.matches(&process(2, "wineserver", 0.0, 0)));
+        let mut proton = process(3, "python3", 0.0, 0);
+        proton.cmd = "python3 /home/u/.steam/steam/steamapps/common/Proton 9.0/proton waitforexitandrun".to_string();
+        assert!(game.predicate.matches(&proton));
+        assert!(!game.predicate.matches(&process(4, "firefox", 0.0, 0)));
+    }
+
+    #[test]
+    fn test_browser_category() {
+        let browser = category("浏览器");
+        for name in ["chrome", "chrome_crashpad_handler", "firefox-bin", "Isolated Web Co", "WebKitWebProcess"] {
+            assert!(browser.predicate.matches(&process(1, name, 0.0, 0)), "{}", name);
+        }
+        assert!(!browser.predicate.matches(&process(1, "chromedriver-helper", 0.0, 0)));
+        assert!(!browser.predicate.matches(&process(1, "cargo", 0.0, 0)));
+    }
+
+    #[test]
+    fn test_compile_category() {
+        let compile = category("编译");
+        for name in ["cc1plus", "rustc", "ld", "ld.lld", "cargo", "ninja", "clang-18", "build-script-build"] {
+            assert!(compile.predicate.matches(&process(1, name, 0.0, 0)), "{}", name);
+        }
+        assert!(!compile.predicate.matches(&process(1, "ldconfig", 0.0, 0)));
+    }
+
+    #[test]
+    fn test_attribute_categories() {
+        let mut containerized = process(1, "nginx", 0.0, 0);
+        assert!(!category("容器").predicate.matches(&containerized));
+        containerized.container = Some(ContainerInfo {
+            runtime: ContainerRuntime::Docker,
+            id: None,
+            pid_ns_differs: true,
+        });
+        assert!(category("容器").predicate.matches(&containerized));
+
+        let mut audio = process(2, "pipewire", 0.0, 0);
+        assert!(!category("实时任务").predicate.matches(&audio));
+        audio.sched_policy = SchedulePolicy::Fifo;
+        assert!(category("实时任务").predicate.matches(&audio));
+
+        let mut mismatched = process(4, "make", 90.0, 0);
+        assert!(!core_mismatch_category().predicate.matches(&mismatched));
+        mismatched.core_mismatch = Some(CoreMismatch::BackgroundOnPerformance);
+        assert!(core_mismatch_category().predicate.matches(&mismatched));
+
+        let high = category("高 CPU");
+        assert!(high.predicate.matches(&process(3, "x", 25.5, 0)));
+        assert!(!high.predicate.matches(&process(3, "x", 25.0, 0)));
+    }
+
+    #[test]
+    fn test_custom_categories() {
+        let custom = vec![
+            CustomCategory { name: "编辑器".to_string(), patterns: vec!["code".to_string(), "nvim".to_string()] },
+            CustomCategory { name: "浏览器".to_string(), patterns: vec!["qutebrowser".to_string()] },
+            // 空名称或没有模式的条目被忽略
+            CustomCategory { name: " ".to_string(), patterns: vec!["x".to_string()] },
+            CustomCategory { name: "空".to_string(), patterns: Vec::new() },
+        ];
+        let categories = process_categories(&custom);
+        assert_eq!(categories.len(), builtin_categories().len() + 1);
+
+        let editor = categories.iter().find(|c| c.name == "编辑器").unwrap();
+        assert!(editor.predicate.matches(&process(1, "NVIM", 0.0, 0)));
+        let browser = categories.iter().find(|c| c.name == "浏览器").unwrap();
+        assert!(browser.predicate.matches(&process(1, "qutebrowser", 0.0, 0)));
+        assert!(browser.predicate.matches(&process(1, "firefox", 0.0, 0)));
+    }
+}
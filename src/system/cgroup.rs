@@ -0,0 +1,440 @@
+//! cgroup CPU 统计 - 读取 cgroup v2 各组 cpu.stat 的 usage_usec，两次刷新求差得到各 slice / service 的 CPU 占用
+//! systemd 按 slice / service / scope 组织进程，按组汇总比扁平的进程列表更能看出 CPU 用在哪里
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// cgroup v2 挂载点
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// 遍历的最大深度（/user.slice/user-1000.slice/user@1000.service/app.slice 为 4 层）
+const MAX_CGROUP_DEPTH: usize = 4;
+
+/// 一个 cgroup 的 CPU 占用
+#[derive(Debug, Clone, PartialEq)]
+pub struct CgroupUsage {
+    /// 相对挂载点的路径（如 "/system.slice/sshd.service"）
+    pub path: String,
+    /// 层级深度（顶层为 1；经 `cgroup_tree` 排列后为在树中的层级）
+    pub depth: usize,
+    /// CPU 使用率（与进程列表一致，100% 为一个核心）
+    pub cpu_percent: f32,
+}
+
+impl CgroupUsage {
+    /// 最后一级的名称
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// 一次遍历得到的各组累计 CPU 时间
+#[derive(Debug, Clone, Default)]
+pub struct CgroupSnapshot {
+    /// 各组累计 CPU 时间 (微秒)
+    usage_usec: HashMap<String, u64>,
+    /// 遍历时间
+    taken_at: Option<Instant>,
+    /// 没有 cpu.stat 或其中没有 usage_usec 的组数
+    pub missing: usize,
+    /// 无权读取的组数
+    pub denied: usize,
+}
+
+impl CgroupSnapshot {
+    /// 距离遍历的时长（尚未遍历时为 None）
+    pub fn age(&self) -> Option<Duration> {
+        self.taken_at.map(|t| t.elapsed())
+    }
+
+    /// 是否没有读到任何组的统计
+    pub fn is_empty(&self) -> bool {
+        self.usage_usec.is_empty()
+    }
+}
+
+/// 解析 cpu.stat 中的 usage_usec
+pub fn parse_cpu_stat_usage(content: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.split_once(' ')?;
+        (key == "usage_usec").then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+/// 由两次快照计算各组的 CPU 使用率，按使用率从高到低排序
+pub fn cgroup_cpu_percent(
+    previous: &HashMap<String, u64>,
+    current: &HashMap<String, u64>,
+    elapsed: Duration,
+) -> Vec<CgroupUsage> {
+    let elapsed_usec = elapsed.as_micros() as f64;
+    if elapsed_usec <= 0.0 {
+        return Vec::new();
+    }
+    let mut usage: Vec<CgroupUsage> = current
+        .iter()
+        .filter_map(|(path, &now)| {
+            // 新出现的组没有基准；计数器回绕（组被删除后重建）时跳过
+            let delta = now.checked_sub(*previous.get(path)?)?;
+            Some(CgroupUsage {
+                path: path.clone(),
+                depth: path.matches('/').count(),
+                cpu_percent: (delta as f64 / elapsed_usec * 100.0) as f32,
+            })
+        })
+        .collect();
+    usage.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent).then_with(|| a.path.cmp(&b.path)));
+    usage
+}
+
+/// 上一级 cgroup 的路径（顶层组返回 None）
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent).filter(|parent| !parent.is_empty())
+}
+
+/// 按树排列 `cgroup_cpu_percent` 的结果：子组紧跟在父组之后，同一父组下按使用率从高到低
+/// 只保留使用率最高的至多 limit 个组，并带上它们在列表中的祖先，因此子组总是显示在自己的父组下；
+/// 父组没有统计的组作为顶层，depth 改为在树中的层级
+pub fn cgroup_tree(usage: Vec<CgroupUsage>, limit: usize) -> Vec<CgroupUsage> {
+    let known: HashSet<String> = usage.iter().map(|u| u.path.clone()).collect();
+    let ancestors = |path: &str| -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut current = parent_path(path);
+        while let Some(parent) = current {
+            if known.contains(parent) {
+                ancestors.push(parent.to_string());
+            }
+            current = parent_path(parent);
+        }
+        ancestors
+    };
+
+    let mut selected: HashSet<String> = HashSet::new();
+    for group in &usage {
+        if selected.contains(&group.path) {
+            continue;
+        }
+        let mut needed = ancestors(&group.path);
+        needed.retain(|path| !selected.contains(path));
+        if selected.len() + needed.len() < limit {
+            selected.extend(needed);
+            selected.insert(group.path.clone());
+        }
+    }
+
+    // 每个组挂在最近的、已选中的祖先下
+    let mut children: HashMap<Option<String>, Vec<CgroupUsage>> = HashMap::new();
+    for group in usage.into_iter().filter(|u| selected.contains(&u.path)) {
+        let parent = ancestors(&group.path).into_iter().find(|path| selected.contains(path));
+        children.entry(parent).or_default().push(group);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent).then_with(|| a.path.cmp(&b.path)));
+    }
+
+    fn visit(
+        parent: Option<String>,
+        depth: usize,
+        children: &mut HashMap<Option<String>, Vec<CgroupUsage>>,
+        ordered: &mut Vec<CgroupUsage>,
+    ) {
+        for mut group in children.remove(&parent).unwrap_or_default() {
+            group.depth = depth;
+            let path = group.path.clone();
+            ordered.push(group);
+            visit(Some(path), depth + 1, children, ordered);
+        }
+    }
+    let mut ordered = Vec::new();
+    visit(None, 1, &mut children, &mut ordered);
+    ordered
+}
+
+/// 遍历 cgroup 树读取各组的 usage_usec（不含根组）
+fn read_snapshot(root: &Path, max_depth: usize) -> CgroupSnapshot {
+    let mut snapshot = CgroupSnapshot::default();
+    walk(root, "", 1, max_depth, &mut snapshot);
+    snapshot.taken_at = Some(Instant::now());
+    snapshot
+}
+
+fn walk(dir: &Path, relative: &str, depth: usize, max_depth: usize, snapshot: &mut CgroupSnapshot) {
+    if depth > max_depth {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if e.kind() == ErrorKind::PermissionDenied {
+                snapshot.denied += 1;
+            }
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        let relative = format!("{}/{}", relative, entry.file_name().to_string_lossy());
+        match fs::read_to_string(path.join("cpu.stat")) {
+            Ok(content) => match parse_cpu_stat_usage(&content) {
+                Some(usage) => {
+                    snapshot.usage_usec.insert(relative.clone(), usage);
+                }
+                None => snapshot.missing += 1,
+            },
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => snapshot.denied += 1,
+            Err(_) => snapshot.missing += 1,
+        }
+        walk(&path, &relative, depth + 1, max_depth, snapshot);
+    }
+}
+
+/// 遍历 root 下的 cgroup 树并与上次快照对比，返回 CPU 占用最高的至多 limit 个组（按树排列）
+/// 首次调用只记录基准，返回空；父组的占用包含其子组
+fn top_cgroups_in(root: &Path, previous: &mut CgroupSnapshot, limit: usize) -> Vec<CgroupUsage> {
+    let current = read_snapshot(root, MAX_CGROUP_DEPTH);
+    let usage = match (previous.taken_at, current.taken_at) {
+        (Some(before), Some(now)) => cgroup_cpu_percent(&previous.usage_usec, &current.usage_usec, now - before),
+        _ => Vec::new(),
+    };
+    *previous = current;
+    cgroup_tree(usage, limit)
+}
+
+/// cgroup CPU 占用的后台采样：遍历 cgroup 树可能读取上千个文件，放在工作线程中进行
+pub struct CgroupSampler {
+    tx: Sender<(u64, CgroupSnapshot, Vec<CgroupUsage>)>,
+    rx: Receiver<(u64, CgroupSnapshot, Vec<CgroupUsage>)>,
+    /// cgroup v2 挂载点
+    root: PathBuf,
+    /// 显示的最多组数
+    limit: usize,
+    /// 最近一次遍历的快照（下次遍历的基准）
+    snapshot: CgroupSnapshot,
+    /// CPU 占用最高的组（按树排列）
+    usage: Vec<CgroupUsage>,
+    /// 重置次数，丢弃重置前发出的遍历结果
+    generation: u64,
+    /// 是否有正在进行的遍历
+    in_flight: bool,
+}
+
+impl CgroupSampler {
+    /// 显示 CPU 占用最高的至多 limit 个组
+    pub fn new(limit: usize) -> Self {
+        Self::new_in(PathBuf::from(CGROUP_ROOT), limit)
+    }
+
+    fn new_in(root: PathBuf, limit: usize) -> Self {
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            root,
+            limit,
+            snapshot: CgroupSnapshot::default(),
+            usage: Vec::new(),
+            generation: 0,
+            in_flight: false,
+        }
+    }
+
+    /// 接收已完成的遍历，距上次遍历超过 interval 时在后台开始新的遍历
+    pub fn refresh(&mut self, interval: Duration) {
+        while let Ok((generation, snapshot, usage)) = self.rx.try_recv() {
+            if generation == self.generation {
+                self.in_flight = false;
+                self.snapshot = snapshot;
+                self.usage = usage;
+            }
+        }
+        if self.in_flight || self.snapshot.age().is_some_and(|age| age < interval) {
+            return;
+        }
+        self.in_flight = true;
+        let tx = self.tx.clone();
+        let root = self.root.clone();
+        let limit = self.limit;
+        let generation = self.generation;
+        let mut previous = self.snapshot.clone();
+        thread::spawn(move || {
+            let usage = top_cgroups_in(&root, &mut previous, limit);
+            // 接收端可能已销毁，忽略发送失败
+            let _ = tx.send((generation, previous, usage));
+        });
+    }
+
+    /// 丢弃基准和结果（重新打开视图时，旧基准已经过期）
+    pub fn reset(&mut self) {
+        self.generation += 1;
+        self.in_flight = false;
+        self.snapshot = CgroupSnapshot::default();
+        self.usage.clear();
+    }
+
+    /// CPU 占用最高的组（按树排列，子组紧跟在父组之后）
+    pub fn usage(&self) -> &[CgroupUsage] {
+        &self.usage
+    }
+
+    /// 最近一次遍历的快照
+    pub fn snapshot(&self) -> &CgroupSnapshot {
+        &self.snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_stat() {
+        let stat = "usage_usec 123456\nuser_usec 100000\nsystem_usec 23456\nnr_periods 0\n";
+        assert_eq!(parse_cpu_stat_usage(stat), Some(123_456));
+        assert_eq!(parse_cpu_stat_usage("user_usec 1\n"), None);
+        assert_eq!(parse_cpu_stat_usage(""), None);
+    }
+
+    #[test]
+    fn test_cgroup_cpu_percent() {
+        let previous: HashMap<String, u64> = [
+            ("/system.slice".to_string(), 1_000_000),
+            ("/system.slice/sshd.service".to_string(), 500_000),
+            ("/user.slice".to_string(), 2_000_000),
+            ("/init.scope".to_string(), 9_000_000),
+        ]
+        .into();
+        let current: HashMap<String, u64> = [
+            ("/system.slice".to_string(), 1_500_000),
+            ("/system.slice/sshd.service".to_string(), 500_000),
+            ("/user.slice".to_string(), 4_000_000),
+            // 新出现的组没有基准
+            ("/machine.slice".to_string(), 100),
+            // 重建后计数器变小
+            ("/init.scope".to_string(), 10),
+        ]
+        .into();
+
+        let usage = cgroup_cpu_percent(&previous, &current, Duration::from_secs(1));
+        let summary: Vec<(&str, usize, f32)> = usage.iter().map(|u| (u.path.as_str(), u.depth, u.cpu_percent)).collect();
+        assert_eq!(
+            summary,
+            vec![("/user.slice", 1, 200.0), ("/system.slice", 1, 50.0), ("/system.slice/sshd.service", 2, 0.0)]
+        );
+        assert_eq!(usage[2].name(), "sshd.service");
+        assert!(cgroup_cpu_percent(&previous, &current, Duration::ZERO).is_empty());
+    }
+
+    #[test]
+    fn test_top_cgroups() {
+        let root = std::env::temp_dir().join(format!("hexin-cgroup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let write = |relative: &str, stat: Option<&str>| {
+            let dir = root.join(relative);
+            fs::create_dir_all(&dir).unwrap();
+            if let Some(stat) = stat {
+                fs::write(dir.join("cpu.stat"), stat).unwrap();
+            }
+        };
+        write("system.slice", Some("usage_usec 100\n"));
+        write("system.slice/cron.service", Some("usage_usec 40\n"));
+        write("user.slice", Some("usage_usec 200\n"));
+        // 未启用 cpu 统计
+        write("empty.slice", None);
+        // 超过最大深度的组不读取
+        write("a.slice/b.slice/c.slice/d.slice/e.scope", Some("usage_usec 1\n"));
+
+        let mut snapshot = CgroupSnapshot::default();
+        assert!(top_cgroups_in(&root, &mut snapshot, 10).is_empty());
+        assert_eq!(snapshot.usage_usec.len(), 3);
+        // a.slice 到 d.slice 没有 cpu.stat
+        assert_eq!(snapshot.missing, 5);
+
+        fs::write(root.join("user.slice/cpu.stat"), "usage_usec 50200\n").unwrap();
+        let top = top_cgroups_in(&root, &mut snapshot, 2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].path, "/user.slice");
+        assert!(top[0].cpu_percent > 0.0);
+        assert_eq!(top[1].cpu_percent, 0.0);
+
+        assert!(top_cgroups_in(&root.join("missing"), &mut CgroupSnapshot::default(), 10).is_empty());
+
+        // 后台采样：第一次遍历只记录基准，第二次得到占用
+        let mut sampler = CgroupSampler::new_in(root.clone(), 10);
+        let pass = |sampler: &mut CgroupSampler| {
+            sampler.refresh(Duration::ZERO);
+            for _ in 0..200 {
+                sampler.refresh(Duration::MAX);
+                if !sampler.in_flight {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+        pass(&mut sampler);
+        assert!(sampler.usage().is_empty());
+        assert_eq!(sampler.snapshot().missing, 5);
+        fs::write(root.join("system.slice/cron.service/cpu.stat"), "usage_usec 9000040\n").unwrap();
+        pass(&mut sampler);
+        let paths: Vec<&str> = sampler.usage().iter().map(|u| u.path.as_str()).collect();
+        assert_eq!(paths, vec!["/system.slice", "/system.slice/cron.service", "/user.slice"]);
+        sampler.reset();
+        assert!(sampler.usage().is_empty() && sampler.snapshot().age().is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_cgroup_tree() {
+        let group = |path: &str, cpu_percent: f32| CgroupUsage {
+            path: path.to_string(),
+            depth: path.matches('/').count(),
+            cpu_percent,
+        };
+        // 扁平排序时 /b.slice/x 排在 /a.slice 之后，缩进后会显示在 /a.slice 下
+        let usage = vec![
+            group("/b.slice", 90.0),
+            group("/a.slice", 60.0),
+            group("/b.slice/x.service", 50.0),
+            group("/a.slice/y.service", 40.0),
+            group("/b.slice/z.service", 30.0),
+            // 父组没有统计
+            group("/c.slice/w.scope/v.scope", 20.0),
+            group("/c.slice/w.scope/u.scope", 10.0),
+        ];
+        let tree = |limit| -> Vec<(String, usize)> {
+            cgroup_tree(usage.clone(), limit).into_iter().map(|u| (u.path, u.depth)).collect()
+        };
+        let expected = |rows: &[(&str, usize)]| -> Vec<(String, usize)> {
+            rows.iter().map(|&(path, depth)| (path.to_string(), depth)).collect()
+        };
+        assert_eq!(
+            tree(10),
+            expected(&[
+                ("/b.slice", 1),
+                ("/b.slice/x.service", 2),
+                ("/b.slice/z.service", 2),
+                ("/a.slice", 1),
+                ("/a.slice/y.service", 2),
+                ("/c.slice/w.scope/v.scope", 1),
+                ("/c.slice/w.scope/u.scope", 1),
+            ])
+        );
+        assert_eq!(
+            tree(3),
+            expected(&[("/b.slice", 1), ("/b.slice/x.service", 2), ("/a.slice", 1)])
+        );
+
+        // 祖先使用率低于子组（两次读取之间的误差）时，子组连同祖先一起保留
+        let skewed = vec![group("/a.slice/y.service", 12.0), group("/b.slice", 11.0), group("/a.slice", 10.0)];
+        let rows: Vec<(String, usize)> = cgroup_tree(skewed, 2).into_iter().map(|u| (u.path, u.depth)).collect();
+        assert_eq!(rows, expected(&[("/a.slice", 1), ("/a.slice/y.service", 2)]));
+        assert!(cgroup_tree(Vec::new(), 5).is_empty());
+    }
+}
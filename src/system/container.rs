@@ -0,0 +1,215 @@
+//! 容器 / 命名空间识别
+//! 通过 PID 命名空间和 cgroup 路径判断进程是否运行在容器中
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use super::read_process_cgroup;
+
+/// 容器运行时
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+    Kubernetes,
+    Lxc,
+    /// systemd-nspawn / libvirt 等 machine.slice 下的容器
+    Machine,
+    /// 仅检测到独立的 PID 命名空间
+    Unknown,
+}
+
+impl ContainerRuntime {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+            ContainerRuntime::Kubernetes => "k8s",
+            ContainerRuntime::Lxc => "lxc",
+            ContainerRuntime::Machine => "machine",
+            ContainerRuntime::Unknown => "容器",
+        }
+    }
+}
+
+/// 进程所属容器信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    /// 容器运行时
+    pub runtime: ContainerRuntime,
+    /// 容器 ID 或名称（从 cgroup 路径中提取）
+    pub id: Option<String>,
+    /// PID 命名空间与 hexin 不同（容器内看到的 PID 与此处不一致）
+    pub pid_ns_differs: bool,
+}
+
+impl ContainerInfo {
+    /// 显示文本（如 "docker 3f2a1b9c4d5e"）
+    pub fn display(&self) -> String {
+        match &self.id {
+            Some(id) => format!("{} {}", self.runtime.display_name(), id),
+            None => self.runtime.display_name().to_string(),
+        }
+    }
+}
+
+/// 截短容器 ID（64 位十六进制 ID 只保留前 12 位，与 docker ps 一致）
+fn short_id(id: &str) -> String {
+    if id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+        id[..12].to_string()
+    } else {
+        id.to_string()
+    }
+}
+
+/// 提取 "<prefix><id>.scope" 形式组件中的 ID
+fn scope_id<'a>(component: &'a str, prefix: &str) -> Option<&'a str> {
+    component.strip_prefix(prefix)?.strip_suffix(".scope")
+}
+
+/// 根据 cgroup 路径推断容器运行时和 ID
+pub fn detect_container_from_cgroup(path: &str) -> Option<(ContainerRuntime, Option<String>)> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+    // Kubernetes 优先：其中的 docker-/cri-containerd- scope 属于 Pod
+    if components.iter().any(|c| c.starts_with("kubepods")) {
+        let id = components.last().map(|last| {
+            let last = last.strip_suffix(".scope").unwrap_or(last);
+            let id = last.rsplit('-').next().unwrap_or(last);
+            short_id(id)
+        });
+        return Some((ContainerRuntime::Kubernetes, id));
+    }
+
+    for (i, component) in components.iter().enumerate() {
+        if let Some(id) = scope_id(component, "docker-") {
+            return Some((ContainerRuntime::Docker, Some(short_id(id))));
+        }
+        if *component == "docker" {
+            let id = components.get(i + 1).map(|id| short_id(id));
+            return Some((ContainerRuntime::Docker, id));
+        }
+        if let Some(id) = scope_id(component, "libpod-") {
+            return Some((ContainerRuntime::Podman, Some(short_id(id))));
+        }
+        if let Some(name) = component.strip_prefix("lxc.payload.") {
+            return Some((ContainerRuntime::Lxc, Some(name.to_string())));
+        }
+        if *component == "lxc" {
+            let name = components.get(i + 1).map(|name| name.to_string());
+            return Some((ContainerRuntime::Lxc, name));
+        }
+        if *component == "machine.slice" {
+            // systemd 会把名称中的 '-' 转义为 "\x2d"
+            let name = components
+                .get(i + 1)
+                .and_then(|c| scope_id(c, "machine-"))
+                .map(|name| name.replace("\\x2d", "-"));
+            return Some((ContainerRuntime::Machine, name));
+        }
+    }
+
+    None
+}
+
+/// 读取进程的 PID 命名空间标识 (如 "pid:[4026531836]")
+pub fn read_pid_namespace(pid: u32) -> Option<String> {
+    fs::read_link(format!("/proc/{}/ns/pid", pid))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+/// 检测进程所属容器，own_pid_ns 为 hexin 自身的 PID 命名空间
+pub fn detect_container(pid: u32, own_pid_ns: Option<&str>) -> Option<ContainerInfo> {
+    let pid_ns_differs = match (own_pid_ns, read_pid_namespace(pid)) {
+        (Some(own), Some(ns)) => own != ns,
+        _ => false,
+    };
+
+    match read_process_cgroup(pid).and_then(|path| detect_container_from_cgroup(&path)) {
+        Some((runtime, id)) => Some(ContainerInfo { runtime, id, pid_ns_differs }),
+        None if pid_ns_differs => Some(ContainerInfo {
+            runtime: ContainerRuntime::Unknown,
+            id: None,
+            pid_ns_differs,
+        }),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_ID: &str = "3f2a1b9c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8";
+
+    #[test]
+    fn test_detect_docker() {
+        let systemd = format!("/system.slice/docker-{}.scope", DOCKER_ID);
+        assert_eq!(
+            detect_container_from_cgroup(&systemd),
+            Some((ContainerRuntime::Docker, Some("3f2a1b9c4d5e".to_string())))
+        );
+
+        let cgroupfs = format!("/docker/{}", DOCKER_ID);
+        assert_eq!(
+            detect_container_from_cgroup(&cgroupfs),
+            Some((ContainerRuntime::Docker, Some("3f2a1b9c4d5e".to_string())))
+        );
+
+        // docker.service 自身不是容器
+        assert_eq!(detect_container_from_cgroup("/system.slice/docker.service"), None);
+    }
+
+    #[test]
+    fn test_detect_other_runtimes() {
+        let podman = format!("/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope", DOCKER_ID);
+        assert_eq!(
+            detect_container_from_cgroup(&podman),
+            Some((ContainerRuntime::Podman, Some("3f2a1b9c4d5e".to_string())))
+        );
+
+        let k8s = format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1234.slice/cri-containerd-{}.scope",
+            DOCKER_ID
+        );
+        assert_eq!(
+            detect_container_from_cgroup(&k8s),
+            Some((ContainerRuntime::Kubernetes, Some("3f2a1b9c4d5e".to_string())))
+        );
+
+        assert_eq!(
+            detect_container_from_cgroup("/lxc.payload.web01/init.scope"),
+            Some((ContainerRuntime::Lxc, Some("web01".to_string())))
+        );
+        assert_eq!(
+            detect_container_from_cgroup("/lxc/web02"),
+            Some((ContainerRuntime::Lxc, Some("web02".to_string())))
+        );
+        assert_eq!(
+            detect_container_from_cgroup("/machine.slice/machine-my\\x2dvm.scope/payload"),
+            Some((ContainerRuntime::Machine, Some("my-vm".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_detect_host_processes() {
+        assert_eq!(detect_container_from_cgroup("/"), None);
+        assert_eq!(detect_container_from_cgroup("/init.scope"), None);
+        assert_eq!(
+            detect_container_from_cgroup("/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_namespace_matches() {
+        let pid = std::process::id();
+        let own = read_pid_namespace(pid);
+        assert!(own.is_some());
+        let info = detect_container(pid, own.as_deref());
+        assert!(!info.is_some_and(|i| i.pid_ns_differs));
+    }
+}
@@ -0,0 +1,173 @@
+//! 混合架构的核心匹配 - 检查进程运行的核心类型是否与其优先级相符
+//! 高优先级进程被限制在能效核心上，或后台任务大量占用性能核心时给出提示，便于找出值得调整的进程
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{CoreType, CpuCore, ProcessInfo, SchedulePolicy};
+
+/// nice 值不低于此值的进程视为后台任务
+pub const BACKGROUND_NICE: i32 = 10;
+/// 后台任务 CPU 使用率超过此值 (%) 时才算占用性能核心（空闲的后台进程不影响其他进程）
+pub const BACKGROUND_HOG_PERCENT: f32 = 10.0;
+/// 判断运行核心类型时保留的最近采样数
+const CORE_TYPE_SAMPLES: usize = 5;
+/// 最近的采样中至少有这么多次在另一种核心类型上才切换（调度器短暂迁移不改变显示的类型）
+const CORE_TYPE_SWITCH_SAMPLES: usize = 4;
+
+/// 进程与核心类型不匹配的情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CoreMismatch {
+    /// 后台任务在性能核心上占用较多 CPU
+    BackgroundOnPerformance,
+    /// 高优先级进程运行在能效核心上
+    PriorityOnEfficiency,
+}
+
+impl CoreMismatch {
+    /// 完整说明
+    pub fn label(&self) -> &'static str {
+        match self {
+            CoreMismatch::BackgroundOnPerformance => "⚠ 后台任务占用性能核",
+            CoreMismatch::PriorityOnEfficiency => "⚠ 高优先级任务在能效核",
+        }
+    }
+
+    /// 进程列表中的简短标记
+    pub fn short_label(&self) -> &'static str {
+        match self {
+            CoreMismatch::BackgroundOnPerformance => "⚠ 后台占P核",
+            CoreMismatch::PriorityOnEfficiency => "⚠ 优先在E核",
+        }
+    }
+}
+
+/// 是否同时有性能核心和能效核心
+pub fn is_hybrid(cores: &[CpuCore]) -> bool {
+    cores.iter().any(|c| c.core_type == CoreType::Performance) && cores.iter().any(|c| c.core_type == CoreType::Efficiency)
+}
+
+/// 进程当前所在的核心类型：优先取最近运行的核心，否则亲和性中的核心全部为同一类型时取该类型
+fn running_core_type(process: &ProcessInfo, core_types: &[CoreType]) -> Option<CoreType> {
+    if let Some(core_type) = process.last_cpu.and_then(|cpu| core_types.get(cpu)) {
+        return Some(*core_type);
+    }
+    let mut types = process.affinity.iter().filter_map(|&cpu| core_types.get(cpu));
+    let first = *types.next()?;
+    types.all(|&t| t == first).then_some(first)
+}
+
+/// 进程最近几次采样所在的核心类型，平滑后的结果只在另一种类型占多数时才切换
+#[derive(Debug, Clone, Default)]
+pub struct CoreTypeHistory {
+    samples: VecDeque<CoreType>,
+    current: Option<CoreType>,
+}
+
+impl CoreTypeHistory {
+    /// 记录一次采样（无法判断时为 None，不计入），返回平滑后的核心类型
+    pub fn record(&mut self, sample: Option<CoreType>) -> Option<CoreType> {
+        if let Some(sample) = sample {
+            if self.samples.len() == CORE_TYPE_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+            let count = self.samples.iter().filter(|&&t| t == sample).count();
+            if self.current.is_none() || count >= CORE_TYPE_SWITCH_SAMPLES {
+                self.current = Some(sample);
+            }
+        }
+        self.current
+    }
+}
+
+/// 判断进程是否运行在与优先级不符的核心类型上；`core_types` 按逻辑 CPU 编号排列
+/// 本次采样记入进程的核心类型历史，按平滑后的类型判断
+pub fn core_mismatch(process: &mut ProcessInfo, core_types: &[CoreType]) -> Option<CoreMismatch> {
+    let sample = running_core_type(process, core_types);
+    let core_type = process.core_type_history.record(sample)?;
+    let high_priority = process.sched_policy.is_realtime() || process.priority < 0;
+    let background = matches!(process.sched_policy, SchedulePolicy::Batch | SchedulePolicy::Idle)
+        || process.priority >= BACKGROUND_NICE;
+    match core_type {
+        CoreType::Efficiency if high_priority => Some(CoreMismatch::PriorityOnEfficiency),
+        CoreType::Performance if background && process.cpu_usage > BACKGROUND_HOG_PERCENT => {
+            Some(CoreMismatch::BackgroundOnPerformance)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+
+    const TYPES: [CoreType; 4] = [CoreType::Performance, CoreType::Performance, CoreType::Efficiency, CoreType::Efficiency];
+
+    #[test]
+    fn test_core_mismatch() {
+        let mut game = process(1, "game", 80.0, 0);
+        game.priority = -5;
+        game.last_cpu = Some(2);
+        assert_eq!(core_mismatch(&mut game, &TYPES), Some(CoreMismatch::PriorityOnEfficiency));
+        let mut game = process(1, "game", 80.0, 0);
+        game.priority = -5;
+        game.last_cpu = Some(0);
+        assert_eq!(core_mismatch(&mut game, &TYPES), None);
+
+        // 没有最近运行的核心时按亲和性判断
+        let mut audio = process(2, "pipewire", 1.0, 0);
+        audio.sched_policy = SchedulePolicy::Fifo;
+        audio.affinity = vec![2, 3];
+        assert_eq!(core_mismatch(&mut audio, &TYPES), Some(CoreMismatch::PriorityOnEfficiency));
+        // 亲和性跨两种核心时无法判断，保持之前的结果
+        audio.affinity = vec![1, 2];
+        assert_eq!(core_mismatch(&mut audio, &TYPES), Some(CoreMismatch::PriorityOnEfficiency));
+        assert_eq!(core_mismatch(&mut process(2, "pipewire", 1.0, 0), &TYPES), None);
+
+        let mut indexer = process(3, "baloo", 40.0, 0);
+        indexer.sched_policy = SchedulePolicy::Idle;
+        indexer.last_cpu = Some(1);
+        assert_eq!(core_mismatch(&mut indexer, &TYPES), Some(CoreMismatch::BackgroundOnPerformance));
+        // 空闲的后台任务不算占用
+        indexer.cpu_usage = 2.0;
+        assert_eq!(core_mismatch(&mut indexer, &TYPES), None);
+
+        let mut build = process(4, "make", 90.0, 0);
+        build.priority = BACKGROUND_NICE;
+        build.last_cpu = Some(0);
+        assert_eq!(core_mismatch(&mut build, &TYPES), Some(CoreMismatch::BackgroundOnPerformance));
+
+        // 普通进程不标记
+        let mut shell = process(5, "bash", 50.0, 0);
+        shell.last_cpu = Some(2);
+        assert_eq!(core_mismatch(&mut shell, &TYPES), None);
+    }
+
+    #[test]
+    fn test_core_type_smoothing() {
+        let mut build = process(4, "make", 90.0, 0);
+        build.priority = BACKGROUND_NICE;
+        let mut sample = |cpu: usize| {
+            build.last_cpu = Some(cpu);
+            core_mismatch(&mut build, &TYPES)
+        };
+        assert_eq!(sample(0), Some(CoreMismatch::BackgroundOnPerformance));
+        // 在两种核心之间来回迁移时不跳动
+        for cpu in [3, 1, 2, 0, 3, 1, 2] {
+            assert_eq!(sample(cpu), Some(CoreMismatch::BackgroundOnPerformance));
+        }
+        // 持续运行在能效核上：最近 5 次中有 4 次后切换
+        assert_eq!(sample(3), Some(CoreMismatch::BackgroundOnPerformance));
+        assert_eq!(sample(2), None);
+        // 短暂回到性能核不切换回去
+        assert_eq!(sample(0), None);
+        assert_eq!(sample(2), None);
+
+        let mut history = CoreTypeHistory::default();
+        assert_eq!(history.record(None), None);
+        assert_eq!(history.record(Some(CoreType::Efficiency)), Some(CoreType::Efficiency));
+        assert_eq!(history.record(None), Some(CoreType::Efficiency));
+    }
+}
@@ -2,11 +2,17 @@
 //! 支持自动检测 AMD/Intel CPU 的核心拓扑、缓存信息等
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
+use std::time::Instant;
 use sysinfo::System;
 
+use super::{
+    is_sleep_state, read_vulnerabilities, CStateTracker, PrivilegeInfo, ProcStatSampler, TemperatureSensors,
+    UsageBreakdown, UsageSource, Vulnerability,
+};
+
 /// CPU 核心类型（用于 Intel 混合架构）
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CoreType {
@@ -52,6 +58,33 @@ pub struct CpuCore {
     pub frequency_mhz: u64,
     /// 当前使用率 (0.0 - 100.0)
     pub usage_percent: f32,
+    /// 使用率分项（来自 /proc/stat，首次采样或无法读取时为 None）
+    #[serde(default)]
+    pub usage_breakdown: Option<UsageBreakdown>,
+    /// 各 C-state 驻留比例 (名称, %)，不支持 cpuidle 时为空
+    pub cstate_residency: Vec<(String, f32)>,
+    /// 空闲时间比例：非 C0 状态驻留之和，无 C-state 数据时为 100 - 使用率
+    pub idle_time_percent: f32,
+    /// 最大睿频 (MHz)，来自 Intel MSR_TURBO_RATIO_LIMIT，无法读取时为 None
+    pub turbo_max_mhz: Option<u64>,
+    /// 核心温度 (°C)，没有单核传感器时为封装温度，未读取时为 None
+    #[serde(default)]
+    pub temperature_c: Option<f32>,
+    /// 本次没有读到温度，`temperature_c` 是上次读取的值
+    #[serde(default)]
+    pub temperature_stale: bool,
+    /// ACPI CPPC 报告的标称 / 最高频率，固件或驱动不提供时为 None
+    #[serde(default)]
+    pub cppc: Option<CppcFreq>,
+}
+
+/// ACPI CPPC 报告的频率（amd-pstate、intel_pstate 等驱动提供 acpi_cppc 目录）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppcFreq {
+    /// 标称频率 (MHz)：持续负载下应能保持的频率
+    pub nominal_mhz: u64,
+    /// 最高频率 (MHz)：按 highest_perf / nominal_perf 由标称频率折算，缺少性能值时为 None
+    pub max_mhz: Option<u64>,
 }
 
 /// CPU 总体信息
@@ -71,12 +104,45 @@ pub struct CpuInfo {
     pub cores: Vec<CpuCore>,
     /// L3 缓存信息
     pub l3_caches: Vec<L3CacheInfo>,
-    /// 基础频率 (MHz)
+    /// 基础频率 (MHz)，cpufreq 不提供 base_frequency 时为最低频率
     pub base_frequency_mhz: u64,
+    /// cpufreq 报告的基础（标称）频率 (MHz)，不提供时为 None
+    #[serde(default)]
+    pub nominal_frequency_mhz: Option<u64>,
     /// 最大频率 (MHz)
     pub max_frequency_mhz: u64,
     /// 总体使用率
     pub total_usage_percent: f32,
+    /// 总体使用率分项（来自 /proc/stat）
+    #[serde(default)]
+    pub total_breakdown: Option<UsageBreakdown>,
+    /// CPU 系列 (family)
+    #[serde(default)]
+    pub family: Option<u32>,
+    /// 型号编号 (model)
+    #[serde(default)]
+    pub model: Option<u32>,
+    /// 步进 (stepping)
+    #[serde(default)]
+    pub stepping: Option<u32>,
+    /// 微码版本，如 "0xa201016"；虚拟机等环境中可能无法获取
+    #[serde(default)]
+    pub microcode: Option<String>,
+    /// 漏洞缓解状态（启动时确定），旧内核没有该信息时为空
+    #[serde(default)]
+    pub vulnerabilities: Vec<Vulnerability>,
+    /// C-state 驻留比例跟踪
+    #[serde(skip)]
+    cstate_tracker: CStateTracker,
+    /// 是否正在显示 C-state 分布（不显示时降低采样频率）
+    #[serde(skip)]
+    cstate_visible: bool,
+    /// /proc/stat 使用率采样
+    #[serde(skip)]
+    proc_stat: ProcStatSampler,
+    /// 温度传感器（首次读取温度时查找）
+    #[serde(skip)]
+    temperature_sensors: TemperatureSensors,
 }
 
 /// CPU 厂商
@@ -103,6 +169,7 @@ impl CpuInfo {
         let model = cpuinfo.get("model name")
             .cloned()
             .unwrap_or_else(|| model_name.clone());
+        let microcode = read_microcode_version().or_else(|| cpuinfo_microcode(&cpuinfo));
 
         let logical_cores = sys.cpus().len();
         let physical_cores = detect_physical_cores(logical_cores);
@@ -127,7 +194,15 @@ impl CpuInfo {
         }
 
         // 检测频率范围
-        let (base_freq, max_freq) = detect_frequency_range();
+        let (nominal_freq, base_freq, max_freq) = detect_frequency_range();
+
+        // Intel 睿频上限（需要 msr 驱动和 CAP_SYS_RAWIO）
+        if vendor == CpuVendor::Intel && PrivilegeInfo::detect().cap_sys_rawio {
+            for core in &mut cores {
+                core.turbo_max_mhz = read_msr(core.cpu_id, MSR_TURBO_RATIO_LIMIT)
+                    .and_then(|value| parse_turbo_ratio_limit(value, 1));
+            }
+        }
 
         CpuInfo {
             model_name: model,
@@ -138,31 +213,154 @@ impl CpuInfo {
             cores,
             l3_caches,
             base_frequency_mhz: base_freq,
+            nominal_frequency_mhz: nominal_freq,
             max_frequency_mhz: max_freq,
             total_usage_percent: 0.0,
+            total_breakdown: None,
+            family: cpuinfo_number(&cpuinfo, "cpu family"),
+            model: cpuinfo_number(&cpuinfo, "model"),
+            stepping: cpuinfo_number(&cpuinfo, "stepping"),
+            microcode,
+            vulnerabilities: read_vulnerabilities(),
+            cstate_tracker: CStateTracker::default(),
+            cstate_visible: false,
+            proc_stat: ProcStatSampler::new(),
+            temperature_sensors: TemperatureSensors::default(),
+        }
+    }
+
+    /// 检测完成前使用的空信息（没有核心）
+    pub fn empty() -> Self {
+        CpuInfo {
+            model_name: String::new(),
+            vendor: CpuVendor::Other,
+            physical_cores: 0,
+            logical_cores: 0,
+            smt_enabled: false,
+            cores: Vec::new(),
+            l3_caches: Vec::new(),
+            base_frequency_mhz: 0,
+            nominal_frequency_mhz: None,
+            max_frequency_mhz: 0,
+            total_usage_percent: 0.0,
+            total_breakdown: None,
+            family: None,
+            model: None,
+            stepping: None,
+            microcode: None,
+            vulnerabilities: Vec::new(),
+            cstate_tracker: CStateTracker::default(),
+            cstate_visible: false,
+            proc_stat: ProcStatSampler::new(),
+            temperature_sensors: TemperatureSensors::default(),
         }
     }
 
     /// 更新 CPU 使用率和频率
-    pub fn update(&mut self, sys: &System) {
+    ///
+    /// 用户态 / 内核态 / iowait 分项总是从 /proc/stat 采样；`source` 为 /proc/stat 时使用率也取自分项，
+    /// 首次采样或缺少某个 CPU 的数据（核心数不一致、离线）时该核心回退到 sysinfo 的值
+    pub fn update(&mut self, sys: &System, source: UsageSource) {
         let cpus = sys.cpus();
         let mut total_usage = 0.0;
 
+        let (proc_total, proc_cores) = self.proc_stat.sample_system();
+        self.total_breakdown = proc_total;
+        let use_proc_stat = source == UsageSource::ProcStat;
+        let sample_cstates = self.cstate_tracker.is_due(self.cstate_visible, Instant::now());
+
         for (i, cpu) in cpus.iter().enumerate() {
             if i < self.cores.len() {
-                self.cores[i].usage_percent = cpu.cpu_usage();
+                let breakdown = proc_cores.get(&self.cores[i].cpu_id).copied();
+                let usage = match breakdown {
+                    Some(b) if use_proc_stat => b.usage,
+                    _ => cpu.cpu_usage(),
+                };
+                self.cores[i].usage_percent = usage;
+                self.cores[i].usage_breakdown = breakdown;
                 self.cores[i].frequency_mhz = cpu.frequency();
-                total_usage += cpu.cpu_usage();
+                total_usage += usage;
+
+                let core = &mut self.cores[i];
+                if sample_cstates {
+                    core.cstate_residency = self.cstate_tracker.sample(core.cpu_id);
+                }
+                core.idle_time_percent = if core.cstate_residency.is_empty() {
+                    100.0 - core.usage_percent
+                } else {
+                    core.cstate_residency
+                        .iter()
+                        .filter(|(name, _)| is_sleep_state(name))
+                        .map(|(_, pct)| pct)
+                        .sum::<f32>()
+                        .min(100.0)
+                };
             }
         }
 
-        self.total_usage_percent = if !cpus.is_empty() {
+        self.total_usage_percent = if let Some(total) = proc_total.filter(|_| use_proc_stat) {
+            total.usage
+        } else if !cpus.is_empty() {
             total_usage / cpus.len() as f32
         } else {
             0.0
         };
     }
 
+    /// 设置是否正在显示 C-state 分布
+    pub fn set_cstate_visible(&mut self, visible: bool) {
+        self.cstate_visible = visible;
+    }
+
+    /// 读取各核心温度（只在需要显示时调用），没有单核传感器时所有核心使用封装温度
+    /// 读取失败的核心保留上次的值并标记为过期
+    pub fn update_temperatures(&mut self) {
+        let per_core = self.temperature_sensors.read_cores();
+        let package = if per_core.is_empty() { self.temperature_sensors.read_package() } else { None };
+        for core in &mut self.cores {
+            match per_core.get(&(core.package_id, core.core_id)).copied().or(package) {
+                Some(celsius) => {
+                    core.temperature_c = Some(celsius);
+                    core.temperature_stale = false;
+                }
+                None => core.temperature_stale = core.temperature_c.is_some(),
+            }
+        }
+    }
+
+    /// 不读取温度时（不显示）已有的值都视为过期
+    pub fn mark_temperatures_stale(&mut self) {
+        for core in &mut self.cores {
+            core.temperature_stale = core.temperature_c.is_some();
+        }
+    }
+
+    /// 所有核心的平均空闲时间比例
+    pub fn mean_idle_percent(&self) -> f32 {
+        if self.cores.is_empty() {
+            return 0.0;
+        }
+        self.cores.iter().map(|c| c.idle_time_percent).sum::<f32>() / self.cores.len() as f32
+    }
+
+    /// 所有核心的平均 C-state 驻留分布 (状态名 -> %)
+    pub fn idle_distribution(&self) -> HashMap<String, f32> {
+        let mut distribution: HashMap<String, f32> = HashMap::new();
+        if self.cores.is_empty() {
+            return distribution;
+        }
+        for core in &self.cores {
+            for (name, pct) in &core.cstate_residency {
+                *distribution.entry(name.clone()).or_insert(0.0) += pct;
+            }
+        }
+        let count = self.cores.len() as f32;
+        for pct in distribution.values_mut() {
+            *pct /= count;
+        }
+        distribution
+    }
+
     /// 计算适合显示的网格布局（列数）
     pub fn grid_columns(&self) -> usize {
         match self.logical_cores {
@@ -175,17 +373,6 @@ impl CpuInfo {
         }
     }
 
-    /// 获取按 L3 缓存分组的核心
-    pub fn cores_by_l3(&self) -> HashMap<u32, Vec<&CpuCore>> {
-        let mut groups: HashMap<u32, Vec<&CpuCore>> = HashMap::new();
-        for core in &self.cores {
-            if let Some(l3_id) = core.l3_cache_id {
-                groups.entry(l3_id).or_default().push(core);
-            }
-        }
-        groups
-    }
-
     /// 获取 3D V-Cache 核心列表
     pub fn vcache_cores(&self) -> Vec<usize> {
         let vcache_ids: Vec<u32> = self.l3_caches
@@ -200,21 +387,215 @@ impl CpuInfo {
             .map(|c| c.cpu_id)
             .collect()
     }
+
+    /// 按物理封装（插槽）分组的逻辑 CPU
+    pub fn packages(&self) -> BTreeMap<usize, Vec<usize>> {
+        group_by_package(&self.cores)
+    }
+
+    /// 某个插槽的平均使用率，插槽不存在时为 None
+    pub fn package_usage(&self, package_id: usize) -> Option<f32> {
+        mean_usage(self.cores.iter().filter(|c| c.package_id == package_id))
+    }
+
+    /// 解析亲和性目标所需的拓扑
+    pub fn topology(&self) -> CoreTopology {
+        CoreTopology {
+            logical_cores: self.logical_cores,
+            vcache_cores: self.vcache_cores(),
+            packages: self.packages(),
+            reserved_cores: Vec::new(),
+        }
+    }
+}
+
+/// 按 L3 缓存分组核心（按缓存 ID 排序）
+pub fn cores_by_l3<'a>(cores: impl IntoIterator<Item = &'a CpuCore>) -> BTreeMap<u32, Vec<&'a CpuCore>> {
+    let mut groups: BTreeMap<u32, Vec<&CpuCore>> = BTreeMap::new();
+    for core in cores {
+        if let Some(l3_id) = core.l3_cache_id {
+            groups.entry(l3_id).or_default().push(core);
+        }
+    }
+    groups
+}
+
+/// 按封装 ID 分组逻辑 CPU（组内升序）
+pub fn group_by_package(cores: &[CpuCore]) -> BTreeMap<usize, Vec<usize>> {
+    let mut packages: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for core in cores {
+        packages.entry(core.package_id).or_default().push(core.cpu_id);
+    }
+    for cpus in packages.values_mut() {
+        cpus.sort_unstable();
+    }
+    packages
+}
+
+/// 核心拓扑摘要：内置预设和符号亲和性目标据此解析为具体的 CPU 列表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoreTopology {
+    /// 逻辑核心数
+    pub logical_cores: usize,
+    /// 3D V-Cache 核心
+    pub vcache_cores: Vec<usize>,
+    /// 各插槽的逻辑 CPU（按封装 ID）
+    pub packages: BTreeMap<usize, Vec<usize>>,
+    /// 保留给系统的核心，符号目标解析时排除
+    pub reserved_cores: Vec<usize>,
+}
+
+impl CoreTopology {
+    /// 单插槽拓扑（测试用）
+    #[cfg(test)]
+    pub fn single_package(logical_cores: usize, vcache_cores: &[usize]) -> Self {
+        Self {
+            logical_cores,
+            vcache_cores: vcache_cores.to_vec(),
+            packages: BTreeMap::from([(0, (0..logical_cores).collect())]),
+            reserved_cores: Vec::new(),
+        }
+    }
+
+    /// 设置保留核心（超出逻辑核心数的编号忽略）
+    pub fn with_reserved(mut self, reserved_cores: &[usize]) -> Self {
+        self.reserved_cores = reserved_cores.iter().copied().filter(|&c| c < self.logical_cores).collect();
+        self
+    }
+
+    /// 不排除保留核心的拓扑（显式覆盖保留设置时使用）
+    pub fn including_reserved(&self) -> Self {
+        Self {
+            reserved_cores: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// 是否为多路（多插槽）系统
+    pub fn is_multi_package(&self) -> bool {
+        self.packages.len() > 1
+    }
+
+    /// 从符号目标解析出的核心中去掉保留核心；全部是保留核心时保持原样，避免得到空亲和性
+    fn exclude_reserved(&self, cores: Vec<usize>) -> Vec<usize> {
+        let remaining: Vec<usize> = cores.iter().copied().filter(|c| !self.reserved_cores.contains(c)).collect();
+        if remaining.is_empty() {
+            cores
+        } else {
+            remaining
+        }
+    }
+}
+
+/// 符号亲和性目标：在当前拓扑上解析，而不是写死 CPU 编号
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AffinityTarget {
+    /// 所有核心
+    All,
+    /// 3D V-Cache 核心
+    VCache,
+    /// 非 V-Cache 的高频核心
+    Frequency,
+    /// 某个插槽的所有核心
+    Package(u32),
+    /// 明确的 CPU 列表
+    Cores(Vec<usize>),
+}
+
+impl AffinityTarget {
+    /// 解析 "all" / "vcache" / "frequency" / "package:N" / CPU 列表（如 "0-7,16-23"）
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        match s {
+            "all" => return Ok(AffinityTarget::All),
+            "vcache" => return Ok(AffinityTarget::VCache),
+            "frequency" => return Ok(AffinityTarget::Frequency),
+            _ => {}
+        }
+        if let Some(id) = s.strip_prefix("package:") {
+            return id.trim().parse().map(AffinityTarget::Package).map_err(|_| format!("无效的插槽编号 '{}'", id));
+        }
+        parse_cpu_list(s)
+            .filter(|cores| !cores.is_empty())
+            .map(AffinityTarget::Cores)
+            .ok_or_else(|| format!("无法识别的亲和性 '{}'", s))
+    }
+
+    /// 解析为 CPU 列表；没有 V-Cache 的 CPU 上 vcache / frequency 返回 None（不修改亲和性）
+    ///
+    /// 所有应用路径（内置预设、profiles.d）都经由这里解析，符号目标在此统一排除保留核心；
+    /// 明确的 CPU 列表按原样使用
+    pub fn resolve(&self, topology: &CoreTopology) -> Result<Option<Vec<usize>>, String> {
+        let logical_cores = topology.logical_cores;
+        let vcache_cores = &topology.vcache_cores;
+        let symbolic = match self {
+            AffinityTarget::All => (0..logical_cores).collect(),
+            AffinityTarget::VCache | AffinityTarget::Frequency if vcache_cores.is_empty() => return Ok(None),
+            AffinityTarget::VCache => vcache_cores.clone(),
+            AffinityTarget::Frequency => (0..logical_cores).filter(|c| !vcache_cores.contains(c)).collect(),
+            AffinityTarget::Package(id) => topology
+                .packages
+                .get(&(*id as usize))
+                .cloned()
+                .ok_or_else(|| format!("插槽 {} 不存在（共 {} 个插槽）", id, topology.packages.len()))?,
+            AffinityTarget::Cores(cores) => {
+                if let Some(core) = cores.iter().find(|&&c| c >= logical_cores) {
+                    return Err(format!("亲和性包含不存在的核心 {}（共 {} 个逻辑核心）", core, logical_cores));
+                }
+                return Ok(Some(cores.clone()));
+            }
+        };
+        Ok(Some(topology.exclude_reserved(symbolic)))
+    }
 }
 
 /// 读取 /proc/cpuinfo
 fn read_cpuinfo() -> HashMap<String, String> {
+    fs::read_to_string("/proc/cpuinfo")
+        .map(|content| parse_cpuinfo(&content))
+        .unwrap_or_default()
+}
+
+/// 解析 /proc/cpuinfo 中第一个处理器（cpu0）的字段
+/// 混合架构上各处理器的字段可能不同，只取第一段以保持与 cpu0 的 sysfs 数据一致
+pub fn parse_cpuinfo(content: &str) -> HashMap<String, String> {
     let mut info = HashMap::new();
-    if let Ok(content) = fs::read_to_string("/proc/cpuinfo") {
-        for line in content.lines() {
-            if let Some((key, value)) = line.split_once(':') {
-                info.insert(key.trim().to_string(), value.trim().to_string());
-            }
+    for line in content.lines().skip_while(|l| l.trim().is_empty()) {
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            info.insert(key.trim().to_string(), value.trim().to_string());
         }
     }
     info
 }
 
+/// 读取 cpuinfo 中的十进制数值字段（如 "cpu family"、"model"、"stepping"）
+pub fn cpuinfo_number(cpuinfo: &HashMap<String, String>, key: &str) -> Option<u32> {
+    cpuinfo.get(key).and_then(|v| v.parse().ok())
+}
+
+/// cpuinfo 中的微码版本（部分架构和虚拟机没有此字段）
+fn cpuinfo_microcode(cpuinfo: &HashMap<String, String>) -> Option<String> {
+    cpuinfo.get("microcode").and_then(|v| normalize_microcode(v))
+}
+
+/// 从 sysfs 读取 cpu0 的微码版本
+fn read_microcode_version() -> Option<String> {
+    fs::read_to_string("/sys/devices/system/cpu/cpu0/microcode/version")
+        .ok()
+        .and_then(|v| normalize_microcode(&v))
+}
+
+/// 统一微码版本格式为小写十六进制 "0x..."，无效值返回 None
+pub fn normalize_microcode(value: &str) -> Option<String> {
+    let value = value.trim();
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+    let version = u64::from_str_radix(digits, 16).ok()?;
+    Some(format!("{:#x}", version))
+}
+
 /// 检测 CPU 厂商
 fn detect_vendor(cpuinfo: &HashMap<String, String>) -> CpuVendor {
     if let Some(vendor) = cpuinfo.get("vendor_id") {
@@ -277,7 +658,116 @@ fn detect_core_topology(cpu_id: usize, vendor: CpuVendor) -> CpuCore {
         l3_cache_id: None, // 稍后填充
         frequency_mhz: 0,
         usage_percent: 0.0,
+        usage_breakdown: None,
+        cstate_residency: Vec::new(),
+        idle_time_percent: 0.0,
+        turbo_max_mhz: None,
+        temperature_c: None,
+        temperature_stale: false,
+        cppc: read_cppc(cpu_id),
+    }
+}
+
+/// 读取核心的 ACPI CPPC 频率
+fn read_cppc(cpu_id: usize) -> Option<CppcFreq> {
+    let dir = format!("/sys/devices/system/cpu/cpu{}/acpi_cppc", cpu_id);
+    let read = |name: &str| read_sysfs_value(&format!("{}/{}", dir, name));
+    cppc_freq(read("nominal_freq")?, read("nominal_perf"), read("highest_perf"))
+}
+
+/// 由标称频率和性能值组成 CPPC 频率；nominal_freq 为 0 表示固件未提供
+fn cppc_freq(nominal_mhz: u64, nominal_perf: Option<u64>, highest_perf: Option<u64>) -> Option<CppcFreq> {
+    if nominal_mhz == 0 {
+        return None;
+    }
+    let max_mhz = match (nominal_perf, highest_perf) {
+        (Some(nominal), Some(highest)) if nominal > 0 && highest >= nominal => Some(nominal_mhz * highest / nominal),
+        _ => None,
+    };
+    Some(CppcFreq { nominal_mhz, max_mhz })
+}
+
+/// CPU 集合完全落在同一个 L3 缓存（CCD）内时返回该缓存 ID
+pub fn l3_containing(cpus: &[usize], l3_caches: &[L3CacheInfo]) -> Option<u32> {
+    if cpus.is_empty() {
+        return None;
+    }
+    l3_caches
+        .iter()
+        .find(|cache| cpus.iter().all(|cpu| cache.shared_cpus.contains(cpu)))
+        .map(|cache| cache.id)
+}
+
+/// 一组核心的平均使用率，没有核心时为 None
+pub fn mean_usage<'a>(cores: impl IntoIterator<Item = &'a CpuCore>) -> Option<f32> {
+    let usages: Vec<f32> = cores.into_iter().map(|c| c.usage_percent).collect();
+    if usages.is_empty() {
+        return None;
     }
+    Some(usages.iter().sum::<f32>() / usages.len() as f32)
+}
+
+/// SMT 争用阈值：同一物理核心的所有线程使用率都超过此值 (%) 时视为争用
+pub const SMT_CONTENTION_THRESHOLD: f32 = 70.0;
+
+/// 兄弟线程都繁忙时每个线程相对独占物理核心的大致吞吐（粗略估计）
+pub const SMT_SHARED_THROUGHPUT: f32 = 0.6;
+
+/// 一个存在 SMT 争用的物理核心
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtContention {
+    /// 物理封装 ID
+    pub package_id: usize,
+    /// 物理核心 ID
+    pub core_id: usize,
+    /// 共享此物理核心的逻辑 CPU（升序）
+    pub cpu_ids: Vec<usize>,
+}
+
+/// 找出所有兄弟线程都繁忙的物理核心（按封装、核心 ID 排序）
+pub fn smt_contention(cores: &[CpuCore]) -> Vec<SmtContention> {
+    let mut physical: BTreeMap<(usize, usize), Vec<&CpuCore>> = BTreeMap::new();
+    for core in cores {
+        physical.entry((core.package_id, core.core_id)).or_default().push(core);
+    }
+    physical
+        .into_iter()
+        .filter(|(_, threads)| {
+            threads.len() > 1 && threads.iter().all(|c| c.usage_percent > SMT_CONTENTION_THRESHOLD)
+        })
+        .map(|((package_id, core_id), threads)| {
+            let mut cpu_ids: Vec<usize> = threads.iter().map(|c| c.cpu_id).collect();
+            cpu_ids.sort_unstable();
+            SmtContention { package_id, core_id, cpu_ids }
+        })
+        .collect()
+}
+
+/// Intel MSR_TURBO_RATIO_LIMIT
+const MSR_TURBO_RATIO_LIMIT: u64 = 0x1AD;
+
+/// 从 MSR_TURBO_RATIO_LIMIT 中取出 active_cores 个核心活跃时的最大睿频 (MHz)
+/// 第 N 个字节为 N+1 个核心活跃时的倍频，单位 100 MHz；active_cores 为 1 时即单核最大睿频
+pub fn parse_turbo_ratio_limit(value: u64, active_cores: usize) -> Option<u64> {
+    let index = active_cores.clamp(1, 8) - 1;
+    let ratio = (value >> (index * 8)) & 0xFF;
+    (ratio > 0).then_some(ratio * 100)
+}
+
+/// 通过 /dev/cpu/N/msr 读取 MSR（不可访问时返回 None）
+#[cfg(target_os = "linux")]
+fn read_msr(cpu_id: usize, offset: u64) -> Option<u64> {
+    use std::os::unix::fs::FileExt;
+
+    let file = fs::File::open(format!("/dev/cpu/{}/msr", cpu_id)).ok()?;
+    let mut buf = [0u8; 8];
+    file.read_exact_at(&mut buf, offset).ok()?;
+    Some(u64::from_le_bytes(buf))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_msr(_cpu_id: usize, _offset: u64) -> Option<u64> {
+    None
 }
 
 /// 检测 NUMA 节点
@@ -364,18 +854,19 @@ fn detect_l3_caches(logical_cores: usize) -> Vec<L3CacheInfo> {
     result
 }
 
-/// 检测频率范围
-fn detect_frequency_range() -> (u64, u64) {
-    let base = read_sysfs_value("/sys/devices/system/cpu/cpu0/cpufreq/base_frequency")
-        .or_else(|| read_sysfs_value("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_min_freq"))
-        .map(|f: u64| f / 1000) // KHz -> MHz
+/// 检测频率范围，返回（标称频率, 基础频率, 最高频率）
+/// 没有 base_frequency 时基础频率退回最低频率，标称频率为 None
+fn detect_frequency_range() -> (Option<u64>, u64, u64) {
+    let nominal = read_sysfs_value("/sys/devices/system/cpu/cpu0/cpufreq/base_frequency").map(|f: u64| f / 1000); // KHz -> MHz
+    let base = nominal
+        .or_else(|| read_sysfs_value("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_min_freq").map(|f: u64| f / 1000))
         .unwrap_or(0);
 
     let max = read_sysfs_value("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
         .map(|f: u64| f / 1000)
         .unwrap_or(0);
 
-    (base, max)
+    (nominal, base, max)
 }
 
 /// 读取 sysfs 数值
@@ -386,7 +877,7 @@ fn read_sysfs_value<T: std::str::FromStr>(path: &str) -> Option<T> {
 }
 
 /// 解析 CPU 列表字符串 (如 "0-7,16-23")
-fn parse_cpu_list(s: &str) -> Option<Vec<usize>> {
+pub fn parse_cpu_list(s: &str) -> Option<Vec<usize>> {
     let mut result = Vec::new();
     for part in s.trim().split(',') {
         let part = part.trim();
@@ -404,6 +895,47 @@ fn parse_cpu_list(s: &str) -> Option<Vec<usize>> {
     Some(result)
 }
 
+/// 解析设置中输入的保留核心（排序去重），不能保留全部核心
+pub fn parse_reserved_cores(s: &str, logical_cores: usize) -> Result<Vec<usize>, String> {
+    let mut cores = parse_cpu_list(s).ok_or_else(|| format!("'{}' 不是有效的 CPU 列表，应为如 0,8 或 0-1", s.trim()))?;
+    cores.sort_unstable();
+    cores.dedup();
+    if let Some(core) = cores.iter().find(|&&c| c >= logical_cores) {
+        return Err(format!("核心 {} 不存在（共 {} 个逻辑核心）", core, logical_cores));
+    }
+    if cores.len() >= logical_cores {
+        return Err("不能保留全部核心".to_string());
+    }
+    Ok(cores)
+}
+
+/// 选择中包含的保留核心（用于手动选择时的提醒）
+pub fn reserved_among(cores: &[usize], reserved: &[usize]) -> Vec<usize> {
+    cores.iter().copied().filter(|c| reserved.contains(c)).collect()
+}
+
+/// 格式化 CPU 列表为范围字符串 (如 [0,1,2,3,8] -> "0-3,8")
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut sorted = cpus.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut parts = Vec::new();
+    let mut iter = sorted.into_iter().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end = iter.next().unwrap_or(end);
+        }
+        if start == end {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, end));
+        }
+    }
+    parts.join(",")
+}
+
 /// 解析缓存大小字符串 (如 "32768K" 或 "32M")
 fn parse_cache_size(s: &str) -> u64 {
     let s = s.trim().to_uppercase();
@@ -427,6 +959,233 @@ mod tests {
         assert_eq!(parse_cpu_list("0-1,4-5"), Some(vec![0, 1, 4, 5]));
     }
 
+    #[test]
+    fn test_format_cpu_list() {
+        assert_eq!(format_cpu_list(&[0, 1, 2, 3, 8]), "0-3,8");
+        assert_eq!(format_cpu_list(&[5, 4, 16, 17]), "4-5,16-17");
+        assert_eq!(format_cpu_list(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_turbo_ratio_limit() {
+        // 1-2 核 57x，3-4 核 55x，其余 53x
+        let value = 0x3535_3535_3737_3939u64;
+        assert_eq!(parse_turbo_ratio_limit(value, 1), Some(5700));
+        assert_eq!(parse_turbo_ratio_limit(value, 3), Some(5500));
+        assert_eq!(parse_turbo_ratio_limit(value, 8), Some(5300));
+        assert_eq!(parse_turbo_ratio_limit(value, 64), Some(5300));
+        assert_eq!(parse_turbo_ratio_limit(0, 1), None);
+    }
+
+    #[test]
+    fn test_cppc_freq() {
+        let cppc = cppc_freq(3000, Some(120), Some(196)).unwrap();
+        assert_eq!((cppc.nominal_mhz, cppc.max_mhz), (3000, Some(4900)));
+        assert_eq!(cppc_freq(3000, None, Some(196)).unwrap().max_mhz, None);
+        assert_eq!(cppc_freq(3000, Some(0), Some(196)).unwrap().max_mhz, None);
+        assert_eq!(cppc_freq(0, Some(120), Some(196)), None);
+    }
+
+    fn core(cpu_id: usize, core_id: usize, usage_percent: f32) -> CpuCore {
+        packaged(cpu_id, 0, core_id, usage_percent)
+    }
+
+    fn packaged(cpu_id: usize, package_id: usize, core_id: usize, usage_percent: f32) -> CpuCore {
+        CpuCore {
+            cpu_id,
+            core_id,
+            package_id,
+            numa_node: 0,
+            core_type: CoreType::Performance,
+            cluster_id: None,
+            l3_cache_id: None,
+            frequency_mhz: 0,
+            usage_percent,
+            usage_breakdown: None,
+            cstate_residency: Vec::new(),
+            idle_time_percent: 0.0,
+            turbo_max_mhz: None,
+            temperature_c: None,
+            temperature_stale: false,
+            cppc: None,
+        }
+    }
+
+    #[test]
+    fn test_smt_contention() {
+        // 8 线程 / 4 物理核心，兄弟线程编号相差 4
+        let usages = [90.0, 95.0, 20.0, 75.0, 80.0, 10.0, 99.0, 71.0];
+        let cores: Vec<CpuCore> = usages.iter().enumerate().map(|(i, &u)| core(i, i % 4, u)).collect();
+        let contended = smt_contention(&cores);
+        assert_eq!(
+            contended.iter().map(|c| c.cpu_ids.clone()).collect::<Vec<_>>(),
+            vec![vec![0, 4], vec![3, 7]]
+        );
+
+        // 未启用 SMT 时不存在争用
+        let cores: Vec<CpuCore> = (0..4).map(|i| core(i, i, 100.0)).collect();
+        assert!(smt_contention(&cores).is_empty());
+
+        // 不同封装的同号核心不是兄弟
+        let mut other_package = core(1, 0, 100.0);
+        other_package.package_id = 1;
+        assert!(smt_contention(&[core(0, 0, 100.0), other_package]).is_empty());
+    }
+
+    /// 双路 2×4 核 8 线程：插槽 0 为 CPU 0-3,8-11，插槽 1 为 4-7,12-15（Linux 常见编号）
+    fn dual_socket() -> Vec<CpuCore> {
+        (0..16)
+            .map(|cpu| {
+                let package = (cpu / 4) % 2;
+                let usage = if package == 0 { 80.0 } else { 20.0 };
+                packaged(cpu, package, cpu % 4, usage)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_package() {
+        let packages = group_by_package(&dual_socket());
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[&0], vec![0, 1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(packages[&1], vec![4, 5, 6, 7, 12, 13, 14, 15]);
+
+        // 同号核心分属两个插槽，互相不是 SMT 兄弟
+        let busy: Vec<CpuCore> = dual_socket().into_iter().map(|mut c| { c.usage_percent = 100.0; c }).collect();
+        let contention = smt_contention(&busy);
+        assert_eq!(contention.len(), 8);
+        assert_eq!(contention[0].cpu_ids, vec![0, 8]);
+        assert_eq!(contention[4].cpu_ids, vec![4, 12]);
+    }
+
+    #[test]
+    fn test_l3_containing() {
+        let cache = |id: u32, shared_cpus: Vec<usize>| L3CacheInfo { id, size_kb: 32768, shared_cpus, is_vcache: false };
+        let caches = [cache(0, vec![0, 1, 2, 3, 8, 9, 10, 11]), cache(1, vec![4, 5, 6, 7, 12, 13, 14, 15])];
+        assert_eq!(l3_containing(&[0, 8, 11], &caches), Some(0));
+        assert_eq!(l3_containing(&[13], &caches), Some(1));
+        assert_eq!(l3_containing(&[3, 4], &caches), None);
+        assert_eq!(l3_containing(&[], &caches), None);
+        assert_eq!(l3_containing(&[0], &[]), None);
+    }
+
+    #[test]
+    fn test_mean_usage() {
+        let cores = dual_socket();
+        assert_eq!(mean_usage(&cores), Some(50.0));
+        assert_eq!(mean_usage(cores.iter().filter(|c| [0, 4, 5].contains(&c.cpu_id))), Some(40.0));
+        assert_eq!(mean_usage(&[]), None);
+    }
+
+    #[test]
+    fn test_affinity_target() {
+        assert_eq!(AffinityTarget::parse("all"), Ok(AffinityTarget::All));
+        assert_eq!(AffinityTarget::parse(" package:1 "), Ok(AffinityTarget::Package(1)));
+        assert_eq!(AffinityTarget::parse("0-1,4"), Ok(AffinityTarget::Cores(vec![0, 1, 4])));
+        assert!(AffinityTarget::parse("package:x").is_err());
+        assert!(AffinityTarget::parse("fast").is_err());
+
+        let topology = CoreTopology {
+            logical_cores: 16,
+            vcache_cores: Vec::new(),
+            packages: group_by_package(&dual_socket()),
+            reserved_cores: Vec::new(),
+        };
+        assert!(topology.is_multi_package());
+        assert_eq!(
+            AffinityTarget::Package(1).resolve(&topology),
+            Ok(Some(vec![4, 5, 6, 7, 12, 13, 14, 15]))
+        );
+        assert!(AffinityTarget::Package(2).resolve(&topology).is_err());
+        assert_eq!(AffinityTarget::VCache.resolve(&topology), Ok(None));
+        assert_eq!(AffinityTarget::All.resolve(&topology).unwrap().unwrap().len(), 16);
+        assert!(AffinityTarget::Cores(vec![16]).resolve(&topology).is_err());
+
+        let single = CoreTopology::single_package(4, &[0, 1]);
+        assert!(!single.is_multi_package());
+        assert_eq!(AffinityTarget::Package(0).resolve(&single), Ok(Some(vec![0, 1, 2, 3])));
+        assert_eq!(AffinityTarget::Frequency.resolve(&single), Ok(Some(vec![2, 3])));
+    }
+
+    #[test]
+    fn test_reserved_cores() {
+        // 双插槽，插槽 0 的 CPU 0 及其 SMT 兄弟 8 保留给系统
+        let topology = CoreTopology {
+            logical_cores: 16,
+            vcache_cores: vec![0, 1, 2, 3, 8, 9, 10, 11],
+            packages: group_by_package(&dual_socket()),
+            reserved_cores: Vec::new(),
+        }
+        .with_reserved(&[0, 8, 99]);
+        assert_eq!(topology.reserved_cores, vec![0, 8]);
+
+        let all = AffinityTarget::All.resolve(&topology).unwrap().unwrap();
+        assert_eq!(all.len(), 14);
+        assert!(!all.contains(&0) && !all.contains(&8));
+        assert_eq!(AffinityTarget::VCache.resolve(&topology), Ok(Some(vec![1, 2, 3, 9, 10, 11])));
+        assert_eq!(AffinityTarget::Frequency.resolve(&topology), Ok(Some(vec![4, 5, 6, 7, 12, 13, 14, 15])));
+        assert_eq!(AffinityTarget::Package(0).resolve(&topology), Ok(Some(vec![1, 2, 3, 9, 10, 11])));
+        assert_eq!(AffinityTarget::Package(1).resolve(&topology), Ok(Some(vec![4, 5, 6, 7, 12, 13, 14, 15])));
+        // 明确的 CPU 列表不受影响
+        assert_eq!(AffinityTarget::Cores(vec![0, 1]).resolve(&topology), Ok(Some(vec![0, 1])));
+
+        // 显式覆盖
+        let unreserved = topology.including_reserved();
+        assert_eq!(AffinityTarget::VCache.resolve(&unreserved), Ok(Some(vec![0, 1, 2, 3, 8, 9, 10, 11])));
+
+        // 目标只剩保留核心时不排除
+        let all_reserved = CoreTopology::single_package(4, &[0, 1]).with_reserved(&[0, 1]);
+        assert_eq!(AffinityTarget::VCache.resolve(&all_reserved), Ok(Some(vec![0, 1])));
+        assert_eq!(AffinityTarget::Frequency.resolve(&all_reserved), Ok(Some(vec![2, 3])));
+        // 没有 V-Cache 时仍不修改亲和性
+        assert_eq!(AffinityTarget::VCache.resolve(&CoreTopology::single_package(4, &[]).with_reserved(&[0])), Ok(None));
+
+        assert_eq!(parse_reserved_cores("8, 0", 16), Ok(vec![0, 8]));
+        assert_eq!(parse_reserved_cores("", 16), Ok(vec![]));
+        assert!(parse_reserved_cores("0-x", 16).is_err());
+        assert!(parse_reserved_cores("16", 16).is_err());
+        assert!(parse_reserved_cores("0-3", 4).is_err());
+        assert_eq!(reserved_among(&[0, 1, 2], &[0, 8]), vec![0]);
+    }
+
+    const CPUINFO: &str = "\
+processor\t: 0
+vendor_id\t: AuthenticAMD
+cpu family\t: 25
+model\t\t: 97
+model name\t: AMD Ryzen 9 7950X3D 16-Core Processor
+stepping\t: 2
+microcode\t: 0xA601203
+cpu MHz\t\t: 3000.000
+
+processor\t: 1
+vendor_id\t: AuthenticAMD
+cpu family\t: 25
+model\t\t: 97
+stepping\t: 3
+microcode\t: 0xa601206
+";
+
+    #[test]
+    fn test_parse_cpuinfo_signature() {
+        let cpuinfo = parse_cpuinfo(CPUINFO);
+        assert_eq!(cpuinfo["model name"], "AMD Ryzen 9 7950X3D 16-Core Processor");
+        assert_eq!(cpuinfo_number(&cpuinfo, "cpu family"), Some(25));
+        assert_eq!(cpuinfo_number(&cpuinfo, "model"), Some(97));
+        // 只取第一个处理器的字段
+        assert_eq!(cpuinfo_number(&cpuinfo, "stepping"), Some(2));
+        assert_eq!(cpuinfo_microcode(&cpuinfo), Some("0xa601203".to_string()));
+
+        // 缺少字段时为 None（如部分 ARM 内核和虚拟机）
+        let minimal = parse_cpuinfo("\nprocessor\t: 0\nBogoMIPS\t: 48.00\n");
+        assert_eq!(cpuinfo_number(&minimal, "stepping"), None);
+        assert_eq!(cpuinfo_microcode(&minimal), None);
+
+        assert_eq!(normalize_microcode("0xa201016\n"), Some("0xa201016".to_string()));
+        assert_eq!(normalize_microcode("b4"), Some("0xb4".to_string()));
+        assert_eq!(normalize_microcode(""), None);
+    }
+
     #[test]
     fn test_parse_cache_size() {
         assert_eq!(parse_cache_size("32768K"), 32768);
@@ -0,0 +1,458 @@
+//! cpufreq 频率限制 - 读写各策略的 scaling_min_freq / scaling_max_freq
+//! 共享同一策略的核心（policy* 目录的 affected_cpus）只写一次；sysfs 中的频率单位为 kHz
+//! 调速器 (scaling_governor) 同样按策略设置，修改一个核心会同时作用于同策略的其他核心
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::format_cpu_list;
+
+/// cpufreq 策略目录
+const CPUFREQ_ROOT: &str = "/sys/devices/system/cpu/cpufreq";
+
+/// 一个 cpufreq 策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpufreqPolicy {
+    /// 策略目录（如 /sys/devices/system/cpu/cpufreq/policy0）
+    pub path: PathBuf,
+    /// 受此策略控制的 CPU
+    pub cpus: Vec<usize>,
+    /// 硬件允许的最低频率 (kHz)
+    pub hw_min_khz: u64,
+    /// 硬件允许的最高频率 (kHz)
+    pub hw_max_khz: u64,
+    /// 当前下限 (kHz)
+    pub min_khz: u64,
+    /// 当前上限 (kHz)
+    pub max_khz: u64,
+    /// 当前调速器（驱动不提供时为 None）
+    pub governor: Option<String>,
+    /// 可用的调速器（scaling_available_governors）
+    pub available_governors: Vec<String>,
+    /// 能耗性能偏好（energy_performance_preference，驱动不支持时为 None）
+    pub epp: Option<String>,
+}
+
+impl CpufreqPolicy {
+    /// 目录名（如 "policy0"）
+    pub fn name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// 把以 MHz 表示的范围限制在硬件范围内并保证下限不高于上限，返回 kHz
+    pub fn clamp_limits(&self, min_mhz: u64, max_mhz: u64) -> (u64, u64) {
+        let clamp = |mhz: u64| mhz_to_khz(mhz).clamp(self.hw_min_khz, self.hw_max_khz);
+        let max_khz = clamp(max_mhz);
+        (clamp(min_mhz).min(max_khz), max_khz)
+    }
+
+    /// 当前的频率范围 (MHz)
+    pub fn limits(&self) -> FreqLimits {
+        FreqLimits {
+            hw_min_mhz: khz_to_mhz(self.hw_min_khz),
+            hw_max_mhz: khz_to_mhz(self.hw_max_khz),
+            min_mhz: khz_to_mhz(self.min_khz),
+            max_mhz: khz_to_mhz(self.max_khz),
+        }
+    }
+
+    /// 设置频率范围 (MHz)，超出硬件范围的值被截断
+    ///
+    /// 新下限高于当前上限时先写上限，否则先写下限，避免内核因中间状态 min > max 拒绝写入
+    pub fn set_limits(&self, min_mhz: u64, max_mhz: u64) -> Result<(), String> {
+        let (min_khz, max_khz) = self.clamp_limits(min_mhz, max_mhz);
+        let min_path = self.path.join("scaling_min_freq");
+        let max_path = self.path.join("scaling_max_freq");
+        if min_khz > self.max_khz {
+            write_khz(&max_path, max_khz)?;
+            write_khz(&min_path, min_khz)
+        } else {
+            write_khz(&min_path, min_khz)?;
+            write_khz(&max_path, max_khz)
+        }
+    }
+
+    /// 切换调速器，写入后读回确认（驱动可能静默拒绝）
+    pub fn set_governor(&self, governor: &str) -> Result<(), String> {
+        if !self.available_governors.iter().any(|g| g == governor) {
+            return Err(format!("{} 不支持调速器 {}", self.name(), governor));
+        }
+        let path = self.path.join("scaling_governor");
+        write_sysfs(&path, governor)?;
+        match read_trimmed(&self.path, "scaling_governor") {
+            Some(current) if current == governor => Ok(()),
+            Some(current) => Err(format!("{} 的调速器仍为 {}，内核未接受 {}", self.name(), current, governor)),
+            None => Err(format!("无法读回 {}", path.display())),
+        }
+    }
+}
+
+/// 核心的有效频率范围 (MHz)：硬件范围 (cpuinfo_*) 和当前的 scaling 限制 (scaling_*)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreqLimits {
+    /// 硬件允许的最低频率
+    pub hw_min_mhz: u64,
+    /// 硬件允许的最高频率
+    pub hw_max_mhz: u64,
+    /// 当前下限 (scaling_min_freq)
+    pub min_mhz: u64,
+    /// 当前上限 (scaling_max_freq)
+    pub max_mhz: u64,
+}
+
+impl FreqLimits {
+    /// 上限低于硬件最高频率：被用户、散热守护进程或固件限制
+    pub fn is_capped(&self) -> bool {
+        self.max_mhz < self.hw_max_mhz
+    }
+}
+
+/// 解析 sysfs 中的频率值 (kHz)
+pub fn parse_khz(s: &str) -> Option<u64> {
+    s.trim().parse().ok()
+}
+
+/// kHz 转 MHz（四舍五入）
+pub fn khz_to_mhz(khz: u64) -> u64 {
+    (khz + 500) / 1000
+}
+
+/// MHz 转 kHz
+pub fn mhz_to_khz(mhz: u64) -> u64 {
+    mhz * 1000
+}
+
+/// 格式化一组频率 (kHz)：全部相同时显示单个值，否则显示范围
+pub fn format_mhz_range(values: impl IntoIterator<Item = u64>) -> String {
+    let mhz: Vec<u64> = values.into_iter().map(khz_to_mhz).collect();
+    match (mhz.iter().min(), mhz.iter().max()) {
+        (Some(min), Some(max)) if min == max => format!("{} MHz", min),
+        (Some(min), Some(max)) => format!("{}-{} MHz", min, max),
+        _ => "-".to_string(),
+    }
+}
+
+/// 解析 affected_cpus（空格分隔的 CPU 编号）
+fn parse_cpu_ids(s: &str) -> Vec<usize> {
+    s.split_whitespace().filter_map(|id| id.parse().ok()).collect()
+}
+
+fn read_khz(dir: &Path, name: &str) -> Option<u64> {
+    parse_khz(&fs::read_to_string(dir.join(name)).ok()?)
+}
+
+fn read_trimmed(dir: &Path, name: &str) -> Option<String> {
+    let value = fs::read_to_string(dir.join(name)).ok()?;
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn write_khz(path: &Path, khz: u64) -> Result<(), String> {
+    write_sysfs(path, &khz.to_string())
+}
+
+fn write_sysfs(path: &Path, value: &str) -> Result<(), String> {
+    fs::write(path, value).map_err(|e| match e.kind() {
+        ErrorKind::PermissionDenied => format!("写入 {} 失败: 权限不足（需要 root 权限）", path.display()),
+        _ => format!("写入 {} 失败: {}", path.display(), e),
+    })
+}
+
+fn read_policy(dir: &Path) -> Option<CpufreqPolicy> {
+    let cpus = parse_cpu_ids(&fs::read_to_string(dir.join("affected_cpus")).ok()?);
+    if cpus.is_empty() {
+        return None;
+    }
+    Some(CpufreqPolicy {
+        path: dir.to_path_buf(),
+        cpus,
+        hw_min_khz: read_khz(dir, "cpuinfo_min_freq")?,
+        hw_max_khz: read_khz(dir, "cpuinfo_max_freq")?,
+        min_khz: read_khz(dir, "scaling_min_freq")?,
+        max_khz: read_khz(dir, "scaling_max_freq")?,
+        governor: read_trimmed(dir, "scaling_governor"),
+        available_governors: read_trimmed(dir, "scaling_available_governors")
+            .map(|s| s.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        epp: read_trimmed(dir, "energy_performance_preference"),
+    })
+}
+
+/// 读取目录下的所有 policy*（按第一个 CPU 排序）
+fn read_policies_in(root: &Path) -> Vec<CpufreqPolicy> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut policies: Vec<CpufreqPolicy> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("policy"))
+        .filter_map(|entry| read_policy(&entry.path()))
+        .collect();
+    policies.sort_by_key(|p| p.cpus[0]);
+    policies
+}
+
+/// 读取所有 cpufreq 策略（没有 cpufreq 驱动时为空）
+pub fn read_cpufreq_policies() -> Vec<CpufreqPolicy> {
+    read_policies_in(Path::new(CPUFREQ_ROOT))
+}
+
+/// 控制指定 CPU 的策略
+pub fn policy_for_cpu(policies: &[CpufreqPolicy], cpu: usize) -> Option<&CpufreqPolicy> {
+    policies.iter().find(|p| p.cpus.contains(&cpu))
+}
+
+/// 各核心调速器不一致时的说明，如 "核心 0-7 为 performance，其余为 schedutil"
+///
+/// 核心数最多的调速器作为"其余"，其他调速器按第一个核心的顺序列出；全部一致或没有调速器时返回 None
+pub fn governor_mismatch(policies: &[CpufreqPolicy]) -> Option<String> {
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for policy in policies {
+        let Some(governor) = policy.governor.as_deref() else {
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == governor) {
+            Some((_, cpus)) => cpus.extend(&policy.cpus),
+            None => groups.push((governor, policy.cpus.clone())),
+        }
+    }
+    if groups.len() < 2 {
+        return None;
+    }
+    for (_, cpus) in &mut groups {
+        cpus.sort_unstable();
+    }
+    groups.sort_by_key(|(_, cpus)| cpus[0]);
+    // 核心数相同时靠后的作为"其余"
+    let majority = groups
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, (_, cpus))| (cpus.len(), *index))
+        .map(|(index, _)| index)?;
+    let mut parts: Vec<String> = groups
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != majority)
+        .map(|(_, (governor, cpus))| format!("核心 {} 为 {}", format_cpu_list(cpus), governor))
+        .collect();
+    parts.push(format!("其余为 {}", groups[majority].0));
+    Some(parts.join("，"))
+}
+
+/// 切换控制 `cpu_id` 的策略的调速器，返回受影响的 CPU（同策略的所有核心）
+pub fn set_core_governor(cpu_id: usize, governor: &str) -> Result<Vec<usize>, String> {
+    set_core_governor_in(Path::new(CPUFREQ_ROOT), cpu_id, governor)
+}
+
+fn set_core_governor_in(root: &Path, cpu_id: usize, governor: &str) -> Result<Vec<usize>, String> {
+    let policies = read_policies_in(root);
+    let policy = policy_for_cpu(&policies, cpu_id).ok_or_else(|| format!("CPU {} 没有 cpufreq 策略", cpu_id))?;
+    policy.set_governor(governor)?;
+    Ok(policy.cpus.clone())
+}
+
+/// 设置控制 `cpu_id` 的策略的频率上限 (MHz)，截断到硬件范围，低于当前下限时一并降低下限
+///
+/// 写入后读回确认，返回实际生效的范围
+pub fn set_scaling_max_freq(cpu_id: usize, max_mhz: u64) -> Result<FreqLimits, String> {
+    set_scaling_freq_in(Path::new(CPUFREQ_ROOT), cpu_id, None, Some(max_mhz))
+}
+
+/// 设置控制 `cpu_id` 的策略的频率下限 (MHz)，截断到硬件范围，高于当前上限时一并提高上限
+///
+/// 写入后读回确认，返回实际生效的范围
+pub fn set_scaling_min_freq(cpu_id: usize, min_mhz: u64) -> Result<FreqLimits, String> {
+    set_scaling_freq_in(Path::new(CPUFREQ_ROOT), cpu_id, Some(min_mhz), None)
+}
+
+fn set_scaling_freq_in(root: &Path, cpu_id: usize, min_mhz: Option<u64>, max_mhz: Option<u64>) -> Result<FreqLimits, String> {
+    let policies = read_policies_in(root);
+    let policy = policy_for_cpu(&policies, cpu_id).ok_or_else(|| format!("CPU {} 没有 cpufreq 策略", cpu_id))?;
+    let current = policy.limits();
+    let (min_mhz, max_mhz) = match (min_mhz, max_mhz) {
+        (Some(min), None) => (min, current.max_mhz.max(min)),
+        (None, Some(max)) => (current.min_mhz.min(max), max),
+        (min, max) => (min.unwrap_or(current.min_mhz), max.unwrap_or(current.max_mhz)),
+    };
+    let expected = policy.clamp_limits(min_mhz, max_mhz);
+    policy.set_limits(min_mhz, max_mhz)?;
+
+    // 散热守护进程、固件 (freq_qos) 等可能让内核不接受或改写请求的值
+    let after = read_policy(&policy.path).ok_or_else(|| format!("无法读回 {}", policy.path.display()))?;
+    if (after.min_khz, after.max_khz) != expected {
+        return Err(format!(
+            "{} 读回 {} - {} MHz，与写入的 {} - {} MHz 不一致（可能被散热守护进程或固件限制）",
+            policy.name(),
+            khz_to_mhz(after.min_khz),
+            khz_to_mhz(after.max_khz),
+            khz_to_mhz(expected.0),
+            khz_to_mhz(expected.1)
+        ));
+    }
+    Ok(after.limits())
+}
+
+/// 把所有策略的最高频率限制为 max_mhz（低于当前下限时一并降低下限），0 个策略时报错
+pub fn limit_max_frequency(policies: &[CpufreqPolicy], max_mhz: u64) -> Result<(), String> {
+    if policies.is_empty() {
+        return Err("当前系统没有 cpufreq 驱动".to_string());
+    }
+    policies.iter().try_for_each(|policy| {
+        let min_mhz = khz_to_mhz(policy.min_khz).min(max_mhz);
+        policy.set_limits(min_mhz, max_mhz)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_units() {
+        assert_eq!(parse_khz("3600000\n"), Some(3_600_000));
+        assert_eq!(parse_khz("<unknown>"), None);
+        assert_eq!(khz_to_mhz(3_600_000), 3600);
+        assert_eq!(khz_to_mhz(2_199_999), 2200);
+        assert_eq!(khz_to_mhz(400_400), 400);
+        assert_eq!(mhz_to_khz(4200), 4_200_000);
+
+        assert_eq!(format_mhz_range([5_200_000, 5_200_000]), "5200 MHz");
+        assert_eq!(format_mhz_range([3_000_000, 5_200_000, 4_000_000]), "3000-5200 MHz");
+        assert_eq!(format_mhz_range([]), "-");
+    }
+
+    fn write_policy(root: &Path, name: &str, cpus: &str, limits: [u64; 4]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("affected_cpus"), cpus).unwrap();
+        for (file, khz) in ["cpuinfo_min_freq", "cpuinfo_max_freq", "scaling_min_freq", "scaling_max_freq"].iter().zip(limits) {
+            fs::write(dir.join(file), format!("{}\n", khz)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_policies() {
+        let root = std::env::temp_dir().join(format!("hexin-cpufreq-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write_policy(&root, "policy2", "2 3\n", [400_000, 5_000_000, 400_000, 5_000_000]);
+        write_policy(&root, "policy0", "0 1\n", [400_000, 5_500_000, 1_000_000, 5_500_000]);
+        fs::create_dir_all(root.join("ondemand")).unwrap();
+
+        let policies = read_policies_in(&root);
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies[0].name(), "policy0");
+        assert_eq!(policies[0].cpus, vec![0, 1]);
+        assert_eq!(policy_for_cpu(&policies, 3).map(|p| p.name()), Some("policy2".to_string()));
+        assert!(policy_for_cpu(&policies, 4).is_none());
+
+        // 超出硬件范围的值被截断，下限不高于上限
+        assert_eq!(policies[0].clamp_limits(100, 9000), (400_000, 5_500_000));
+        assert_eq!(policies[0].clamp_limits(4000, 3000), (3_000_000, 3_000_000));
+
+        // 限制最大频率：低于当前下限时一并降低下限
+        limit_max_frequency(&policies, 800).unwrap();
+        let policies = read_policies_in(&root);
+        assert_eq!((policies[0].min_khz, policies[0].max_khz), (800_000, 800_000));
+        assert_eq!((policies[1].min_khz, policies[1].max_khz), (400_000, 800_000));
+
+        policies[1].set_limits(1200, 4800).unwrap();
+        let policies = read_policies_in(&root);
+        assert_eq!((policies[1].min_khz, policies[1].max_khz), (1_200_000, 4_800_000));
+
+        assert!(limit_max_frequency(&[], 3000).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_governor() {
+        let root = std::env::temp_dir().join(format!("hexin-governor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write_policy(&root, "policy0", "0 1\n", [400_000, 5_000_000, 400_000, 5_000_000]);
+        write_policy(&root, "policy2", "2\n", [400_000, 5_000_000, 400_000, 5_000_000]);
+        let dir = root.join("policy0");
+        fs::write(dir.join("scaling_governor"), "powersave\n").unwrap();
+        fs::write(dir.join("scaling_available_governors"), "performance powersave \n").unwrap();
+
+        fs::write(dir.join("energy_performance_preference"), "balance_power\n").unwrap();
+        let policies = read_policies_in(&root);
+        assert_eq!(policies[0].governor.as_deref(), Some("powersave"));
+        assert_eq!(policies[0].epp.as_deref(), Some("balance_power"));
+        assert_eq!(policies[1].epp, None);
+        assert_eq!(policies[0].available_governors, vec!["performance", "powersave"]);
+        assert_eq!(policies[1].governor, None);
+        assert!(policies[1].available_governors.is_empty());
+
+        // 修改 CPU 1 会同时作用于同策略的 CPU 0
+        assert_eq!(set_core_governor_in(&root, 1, "performance"), Ok(vec![0, 1]));
+        assert_eq!(read_policies_in(&root)[0].governor.as_deref(), Some("performance"));
+        assert!(set_core_governor_in(&root, 0, "schedutil").is_err());
+        assert!(set_core_governor_in(&root, 2, "performance").is_err());
+        assert!(set_core_governor_in(&root, 7, "performance").is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_governor_mismatch() {
+        let policy = |cpus: &[usize], governor: Option<&str>| CpufreqPolicy {
+            path: PathBuf::new(),
+            cpus: cpus.to_vec(),
+            hw_min_khz: 0,
+            hw_max_khz: 0,
+            min_khz: 0,
+            max_khz: 0,
+            governor: governor.map(str::to_string),
+            available_governors: Vec::new(),
+            epp: None,
+        };
+        let performance = [policy(&[0, 1, 2, 3], Some("performance")), policy(&[4, 5, 6, 7], Some("performance"))];
+        assert_eq!(governor_mismatch(&performance), None);
+        assert_eq!(governor_mismatch(&[]), None);
+
+        let mixed = [
+            policy(&[8, 9, 10, 11, 12, 13, 14, 15], Some("schedutil")),
+            policy(&[0, 1, 2, 3], Some("performance")),
+            policy(&[4, 5, 6, 7], Some("performance")),
+            policy(&[16], None),
+        ];
+        assert_eq!(governor_mismatch(&mixed).as_deref(), Some("核心 0-7 为 performance，其余为 schedutil"));
+
+        let three = [
+            policy(&[0], Some("performance")),
+            policy(&[1], Some("powersave")),
+            policy(&[2, 3], Some("schedutil")),
+        ];
+        assert_eq!(
+            governor_mismatch(&three).as_deref(),
+            Some("核心 0 为 performance，核心 1 为 powersave，其余为 schedutil")
+        );
+        // 核心数相同时靠后的调速器作为"其余"
+        let tie = [policy(&[0], Some("performance")), policy(&[1], Some("powersave"))];
+        assert_eq!(governor_mismatch(&tie).as_deref(), Some("核心 0 为 performance，其余为 powersave"));
+    }
+
+    #[test]
+    fn test_scaling_limits() {
+        let root = std::env::temp_dir().join(format!("hexin-scaling-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write_policy(&root, "policy0", "0 1\n", [400_000, 5_000_000, 1_000_000, 3_000_000]);
+
+        let limits = read_policies_in(&root)[0].limits();
+        assert_eq!(limits, FreqLimits { hw_min_mhz: 400, hw_max_mhz: 5000, min_mhz: 1000, max_mhz: 3000 });
+        assert!(limits.is_capped());
+
+        // 上限低于当前下限时下限随之降低；超出硬件范围的值被截断
+        let limits = set_scaling_freq_in(&root, 1, None, Some(800)).unwrap();
+        assert_eq!((limits.min_mhz, limits.max_mhz), (800, 800));
+        let limits = set_scaling_freq_in(&root, 0, None, Some(9000)).unwrap();
+        assert_eq!((limits.min_mhz, limits.max_mhz), (800, 5000));
+        assert!(!limits.is_capped());
+        let limits = set_scaling_freq_in(&root, 0, Some(100), None).unwrap();
+        assert_eq!((limits.min_mhz, limits.max_mhz), (400, 5000));
+        set_scaling_freq_in(&root, 0, None, Some(3000)).unwrap();
+        let limits = set_scaling_freq_in(&root, 0, Some(4000), None).unwrap();
+        assert_eq!((limits.min_mhz, limits.max_mhz), (4000, 4000));
+        assert!(set_scaling_freq_in(&root, 4, Some(1000), None).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! CPU 空闲状态 (C-state) 驻留时间统计
+//! 读取 /sys/devices/system/cpu/cpuN/cpuidle/stateK/{name,time}，按两次采样之差计算驻留比例
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::utils::rate::CounterRate;
+
+/// 不显示 C-state 分布时的采样间隔（只为保持计数器基准不过期）
+const HIDDEN_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 读取单个 CPU 各 C-state 的累计驻留时间 (名称, 微秒)
+pub fn read_cstate_times(cpu_id: usize) -> Vec<(String, u64)> {
+    read_cstate_times_in(Path::new(&format!("/sys/devices/system/cpu/cpu{}/cpuidle", cpu_id)))
+}
+
+/// 读取 cpuidle 目录下各 stateK 的累计驻留时间，遇到第一个缺失的状态时停止
+fn read_cstate_times_in(base: &Path) -> Vec<(String, u64)> {
+    let mut states = Vec::new();
+    for index in 0.. {
+        let dir = base.join(format!("state{}", index));
+        let (Ok(name), Ok(time)) = (
+            fs::read_to_string(dir.join("name")),
+            fs::read_to_string(dir.join("time")),
+        ) else {
+            break;
+        };
+        if let Ok(time) = time.trim().parse() {
+            states.push((name.trim().to_string(), time));
+        }
+    }
+    states
+}
+
+/// 是否为真正的睡眠状态（POLL 是忙等轮询，不计入空闲）
+pub fn is_sleep_state(name: &str) -> bool {
+    name != "POLL"
+}
+
+/// 是否为浅睡眠 (C1 / C1E / C1_ACPI)，其余睡眠状态视为深睡眠（注意 C10 不是浅睡眠）
+pub fn is_light_sleep(name: &str) -> bool {
+    name.strip_prefix("C1")
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// 把 C-state 分布拆分为 (浅睡眠 %, 深睡眠 %)
+pub fn split_idle_distribution(distribution: &HashMap<String, f32>) -> (f32, f32) {
+    distribution
+        .iter()
+        .filter(|(name, _)| is_sleep_state(name))
+        .fold((0.0, 0.0), |(light, deep), (name, pct)| {
+            if is_light_sleep(name) {
+                (light + pct, deep)
+            } else {
+                (light, deep + pct)
+            }
+        })
+}
+
+/// C-state 驻留比例跟踪器
+#[derive(Debug, Clone, Default)]
+pub struct CStateTracker {
+    /// 每个 CPU、每个状态的速率计算
+    rates: HashMap<(usize, String), CounterRate>,
+    /// 创建时间（用于速率时间戳）
+    start_time: Option<Instant>,
+    /// 上一次采样的时间
+    last_sample: Option<Instant>,
+}
+
+impl CStateTracker {
+    /// 本次刷新是否需要采样：显示 C-state 分布时每次都采样，不显示时降到每 5 秒一次
+    pub fn is_due(&mut self, visible: bool, now: Instant) -> bool {
+        let due = visible || self.last_sample.is_none_or(|at| now.duration_since(at) >= HIDDEN_SAMPLE_INTERVAL);
+        if due {
+            self.last_sample = Some(now);
+        }
+        due
+    }
+
+    /// 采样一个 CPU，返回各 C-state 的驻留比例 (名称, %)；首次采样或不支持 cpuidle 时为空
+    pub fn sample(&mut self, cpu_id: usize) -> Vec<(String, f32)> {
+        let start = *self.start_time.get_or_insert_with(Instant::now);
+        let timestamp = start.elapsed().as_secs_f64();
+
+        read_cstate_times(cpu_id)
+            .into_iter()
+            .filter_map(|(name, time_us)| {
+                let rate = self.rates.entry((cpu_id, name.clone())).or_default();
+                // 每秒驻留的微秒数 -> 百分比
+                let pct = rate.update(time_us, timestamp)? / 10_000.0;
+                Some((name, (pct as f32).clamp(0.0, 100.0)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_idle_distribution() {
+        let distribution: HashMap<String, f32> = [
+            ("POLL".to_string(), 0.5),
+            ("C1".to_string(), 10.0),
+            ("C1E".to_string(), 5.0),
+            ("C6".to_string(), 60.0),
+            ("C10".to_string(), 4.0),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(split_idle_distribution(&distribution), (15.0, 64.0));
+        assert_eq!(split_idle_distribution(&HashMap::new()), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_light_sleep() {
+        assert!(is_light_sleep("C1"));
+        assert!(is_light_sleep("C1E"));
+        assert!(is_light_sleep("C1_ACPI"));
+        assert!(!is_light_sleep("C10"));
+        assert!(!is_light_sleep("C6"));
+        assert!(!is_light_sleep("POLL"));
+        assert!(!is_sleep_state("POLL"));
+        assert!(is_sleep_state("C1"));
+    }
+
+    #[test]
+    fn test_read_cstate_times() {
+        let base = std::env::temp_dir().join(format!("hexin-cpuidle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        for (index, name, time) in [(0, "POLL", "120\n"), (1, "C1_ACPI", "5000\n"), (2, "C2_ACPI", "n/a\n"), (4, "C3", "1\n")] {
+            let dir = base.join(format!("state{}", index));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("name"), format!("{}\n", name)).unwrap();
+            fs::write(dir.join("time"), time).unwrap();
+        }
+
+        // 无法解析的时间跳过，编号不连续时在缺口处停止
+        assert_eq!(
+            read_cstate_times_in(&base),
+            vec![("POLL".to_string(), 120), ("C1_ACPI".to_string(), 5000)]
+        );
+        assert!(read_cstate_times_in(&base.join("missing")).is_empty());
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_hidden_sampling_is_throttled() {
+        let mut tracker = CStateTracker::default();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(tracker.is_due(false, at(0)));
+        assert!(!tracker.is_due(false, at(1)));
+        assert!(!tracker.is_due(false, at(4)));
+        assert!(tracker.is_due(false, at(5)));
+        // 显示时每次刷新都采样
+        assert!(tracker.is_due(true, at(6)));
+        assert!(tracker.is_due(true, at(6)));
+        assert!(!tracker.is_due(false, at(7)));
+    }
+}
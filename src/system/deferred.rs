@@ -0,0 +1,60 @@
+//! 一次性延后动作 - 指定名称的进程下一次出现时应用一次预设，触发或到期后自动删除
+//! 与自动规则不同：只生效一次，并且有过期时间；与规则一起保存在 rules.toml
+
+use serde::{Deserialize, Serialize};
+
+use super::{wildcard_match, SchedulePreset};
+
+/// 默认有效期（小时）
+pub const DEFAULT_DEFERRED_EXPIRY_HOURS: u64 = 48;
+
+/// 一次性延后动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeferredAction {
+    /// 编号（由规则引擎分配，用于删除）
+    pub id: u64,
+    /// 进程名匹配模式（不区分大小写，支持 * 通配符）
+    pub pattern: String,
+    /// 要应用的调度设置
+    pub preset: SchedulePreset,
+    /// 创建时间 (Unix 秒)
+    pub created_at: u64,
+    /// 过期时间 (Unix 秒)
+    pub expires_at: u64,
+}
+
+impl DeferredAction {
+    /// 创建在 `hours` 小时后过期的动作（编号在加入规则引擎时分配）
+    pub fn new(pattern: &str, preset: SchedulePreset, now: u64, hours: u64) -> Self {
+        Self {
+            id: 0,
+            pattern: pattern.trim().to_string(),
+            preset,
+            created_at: now,
+            expires_at: now.saturating_add(hours.saturating_mul(3600)),
+        }
+    }
+
+    /// 进程名是否匹配
+    pub fn matches(&self, process_name: &str) -> bool {
+        wildcard_match(&self.pattern.to_lowercase(), &process_name.to_lowercase())
+    }
+
+    /// 在 `now` 时是否已过期
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// 剩余有效时间的描述，如 "1 天 3 小时"、"25 分钟"
+    pub fn remaining(&self, now: u64) -> String {
+        let secs = self.expires_at.saturating_sub(now);
+        let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+        if days > 0 {
+            format!("{} 天 {} 小时", days, hours)
+        } else if hours > 0 {
+            format!("{} 小时 {} 分钟", hours, minutes)
+        } else {
+            format!("{} 分钟", minutes.max(1))
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! 进程调度详情的异步加载
+//! 在工作线程中读取较慢的 /proc 信息，通过通道把结果送回 UI
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{
+    get_io_priority, get_oom_score_adj, get_process_affinity, get_rt_priority, get_scheduler_info,
+    read_process_args, read_process_limits, IoPriority, ProcessLimits, SchedulePolicy,
+};
+
+/// 已加载详情的刷新间隔
+const DETAILS_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 进程的完整调度详情
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSchedDetails {
+    /// 调度策略
+    pub policy: SchedulePolicy,
+    /// nice 值
+    pub nice: i32,
+    /// 实时优先级
+    pub rt_priority: Option<i32>,
+    /// CPU 亲和性
+    pub affinity: Vec<usize>,
+    /// I/O 优先级
+    pub io_priority: Option<IoPriority>,
+    /// OOM 分数调整值
+    pub oom_score_adj: Option<i32>,
+    /// cgroup 路径
+    pub cgroup: Option<String>,
+    /// 命令行参数（内核线程为空）
+    pub args: Vec<String>,
+    /// RLIMIT_NICE / RLIMIT_RTPRIO / RLIMIT_NOFILE
+    pub limits: Option<ProcessLimits>,
+    /// 打开的文件描述符数，读取失败时为错误说明（其他用户的进程为"无权限"）
+    pub open_fds: Result<usize, String>,
+}
+
+impl ProcessSchedDetails {
+    /// 读取进程详情，进程不存在时返回 None
+    pub fn read(pid: u32, logical_cores: usize) -> Option<Self> {
+        if !Path::new(&format!("/proc/{}", pid)).exists() {
+            return None;
+        }
+        let pid_i32 = pid as i32;
+        let (policy, nice, _) = get_scheduler_info(pid_i32);
+        let details = ProcessSchedDetails {
+            policy,
+            nice,
+            rt_priority: get_rt_priority(pid_i32),
+            affinity: get_process_affinity(pid_i32, logical_cores),
+            io_priority: get_io_priority(pid_i32).ok(),
+            oom_score_adj: get_oom_score_adj(pid_i32).ok(),
+            cgroup: read_process_cgroup(pid),
+            args: read_process_args(pid),
+            limits: read_process_limits(pid),
+            open_fds: count_open_fds(pid),
+        };
+        // 读取过程中进程退出
+        if !Path::new(&format!("/proc/{}", pid)).exists() {
+            return None;
+        }
+        Some(details)
+    }
+}
+
+/// 解析 /proc/[pid]/cgroup，优先返回 cgroup v2 统一层级的路径
+pub fn parse_cgroup(content: &str) -> Option<String> {
+    let mut fallback = None;
+    for line in content.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (Some(id), Some(controllers), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if id == "0" && controllers.is_empty() {
+            return Some(path.to_string());
+        }
+        if fallback.is_none() {
+            fallback = Some(path.to_string());
+        }
+    }
+    fallback
+}
+
+/// 读取进程的 cgroup 路径
+pub fn read_process_cgroup(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/cgroup", pid))
+        .ok()
+        .and_then(|s| parse_cgroup(&s))
+}
+
+/// 统计进程打开的文件描述符数（/proc/[pid]/fd 的条目数）
+pub fn count_open_fds(pid: u32) -> Result<usize, String> {
+    match fs::read_dir(format!("/proc/{}/fd", pid)) {
+        Ok(entries) => Ok(entries.count()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Err("无权限".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 详情加载状态
+pub enum DetailsState<'a> {
+    /// 加载中
+    Loading,
+    /// 已加载
+    Loaded(&'a ProcessSchedDetails),
+    /// 进程已退出
+    Exited,
+}
+
+/// 进程详情异步加载器（同一时间只跟踪一个进程）
+pub struct DetailsLoader {
+    tx: Sender<(u32, Option<ProcessSchedDetails>)>,
+    rx: Receiver<(u32, Option<ProcessSchedDetails>)>,
+    /// 逻辑核心数
+    logical_cores: usize,
+    /// 当前目标 PID
+    target: Option<u32>,
+    /// 是否有正在进行的读取
+    in_flight: bool,
+    /// 最近一次结果和完成时间
+    result: Option<(Option<ProcessSchedDetails>, Instant)>,
+}
+
+impl DetailsLoader {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            logical_cores: 0,
+            target: None,
+            in_flight: false,
+            result: None,
+        }
+    }
+
+    /// 请求加载进程详情；切换进程时丢弃旧结果，已加载的结果定期后台刷新
+    pub fn request(&mut self, pid: u32, logical_cores: usize) {
+        self.poll();
+        self.logical_cores = logical_cores;
+
+        if self.target != Some(pid) {
+            self.target = Some(pid);
+            self.result = None;
+            self.spawn(pid);
+        } else if !self.in_flight
+            && self.result.as_ref().is_some_and(|(_, at)| at.elapsed() >= DETAILS_REFRESH_INTERVAL)
+        {
+            self.spawn(pid);
+        }
+    }
+
+    /// 强制重新加载
+    pub fn reload(&mut self) {
+        if let Some(pid) = self.target {
+            if !self.in_flight {
+                self.spawn(pid);
+            }
+        }
+    }
+
+    /// 接收工作线程的结果（忽略已切换掉的进程）
+    pub fn poll(&mut self) {
+        while let Ok((pid, details)) = self.rx.try_recv() {
+            if Some(pid) == self.target {
+                self.in_flight = false;
+                self.result = Some((details, Instant::now()));
+            }
+        }
+    }
+
+    /// 获取指定进程的加载状态
+    pub fn state(&self, pid: u32) -> DetailsState<'_> {
+        if self.target != Some(pid) {
+            return DetailsState::Loading;
+        }
+        match &self.result {
+            Some((Some(details), _)) => DetailsState::Loaded(details),
+            Some((None, _)) => DetailsState::Exited,
+            None => DetailsState::Loading,
+        }
+    }
+
+    fn spawn(&mut self, pid: u32) {
+        self.in_flight = true;
+        let tx = self.tx.clone();
+        let logical_cores = self.logical_cores;
+        thread::spawn(move || {
+            let details = ProcessSchedDetails::read(pid, logical_cores);
+            // 接收端可能已销毁，忽略发送失败
+            let _ = tx.send((pid, details));
+        });
+    }
+}
+
+impl Default for DetailsLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cgroup() {
+        let v2 = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-firefox.scope\n";
+        assert_eq!(
+            parse_cgroup(v2).as_deref(),
+            Some("/user.slice/user-1000.slice/user@1000.service/app.slice/app-firefox.scope")
+        );
+
+        let hybrid = "12:cpuset:/docker/abc\n0::/system.slice/docker-abc.scope\n";
+        assert_eq!(parse_cgroup(hybrid).as_deref(), Some("/system.slice/docker-abc.scope"));
+
+        let v1 = "4:cpu,cpuacct:/system.slice/sshd.service\n";
+        assert_eq!(parse_cgroup(v1).as_deref(), Some("/system.slice/sshd.service"));
+
+        assert_eq!(parse_cgroup(""), None);
+    }
+
+    #[test]
+    fn test_count_open_fds() {
+        // 测试进程至少打开了标准输入输出
+        assert!(count_open_fds(std::process::id()).unwrap() > 0);
+        assert!(count_open_fds(u32::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn test_details_loader_exited_process() {
+        let mut loader = DetailsLoader::new();
+        // PID 上限之外的进程不存在
+        let pid = u32::MAX - 1;
+        loader.request(pid, 1);
+        for _ in 0..200 {
+            loader.poll();
+            if !matches!(loader.state(pid), DetailsState::Loading) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(matches!(loader.state(pid), DetailsState::Exited));
+    }
+}
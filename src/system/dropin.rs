@@ -0,0 +1,269 @@
+//! profiles.d 目录 - 发行版或社区仓库提供的单进程调度档案
+//! 每个 ~/.config/hexin/profiles.d/*.toml 文件描述一条规则，每次刷新按修改时间检查变化
+//!
+//! ```toml
+//! pattern = "factorio*"
+//! policy = "Other"        # 可选，默认 Other
+//! nice = -5               # 可选
+//! rt_priority = 50        # 实时策略的优先级
+//! affinity = "vcache"     # 可选：vcache / frequency / all / package:N / CPU 列表如 "0-7,16-23"
+//! io_priority = { class = "BestEffort", level = 0 }
+//! ```
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{AffinityTarget, AutoRule, CoreTopology, IoPriority, RuleMatchMode, SchedulePolicy, SchedulePreset};
+
+/// profiles.d 规则名称前缀（规则名称为 "profiles.d/<文件名>"）
+pub const DROPIN_RULE_PREFIX: &str = "profiles.d/";
+
+/// 单个档案文件的格式
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DropInProfile {
+    /// 进程名匹配模式
+    pattern: String,
+    /// 说明
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_policy")]
+    policy: SchedulePolicy,
+    /// nice 值（非实时策略）
+    #[serde(default)]
+    nice: i32,
+    /// 实时优先级（FIFO / RR）
+    #[serde(default)]
+    rt_priority: Option<i32>,
+    /// 亲和性目标
+    #[serde(default)]
+    affinity: Option<String>,
+    /// 符号亲和性目标是否包含设置中的保留核心
+    #[serde(default)]
+    include_reserved: bool,
+    #[serde(default)]
+    io_priority: Option<IoPriority>,
+    /// 进程出现多少秒后再应用
+    #[serde(default)]
+    delay_secs: u64,
+}
+
+fn default_policy() -> SchedulePolicy {
+    SchedulePolicy::Other
+}
+
+/// 解析一个档案文件为自动规则，id 为文件名（不含扩展名）
+pub fn parse_dropin(id: &str, content: &str, topology: &CoreTopology) -> Result<AutoRule, String> {
+    let profile: DropInProfile = toml::from_str(content).map_err(|e| e.to_string())?;
+    if profile.pattern.trim().is_empty() {
+        return Err("pattern 不能为空".to_string());
+    }
+    if profile.policy.is_realtime() && profile.rt_priority.is_none() {
+        return Err("实时策略需要设置 rt_priority".to_string());
+    }
+    let affinity_cores = match &profile.affinity {
+        Some(target) if profile.include_reserved => AffinityTarget::parse(target)?.resolve(&topology.including_reserved())?,
+        Some(target) => AffinityTarget::parse(target)?.resolve(topology)?,
+        None => None,
+    };
+    let name = format!("{}{}", DROPIN_RULE_PREFIX, id);
+
+    Ok(AutoRule {
+        name: name.clone(),
+        pattern: profile.pattern,
+        preset: SchedulePreset {
+            name,
+            description: profile.description,
+            policy: profile.policy,
+            priority: profile.rt_priority.filter(|_| profile.policy.is_realtime()).unwrap_or(profile.nice),
+            affinity_cores,
+            io_priority: profile.io_priority,
+            oom_score_adj: None,
+            is_builtin: false,
+        },
+        enabled: true,
+        delay_secs: profile.delay_secs,
+        match_mode: RuleMatchMode::EveryInstance,
+        max_instances: None,
+    })
+}
+
+/// 一次加载的结果
+#[derive(Debug, Default)]
+pub struct DropInLoad {
+    /// 解析成功的规则（按文件名排序）
+    pub rules: Vec<AutoRule>,
+    /// 跳过的文件及原因
+    pub warnings: Vec<String>,
+}
+
+/// profiles.d 目录监视器：文件增删或修改时重新加载
+#[derive(Debug)]
+pub struct DropInWatcher {
+    dir: PathBuf,
+    /// 上次加载时各文件的 (修改时间, 大小)
+    stamps: BTreeMap<PathBuf, (Option<SystemTime>, u64)>,
+    /// 是否已加载过
+    loaded: bool,
+}
+
+impl DropInWatcher {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            stamps: BTreeMap::new(),
+            loaded: false,
+        }
+    }
+
+    /// 默认目录 ~/.config/hexin/profiles.d/
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("profiles.d"))
+    }
+
+    /// 目录中的档案文件及其时间戳
+    fn scan(&self) -> BTreeMap<PathBuf, (Option<SystemTime>, u64)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return BTreeMap::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                Some((path, (metadata.modified().ok(), metadata.len())))
+            })
+            .collect()
+    }
+
+    /// 检查目录变化（每次刷新调用），有变化时返回重新加载的结果
+    pub fn poll(&mut self, topology: &CoreTopology) -> Option<DropInLoad> {
+        let stamps = self.scan();
+        if self.loaded && stamps == self.stamps {
+            return None;
+        }
+        self.loaded = true;
+        let load = load_files(stamps.keys(), topology);
+        self.stamps = stamps;
+        Some(load)
+    }
+
+    /// 下次检查时强制重新加载（拓扑或保留核心变化后调用）
+    pub fn invalidate(&mut self) {
+        self.loaded = false;
+    }
+}
+
+/// 加载给定的档案文件，无法解析的文件跳过并记录原因
+fn load_files<'a>(paths: impl Iterator<Item = &'a PathBuf>, topology: &CoreTopology) -> DropInLoad {
+    let mut load = DropInLoad::default();
+    for path in paths {
+        let id = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let result = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_dropin(&id, &content, topology));
+        match result {
+            Ok(rule) => load.rules.push(rule),
+            Err(e) => load.warnings.push(format!("跳过 {}: {}", display_name(path), e)),
+        }
+    }
+    load
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::IoClass;
+
+    const FACTORIO: &str = r#"
+pattern = "factorio*"
+nice = -5
+affinity = "vcache"
+io_priority = { class = "BestEffort", level = 0 }
+"#;
+
+    #[test]
+    fn test_parse_dropin() {
+        let rule = parse_dropin("factorio", FACTORIO, &CoreTopology::single_package(8, &[0, 1, 2, 3])).unwrap();
+        assert_eq!(rule.name, "profiles.d/factorio");
+        assert!(rule.matches("factorio"));
+        assert_eq!(rule.preset.policy, SchedulePolicy::Other);
+        assert_eq!(rule.preset.priority, -5);
+        assert_eq!(rule.preset.affinity_cores, Some(vec![0, 1, 2, 3]));
+        assert_eq!(rule.preset.io_priority, Some(IoPriority { class: IoClass::BestEffort, level: 0 }));
+
+        // 没有 V-Cache 的 CPU 上不修改亲和性
+        let rule = parse_dropin("factorio", FACTORIO, &CoreTopology::single_package(8, &[])).unwrap();
+        assert_eq!(rule.preset.affinity_cores, None);
+
+        let topology = CoreTopology::single_package(4, &[0, 1]);
+        let rt = parse_dropin("audio", "pattern = \"jackd\"\npolicy = \"Fifo\"\nrt_priority = 70\naffinity = \"frequency\"\n", &topology)
+            .unwrap();
+        assert_eq!(rt.preset.priority, 70);
+        assert_eq!(rt.preset.affinity_cores, Some(vec![2, 3]));
+
+        // 保留核心默认被排除，include_reserved 可显式包含
+        let reserved = CoreTopology::single_package(8, &[0, 1, 2, 3]).with_reserved(&[0]);
+        let rule = parse_dropin("factorio", FACTORIO, &reserved).unwrap();
+        assert_eq!(rule.preset.affinity_cores, Some(vec![1, 2, 3]));
+        let rule = parse_dropin("factorio", &format!("{}include_reserved = true\n", FACTORIO), &reserved).unwrap();
+        assert_eq!(rule.preset.affinity_cores, Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_parse_dropin_errors() {
+        let topology = CoreTopology::single_package(8, &[]);
+        assert!(parse_dropin("x", "pattern = ", &topology).is_err());
+        assert!(parse_dropin("x", "pattern = \"\"", &topology).is_err());
+        assert!(parse_dropin("x", "pattern = \"a\"\npolicy = \"Fifo\"", &topology).is_err());
+        assert!(parse_dropin("x", "pattern = \"a\"\naffinity = \"0-15\"", &topology).is_err());
+        assert!(parse_dropin("x", "pattern = \"a\"\naffinity = \"fast\"", &topology).is_err());
+        // 拼写错误的字段不会被静默忽略
+        assert!(parse_dropin("x", "pattern = \"a\"\nnicee = 5", &topology).is_err());
+    }
+
+    #[test]
+    fn test_watcher_hot_reload() {
+        let dir = std::env::temp_dir().join(format!("hexin_profiles_d_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("factorio.toml"), FACTORIO).unwrap();
+        fs::write(dir.join("broken.toml"), "pattern = [").unwrap();
+        fs::write(dir.join("README.md"), "不是档案").unwrap();
+
+        let topology = CoreTopology::single_package(4, &[0, 1]);
+        let mut watcher = DropInWatcher::new(dir.clone());
+        let load = watcher.poll(&topology).unwrap();
+        assert_eq!(load.rules.len(), 1);
+        assert_eq!(load.warnings.len(), 1);
+        assert!(load.warnings[0].starts_with("跳过 broken.toml"));
+
+        // 没有变化时不重新加载
+        assert!(watcher.poll(&topology).is_none());
+
+        // 修复文件后重新加载
+        fs::write(dir.join("broken.toml"), "pattern = \"blender\"\nnice = 10\n").unwrap();
+        let load = watcher.poll(&topology).unwrap();
+        assert!(load.warnings.is_empty());
+        assert_eq!(
+            load.rules.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            vec!["profiles.d/broken", "profiles.d/factorio"]
+        );
+
+        // 删除文件
+        fs::remove_file(dir.join("factorio.toml")).unwrap();
+        assert_eq!(watcher.poll(&topology).unwrap().rules.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+        // 目录不存在时为空
+        assert!(watcher.poll(&topology).unwrap().rules.is_empty());
+    }
+}
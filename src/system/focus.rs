@@ -0,0 +1,382 @@
+//! 前台加速 - 焦点窗口所属进程临时应用"前台"预设，失去焦点后恢复原值
+//! 焦点跟踪需要 `focus-x11` feature（X11 / XWayland 窗口）
+
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use super::{
+    apply_changes, preset_changes, read_process_start_time, rollback, ApplySource, SchedChange, SchedulePreset,
+    SessionJournal,
+};
+
+/// 默认使用的预设名称
+pub const FOREGROUND_PRESET: &str = "前台";
+
+fn default_preset() -> String {
+    FOREGROUND_PRESET.to_string()
+}
+
+/// 前台加速配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusBoostConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 应用到焦点进程的预设名称
+    #[serde(default = "default_preset")]
+    pub preset: String,
+}
+
+impl Default for FocusBoostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            preset: default_preset(),
+        }
+    }
+}
+
+/// 一个被加速的进程及其原始设置
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoostedProcess {
+    /// 进程 ID
+    pub pid: u32,
+    /// 进程启动时间（用于识别 PID 复用）
+    pub start_time: Option<u64>,
+    /// 加速前的原始值
+    pub previous: Vec<SchedChange>,
+}
+
+impl BoostedProcess {
+    /// 当前启动时间为 `current` 的进程是否仍是被加速的那个进程
+    pub fn is_same_process(&self, current: Option<u64>) -> bool {
+        match current {
+            Some(start_time) => self.start_time.is_none() || self.start_time == Some(start_time),
+            None => false,
+        }
+    }
+}
+
+/// 加速记录：同一时间只有焦点进程处于加速状态
+#[derive(Debug, Default)]
+pub struct BoostLedger {
+    boosted: Option<BoostedProcess>,
+}
+
+impl BoostLedger {
+    pub fn new() -> Self {
+        Self { boosted: None }
+    }
+
+    /// 当前被加速的进程
+    pub fn boosted_pid(&self) -> Option<u32> {
+        self.boosted.as_ref().map(|b| b.pid)
+    }
+
+    /// 焦点切换到 `pid`：返回需要恢复的上一个前台进程（焦点未变化时为 None）
+    pub fn focus_changed(&mut self, pid: Option<u32>) -> Option<BoostedProcess> {
+        if pid.is_some() && pid == self.boosted_pid() {
+            return None;
+        }
+        self.boosted.take()
+    }
+
+    /// 记录加速成功的进程
+    pub fn record(&mut self, boosted: BoostedProcess) {
+        self.boosted = Some(boosted);
+    }
+
+    /// 取出所有需要恢复的进程（退出或关闭功能时）
+    pub fn take_all(&mut self) -> Vec<BoostedProcess> {
+        self.boosted.take().into_iter().collect()
+    }
+}
+
+/// 前台加速控制器（在刷新循环中轮询焦点变化）
+pub struct FocusBooster {
+    /// 焦点跟踪线程发来的焦点进程 PID
+    receiver: Option<Receiver<Option<u32>>>,
+    ledger: BoostLedger,
+    /// 最近的焦点窗口所属进程（hexin 自身获得焦点时为 None）
+    focused: Option<u32>,
+    /// 焦点跟踪无法启动的原因（重新启用前不再重试）
+    tracker_error: Option<String>,
+    /// 最近的错误
+    message: Option<String>,
+}
+
+impl Default for FocusBooster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusBooster {
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            ledger: BoostLedger::new(),
+            focused: None,
+            tracker_error: None,
+            message: None,
+        }
+    }
+
+    /// 当前被加速的进程
+    pub fn boosted_pid(&self) -> Option<u32> {
+        self.ledger.boosted_pid()
+    }
+
+    /// 最近的焦点窗口所属进程（仅在前台加速启用或全局快捷键需要时跟踪）
+    pub fn focused_pid(&self) -> Option<u32> {
+        self.focused
+    }
+
+    /// 焦点跟踪无法启动的原因
+    pub fn tracker_error(&self) -> Option<&str> {
+        self.tracker_error.as_deref()
+    }
+
+    /// 最近的错误
+    pub fn message(&self) -> Option<&str> {
+        self.tracker_error.as_deref().or(self.message.as_deref())
+    }
+
+    /// 处理焦点变化（在刷新循环中调用）；`track_focus` 为 true 时即使前台加速关闭也跟踪焦点
+    pub fn poll(
+        &mut self,
+        config: &FocusBoostConfig,
+        track_focus: bool,
+        presets: &[SchedulePreset],
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        if !config.enabled {
+            // 关闭时恢复仍在加速的进程，不需要焦点时停止跟踪
+            self.restore_all();
+            if !track_focus {
+                self.receiver = None;
+                self.tracker_error = None;
+                self.focused = None;
+                return;
+            }
+        }
+        if self.tracker_error.is_some() {
+            return;
+        }
+        if self.receiver.is_none() {
+            match spawn_focus_tracker() {
+                Ok(receiver) => self.receiver = Some(receiver),
+                Err(e) => {
+                    self.tracker_error = Some(e);
+                    return;
+                }
+            }
+        }
+
+        // 只处理最新的焦点
+        let mut focused = None;
+        if let Some(receiver) = &self.receiver {
+            loop {
+                match receiver.try_recv() {
+                    Ok(pid) => focused = Some(pid),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.receiver = None;
+                        self.tracker_error = Some("焦点跟踪已停止".to_string());
+                        break;
+                    }
+                }
+            }
+        }
+        let Some(pid) = focused else {
+            return;
+        };
+        // hexin 自身获得焦点时不加速
+        let pid = pid.filter(|&pid| pid != std::process::id());
+        self.focused = pid;
+        if !config.enabled {
+            return;
+        }
+
+        if let Some(previous) = self.ledger.focus_changed(pid) {
+            self.restore(previous);
+        }
+        let Some(pid) = pid.filter(|&pid| Some(pid) != self.ledger.boosted_pid()) else {
+            return;
+        };
+        let Some(preset) = presets.iter().find(|p| p.name == config.preset) else {
+            self.message = Some(format!("找不到预设 '{}'", config.preset));
+            return;
+        };
+
+        let start_time = read_process_start_time(pid);
+        let changes = preset_changes(preset);
+        let report = apply_changes(pid as i32, &changes, logical_cores);
+        session.record_application(pid, &changes, &report, ApplySource::FocusBoost);
+        if report.is_success() {
+            self.message = None;
+            self.ledger.record(BoostedProcess {
+                pid,
+                start_time,
+                previous: report.previous,
+            });
+        } else {
+            self.message = Some(format!("加速进程 {} 失败: {}", pid, report.summary()));
+        }
+    }
+
+    /// 恢复所有被加速的进程（退出 hexin 时调用）
+    pub fn restore_all(&mut self) {
+        for boosted in self.ledger.take_all() {
+            self.restore(boosted);
+        }
+    }
+
+    fn restore(&mut self, boosted: BoostedProcess) {
+        // 进程已退出或 PID 被复用时无需恢复
+        if !boosted.is_same_process(read_process_start_time(boosted.pid)) {
+            return;
+        }
+        let (_, errors) = rollback(boosted.pid as i32, &boosted.previous);
+        if !errors.is_empty() {
+            self.message = Some(format!("恢复进程 {} 失败: {}", boosted.pid, errors.join("; ")));
+        }
+    }
+}
+
+/// 启动焦点跟踪线程，返回焦点窗口所属进程 PID 的接收端（None 表示没有焦点窗口）
+#[cfg(feature = "focus-x11")]
+pub fn spawn_focus_tracker() -> Result<Receiver<Option<u32>>, String> {
+    x11::spawn()
+}
+
+#[cfg(not(feature = "focus-x11"))]
+pub fn spawn_focus_tracker() -> Result<Receiver<Option<u32>>, String> {
+    Err("此版本编译时未启用焦点跟踪 (focus-x11 feature)".to_string())
+}
+
+/// 通过 EWMH 的 _NET_ACTIVE_WINDOW 和 _NET_WM_PID 跟踪焦点
+/// Wayland 的 foreign-toplevel 协议不提供窗口 PID，Wayland 下只能跟踪 XWayland 窗口
+#[cfg(feature = "focus-x11")]
+mod x11 {
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window};
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+
+    pub fn spawn() -> Result<Receiver<Option<u32>>, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("连接 X11 显示服务失败: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+        let active_atom = intern(&conn, b"_NET_ACTIVE_WINDOW")?;
+        let pid_atom = intern(&conn, b"_NET_WM_PID")?;
+        conn.change_window_attributes(root, &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE))
+            .map_err(|e| e.to_string())?;
+        conn.flush().map_err(|e| e.to_string())?;
+
+        let (sender, receiver) = channel();
+        std::thread::Builder::new()
+            .name("hexin-focus".to_string())
+            .spawn(move || {
+                if let Err(e) = track(&conn, root, active_atom, pid_atom, &sender) {
+                    tracing::warn!("焦点跟踪结束: {}", e);
+                }
+            })
+            .map_err(|e| format!("无法启动焦点跟踪线程: {}", e))?;
+        Ok(receiver)
+    }
+
+    fn intern(conn: &RustConnection, name: &[u8]) -> Result<Atom, String> {
+        conn.intern_atom(false, name)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map(|reply| reply.atom)
+            .map_err(|e| e.to_string())
+    }
+
+    /// 读取窗口的 32 位属性
+    fn property(conn: &RustConnection, window: Window, atom: Atom, kind: AtomEnum) -> Option<u32> {
+        let reply = conn.get_property(false, window, atom, kind, 0, 1).ok()?.reply().ok()?;
+        let value = reply.value32()?.next()?;
+        Some(value)
+    }
+
+    fn focused_pid(conn: &RustConnection, root: Window, active_atom: Atom, pid_atom: Atom) -> Option<u32> {
+        let window = property(conn, root, active_atom, AtomEnum::WINDOW).filter(|&w| w != 0)?;
+        property(conn, window, pid_atom, AtomEnum::CARDINAL).filter(|&pid| pid != 0)
+    }
+
+    /// 每次焦点变化发送一次 PID，接收端关闭时结束
+    fn track(
+        conn: &RustConnection,
+        root: Window,
+        active_atom: Atom,
+        pid_atom: Atom,
+        sender: &Sender<Option<u32>>,
+    ) -> Result<(), String> {
+        let mut last = None;
+        loop {
+            let pid = focused_pid(conn, root, active_atom, pid_atom);
+            if last != Some(pid) {
+                if sender.send(pid).is_err() {
+                    return Ok(());
+                }
+                last = Some(pid);
+            }
+            // 等待根窗口的 _NET_ACTIVE_WINDOW 变化
+            loop {
+                match conn.wait_for_event().map_err(|e| e.to_string())? {
+                    Event::PropertyNotify(event) if event.atom == active_atom => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boosted(pid: u32) -> BoostedProcess {
+        BoostedProcess {
+            pid,
+            start_time: Some(1000 + pid as u64),
+            previous: vec![SchedChange::Nice(0)],
+        }
+    }
+
+    #[test]
+    fn test_boost_ledger() {
+        let mut ledger = BoostLedger::new();
+        assert_eq!(ledger.focus_changed(Some(10)), None);
+        ledger.record(boosted(10));
+
+        // 同一进程再次获得焦点时不恢复
+        assert_eq!(ledger.focus_changed(Some(10)), None);
+        assert_eq!(ledger.boosted_pid(), Some(10));
+
+        // 焦点切换到其他进程时恢复上一个
+        assert_eq!(ledger.focus_changed(Some(20)), Some(boosted(10)));
+        assert_eq!(ledger.boosted_pid(), None);
+        ledger.record(boosted(20));
+
+        // 没有焦点窗口时也恢复
+        assert_eq!(ledger.focus_changed(None), Some(boosted(20)));
+        assert_eq!(ledger.focus_changed(None), None);
+
+        // 退出时取出所有被加速的进程
+        ledger.record(boosted(30));
+        assert_eq!(ledger.take_all(), vec![boosted(30)]);
+        assert!(ledger.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_boosted_same_process() {
+        let process = boosted(10);
+        assert!(process.is_same_process(Some(1010)));
+        // PID 被复用或进程已退出
+        assert!(!process.is_same_process(Some(2000)));
+        assert!(!process.is_same_process(None));
+    }
+}
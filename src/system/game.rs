@@ -0,0 +1,165 @@
+//! 游戏识别 - 用于在亲和性排除 V-Cache 核心时给出提示
+//! 游戏列表保存在 ~/.config/hexin/games.toml，可手动编辑
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::wildcard_match;
+
+fn default_warn() -> bool {
+    true
+}
+
+/// 默认的游戏可执行文件模式（不区分大小写，支持 * 通配符）
+fn default_executables() -> Vec<String> {
+    [
+        "*.exe",
+        "cs2",
+        "dota2",
+        "hl2_linux",
+        "factorio",
+        "eldenring*",
+        "witcher3*",
+        "cyberpunk2077*",
+        "bg3*",
+        "minecraft*",
+        "rimworld*",
+        "stellaris*",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// 游戏列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameList {
+    /// 亲和性排除 V-Cache 核心时是否提示
+    #[serde(default = "default_warn")]
+    pub warn_vcache: bool,
+    /// 游戏可执行文件模式
+    #[serde(default = "default_executables")]
+    pub executables: Vec<String>,
+}
+
+impl Default for GameList {
+    fn default() -> Self {
+        Self {
+            warn_vcache: default_warn(),
+            executables: default_executables(),
+        }
+    }
+}
+
+impl GameList {
+    /// 获取游戏列表文件路径
+    fn games_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("games.toml"))
+    }
+
+    /// 加载游戏列表，文件不存在时写入默认列表方便用户编辑
+    pub fn load() -> Self {
+        let Some(path) = Self::games_path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_default(),
+            Err(_) => {
+                let list = Self::default();
+                list.save();
+                list
+            }
+        }
+    }
+
+    /// 保存游戏列表
+    pub fn save(&self) {
+        if let Some(path) = Self::games_path() {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(content) = toml::to_string_pretty(self) {
+                let _ = fs::write(&path, content);
+            }
+        }
+    }
+
+    /// 可执行文件名是否在游戏列表中
+    pub fn matches(&self, executable: &str) -> bool {
+        let executable = executable.to_lowercase();
+        self.executables
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), &executable))
+    }
+
+    /// 进程看起来是否是游戏：可执行文件在列表中，或由 Steam 启动
+    pub fn is_game(&self, pid: u32) -> bool {
+        let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+        let exe = fs::read(format!("/proc/{}/cmdline", pid))
+            .ok()
+            .and_then(|cmdline| parse_cmdline_executable(&cmdline));
+        self.matches(comm.trim())
+            || exe.is_some_and(|exe| self.matches(&exe))
+            || fs::read(format!("/proc/{}/environ", pid)).is_ok_and(|env| has_steam_game_id(&env))
+    }
+}
+
+/// 从 /proc/[pid]/cmdline 中取出第一个参数的文件名（兼容 Wine 的 Windows 路径）
+pub fn parse_cmdline_executable(cmdline: &[u8]) -> Option<String> {
+    let argv0 = cmdline.split(|&b| b == 0).next()?;
+    let argv0 = String::from_utf8_lossy(argv0);
+    let name = argv0.rsplit(['/', '\\']).next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// /proc/[pid]/environ 中是否有 Steam 为游戏设置的 SteamGameId
+pub fn has_steam_game_id(environ: &[u8]) -> bool {
+    environ
+        .split(|&b| b == 0)
+        .any(|var| var.starts_with(b"SteamGameId=") && var.len() > b"SteamGameId=".len())
+}
+
+/// 亲和性是否完全排除了 V-Cache 核心（没有 V-Cache 时为 false）
+pub fn excludes_vcache(affinity: &[usize], vcache_cores: &[usize]) -> bool {
+    !vcache_cores.is_empty() && !affinity.iter().any(|core| vcache_cores.contains(core))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_list_matches() {
+        let list = GameList::default();
+        assert!(list.matches("Game.EXE"));
+        assert!(list.matches("cs2"));
+        assert!(list.matches("Minecraft Launcher"));
+        assert!(!list.matches("firefox"));
+    }
+
+    #[test]
+    fn test_parse_cmdline_executable() {
+        assert_eq!(parse_cmdline_executable(b"/usr/bin/cs2\0-novid\0"), Some("cs2".to_string()));
+        assert_eq!(
+            parse_cmdline_executable(b"C:\\Games\\Foo\\Foo.exe\0"),
+            Some("Foo.exe".to_string())
+        );
+        assert_eq!(parse_cmdline_executable(b""), None);
+    }
+
+    #[test]
+    fn test_has_steam_game_id() {
+        assert!(has_steam_game_id(b"HOME=/home/a\0SteamGameId=570\0"));
+        assert!(!has_steam_game_id(b"HOME=/home/a\0SteamGameId=\0"));
+        assert!(!has_steam_game_id(b"HOME=/home/a\0"));
+    }
+
+    #[test]
+    fn test_excludes_vcache() {
+        let vcache = [0, 1, 2, 3];
+        assert!(excludes_vcache(&[4, 5], &vcache));
+        assert!(!excludes_vcache(&[3, 4], &vcache));
+        assert!(!excludes_vcache(&[4, 5], &[]));
+    }
+}
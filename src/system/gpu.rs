@@ -0,0 +1,389 @@
+//! 进程级 GPU 使用率监控
+//! NVIDIA 通过 nvidia-smi 查询，AMD 通过 sysfs + /proc/[pid]/fdinfo 的 DRM 统计计算
+//! 采样较慢（启动外部命令或遍历所有进程的 fdinfo），在后台线程进行
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::utils::rate::CounterRate;
+
+/// amdgpu 驱动在 sysfs 中的目录
+const AMDGPU_DRIVER_PATH: &str = "/sys/bus/pci/drivers/amdgpu";
+/// 后台采样间隔
+const GPU_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// GPU 监控后端
+pub trait GpuMonitor: Send {
+    /// 后端名称
+    fn name(&self) -> &'static str;
+
+    /// 采样各进程的 GPU 使用率 (PID -> %)
+    fn sample(&mut self) -> HashMap<u32, f32>;
+}
+
+/// 解析 nvidia-smi --query-compute-apps=pid,used_memory --format=csv,noheader,nounits 的输出
+/// 返回 PID -> 显存 (MiB)，显存为 [N/A]（如 WDDM / 部分虚拟化环境）时记为 0
+pub fn parse_nvidia_compute_apps(output: &str) -> HashMap<u32, u64> {
+    let mut memory = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.split(',').map(str::trim);
+        let Some(Ok(pid)) = fields.next().map(str::parse::<u32>) else {
+            continue;
+        };
+        let used = fields.next().and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+        // 同一进程可能在多块 GPU 上运行
+        *memory.entry(pid).or_insert(0) += used;
+    }
+    memory
+}
+
+/// 解析 nvidia-smi pmon -c 1 -s u 的输出，返回 PID -> SM 利用率 (%)
+/// 列按表头定位（新驱动增加了 jpg / ofa 列）；"-" 表示本次采样中没有活动，记为 0
+pub fn parse_nvidia_pmon(output: &str) -> HashMap<u32, f32> {
+    let mut usage = HashMap::new();
+    let mut columns: Option<(usize, usize)> = None;
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix('#') {
+            let names: Vec<&str> = header.split_whitespace().collect();
+            if let (Some(pid), Some(sm)) = (names.iter().position(|n| *n == "pid"), names.iter().position(|n| *n == "sm")) {
+                columns = Some((pid, sm));
+            }
+            continue;
+        }
+        let Some((pid_column, sm_column)) = columns else {
+            continue;
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // 空闲 GPU 的行 PID 为 "-"
+        let Some(Ok(pid)) = fields.get(pid_column).map(|f| f.parse::<u32>()) else {
+            continue;
+        };
+        let sm = fields.get(sm_column).and_then(|f| f.parse::<f32>().ok()).unwrap_or(0.0);
+        *usage.entry(pid).or_insert(0.0) += sm;
+    }
+    usage
+}
+
+/// 运行 nvidia-smi 并返回标准输出（失败时为 None）
+fn run_nvidia_smi(args: &[&str]) -> Option<String> {
+    let output = Command::new("nvidia-smi").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// NVIDIA GPU 监控 (依赖 nvidia-smi)
+pub struct NvidiaGpuMonitor;
+
+impl NvidiaGpuMonitor {
+    /// nvidia-smi 可用时创建
+    pub fn detect() -> Option<Self> {
+        let status = Command::new("nvidia-smi").arg("-L").output().ok()?.status;
+        status.success().then_some(NvidiaGpuMonitor)
+    }
+}
+
+impl GpuMonitor for NvidiaGpuMonitor {
+    fn name(&self) -> &'static str {
+        "NVIDIA"
+    }
+
+    fn sample(&mut self) -> HashMap<u32, f32> {
+        // 利用率来自 pmon；占用显存的计算进程即使本次采样没有活动也显示为 0%
+        let mut usage = run_nvidia_smi(&["pmon", "-c", "1", "-s", "u"])
+            .map(|output| parse_nvidia_pmon(&output))
+            .unwrap_or_default();
+        if let Some(output) = run_nvidia_smi(&["--query-compute-apps=pid,used_memory", "--format=csv,noheader,nounits"]) {
+            for pid in parse_nvidia_compute_apps(&output).into_keys() {
+                usage.entry(pid).or_insert(0.0);
+            }
+        }
+        usage
+    }
+}
+
+/// 从 DRM fdinfo 中解析 (设备 PCI 地址, 客户端 ID, gfx 引擎累计时间 ns)
+pub fn parse_drm_fdinfo(content: &str) -> Option<(String, u64, u64)> {
+    let mut pdev = None;
+    let mut client_id = None;
+    let mut gfx_ns = None;
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "drm-pdev" => pdev = Some(value.to_string()),
+            "drm-client-id" => client_id = value.parse().ok(),
+            "drm-engine-gfx" => gfx_ns = value.trim_end_matches("ns").trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some((pdev?, client_id?, gfx_ns?))
+}
+
+/// AMD GPU 监控 (amdgpu 驱动)
+pub struct AmdGpuMonitor {
+    /// amdgpu 设备的 PCI 地址
+    devices: HashSet<String>,
+    /// 每个进程 gfx 引擎时间的速率计算
+    rates: HashMap<u32, CounterRate>,
+    /// 创建时间（用于速率时间戳）
+    start_time: Instant,
+}
+
+impl AmdGpuMonitor {
+    /// 存在带显存信息的 amdgpu 设备时创建
+    pub fn detect() -> Option<Self> {
+        let devices: HashSet<String> = fs::read_dir(AMDGPU_DRIVER_PATH)
+            .ok()?
+            .flatten()
+            .filter(|entry| entry.path().join("mem_info_vram_total").exists())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        (!devices.is_empty()).then(|| AmdGpuMonitor {
+            devices,
+            rates: HashMap::new(),
+            start_time: Instant::now(),
+        })
+    }
+
+    /// 汇总进程在 amdgpu 设备上的 gfx 引擎累计时间（按客户端去重）
+    fn read_process_gfx_ns(&self, pid: u32) -> Option<u64> {
+        let entries = fs::read_dir(format!("/proc/{}/fdinfo", pid)).ok()?;
+        let mut clients = HashMap::new();
+        for entry in entries.flatten() {
+            let Ok(content) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if let Some((pdev, client_id, gfx_ns)) = parse_drm_fdinfo(&content) {
+                if self.devices.contains(&pdev) {
+                    clients.insert((pdev, client_id), gfx_ns);
+                }
+            }
+        }
+        (!clients.is_empty()).then(|| clients.values().sum())
+    }
+}
+
+impl GpuMonitor for AmdGpuMonitor {
+    fn name(&self) -> &'static str {
+        "AMD"
+    }
+
+    fn sample(&mut self) -> HashMap<u32, f32> {
+        let timestamp = self.start_time.elapsed().as_secs_f64();
+        let pids: Vec<u32> = fs::read_dir("/proc")
+            .map(|dir| {
+                dir.flatten()
+                    .filter_map(|e| e.file_name().to_str()?.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut usage = HashMap::new();
+        let mut seen = HashSet::new();
+        for pid in pids {
+            let Some(gfx_ns) = self.read_process_gfx_ns(pid) else {
+                continue;
+            };
+            seen.insert(pid);
+            let rate = self.rates.entry(pid).or_default();
+            if let Some(ns_per_sec) = rate.update(gfx_ns, timestamp) {
+                usage.insert(pid, (ns_per_sec / 1e7).min(100.0) as f32);
+            }
+        }
+        // 丢弃已不使用 GPU 的进程
+        self.rates.retain(|pid, _| seen.contains(pid));
+        usage
+    }
+}
+
+/// 在后台线程检测后端并定期采样 GPU 使用率，界面线程只读取最近一次结果；丢弃时线程在下一次采样前退出
+pub struct GpuSampler {
+    /// 后端名称（检测完成前为 None）
+    name: Option<&'static str>,
+    /// 后台检测结果：后端名称，无可用 GPU 时为 None（取回后为 None）
+    detected: Option<Receiver<Option<&'static str>>>,
+    latest: Arc<Mutex<HashMap<u32, f32>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl GpuSampler {
+    /// 启动采样线程：检测（可能要启动 nvidia-smi）也在线程中进行，结果通过通道报告
+    pub fn spawn(detect: impl FnOnce() -> Option<Box<dyn GpuMonitor>> + Send + 'static) -> Self {
+        let latest = Arc::new(Mutex::new(HashMap::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let (thread_latest, thread_stop) = (Arc::clone(&latest), Arc::clone(&stop));
+        thread::spawn(move || {
+            let Some(mut monitor) = detect() else {
+                let _ = tx.send(None);
+                return;
+            };
+            let _ = tx.send(Some(monitor.name()));
+            while !thread_stop.load(Ordering::Relaxed) {
+                let usage = monitor.sample();
+                if let Ok(mut latest) = thread_latest.lock() {
+                    *latest = usage;
+                }
+                thread::sleep(GPU_SAMPLE_INTERVAL);
+            }
+        });
+        Self { name: None, detected: Some(rx), latest, stop }
+    }
+
+    /// 取回后台检测结果，没有可用的 GPU 后端时返回 false
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = &self.detected else {
+            return true;
+        };
+        match rx.try_recv() {
+            Ok(Some(name)) => {
+                self.name = Some(name);
+                self.detected = None;
+                true
+            }
+            Ok(None) | Err(TryRecvError::Disconnected) => false,
+            Err(TryRecvError::Empty) => true,
+        }
+    }
+
+    /// 后端名称（检测完成前为 None）
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// 对最近一次采样结果执行操作（避免逐进程加锁）
+    pub fn with_latest<R>(&self, f: impl FnOnce(&HashMap<u32, f32>) -> R) -> Option<R> {
+        self.latest.lock().ok().map(|latest| f(&latest))
+    }
+}
+
+impl Drop for GpuSampler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 检测可用的 GPU 监控后端
+pub fn detect_gpu_monitor() -> Option<Box<dyn GpuMonitor>> {
+    if let Some(monitor) = NvidiaGpuMonitor::detect() {
+        return Some(Box::new(monitor));
+    }
+    AmdGpuMonitor::detect().map(|m| Box::new(m) as Box<dyn GpuMonitor>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_compute_apps() {
+        // nvidia-smi --query-compute-apps=pid,used_memory --format=csv,noheader,nounits
+        let output = "\
+2817, 1536
+4410, [N/A]
+2817, 512
+";
+        let memory = parse_nvidia_compute_apps(output);
+        assert_eq!(memory.get(&2817), Some(&2048));
+        assert_eq!(memory.get(&4410), Some(&0));
+        assert!(parse_nvidia_compute_apps("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_nvidia_pmon() {
+        // 535 驱动的 nvidia-smi pmon -c 1 -s u（含 jpg / ofa 列）
+        let output = "\
+# gpu         pid   type     sm    mem    enc    dec    jpg    ofa    command 
+# Idx           #    C/G      %      %      %      %      %      %    name 
+    0       1873     G      7      2      -      -      -      -    Xorg           
+    0       2817     C     63     21      -      -      -      -    python3        
+    0       3302   C+G      -      -      -      -      -      -    firefox        
+    1          -     -      -      -      -      -      -      -    -              
+";
+        let usage = parse_nvidia_pmon(output);
+        assert_eq!(usage.get(&1873), Some(&7.0));
+        assert_eq!(usage.get(&2817), Some(&63.0));
+        assert_eq!(usage.get(&3302), Some(&0.0));
+        assert_eq!(usage.len(), 3);
+
+        // 旧驱动没有 jpg / ofa 列
+        let old = "\
+# gpu        pid  type    sm   mem   enc   dec   command
+# Idx          #   C/G     %     %     %     %   name
+    0       1873     G    12     3     -     -   Xorg
+";
+        assert_eq!(parse_nvidia_pmon(old).get(&1873), Some(&12.0));
+        assert!(parse_nvidia_pmon("").is_empty());
+    }
+
+    struct FakeMonitor;
+
+    impl GpuMonitor for FakeMonitor {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn sample(&mut self) -> HashMap<u32, f32> {
+            HashMap::from([(42, 50.0)])
+        }
+    }
+
+    /// 轮询直到后台检测完成，返回最后一次 poll 的结果
+    fn settle(sampler: &mut GpuSampler) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while sampler.detected.is_some() && Instant::now() < deadline {
+            if !sampler.poll() {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        true
+    }
+
+    #[test]
+    fn test_sampler_detection() {
+        // 检测在后台线程进行，完成前没有后端名称
+        let mut sampler = GpuSampler::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            Some(Box::new(FakeMonitor) as Box<dyn GpuMonitor>)
+        });
+        assert_eq!(sampler.name(), None);
+        assert!(settle(&mut sampler));
+        assert_eq!(sampler.name(), Some("fake"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while sampler.with_latest(|usage| usage.is_empty()) == Some(true) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(sampler.with_latest(|usage| usage.get(&42).copied()), Some(Some(50.0)));
+
+        // 没有可用的后端
+        let mut sampler = GpuSampler::spawn(|| None);
+        assert!(!settle(&mut sampler));
+        assert_eq!(sampler.name(), None);
+    }
+
+    #[test]
+    fn test_parse_drm_fdinfo() {
+        let content = "\
+pos:\t0
+flags:\t02100002
+drm-driver:\tamdgpu
+drm-pdev:\t0000:03:00.0
+drm-client-id:\t42
+drm-engine-gfx:\t123456789 ns
+drm-engine-compute:\t0 ns
+";
+        assert_eq!(
+            parse_drm_fdinfo(content),
+            Some(("0000:03:00.0".to_string(), 42, 123456789))
+        );
+        assert_eq!(parse_drm_fdinfo("pos:\t0\nflags:\t02\n"), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! 调度健康评分 - 把 CPU 压力、核心负载不均、僵尸进程和运行延迟合成一个 0-100 的分数
+//! 满分 100，每项信号按固定权重扣分并设上限，各项上限之和为 100；读不到的信号不扣分也不列出
+
+use super::{CpuInfo, ProcessManager, Psi};
+
+/// CPU 压力：PSI some avg10 每 1% 扣 1 分，最多 40 分（25% 为"严重"，扣 25 分）
+const PRESSURE_WEIGHT: f32 = 1.0;
+const PRESSURE_MAX_PENALTY: f32 = 40.0;
+/// 核心负载不均：各核心使用率标准差每 1% 扣 0.3 分，最多 15 分
+/// 单线程负载本身就会造成不均，所以权重较低
+const IMBALANCE_WEIGHT: f32 = 0.3;
+const IMBALANCE_MAX_PENALTY: f32 = 15.0;
+/// 僵尸进程：每个扣 2 分，最多 10 分
+const ZOMBIE_WEIGHT: f32 = 2.0;
+const ZOMBIE_MAX_PENALTY: f32 = 10.0;
+/// 运行延迟：全系统每个 CPU 每秒有任务在运行队列中等待的毫秒数，每 1 ms/s 扣 0.035 分，一直在等待 (1000 ms/s) 时扣满 35 分
+const RUN_DELAY_WEIGHT: f32 = 0.035;
+const RUN_DELAY_MAX_PENALTY: f32 = 35.0;
+
+/// 计算评分用的信号
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthSignals {
+    /// PSI some avg10 (%)，内核不支持时为 None
+    pub pressure: Option<f32>,
+    /// 各核心使用率 (%)
+    pub core_usage: Vec<f32>,
+    /// 僵尸进程数
+    pub zombies: usize,
+    /// 全系统运行延迟 (每核每秒等待毫秒数)，内核未开启调度统计时为 None
+    pub run_delay_ms: Option<f32>,
+}
+
+/// 总体标准差（少于两个值时为 0）
+fn std_dev(values: &[f32]) -> f32 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    variance.sqrt()
+}
+
+/// 由信号计算评分，返回分数和各项扣分（说明，扣分）
+pub fn health_from_signals(signals: &HealthSignals) -> (u8, Vec<(String, f32)>) {
+    let penalty = |value: f32, weight: f32, max: f32| (value.max(0.0) * weight).min(max);
+    let mut factors = Vec::new();
+    if let Some(pressure) = signals.pressure {
+        factors.push((
+            format!("CPU 压力 (PSI 10 秒 {:.1}%)", pressure),
+            penalty(pressure, PRESSURE_WEIGHT, PRESSURE_MAX_PENALTY),
+        ));
+    }
+    if !signals.core_usage.is_empty() {
+        let deviation = std_dev(&signals.core_usage);
+        factors.push((
+            format!("核心负载不均 (标准差 {:.1}%)", deviation),
+            penalty(deviation, IMBALANCE_WEIGHT, IMBALANCE_MAX_PENALTY),
+        ));
+    }
+    factors.push((
+        format!("僵尸进程 ({} 个)", signals.zombies),
+        penalty(signals.zombies as f32, ZOMBIE_WEIGHT, ZOMBIE_MAX_PENALTY),
+    ));
+    if let Some(delay) = signals.run_delay_ms {
+        factors.push((
+            format!("运行队列等待 (每核 {:.1} ms/s)", delay),
+            penalty(delay, RUN_DELAY_WEIGHT, RUN_DELAY_MAX_PENALTY),
+        ));
+    }
+    let total: f32 = factors.iter().map(|(_, p)| p).sum();
+    let score = (100.0 - total).round().clamp(0.0, 100.0) as u8;
+    (score, factors)
+}
+
+/// 当前系统的调度健康评分，返回分数和各项扣分（说明，扣分）
+pub fn scheduling_health(
+    cpu_info: &CpuInfo,
+    process_manager: &ProcessManager,
+    psi: Option<Psi>,
+    run_delay_ms: Option<f32>,
+) -> (u8, Vec<(String, f32)>) {
+    health_from_signals(&HealthSignals {
+        pressure: psi.map(|p| p.some.avg10),
+        core_usage: cpu_info.cores.iter().map(|c| c.usage_percent).collect(),
+        zombies: process_manager.processes().filter(|p| p.status == "Zombie").count(),
+        run_delay_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+    use crate::system::PsiLine;
+
+    #[test]
+    fn test_health_from_signals() {
+        // 空闲且均衡的系统满分
+        let idle = HealthSignals {
+            pressure: Some(0.0),
+            core_usage: vec![5.0, 5.0, 5.0, 5.0],
+            zombies: 0,
+            run_delay_ms: None,
+        };
+        let (score, factors) = health_from_signals(&idle);
+        assert_eq!(score, 100);
+        assert_eq!(factors.len(), 3);
+        assert!(factors.iter().all(|(_, p)| *p == 0.0));
+
+        // 压力 20%、标准差 50%（一半核心满载）、3 个僵尸进程、延迟 200 ms/s
+        let busy = HealthSignals {
+            pressure: Some(20.0),
+            core_usage: vec![100.0, 0.0, 100.0, 0.0],
+            zombies: 3,
+            run_delay_ms: Some(200.0),
+        };
+        let (score, factors) = health_from_signals(&busy);
+        let penalties: Vec<f32> = factors.iter().map(|(_, p)| *p).collect();
+        assert_eq!(penalties, vec![20.0, 15.0, 6.0, 7.0]);
+        assert_eq!(score, 52);
+
+        // 各项按上限扣分，总分不低于 0
+        let worst = HealthSignals {
+            pressure: Some(100.0),
+            core_usage: vec![100.0, 0.0],
+            zombies: 50,
+            run_delay_ms: Some(5000.0),
+        };
+        assert_eq!(health_from_signals(&worst).0, 0);
+
+        // 读不到的信号不列出
+        let (score, factors) = health_from_signals(&HealthSignals::default());
+        assert_eq!(score, 100);
+        assert_eq!(factors.len(), 1);
+    }
+
+    #[test]
+    fn test_scheduling_health() {
+        let mut manager = ProcessManager::new(1);
+        let mut zombie = process(10, "defunct", 0.0, 0);
+        zombie.status = "Zombie".to_string();
+        manager.insert_process(zombie);
+        manager.insert_process(process(11, "bash", 0.0, 0));
+
+        let psi = Psi { some: PsiLine { avg10: 4.5, ..Default::default() }, full: None };
+        let (score, factors) = scheduling_health(&CpuInfo::empty(), &manager, Some(psi), None);
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[1].1, 2.0);
+        assert_eq!(score, 94);
+
+        // 全系统运行延迟与选中哪个进程无关
+        let (score, factors) = scheduling_health(&CpuInfo::empty(), &manager, None, Some(200.0));
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[1].1, 7.0);
+        assert_eq!(score, 91);
+    }
+}
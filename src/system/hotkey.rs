@@ -0,0 +1,440 @@
+//! 快捷键 - 把按键组合绑定到操作（应用预设 / 规则、切换标签页、暂停刷新、跳转到搜索）
+//! 应用内快捷键由界面把 egui 按键事件转换为 `KeyChord` 后查表；
+//! 全局快捷键在 hexin 窗口不在前台时也生效，需要 `global-hotkey` feature（X11 XGrabKey）
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+
+/// 按键组合，如 Ctrl+Shift+G；按键名称与 egui 的 `Key::name` 相同（字母为大写）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: String,
+}
+
+impl KeyChord {
+    /// 解析 "Ctrl+Alt+Shift+G" 形式的文本（修饰键不区分大小写、顺序不限）
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or("未设置按键")?;
+        let mut chord = Self { ctrl: false, alt: false, shift: false, key: normalize_key(key) };
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return Err(format!("无法识别的修饰键: {}", modifier)),
+            }
+        }
+        Ok(chord)
+    }
+
+    /// 是否带有 Ctrl 或 Alt（只有 Shift 的组合在输入框中会输入文字）
+    pub fn has_command_modifier(&self) -> bool {
+        self.ctrl || self.alt
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pressed, name) in [(self.ctrl, "Ctrl+"), (self.alt, "Alt+"), (self.shift, "Shift+")] {
+            if pressed {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+/// 单个字母统一为大写
+fn normalize_key(key: &str) -> String {
+    if key.chars().count() == 1 {
+        key.to_uppercase()
+    } else {
+        key.to_string()
+    }
+}
+
+/// 快捷键触发的操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    /// 对选中的进程应用预设（全局快捷键作用于焦点窗口所属进程）
+    ApplyPreset(String),
+    /// 把自动规则应用到已在运行的匹配进程
+    ApplyRule(String),
+    /// 切换到标签页（--tab 使用的名称）
+    SwitchTab(String),
+    /// 暂停 / 继续数据刷新
+    TogglePause,
+    /// 跳转到进程搜索框
+    FocusSearch,
+}
+
+/// 一个快捷键绑定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    /// 按键组合文本（见 `KeyChord::parse`），为空表示尚未设置
+    pub keys: String,
+    /// 触发的操作
+    pub action: HotkeyAction,
+    /// 注册为全局快捷键
+    #[serde(default)]
+    pub global: bool,
+}
+
+impl Default for HotkeyBinding {
+    fn default() -> Self {
+        Self {
+            keys: String::new(),
+            action: HotkeyAction::TogglePause,
+            global: false,
+        }
+    }
+}
+
+impl HotkeyBinding {
+    /// 解析后的按键组合
+    pub fn chord(&self) -> Option<KeyChord> {
+        KeyChord::parse(&self.keys).ok()
+    }
+}
+
+/// 每个绑定与之冲突的第一个其他绑定（按键组合相同），没有冲突时为 None
+pub fn hotkey_conflicts(bindings: &[HotkeyBinding]) -> Vec<Option<usize>> {
+    let chords: Vec<Option<KeyChord>> = bindings.iter().map(HotkeyBinding::chord).collect();
+    chords
+        .iter()
+        .enumerate()
+        .map(|(index, chord)| {
+            let chord = chord.as_ref()?;
+            chords.iter().enumerate().position(|(other, c)| other != index && c.as_ref() == Some(chord))
+        })
+        .collect()
+}
+
+/// 查找按键组合对应的操作（冲突时第一个绑定生效）
+pub fn dispatch_hotkey<'a>(bindings: &'a [HotkeyBinding], chord: &KeyChord) -> Option<&'a HotkeyAction> {
+    bindings
+        .iter()
+        .find(|binding| binding.chord().as_ref() == Some(chord))
+        .map(|binding| &binding.action)
+}
+
+/// 需要注册为全局快捷键的按键组合（去重）
+pub fn global_chords(bindings: &[HotkeyBinding]) -> Vec<KeyChord> {
+    let mut chords: Vec<KeyChord> = Vec::new();
+    for chord in bindings.iter().filter(|b| b.global).filter_map(HotkeyBinding::chord) {
+        if !chords.contains(&chord) {
+            chords.push(chord);
+        }
+    }
+    chords
+}
+
+/// 全局快捷键线程的通道（注册按键组合的发送端, 按下的按键组合或注册错误的接收端）
+pub type HotkeyChannel = (Sender<Vec<KeyChord>>, Receiver<Result<KeyChord, String>>);
+
+/// 全局快捷键：按绑定注册，绑定变化时重新注册
+#[derive(Debug, Default)]
+pub struct GlobalHotkeys {
+    /// 发送要注册的按键组合 / 接收按下的按键组合
+    channel: Option<HotkeyChannel>,
+    /// 已注册的按键组合
+    registered: Vec<KeyChord>,
+    /// 无法注册的原因（线程无法启动时，绑定变化前不再重试）
+    error: Option<String>,
+}
+
+impl GlobalHotkeys {
+    /// 无法注册的原因
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// 同步注册的按键组合并返回期间按下的组合（在刷新循环中调用）；`wake` 在按键时调用（用于唤醒隐藏的窗口）
+    pub fn poll(&mut self, bindings: &[HotkeyBinding], wake: impl Fn() + Send + 'static) -> Vec<KeyChord> {
+        let chords = global_chords(bindings);
+        if chords.is_empty() {
+            // 关闭发送端后线程取消注册并退出
+            self.channel = None;
+            self.registered.clear();
+            self.error = None;
+            return Vec::new();
+        }
+        if chords != self.registered {
+            self.error = None;
+        }
+        if self.channel.is_none() {
+            if self.error.is_some() {
+                return Vec::new();
+            }
+            match spawn_global_hotkeys(wake) {
+                Ok(channel) => self.channel = Some(channel),
+                Err(e) => {
+                    self.error = Some(e);
+                    self.registered = chords;
+                    return Vec::new();
+                }
+            }
+        }
+        let Some((sender, receiver)) = &self.channel else {
+            return Vec::new();
+        };
+        if chords != self.registered {
+            let _ = sender.send(chords.clone());
+            self.registered = chords;
+        }
+
+        let mut pressed = Vec::new();
+        loop {
+            match receiver.try_recv() {
+                Ok(Ok(chord)) => pressed.push(chord),
+                Ok(Err(e)) => self.error = Some(e),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.channel = None;
+                    self.error = Some("全局快捷键已停止".to_string());
+                    break;
+                }
+            }
+        }
+        pressed
+    }
+}
+
+/// 启动全局快捷键线程
+#[cfg(feature = "global-hotkey")]
+pub fn spawn_global_hotkeys(wake: impl Fn() + Send + 'static) -> Result<HotkeyChannel, String> {
+    x11::spawn(Box::new(wake))
+}
+
+#[cfg(not(feature = "global-hotkey"))]
+pub fn spawn_global_hotkeys(_wake: impl Fn() + Send + 'static) -> Result<HotkeyChannel, String> {
+    Err("此版本编译时未启用全局快捷键 (global-hotkey feature)".to_string())
+}
+
+/// 通过 XGrabKey 在根窗口上抓取按键（X11 / XWayland 会话）
+#[cfg(feature = "global-hotkey")]
+mod x11 {
+    use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt, GrabMode, Keycode, Keysym, ModMask, Window};
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+
+    use super::{HotkeyChannel, KeyChord};
+
+    /// 没有事件时的轮询间隔（同时检查注册变化）
+    const IDLE_POLL: Duration = Duration::from_millis(50);
+    /// CapsLock / NumLock 的各种状态都要抓取，否则开启 NumLock 时快捷键失效
+    const LOCK_MASKS: [u16; 4] = [0, 1 << 1, 1 << 4, (1 << 1) | (1 << 4)];
+
+    /// 一个已抓取的按键（键码, Ctrl/Alt/Shift 掩码, 按键组合）
+    type Grab = (Keycode, u16, KeyChord);
+
+    pub fn spawn(wake: Box<dyn Fn() + Send>) -> Result<HotkeyChannel, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("连接 X11 显示服务失败: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+        let (chord_sender, chord_receiver) = channel();
+        let (event_sender, event_receiver) = channel();
+        thread::Builder::new()
+            .name("hexin-hotkey".to_string())
+            .spawn(move || run(&conn, root, &chord_receiver, &event_sender, &*wake))
+            .map_err(|e| format!("启动全局快捷键线程失败: {}", e))?;
+        Ok((chord_sender, event_receiver))
+    }
+
+    fn run(
+        conn: &RustConnection,
+        root: Window,
+        chords: &Receiver<Vec<KeyChord>>,
+        events: &Sender<Result<KeyChord, String>>,
+        wake: &dyn Fn(),
+    ) {
+        let mut grabs: Vec<Grab> = Vec::new();
+        loop {
+            match chords.try_recv() {
+                Ok(chords) => {
+                    ungrab(conn, root, &grabs);
+                    grabs = chords.into_iter().filter_map(|chord| grab(conn, root, chord, events)).collect();
+                    let _ = conn.flush();
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    ungrab(conn, root, &grabs);
+                    let _ = conn.flush();
+                    return;
+                }
+            }
+            match conn.poll_for_event() {
+                Ok(Some(Event::KeyPress(event))) => {
+                    let mods = u16::from(event.state) & modifier_mask(true, true, true);
+                    if let Some((_, _, chord)) = grabs.iter().find(|(code, m, _)| *code == event.detail && *m == mods) {
+                        if events.send(Ok(chord.clone())).is_err() {
+                            return;
+                        }
+                        wake();
+                    }
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => thread::sleep(IDLE_POLL),
+                Err(e) => {
+                    let _ = events.send(Err(format!("X11 连接中断: {}", e)));
+                    wake();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn modifier_mask(ctrl: bool, alt: bool, shift: bool) -> u16 {
+        let mut mask = 0;
+        for (pressed, modifier) in [(ctrl, ModMask::CONTROL), (alt, ModMask::M1), (shift, ModMask::SHIFT)] {
+            if pressed {
+                mask |= u16::from(modifier);
+            }
+        }
+        mask
+    }
+
+    /// 抓取一个按键组合，失败（按键不存在或已被其他程序占用）时通过事件通道报告
+    fn grab(conn: &RustConnection, root: Window, chord: KeyChord, events: &Sender<Result<KeyChord, String>>) -> Option<Grab> {
+        let Some(code) = keysym(&chord.key).and_then(|sym| keycode(conn, sym)) else {
+            let _ = events.send(Err(format!("{}: 键盘上没有此按键", chord)));
+            return None;
+        };
+        let mods = modifier_mask(chord.ctrl, chord.alt, chord.shift);
+        for lock in LOCK_MASKS {
+            let result = conn
+                .grab_key(false, root, ModMask::from(mods | lock), code, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| e.to_string())
+                .and_then(|cookie| cookie.check().map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                let _ = events.send(Err(format!("{} 已被其他程序占用: {}", chord, e)));
+                ungrab(conn, root, &[(code, mods, chord)]);
+                return None;
+            }
+        }
+        Some((code, mods, chord))
+    }
+
+    fn ungrab(conn: &RustConnection, root: Window, grabs: &[Grab]) {
+        for (code, mods, _) in grabs {
+            for lock in LOCK_MASKS {
+                let _ = conn.ungrab_key(*code, root, ModMask::from(mods | lock));
+            }
+        }
+    }
+
+    /// 查找产生 `sym` 的键码
+    fn keycode(conn: &RustConnection, sym: Keysym) -> Option<Keycode> {
+        let setup = conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn.get_keyboard_mapping(min, max - min + 1).ok()?.reply().ok()?;
+        let per_keycode = usize::from(mapping.keysyms_per_keycode).max(1);
+        let index = mapping.keysyms.chunks(per_keycode).position(|syms| syms.contains(&sym))?;
+        Some(min + index as u8)
+    }
+
+    /// egui 按键名称对应的 X11 keysym（字母取小写）
+    fn keysym(key: &str) -> Option<Keysym> {
+        let mut chars = key.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return c.is_ascii_alphanumeric().then_some(c.to_ascii_lowercase() as Keysym);
+        }
+        if let Some(n) = key.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()).filter(|n| (1..=35).contains(n)) {
+            return Some(0xffbe + n - 1);
+        }
+        Some(match key {
+            "Space" => 0x20,
+            "Enter" => 0xff0d,
+            "Escape" => 0xff1b,
+            "Tab" => 0xff09,
+            "Backspace" => 0xff08,
+            "Insert" => 0xff63,
+            "Delete" => 0xffff,
+            "Home" => 0xff50,
+            "End" => 0xff57,
+            "PageUp" => 0xff55,
+            "PageDown" => 0xff56,
+            "Left" => 0xff51,
+            "Up" => 0xff52,
+            "Right" => 0xff53,
+            "Down" => 0xff54,
+            _ => return None,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_keysym() {
+            assert_eq!(keysym("G"), Some(0x67));
+            assert_eq!(keysym("1"), Some(0x31));
+            assert_eq!(keysym("F1"), Some(0xffbe));
+            assert_eq!(keysym("F12"), Some(0xffc9));
+            assert_eq!(keysym("Space"), Some(0x20));
+            assert_eq!(keysym("Up"), Some(0xff52));
+            assert_eq!(keysym("Copy"), None);
+            assert_eq!(modifier_mask(true, false, true), 0b101);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(keys: &str, action: HotkeyAction, global: bool) -> HotkeyBinding {
+        HotkeyBinding { keys: keys.to_string(), action, global }
+    }
+
+    #[test]
+    fn test_key_chord() {
+        let chord = KeyChord::parse("shift+Ctrl+g").unwrap();
+        assert_eq!(chord, KeyChord { ctrl: true, alt: false, shift: true, key: "G".to_string() });
+        assert_eq!(chord.to_string(), "Ctrl+Shift+G");
+        assert!(chord.has_command_modifier());
+        assert_eq!(KeyChord::parse("F5").unwrap().to_string(), "F5");
+        assert!(!KeyChord::parse("Shift+F5").unwrap().has_command_modifier());
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("Ctrl+").is_err());
+        assert!(KeyChord::parse("Super+G").is_err());
+    }
+
+    #[test]
+    fn test_dispatch_and_conflicts() {
+        let bindings = vec![
+            binding("Ctrl+G", HotkeyAction::ApplyPreset("游戏".to_string()), true),
+            binding("F5", HotkeyAction::TogglePause, false),
+            binding("ctrl+g", HotkeyAction::SwitchTab("scheduler".to_string()), false),
+            binding("", HotkeyAction::FocusSearch, false),
+            binding("Ctrl+F", HotkeyAction::FocusSearch, true),
+        ];
+
+        // 大小写不同的相同组合视为冲突，未设置的绑定不参与
+        assert_eq!(hotkey_conflicts(&bindings), vec![Some(2), None, Some(0), None, None]);
+
+        // 冲突时第一个绑定生效
+        let ctrl_g = KeyChord::parse("Ctrl+G").unwrap();
+        assert_eq!(dispatch_hotkey(&bindings, &ctrl_g), Some(&HotkeyAction::ApplyPreset("游戏".to_string())));
+        assert_eq!(dispatch_hotkey(&bindings, &KeyChord::parse("F5").unwrap()), Some(&HotkeyAction::TogglePause));
+        assert_eq!(dispatch_hotkey(&bindings, &KeyChord::parse("Shift+F5").unwrap()), None);
+
+        assert_eq!(global_chords(&bindings), vec![ctrl_g, KeyChord::parse("Ctrl+F").unwrap()]);
+    }
+
+    #[test]
+    fn test_global_hotkeys_without_bindings() {
+        let mut hotkeys = GlobalHotkeys::default();
+        assert!(hotkeys.poll(&[], || {}).is_empty());
+        assert!(hotkeys.error().is_none());
+    }
+}
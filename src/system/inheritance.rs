@@ -0,0 +1,138 @@
+//! 实时策略继承检查 - 子进程默认继承父进程的调度策略
+//! 给 shell 或启动器设置 FIFO 后，它之后启动的所有程序都会变成实时进程，可能锁死桌面
+
+use super::ProcessInfo;
+
+/// 会启动大量其他程序的 shell 和启动器（按进程名匹配）
+pub const RT_LAUNCHERS: &[&str] = &[
+    "bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "nu", "tmux", "screen", "gamescope", "steam", "lutris",
+    "heroic", "bottles", "wine", "wineserver", "gamemoderun", "systemd", "plasmashell", "gnome-shell", "kwin_wayland",
+];
+
+/// 预期以实时策略运行的程序（由 rtkit 等授予，不视为意外继承）
+pub const EXPECTED_RT: &[&str] = &[
+    "pipewire", "pipewire-pulse", "wireplumber", "pulseaudio", "jackd", "jackdbus", "rtkit-daemon", "irqbalance",
+];
+
+/// 子进程数达到此值时视为启动器
+pub const RT_CHILDREN_THRESHOLD: usize = 4;
+
+/// 对进程设置实时策略的继承风险
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InheritanceRisk {
+    /// 已知的 shell 或启动器
+    Launcher,
+    /// 已有较多子进程
+    ManyChildren(usize),
+}
+
+impl InheritanceRisk {
+    /// 提示文本
+    pub fn describe(&self, name: &str) -> String {
+        match self {
+            InheritanceRisk::Launcher => format!("{} 是 shell 或启动器，之后由它启动的所有程序都会继承实时策略", name),
+            InheritanceRisk::ManyChildren(count) => {
+                format!("{} 已有 {} 个子进程，它之后启动的程序都会继承实时策略", name, count)
+            }
+        }
+    }
+}
+
+/// 判断给进程设置实时策略的继承风险
+pub fn inheritance_risk(name: &str, child_count: usize) -> Option<InheritanceRisk> {
+    if RT_LAUNCHERS.contains(&name) {
+        Some(InheritanceRisk::Launcher)
+    } else if child_count >= RT_CHILDREN_THRESHOLD {
+        Some(InheritanceRisk::ManyChildren(child_count))
+    } else {
+        None
+    }
+}
+
+/// 直接子进程数
+pub fn child_count<'a>(processes: impl IntoIterator<Item = &'a ProcessInfo>, pid: u32) -> usize {
+    processes.into_iter().filter(|p| p.parent_pid == Some(pid)).count()
+}
+
+/// 疑似意外继承实时策略的进程
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritedRt {
+    pub pid: u32,
+    pub name: String,
+    pub parent_pid: u32,
+    pub parent_name: String,
+}
+
+/// 找出父进程不是实时策略的实时进程：通常是启动器曾被设为实时后又恢复，
+/// 或中间的启动器已退出，它启动的程序仍保留着继承来的实时策略
+/// 内核线程和预期以实时策略运行的程序除外
+pub fn suspected_rt_inheritance(processes: &[&ProcessInfo]) -> Vec<InheritedRt> {
+    processes
+        .iter()
+        .filter(|p| p.sched_policy.is_realtime() && !p.kernel_thread && !EXPECTED_RT.contains(&p.name.as_str()))
+        .filter_map(|p| {
+            let parent = processes.iter().find(|parent| Some(parent.pid) == p.parent_pid)?;
+            (!parent.sched_policy.is_realtime()).then(|| InheritedRt {
+                pid: p.pid,
+                name: p.name.clone(),
+                parent_pid: parent.pid,
+                parent_name: parent.name.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+    use crate::system::SchedulePolicy;
+
+    #[test]
+    fn test_inheritance_risk() {
+        let cases = [
+            ("bash", 0, Some(InheritanceRisk::Launcher)),
+            ("steam", 1, Some(InheritanceRisk::Launcher)),
+            ("gamescope", 2, Some(InheritanceRisk::Launcher)),
+            ("blender", 0, None),
+            ("blender", RT_CHILDREN_THRESHOLD - 1, None),
+            ("make", 12, Some(InheritanceRisk::ManyChildren(12))),
+            // 只匹配完整进程名
+            ("bashtop", 0, None),
+        ];
+        for (name, children, expected) in cases {
+            assert_eq!(inheritance_risk(name, children), expected, "{} ({} 个子进程)", name, children);
+        }
+    }
+
+    fn rt_process(pid: u32, name: &str, parent: Option<u32>, policy: SchedulePolicy) -> ProcessInfo {
+        let mut p = process(pid, name, 0.0, 0);
+        p.parent_pid = parent;
+        p.sched_policy = policy;
+        p
+    }
+
+    #[test]
+    fn test_suspected_rt_inheritance() {
+        let processes = [
+            rt_process(1, "systemd", None, SchedulePolicy::Other),
+            rt_process(100, "steam", Some(1), SchedulePolicy::Other),
+            // steam 曾被设为 FIFO 又恢复，游戏仍是 FIFO
+            rt_process(200, "game", Some(100), SchedulePolicy::Fifo),
+            // 父进程也是实时：继承链的中间，由上层报告
+            rt_process(201, "game-helper", Some(200), SchedulePolicy::RoundRobin),
+            rt_process(300, "pipewire", Some(1), SchedulePolicy::Fifo),
+            rt_process(400, "orphan", Some(999), SchedulePolicy::Fifo),
+        ];
+        let refs: Vec<&ProcessInfo> = processes.iter().collect();
+        let flagged = suspected_rt_inheritance(&refs);
+        assert_eq!(
+            flagged,
+            vec![InheritedRt { pid: 200, name: "game".to_string(), parent_pid: 100, parent_name: "steam".to_string() }]
+        );
+
+        assert_eq!(child_count(&processes, 1), 2);
+        assert_eq!(child_count(&processes, 200), 1);
+        assert_eq!(child_count(&processes, 400), 0);
+    }
+}
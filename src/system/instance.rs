@@ -0,0 +1,230 @@
+//! 单实例 - 第二次启动时把命令行意图（--tab / --filter）转发给正在运行的实例并退出
+//! 通过 $XDG_RUNTIME_DIR 下的 Unix socket 通信（仅 Unix）；程序崩溃后遗留的 socket 无法连接，启动时会被清理
+
+use std::fs;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time::Duration;
+
+/// 等待正在运行的实例确认的时长
+#[cfg(unix)]
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+/// 确认回复
+#[cfg(unix)]
+const ACK: &str = "ok";
+
+/// 转发给正在运行的实例的命令：显示并聚焦窗口，可选切换标签页和设置进程过滤
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceCommand {
+    /// 标签页名称（见 `TAB_NAMES`）
+    pub tab: Option<String>,
+    /// 进程过滤文本
+    pub filter: Option<String>,
+}
+
+impl InstanceCommand {
+    /// 编码为每行一项的文本（"tab scheduler"、"filter game"）
+    #[cfg(unix)]
+    fn encode(&self) -> String {
+        let mut text = String::new();
+        if let Some(tab) = &self.tab {
+            text.push_str(&format!("tab {}\n", tab));
+        }
+        if let Some(filter) = &self.filter {
+            text.push_str(&format!("filter {}\n", filter.replace('\n', " ")));
+        }
+        text
+    }
+
+    /// 解析 `encode` 的输出，忽略无法识别的行
+    #[cfg(unix)]
+    fn decode(text: &str) -> Self {
+        let mut command = Self::default();
+        for line in text.lines() {
+            match line.split_once(' ') {
+                Some(("tab", tab)) => command.tab = Some(tab.to_string()),
+                Some(("filter", filter)) => command.filter = Some(filter.to_string()),
+                _ => {}
+            }
+        }
+        command
+    }
+}
+
+/// 单实例检测的结果
+#[derive(Debug)]
+pub enum Instance {
+    /// 没有其他实例在运行，本进程持有 socket
+    Primary(InstanceGuard),
+    /// 命令已转发给正在运行的实例
+    Forwarded,
+}
+
+/// 持有单实例 socket，释放时删除 socket 文件
+#[derive(Debug)]
+pub struct InstanceGuard {
+    path: PathBuf,
+    #[cfg(unix)]
+    listener: Option<UnixListener>,
+}
+
+impl InstanceGuard {
+    /// 在后台线程接收其他实例转发的命令；`wake` 在命令到达时调用（用于唤醒隐藏的窗口）
+    #[cfg(unix)]
+    pub fn listen(&mut self, wake: impl Fn() + Send + 'static) -> Receiver<InstanceCommand> {
+        let (sender, receiver) = mpsc::channel();
+        let Some(listener) = self.listener.take() else {
+            return receiver;
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Some(command) = read_command(stream) else {
+                    continue;
+                };
+                if sender.send(command).is_err() {
+                    break;
+                }
+                wake();
+            }
+        });
+        receiver
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen(&mut self, _wake: impl Fn() + Send + 'static) -> Receiver<InstanceCommand> {
+        mpsc::channel().1
+    }
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 读取一条命令并回复确认
+#[cfg(unix)]
+fn read_command(mut stream: UnixStream) -> Option<InstanceCommand> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT)).ok()?;
+    let mut text = String::new();
+    stream.read_to_string(&mut text).ok()?;
+    stream.write_all(format!("{}\n", ACK).as_bytes()).ok()?;
+    Some(InstanceCommand::decode(&text))
+}
+
+/// 单实例 socket 路径 $XDG_RUNTIME_DIR/hexin.sock
+/// 没有设置运行时目录时不做单实例检测：/tmp 等共享目录中的 socket 可能被其他用户抢先创建
+#[cfg(unix)]
+pub fn instance_socket_path() -> Result<PathBuf, String> {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir).join("hexin.sock")),
+        _ => Err("没有设置 $XDG_RUNTIME_DIR".to_string()),
+    }
+}
+
+/// 检测是否已有实例在运行：有则转发命令，否则占用 socket 成为主实例
+#[cfg(unix)]
+pub fn claim_instance(command: &InstanceCommand) -> Result<Instance, String> {
+    claim_instance_at(&instance_socket_path()?, command)
+}
+
+#[cfg(not(unix))]
+pub fn claim_instance(_command: &InstanceCommand) -> Result<Instance, String> {
+    Err("单实例检测仅支持 Unix".to_string())
+}
+
+#[cfg(unix)]
+fn claim_instance_at(path: &Path, command: &InstanceCommand) -> Result<Instance, String> {
+    match forward(path, command) {
+        Ok(()) => return Ok(Instance::Forwarded),
+        // 崩溃后遗留的 socket：文件存在但没有进程监听
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => {
+            fs::remove_file(path).map_err(|e| format!("无法删除遗留的 {}: {}", path.display(), e))?;
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("正在运行的实例没有响应 ({}): {}", path.display(), e)),
+    }
+    match UnixListener::bind(path) {
+        Ok(listener) => Ok(Instance::Primary(InstanceGuard { path: path.to_path_buf(), listener: Some(listener) })),
+        // 另一个实例同时启动并抢先占用
+        Err(e) if e.kind() == ErrorKind::AddrInUse => forward(path, command)
+            .map(|()| Instance::Forwarded)
+            .map_err(|e| format!("连接正在运行的实例失败: {}", e)),
+        Err(e) => Err(format!("无法创建 {}: {}", path.display(), e)),
+    }
+}
+
+/// 发送命令并等待确认
+#[cfg(unix)]
+fn forward(path: &Path, command: &InstanceCommand) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.write_all(command.encode().as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == ACK {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(ErrorKind::InvalidData, "未收到确认"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_command_encoding() {
+        let command = InstanceCommand { tab: Some("scheduler".to_string()), filter: Some("my game\nx".to_string()) };
+        let decoded = InstanceCommand::decode(&command.encode());
+        assert_eq!(decoded.tab.as_deref(), Some("scheduler"));
+        assert_eq!(decoded.filter.as_deref(), Some("my game x"));
+        assert_eq!(InstanceCommand::decode(""), InstanceCommand::default());
+        assert_eq!(InstanceCommand::decode("unknown 1\ntab cpu\n").tab.as_deref(), Some("cpu"));
+    }
+
+    #[test]
+    fn test_claim_instance() {
+        let path = std::env::temp_dir().join(format!("hexin-instance-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        // 崩溃遗留的 socket 文件被清理
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let Ok(Instance::Primary(mut guard)) = claim_instance_at(&path, &InstanceCommand::default()) else {
+            panic!("应成为主实例");
+        };
+        let woken = Arc::new(AtomicUsize::new(0));
+        let counter = woken.clone();
+        let commands = guard.listen(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let command = InstanceCommand { tab: Some("processes".to_string()), filter: Some("game".to_string()) };
+        assert!(matches!(claim_instance_at(&path, &command), Ok(Instance::Forwarded)));
+        assert_eq!(commands.recv_timeout(FORWARD_TIMEOUT), Ok(command));
+        // wake 在命令送出后调用
+        for _ in 0..100 {
+            if woken.load(Ordering::SeqCst) == 1 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+
+        drop(guard);
+        assert!(!path.exists());
+    }
+}
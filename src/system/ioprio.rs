@@ -0,0 +1,136 @@
+//! Linux I/O 优先级 (ioprio) API 封装
+
+use serde::{Deserialize, Serialize};
+
+/// ioprio 中类别所在的位偏移
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+/// ioprio_get/ioprio_set 的目标类型：单个进程
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: i32 = 1;
+
+/// I/O 调度类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IoClass {
+    /// 未设置（由 nice 值推导）
+    None,
+    /// 实时 I/O
+    RealTime,
+    /// 尽力而为（默认）
+    BestEffort,
+    /// 仅在磁盘空闲时进行 I/O
+    Idle,
+}
+
+impl IoClass {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            IoClass::None => "默认",
+            IoClass::RealTime => "实时",
+            IoClass::BestEffort => "尽力",
+            IoClass::Idle => "空闲",
+        }
+    }
+
+    /// 可设置的类别
+    pub fn all() -> &'static [IoClass] {
+        &[IoClass::BestEffort, IoClass::Idle, IoClass::RealTime]
+    }
+}
+
+/// I/O 优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IoPriority {
+    /// 调度类别
+    pub class: IoClass,
+    /// 类别内的级别 (0 最高 - 7 最低，空闲类别忽略)
+    pub level: u8,
+}
+
+impl IoPriority {
+    /// 从内核 ioprio 值转换
+    pub fn from_raw(raw: i32) -> Self {
+        let class = match raw >> IOPRIO_CLASS_SHIFT {
+            1 => IoClass::RealTime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        IoPriority {
+            class,
+            level: (raw & 0x7) as u8,
+        }
+    }
+
+    /// 转换为内核 ioprio 值
+    pub fn to_raw(self) -> i32 {
+        let class = match self.class {
+            IoClass::None => 0,
+            IoClass::RealTime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        let level = if self.class == IoClass::Idle { 0 } else { self.level.min(7) as i32 };
+        (class << IOPRIO_CLASS_SHIFT) | level
+    }
+
+    /// 显示文本
+    pub fn display(&self) -> String {
+        match self.class {
+            IoClass::None | IoClass::Idle => self.class.display_name().to_string(),
+            _ => format!("{} {}", self.class.display_name(), self.level),
+        }
+    }
+}
+
+/// 获取进程的 I/O 优先级 (Linux only)
+#[cfg(target_os = "linux")]
+pub fn get_io_priority(pid: i32) -> Result<IoPriority, String> {
+    let result = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid) };
+    if result >= 0 {
+        Ok(IoPriority::from_raw(result as i32))
+    } else {
+        let err = std::io::Error::last_os_error();
+        Err(format!("读取 I/O 优先级失败: {}", err))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_io_priority(_pid: i32) -> Result<IoPriority, String> {
+    Err("I/O 优先级仅支持 Linux".to_string())
+}
+
+/// 设置进程的 I/O 优先级 (Linux only)
+#[cfg(target_os = "linux")]
+pub fn set_io_priority(pid: i32, priority: IoPriority) -> Result<(), String> {
+    let result = unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid, priority.to_raw())
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        let err = std::io::Error::last_os_error();
+        Err(format!("设置 I/O 优先级失败: {} (实时类别需要 root 权限)", err))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_io_priority(_pid: i32, _priority: IoPriority) -> Result<(), String> {
+    Err("I/O 优先级设置仅支持 Linux".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_priority_raw_roundtrip() {
+        let be4 = IoPriority { class: IoClass::BestEffort, level: 4 };
+        assert_eq!(be4.to_raw(), (2 << 13) | 4);
+        assert_eq!(IoPriority::from_raw(be4.to_raw()), be4);
+
+        let idle = IoPriority { class: IoClass::Idle, level: 0 };
+        assert_eq!(IoPriority::from_raw(idle.to_raw()), idle);
+        assert_eq!(IoPriority::from_raw(0).class, IoClass::None);
+    }
+}
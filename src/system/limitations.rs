@@ -0,0 +1,415 @@
+//! 权限与限制 - 把运行时发现的权限和功能可用性问题汇总为一份持续显示的清单
+//! 条目来自两处：启动时（以及用户点击"重新检查"时）的系统检查 (`detect_limitations`)，
+//! 和操作因权限被拒绝时观察到的失败 (`Limitations::observe_failure`)。
+//! 每项给出具体的解决办法和可复制的命令
+
+use std::fs;
+use std::path::Path;
+
+use super::{
+    is_delay_accounting_enabled, is_sched_debug_readable, read_cpufreq_policies, CpuVendor, IoClass,
+    PrivilegeInfo, SchedChange, TemperatureSensors,
+};
+
+/// 限制的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LimitationKind {
+    /// 没有 CAP_SYS_NICE（检查）
+    NoSysNice,
+    /// 设置实时调度策略被拒绝（观察）
+    RealtimeDenied,
+    /// 降低 nice 值被拒绝（观察）
+    NiceDenied,
+    /// 设置实时 I/O 优先级被拒绝（观察）
+    IoRealtimeDenied,
+    /// 修改 OOM 调整值被拒绝（观察）
+    OomDenied,
+    /// 修改其他用户的进程被拒绝（观察）
+    OtherUserDenied,
+    /// 写入 cpufreq / EPP 被拒绝（检查或观察）
+    CpufreqReadOnly,
+    /// 没有 cpufreq 驱动（检查）
+    CpufreqMissing,
+    /// /sys/fs/cgroup 不是 cgroup v2（检查）
+    CgroupV2Missing,
+    /// Intel 上无法读取 MSR（检查）
+    MsrUnavailable,
+    /// 没有 CPU 温度传感器（检查）
+    TemperatureMissing,
+    /// 启用了延迟统计显示但内核未开启（检查）
+    DelayAccountingOff,
+    /// sched/debug 不可读（检查）
+    SchedDebugUnreadable,
+}
+
+impl LimitationKind {
+    /// 受影响的功能
+    pub fn title(&self) -> &'static str {
+        match self {
+            LimitationKind::NoSysNice => "以普通用户运行，部分调度设置不可用",
+            LimitationKind::RealtimeDenied => "设置实时调度策略被拒绝",
+            LimitationKind::NiceDenied => "降低 nice 值被拒绝",
+            LimitationKind::IoRealtimeDenied => "设置实时 I/O 优先级被拒绝",
+            LimitationKind::OomDenied => "修改 OOM 调整值被拒绝",
+            LimitationKind::OtherUserDenied => "修改其他用户的进程被拒绝",
+            LimitationKind::CpufreqReadOnly => "频率限制、调速器和 EPP 只读",
+            LimitationKind::CpufreqMissing => "频率控制不可用",
+            LimitationKind::CgroupV2Missing => "cgroup CPU 统计不可用",
+            LimitationKind::MsrUnavailable => "无法读取最大睿频",
+            LimitationKind::TemperatureMissing => "没有 CPU 温度数据",
+            LimitationKind::DelayAccountingOff => "延迟统计未开启",
+            LimitationKind::SchedDebugUnreadable => "无法读取调度器调试信息",
+        }
+    }
+
+    /// 原因
+    pub fn cause(&self) -> &'static str {
+        match self {
+            LimitationKind::NoSysNice => "没有 CAP_SYS_NICE：不能使用 FIFO / RR 实时策略、降低 nice 值或修改其他用户的进程",
+            LimitationKind::RealtimeDenied => "FIFO / RR 需要 CAP_SYS_NICE，或目标进程的 RLIMIT_RTPRIO 允许所请求的优先级",
+            LimitationKind::NiceDenied => "降低 nice 值（提高优先级）需要 CAP_SYS_NICE，或目标进程的 RLIMIT_NICE 允许",
+            LimitationKind::IoRealtimeDenied => "实时 I/O 类别需要 root 权限",
+            LimitationKind::OomDenied => "降低 oom_score_adj 或修改其他用户进程的该值需要 root 权限",
+            LimitationKind::OtherUserDenied => "没有 CAP_SYS_NICE 时只能修改与 hexin 相同用户的进程",
+            LimitationKind::CpufreqReadOnly => "写入 /sys/devices/system/cpu/cpufreq 需要 root 权限",
+            LimitationKind::CpufreqMissing => "没有 cpufreq 驱动（虚拟机中很常见）",
+            LimitationKind::CgroupV2Missing => "/sys/fs/cgroup 不是 cgroup v2 统一层级",
+            LimitationKind::MsrUnavailable => "读取 Intel MSR 需要 msr 驱动和 CAP_SYS_RAWIO",
+            LimitationKind::TemperatureMissing => "hwmon 中找不到 k10temp / coretemp 等 CPU 温度传感器",
+            LimitationKind::DelayAccountingOff => "内核关闭了 task_delayacct，进程详情中的等待 CPU 时间不可用",
+            LimitationKind::SchedDebugUnreadable => "sched/debug 需要挂载 debugfs 并以 root 读取，进程详情中的迁移统计不可用",
+        }
+    }
+
+    /// 解决办法
+    pub fn remedy(&self) -> &'static str {
+        match self {
+            LimitationKind::NoSysNice
+            | LimitationKind::NiceDenied
+            | LimitationKind::OtherUserDenied => "以 root 运行 hexin，或为程序授予 CAP_SYS_NICE",
+            LimitationKind::RealtimeDenied => {
+                "以 root 运行 hexin，或为程序授予 CAP_SYS_NICE；也可以在 /etc/security/limits.conf 中为目标进程的用户设置 rtprio"
+            }
+            LimitationKind::IoRealtimeDenied | LimitationKind::OomDenied | LimitationKind::CpufreqReadOnly => {
+                "以 root 运行 hexin"
+            }
+            LimitationKind::CpufreqMissing => "加载 cpufreq 驱动（amd-pstate、intel_pstate 或 acpi-cpufreq）",
+            LimitationKind::CgroupV2Missing => {
+                "挂载 cgroup2，或在内核参数中加入 systemd.unified_cgroup_hierarchy=1 后重启"
+            }
+            LimitationKind::MsrUnavailable => "加载 msr 模块并以 root 运行 hexin",
+            LimitationKind::TemperatureMissing => "加载 CPU 温度传感器驱动",
+            LimitationKind::DelayAccountingOff => "开启 kernel.task_delayacct（或在内核参数中加入 delayacct）",
+            LimitationKind::SchedDebugUnreadable => "挂载 debugfs 并以 root 运行 hexin",
+        }
+    }
+
+    /// 可直接执行的命令（`exe` 为 hexin 程序路径）
+    pub fn command(&self, vendor: CpuVendor, exe: &str) -> String {
+        match self {
+            LimitationKind::NoSysNice
+            | LimitationKind::RealtimeDenied
+            | LimitationKind::NiceDenied
+            | LimitationKind::OtherUserDenied => format!("sudo setcap cap_sys_nice+ep {}", exe),
+            LimitationKind::IoRealtimeDenied | LimitationKind::OomDenied | LimitationKind::CpufreqReadOnly => {
+                format!("sudo -E {}", exe)
+            }
+            LimitationKind::CpufreqMissing => "sudo modprobe acpi-cpufreq".to_string(),
+            LimitationKind::CgroupV2Missing => "sudo mount -t cgroup2 none /sys/fs/cgroup".to_string(),
+            LimitationKind::MsrUnavailable => "sudo modprobe msr".to_string(),
+            LimitationKind::TemperatureMissing => match vendor {
+                CpuVendor::AMD => "sudo modprobe k10temp".to_string(),
+                _ => "sudo modprobe coretemp".to_string(),
+            },
+            LimitationKind::DelayAccountingOff => "sudo sysctl kernel.task_delayacct=1".to_string(),
+            LimitationKind::SchedDebugUnreadable => "sudo mount -t debugfs none /sys/kernel/debug".to_string(),
+        }
+    }
+}
+
+/// 检查所需的系统状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemProbe {
+    pub privilege: PrivilegeInfo,
+    pub vendor: CpuVendor,
+    /// /sys/fs/cgroup 为 cgroup v2
+    pub cgroup_v2: bool,
+    /// cpufreq 策略是否可写，没有 cpufreq 驱动时为 None
+    pub cpufreq_writable: Option<bool>,
+    /// /dev/cpu/0/msr 存在
+    pub msr_device: bool,
+    /// 有 CPU 温度传感器
+    pub temperature: bool,
+    /// kernel.task_delayacct（旧内核无此项时为 None）
+    pub delay_accounting: Option<bool>,
+    /// sched/debug 可读
+    pub sched_debug: bool,
+}
+
+impl SystemProbe {
+    /// 读取当前系统状态
+    pub fn read(vendor: CpuVendor) -> Self {
+        let cpufreq_writable = read_cpufreq_policies()
+            .first()
+            .map(|policy| is_writable(&policy.path.join("scaling_max_freq")));
+        SystemProbe {
+            privilege: PrivilegeInfo::detect(),
+            vendor,
+            cgroup_v2: Path::new("/sys/fs/cgroup/cgroup.controllers").exists(),
+            cpufreq_writable,
+            msr_device: Path::new("/dev/cpu/0/msr").exists(),
+            temperature: !TemperatureSensors::discover().is_empty(),
+            delay_accounting: is_delay_accounting_enabled(),
+            sched_debug: is_sched_debug_readable(),
+        }
+    }
+}
+
+fn is_writable(path: &Path) -> bool {
+    fs::OpenOptions::new().write(true).open(path).is_ok()
+}
+
+/// 根据系统状态列出限制；`delay_accounting` 为是否启用了延迟统计显示
+pub fn detect_limitations(probe: &SystemProbe, delay_accounting: bool) -> Vec<LimitationKind> {
+    let mut kinds = Vec::new();
+    if !probe.privilege.cap_sys_nice {
+        kinds.push(LimitationKind::NoSysNice);
+    }
+    match probe.cpufreq_writable {
+        None => kinds.push(LimitationKind::CpufreqMissing),
+        Some(false) => kinds.push(LimitationKind::CpufreqReadOnly),
+        Some(true) => {}
+    }
+    if !probe.cgroup_v2 {
+        kinds.push(LimitationKind::CgroupV2Missing);
+    }
+    if probe.vendor == CpuVendor::Intel && !(probe.msr_device && probe.privilege.cap_sys_rawio) {
+        kinds.push(LimitationKind::MsrUnavailable);
+    }
+    if !probe.temperature {
+        kinds.push(LimitationKind::TemperatureMissing);
+    }
+    if delay_accounting && probe.delay_accounting == Some(false) {
+        kinds.push(LimitationKind::DelayAccountingOff);
+    }
+    if !probe.sched_debug {
+        kinds.push(LimitationKind::SchedDebugUnreadable);
+    }
+    kinds
+}
+
+/// 错误信息是否为权限不足 (EPERM / EACCES)
+fn is_permission_error(error: &str) -> bool {
+    error.contains("(os error 1)") || error.contains("(os error 13)") || error.contains("权限不足")
+}
+
+/// 调度设置因权限失败时对应的限制（进程已退出等其他错误返回 None）
+pub fn classify_failure(change: &SchedChange, error: &str) -> Option<LimitationKind> {
+    if !is_permission_error(error) {
+        return None;
+    }
+    Some(match change {
+        SchedChange::Policy { policy, .. } if policy.is_realtime() => LimitationKind::RealtimeDenied,
+        SchedChange::Nice(nice) if *nice < 0 => LimitationKind::NiceDenied,
+        SchedChange::IoPriority(prio) if prio.class == IoClass::RealTime => LimitationKind::IoRealtimeDenied,
+        SchedChange::OomScoreAdj(_) => LimitationKind::OomDenied,
+        _ => LimitationKind::OtherUserDenied,
+    })
+}
+
+/// 清单中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limitation {
+    pub kind: LimitationKind,
+    /// 由系统检查发现
+    pub detected: bool,
+    /// 观察到的失败次数
+    pub failures: u32,
+    /// 最近一次失败的错误信息
+    pub last_error: Option<String>,
+}
+
+/// 权限与限制清单
+#[derive(Debug, Clone)]
+pub struct Limitations {
+    vendor: CpuVendor,
+    /// hexin 程序路径（用于命令）
+    exe: String,
+    entries: Vec<Limitation>,
+}
+
+impl Default for Limitations {
+    fn default() -> Self {
+        Self {
+            vendor: CpuVendor::Other,
+            exe: std::env::current_exe()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| "hexin".to_string()),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Limitations {
+    /// 重新检查：更新检查发现的条目，保留观察到失败的条目
+    pub fn recheck(&mut self, probe: &SystemProbe, delay_accounting: bool) {
+        self.vendor = probe.vendor;
+        self.set_detected(&detect_limitations(probe, delay_accounting));
+    }
+
+    fn set_detected(&mut self, kinds: &[LimitationKind]) {
+        self.entries.retain_mut(|entry| {
+            entry.detected = kinds.contains(&entry.kind);
+            entry.detected || entry.failures > 0
+        });
+        for &kind in kinds {
+            if !self.entries.iter().any(|e| e.kind == kind) {
+                self.entries.push(Limitation { kind, detected: true, failures: 0, last_error: None });
+            }
+        }
+        self.entries.sort_by_key(|e| e.kind);
+    }
+
+    /// 记录一次调度设置失败，权限问题时加入清单
+    pub fn observe_failure(&mut self, change: &SchedChange, error: &str) {
+        if let Some(kind) = classify_failure(change, error) {
+            self.observe(kind, error);
+        }
+    }
+
+    /// 记录一次 cpufreq / EPP 写入失败，权限问题时加入清单
+    pub fn observe_sysfs_failure(&mut self, error: &str) {
+        if is_permission_error(error) {
+            self.observe(LimitationKind::CpufreqReadOnly, error);
+        }
+    }
+
+    fn observe(&mut self, kind: LimitationKind, error: &str) {
+        match self.entries.iter_mut().find(|e| e.kind == kind) {
+            Some(entry) => {
+                entry.failures += 1;
+                entry.last_error = Some(error.to_string());
+            }
+            None => {
+                self.entries.push(Limitation { kind, detected: false, failures: 1, last_error: Some(error.to_string()) });
+                self.entries.sort_by_key(|e| e.kind);
+            }
+        }
+    }
+
+    /// 从清单中移除（重新检查时仍存在的问题会再次出现）
+    pub fn dismiss(&mut self, kind: LimitationKind) {
+        self.entries.retain(|e| e.kind != kind);
+    }
+
+    /// 清单条目（按种类排序）
+    pub fn entries(&self) -> &[Limitation] {
+        &self.entries
+    }
+
+    /// 条目可直接执行的命令
+    pub fn command(&self, kind: LimitationKind) -> String {
+        kind.command(self.vendor, &self.exe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{IoPriority, SchedulePolicy};
+
+    fn probe() -> SystemProbe {
+        SystemProbe {
+            privilege: PrivilegeInfo { euid: 0, cap_sys_nice: true, cap_sys_rawio: true },
+            vendor: CpuVendor::Intel,
+            cgroup_v2: true,
+            cpufreq_writable: Some(true),
+            msr_device: true,
+            temperature: true,
+            delay_accounting: Some(true),
+            sched_debug: true,
+        }
+    }
+
+    #[test]
+    fn test_detect_limitations() {
+        assert!(detect_limitations(&probe(), true).is_empty());
+
+        let user = SystemProbe {
+            privilege: PrivilegeInfo { euid: 1000, cap_sys_nice: false, cap_sys_rawio: false },
+            cpufreq_writable: Some(false),
+            delay_accounting: Some(false),
+            ..probe()
+        };
+        assert_eq!(
+            detect_limitations(&user, false),
+            vec![LimitationKind::NoSysNice, LimitationKind::CpufreqReadOnly, LimitationKind::MsrUnavailable]
+        );
+        assert!(detect_limitations(&user, true).contains(&LimitationKind::DelayAccountingOff));
+
+        // 虚拟机：没有 cpufreq、温度传感器；MSR 只在 Intel 上检查
+        let vm = SystemProbe {
+            vendor: CpuVendor::AMD,
+            cgroup_v2: false,
+            cpufreq_writable: None,
+            msr_device: false,
+            temperature: false,
+            ..probe()
+        };
+        assert_eq!(
+            detect_limitations(&vm, false),
+            vec![LimitationKind::CpufreqMissing, LimitationKind::CgroupV2Missing, LimitationKind::TemperatureMissing]
+        );
+        assert_eq!(
+            LimitationKind::TemperatureMissing.command(CpuVendor::AMD, "hexin"),
+            "sudo modprobe k10temp"
+        );
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        let eperm = "设置调度策略失败: Operation not permitted (os error 1) (可能需要 root 权限或 CAP_SYS_NICE)";
+        let fifo = SchedChange::Policy { policy: SchedulePolicy::Fifo, priority: 50, reset_on_fork: false };
+        let other = SchedChange::Policy { policy: SchedulePolicy::Batch, priority: 0, reset_on_fork: false };
+        assert_eq!(classify_failure(&fifo, eperm), Some(LimitationKind::RealtimeDenied));
+        assert_eq!(classify_failure(&other, eperm), Some(LimitationKind::OtherUserDenied));
+        assert_eq!(classify_failure(&SchedChange::Nice(-5), eperm), Some(LimitationKind::NiceDenied));
+        assert_eq!(classify_failure(&SchedChange::Nice(5), eperm), Some(LimitationKind::OtherUserDenied));
+        let rt_io = SchedChange::IoPriority(IoPriority { class: IoClass::RealTime, level: 0 });
+        assert_eq!(classify_failure(&rt_io, eperm), Some(LimitationKind::IoRealtimeDenied));
+        assert_eq!(
+            classify_failure(&SchedChange::OomScoreAdj(-500), "Permission denied (os error 13)"),
+            Some(LimitationKind::OomDenied)
+        );
+        // 进程已退出 (ESRCH) 不是权限问题
+        assert_eq!(classify_failure(&fifo, "No such process (os error 3)"), None);
+    }
+
+    #[test]
+    fn test_limitations_list() {
+        let mut limitations = Limitations::default();
+        let user = SystemProbe {
+            privilege: PrivilegeInfo { euid: 1000, cap_sys_nice: false, cap_sys_rawio: true },
+            ..probe()
+        };
+        limitations.recheck(&user, false);
+        assert_eq!(limitations.entries().len(), 1);
+
+        let fifo = SchedChange::Policy { policy: SchedulePolicy::Fifo, priority: 50, reset_on_fork: false };
+        limitations.observe_failure(&fifo, "Operation not permitted (os error 1)");
+        limitations.observe_failure(&fifo, "Operation not permitted (os error 1)");
+        limitations.observe_failure(&fifo, "No such process (os error 3)");
+        limitations.observe_sysfs_failure("写入 /sys/x 失败: 权限不足（需要 root 权限）");
+        let kinds: Vec<LimitationKind> = limitations.entries().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![LimitationKind::NoSysNice, LimitationKind::RealtimeDenied, LimitationKind::CpufreqReadOnly]);
+        assert_eq!(limitations.entries()[1].failures, 2);
+
+        // 重新检查：已解决的检查项消失，观察到的失败保留到用户忽略
+        limitations.recheck(&probe(), false);
+        let kinds: Vec<LimitationKind> = limitations.entries().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![LimitationKind::RealtimeDenied, LimitationKind::CpufreqReadOnly]);
+        assert!(!limitations.entries()[1].detected);
+        limitations.dismiss(LimitationKind::RealtimeDenied);
+        assert_eq!(limitations.entries().len(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+//! 负载生成器 - 调试用：在指定核心上运行已知占空比的忙循环
+//! 用于验证核心网格和图表的反应，以及亲和性是否真正限制了执行位置
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::set_process_affinity;
+
+/// 一个忙 / 闲周期的长度
+const DUTY_PERIOD: Duration = Duration::from_millis(100);
+
+/// 尚未观测到运行核心
+const NO_CPU: usize = usize::MAX;
+
+/// 当前线程的 TID
+#[cfg(target_os = "linux")]
+fn current_tid() -> i32 {
+    unsafe { libc::gettid() }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_tid() -> i32 {
+    0
+}
+
+/// 当前线程正在运行的 CPU
+#[cfg(target_os = "linux")]
+fn current_cpu() -> Option<usize> {
+    let cpu = unsafe { libc::sched_getcpu() };
+    usize::try_from(cpu).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_cpu() -> Option<usize> {
+    None
+}
+
+/// 工作线程与生成器共享的状态
+#[derive(Default)]
+struct WorkerShared {
+    /// 最近一次观测到的运行 CPU
+    last_cpu: AtomicUsize,
+    /// 在目标核心之外运行的采样次数
+    off_target: AtomicU64,
+    /// 总采样次数
+    samples: AtomicU64,
+}
+
+struct Worker {
+    cpu: usize,
+    tid: i32,
+    shared: Arc<WorkerShared>,
+    handle: JoinHandle<()>,
+}
+
+/// 一个工作线程的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerStatus {
+    /// 绑定的核心
+    pub cpu: usize,
+    /// 线程 ID
+    pub tid: i32,
+    /// 最近一次观测到的运行 CPU
+    pub last_cpu: Option<usize>,
+    /// 在绑定核心之外运行的采样次数（亲和性生效时应为 0）
+    pub off_target: u64,
+    /// 总采样次数
+    pub samples: u64,
+}
+
+/// 负载生成器：每个选中的核心一个绑定的忙循环线程
+pub struct LoadGenerator {
+    workers: Vec<Worker>,
+    stop: Arc<AtomicBool>,
+    /// 占空比 (0-100)
+    duty_percent: Arc<AtomicU32>,
+}
+
+impl LoadGenerator {
+    pub fn new() -> Self {
+        Self {
+            workers: Vec::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            duty_percent: Arc::new(AtomicU32::new(50)),
+        }
+    }
+
+    /// 是否有工作线程在运行
+    pub fn is_running(&self) -> bool {
+        !self.workers.is_empty()
+    }
+
+    /// 当前占空比
+    pub fn duty_percent(&self) -> u32 {
+        self.duty_percent.load(Ordering::Relaxed)
+    }
+
+    /// 调整占空比，运行中的线程在下一个周期生效
+    pub fn set_duty_percent(&mut self, percent: u32) {
+        self.duty_percent.store(percent.min(100), Ordering::Relaxed);
+    }
+
+    /// 在每个核心上启动一个绑定的工作线程（先停止已有的）
+    /// 任一线程无法绑定时全部停止并返回错误
+    pub fn start(&mut self, cores: &[usize]) -> Result<(), String> {
+        self.stop();
+        if cores.is_empty() {
+            return Err("至少选择一个核心".to_string());
+        }
+        self.stop = Arc::new(AtomicBool::new(false));
+
+        let (tx, rx) = mpsc::channel();
+        for &cpu in cores {
+            let shared = Arc::new(WorkerShared {
+                last_cpu: AtomicUsize::new(NO_CPU),
+                ..Default::default()
+            });
+            let stop = self.stop.clone();
+            let duty = self.duty_percent.clone();
+            let worker_shared = shared.clone();
+            let tx = tx.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("hexin-load-{}", cpu))
+                .spawn(move || {
+                    let tid = current_tid();
+                    let bound = set_process_affinity(tid, &[cpu]);
+                    let ok = bound.is_ok();
+                    let _ = tx.send((cpu, tid, bound));
+                    drop(tx);
+                    if ok {
+                        run_worker(cpu, &stop, &duty, &worker_shared);
+                    }
+                });
+            match spawned {
+                Ok(handle) => self.workers.push(Worker { cpu, tid: 0, shared, handle }),
+                Err(e) => {
+                    self.stop();
+                    return Err(format!("无法创建工作线程: {}", e));
+                }
+            }
+        }
+        drop(tx);
+
+        // 每个线程绑定后报告一次；线程异常退出时发送端随之销毁，不会一直等待
+        let mut error = None;
+        let mut reported = 0;
+        for (cpu, tid, bound) in rx.iter() {
+            reported += 1;
+            if let Some(worker) = self.workers.iter_mut().find(|w| w.cpu == cpu && w.tid == 0) {
+                worker.tid = tid;
+            }
+            if let Err(e) = bound {
+                error.get_or_insert(format!("CPU {}: {}", cpu, e));
+            }
+        }
+        if reported < cores.len() {
+            error.get_or_insert("工作线程异常退出".to_string());
+        }
+        match error {
+            Some(e) => {
+                self.stop();
+                Err(e)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 停止并等待所有工作线程退出
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for worker in self.workers.drain(..) {
+            let _ = worker.handle.join();
+        }
+    }
+
+    /// 各工作线程的状态
+    pub fn status(&self) -> Vec<WorkerStatus> {
+        self.workers
+            .iter()
+            .map(|w| {
+                let last_cpu = w.shared.last_cpu.load(Ordering::Relaxed);
+                WorkerStatus {
+                    cpu: w.cpu,
+                    tid: w.tid,
+                    last_cpu: (last_cpu != NO_CPU).then_some(last_cpu),
+                    off_target: w.shared.off_target.load(Ordering::Relaxed),
+                    samples: w.shared.samples.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for LoadGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LoadGenerator {
+    /// 工作线程不会比生成器活得更久
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 忙 / 闲循环：每个周期先忙等 duty% 的时间，其余时间睡眠；每个周期记录一次运行 CPU
+fn run_worker(cpu: usize, stop: &AtomicBool, duty: &AtomicU32, shared: &WorkerShared) {
+    while !stop.load(Ordering::Relaxed) {
+        let period_start = Instant::now();
+        let busy = DUTY_PERIOD.mul_f64(f64::from(duty.load(Ordering::Relaxed).min(100)) / 100.0);
+        while period_start.elapsed() < busy && !stop.load(Ordering::Relaxed) {
+            std::hint::spin_loop();
+        }
+        if let Some(running) = current_cpu() {
+            shared.last_cpu.store(running, Ordering::Relaxed);
+            if running != cpu {
+                shared.off_target.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        shared.samples.fetch_add(1, Ordering::Relaxed);
+        // 分段睡眠，让停止请求尽快生效
+        while period_start.elapsed() < DUTY_PERIOD && !stop.load(Ordering::Relaxed) {
+            thread::sleep((DUTY_PERIOD - period_start.elapsed()).min(Duration::from_millis(10)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_load_generator() {
+        let mut generator = LoadGenerator::new();
+        assert!(generator.start(&[]).is_err());
+
+        generator.set_duty_percent(150);
+        assert_eq!(generator.duty_percent(), 100);
+        generator.set_duty_percent(20);
+        // 使用测试进程允许运行的第一个核心
+        let cpu = crate::system::get_process_affinity(0, 1024)[0];
+        generator.start(&[cpu]).unwrap();
+        assert!(generator.is_running());
+        thread::sleep(Duration::from_millis(250));
+
+        let status = generator.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].cpu, cpu);
+        assert!(status[0].tid > 0);
+        assert!(status[0].samples > 0);
+        // 线程只在绑定的核心上运行
+        assert_eq!(status[0].last_cpu, Some(cpu));
+        assert_eq!(status[0].off_target, 0);
+
+        generator.stop();
+        assert!(!generator.is_running());
+        assert!(generator.status().is_empty());
+
+        // 不存在的核心无法绑定，已启动的线程全部停止
+        assert!(generator.start(&[cpu, 1023]).is_err());
+        assert!(!generator.is_running());
+    }
+}
@@ -0,0 +1,186 @@
+//! 进程内存特性检测模块
+//! 读取透明大页 (THP) 和 KSM 的进程级状态，以及系统级 THP 设置
+
+use std::fs;
+use std::path::Path;
+
+/// 系统级透明大页设置文件
+pub const THP_ENABLED_PATH: &str = "/sys/kernel/mm/transparent_hugepage/enabled";
+
+/// 透明大页模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThpMode {
+    /// 所有匿名映射都尝试使用大页
+    Always,
+    /// 仅 madvise(MADV_HUGEPAGE) 的区域使用大页
+    Madvise,
+    /// 禁用透明大页
+    Never,
+}
+
+impl ThpMode {
+    /// 从内核名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(ThpMode::Always),
+            "madvise" => Some(ThpMode::Madvise),
+            "never" => Some(ThpMode::Never),
+            _ => None,
+        }
+    }
+
+    /// 内核使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThpMode::Always => "always",
+            ThpMode::Madvise => "madvise",
+            ThpMode::Never => "never",
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ThpMode::Always => "always (始终启用)",
+            ThpMode::Madvise => "madvise (按需)",
+            ThpMode::Never => "never (禁用)",
+        }
+    }
+}
+
+/// 系统级透明大页设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThpSetting {
+    /// 当前模式
+    pub current: ThpMode,
+    /// 内核支持的模式
+    pub available: Vec<ThpMode>,
+}
+
+/// 进程内存特性
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessMemoryFeatures {
+    /// 匿名大页使用量 (KB)
+    pub anon_huge_pages_kb: u64,
+    /// 是否有适用 THP 的映射（较新内核的 smaps_rollup 不提供）
+    pub thp_eligible: Option<bool>,
+    /// KSM 合并的页数（内核 6.1+）
+    pub ksm_merging_pages: Option<u64>,
+}
+
+/// 解析 THP 设置 (如 "always [madvise] never")
+pub fn parse_thp_setting(s: &str) -> Option<ThpSetting> {
+    let mut current = None;
+    let mut available = Vec::new();
+
+    for token in s.split_whitespace() {
+        let (name, selected) = match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(name) => (name, true),
+            None => (token, false),
+        };
+        if let Some(mode) = ThpMode::from_name(name) {
+            if selected {
+                current = Some(mode);
+            }
+            available.push(mode);
+        }
+    }
+
+    current.map(|current| ThpSetting { current, available })
+}
+
+/// 读取系统级 THP 设置
+pub fn read_thp_setting() -> Option<ThpSetting> {
+    fs::read_to_string(THP_ENABLED_PATH)
+        .ok()
+        .and_then(|s| parse_thp_setting(&s))
+}
+
+/// 将 THP 模式写入指定设置文件
+pub fn write_thp_setting(path: &Path, mode: ThpMode) -> Result<(), String> {
+    fs::write(path, mode.as_str())
+        .map_err(|e| format!("设置透明大页模式失败: {} (可能需要 root 权限)", e))
+}
+
+/// 设置系统级 THP 模式
+pub fn set_thp_mode(mode: ThpMode) -> Result<(), String> {
+    write_thp_setting(Path::new(THP_ENABLED_PATH), mode)
+}
+
+/// 解析 /proc/[pid]/smaps_rollup，返回 (AnonHugePages KB, THPeligible)
+pub fn parse_smaps_rollup(content: &str) -> (u64, Option<bool>) {
+    let mut anon_huge_pages_kb = 0;
+    let mut thp_eligible = None;
+
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "AnonHugePages" => {
+                    anon_huge_pages_kb = value
+                        .trim_end_matches("kB")
+                        .trim()
+                        .parse()
+                        .unwrap_or(0);
+                }
+                "THPeligible" => {
+                    thp_eligible = value.parse::<u8>().ok().map(|v| v != 0);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    (anon_huge_pages_kb, thp_eligible)
+}
+
+/// 读取进程的内存特性，无权限或进程已退出时返回 None
+pub fn read_process_memory_features(pid: u32) -> Option<ProcessMemoryFeatures> {
+    let content = fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok()?;
+    let (anon_huge_pages_kb, thp_eligible) = parse_smaps_rollup(&content);
+
+    let ksm_merging_pages = fs::read_to_string(format!("/proc/{}/ksm_merging_pages", pid))
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+
+    Some(ProcessMemoryFeatures {
+        anon_huge_pages_kb,
+        thp_eligible,
+        ksm_merging_pages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thp_setting() {
+        let setting = parse_thp_setting("always [madvise] never\n").unwrap();
+        assert_eq!(setting.current, ThpMode::Madvise);
+        assert_eq!(setting.available, vec![ThpMode::Always, ThpMode::Madvise, ThpMode::Never]);
+
+        assert_eq!(parse_thp_setting("[always] madvise never").unwrap().current, ThpMode::Always);
+        assert_eq!(parse_thp_setting("always madvise never"), None);
+    }
+
+    #[test]
+    fn test_parse_smaps_rollup() {
+        let content = "\
+55d0c0a00000-7ffd5b9f3000 ---p 00000000 00:00 0                          [rollup]
+Rss:              123456 kB
+AnonHugePages:     40960 kB
+THPeligible:    1
+";
+        assert_eq!(parse_smaps_rollup(content), (40960, Some(true)));
+        assert_eq!(parse_smaps_rollup("Rss: 10 kB\nAnonHugePages: 0 kB\n"), (0, None));
+    }
+
+    #[test]
+    fn test_write_thp_setting() {
+        let path = std::env::temp_dir().join(format!("hexin_thp_test_{}", std::process::id()));
+        write_thp_setting(&path, ThpMode::Never).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "never");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,7 +1,89 @@
+pub mod apply;
+pub mod category;
+pub mod cgroup;
+pub mod container;
+pub mod core_fit;
 pub mod cpu_info;
+pub mod cpufreq;
+pub mod cstate;
+pub mod deferred;
+pub mod details;
+pub mod dropin;
+pub mod focus;
+pub mod game;
+pub mod gpu;
+pub mod health;
+pub mod hotkey;
+pub mod inheritance;
+pub mod instance;
+pub mod ioprio;
+pub mod limitations;
+pub mod loadgen;
+pub mod memory;
+pub mod origin;
+pub mod parking;
+pub mod pinned;
+pub mod preset;
+pub mod pressure;
+pub mod privilege;
 pub mod process;
+pub mod procstat;
+pub mod profile;
+pub mod report;
+pub mod rules;
+pub mod sched_debug;
 pub mod scheduler;
+pub mod script;
+pub mod serve;
+pub mod session;
+pub mod taskstats;
+pub mod thermal;
+pub mod timetable;
+pub mod tray;
+pub mod underclock;
+pub mod vulnerability;
 
+pub use apply::*;
+pub use category::*;
+pub use cgroup::*;
+pub use container::*;
+pub use core_fit::*;
 pub use cpu_info::*;
+pub use cpufreq::*;
+pub use cstate::*;
+pub use deferred::*;
+pub use details::*;
+pub use dropin::*;
+pub use focus::*;
+pub use game::*;
+pub use gpu::*;
+pub use health::*;
+pub use hotkey::*;
+pub use inheritance::*;
+pub use instance::*;
+pub use ioprio::*;
+pub use limitations::*;
+pub use loadgen::*;
+pub use memory::*;
+pub use origin::*;
+pub use parking::*;
+pub use pinned::*;
+pub use preset::*;
+pub use pressure::*;
+pub use privilege::*;
 pub use process::*;
+pub use procstat::*;
+pub use profile::*;
+pub use report::*;
+pub use rules::*;
+pub use sched_debug::*;
 pub use scheduler::*;
+pub use script::*;
+pub use serve::*;
+pub use session::*;
+pub use taskstats::*;
+pub use thermal::*;
+pub use timetable::*;
+pub use tray::*;
+pub use underclock::*;
+pub use vulnerability::*;
@@ -0,0 +1,128 @@
+//! 进程来源分类 - 根据 systemd cgroup 层级区分用户应用、用户服务和系统服务
+
+use serde::{Deserialize, Serialize};
+
+use super::read_process_cgroup;
+
+/// 进程来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProcessOrigin {
+    /// 用户启动的应用（user@.service 下的 app.slice，或登录会话 scope）
+    UserApp,
+    /// systemd --user 管理的服务
+    UserService,
+    /// system.slice 下的系统服务
+    SystemService,
+    /// 内核线程
+    Kernel,
+    /// 无法判断（非 systemd 系统或读取 cgroup 失败）
+    #[default]
+    Unknown,
+}
+
+impl ProcessOrigin {
+    /// 所有来源（筛选条显示顺序）
+    pub const ALL: [ProcessOrigin; 5] = [
+        ProcessOrigin::UserApp,
+        ProcessOrigin::UserService,
+        ProcessOrigin::SystemService,
+        ProcessOrigin::Kernel,
+        ProcessOrigin::Unknown,
+    ];
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ProcessOrigin::UserApp => "用户应用",
+            ProcessOrigin::UserService => "用户服务",
+            ProcessOrigin::SystemService => "系统服务",
+            ProcessOrigin::Kernel => "内核",
+            ProcessOrigin::Unknown => "未知",
+        }
+    }
+}
+
+/// 根据 cgroup v2 路径判断进程来源（不处理内核线程，它们位于根 cgroup）
+pub fn classify_cgroup(path: &str) -> ProcessOrigin {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+    // systemd --user 实例：/user.slice/user-1000.slice/user@1000.service/...
+    if let Some(i) = components.iter().position(|c| c.starts_with("user@") && c.ends_with(".service")) {
+        return match components.get(i + 1) {
+            Some(&"app.slice") => match components.get(i + 2) {
+                // app.slice 下的 .service 是用户级守护进程（如 xdg-desktop-portal）
+                Some(unit) if unit.ends_with(".service") && !unit.starts_with("app-") => ProcessOrigin::UserService,
+                _ => ProcessOrigin::UserApp,
+            },
+            _ => ProcessOrigin::UserService,
+        };
+    }
+
+    match components.first() {
+        // 登录会话（tty / ssh / 旧式 X 会话）中启动的进程
+        Some(&"user.slice") if components.iter().any(|c| c.starts_with("session-") && c.ends_with(".scope")) => {
+            ProcessOrigin::UserApp
+        }
+        Some(&"system.slice") | Some(&"init.scope") | Some(&"machine.slice") => ProcessOrigin::SystemService,
+        _ => ProcessOrigin::Unknown,
+    }
+}
+
+/// 检测进程来源
+pub fn detect_origin(pid: u32, kernel_thread: bool) -> ProcessOrigin {
+    if kernel_thread {
+        return ProcessOrigin::Kernel;
+    }
+    read_process_cgroup(pid)
+        .map(|path| classify_cgroup(&path))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "/user.slice/user-1000.slice/user@1000.service";
+
+    #[test]
+    fn test_classify_user_processes() {
+        let app = format!("{}/app.slice/app-org.gnome.Terminal.slice/vte-spawn-1234.scope", USER);
+        assert_eq!(classify_cgroup(&app), ProcessOrigin::UserApp);
+        let flatpak = format!("{}/app.slice/app-flatpak-org.mozilla.firefox-5678.scope", USER);
+        assert_eq!(classify_cgroup(&flatpak), ProcessOrigin::UserApp);
+        let launched = format!("{}/app.slice/app-gnome-code-4321.scope", USER);
+        assert_eq!(classify_cgroup(&launched), ProcessOrigin::UserApp);
+        assert_eq!(
+            classify_cgroup("/user.slice/user-1000.slice/session-2.scope"),
+            ProcessOrigin::UserApp
+        );
+
+        let portal = format!("{}/app.slice/xdg-desktop-portal.service", USER);
+        assert_eq!(classify_cgroup(&portal), ProcessOrigin::UserService);
+        let pipewire = format!("{}/session.slice/pipewire.service", USER);
+        assert_eq!(classify_cgroup(&pipewire), ProcessOrigin::UserService);
+        let shell = format!("{}/session.slice/org.gnome.Shell@wayland.service", USER);
+        assert_eq!(classify_cgroup(&shell), ProcessOrigin::UserService);
+        let manager = format!("{}/init.scope", USER);
+        assert_eq!(classify_cgroup(&manager), ProcessOrigin::UserService);
+    }
+
+    #[test]
+    fn test_classify_system_processes() {
+        assert_eq!(classify_cgroup("/system.slice/sshd.service"), ProcessOrigin::SystemService);
+        assert_eq!(
+            classify_cgroup("/system.slice/system-getty.slice/getty@tty1.service"),
+            ProcessOrigin::SystemService
+        );
+        assert_eq!(classify_cgroup("/init.scope"), ProcessOrigin::SystemService);
+        assert_eq!(
+            classify_cgroup("/machine.slice/machine-my\\x2dvm.scope/payload"),
+            ProcessOrigin::SystemService
+        );
+
+        // 非 systemd 系统或 cgroup v1
+        assert_eq!(classify_cgroup("/"), ProcessOrigin::Unknown);
+        assert_eq!(classify_cgroup("/docker/3f2a1b9c4d5e"), ProcessOrigin::Unknown);
+        assert_eq!(classify_cgroup("/user.slice/user-1000.slice"), ProcessOrigin::Unknown);
+    }
+}
@@ -0,0 +1,187 @@
+//! 核心"停放"建议
+//! 根据历史窗口内的负载分布，给出离线空闲核心或收拢进程以节能的建议
+//!
+//! 启发式规则（刻意保守）：
+//! - 历史样本不足 `MIN_SAMPLES` 时不给建议
+//! - 核心在窗口内 95 分位使用率低于 `IDLE_P95_PERCENT` 才视为持续空闲
+//! - 所需核心数按平均总负载以 `TARGET_UTILIZATION` 的利用率估算，并且不少于峰值时的总负载
+//! - 仅当空闲核心不少于 `MIN_PARK_CORES` 个且剩余核心足以承载负载时才建议离线；CPU 0 始终保留
+//! - 总负载较低但分散在很多核心上时，建议收拢到较少的核心；有多个 L3（CCD）时目标核心尽量落在
+//!   当前负载最高的同一个 CCD 内，避免跨 CCD 访问缓存
+
+use super::{format_cpu_list, l3_containing, CpuInfo, L3CacheInfo};
+use crate::utils::CpuHistory;
+
+/// 给出建议所需的最少样本数
+const MIN_SAMPLES: usize = 60;
+/// 持续空闲的 95 分位使用率阈值 (%)
+pub const IDLE_P95_PERCENT: f32 = 5.0;
+/// 估算所需核心数时的目标利用率
+const TARGET_UTILIZATION: f32 = 0.6;
+/// 建议离线的最少核心数
+const MIN_PARK_CORES: usize = 4;
+/// 视为"有负载"的平均使用率阈值 (%)
+const ACTIVE_MEAN_PERCENT: f32 = 5.0;
+
+/// 单个核心在窗口内的统计
+struct CoreStats {
+    mean: f32,
+    p95: f32,
+}
+
+fn core_stats(samples: &[f32]) -> CoreStats {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let p95_index = ((sorted.len() as f32 * 0.95).ceil() as usize).saturating_sub(1);
+    CoreStats {
+        mean: samples.iter().sum::<f32>() / samples.len().max(1) as f32,
+        p95: sorted.get(p95_index).copied().unwrap_or(0.0),
+    }
+}
+
+/// 收拢的目标核心（升序）：按负载从高到低选取 CCD，直到核心数足以容纳 `needed`，
+/// 再在这些 CCD 中取负载最高的 `needed` 个核心；没有 L3 信息时取编号最小的核心
+fn consolidation_target(stats: &[CoreStats], needed: usize, l3_caches: &[L3CacheInfo]) -> Vec<usize> {
+    if l3_caches.len() < 2 {
+        return (0..needed.min(stats.len())).collect();
+    }
+    let load = |cpus: &[usize]| cpus.iter().filter_map(|&cpu| stats.get(cpu)).map(|s| s.mean).sum::<f32>();
+    let mut caches: Vec<&L3CacheInfo> = l3_caches.iter().collect();
+    caches.sort_by(|a, b| load(&b.shared_cpus).partial_cmp(&load(&a.shared_cpus)).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut candidates = Vec::new();
+    for cache in caches {
+        if candidates.len() >= needed {
+            break;
+        }
+        candidates.extend(cache.shared_cpus.iter().copied().filter(|&cpu| cpu < stats.len()));
+    }
+    candidates.sort_by(|&a, &b| {
+        stats[b].mean.partial_cmp(&stats[a].mean).unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(&b))
+    });
+    candidates.truncate(needed);
+    candidates.sort_unstable();
+    candidates
+}
+
+/// 根据每个核心的使用率样本（按 CPU ID 索引）和 L3 缓存拓扑生成建议
+pub fn recommend_from_samples(samples: &[Vec<f32>], l3_caches: &[L3CacheInfo]) -> Vec<String> {
+    let sample_count = samples.iter().map(|s| s.len()).min().unwrap_or(0);
+    if samples.is_empty() || sample_count < MIN_SAMPLES {
+        return Vec::new();
+    }
+
+    let stats: Vec<CoreStats> = samples.iter().map(|s| core_stats(s)).collect();
+    let total_mean: f32 = stats.iter().map(|s| s.mean).sum();
+    let total_p95: f32 = stats.iter().map(|s| s.p95).sum();
+    let needed = ((total_mean / 100.0 / TARGET_UTILIZATION).ceil() as usize)
+        .max((total_p95 / 100.0).ceil() as usize)
+        .max(1);
+
+    let mut hints = Vec::new();
+
+    let idle: Vec<usize> = stats
+        .iter()
+        .enumerate()
+        .filter(|(cpu, s)| *cpu != 0 && s.p95 < IDLE_P95_PERCENT)
+        .map(|(cpu, _)| cpu)
+        .collect();
+    let busy_count = samples.len() - idle.len();
+    if idle.len() >= MIN_PARK_CORES && busy_count >= needed {
+        hints.push(format!(
+            "负载集中在 {} 核，可考虑离线 CPU {}",
+            busy_count,
+            format_cpu_list(&idle)
+        ));
+    }
+
+    let active = stats.iter().filter(|s| s.mean >= ACTIVE_MEAN_PERCENT).count();
+    if active >= needed * 2 && active >= MIN_PARK_CORES {
+        let target = consolidation_target(&stats, needed, l3_caches);
+        let mut hint = format!(
+            "负载分散在 {} 个核心上，但总量约相当于 {:.1} 核，可考虑将进程亲和性收拢到 CPU {}",
+            active,
+            total_mean / 100.0,
+            format_cpu_list(&target)
+        );
+        if l3_caches.len() > 1 && l3_containing(&target, l3_caches).is_some() {
+            hint.push_str("（同一 CCD，共享 L3）");
+        }
+        hints.push(hint);
+    }
+
+    hints
+}
+
+/// 基于历史窗口生成节能建议
+pub fn recommend_consolidation(cpu_info: &CpuInfo, history: &CpuHistory) -> Vec<String> {
+    let samples: Vec<Vec<f32>> = (0..cpu_info.logical_cores)
+        .map(|cpu| history.core_history(cpu).unwrap_or_default())
+        .collect();
+    recommend_from_samples(&samples, &cpu_info.l3_caches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(cores: &[f32]) -> Vec<Vec<f32>> {
+        cores.iter().map(|&u| vec![u; MIN_SAMPLES]).collect()
+    }
+
+    #[test]
+    fn test_recommend_parking_idle_cores() {
+        let mut usage = vec![50.0, 40.0, 30.0, 20.0];
+        usage.extend([1.0; 12]);
+        let hints = recommend_from_samples(&constant(&usage), &[]);
+        assert_eq!(hints, vec!["负载集中在 4 核，可考虑离线 CPU 4-15".to_string()]);
+    }
+
+    #[test]
+    fn test_recommend_consolidation_spread_load() {
+        // 8 个核心各 10%，总量不足 1 核
+        let hints = recommend_from_samples(&constant(&[10.0; 8]), &[]);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("收拢到 CPU 0-1"), "{}", hints[0]);
+    }
+
+    fn ccd(id: u32, cpus: std::ops::Range<usize>) -> L3CacheInfo {
+        L3CacheInfo { id, size_kb: 32768, shared_cpus: cpus.collect(), is_vcache: false }
+    }
+
+    #[test]
+    fn test_consolidation_respects_ccd() {
+        // 负载分散在第二个 CCD 上：收拢目标留在该 CCD 内，而不是 CPU 0-1
+        let mut usage = vec![0.0; 8];
+        usage.extend([10.0; 8]);
+        let caches = [ccd(0, 0..8), ccd(1, 8..16)];
+        let hints = recommend_from_samples(&constant(&usage), &caches);
+        assert_eq!(hints.len(), 2);
+        assert!(hints[1].contains("收拢到 CPU 8-9（同一 CCD，共享 L3）"), "{}", hints[1]);
+
+        // 需要的核心数超过一个 CCD 时按负载顺序跨 CCD 选取负载最高的核心
+        let mut usage = vec![30.0; 4];
+        usage.extend([15.0; 4]);
+        usage.extend([20.0; 4]);
+        let caches = [ccd(0, 0..4), ccd(1, 4..8), ccd(2, 8..12)];
+        let stats: Vec<CoreStats> = constant(&usage).iter().map(|s| core_stats(s)).collect();
+        assert_eq!(consolidation_target(&stats, 6, &caches), vec![0, 1, 2, 3, 8, 9]);
+    }
+
+    #[test]
+    fn test_no_recommendation_when_busy_or_short() {
+        assert!(recommend_from_samples(&constant(&[90.0; 8]), &[]).is_empty());
+
+        // 偶发尖峰的核心不算空闲
+        let mut samples = constant(&[50.0, 50.0, 1.0, 1.0, 1.0, 1.0]);
+        for core in samples.iter_mut().skip(2) {
+            for v in core.iter_mut().step_by(5) {
+                *v = 90.0;
+            }
+        }
+        assert!(recommend_from_samples(&samples, &[]).is_empty());
+
+        let short: Vec<Vec<f32>> = vec![vec![1.0; 10]; 16];
+        assert!(recommend_from_samples(&short, &[]).is_empty());
+    }
+}
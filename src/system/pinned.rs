@@ -0,0 +1,292 @@
+//! 固定的监视进程 - 监视列表按进程名和启动时间保存到配置，重启 hexin 后重新找到同一进程
+//! 进程重新启动（同名但启动时间不同）时不自动绑定，等待用户确认
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::{ProcessInfo, ProcessManager};
+
+/// 配置中保存的固定进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedProcess {
+    pub name: String,
+    /// 启动时间（与 ProcessInfo::start_time 相同，用于区分同名进程和 PID 复用）
+    pub start_time: u64,
+}
+
+impl PinnedProcess {
+    pub fn of(process: &ProcessInfo) -> Self {
+        Self {
+            name: process.name.clone(),
+            start_time: process.start_time,
+        }
+    }
+
+    /// 是否为同一进程（名称和启动时间都相同）
+    pub fn is_same(&self, process: &ProcessInfo) -> bool {
+        process.name == self.name && process.start_time == self.start_time
+    }
+}
+
+/// 固定进程在当前进程中的对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinMatch {
+    /// 同一进程仍在运行
+    Same(u32),
+    /// 同名进程已重新启动（取最早启动的一个，通常是主进程）
+    Restarted(u32),
+    /// 没有同名进程
+    Missing,
+}
+
+/// 在进程中查找固定的进程
+pub fn find_pinned<'a>(pin: &PinnedProcess, processes: impl IntoIterator<Item = &'a ProcessInfo>) -> PinMatch {
+    let mut restarted: Option<&ProcessInfo> = None;
+    for process in processes.into_iter().filter(|p| p.name == pin.name) {
+        if pin.is_same(process) {
+            return PinMatch::Same(process.pid);
+        }
+        if restarted.is_none_or(|r| (process.start_time, process.pid) < (r.start_time, r.pid)) {
+            restarted = Some(process);
+        }
+    }
+    restarted.map_or(PinMatch::Missing, |p| PinMatch::Restarted(p.pid))
+}
+
+/// 固定进程的当前状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinState {
+    /// 已绑定到监视列表中的 PID
+    Bound(u32),
+    /// 同名进程已重新启动，等待确认是否绑定
+    Restarted(u32),
+    /// 进程未运行
+    Missing,
+}
+
+/// 一个固定进程
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinEntry {
+    pub pin: PinnedProcess,
+    pub state: PinState,
+}
+
+/// 监视列表的持久化部分：与进程管理器的监视列表同步，并记录需要保存到配置的固定进程
+#[derive(Debug, Default)]
+pub struct PinnedWatchlist {
+    entries: Vec<PinEntry>,
+    /// 已退出、不应视为用户移除的 PID
+    exited: HashSet<u32>,
+    /// 用户拒绝重新绑定的 PID
+    declined: HashSet<u32>,
+    /// 固定的进程有变化，需要保存配置
+    changed: bool,
+}
+
+impl PinnedWatchlist {
+    /// 从配置恢复（首次同步时绑定仍在运行的同一进程）
+    pub fn new(pins: Vec<PinnedProcess>) -> Self {
+        Self {
+            entries: pins.into_iter().map(|pin| PinEntry { pin, state: PinState::Missing }).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn entries(&self) -> &[PinEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 记录已退出（从监视列表移除）的进程，它们的固定项保留
+    pub fn mark_exited(&mut self, pids: impl IntoIterator<Item = u32>) {
+        self.exited.extend(pids);
+    }
+
+    /// 与进程管理器的监视列表同步
+    /// - 用户移出监视列表的进程取消固定，新加入的进程被固定
+    /// - `rescan` 为 true 时（完整刷新之后）为未运行的固定项查找同一进程或重新启动的同名进程
+    pub fn sync(&mut self, manager: &mut ProcessManager, rescan: bool) {
+        let exited = std::mem::take(&mut self.exited);
+        let before = self.entries.len();
+        self.entries.retain_mut(|entry| match entry.state {
+            PinState::Bound(pid) if !manager.is_watched(pid) => {
+                if !exited.contains(&pid) {
+                    return false;
+                }
+                entry.state = PinState::Missing;
+                true
+            }
+            _ => true,
+        });
+        self.changed |= self.entries.len() != before;
+
+        // 已绑定的进程执行了 exec（名称变化）：按新名称保存
+        for entry in &mut self.entries {
+            if let PinState::Bound(pid) = entry.state {
+                if let Some(process) = manager.process(pid).filter(|p| p.name != entry.pin.name) {
+                    entry.pin = PinnedProcess::of(process);
+                    self.changed = true;
+                }
+            }
+        }
+
+        for &pid in manager.watched() {
+            if self.entries.iter().any(|e| e.state == PinState::Bound(pid)) {
+                continue;
+            }
+            if let Some(process) = manager.process(pid) {
+                let pin = PinnedProcess::of(process);
+                // 同一进程的未绑定固定项（如确认前被手动加入）直接绑定
+                match self.entries.iter_mut().find(|e| e.pin == pin) {
+                    Some(entry) => entry.state = PinState::Bound(pid),
+                    None => {
+                        self.entries.push(PinEntry { pin, state: PinState::Bound(pid) });
+                        self.changed = true;
+                    }
+                }
+            }
+        }
+
+        if !rescan {
+            return;
+        }
+        for entry in self.entries.iter_mut().filter(|e| !matches!(e.state, PinState::Bound(_))) {
+            entry.state = match find_pinned(&entry.pin, manager.processes()) {
+                PinMatch::Same(pid) => {
+                    manager.watch(pid);
+                    PinState::Bound(pid)
+                }
+                PinMatch::Restarted(pid) if !self.declined.contains(&pid) => PinState::Restarted(pid),
+                _ => PinState::Missing,
+            };
+        }
+    }
+
+    /// 确认把固定项绑定到重新启动的进程
+    pub fn confirm_rebind(&mut self, index: usize, manager: &mut ProcessManager) {
+        let Some(entry) = self.entries.get_mut(index) else {
+            return;
+        };
+        let PinState::Restarted(pid) = entry.state else {
+            return;
+        };
+        if let Some(process) = manager.process(pid) {
+            entry.pin = PinnedProcess::of(process);
+            entry.state = PinState::Bound(pid);
+            manager.watch(pid);
+            self.changed = true;
+        }
+    }
+
+    /// 拒绝绑定到重新启动的进程（该 PID 不再提示）
+    pub fn decline_rebind(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            if let PinState::Restarted(pid) = entry.state {
+                self.declined.insert(pid);
+                entry.state = PinState::Missing;
+            }
+        }
+    }
+
+    /// 取消固定（已绑定的进程同时移出监视列表）
+    pub fn unpin(&mut self, index: usize, manager: &mut ProcessManager) {
+        if index >= self.entries.len() {
+            return;
+        }
+        let entry = self.entries.remove(index);
+        if let PinState::Bound(pid) = entry.state {
+            manager.unwatch(pid);
+        }
+        self.changed = true;
+    }
+
+    /// 固定的进程有变化时取出要保存的列表
+    pub fn take_changed(&mut self) -> Option<Vec<PinnedProcess>> {
+        std::mem::take(&mut self.changed).then(|| self.entries.iter().map(|e| e.pin.clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+
+    fn started(pid: u32, name: &str, start_time: u64) -> ProcessInfo {
+        let mut process = process(pid, name, 0.0, 0);
+        process.start_time = start_time;
+        process
+    }
+
+    fn pin(name: &str, start_time: u64) -> PinnedProcess {
+        PinnedProcess { name: name.to_string(), start_time }
+    }
+
+    #[test]
+    fn test_find_pinned() {
+        let processes = [started(10, "game", 500), started(11, "game", 300), started(12, "steam", 100)];
+        assert_eq!(find_pinned(&pin("steam", 100), &processes), PinMatch::Same(12));
+        // 同名进程中最早启动的一个
+        assert_eq!(find_pinned(&pin("game", 100), &processes), PinMatch::Restarted(11));
+        assert_eq!(find_pinned(&pin("game", 500), &processes), PinMatch::Same(10));
+        assert_eq!(find_pinned(&pin("blender", 100), &processes), PinMatch::Missing);
+    }
+
+    #[test]
+    fn test_pinned_watchlist_sync() {
+        let mut manager = ProcessManager::new(1);
+        manager.insert_process(started(10, "game", 500));
+        manager.insert_process(started(20, "steam", 100));
+        manager.insert_process(started(30, "editor", 50));
+
+        // 配置中的同一进程自动绑定，重新启动的进程等待确认
+        let mut pinned = PinnedWatchlist::new(vec![pin("steam", 100), pin("game", 400), pin("blender", 1)]);
+        pinned.sync(&mut manager, true);
+        let states: Vec<PinState> = pinned.entries().iter().map(|e| e.state).collect();
+        assert_eq!(states, vec![PinState::Bound(20), PinState::Restarted(10), PinState::Missing]);
+        assert_eq!(manager.watched(), &[20]);
+        assert_eq!(pinned.take_changed(), None);
+
+        pinned.confirm_rebind(1, &mut manager);
+        assert_eq!(pinned.entries()[1].pin, pin("game", 500));
+        assert!(manager.is_watched(10));
+        assert!(pinned.take_changed().is_some());
+
+        // 从右键菜单加入的进程被固定
+        manager.watch(30);
+        pinned.sync(&mut manager, false);
+        assert_eq!(pinned.entries().last().map(|e| e.state), Some(PinState::Bound(30)));
+        assert_eq!(pinned.take_changed().map(|pins| pins.len()), Some(4));
+
+        // exec 后按新名称保存
+        pinned.take_changed();
+        manager.insert_process(started(30, "editor-bin", 50));
+        pinned.sync(&mut manager, false);
+        assert_eq!(pinned.entries()[3].pin, pin("editor-bin", 50));
+        assert!(pinned.take_changed().is_some());
+
+        // 已退出的进程保留固定项，用户移出的进程取消固定
+        manager.unwatch(10);
+        pinned.mark_exited([10]);
+        manager.unwatch(30);
+        pinned.sync(&mut manager, false);
+        let names: Vec<&str> = pinned.entries().iter().map(|e| e.pin.name.as_str()).collect();
+        assert_eq!(names, vec!["steam", "game", "blender"]);
+        assert_eq!(pinned.entries()[1].state, PinState::Missing);
+
+        // 拒绝后同一 PID 不再提示
+        manager.insert_process(started(40, "blender", 9));
+        pinned.sync(&mut manager, true);
+        assert_eq!(pinned.entries()[2].state, PinState::Restarted(40));
+        pinned.decline_rebind(2);
+        pinned.sync(&mut manager, true);
+        assert_eq!(pinned.entries()[2].state, PinState::Missing);
+
+        pinned.take_changed();
+        pinned.unpin(0, &mut manager);
+        assert!(!manager.is_watched(20));
+        assert_eq!(pinned.take_changed(), Some(vec![pin("game", 500), pin("blender", 1)]));
+    }
+}
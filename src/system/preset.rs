@@ -0,0 +1,180 @@
+//! 预设存储 - 内置预设和用户自定义预设的管理与持久化
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::{CoreTopology, SchedulePreset};
+
+/// 自定义预设文件格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PresetFile {
+    #[serde(default)]
+    presets: Vec<SchedulePreset>,
+}
+
+/// 预设存储
+#[derive(Debug, Clone)]
+pub struct PresetStore {
+    /// 所有预设（内置在前，自定义在后）
+    presets: Vec<SchedulePreset>,
+}
+
+impl PresetStore {
+    /// 由内置预设和自定义预设创建
+    pub fn new(builtin: Vec<SchedulePreset>, custom: Vec<SchedulePreset>) -> Self {
+        let mut presets = builtin;
+        presets.extend(custom.into_iter().map(|mut p| {
+            p.is_builtin = false;
+            p
+        }));
+        Self { presets }
+    }
+
+    /// 获取自定义预设文件路径
+    fn presets_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("presets.toml"))
+    }
+
+    /// 加载内置预设和已保存的自定义预设
+    pub fn load(topology: &CoreTopology) -> Self {
+        let builtin = SchedulePreset::builtin_presets(topology);
+        let custom = Self::presets_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str::<PresetFile>(&content).ok())
+            .map(|file| file.presets)
+            .unwrap_or_default();
+        Self::new(builtin, custom)
+    }
+
+    /// 保存自定义预设
+    pub fn save(&self) {
+        if let Some(path) = Self::presets_path() {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let file = PresetFile {
+                presets: self.presets.iter().filter(|p| !p.is_builtin).cloned().collect(),
+            };
+            if let Ok(content) = toml::to_string_pretty(&file) {
+                let _ = fs::write(&path, content);
+            }
+        }
+    }
+
+    /// 所有预设
+    pub fn presets(&self) -> &[SchedulePreset] {
+        &self.presets
+    }
+
+    /// 自定义预设
+    pub fn custom(&self) -> Vec<SchedulePreset> {
+        self.presets.iter().filter(|p| !p.is_builtin).cloned().collect()
+    }
+
+    /// 替换内置预设（拓扑或保留核心变化时），自定义预设保留
+    pub fn set_builtin(&mut self, builtin: Vec<SchedulePreset>) {
+        self.presets.retain(|p| !p.is_builtin);
+        let custom = std::mem::take(&mut self.presets);
+        self.presets = builtin;
+        self.presets.extend(custom);
+    }
+
+    /// 替换全部自定义预设（内置预设保留）
+    pub fn set_custom(&mut self, custom: Vec<SchedulePreset>) {
+        self.presets.retain(|p| p.is_builtin);
+        self.presets.extend(custom.into_iter().map(|p| SchedulePreset { is_builtin: false, ..p }));
+    }
+
+    /// 添加自定义预设，名称为空或与已有预设重名时返回错误，成功时返回新预设的索引
+    pub fn add(&mut self, preset: SchedulePreset) -> Result<usize, String> {
+        let name = preset.name.trim();
+        if name.is_empty() {
+            return Err("预设名称不能为空".to_string());
+        }
+        if self.presets.iter().any(|p| p.name == name) {
+            return Err(format!("已存在名为 '{}' 的预设", name));
+        }
+        self.presets.push(SchedulePreset { name: name.to_string(), is_builtin: false, ..preset });
+        Ok(self.presets.len() - 1)
+    }
+
+    /// 复制指定预设为新的自定义预设，返回新预设的索引
+    pub fn duplicate(&mut self, index: usize) -> Option<usize> {
+        let copy = self.presets.get(index)?.duplicate();
+        self.presets.push(copy);
+        Some(self.presets.len() - 1)
+    }
+
+    /// 更新自定义预设（内置预设不可修改）
+    pub fn update(&mut self, index: usize, preset: SchedulePreset) -> bool {
+        match self.presets.get_mut(index) {
+            Some(existing) if !existing.is_builtin => {
+                *existing = SchedulePreset { is_builtin: false, ..preset };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 删除自定义预设（内置预设不可删除）
+    pub fn remove(&mut self, index: usize) -> bool {
+        if self.presets.get(index).is_some_and(|p| !p.is_builtin) {
+            self.presets.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_builtin_preset() {
+        let mut store = PresetStore::new(SchedulePreset::builtin_presets(&CoreTopology::single_package(4, &[0, 1])), Vec::new());
+        let builtin_count = store.presets().len();
+
+        let index = store.duplicate(0).unwrap();
+        assert_eq!(index, builtin_count);
+        let copy = store.presets()[index].clone();
+        assert!(!copy.is_builtin);
+        assert_eq!(copy.name, format!("{} (副本)", store.presets()[0].name));
+
+        // 副本可独立编辑，不影响原预设
+        let mut edited = copy.clone();
+        edited.priority = 7;
+        assert!(store.update(index, edited));
+        assert_eq!(store.presets()[index].priority, 7);
+        assert_ne!(store.presets()[0].priority, 7);
+
+        // 内置预设不可修改或删除
+        assert!(!store.update(0, copy));
+        assert!(!store.remove(0));
+        assert!(store.remove(index));
+        assert_eq!(store.presets().len(), builtin_count);
+    }
+
+    #[test]
+    fn test_add_preset() {
+        let mut store = PresetStore::new(SchedulePreset::builtin_presets(&CoreTopology::single_package(4, &[0, 1])), Vec::new());
+        let builtin = store.presets()[0].clone();
+        let preset = SchedulePreset {
+            name: " 编译核心 ".to_string(),
+            affinity_cores: Some(vec![2, 3]),
+            is_builtin: true,
+            ..builtin.clone()
+        };
+
+        let index = store.add(preset.clone()).unwrap();
+        assert_eq!(store.presets()[index].name, "编译核心");
+        assert!(!store.presets()[index].is_builtin);
+        assert_eq!(store.custom().len(), 1);
+
+        assert!(store.add(preset).is_err());
+        assert!(store.add(SchedulePreset { name: builtin.name.clone(), ..builtin.clone() }).is_err());
+        assert!(store.add(SchedulePreset { name: "  ".to_string(), ..builtin }).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Pressure Stall Information (PSI) 读取模块
+//! 通过 /proc/pressure/cpu 衡量 CPU 资源争用程度，并由 /proc/schedstat 统计全系统的运行延迟
+
+use std::fs;
+
+/// PSI 文件路径
+const CPU_PRESSURE_PATH: &str = "/proc/pressure/cpu";
+/// 调度统计文件路径（需要 CONFIG_SCHEDSTATS）
+const SCHEDSTAT_PATH: &str = "/proc/schedstat";
+
+/// 单行 PSI 统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PsiLine {
+    /// 10 秒平均阻塞比例 (%)
+    pub avg10: f32,
+    /// 60 秒平均阻塞比例 (%)
+    pub avg60: f32,
+    /// 300 秒平均阻塞比例 (%)
+    pub avg300: f32,
+    /// 累计阻塞时间 (微秒)
+    pub total_us: u64,
+}
+
+/// CPU 压力信息
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Psi {
+    /// 至少一个任务在等待 CPU
+    pub some: PsiLine,
+    /// 所有非空闲任务都在等待（部分内核对 CPU 不提供）
+    pub full: Option<PsiLine>,
+}
+
+impl Psi {
+    /// 按 some avg10 判断的严重程度 (0 = 正常, 1 = 偏高, 2 = 严重)
+    pub fn severity(&self) -> u8 {
+        if self.some.avg10 >= 25.0 {
+            2
+        } else if self.some.avg10 >= 5.0 {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// 解析单行 PSI (如 "some avg10=0.00 avg60=0.00 avg300=0.00 total=0")
+fn parse_psi_line(line: &str) -> Option<(&str, PsiLine)> {
+    let mut parts = line.split_whitespace();
+    let kind = parts.next()?;
+    let mut psi = PsiLine::default();
+
+    for part in parts {
+        let (key, value) = part.split_once('=')?;
+        match key {
+            "avg10" => psi.avg10 = value.parse().ok()?,
+            "avg60" => psi.avg60 = value.parse().ok()?,
+            "avg300" => psi.avg300 = value.parse().ok()?,
+            "total" => psi.total_us = value.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    Some((kind, psi))
+}
+
+/// 解析 /proc/pressure/cpu 内容
+pub fn parse_psi(content: &str) -> Option<Psi> {
+    let mut some = None;
+    let mut full = None;
+
+    for line in content.lines() {
+        match parse_psi_line(line) {
+            Some(("some", psi)) => some = Some(psi),
+            Some(("full", psi)) => full = Some(psi),
+            _ => {}
+        }
+    }
+
+    some.map(|some| Psi { some, full })
+}
+
+/// 读取 CPU 压力，内核或容器不支持 PSI 时返回 None
+pub fn read_cpu_pressure() -> Option<Psi> {
+    fs::read_to_string(CPU_PRESSURE_PATH)
+        .ok()
+        .and_then(|s| parse_psi(&s))
+}
+
+/// 解析 /proc/schedstat，返回所有 CPU 累计的运行延迟 (纳秒) 和 CPU 数
+/// 每个 "cpuN" 行的第 8 个数值是该 CPU 上任务在运行队列中等待的总时间
+pub fn parse_schedstat_run_delay(content: &str) -> Option<(u64, usize)> {
+    let mut total = 0u64;
+    let mut cpus = 0;
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        if !fields.next().is_some_and(|name| name.starts_with("cpu")) {
+            continue;
+        }
+        total += fields.nth(7)?.parse::<u64>().ok()?;
+        cpus += 1;
+    }
+    (cpus > 0).then_some((total, cpus))
+}
+
+/// 读取全系统累计运行延迟，内核未开启调度统计时返回 None
+pub fn read_schedstat_run_delay() -> Option<(u64, usize)> {
+    fs::read_to_string(SCHEDSTAT_PATH)
+        .ok()
+        .and_then(|s| parse_schedstat_run_delay(&s))
+}
+
+/// 全系统运行延迟采样器，由相邻两次累计值换算每核每秒的等待毫秒数
+#[derive(Debug, Default)]
+pub struct RunDelaySampler {
+    /// 上次的累计延迟 (纳秒)、CPU 数和采样时间 (单调秒)
+    last: Option<(u64, usize, f64)>,
+}
+
+impl RunDelaySampler {
+    /// 读取 /proc/schedstat 并返回最新速率
+    pub fn sample(&mut self, now: f64) -> Option<f32> {
+        match read_schedstat_run_delay() {
+            Some((total_ns, cpus)) => self.update(total_ns, cpus, now),
+            None => {
+                self.last = None;
+                None
+            }
+        }
+    }
+
+    /// 记录一次累计值，返回与上次之间每核每秒的等待毫秒数
+    /// 第一次采样、CPU 数变化（热插拔）或计数回退时返回 None
+    pub fn update(&mut self, total_ns: u64, cpus: usize, now: f64) -> Option<f32> {
+        let previous = self.last.replace((total_ns, cpus, now));
+        let (last_ns, last_cpus, last_time) = previous?;
+        let elapsed = now - last_time;
+        if last_cpus != cpus || total_ns < last_ns || elapsed <= 0.0 {
+            return None;
+        }
+        let delay_ms = (total_ns - last_ns) as f64 / 1_000_000.0;
+        Some((delay_ms / elapsed / cpus as f64) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_psi() {
+        let content = "\
+some avg10=1.53 avg60=0.87 avg300=0.42 total=123456789
+full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+";
+        let psi = parse_psi(content).unwrap();
+        assert_eq!(psi.some.avg10, 1.53);
+        assert_eq!(psi.some.avg60, 0.87);
+        assert_eq!(psi.some.avg300, 0.42);
+        assert_eq!(psi.some.total_us, 123456789);
+        assert_eq!(psi.full, Some(PsiLine::default()));
+
+        // 旧内核只有 some 行
+        let psi = parse_psi("some avg10=30.00 avg60=10.00 avg300=2.00 total=42").unwrap();
+        assert_eq!(psi.full, None);
+        assert_eq!(psi.severity(), 2);
+
+        assert_eq!(parse_psi(""), None);
+    }
+
+    #[test]
+    fn test_parse_schedstat_run_delay() {
+        let content = "\
+version 15
+timestamp 4295806946
+cpu0 0 0 1000 200 500 300 9000000000 2000000000 800
+domain0 00000003 10 10 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+cpu1 0 0 1000 200 500 300 8000000000 500000000 700
+";
+        assert_eq!(parse_schedstat_run_delay(content), Some((2_500_000_000, 2)));
+        assert_eq!(parse_schedstat_run_delay("version 15\ntimestamp 1\n"), None);
+        assert_eq!(parse_schedstat_run_delay("cpu0 1 2 3\n"), None);
+    }
+
+    #[test]
+    fn test_run_delay_sampler() {
+        let mut sampler = RunDelaySampler::default();
+        assert_eq!(sampler.update(1_000_000_000, 4, 10.0), None);
+        // 2 秒内 4 个核心共等待 400 ms = 每核 50 ms/s
+        assert_eq!(sampler.update(1_400_000_000, 4, 12.0), Some(50.0));
+        // CPU 数变化时重新开始
+        assert_eq!(sampler.update(1_500_000_000, 8, 13.0), None);
+        assert_eq!(sampler.update(1_500_000_000, 8, 14.0), Some(0.0));
+    }
+}
@@ -0,0 +1,296 @@
+//! 权限检测
+//! 判断 hexin 能否修改目标进程的调度设置（同一用户、root 或 CAP_SYS_NICE），
+//! 以及目标进程自身的 RLIMIT_NICE / RLIMIT_RTPRIO 是否允许请求的值
+
+use std::fs;
+
+/// CAP_SYS_RAWIO 在能力位图中的位置
+const CAP_SYS_RAWIO: u32 = 17;
+/// CAP_SYS_NICE 在能力位图中的位置
+const CAP_SYS_NICE: u32 = 23;
+
+/// hexin 自身的权限信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrivilegeInfo {
+    /// 有效用户 ID
+    pub euid: u32,
+    /// 拥有 CAP_SYS_NICE（root 默认拥有）
+    pub cap_sys_nice: bool,
+    /// 拥有 CAP_SYS_RAWIO（读取 MSR 需要）
+    pub cap_sys_rawio: bool,
+}
+
+impl PrivilegeInfo {
+    /// 读取当前进程的权限
+    pub fn detect() -> Self {
+        let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+        let euid = parse_status_uids(&status).map(|(_, euid)| euid).unwrap_or(u32::MAX);
+        let cap_eff = parse_cap_eff(&status).unwrap_or(0);
+        PrivilegeInfo {
+            euid,
+            cap_sys_nice: euid == 0 || cap_eff & (1 << CAP_SYS_NICE) != 0,
+            cap_sys_rawio: cap_eff & (1 << CAP_SYS_RAWIO) != 0,
+        }
+    }
+
+    /// 能否修改属于指定用户的进程（与内核规则一致：有效 UID 与目标的真实或有效 UID 相同）
+    pub fn can_modify(&self, target_ruid: u32, target_euid: u32) -> bool {
+        self.cap_sys_nice || self.euid == target_ruid || self.euid == target_euid
+    }
+}
+
+/// 从 /proc/[pid]/status 中解析 (真实 UID, 有效 UID)
+pub fn parse_status_uids(status: &str) -> Option<(u32, u32)> {
+    let line = status.lines().find_map(|l| l.strip_prefix("Uid:"))?;
+    let mut fields = line.split_whitespace().map(|f| f.parse::<u32>());
+    Some((fields.next()?.ok()?, fields.next()?.ok()?))
+}
+
+/// 从 /proc/[pid]/status 中解析有效能力位图
+pub fn parse_cap_eff(status: &str) -> Option<u64> {
+    let value = status.lines().find_map(|l| l.strip_prefix("CapEff:"))?;
+    u64::from_str_radix(value.trim(), 16).ok()
+}
+
+/// 从 /etc/passwd 内容中查找用户名
+pub fn parse_passwd_name(passwd: &str, uid: u32) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let id = fields.nth(1)?.parse::<u32>().ok()?;
+        (id == uid).then(|| name.to_string())
+    })
+}
+
+/// 用户名（查不到时显示 UID）
+pub fn user_name(uid: u32) -> String {
+    fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| parse_passwd_name(&passwd, uid))
+        .unwrap_or_else(|| format!("uid {}", uid))
+}
+
+/// PID 预检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PidCheck {
+    /// 可直接修改
+    Modifiable { name: String, owner: String },
+    /// 进程存在，但需要 root 或 CAP_SYS_NICE
+    NeedsPrivilege { name: String, owner: String },
+    /// 进程不存在
+    NotFound,
+}
+
+/// 检查 PID 是否存在以及能否修改（只读取 /proc/[pid]/status，开销很小）
+pub fn check_pid(pid: u32, privilege: &PrivilegeInfo) -> PidCheck {
+    let Ok(status) = fs::read_to_string(format!("/proc/{}/status", pid)) else {
+        return PidCheck::NotFound;
+    };
+    let name = status
+        .lines()
+        .find_map(|l| l.strip_prefix("Name:"))
+        .map(|n| n.trim().to_string())
+        .unwrap_or_default();
+    let Some((ruid, euid)) = parse_status_uids(&status) else {
+        return PidCheck::NotFound;
+    };
+
+    let owner = user_name(euid);
+    if privilege.can_modify(ruid, euid) {
+        PidCheck::Modifiable { name, owner }
+    } else {
+        PidCheck::NeedsPrivilege { name, owner }
+    }
+}
+
+/// 资源限制值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RlimitValue {
+    Unlimited,
+    Value(u64),
+}
+
+impl RlimitValue {
+    fn parse(s: &str) -> Option<Self> {
+        if s == "unlimited" {
+            Some(RlimitValue::Unlimited)
+        } else {
+            s.parse().ok().map(RlimitValue::Value)
+        }
+    }
+
+    /// 是否允许不超过 `value` 的请求
+    pub fn allows(&self, value: u64) -> bool {
+        match self {
+            RlimitValue::Unlimited => true,
+            RlimitValue::Value(limit) => value <= *limit,
+        }
+    }
+}
+
+impl std::fmt::Display for RlimitValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RlimitValue::Unlimited => write!(f, "不限"),
+            RlimitValue::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// 软 / 硬限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlimit {
+    pub soft: RlimitValue,
+    pub hard: RlimitValue,
+}
+
+/// 进程与调度相关的资源限制（内核按目标进程的软限制判断非特权修改）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessLimits {
+    /// RLIMIT_NICE：允许的最低 nice 为 20 - 软限制
+    pub nice: Rlimit,
+    /// RLIMIT_RTPRIO：允许的最高实时优先级
+    pub rtprio: Rlimit,
+    /// RLIMIT_NOFILE：可打开的文件描述符数（旧内核缺少该行时为 None）
+    pub open_files: Option<Rlimit>,
+}
+
+impl ProcessLimits {
+    /// RLIMIT_NICE 允许的最低 nice 值
+    pub fn min_nice(&self) -> i32 {
+        match self.nice.soft {
+            RlimitValue::Unlimited => -20,
+            RlimitValue::Value(v) => (20 - v.min(40) as i32).clamp(-20, 19),
+        }
+    }
+
+    /// 没有 CAP_SYS_NICE 时把 nice 从 `current` 降到 `requested` 是否会被拒绝，返回提示
+    pub fn nice_warning(&self, requested: i32, current: i32) -> Option<String> {
+        (requested < current && requested < self.min_nice()).then(|| {
+            format!(
+                "该进程的 RLIMIT_NICE 为 {}，nice 最低只能设为 {}",
+                self.nice.soft,
+                self.min_nice().min(current)
+            )
+        })
+    }
+
+    /// 没有 CAP_SYS_NICE 时设置实时优先级 `requested` 是否会被拒绝，返回提示
+    pub fn rtprio_warning(&self, requested: i32) -> Option<String> {
+        (!self.rtprio.soft.allows(requested.max(0) as u64))
+            .then(|| format!("该进程的 RTPRIO 上限为 {}", self.rtprio.soft))
+    }
+
+    /// 打开 `count` 个文件描述符占软限制的比例，不限时为 None
+    pub fn open_files_usage(&self, count: usize) -> Option<f32> {
+        match self.open_files?.soft {
+            RlimitValue::Unlimited => None,
+            RlimitValue::Value(0) => Some(1.0),
+            RlimitValue::Value(limit) => Some(count as f32 / limit as f32),
+        }
+    }
+}
+
+/// 解析 /proc/[pid]/limits
+pub fn parse_limits(content: &str) -> Option<ProcessLimits> {
+    let find = |label: &str| -> Option<Rlimit> {
+        let rest = content.lines().find_map(|line| line.strip_prefix(label))?;
+        let mut fields = rest.split_whitespace();
+        Some(Rlimit {
+            soft: RlimitValue::parse(fields.next()?)?,
+            hard: RlimitValue::parse(fields.next()?)?,
+        })
+    };
+    Some(ProcessLimits {
+        nice: find("Max nice priority")?,
+        rtprio: find("Max realtime priority")?,
+        open_files: find("Max open files"),
+    })
+}
+
+/// 读取进程的调度相关资源限制
+pub fn read_process_limits(pid: u32) -> Option<ProcessLimits> {
+    parse_limits(&fs::read_to_string(format!("/proc/{}/limits", pid)).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = "\
+Name:\tbash
+Umask:\t0022
+State:\tS (sleeping)
+Uid:\t1000\t1001\t1000\t1000
+Gid:\t1000\t1000\t1000\t1000
+CapEff:\t0000000000800000
+";
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status_uids(STATUS), Some((1000, 1001)));
+        assert_eq!(parse_cap_eff(STATUS), Some(1 << CAP_SYS_NICE));
+        assert_eq!(parse_status_uids("Name:\tx\n"), None);
+    }
+
+    #[test]
+    fn test_parse_passwd_name() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\n";
+        assert_eq!(parse_passwd_name(passwd, 1000), Some("alice".to_string()));
+        assert_eq!(parse_passwd_name(passwd, 0), Some("root".to_string()));
+        assert_eq!(parse_passwd_name(passwd, 42), None);
+    }
+
+    const LIMITS: &str = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max stack size            8388608              unlimited            bytes     
+Max open files            1024                 524288               files     
+Max nice priority         0                    0                    
+Max realtime priority     0                    95                   
+Max realtime timeout      unlimited            unlimited            us        
+";
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = parse_limits(LIMITS).unwrap();
+        assert_eq!(limits.nice, Rlimit { soft: RlimitValue::Value(0), hard: RlimitValue::Value(0) });
+        assert_eq!(limits.rtprio, Rlimit { soft: RlimitValue::Value(0), hard: RlimitValue::Value(95) });
+        assert_eq!(limits.min_nice(), 19);
+        assert_eq!(limits.open_files, Some(Rlimit { soft: RlimitValue::Value(1024), hard: RlimitValue::Value(524288) }));
+        assert_eq!(limits.open_files_usage(512), Some(0.5));
+        assert_eq!(limits.rtprio_warning(10).as_deref(), Some("该进程的 RTPRIO 上限为 0"));
+        assert!(limits.nice_warning(-5, 0).is_some());
+        // 提高 nice 不受限制
+        assert!(limits.nice_warning(5, 0).is_none());
+
+        let unlimited = parse_limits(&LIMITS.replace("0                    95 ", "unlimited            unlimited"))
+            .unwrap();
+        assert_eq!(unlimited.rtprio.soft, RlimitValue::Unlimited);
+        assert!(unlimited.rtprio_warning(99).is_none());
+
+        // RLIMIT_NICE 30 允许降到 -10
+        let nice30 = parse_limits(&LIMITS.replace("Max nice priority         0                    0", "Max nice priority         30                   30"))
+            .unwrap();
+        assert_eq!(nice30.min_nice(), -10);
+        assert!(nice30.nice_warning(-10, 0).is_none());
+        assert!(nice30.nice_warning(-11, 0).is_some());
+
+        assert_eq!(parse_limits("Limit  Soft Limit\n"), None);
+        // 缺少 Max open files 行仍可解析
+        let no_nofile = parse_limits(&LIMITS.replace("Max open files", "Max other")).unwrap();
+        assert_eq!(no_nofile.open_files, None);
+        assert_eq!(no_nofile.open_files_usage(10), None);
+    }
+
+    #[test]
+    fn test_can_modify() {
+        let user = PrivilegeInfo { euid: 1000, cap_sys_nice: false, cap_sys_rawio: false };
+        assert!(user.can_modify(1000, 1000));
+        assert!(user.can_modify(0, 1000));
+        assert!(!user.can_modify(0, 0));
+
+        let privileged = PrivilegeInfo { euid: 1000, cap_sys_nice: true, cap_sys_rawio: false };
+        assert!(privileged.can_modify(0, 0));
+    }
+}
@@ -1,8 +1,37 @@
 //! 进程信息和管理模块
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::{
+    core_mismatch, detect_container, detect_gpu_monitor, detect_origin, read_pid_namespace, unix_timestamp, ContainerInfo,
+    CoreMismatch, CoreType, CoreTypeHistory, GpuSampler, ProcessCategory, ProcessOrigin,
+};
 use sysinfo::{Process, System};
 
+use crate::utils::rate::CounterRate;
+use crate::utils::ProcessHistory;
+
+/// 单进程历史长度（数据点数，约 2 分钟 @ 1s）
+const PROCESS_HISTORY_LENGTH: usize = 120;
+/// 已退出进程在列表中保留显示的时间
+const EXITED_DISPLAY_DURATION: Duration = Duration::from_secs(3);
+/// 尚未启用 seccomp 的进程重新检查的间隔
+const SECCOMP_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// 分级刷新时，CPU 使用率高于此值 (%) 的进程每个周期都刷新
+pub const HOT_PROCESS_CPU_PERCENT: f32 = 0.5;
+
+/// 新进程出现时的回调
+pub type ProcessAppearedCallback = Box<dyn Fn(&ProcessInfo) + Send>;
+/// 进程退出时的回调
+pub type ProcessDisappearedCallback = Box<dyn Fn(u32) + Send>;
+/// 进程执行 exec（名称变化）时的回调，参数为更新后的进程和旧名称
+pub type ProcessExecCallback = Box<dyn Fn(&ProcessInfo, &str) + Send>;
+
 /// 进程信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
@@ -10,12 +39,19 @@ pub struct ProcessInfo {
     pub pid: u32,
     /// 进程名称
     pub name: String,
+    /// 父进程 ID
+    #[serde(default)]
+    pub parent_pid: Option<u32>,
     /// 命令行
     pub cmd: String,
     /// CPU 使用率
     pub cpu_usage: f32,
     /// 内存使用 (字节)
     pub memory: u64,
+    /// 与上次刷新相比的内存变化 (字节)
+    pub memory_delta: i64,
+    /// 进程启动时间（用于识别 PID 复用）
+    pub start_time: u64,
     /// 进程状态
     pub status: String,
     /// CPU 亲和性掩码
@@ -24,49 +60,175 @@ pub struct ProcessInfo {
     pub sched_policy: super::SchedulePolicy,
     /// 优先级/nice 值
     pub priority: i32,
+    /// 扩展调度属性 (sched_getattr)
+    pub sched_attr: super::SchedAttr,
+    /// 最近一次运行所在的逻辑 CPU
+    #[serde(default)]
+    pub last_cpu: Option<usize>,
+    /// GPU 使用率 (未启用 GPU 监控或进程未使用 GPU 时为 None)
+    pub gpu_usage_percent: Option<f32>,
+    /// 所属容器（宿主机进程为 None）
+    pub container: Option<ContainerInfo>,
+    /// 来源（用户应用 / 用户服务 / 系统服务 / 内核）
+    #[serde(default)]
+    pub origin: ProcessOrigin,
+    /// seccomp 模式 (0=禁用, 1=严格, 2=过滤器)
+    #[serde(default)]
+    pub seccomp_mode: u8,
+    /// 是否为内核线程 (kthreadd 及其子线程)
+    #[serde(default)]
+    pub kernel_thread: bool,
+    /// 累计次要缺页数 (minflt)
+    #[serde(default)]
+    pub minor_faults: u64,
+    /// 累计主要缺页数 (majflt，需要从磁盘读入)
+    #[serde(default)]
+    pub major_faults: u64,
+    /// 每秒次要缺页数（两次完整刷新之间，尚无数据时为 None）
+    #[serde(default)]
+    pub minor_fault_rate: Option<f64>,
+    /// 每秒主要缺页数
+    #[serde(default)]
+    pub major_fault_rate: Option<f64>,
+    /// 可执行文件在进程运行期间被删除或替换（如软件包升级），进程仍在运行旧代码
+    #[serde(default)]
+    pub exe_deleted: bool,
+    /// /proc/[pid]/exe 的链接目标（无权读取、内核线程时为 None），变化时说明进程执行了 exec
+    #[serde(skip)]
+    pub exe: Option<PathBuf>,
+    /// 混合架构上运行的核心类型与优先级不符（非混合架构时总为 None）
+    #[serde(default)]
+    pub core_mismatch: Option<CoreMismatch>,
+    /// 最近几次采样所在的核心类型（平滑 `core_mismatch`）
+    #[serde(skip)]
+    pub core_type_history: CoreTypeHistory,
 }
 
 impl ProcessInfo {
-    /// 从 sysinfo Process 创建
-    pub fn from_process(pid: u32, process: &Process, logical_cores: usize) -> Self {
+    /// 从 sysinfo Process 创建，stat 为同一刷新中读取的 /proc/[pid]/stat
+    pub fn from_process(pid: u32, process: &Process, stat: Option<&ProcessStat>, logical_cores: usize) -> Self {
         let cmd: Vec<String> = process.cmd().iter().map(|s| s.to_string_lossy().to_string()).collect();
         let cmd_str = cmd.join(" ");
-        let affinity = get_process_affinity(pid as i32, logical_cores);
-        let (sched_policy, priority) = super::get_scheduler_info(pid as i32);
+        let affinity = read_process_affinity(pid, logical_cores);
+        let (sched_policy, priority, sched_attr) = super::get_scheduler_info(pid as i32);
+        let (seccomp_mode, kernel_thread) = read_process_security(pid);
 
-        ProcessInfo {
+        let mut info = ProcessInfo {
             pid,
             name: process.name().to_string_lossy().to_string(),
-            cmd: if cmd_str.is_empty() {
-                process.name().to_string_lossy().to_string()
-            } else {
-                cmd_str
-            },
+            parent_pid: process.parent().map(|p| p.as_u32()),
+            cmd: cmd_str,
             cpu_usage: process.cpu_usage(),
             memory: process.memory(),
+            memory_delta: 0,
+            start_time: process.start_time(),
             status: format!("{:?}", process.status()),
             affinity,
             sched_policy,
             priority,
+            sched_attr,
+            last_cpu: stat.and_then(|s| s.processor),
+            gpu_usage_percent: None,
+            container: None,
+            origin: ProcessOrigin::Unknown,
+            seccomp_mode,
+            kernel_thread,
+            minor_faults: stat.map_or(0, |s| s.minor_faults),
+            major_faults: stat.map_or(0, |s| s.major_faults),
+            minor_fault_rate: None,
+            major_fault_rate: None,
+            exe_deleted: false,
+            exe: None,
+            core_mismatch: None,
+            core_type_history: CoreTypeHistory::default(),
+        };
+        // 以当前的可执行文件和 comm 为基准，之后可执行文件变化才视为 exec
+        info.refresh_identity();
+        info
+    }
+
+    /// 可执行文件变化（进程执行了 exec）时重新读取 comm 和命令行，名称变化时更新名称和命令行，返回旧名称
+    ///
+    /// 只改名（prctl / 写入 comm）不会改变可执行文件，不视为 exec；内核线程会自行改名（如 kworker
+    /// 切换工作队列），跳过。无权读取可执行文件的进程（其他用户的进程）无法识别 exec
+    pub fn refresh_identity(&mut self) -> Option<String> {
+        if self.kernel_thread {
+            return None;
         }
+        let exe = std::fs::read_link(format!("/proc/{}/exe", self.pid)).ok();
+        if exe == self.exe {
+            return None;
+        }
+        // 可执行文件被删除时链接目标带 " (deleted)" 后缀，之后不会恢复
+        self.exe_deleted |= exe.as_ref().is_some_and(|target| is_deleted_exe_target(&target.to_string_lossy()));
+        self.exe = exe;
+        let comm = std::fs::read_to_string(format!("/proc/{}/comm", self.pid)).ok()?;
+        let cmdline = std::fs::read(format!("/proc/{}/cmdline", self.pid)).unwrap_or_default();
+        let argv0 = cmdline.split(|&b| b == 0).next().map(String::from_utf8_lossy);
+        let name = exec_name(&self.name, comm.trim_end_matches('\n'), argv0.as_deref())?;
+        if !cmdline.is_empty() {
+            self.cmd = parse_cmdline(&cmdline);
+        }
+        Some(std::mem::replace(&mut self.name, name))
     }
 
-    /// 更新进程信息
-    pub fn update(&mut self, process: &Process, logical_cores: usize) {
+    /// 命令行的显示文本：内核线程显示 "[内核线程]"，命令行为空（如僵尸进程）时显示方括号括起的名称
+    pub fn display_command(&self) -> Cow<'_, str> {
+        if self.kernel_thread {
+            Cow::Borrowed("[内核线程]")
+        } else if self.cmd.trim().is_empty() {
+            Cow::Owned(format!("[{}]", self.name))
+        } else {
+            Cow::Borrowed(&self.cmd)
+        }
+    }
+
+    /// 批量修改时必须跳过的原因：hexin 自身、init 和内核线程；普通进程返回 None
+    pub fn protected_reason(&self) -> Option<&'static str> {
+        protected_reason(self.pid, self.kernel_thread)
+    }
+
+    /// seccomp 是否处于严格或过滤器模式
+    pub fn is_seccomp_enabled(&self) -> bool {
+        self.seccomp_mode > 0
+    }
+
+    /// 重新读取调度策略、nice / 实时优先级、亲和性和最近运行的 CPU
+    pub fn refresh_scheduling(&mut self, logical_cores: usize) {
+        self.refresh_policy(logical_cores);
+        self.last_cpu = read_process_processor(self.pid);
+    }
+
+    /// 重新读取调度策略、nice / 实时优先级和亲和性
+    fn refresh_policy(&mut self, logical_cores: usize) {
+        self.affinity = read_process_affinity(self.pid, logical_cores);
+        let (sched_policy, priority, sched_attr) = super::get_scheduler_info(self.pid as i32);
+        self.sched_policy = sched_policy;
+        self.priority = priority;
+        self.sched_attr = sched_attr;
+    }
+
+    /// 更新进程信息，detailed 为 false 时跳过亲和性和调度策略等逐进程读取
+    /// stat 为同一刷新中读取的 /proc/[pid]/stat（提供最近运行的 CPU）
+    pub fn update(&mut self, process: &Process, stat: Option<&ProcessStat>, logical_cores: usize, detailed: bool) {
         self.cpu_usage = process.cpu_usage();
+        self.memory_delta = process.memory() as i64 - self.memory as i64;
         self.memory = process.memory();
         self.status = format!("{:?}", process.status());
-        self.affinity = get_process_affinity(self.pid as i32, logical_cores);
-        let (sched_policy, priority) = super::get_scheduler_info(self.pid as i32);
-        self.sched_policy = sched_policy;
-        self.priority = priority;
+        // 父进程退出后会被重新挂到 init 或 subreaper 下
+        self.parent_pid = process.parent().map(|p| p.as_u32());
+        if !detailed {
+            return;
+        }
+        self.refresh_policy(logical_cores);
+        self.last_cpu = stat.and_then(|s| s.processor);
     }
 }
 
 /// 进程列表管理器
 pub struct ProcessManager {
-    /// 所有进程
-    processes: Vec<ProcessInfo>,
+    /// 所有进程 (按 PID 索引)
+    processes: HashMap<u32, ProcessInfo>,
     /// 逻辑核心数
     logical_cores: usize,
     /// 搜索过滤器
@@ -75,58 +237,809 @@ pub struct ProcessManager {
     sort_by: SortField,
     /// 排序方向
     sort_desc: bool,
+    /// 当前跟踪的进程历史（选中进程）
+    tracked: Option<ProcessHistory>,
+    /// 创建时间（用于历史时间戳）
+    start_time: Instant,
+    /// GPU 后台采样（未启用或无可用 GPU 时为 None）
+    gpu_sampler: Option<GpuSampler>,
+    /// hexin 自身的 PID 命名空间
+    own_pid_ns: Option<String>,
+    /// 仅显示容器进程
+    containers_only: bool,
+    /// 被筛选条隐藏的进程来源
+    hidden_origins: HashSet<ProcessOrigin>,
+    /// 可用的进程分类（快速过滤条件）
+    categories: Vec<ProcessCategory>,
+    /// 启用的分类名称（按分类顺序），进程需满足全部启用的分类
+    active_categories: Vec<String>,
+    /// 各进程首次出现的时间
+    first_seen: HashMap<u32, Instant>,
+    /// 最近退出的进程及退出时间
+    recently_exited: Vec<(ProcessInfo, Instant)>,
+    /// 最近一次刷新的新增 / 退出统计
+    last_diff: ProcessDiff,
+    /// 新进程出现时的回调
+    appeared_callbacks: Vec<ProcessAppearedCallback>,
+    /// 进程退出时的回调
+    disappeared_callbacks: Vec<ProcessDisappearedCallback>,
+    /// exec 回调
+    exec_callbacks: Vec<ProcessExecCallback>,
+    /// 尚未被取走的 exec（PID, 旧名称）
+    recent_execs: Vec<(u32, String)>,
+    /// 各进程 (次要, 主要) 缺页数的速率计算
+    fault_rates: HashMap<u32, (CounterRate, CounterRate)>,
+    /// 是否为所有进程计算缺页速率（显示缺页列时），否则只计算选中和打开详情的进程
+    track_faults: bool,
+    /// 尚未启用 seccomp 的进程上一次检查时的 (启动时间, 检查时间)
+    seccomp_checked: HashMap<u32, (u64, Instant)>,
+    /// 分级刷新：部分周期只刷新活跃进程，CPU 使用率改为按各进程自身的刷新间隔计算
+    tiered: bool,
+    /// 各进程 CPU 时间（utime + stime，单位 tick）的速率计算，记录该进程上一次刷新的时间
+    cpu_rates: HashMap<u32, CounterRate>,
+    /// 监视列表中的 PID（按加入顺序），每次 CPU 刷新单独更新
+    watched: Vec<u32>,
+    /// 次要排序字段（主排序值相同时使用，最后总是按 PID 升序）
+    secondary_sort: Option<SortField>,
+    /// 各逻辑 CPU 的核心类型（仅混合架构，用于判断核心匹配）
+    core_types: Vec<CoreType>,
+    /// 进程数据版本，每次刷新递增（供界面缓存搜索结果）
+    generation: u64,
+    /// 界面中打开的其他进程（调度页的选择、独立详情窗口），分级刷新时每个周期都刷新
+    open_pids: Vec<u32>,
+}
+
+/// 一次刷新中进程的增减
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessDiff {
+    /// 新出现的进程数
+    pub appeared: usize,
+    /// 退出的进程数
+    pub exited: usize,
+    /// 执行了 exec（名称变化）的进程数
+    pub execed: usize,
+}
+
+/// 同名进程分组
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessGroup {
+    /// 进程名称
+    pub name: String,
+    /// 组内进程 PID（按当前排序）
+    pub pids: Vec<u32>,
+    /// CPU 使用率之和
+    pub cpu_usage: f32,
+    /// 内存使用之和 (字节)
+    pub memory: u64,
+}
+
+impl ProcessGroup {
+    /// 实例数
+    pub fn count(&self) -> usize {
+        self.pids.len()
+    }
+}
+
+/// 按名称聚合进程（输入顺序决定组内顺序），并按排序字段对分组排序
+pub fn group_by_name(processes: &[&ProcessInfo], sort_by: SortField, sort_desc: bool) -> Vec<ProcessGroup> {
+    let mut groups: Vec<ProcessGroup> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for process in processes {
+        let i = *index.entry(process.name.as_str()).or_insert_with(|| {
+            groups.push(ProcessGroup {
+                name: process.name.clone(),
+                pids: Vec::new(),
+                cpu_usage: 0.0,
+                memory: 0,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[i];
+        group.pids.push(process.pid);
+        group.cpu_usage += process.cpu_usage;
+        group.memory += process.memory;
+    }
+
+    groups.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortField::Pid => a.pids.iter().min().cmp(&b.pids.iter().min()),
+            SortField::Name | SortField::CoreFit => Ordering::Equal,
+            SortField::CpuUsage => a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(Ordering::Equal),
+            SortField::Memory => a.memory.cmp(&b.memory),
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+    if sort_desc {
+        groups.reverse();
+    }
+    groups
 }
 
 /// 排序字段
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortField {
     Pid,
     Name,
     CpuUsage,
     Memory,
+    /// 核心匹配（仅混合架构显示）
+    CoreFit,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 5] =
+        [SortField::Pid, SortField::Name, SortField::CpuUsage, SortField::Memory, SortField::CoreFit];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SortField::Pid => "PID",
+            SortField::Name => "名称",
+            SortField::CpuUsage => "CPU%",
+            SortField::Memory => "内存",
+            SortField::CoreFit => "核心匹配",
+        }
+    }
+
+    /// 作为次要排序键时是否降序（数值从大到小，PID 和名称从小到大）
+    fn secondary_desc(&self) -> bool {
+        matches!(self, SortField::CpuUsage | SortField::Memory | SortField::CoreFit)
+    }
+
+    /// 按此字段比较两个进程（升序）
+    fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        match self {
+            SortField::Pid => a.pid.cmp(&b.pid),
+            SortField::Name => a.name.cmp(&b.name),
+            SortField::CpuUsage => a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(Ordering::Equal),
+            SortField::Memory => a.memory.cmp(&b.memory),
+            SortField::CoreFit => a.core_mismatch.cmp(&b.core_mismatch),
+        }
+    }
+}
+
+/// 快照默认最多包含的进程数
+pub const SNAPSHOT_MAX_PROCESSES: usize = 5000;
+
+/// 进程列表快照：导出和外部工具使用的统一格式
+/// 序列化时借用进程管理器中的数据，不复制进程列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSnapshot<'a> {
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    /// 是否只包含符合过滤条件的进程
+    pub filtered: bool,
+    /// 搜索过滤器
+    pub filter: String,
+    /// 是否仅显示容器进程
+    pub containers_only: bool,
+    /// 被隐藏的进程来源
+    pub hidden_origins: Vec<ProcessOrigin>,
+    /// 启用的进程分类
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// 排序字段
+    pub sort_by: SortField,
+    /// 是否降序
+    pub sort_desc: bool,
+    /// 截断前的进程总数（大于 processes 的长度时说明已截断）
+    pub total: usize,
+    /// 进程列表（按当前排序）
+    pub processes: Vec<Cow<'a, ProcessInfo>>,
 }
 
 impl ProcessManager {
     pub fn new(logical_cores: usize) -> Self {
         Self {
-            processes: Vec::new(),
+            processes: HashMap::new(),
             logical_cores,
             filter: String::new(),
             sort_by: SortField::CpuUsage,
             sort_desc: true,
+            tracked: None,
+            start_time: Instant::now(),
+            gpu_sampler: None,
+            own_pid_ns: read_pid_namespace(std::process::id()),
+            containers_only: false,
+            hidden_origins: HashSet::new(),
+            categories: Vec::new(),
+            active_categories: Vec::new(),
+            first_seen: HashMap::new(),
+            recently_exited: Vec::new(),
+            last_diff: ProcessDiff::default(),
+            appeared_callbacks: Vec::new(),
+            disappeared_callbacks: Vec::new(),
+            exec_callbacks: Vec::new(),
+            recent_execs: Vec::new(),
+            fault_rates: HashMap::new(),
+            track_faults: false,
+            seccomp_checked: HashMap::new(),
+            tiered: false,
+            cpu_rates: HashMap::new(),
+            watched: Vec::new(),
+            secondary_sort: None,
+            core_types: Vec::new(),
+            generation: 0,
+            open_pids: Vec::new(),
         }
     }
 
-    /// 更新进程列表
-    pub fn update(&mut self, sys: &System) {
-        let mut new_processes = Vec::new();
+    /// 更新进程列表（已有进程原地更新，保留跨刷新的状态）
+    ///
+    /// detailed 为 false 时为轻量刷新：已有进程只更新 CPU / 内存 / 状态，
+    /// 不读取亲和性、调度策略和 GPU 使用率
+    pub fn update(&mut self, sys: &System, detailed: bool) {
+        let now = Instant::now();
+        self.generation += 1;
+        // 首次加载时不把所有进程都当作新进程
+        let initial = self.processes.is_empty();
+        let mut diff = ProcessDiff::default();
+        let mut appeared = Vec::new();
+        let mut disappeared = Vec::new();
+        let mut execed = Vec::new();
+        // 每个进程的 stat 只读取一次，供最近运行的 CPU、缺页和 CPU 时间共用
+        let read_stat = detailed || self.tiered;
+        let mut stats = Vec::new();
+        // 轻量保活刷新不逐个读取 /proc/<pid>/exe，只检查活跃、选中、打开和监视中的进程是否 exec
+        let identity_pids: HashSet<u32> = if detailed { HashSet::new() } else { self.hot_pids().into_iter().collect() };
+
+        let sys_processes = sys.processes();
+        let exited: Vec<u32> = self
+            .processes
+            .keys()
+            .filter(|pid| !sys_processes.contains_key(&sysinfo::Pid::from_u32(**pid)))
+            .copied()
+            .collect();
+        for pid in exited {
+            if let Some(info) = self.processes.remove(&pid) {
+                self.first_seen.remove(&pid);
+                self.recently_exited.push((info, now));
+                diff.exited += 1;
+                disappeared.push(pid);
+            }
+        }
 
-        for (pid, process) in sys.processes() {
+        for (pid, process) in sys_processes {
             let pid_u32 = pid.as_u32();
-            new_processes.push(ProcessInfo::from_process(pid_u32, process, self.logical_cores));
+            match self.processes.get_mut(&pid_u32) {
+                Some(info) if info.start_time == process.start_time() => {
+                    let stat = if read_stat { read_process_stat(pid_u32) } else { None };
+                    info.update(process, stat.as_ref(), self.logical_cores, detailed);
+                    stats.extend(stat.map(|s| (pid_u32, s)));
+                    // 启动时间不变而名称变化：启动器 exec 成了真正的程序
+                    let check_identity = detailed || identity_pids.contains(&pid_u32);
+                    if let Some(old_name) = check_identity.then(|| info.refresh_identity()).flatten() {
+                        execed.push((pid_u32, old_name));
+                        diff.execed += 1;
+                    }
+                }
+                previous => {
+                    // PID 被复用：旧进程视为已退出
+                    if previous.is_some() {
+                        self.fault_rates.remove(&pid_u32);
+                        self.cpu_rates.remove(&pid_u32);
+                        self.seccomp_checked.remove(&pid_u32);
+                        if let Some(old) = self.processes.remove(&pid_u32) {
+                            self.recently_exited.push((old, now));
+                            diff.exited += 1;
+                            disappeared.push(pid_u32);
+                        }
+                    }
+                    let stat = read_process_stat(pid_u32);
+                    let mut info = ProcessInfo::from_process(pid_u32, process, stat.as_ref(), self.logical_cores);
+                    if read_stat {
+                        stats.extend(stat.map(|s| (pid_u32, s)));
+                    }
+                    info.container = detect_container(pid_u32, self.own_pid_ns.as_deref());
+                    info.origin = detect_origin(pid_u32, info.kernel_thread);
+                    self.processes.insert(pid_u32, info);
+                    appeared.push(pid_u32);
+                    if !initial {
+                        self.first_seen.insert(pid_u32, now);
+                        diff.appeared += 1;
+                    }
+                }
+            }
+        }
+
+        self.recently_exited.retain(|(_, at)| now.duration_since(*at) < EXITED_DISPLAY_DURATION);
+        self.first_seen.retain(|_, at| now.duration_since(*at) < EXITED_DISPLAY_DURATION);
+        self.last_diff = diff;
+
+        // 首次加载时也通知回调，使规则作用于已在运行的进程
+        for pid in disappeared {
+            for callback in &self.disappeared_callbacks {
+                callback(pid);
+            }
+        }
+        for pid in appeared {
+            if let Some(info) = self.processes.get(&pid) {
+                for callback in &self.appeared_callbacks {
+                    callback(info);
+                }
+            }
+        }
+        for (pid, old_name) in execed {
+            if let Some(info) = self.processes.get(&pid) {
+                tracing::info!("进程 {} exec: {} -> {}", pid, old_name, info.name);
+                for callback in &self.exec_callbacks {
+                    callback(info, &old_name);
+                }
+                self.recent_execs.push((pid, old_name));
+            }
+        }
+
+        // 采样线程报告没有可用的 GPU 后端时停止监控
+        if self.gpu_sampler.as_mut().is_some_and(|sampler| !sampler.poll()) {
+            tracing::info!("未找到可用的 GPU 监控后端");
+            self.gpu_sampler = None;
+        }
+        if let Some(sampler) = self.gpu_sampler.as_ref().filter(|_| detailed) {
+            let processes = &mut self.processes;
+            sampler.with_latest(|usage| {
+                for process in processes.values_mut() {
+                    process.gpu_usage_percent = usage.get(&process.pid).copied();
+                }
+            });
+        }
+
+        if detailed {
+            self.fault_rates.retain(|pid, _| self.processes.contains_key(pid));
+            self.update_fault_rates(&stats, now);
+            self.recheck_seccomp(now);
+        }
+        if self.tiered {
+            // sysinfo 按两次完整刷新之间的全局 CPU 时间计算使用率，只在部分周期刷新的进程会被高估
+            self.cpu_rates.retain(|pid, _| self.processes.contains_key(pid));
+            self.update_cpu_rates(&stats, now);
+        }
+        // 只有详细刷新会重新读取最近运行的核心，其余刷新不计为新的采样
+        if detailed && !self.core_types.is_empty() {
+            for process in self.processes.values_mut() {
+                process.core_mismatch = core_mismatch(process, &self.core_types);
+            }
+        }
+
+        self.refresh_tracked_affinity();
+        self.sample_tracked();
+    }
+
+    /// 启用或关闭分级刷新（`update_hot` 只刷新部分进程时需要启用）
+    pub fn set_tiered_refresh(&mut self, enabled: bool) {
+        if self.tiered != enabled {
+            self.tiered = enabled;
+            self.cpu_rates.clear();
         }
+    }
 
-        self.processes = new_processes;
-        self.sort();
+    /// 设置是否为所有进程计算缺页速率
+    pub fn set_track_faults(&mut self, enabled: bool) {
+        if self.track_faults != enabled {
+            self.track_faults = enabled;
+            self.fault_rates.clear();
+        }
     }
 
-    /// 获取过滤后的进程列表
+    /// 设置界面中打开的其他进程（调度页的选择、独立详情窗口）
+    pub fn set_open_pids(&mut self, pids: Vec<u32>) {
+        self.open_pids = pids;
+    }
+
+    /// 分级刷新中每个周期都需要刷新的进程：近期活跃、选中、在界面中打开、监视中或匹配当前搜索文本
+    pub fn hot_pids(&self) -> Vec<u32> {
+        let tracked = self.tracked.as_ref().map(|h| h.pid());
+        let filter = self.filter.to_lowercase();
+        self.processes
+            .values()
+            .filter(|p| {
+                p.cpu_usage > HOT_PROCESS_CPU_PERCENT
+                    || Some(p.pid) == tracked
+                    || self.open_pids.contains(&p.pid)
+                    || self.watched.contains(&p.pid)
+                    || (!filter.is_empty() && matches_text(p, &filter))
+            })
+            .map(|p| p.pid)
+            .collect()
+    }
+
+    /// 只更新 `pids` 中的进程（调用前应先用 ProcessesToUpdate::Some 刷新 sys 中的这些进程）
+    ///
+    /// 新进程要等下一次完整刷新（`update`）才会出现
+    pub fn update_hot(&mut self, sys: &System, pids: &[u32], detailed: bool) {
+        let now = Instant::now();
+        self.generation += 1;
+        let mut diff = ProcessDiff::default();
+        let mut disappeared = Vec::new();
+        let mut execed = Vec::new();
+        let mut stats = Vec::new();
+
+        for &pid in pids {
+            let Some(info) = self.processes.get_mut(&pid) else {
+                continue;
+            };
+            match sys.process(sysinfo::Pid::from_u32(pid)) {
+                Some(process) if process.start_time() == info.start_time => {
+                    let stat = read_process_stat(pid);
+                    info.update(process, stat.as_ref(), self.logical_cores, detailed);
+                    stats.extend(stat.map(|s| (pid, s)));
+                    if let Some(old_name) = info.refresh_identity() {
+                        execed.push((pid, old_name));
+                        diff.execed += 1;
+                    }
+                    if detailed && !self.core_types.is_empty() {
+                        info.core_mismatch = core_mismatch(info, &self.core_types);
+                    }
+                }
+                // 已退出或 PID 已被复用（新进程由下一次完整刷新加入）
+                _ => {
+                    if let Some(info) = self.processes.remove(&pid) {
+                        self.first_seen.remove(&pid);
+                        self.fault_rates.remove(&pid);
+                        self.cpu_rates.remove(&pid);
+                        self.recently_exited.push((info, now));
+                        diff.exited += 1;
+                        disappeared.push(pid);
+                    }
+                }
+            }
+        }
+        self.update_cpu_rates(&stats, now);
+
+        self.recently_exited.retain(|(_, at)| now.duration_since(*at) < EXITED_DISPLAY_DURATION);
+        self.first_seen.retain(|_, at| now.duration_since(*at) < EXITED_DISPLAY_DURATION);
+        self.last_diff = diff;
+
+        for pid in disappeared {
+            for callback in &self.disappeared_callbacks {
+                callback(pid);
+            }
+        }
+        for (pid, old_name) in execed {
+            if let Some(info) = self.processes.get(&pid) {
+                tracing::info!("进程 {} exec: {} -> {}", pid, old_name, info.name);
+                for callback in &self.exec_callbacks {
+                    callback(info, &old_name);
+                }
+                self.recent_execs.push((pid, old_name));
+            }
+        }
+
+        self.refresh_tracked_affinity();
+        self.sample_tracked();
+    }
+
+    /// 按各进程距上一次刷新的 CPU 时间增量计算使用率（首次采样保留原值）
+    fn update_cpu_rates(&mut self, stats: &[(u32, ProcessStat)], now: Instant) {
+        let timestamp = now.duration_since(self.start_time).as_secs_f64();
+        let ticks_per_sec = clock_ticks_per_sec();
+        for (pid, stat) in stats {
+            let Some(process) = self.processes.get_mut(pid) else {
+                continue;
+            };
+            if let Some(rate) = self.cpu_rates.entry(*pid).or_default().update(stat.cpu_ticks, timestamp) {
+                process.cpu_usage = (rate / ticks_per_sec * 100.0) as f32;
+            }
+        }
+    }
+
+    /// 由本次读取的缺页计数计算每秒速率（不显示缺页列时只计算选中和打开详情的进程）
+    fn update_fault_rates(&mut self, stats: &[(u32, ProcessStat)], now: Instant) {
+        let timestamp = now.duration_since(self.start_time).as_secs_f64();
+        let tracked = self.tracked.as_ref().map(|h| h.pid());
+        let shown = |pid: &u32| self.track_faults || tracked == Some(*pid) || self.open_pids.contains(pid);
+        for (pid, stat) in stats.iter().filter(|(pid, _)| shown(pid)) {
+            let Some(process) = self.processes.get_mut(pid) else {
+                continue;
+            };
+            let (minor_rate, major_rate) = self.fault_rates.entry(*pid).or_default();
+            process.minor_faults = stat.minor_faults;
+            process.major_faults = stat.major_faults;
+            process.minor_fault_rate = minor_rate.update(stat.minor_faults, timestamp);
+            process.major_fault_rate = major_rate.update(stat.major_faults, timestamp);
+        }
+    }
+
+    /// 重新读取尚未启用 seccomp 的进程的模式（seccomp 一旦启用就无法关闭）
+    /// 按 PID 和启动时间缓存，每个进程每 30 秒最多读取一次 status；新进程在创建时已读取
+    fn recheck_seccomp(&mut self, now: Instant) {
+        let checked = &mut self.seccomp_checked;
+        checked.retain(|pid, _| self.processes.get(pid).is_some_and(|p| p.seccomp_mode == 0));
+        for process in self.processes.values_mut().filter(|p| p.seccomp_mode == 0 && !p.kernel_thread) {
+            match checked.get(&process.pid) {
+                Some(&(start_time, at))
+                    if start_time == process.start_time && now.duration_since(at) < SECCOMP_RECHECK_INTERVAL => {}
+                Some(&(start_time, _)) if start_time == process.start_time => {
+                    process.seccomp_mode = read_process_security(process.pid).0;
+                    checked.insert(process.pid, (process.start_time, now));
+                }
+                _ => {
+                    checked.insert(process.pid, (process.start_time, now));
+                }
+            }
+        }
+    }
+
+    /// 选中进程的亲和性以 sched_getaffinity 为准
+    fn refresh_tracked_affinity(&mut self) {
+        let Some(pid) = self.tracked.as_ref().map(|h| h.pid()) else {
+            return;
+        };
+        if let Some(process) = self.processes.get_mut(&pid) {
+            process.affinity = get_process_affinity(pid as i32, self.logical_cores);
+        }
+    }
+
+    /// 为跟踪的进程记录一个历史数据点
+    fn sample_tracked(&mut self) {
+        let Some(tracked) = self.tracked.as_mut() else {
+            return;
+        };
+        let pid = tracked.pid();
+        // 进程已退出时保留历史但不再追加
+        if let Some(process) = self.processes.get(&pid) {
+            let timestamp = self.start_time.elapsed().as_secs_f64();
+            let run_delay = super::read_schedstat(pid).map(|s| s.run_delay_ns);
+            tracked.push(process.cpu_usage, run_delay, timestamp);
+        }
+    }
+
+    /// 直接从 /proc 刷新单个进程的调度信息（应用设置后或打开详情时），不等待下一次完整刷新
+    /// 不在列表中或已退出时返回 false，已退出的进程由下一次完整刷新移除
+    pub fn refresh_pid(&mut self, pid: u32) -> bool {
+        let Some(process) = self.processes.get_mut(&pid) else {
+            return false;
+        };
+        if !std::path::Path::new(&format!("/proc/{}", pid)).exists() {
+            return false;
+        }
+        process.refresh_scheduling(self.logical_cores);
+        true
+    }
+
+    /// 直接加入进程（测试其他模块时代替刷新）
+    #[cfg(test)]
+    pub(crate) fn insert_process(&mut self, process: ProcessInfo) {
+        self.processes.insert(process.pid, process);
+        self.generation += 1;
+    }
+
+    /// 加入监视列表（已在列表中时忽略）
+    pub fn watch(&mut self, pid: u32) {
+        if !self.watched.contains(&pid) {
+            self.watched.push(pid);
+        }
+    }
+
+    /// 移出监视列表
+    pub fn unwatch(&mut self, pid: u32) {
+        self.watched.retain(|&p| p != pid);
+    }
+
+    /// 加入或移出监视列表
+    pub fn toggle_watch(&mut self, pid: u32) {
+        if self.is_watched(pid) {
+            self.unwatch(pid);
+        } else {
+            self.watch(pid);
+        }
+    }
+
+    /// 是否在监视列表中
+    pub fn is_watched(&self, pid: u32) -> bool {
+        self.watched.contains(&pid)
+    }
+
+    /// 监视列表中的 PID
+    pub fn watched(&self) -> &[u32] {
+        &self.watched
+    }
+
+    /// 更新监视列表中进程的 CPU / 内存和调度信息
+    /// 调用前应先用 ProcessesToUpdate::Some 刷新 sys 中的这些进程；
+    /// 已退出的进程从列表移除，返回它们的 (PID, 名称)
+    pub fn refresh_watched(&mut self, sys: &System) -> Vec<(u32, String)> {
+        let mut exited = Vec::new();
+        let logical_cores = self.logical_cores;
+        let processes = &mut self.processes;
+        let recently_exited = &self.recently_exited;
+        self.watched.retain(|&pid| match (processes.get_mut(&pid), sys.process(sysinfo::Pid::from_u32(pid))) {
+            (Some(info), Some(process)) => {
+                info.update(process, read_process_stat(pid).as_ref(), logical_cores, true);
+                true
+            }
+            (info, _) => {
+                // 完整刷新可能已把进程移到最近退出列表
+                let name = info
+                    .map(|p| p.name.clone())
+                    .or_else(|| recently_exited.iter().find(|(p, _)| p.pid == pid).map(|(p, _)| p.name.clone()))
+                    .unwrap_or_default();
+                exited.push((pid, name));
+                false
+            }
+        });
+        exited
+    }
+
+    /// 记录跟踪进程当前所在的核心（按 CPU 刷新频率调用，只读取跟踪进程的 stat）
+    pub fn sample_tracked_cpu(&mut self) {
+        let Some(tracked) = self.tracked.as_mut() else {
+            return;
+        };
+        if let Some(cpu) = read_process_processor(tracked.pid()) {
+            tracked.push_cpu(cpu, self.start_time.elapsed().as_secs_f64());
+        }
+    }
+
+    /// 注册新进程出现时的回调（在 update 中调用）
+    pub fn on_process_appeared(&mut self, callback: impl Fn(&ProcessInfo) + Send + 'static) {
+        self.appeared_callbacks.push(Box::new(callback));
+    }
+
+    /// 注册进程退出时的回调（在 update 中调用，参数为 PID）
+    pub fn on_process_disappeared(&mut self, callback: impl Fn(u32) + Send + 'static) {
+        self.disappeared_callbacks.push(Box::new(callback));
+    }
+
+    /// 注册进程执行 exec（名称变化）时的回调（在 update 中调用）
+    pub fn on_process_exec(&mut self, callback: impl Fn(&ProcessInfo, &str) + Send + 'static) {
+        self.exec_callbacks.push(Box::new(callback));
+    }
+
+    /// 取出自上次调用以来执行了 exec 的进程（PID, 旧名称）
+    pub fn take_execs(&mut self) -> Vec<(u32, String)> {
+        std::mem::take(&mut self.recent_execs)
+    }
+
+    /// 启用或关闭 GPU 监控，启用时在采样线程中检测 NVIDIA / AMD 后端
+    pub fn set_gpu_monitoring(&mut self, enabled: bool) {
+        self.gpu_sampler = enabled.then(|| GpuSampler::spawn(detect_gpu_monitor));
+    }
+
+    /// 当前 GPU 监控后端名称
+    pub fn gpu_monitor_name(&self) -> Option<&'static str> {
+        self.gpu_sampler.as_ref().and_then(GpuSampler::name)
+    }
+
+    /// 设置需要跟踪历史的进程（切换时清空旧历史）
+    pub fn track_pid(&mut self, pid: Option<u32>) {
+        if self.tracked.as_ref().map(|h| h.pid()) == pid {
+            return;
+        }
+        self.tracked = pid.map(|pid| ProcessHistory::new(pid, PROCESS_HISTORY_LENGTH));
+        self.refresh_tracked_affinity();
+        self.sample_tracked();
+    }
+
+    /// 获取跟踪进程的历史
+    pub fn tracked_history(&self) -> Option<&ProcessHistory> {
+        self.tracked.as_ref()
+    }
+
+    /// 是否匹配当前过滤条件
+    fn matches_filter(&self, p: &ProcessInfo, filter_lower: &str) -> bool {
+        if self.containers_only && p.container.is_none() {
+            return false;
+        }
+        if self.hidden_origins.contains(&p.origin) {
+            return false;
+        }
+        let categories_match = self
+            .categories
+            .iter()
+            .filter(|c| self.active_categories.contains(&c.name))
+            .all(|c| c.predicate.matches(p));
+        if !categories_match {
+            return false;
+        }
+        matches_text(p, filter_lower)
+    }
+
+    /// 按名称 / 命令行 / PID 搜索全部进程（不受进程列表的过滤和分类影响），按 CPU 使用率降序
+    pub fn search(&self, text: &str) -> Vec<&ProcessInfo> {
+        let text_lower = text.trim().to_lowercase();
+        let mut processes: Vec<&ProcessInfo> =
+            self.processes.values().filter(|p| matches_text(p, &text_lower)).collect();
+        processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(a.pid.cmp(&b.pid)));
+        processes
+    }
+
+    /// 进程数据版本，进程列表或 CPU 使用率刷新后改变
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// 获取过滤后的进程列表（按当前排序）
     pub fn filtered_processes(&self) -> Vec<&ProcessInfo> {
         let filter_lower = self.filter.to_lowercase();
+        let mut processes: Vec<&ProcessInfo> = self
+            .processes
+            .values()
+            .filter(|p| self.matches_filter(p, &filter_lower))
+            .collect();
+        self.sort(&mut processes);
+        processes
+    }
+
+    /// 生成进程列表快照，filtered 为 false 时包含全部进程，最多 limit 个
+    pub fn snapshot(&self, filtered: bool, limit: usize) -> ProcessSnapshot<'_> {
+        let mut processes = if filtered {
+            self.filtered_processes()
+        } else {
+            let mut all: Vec<&ProcessInfo> = self.processes.values().collect();
+            self.sort(&mut all);
+            all
+        };
+        let total = processes.len();
+        processes.truncate(limit);
+
+        let mut hidden_origins: Vec<ProcessOrigin> = self.hidden_origins.iter().copied().collect();
+        hidden_origins.sort_by_key(|origin| ProcessOrigin::ALL.iter().position(|o| o == origin));
+
+        ProcessSnapshot {
+            timestamp: unix_timestamp(),
+            filtered,
+            filter: self.filter.clone(),
+            containers_only: self.containers_only,
+            hidden_origins,
+            categories: self.active_categories.clone(),
+            sort_by: self.sort_by,
+            sort_desc: self.sort_desc,
+            total,
+            processes: processes.into_iter().map(Cow::Borrowed).collect(),
+        }
+    }
+
+    /// 序列化进程列表快照为紧凑的 JSON（最多 SNAPSHOT_MAX_PROCESSES 个进程）
+    pub fn snapshot_json(&self, filtered: bool) -> Result<String, String> {
+        serde_json::to_string(&self.snapshot(filtered, SNAPSHOT_MAX_PROCESSES))
+            .map_err(|e| format!("序列化进程快照失败: {}", e))
+    }
+
+    /// 未启用 seccomp 的用户态进程（排除内核线程）
+    pub fn unsecured_processes(&self) -> Vec<&ProcessInfo> {
         self.processes
+            .values()
+            .filter(|p| !p.is_seccomp_enabled() && !p.kernel_thread)
+            .collect()
+    }
+
+    /// 所有进程（不受过滤条件影响，无序）
+    pub fn processes(&self) -> impl Iterator<Item = &ProcessInfo> {
+        self.processes.values()
+    }
+
+    /// 按名称聚合的过滤后进程（分组按当前排序）
+    pub fn grouped_by_name(&self) -> Vec<ProcessGroup> {
+        group_by_name(&self.filtered_processes(), self.sort_by, self.sort_desc)
+    }
+
+    /// 按 PID 获取进程
+    pub fn process(&self, pid: u32) -> Option<&ProcessInfo> {
+        self.processes.get(&pid)
+    }
+
+    /// 最近退出且匹配过滤条件的进程及退出时间
+    pub fn recently_exited(&self) -> Vec<(&ProcessInfo, Instant)> {
+        let filter_lower = self.filter.to_lowercase();
+        self.recently_exited
             .iter()
-            .filter(|p| {
-                if self.filter.is_empty() {
-                    true
-                } else {
-                    p.name.to_lowercase().contains(&filter_lower)
-                        || p.cmd.to_lowercase().contains(&filter_lower)
-                        || p.pid.to_string().contains(&filter_lower)
-                }
-            })
+            .filter(|(p, at)| at.elapsed() < EXITED_DISPLAY_DURATION && self.matches_filter(p, &filter_lower))
+            .map(|(p, at)| (p, *at))
             .collect()
     }
 
+    /// 新出现进程的存在时长（超过显示窗口或首次加载的进程为 None）
+    pub fn process_age(&self, pid: u32) -> Option<Duration> {
+        self.first_seen
+            .get(&pid)
+            .map(|at| at.elapsed())
+            .filter(|age| *age < EXITED_DISPLAY_DURATION)
+    }
+
+    /// 最近一次刷新的新增 / 退出统计
+    pub fn last_diff(&self) -> ProcessDiff {
+        self.last_diff
+    }
+
     /// 设置搜索过滤器
     pub fn set_filter(&mut self, filter: String) {
         self.filter = filter;
@@ -137,6 +1050,74 @@ impl ProcessManager {
         &self.filter
     }
 
+    /// 设置是否仅显示容器进程
+    pub fn set_containers_only(&mut self, containers_only: bool) {
+        self.containers_only = containers_only;
+    }
+
+    /// 是否仅显示容器进程
+    pub fn containers_only(&self) -> bool {
+        self.containers_only
+    }
+
+    /// 设置是否显示某一来源的进程
+    pub fn set_origin_visible(&mut self, origin: ProcessOrigin, visible: bool) {
+        if visible {
+            self.hidden_origins.remove(&origin);
+        } else {
+            self.hidden_origins.insert(origin);
+        }
+    }
+
+    /// 是否显示某一来源的进程
+    pub fn is_origin_visible(&self, origin: ProcessOrigin) -> bool {
+        !self.hidden_origins.contains(&origin)
+    }
+
+    /// 设置可用的进程分类和启用的分类（忽略不存在的分类名称）
+    pub fn set_categories(&mut self, categories: Vec<ProcessCategory>, active: &[String]) {
+        self.active_categories = categories
+            .iter()
+            .filter(|c| active.contains(&c.name))
+            .map(|c| c.name.clone())
+            .collect();
+        self.categories = categories;
+    }
+
+    /// 可用的进程分类
+    pub fn categories(&self) -> &[ProcessCategory] {
+        &self.categories
+    }
+
+    /// 启用的分类名称
+    pub fn active_categories(&self) -> &[String] {
+        &self.active_categories
+    }
+
+    /// 启用或停用一个分类
+    pub fn set_category_active(&mut self, name: &str, active: bool) {
+        let mut names: Vec<String> = self.active_categories.iter().filter(|n| *n != name).cloned().collect();
+        if active {
+            names.push(name.to_string());
+        }
+        self.active_categories = self
+            .categories
+            .iter()
+            .filter(|c| names.contains(&c.name))
+            .map(|c| c.name.clone())
+            .collect();
+    }
+
+    /// 设置各逻辑 CPU 的核心类型；非混合架构时传入空列表，不判断核心匹配
+    pub fn set_core_types(&mut self, core_types: Vec<CoreType>) {
+        self.core_types = core_types;
+    }
+
+    /// 是否判断核心匹配（混合架构）
+    pub fn has_core_types(&self) -> bool {
+        !self.core_types.is_empty()
+    }
+
     /// 设置排序
     pub fn set_sort(&mut self, field: SortField) {
         if self.sort_by == field {
@@ -145,7 +1126,6 @@ impl ProcessManager {
             self.sort_by = field;
             self.sort_desc = true;
         }
-        self.sort();
     }
 
     /// 获取当前排序字段
@@ -158,32 +1138,239 @@ impl ProcessManager {
         self.sort_desc
     }
 
-    fn sort(&mut self) {
-        match self.sort_by {
-            SortField::Pid => {
-                self.processes.sort_by_key(|p| p.pid);
-            }
-            SortField::Name => {
-                self.processes.sort_by(|a, b| a.name.cmp(&b.name));
-            }
-            SortField::CpuUsage => {
-                self.processes.sort_by(|a, b| {
-                    a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(std::cmp::Ordering::Equal)
-                });
-            }
-            SortField::Memory => {
-                self.processes.sort_by_key(|p| p.memory);
-            }
-        }
-        if self.sort_desc {
-            self.processes.reverse();
-        }
+    /// 设置次要排序字段
+    pub fn set_secondary_sort(&mut self, field: Option<SortField>) {
+        self.secondary_sort = field;
+    }
+
+    /// 当前次要排序字段
+    pub fn secondary_sort(&self) -> Option<SortField> {
+        self.secondary_sort
+    }
+
+    /// 排序（HashMap 迭代顺序不固定，主、次排序值都相同时按 PID 升序，与排序方向无关，以免每帧跳动）
+    fn sort(&self, processes: &mut [&ProcessInfo]) {
+        let directed = |ordering: Ordering, desc: bool| if desc { ordering.reverse() } else { ordering };
+        processes.sort_by(|a, b| {
+            directed(self.sort_by.compare(a, b), self.sort_desc)
+                .then_with(|| {
+                    self.secondary_sort
+                        .map_or(Ordering::Equal, |field| directed(field.compare(a, b), field.secondary_desc()))
+                })
+                .then(a.pid.cmp(&b.pid))
+        });
     }
 }
 
-/// 获取进程的 CPU 亲和性 (Linux only)
-#[cfg(target_os = "linux")]
+/// 名称、命令行或 PID 包含搜索文本（已转为小写，空文本匹配所有进程）
+fn matches_text(p: &ProcessInfo, text_lower: &str) -> bool {
+    text_lower.is_empty()
+        || p.name.to_lowercase().contains(text_lower)
+        || p.cmd.to_lowercase().contains(text_lower)
+        || p.pid.to_string().contains(text_lower)
+}
+
+/// comm 的最大长度（内核 TASK_COMM_LEN 减去结尾的 NUL）
+const COMM_MAX_LEN: usize = 15;
+
+/// 由 comm 判断进程名称是否变化（exec），返回新名称
+/// comm 被截断为 15 字节：当前名称以 comm 开头时视为未变化，新名称优先取以 comm 开头的 argv[0] 文件名
+pub fn exec_name(current: &str, comm: &str, argv0: Option<&str>) -> Option<String> {
+    let truncated = comm.len() == COMM_MAX_LEN;
+    if comm.is_empty() || current == comm || (truncated && current.starts_with(comm)) {
+        return None;
+    }
+    let full_name = argv0
+        .map(|arg| arg.rsplit('/').next().unwrap_or(arg))
+        .filter(|base| truncated && base.starts_with(comm));
+    Some(full_name.unwrap_or(comm).to_string())
+}
+
+/// 把 /proc/[pid]/cmdline 的 NUL 分隔参数拼成命令行
+pub fn parse_cmdline(content: &[u8]) -> String {
+    parse_cmdline_args(content).join(" ")
+}
+
+/// 拆分 /proc/[pid]/cmdline 的 NUL 分隔参数（跳过空参数）
+pub fn parse_cmdline_args(content: &[u8]) -> Vec<String> {
+    content
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+/// 读取进程的命令行参数，内核线程或无法读取时为空
+pub fn read_process_args(pid: u32) -> Vec<String> {
+    std::fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|content| parse_cmdline_args(&content))
+        .unwrap_or_default()
+}
+
+/// 从 /proc/[pid]/stat 内容中解析进程启动时间 (第 22 个字段，单位 clock ticks)
+pub fn parse_stat_start_time(stat: &str) -> Option<u64> {
+    // comm 字段可能包含空格和括号，从最后一个 ')' 之后开始计数（第 3 个字段起）
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// 读取进程启动时间，进程不存在时返回 None
+pub fn read_process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_start_time(&stat)
+}
+
+/// 从 /proc/[pid]/stat 内容中解析 (次要缺页数, 主要缺页数)（第 10、12 个字段）
+pub fn parse_stat_faults(stat: &str) -> Option<(u64, u64)> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let mut fields = rest.split_whitespace();
+    let minor = fields.nth(7)?.parse().ok()?;
+    let major = fields.nth(1)?.parse().ok()?;
+    Some((minor, major))
+}
+
+/// 从 /proc/[pid]/stat 内容中解析累计 CPU 时间 utime + stime（第 14、15 个字段，单位 clock ticks）
+pub fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let mut fields = rest.split_whitespace();
+    let utime: u64 = fields.nth(11)?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// /proc/[pid]/stat 中每次刷新用到的字段（每个进程每次刷新只读取一次）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessStat {
+    /// 累计次要缺页数
+    pub minor_faults: u64,
+    /// 累计主要缺页数
+    pub major_faults: u64,
+    /// 累计 CPU 时间 utime + stime (clock ticks)
+    pub cpu_ticks: u64,
+    /// 最近运行所在的 CPU
+    pub processor: Option<usize>,
+}
+
+/// 解析 /proc/[pid]/stat 内容
+pub fn parse_process_stat(stat: &str) -> Option<ProcessStat> {
+    let (minor_faults, major_faults) = parse_stat_faults(stat)?;
+    Some(ProcessStat {
+        minor_faults,
+        major_faults,
+        cpu_ticks: parse_stat_cpu_ticks(stat)?,
+        processor: parse_stat_processor(stat),
+    })
+}
+
+/// 读取 /proc/[pid]/stat，进程不存在时返回 None
+pub fn read_process_stat(pid: u32) -> Option<ProcessStat> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_process_stat(&stat)
+}
+
+/// 每秒的 clock tick 数 (USER_HZ)
+fn clock_ticks_per_sec() -> f64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    }
+}
+
+/// 从 /proc/[pid]/stat 内容中解析最近运行所在的 CPU (第 39 个字段)
+pub fn parse_stat_processor(stat: &str) -> Option<usize> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(36)?.parse().ok()
+}
+
+/// 读取进程（主线程）当前所在的 CPU，进程不存在时返回 None
+pub fn read_process_processor(pid: u32) -> Option<usize> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_processor(&stat)
+}
+
+/// 从 /proc/[pid]/status 内容中解析 Cpus_allowed_list
+pub fn parse_cpus_allowed_list(status: &str) -> Option<Vec<usize>> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+        .and_then(super::parse_cpu_list)
+}
+
+/// 读取内核记录的允许运行的 CPU (跨 PID 命名空间也可用，sched_getaffinity 失败时的回退)
+pub fn read_cpus_allowed(pid: u32, logical_cores: usize) -> Option<Vec<usize>> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let mut cpus = parse_cpus_allowed_list(&status)?;
+    cpus.retain(|&cpu| cpu < logical_cores);
+    Some(cpus)
+}
+
+/// 从 /proc/[pid]/status 内容中解析 seccomp 模式
+pub fn parse_status_seccomp(status: &str) -> Option<u8> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Seccomp:"))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// 从 /proc/[pid]/status 内容中判断是否为内核线程
+pub fn parse_status_kernel_thread(status: &str, pid: u32) -> bool {
+    // 新内核直接提供 Kthread 字段，旧内核通过父进程是否为 kthreadd (PID 2) 判断
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.trim().to_string())
+    };
+    if let Some(kthread) = field("Kthread:") {
+        return kthread == "1";
+    }
+    pid == 2 || field("PPid:").as_deref() == Some("2")
+}
+
+/// 不允许 hexin 修改的进程（hexin 自身、init 和内核线程）的原因
+pub fn protected_reason(pid: u32, kernel_thread: bool) -> Option<&'static str> {
+    if pid == std::process::id() {
+        Some("hexin 自身")
+    } else if pid == 1 {
+        Some("init")
+    } else if kernel_thread {
+        Some("内核线程")
+    } else {
+        None
+    }
+}
+
+/// 读取进程的 seccomp 模式和是否为内核线程，进程不存在时返回 (0, false)
+pub fn read_process_security(pid: u32) -> (u8, bool) {
+    match std::fs::read_to_string(format!("/proc/{}/status", pid)) {
+        Ok(status) => (
+            parse_status_seccomp(&status).unwrap_or(0),
+            parse_status_kernel_thread(&status, pid),
+        ),
+        Err(_) => (0, false),
+    }
+}
+
+/// /proc/[pid]/exe 的链接目标是否表示可执行文件已被删除或替换
+pub fn is_deleted_exe_target(target: &str) -> bool {
+    target.ends_with(" (deleted)")
+}
+
+/// 批量刷新用的亲和性读取：优先使用系统调用，失败时回退到 status
+pub fn read_process_affinity(pid: u32, logical_cores: usize) -> Vec<usize> {
+    query_process_affinity(pid as i32, logical_cores)
+        .or_else(|| read_cpus_allowed(pid, logical_cores))
+        .unwrap_or_else(|| (0..logical_cores).collect())
+}
+
+/// 获取进程的 CPU 亲和性，失败时返回所有核心
 pub fn get_process_affinity(pid: i32, logical_cores: usize) -> Vec<usize> {
+    query_process_affinity(pid, logical_cores).unwrap_or_else(|| (0..logical_cores).collect())
+}
+
+/// 通过 sched_getaffinity 读取进程的 CPU 亲和性 (Linux only)，失败时返回 None
+#[cfg(target_os = "linux")]
+fn query_process_affinity(pid: i32, logical_cores: usize) -> Option<Vec<usize>> {
     use libc::{cpu_set_t, sched_getaffinity, CPU_ISSET, CPU_SETSIZE};
     use std::mem::MaybeUninit;
 
@@ -203,16 +1390,16 @@ pub fn get_process_affinity(pid: i32, logical_cores: usize) -> Vec<usize> {
                     affinity.push(i);
                 }
             }
-            affinity
+            Some(affinity)
         } else {
-            (0..logical_cores).collect()
+            None
         }
     }
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn get_process_affinity(_pid: i32, logical_cores: usize) -> Vec<usize> {
-    (0..logical_cores).collect()
+fn query_process_affinity(_pid: i32, _logical_cores: usize) -> Option<Vec<usize>> {
+    None
 }
 
 /// 设置进程的 CPU 亲和性 (Linux only)
@@ -245,6 +1432,40 @@ pub fn set_process_affinity(_pid: i32, _cores: &[usize]) -> Result<(), String> {
     Err("CPU 亲和性设置仅支持 Linux".to_string())
 }
 
+/// 获取进程的 OOM 分数调整值 (-1000 ~ 1000)
+pub fn get_oom_score_adj(pid: i32) -> Result<i32, String> {
+    std::fs::read_to_string(format!("/proc/{}/oom_score_adj", pid))
+        .map_err(|e| format!("读取 oom_score_adj 失败: {}", e))?
+        .trim()
+        .parse()
+        .map_err(|e| format!("解析 oom_score_adj 失败: {}", e))
+}
+
+/// 设置进程的 OOM 分数调整值
+pub fn set_oom_score_adj(pid: i32, value: i32) -> Result<(), String> {
+    std::fs::write(format!("/proc/{}/oom_score_adj", pid), value.clamp(-1000, 1000).to_string())
+        .map_err(|e| format!("设置 oom_score_adj 失败: {} (降低该值需要 root 权限)", e))
+}
+
+/// 结束进程：force 为 false 时发送 SIGTERM，为 true 时发送 SIGKILL (Linux only)
+#[cfg(target_os = "linux")]
+pub fn terminate_process(pid: i32, force: bool) -> Result<(), String> {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    let result = unsafe { libc::kill(pid, signal) };
+
+    if result == 0 {
+        Ok(())
+    } else {
+        let err = std::io::Error::last_os_error();
+        Err(format!("结束进程失败: {} (可能需要 root 权限)", err))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn terminate_process(_pid: i32, _force: bool) -> Result<(), String> {
+    Err("结束进程仅支持 Linux".to_string())
+}
+
 /// 格式化内存大小
 pub fn format_memory(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -261,3 +1482,606 @@ pub fn format_memory(bytes: u64) -> String {
         format!("{} B", bytes)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpus_allowed_list() {
+        let status = "\
+Name:\tbash
+Cpus_allowed:\tff
+Cpus_allowed_list:\t0-3,6
+Mems_allowed_list:\t0
+";
+        assert_eq!(parse_cpus_allowed_list(status), Some(vec![0, 1, 2, 3, 6]));
+        assert_eq!(parse_cpus_allowed_list("Name:\tbash\n"), None);
+    }
+
+    #[test]
+    fn test_parse_status_security() {
+        let status = "\
+Name:\tfirefox
+PPid:\t1
+Seccomp:\t2
+Seccomp_filters:\t3
+";
+        assert_eq!(parse_status_seccomp(status), Some(2));
+        assert!(!parse_status_kernel_thread(status, 4321));
+
+        let kworker = "Name:\tkworker/0:1\nPPid:\t2\nSeccomp:\t0\n";
+        assert_eq!(parse_status_seccomp(kworker), Some(0));
+        assert!(parse_status_kernel_thread(kworker, 87));
+        assert!(parse_status_kernel_thread("Name:\tkthreadd\nPPid:\t0\n", 2));
+        assert!(!parse_status_kernel_thread("Kthread:\t0\nPPid:\t2\n", 99));
+        assert_eq!(parse_status_seccomp("Name:\tbash\n"), None);
+    }
+
+    #[test]
+    fn test_exe_deleted() {
+        assert!(is_deleted_exe_target("/usr/bin/sshd (deleted)"));
+        assert!(!is_deleted_exe_target("/usr/bin/sshd"));
+        assert!(!is_deleted_exe_target("/opt/deleted/bin"));
+        for pid in [std::process::id(), u32::MAX] {
+            let mut info = process(pid, "hexin", 0.0, 0);
+            info.refresh_identity();
+            assert!(!info.exe_deleted);
+        }
+    }
+
+    /// 运行中的可执行文件被删除后链接目标带 " (deleted)" 后缀
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_exe_deleted() {
+        let exe = std::env::temp_dir().join(format!("hexin-sleep-{}", std::process::id()));
+        std::fs::copy("/bin/sleep", &exe).unwrap();
+        let Ok(mut child) = std::process::Command::new(&exe).arg("30").spawn() else {
+            // 临时目录以 noexec 挂载
+            let _ = std::fs::remove_file(&exe);
+            return;
+        };
+        let mut info = process(child.id(), "sleep", 0.0, 0);
+        info.refresh_identity();
+        assert!(!info.exe_deleted);
+        std::fs::remove_file(&exe).unwrap();
+        info.refresh_identity();
+        assert!(info.exe_deleted);
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_exec_name() {
+        assert_eq!(exec_name("steam", "steam", None), None);
+        assert_eq!(exec_name("launcher.sh", "eldenring.exe", Some("Z:\\game\\eldenring.exe")), Some("eldenring.exe".to_string()));
+        // comm 截断为 15 字节
+        assert_eq!(exec_name("gnome-shell-calendar-server", "gnome-shell-cal", None), None);
+        assert_eq!(
+            exec_name("sh", "gnome-shell-cal", Some("/usr/libexec/gnome-shell-calendar-server")),
+            Some("gnome-shell-calendar-server".to_string())
+        );
+        assert_eq!(exec_name("sh", "gnome-shell-cal", Some("/bin/other")), Some("gnome-shell-cal".to_string()));
+        assert_eq!(exec_name("sh", "", None), None);
+        assert_eq!(parse_cmdline(b"/usr/bin/game\0--fullscreen\0\0"), "/usr/bin/game --fullscreen");
+        assert_eq!(parse_cmdline_args(b"/usr/bin/game\0--title=My Game\0"), ["/usr/bin/game", "--title=My Game"]);
+        assert!(parse_cmdline_args(b"").is_empty());
+    }
+
+    #[test]
+    fn test_command_display() {
+        let mut zombie = process(1, "defunct", 0.0, 0);
+        zombie.cmd = String::new();
+        assert_eq!(zombie.display_command(), "[defunct]");
+        zombie.kernel_thread = true;
+        assert_eq!(zombie.display_command(), "[内核线程]");
+        assert_eq!(process(2, "bash", 0.0, 0).display_command(), "bash");
+    }
+
+    #[test]
+    fn test_protected_reason() {
+        assert_eq!(process(std::process::id(), "hexin", 0.0, 0).protected_reason(), Some("hexin 自身"));
+        assert_eq!(process(1, "systemd", 0.0, 0).protected_reason(), Some("init"));
+        let mut kworker = process(40, "kworker/0:1", 0.0, 0);
+        kworker.kernel_thread = true;
+        assert_eq!(kworker.protected_reason(), Some("内核线程"));
+        assert_eq!(process(41, "chrome", 0.0, 0).protected_reason(), None);
+    }
+
+    #[test]
+    fn test_refresh_identity_after_exec() {
+        let script = "sleep 0.2; printf renamed > /proc/self/comm; sleep 0.2; exec sleep 30";
+        let Ok(mut child) = std::process::Command::new("sh").args(["-c", script]).spawn() else {
+            return;
+        };
+        let mut info = process(child.id(), "sh", 0.0, 0);
+        // fork 之后、exec sh 之前子进程的 comm 仍是测试进程的名称
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let comm_path = format!("/proc/{}/comm", child.id());
+        while std::fs::read_to_string(&comm_path).is_ok_and(|comm| comm.trim_end() != "sh") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(info.refresh_identity(), None);
+        // 只改名（可执行文件不变）不算 exec
+        while std::fs::read_to_string(&comm_path).is_ok_and(|comm| comm.trim_end() != "renamed") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(info.refresh_identity(), None);
+        assert_eq!(info.name, "sh");
+        let mut old_name = None;
+        while old_name.is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            old_name = info.refresh_identity();
+        }
+        assert_eq!(old_name.as_deref(), Some("sh"));
+        assert_eq!((info.name.as_str(), info.cmd.as_str()), ("sleep", "sleep 30"));
+        // 内核线程的改名不算 exec
+        info.name = "kworker/0:1".to_string();
+        info.kernel_thread = true;
+        assert_eq!(info.refresh_identity(), None);
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_parse_stat_start_time() {
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 0 1 0 987654 10000 200";
+        assert_eq!(parse_stat_start_time(stat), Some(987654));
+        assert_eq!(parse_stat_start_time("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_stat_faults() {
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 52310 917 86 3 5 3 0 0 20 0 1 0 987654 10000 200";
+        assert_eq!(parse_stat_faults(stat), Some((52310, 86)));
+        assert_eq!(parse_stat_faults("1 (x) S 1 2 3"), None);
+        // utime 5 + stime 3
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(8));
+        assert_eq!(parse_stat_cpu_ticks("1 (x) S 1 2 3"), None);
+    }
+
+    #[test]
+    fn test_parse_process_stat() {
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 52310 917 86 3 5 3 0 0 20 0 1 0 987654 10000 200 \
+                    18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 7 0 0 0 0 0";
+        assert_eq!(
+            parse_process_stat(stat),
+            Some(ProcessStat { minor_faults: 52310, major_faults: 86, cpu_ticks: 8, processor: Some(7) })
+        );
+        // 旧内核没有 processor 字段
+        let short = "1234 (x) S 1 1234 1234 0 -1 4194560 52310 917 86 3 5 3 0 0 20 0 1 0 987654";
+        assert_eq!(parse_process_stat(short).map(|s| s.processor), Some(None));
+        assert_eq!(parse_process_stat("1 (x) S 1 2 3"), None);
+
+        let own = read_process_stat(std::process::id()).unwrap();
+        assert!(own.cpu_ticks > 0 || own.minor_faults > 0);
+    }
+
+    #[test]
+    fn test_tiered_refresh() {
+        let mut manager = ProcessManager::new(1);
+        for (pid, name, cpu) in [(1, "init", 0.0), (2, "game", 3.0), (3, "editor", 0.0), (4, "idle", 0.1)] {
+            manager.processes.insert(pid, process(pid, name, cpu, 0));
+        }
+        manager.watch(4);
+        manager.set_filter("init".to_string());
+        let mut hot = manager.hot_pids();
+        hot.sort_unstable();
+        assert_eq!(hot, vec![1, 2, 4]);
+        // 调度页选中或在详情窗口中打开的进程
+        manager.set_open_pids(vec![3]);
+        let mut hot = manager.hot_pids();
+        hot.sort_unstable();
+        assert_eq!(hot, vec![1, 2, 3, 4]);
+        manager.set_open_pids(Vec::new());
+
+        // sys 中没有的进程视为已退出，其他进程不受影响
+        manager.set_tiered_refresh(true);
+        manager.update_hot(&System::new(), &[2], true);
+        assert!(manager.process(2).is_none());
+        assert_eq!(manager.last_diff().exited, 1);
+        assert_eq!(manager.recently_exited[0].0.pid, 2);
+        assert!(manager.process(3).is_some());
+
+        // 自身进程：按 CPU 时间增量计算使用率
+        let own = std::process::id();
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, false);
+        assert!(manager.cpu_rates.contains_key(&own));
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sysinfo::Pid::from_u32(own)]), true);
+        manager.update_hot(&sys, &[own], false);
+        assert!(manager.process(own).is_some());
+    }
+
+    #[test]
+    fn test_fault_tracking() {
+        let own = std::process::id();
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        let mut manager = ProcessManager::new(1);
+
+        // 不显示缺页列时只计算打开详情的进程
+        manager.update(&sys, true);
+        assert!(manager.fault_rates.is_empty());
+        manager.set_open_pids(vec![own]);
+        manager.update(&sys, true);
+        assert_eq!(manager.fault_rates.keys().collect::<Vec<_>>(), vec![&own]);
+
+        manager.set_track_faults(true);
+        assert!(manager.fault_rates.is_empty());
+        manager.update(&sys, true);
+        assert!(manager.fault_rates.len() > 1);
+    }
+
+    #[test]
+    fn test_seccomp_recheck_cache() {
+        let mut manager = ProcessManager::new(1);
+        // 不存在的 PID：重新检查时读取失败，模式保持为 0
+        let pid = u32::MAX - 1;
+        manager.processes.insert(pid, process(pid, "init", 0.0, 0));
+        let mut sandboxed = process(2, "browser", 0.0, 0);
+        sandboxed.seccomp_mode = 2;
+        manager.processes.insert(2, sandboxed);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // 创建时已读取，首次只记录；已启用的进程不再检查
+        manager.recheck_seccomp(at(0));
+        assert_eq!(manager.seccomp_checked.get(&pid), Some(&(0, at(0))));
+        assert!(!manager.seccomp_checked.contains_key(&2));
+
+        manager.recheck_seccomp(at(10));
+        assert_eq!(manager.seccomp_checked.get(&pid), Some(&(0, at(0))));
+        manager.recheck_seccomp(at(30));
+        assert_eq!(manager.seccomp_checked.get(&pid), Some(&(0, at(30))));
+
+        // PID 复用：新进程的启动时间不同，重新计时
+        manager.processes.get_mut(&pid).unwrap().start_time = 5;
+        manager.recheck_seccomp(at(31));
+        assert_eq!(manager.seccomp_checked.get(&pid), Some(&(5, at(31))));
+
+        manager.processes.remove(&pid);
+        manager.recheck_seccomp(at(32));
+        assert!(manager.seccomp_checked.is_empty());
+    }
+
+    #[test]
+    fn test_pid_reuse() {
+        let own = std::process::id();
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        let mut manager = ProcessManager::new(1);
+        manager.update(&sys, false);
+
+        // 同一 PID 上的旧进程（启动时间不同）留下的速率和 seccomp 检查记录
+        let stale = process(own, "old", 0.0, 0);
+        manager.processes.insert(own, stale);
+        manager.cpu_rates.insert(own, CounterRate::default());
+        manager.fault_rates.insert(own, Default::default());
+        manager.seccomp_checked.insert(own, (0, Instant::now()));
+
+        manager.update(&sys, false);
+        assert_eq!(manager.last_diff().exited, 1);
+        assert!(manager.recently_exited.iter().any(|(p, _)| p.pid == own && p.name == "old"));
+        assert_ne!(manager.process(own).unwrap().name, "old");
+        assert!(!manager.cpu_rates.contains_key(&own));
+        assert!(!manager.fault_rates.contains_key(&own));
+        assert!(!manager.seccomp_checked.contains_key(&own));
+    }
+
+    #[test]
+    fn test_parse_stat_processor() {
+        let stat = "1234 (a) b) R 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 0 1 0 987654 10000 200 \
+                    18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 7 0 0 0 0 0";
+        assert_eq!(parse_stat_processor(stat), Some(7));
+        assert_eq!(parse_stat_processor("1 (x) S 1 2 3"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpus_allowed_matches_syscall() {
+        let pid = std::process::id();
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get()).max(64);
+        let from_status = read_cpus_allowed(pid, cores).expect("无法读取 /proc/self/status");
+        assert_eq!(from_status, get_process_affinity(pid as i32, cores));
+        assert_eq!(read_process_affinity(pid, cores), from_status);
+        // 不存在的进程：系统调用和 status 都失败时视为可以在所有核心上运行
+        assert_eq!(read_process_affinity(u32::MAX - 1, 4), vec![0, 1, 2, 3]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_refresh_pid() {
+        use crate::system::{set_process_nice, set_scheduler, SchedulePolicy};
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().expect("无法启动 sleep");
+        let pid = child.id();
+        let mut manager = ProcessManager::new(1);
+        manager.processes.insert(pid, process(pid, "sleep", 0.0, 0));
+        let mut other = process(1, "init", 3.0, 10);
+        other.affinity = vec![5];
+        other.last_cpu = Some(5);
+        manager.processes.insert(1, other);
+
+        // 无需特权：降低优先级并切换到 Batch
+        set_process_nice(pid as i32, 7).unwrap();
+        set_scheduler(pid as i32, SchedulePolicy::Batch, 0, false).unwrap();
+        set_process_affinity(pid as i32, &[0]).unwrap();
+
+        assert!(manager.refresh_pid(pid));
+        let refreshed = manager.process(pid).unwrap();
+        assert_eq!(refreshed.sched_policy, SchedulePolicy::Batch);
+        assert_eq!(refreshed.priority, 7);
+        assert_eq!(refreshed.affinity, vec![0]);
+        assert!(refreshed.last_cpu.is_some());
+        // 其他进程保持不变
+        let other = manager.process(1).unwrap();
+        assert_eq!((other.affinity.as_slice(), other.last_cpu, other.cpu_usage), (&[5][..], Some(5), 3.0));
+
+        let _ = child.kill();
+        let _ = child.wait();
+        assert!(!manager.refresh_pid(pid));
+        assert!(!manager.refresh_pid(u32::MAX));
+    }
+
+    pub(crate) fn process(pid: u32, name: &str, cpu_usage: f32, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            parent_pid: None,
+            cmd: name.to_string(),
+            cpu_usage,
+            memory,
+            memory_delta: 0,
+            start_time: 0,
+            status: String::new(),
+            affinity: Vec::new(),
+            sched_policy: crate::system::SchedulePolicy::Other,
+            priority: 0,
+            sched_attr: crate::system::SchedAttr::default(),
+            last_cpu: None,
+            gpu_usage_percent: None,
+            container: None,
+            origin: ProcessOrigin::Unknown,
+            seccomp_mode: 0,
+            kernel_thread: false,
+            minor_faults: 0,
+            major_faults: 0,
+            minor_fault_rate: None,
+            major_fault_rate: None,
+            exe_deleted: false,
+            exe: None,
+            core_mismatch: None,
+            core_type_history: CoreTypeHistory::default(),
+        }
+    }
+
+    #[test]
+    fn test_sort_tie_break() {
+        let mut manager = ProcessManager::new(1);
+        for (pid, name, cpu, memory) in [(30, "b", 5.0, 100), (4, "a", 5.0, 300), (17, "c", 5.0, 200), (9, "d", 8.0, 0)] {
+            manager.processes.insert(pid, process(pid, name, cpu, memory));
+        }
+        let pids = |manager: &ProcessManager| manager.filtered_processes().iter().map(|p| p.pid).collect::<Vec<_>>();
+
+        // 默认按 CPU 降序，相同值按 PID 升序，重复排序结果不变
+        assert_eq!(pids(&manager), vec![9, 4, 17, 30]);
+        assert_eq!(pids(&manager), vec![9, 4, 17, 30]);
+        manager.set_sort(SortField::CpuUsage);
+        assert!(!manager.is_sort_desc());
+        assert_eq!(pids(&manager), vec![4, 17, 30, 9]);
+
+        // 次要排序：内存降序
+        manager.set_secondary_sort(Some(SortField::Memory));
+        assert_eq!(pids(&manager), vec![4, 17, 30, 9]);
+        manager.set_sort(SortField::CpuUsage);
+        assert_eq!(pids(&manager), vec![9, 4, 17, 30]);
+        manager.set_secondary_sort(Some(SortField::Name));
+        assert_eq!(pids(&manager), vec![9, 4, 30, 17]);
+
+        manager.set_sort(SortField::Pid);
+        assert_eq!(pids(&manager), vec![30, 17, 9, 4]);
+
+        // 核心匹配：不匹配的进程排在前面
+        manager.processes.get_mut(&17).unwrap().core_mismatch = Some(CoreMismatch::PriorityOnEfficiency);
+        manager.processes.get_mut(&30).unwrap().core_mismatch = Some(CoreMismatch::BackgroundOnPerformance);
+        manager.set_sort(SortField::CoreFit);
+        assert_eq!(pids(&manager), vec![17, 30, 4, 9]);
+    }
+
+    #[test]
+    fn test_group_by_name() {
+        let processes = [
+            process(10, "chrome", 5.0, 100),
+            process(3, "bash", 1.0, 50),
+            process(11, "chrome", 20.0, 300),
+            process(12, "chrome", 0.5, 10),
+        ];
+        let refs: Vec<&ProcessInfo> = processes.iter().collect();
+
+        let groups = group_by_name(&refs, SortField::CpuUsage, true);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "chrome");
+        assert_eq!(groups[0].pids, vec![10, 11, 12]);
+        assert_eq!(groups[0].count(), 3);
+        assert_eq!(groups[0].cpu_usage, 25.5);
+        assert_eq!(groups[0].memory, 410);
+
+        let groups = group_by_name(&refs, SortField::Pid, false);
+        assert_eq!(groups.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(), vec!["bash", "chrome"]);
+
+        let groups = group_by_name(&refs, SortField::Name, true);
+        assert_eq!(groups[0].name, "chrome");
+    }
+
+    #[test]
+    fn test_snapshot_json_roundtrip() {
+        let mut manager = ProcessManager::new(1);
+        for p in [process(10, "chrome", 5.0, 100), process(11, "chrome", 20.0, 300), process(3, "bash", 1.0, 50)] {
+            manager.processes.insert(p.pid, p);
+        }
+        manager.set_filter("chrome".to_string());
+        manager.set_origin_visible(ProcessOrigin::Kernel, false);
+
+        let snapshot = manager.snapshot(true, 1);
+        assert_eq!(snapshot.total, 2);
+        assert_eq!(snapshot.processes.len(), 1);
+        assert_eq!(snapshot.processes[0].pid, 11);
+        assert_eq!(manager.snapshot(false, 10).total, 3);
+
+        let json = manager.snapshot_json(true).unwrap();
+        let parsed: ProcessSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.filter, "chrome");
+        assert_eq!(parsed.hidden_origins, vec![ProcessOrigin::Kernel]);
+        assert_eq!(parsed.sort_by, SortField::CpuUsage);
+        assert!(parsed.sort_desc);
+        assert_eq!(parsed.processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![11, 10]);
+        assert_eq!(parsed.processes[1].memory, 100);
+    }
+
+    #[test]
+    fn test_category_filter() {
+        let mut manager = ProcessManager::new(1);
+        for p in [process(1, "firefox", 40.0, 0), process(2, "firefox", 2.0, 0), process(3, "rustc", 90.0, 0)] {
+            manager.processes.insert(p.pid, p);
+        }
+        let pids = |manager: &ProcessManager| {
+            let mut pids: Vec<u32> = manager.filtered_processes().iter().map(|p| p.pid).collect();
+            pids.sort();
+            pids
+        };
+        // 配置中不存在的分类被忽略
+        manager.set_categories(crate::system::builtin_categories(), &["高 CPU".to_string(), "已删除".to_string()]);
+        assert_eq!(manager.active_categories(), ["高 CPU".to_string()]);
+        assert_eq!(pids(&manager), vec![1, 3]);
+
+        // 多个分类之间、分类与搜索文本之间都是"与"
+        manager.set_category_active("浏览器", true);
+        assert_eq!(manager.active_categories(), ["浏览器".to_string(), "高 CPU".to_string()]);
+        assert_eq!(pids(&manager), vec![1]);
+        manager.set_category_active("高 CPU", false);
+        manager.set_filter("2".to_string());
+        assert_eq!(pids(&manager), vec![2]);
+        assert_eq!(manager.snapshot(true, 10).categories, vec!["浏览器".to_string()]);
+
+        // 搜索不受进程列表的过滤和分类影响，按 CPU 使用率降序
+        let search = |text: &str| manager.search(text).iter().map(|p| p.pid).collect::<Vec<u32>>();
+        assert_eq!(search(""), vec![3, 1, 2]);
+        assert_eq!(search(" FIRE "), vec![1, 2]);
+        assert!(search("bash").is_empty());
+    }
+
+    #[test]
+    fn test_lifecycle_callbacks() {
+        use std::sync::{Arc, Mutex};
+
+        let appeared = Arc::new(Mutex::new(Vec::new()));
+        let disappeared = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ProcessManager::new(1);
+        let sink = appeared.clone();
+        manager.on_process_appeared(move |p| sink.lock().unwrap().push(p.pid));
+        let sink = disappeared.clone();
+        manager.on_process_disappeared(move |pid| sink.lock().unwrap().push(pid));
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let child_pid = child.id();
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, false);
+        assert!(appeared.lock().unwrap().contains(&std::process::id()));
+        assert!(appeared.lock().unwrap().contains(&child_pid));
+
+        // 已知进程不会重复通知
+        appeared.lock().unwrap().clear();
+        manager.update(&sys, false);
+        assert!(!appeared.lock().unwrap().contains(&child_pid));
+
+        let _ = child.kill();
+        let _ = child.wait();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, false);
+        assert!(disappeared.lock().unwrap().contains(&child_pid));
+    }
+
+    #[test]
+    fn test_watchlist() {
+        let mut manager = ProcessManager::new(1);
+        manager.processes.insert(1, process(1, "init", 0.0, 0));
+        manager.recently_exited.push((process(42, "make", 0.0, 0), Instant::now()));
+
+        manager.watch(1);
+        manager.watch(42);
+        manager.watch(1);
+        manager.watch(7);
+        assert_eq!(manager.watched(), &[1, 42, 7]);
+        manager.toggle_watch(7);
+        assert!(!manager.is_watched(7));
+
+        // sys 中没有的进程视为已退出，名称取自进程列表或最近退出列表
+        let exited = manager.refresh_watched(&System::new());
+        assert_eq!(exited, vec![(1, "init".to_string()), (42, "make".to_string())]);
+        assert!(manager.watched().is_empty());
+
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, false);
+        let own = std::process::id();
+        manager.watch(own);
+        assert!(manager.refresh_watched(&sys).is_empty());
+        assert_eq!(manager.watched(), &[own]);
+    }
+
+    /// 对比完整刷新与分级刷新（只刷新活跃进程）的耗时：cargo test --release -- --ignored --nocapture bench_tiered
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore]
+    fn bench_tiered_refresh() {
+        let mut sys = System::new();
+        let mut manager = ProcessManager::new(sys.cpus().len().max(1));
+        manager.set_tiered_refresh(true);
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, true);
+        std::thread::sleep(Duration::from_millis(500));
+
+        let start = Instant::now();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        manager.update(&sys, true);
+        let full = start.elapsed();
+
+        let start = Instant::now();
+        let hot = manager.hot_pids();
+        let pids: Vec<sysinfo::Pid> = hot.iter().map(|&pid| sysinfo::Pid::from_u32(pid)).collect();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&pids), true);
+        manager.update_hot(&sys, &hot, true);
+        let tiered = start.elapsed();
+
+        println!("{} 个进程: 完整刷新 {:?}, 分级刷新 {} 个活跃进程 {:?}", manager.processes.len(), full, hot.len(), tiered);
+    }
+
+    /// 对比两种亲和性读取方式的耗时：cargo test --release -- --ignored --nocapture bench_affinity
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore]
+    fn bench_affinity_sources() {
+        let pids: Vec<u32> = std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        let cores = 64;
+
+        let start = Instant::now();
+        for &pid in &pids {
+            std::hint::black_box(get_process_affinity(pid as i32, cores));
+        }
+        let syscall = start.elapsed();
+
+        let start = Instant::now();
+        for &pid in &pids {
+            std::hint::black_box(read_process_affinity(pid, cores));
+        }
+        let status = start.elapsed();
+
+        println!("{} 个进程: sched_getaffinity {:?}, status {:?}", pids.len(), syscall, status);
+    }
+}
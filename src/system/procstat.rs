@@ -0,0 +1,254 @@
+//! 基于 /proc/stat 的 CPU 使用率 - 对两次采样的 jiffies 求差
+//! 与内核统计口径一致，并提供用户态 / 内核态 / iowait 等分项
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 核心使用率的数据来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UsageSource {
+    /// sysinfo 库
+    #[default]
+    Sysinfo,
+    /// 直接读取 /proc/stat
+    ProcStat,
+}
+
+impl UsageSource {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            UsageSource::Sysinfo => "sysinfo",
+            UsageSource::ProcStat => "/proc/stat",
+        }
+    }
+}
+
+/// /proc/stat 中一个 cpu 行的累计时间 (jiffies)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// 总时间（guest 已计入 user，不重复累加）
+    pub fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.idle + self.iowait + self.irq + self.softirq + self.steal
+    }
+}
+
+/// 两次采样之间的使用率分项 (%)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageBreakdown {
+    /// 总使用率（除 idle 和 iowait 外的所有时间）
+    pub usage: f32,
+    /// 用户态（含 nice）
+    pub user: f32,
+    /// 内核态
+    pub system: f32,
+    /// 等待 I/O
+    pub iowait: f32,
+    /// 硬中断和软中断
+    pub irq: f32,
+    /// 被虚拟机监控程序占用
+    pub steal: f32,
+}
+
+impl UsageBreakdown {
+    /// 由两次采样计算，时间未前进或计数器回退（CPU 热插拔）时返回 None
+    pub fn between(previous: &CpuTimes, current: &CpuTimes) -> Option<Self> {
+        let total = current.total().checked_sub(previous.total()).filter(|&t| t > 0)? as f32;
+        let delta = |cur: u64, prev: u64| cur.saturating_sub(prev) as f32 / total * 100.0;
+        let idle = delta(current.idle, previous.idle);
+        let iowait = delta(current.iowait, previous.iowait);
+        Some(Self {
+            usage: (100.0 - idle - iowait).clamp(0.0, 100.0),
+            user: delta(current.user, previous.user) + delta(current.nice, previous.nice),
+            system: delta(current.system, previous.system),
+            iowait,
+            irq: delta(current.irq, previous.irq) + delta(current.softirq, previous.softirq),
+            steal: delta(current.steal, previous.steal),
+        })
+    }
+}
+
+/// 解析 /proc/stat，返回 (汇总行, 各 CPU 行)
+pub fn parse_proc_stat(content: &str) -> (Option<CpuTimes>, Vec<(usize, CpuTimes)>) {
+    let mut aggregate = None;
+    let mut cpus = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(label) = fields.next().and_then(|l| l.strip_prefix("cpu")) else {
+            continue;
+        };
+        let values: Vec<u64> = fields.map_while(|v| v.parse().ok()).collect();
+        // 旧内核没有 steal 等字段，缺失的按 0 处理
+        if values.len() < 4 {
+            continue;
+        }
+        let field = |i: usize| values.get(i).copied().unwrap_or(0);
+        let times = CpuTimes {
+            user: field(0),
+            nice: field(1),
+            system: field(2),
+            idle: field(3),
+            iowait: field(4),
+            irq: field(5),
+            softirq: field(6),
+            steal: field(7),
+        };
+        if label.is_empty() {
+            aggregate = Some(times);
+        } else if let Ok(id) = label.parse() {
+            cpus.push((id, times));
+        }
+    }
+    (aggregate, cpus)
+}
+
+/// /proc/stat 采样器：保存上一次的计数用于求差
+#[derive(Debug, Clone, Default)]
+pub struct ProcStatSampler {
+    previous_total: Option<CpuTimes>,
+    previous: HashMap<usize, CpuTimes>,
+}
+
+impl ProcStatSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 输入 /proc/stat 内容，返回 (总体, 按 CPU ID) 的分项
+    /// 首次采样没有差值，对应项缺失；离线的 CPU 不出现在结果中
+    pub fn sample(&mut self, content: &str) -> (Option<UsageBreakdown>, HashMap<usize, UsageBreakdown>) {
+        let (aggregate, cpus) = parse_proc_stat(content);
+
+        let total = match (&self.previous_total, &aggregate) {
+            (Some(previous), Some(current)) => UsageBreakdown::between(previous, current),
+            _ => None,
+        };
+        self.previous_total = aggregate;
+
+        let mut per_cpu = HashMap::new();
+        let mut current = HashMap::with_capacity(cpus.len());
+        for (id, times) in cpus {
+            if let Some(breakdown) = self.previous.get(&id).and_then(|prev| UsageBreakdown::between(prev, &times)) {
+                per_cpu.insert(id, breakdown);
+            }
+            current.insert(id, times);
+        }
+        self.previous = current;
+
+        (total, per_cpu)
+    }
+
+    /// 读取并采样 /proc/stat，无法读取时返回空结果
+    pub fn sample_system(&mut self) -> (Option<UsageBreakdown>, HashMap<usize, UsageBreakdown>) {
+        match std::fs::read_to_string("/proc/stat") {
+            Ok(content) => self.sample(&content),
+            Err(_) => (None, HashMap::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = "\
+cpu  1000 100 500 8000 200 50 50 0 0 0
+cpu0 500 50 250 4000 100 25 25 0 0 0
+cpu1 500 50 250 4000 100 25 25 0 0 0
+intr 12345 0 0
+ctxt 67890
+";
+
+    const SECOND: &str = "\
+cpu  1400 100 700 8600 300 50 50 0 0 0
+cpu0 800 50 350 4200 100 25 25 0 0 0
+cpu1 600 50 350 4400 200 25 25 0 0 0
+cpu2 10 0 10 80 0 0 0 0 0 0
+";
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let (aggregate, cpus) = parse_proc_stat(FIRST);
+        assert_eq!(aggregate.unwrap().total(), 9900);
+        assert_eq!(cpus.len(), 2);
+        assert_eq!(cpus[1].0, 1);
+        assert_eq!(cpus[1].1.iowait, 100);
+
+        // 旧内核只有 4 个字段
+        let (_, cpus) = parse_proc_stat("cpu0 1 2 3 4\n");
+        assert_eq!(cpus[0].1.total(), 10);
+    }
+
+    #[test]
+    fn test_jiffy_differencing() {
+        let mut sampler = ProcStatSampler::new();
+
+        // 首次采样没有差值
+        let (total, per_cpu) = sampler.sample(FIRST);
+        assert_eq!(total, None);
+        assert!(per_cpu.is_empty());
+
+        let (total, per_cpu) = sampler.sample(SECOND);
+        // 汇总：Δ 总计 1300，其中 idle 600，iowait 100
+        let total = total.unwrap();
+        assert!((total.usage - 600.0 / 13.0).abs() < 1e-3);
+
+        // cpu0：Δ user 300 + system 100 + idle 200 = 600
+        let cpu0 = per_cpu[&0];
+        assert!((cpu0.usage - 400.0 / 6.0).abs() < 1e-3);
+        assert!((cpu0.user - 50.0).abs() < 1e-3);
+        assert!((cpu0.iowait - 0.0).abs() < 1e-3);
+
+        // cpu1：Δ user 100 + system 100 + idle 400 + iowait 100 = 700
+        let cpu1 = per_cpu[&1];
+        assert!((cpu1.iowait - 100.0 / 7.0).abs() < 1e-3);
+        assert!((cpu1.usage - 200.0 / 7.0).abs() < 1e-3);
+
+        // 新上线的 cpu2 没有上一次数据
+        assert!(!per_cpu.contains_key(&2));
+
+        // 计数器回退（CPU 下线后重新上线）时跳过
+        let (_, per_cpu) = sampler.sample(FIRST);
+        assert!(per_cpu.is_empty());
+    }
+
+    #[test]
+    fn test_captured_breakdown() {
+        // 真实机器上间隔约 1 秒的两次采样（期间有同步写盘），intr 行已截断
+        const BEFORE: &str = "\
+cpu  291304 0 37764 686652 2836 0 25 993 0 0
+cpu0 291304 0 37764 686652 2836 0 25 993 0 0
+intr 1387672 0 0 0 0 0 0 0
+ctxt 2718341
+";
+        const AFTER: &str = "\
+cpu  291312 0 37777 686824 2842 0 25 993 0 0
+cpu0 291312 0 37777 686824 2842 0 25 993 0 0
+intr 1387837 0 0 0 0 0 0 0
+ctxt 2718702
+";
+        let mut sampler = ProcStatSampler::new();
+        sampler.sample(BEFORE);
+        let (total, per_cpu) = sampler.sample(AFTER);
+
+        // Δ user 8、system 13、idle 172、iowait 6，共 199
+        let cpu0 = per_cpu[&0];
+        assert_eq!(total, Some(cpu0));
+        assert!((cpu0.user - 800.0 / 199.0).abs() < 1e-3);
+        assert!((cpu0.system - 1300.0 / 199.0).abs() < 1e-3);
+        assert!((cpu0.iowait - 600.0 / 199.0).abs() < 1e-3);
+        assert!((cpu0.usage - 2100.0 / 199.0).abs() < 1e-3);
+        assert_eq!((cpu0.irq, cpu0.steal), (0.0, 0.0));
+    }
+}
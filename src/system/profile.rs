@@ -0,0 +1,248 @@
+//! 调优档案 - 把自定义预设和自动规则打包成可分享的单个文件
+//! 档案库位于 ~/.config/hexin/profiles/
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{AutoRule, SchedulePreset};
+
+/// 当前档案格式版本，格式有不兼容的变化时递增
+pub const PROFILE_SCHEMA_VERSION: u32 = 1;
+
+fn default_schema_version() -> u32 {
+    PROFILE_SCHEMA_VERSION
+}
+
+/// 档案元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileMetadata {
+    /// 档案名称
+    pub name: String,
+    /// 作者
+    #[serde(default)]
+    pub author: String,
+    /// 目标 CPU 型号（留空表示不限）
+    #[serde(default)]
+    pub target_cpu: String,
+    /// 说明
+    #[serde(default)]
+    pub description: String,
+}
+
+/// 调优档案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// 档案格式版本
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    pub metadata: ProfileMetadata,
+    /// 自定义预设
+    #[serde(default)]
+    pub presets: Vec<SchedulePreset>,
+    /// 自动规则
+    #[serde(default)]
+    pub rules: Vec<AutoRule>,
+}
+
+/// 档案与当前系统的兼容性检查结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileCheck {
+    /// 可以继续但需要提示的问题
+    pub warnings: Vec<String>,
+    /// 无法启用的问题
+    pub errors: Vec<String>,
+}
+
+impl ProfileCheck {
+    /// 是否可以启用
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Profile {
+    /// 由当前设置创建档案
+    pub fn capture(metadata: ProfileMetadata, presets: Vec<SchedulePreset>, rules: Vec<AutoRule>) -> Self {
+        Self {
+            schema_version: PROFILE_SCHEMA_VERSION,
+            metadata,
+            presets,
+            rules,
+        }
+    }
+
+    /// 从 TOML 文本解析
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let profile: Profile = toml::from_str(content).map_err(|e| format!("档案格式错误: {}", e))?;
+        if profile.schema_version > PROFILE_SCHEMA_VERSION {
+            return Err(format!(
+                "档案格式版本 {} 高于当前支持的 {}，请更新 hexin",
+                profile.schema_version, PROFILE_SCHEMA_VERSION
+            ));
+        }
+        Ok(profile)
+    }
+
+    /// 导出到文件
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string_pretty(self).map_err(|e| format!("序列化档案失败: {}", e))?;
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        fs::write(path, content).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+    }
+
+    /// 从文件导入
+    pub fn import(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+        Self::from_toml(&content)
+    }
+
+    /// 检查档案是否适用于当前 CPU
+    pub fn check(&self, cpu_model: &str, logical_cores: usize) -> ProfileCheck {
+        let mut check = ProfileCheck::default();
+
+        let target = self.metadata.target_cpu.trim();
+        if !target.is_empty() && target != cpu_model.trim() {
+            check.warnings.push(format!("档案针对 {}，当前 CPU 为 {}", target, cpu_model.trim()));
+        }
+
+        let presets = self
+            .presets
+            .iter()
+            .map(|p| (format!("预设 '{}'", p.name), p))
+            .chain(self.rules.iter().map(|r| (format!("规则 '{}'", r.name), &r.preset)));
+        for (owner, preset) in presets {
+            let out_of_range: Vec<String> = preset
+                .affinity_cores
+                .iter()
+                .flatten()
+                .filter(|&&core| core >= logical_cores)
+                .map(|core| core.to_string())
+                .collect();
+            if !out_of_range.is_empty() {
+                check.errors.push(format!(
+                    "{} 使用了不存在的核心 {}（当前共 {} 个逻辑核心）",
+                    owner,
+                    out_of_range.join(","),
+                    logical_cores
+                ));
+            }
+        }
+        check
+    }
+
+    /// 档案库目录
+    pub fn gallery_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("profiles"))
+    }
+
+    /// 档案库中的所有档案（按名称排序，无法解析的文件跳过）
+    pub fn gallery() -> Vec<(PathBuf, Profile)> {
+        let Some(entries) = Self::gallery_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<(PathBuf, Profile)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| Self::import(&path).ok().map(|profile| (path, profile)))
+            .collect();
+        profiles.sort_by(|a, b| a.1.metadata.name.cmp(&b.1.metadata.name));
+        profiles
+    }
+
+    /// 保存到档案库，返回保存路径
+    pub fn save_to_gallery(&self) -> Result<PathBuf, String> {
+        let dir = Self::gallery_dir().ok_or("无法确定配置目录")?;
+        let path = dir.join(profile_file_name(&self.metadata.name));
+        self.export(&path)?;
+        Ok(path)
+    }
+}
+
+/// 由档案名生成文件名（替换路径分隔符等不安全字符）
+pub fn profile_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let stem = if stem.is_empty() { "profile".to_string() } else { stem };
+    format!("{}.toml", stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::SchedulePolicy;
+
+    fn preset(name: &str, cores: Option<Vec<usize>>) -> SchedulePreset {
+        SchedulePreset {
+            name: name.to_string(),
+            description: String::new(),
+            policy: SchedulePolicy::Other,
+            priority: -5,
+            affinity_cores: cores,
+            io_priority: None,
+            oom_score_adj: None,
+            is_builtin: false,
+        }
+    }
+
+    fn profile() -> Profile {
+        Profile::capture(
+            ProfileMetadata {
+                name: "7950X3D 游戏档案".to_string(),
+                author: "alice".to_string(),
+                target_cpu: "AMD Ryzen 9 7950X3D 16-Core Processor".to_string(),
+                description: "游戏绑定 V-Cache CCD".to_string(),
+            },
+            vec![preset("游戏", Some((0..16).collect()))],
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_profile_roundtrip() {
+        let original = profile();
+        let content = toml::to_string_pretty(&original).unwrap();
+        let parsed = Profile::from_toml(&content).unwrap();
+        assert_eq!(parsed.schema_version, PROFILE_SCHEMA_VERSION);
+        assert_eq!(parsed.metadata, original.metadata);
+        assert_eq!(parsed.presets[0].affinity_cores, original.presets[0].affinity_cores);
+    }
+
+    #[test]
+    fn test_profile_schema_version() {
+        let content = toml::to_string_pretty(&profile()).unwrap();
+        let future = content.replace("schema_version = 1", "schema_version = 99");
+        assert!(Profile::from_toml(&future).is_err());
+
+        // 缺少版本号时按当前版本处理
+        let legacy = content.replace("schema_version = 1\n", "");
+        assert_eq!(Profile::from_toml(&legacy).unwrap().schema_version, PROFILE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_profile_check() {
+        let profile = profile();
+        let same = profile.check("AMD Ryzen 9 7950X3D 16-Core Processor", 32);
+        assert_eq!(same, ProfileCheck::default());
+
+        let other_cpu = profile.check("Intel(R) Core(TM) i9-13900K", 32);
+        assert!(other_cpu.is_ok());
+        assert_eq!(other_cpu.warnings.len(), 1);
+
+        let too_few_cores = profile.check("AMD Ryzen 9 7950X3D 16-Core Processor", 8);
+        assert!(!too_few_cores.is_ok());
+        assert!(too_few_cores.errors[0].contains("8,9,10,11,12,13,14,15"));
+    }
+
+    #[test]
+    fn test_profile_file_name() {
+        assert_eq!(profile_file_name("编译/服务器 档案"), "编译_服务器_档案.toml");
+        assert_eq!(profile_file_name("  "), "profile.toml");
+    }
+}
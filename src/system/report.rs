@@ -0,0 +1,135 @@
+//! 进程状态报告 - 导出为 JSON 附在性能问题报告中
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{read_process_cgroup, read_process_processor, CpuInfo, CpuVendor, ProcessInfo};
+
+/// 报告元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportMetadata {
+    pub model_name: String,
+    pub vendor: CpuVendor,
+    pub logical_cores: usize,
+    /// Unix 时间戳（秒）
+    pub timestamp: u64,
+    pub hexin_version: &'static str,
+}
+
+/// 报告中的单个进程
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessReportEntry {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    /// cgroup 路径（导出时读取，进程已退出时为 None）
+    pub cgroup: Option<String>,
+}
+
+/// 进程状态报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessReport {
+    pub metadata: ReportMetadata,
+    pub processes: Vec<ProcessReportEntry>,
+}
+
+impl ProcessReport {
+    /// 为给定进程生成报告（按 PID 排序）
+    /// 分级刷新中进程上次运行的核心可能已过时，导出时重新读取（进程已退出时保留原值）
+    pub fn new(processes: &[&ProcessInfo], cpu_info: &CpuInfo, timestamp: u64) -> Self {
+        let mut processes: Vec<ProcessReportEntry> = processes
+            .iter()
+            .map(|&p| {
+                let mut process = p.clone();
+                if let Some(cpu) = read_process_processor(p.pid) {
+                    process.last_cpu = Some(cpu);
+                }
+                ProcessReportEntry { process, cgroup: read_process_cgroup(p.pid) }
+            })
+            .collect();
+        processes.sort_by_key(|e| e.process.pid);
+
+        Self {
+            metadata: ReportMetadata {
+                model_name: cpu_info.model_name.clone(),
+                vendor: cpu_info.vendor,
+                logical_cores: cpu_info.logical_cores,
+                timestamp,
+                hexin_version: env!("CARGO_PKG_VERSION"),
+            },
+            processes,
+        }
+    }
+
+    /// 序列化为格式化的 JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化报告失败: {}", e))
+    }
+
+    /// 写入文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = self.to_json()?;
+        fs::write(path, json).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+    }
+}
+
+/// 当前 Unix 时间戳（秒）
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// 报告文件名
+pub fn report_file_name(timestamp: u64) -> String {
+    format!("hexin_report_{}.json", timestamp)
+}
+
+/// 默认保存路径（主目录，取不到时为当前目录）
+pub fn default_report_path(timestamp: u64) -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(report_file_name(timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_file_name() {
+        assert_eq!(report_file_name(1700000000), "hexin_report_1700000000.json");
+        assert!(default_report_path(1).ends_with("hexin_report_1.json"));
+    }
+
+    #[test]
+    fn test_report_json() {
+        use crate::system::process::tests::process;
+
+        let mut exited = process(u32::MAX - 1, "exited", 0.0, 0);
+        exited.last_cpu = Some(3);
+        let own = process(std::process::id(), "hexin", 12.5, 4096);
+        let mut cpu_info = CpuInfo::empty();
+        cpu_info.model_name = "AMD Ryzen 9 7950X3D".to_string();
+        cpu_info.logical_cores = 32;
+
+        let report = ProcessReport::new(&[&exited, &own], &cpu_info, 1700000000);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["metadata"]["model_name"], "AMD Ryzen 9 7950X3D");
+        assert_eq!(json["metadata"]["logical_cores"], 32);
+        assert_eq!(json["metadata"]["timestamp"], 1700000000);
+        let processes = json["processes"].as_array().unwrap();
+        assert_eq!(processes.len(), 2);
+        // 按 PID 排序，进程字段展开在条目中
+        assert_eq!(processes[0]["pid"], std::process::id());
+        assert_eq!(processes[0]["name"], "hexin");
+        assert_eq!(processes[0]["memory"], 4096);
+        // 运行中的进程导出时重新读取上次运行的核心
+        assert!(processes[0]["last_cpu"].is_u64());
+        assert_eq!(processes[1]["pid"], u32::MAX - 1);
+        assert_eq!(processes[1]["last_cpu"], 3);
+        assert!(processes[1]["cgroup"].is_null());
+    }
+}
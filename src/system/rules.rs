@@ -0,0 +1,1140 @@
+//! 自动规则 - 新进程名称匹配时自动应用调度设置
+//! 规则保存在 ~/.config/hexin/rules.toml
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::{
+    apply_changes, differing_changes, format_cpu_list, merge_into_preset, preset_changes, read_process_start_time,
+    unix_timestamp, ApplyReport, DeferredAction, ProcessInfo, SchedChange, SchedulePolicy, SchedulePreset,
+};
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 自动规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoRule {
+    /// 规则名称
+    pub name: String,
+    /// 进程名匹配模式（不区分大小写，支持 * 通配符）
+    pub pattern: String,
+    /// 要应用的调度设置
+    pub preset: SchedulePreset,
+    /// 是否启用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 进程启动（或 exec）多少秒后再应用（应对启动后立即 re-exec 的程序）
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// 匹配模式
+    #[serde(default)]
+    pub match_mode: RuleMatchMode,
+    /// 同时应用此规则的最多进程数（None 表示不限）
+    #[serde(default)]
+    pub max_instances: Option<usize>,
+}
+
+/// 规则匹配模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleMatchMode {
+    /// 每个新实例都应用
+    #[default]
+    EveryInstance,
+    /// 只应用于第一个匹配的进程
+    Once,
+}
+
+impl AutoRule {
+    /// 亲和性模板规则：固定同名进程的亲和性，调度策略和优先级沿用模板进程当前的值
+    pub fn affinity_template(process_name: &str, cores: &[usize], policy: SchedulePolicy, priority: i32) -> Self {
+        let name = format!("{} 亲和性", process_name);
+        Self {
+            name: name.clone(),
+            pattern: process_name.to_string(),
+            preset: SchedulePreset {
+                name,
+                description: format!("绑定 CPU {}", format_cpu_list(cores)),
+                policy,
+                priority,
+                affinity_cores: Some(cores.to_vec()),
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: false,
+            },
+            enabled: true,
+            delay_secs: 0,
+            match_mode: RuleMatchMode::EveryInstance,
+            max_instances: None,
+        }
+    }
+
+    /// 进程名是否匹配
+    pub fn matches(&self, process_name: &str) -> bool {
+        wildcard_match(&self.pattern.to_lowercase(), &process_name.to_lowercase())
+    }
+}
+
+/// 简单通配符匹配：* 匹配任意长度字符
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // 没有通配符时需要完全匹配
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// 两个通配符模式是否可能匹配同一个名称（不区分大小写）
+pub fn patterns_overlap(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut memo = vec![vec![None; b.len() + 1]; a.len() + 1];
+    overlap_from(&a, &b, 0, 0, &mut memo)
+}
+
+/// a[i..] 与 b[j..] 是否存在共同匹配的名称
+fn overlap_from(a: &[char], b: &[char], i: usize, j: usize, memo: &mut Vec<Vec<Option<bool>>>) -> bool {
+    if let Some(result) = memo[i][j] {
+        return result;
+    }
+    let result = match (a.get(i), b.get(j)) {
+        (None, None) => true,
+        // * 可以为空，也可以吞下对方的一个字符
+        (Some('*'), other) => overlap_from(a, b, i + 1, j, memo) || (other.is_some() && overlap_from(a, b, i, j + 1, memo)),
+        (other, Some('*')) => overlap_from(a, b, i, j + 1, memo) || (other.is_some() && overlap_from(a, b, i + 1, j, memo)),
+        (Some(x), Some(y)) => x == y && overlap_from(a, b, i + 1, j + 1, memo),
+        _ => false,
+    };
+    memo[i][j] = Some(result);
+    result
+}
+
+/// 两条启用的规则可能匹配同一进程且设置相互矛盾
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleOverlap {
+    /// 生效的规则（匹配顺序靠前）
+    pub winner: String,
+    /// 对两者都匹配的进程不会生效的规则
+    pub shadowed: String,
+    /// 取值不同的设置项
+    pub labels: Vec<&'static str>,
+}
+
+impl RuleOverlap {
+    /// 提示文本
+    pub fn describe(&self) -> String {
+        format!(
+            "'{}' 与 '{}' 可能匹配同一进程，但{}不同；两者都匹配时 '{}' 生效",
+            self.winner,
+            self.shadowed,
+            self.labels.join("、"),
+            self.winner
+        )
+    }
+}
+
+/// 规则文件格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<AutoRule>,
+    #[serde(default)]
+    deferred: Vec<DeferredAction>,
+}
+
+/// 一次规则应用的结果
+#[derive(Debug, Clone)]
+pub struct RuleApplication {
+    /// 进程 ID
+    pub pid: u32,
+    /// 进程名称
+    pub process_name: String,
+    /// 规则名称
+    pub rule_name: String,
+    /// 来自一次性延后动作（应用后已从列表删除）
+    pub deferred: bool,
+    /// 应用的设置项
+    pub changes: Vec<SchedChange>,
+    /// 应用结果
+    pub report: ApplyReport,
+}
+
+/// 手动修改与自动规则的冲突
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleConflict {
+    /// 冲突的规则
+    pub rule_name: String,
+    /// 取值不同的设置项
+    pub labels: Vec<&'static str>,
+    /// 规则是否可修改（profiles.d 规则只读）
+    pub editable: bool,
+}
+
+/// 冲突的处理方式（由规则引擎执行）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleResolution {
+    /// 覆盖一次：规则不再作用于此进程
+    Exempt(u32),
+    /// 用手动修改的值更新规则
+    UpdateRule { rule_name: String, changes: Vec<SchedChange> },
+}
+
+/// 规则快照，供界面在手动应用前检查冲突
+#[derive(Debug, Clone, Default)]
+pub struct RuleGuard {
+    /// 当前生效的规则（按匹配顺序）及是否可修改
+    rules: Vec<(AutoRule, bool)>,
+    /// 已选择覆盖一次的进程
+    exempt: HashSet<u32>,
+}
+
+impl RuleGuard {
+    /// 手动应用的设置是否会与匹配此进程的规则冲突
+    pub fn conflict(&self, pid: u32, process_name: &str, changes: &[SchedChange]) -> Option<RuleConflict> {
+        if self.exempt.contains(&pid) {
+            return None;
+        }
+        let (rule, editable) = self.rules.iter().find(|(rule, _)| rule.matches(process_name))?;
+        let labels = differing_changes(changes, &preset_changes(&rule.preset));
+        (!labels.is_empty()).then(|| RuleConflict {
+            rule_name: rule.name.clone(),
+            labels,
+            editable: *editable,
+        })
+    }
+}
+
+/// 规则引擎访问系统的接口（便于测试时替换）
+pub trait SystemSource {
+    /// 进程启动时间，进程不存在时返回 None
+    fn start_time(&self, pid: u32) -> Option<u64>;
+
+    /// 应用调度设置
+    fn apply(&mut self, pid: u32, changes: &[SchedChange], logical_cores: usize) -> ApplyReport;
+}
+
+/// 读取 /proc 并直接修改进程的实现
+pub struct LiveSystem;
+
+impl SystemSource for LiveSystem {
+    fn start_time(&self, pid: u32) -> Option<u64> {
+        read_process_start_time(pid)
+    }
+
+    fn apply(&mut self, pid: u32, changes: &[SchedChange], logical_cores: usize) -> ApplyReport {
+        apply_changes(pid as i32, changes, logical_cores)
+    }
+}
+
+/// 等待延迟到期的规则应用
+#[derive(Debug, Clone)]
+struct PendingApplication {
+    pid: u32,
+    process_name: String,
+    /// 入队时的进程启动时间，到期时不一致说明进程已退出或 PID 被复用
+    start_time: Option<u64>,
+    rule_name: String,
+    deadline: Instant,
+}
+
+/// 规则引擎
+pub struct RuleEngine {
+    /// 用户规则（按顺序匹配，第一条匹配的规则生效）
+    rules: Vec<AutoRule>,
+    /// profiles.d 中的规则（在所有用户规则之后匹配）
+    dropin_rules: Vec<AutoRule>,
+    /// 逻辑核心数
+    logical_cores: usize,
+    /// 系统访问
+    source: Box<dyn SystemSource + Send>,
+    /// 已处理过的进程
+    handled: HashSet<u32>,
+    /// 等待延迟到期的应用
+    pending: Vec<PendingApplication>,
+    /// 当前已应用规则的进程 (PID -> 规则名称)
+    active: HashMap<u32, String>,
+    /// 已触发过的 Once 规则
+    fired_once: HashSet<String>,
+    /// 受定时控制、当前不在时间段内的规则（视为停用）
+    scheduled_inactive: HashSet<String>,
+    /// 尚未被取走的应用结果
+    applications: Vec<RuleApplication>,
+    /// 手动覆盖过规则的进程（不再对其应用规则）
+    exempt: HashSet<u32>,
+    /// 一次性延后动作（触发或过期后删除）
+    deferred: Vec<DeferredAction>,
+    /// 规则、覆盖记录或延后动作每次变化时递增，界面据此判断快照是否需要更新
+    revision: u64,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<AutoRule>, logical_cores: usize) -> Self {
+        Self::with_source(rules, logical_cores, Box::new(LiveSystem))
+    }
+
+    /// 使用指定的系统访问实现创建
+    pub fn with_source(rules: Vec<AutoRule>, logical_cores: usize, source: Box<dyn SystemSource + Send>) -> Self {
+        Self {
+            rules,
+            dropin_rules: Vec::new(),
+            logical_cores,
+            source,
+            handled: HashSet::new(),
+            pending: Vec::new(),
+            active: HashMap::new(),
+            fired_once: HashSet::new(),
+            scheduled_inactive: HashSet::new(),
+            applications: Vec::new(),
+            exempt: HashSet::new(),
+            deferred: Vec::new(),
+            revision: 0,
+        }
+    }
+
+    /// 获取规则文件路径
+    fn rules_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("rules.toml"))
+    }
+
+    /// 加载已保存的规则
+    pub fn load(logical_cores: usize) -> Self {
+        let file = Self::rules_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str::<RuleFile>(&content).ok())
+            .unwrap_or_default();
+        let mut engine = Self::new(file.rules, logical_cores);
+        engine.deferred = file.deferred;
+        engine
+    }
+
+    /// 保存规则
+    pub fn save(&self) {
+        if let Some(path) = Self::rules_path() {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let file = RuleFile { rules: self.rules.clone(), deferred: self.deferred.clone() };
+            if let Ok(content) = toml::to_string_pretty(&file) {
+                let _ = fs::write(&path, content);
+            }
+        }
+    }
+
+    /// 用户规则（不含 profiles.d）
+    pub fn rules(&self) -> &[AutoRule] {
+        &self.rules
+    }
+
+    /// 替换全部规则（等待中的延迟应用一并丢弃，已在运行的进程不受影响）
+    pub fn set_rules(&mut self, rules: Vec<AutoRule>) {
+        self.rules = rules;
+        self.pending.clear();
+        self.revision += 1;
+    }
+
+    /// 替换 profiles.d 规则，等待中的延迟应用只保留规则仍存在的；
+    /// 返回新增或内容有变化的已启用规则名称（调用方用 `apply_to_running` 重新应用到已在运行的进程）
+    pub fn set_dropin_rules(&mut self, rules: Vec<AutoRule>) -> Vec<String> {
+        let changed = rules
+            .iter()
+            .filter(|rule| rule.enabled && !self.dropin_rules.contains(rule))
+            .map(|rule| rule.name.clone())
+            .collect();
+        self.dropin_rules = rules;
+        let names: HashSet<String> = self.all_rules().map(|r| r.name.clone()).collect();
+        self.pending.retain(|p| names.contains(&p.rule_name));
+        self.revision += 1;
+        changed
+    }
+
+    /// 加入亲和性模板规则：已有匹配该进程名的用户规则时只更新其亲和性（新规则排在它之后不会生效），
+    /// 否则追加新规则；返回结果说明，调用方负责保存
+    pub fn add_affinity_rule(&mut self, rule: AutoRule) -> String {
+        self.revision += 1;
+        if let Some(existing) = self.rules.iter_mut().find(|r| r.enabled && r.matches(&rule.pattern)) {
+            existing.preset.affinity_cores = rule.preset.affinity_cores;
+            return format!("已更新规则 '{}' 的亲和性", existing.name);
+        }
+        let message = format!("已创建规则 '{}'", rule.name);
+        self.rules.push(rule);
+        message
+    }
+
+    /// 全部规则的名称（用户规则在前）
+    pub fn rule_names(&self) -> Vec<String> {
+        self.all_rules().map(|r| r.name.clone()).collect()
+    }
+
+    /// 用户规则在前、profiles.d 规则在后的全部规则
+    fn all_rules(&self) -> impl Iterator<Item = &AutoRule> {
+        self.rules.iter().chain(&self.dropin_rules)
+    }
+
+    /// 进程名匹配的第一条启用规则（用户规则优先于 profiles.d，不在定时时间段内的规则跳过）
+    pub fn matching_rule(&self, process_name: &str) -> Option<&AutoRule> {
+        self.all_rules()
+            .find(|r| r.enabled && !self.scheduled_inactive.contains(&r.name) && r.matches(process_name))
+    }
+
+    /// 更新定时状态，返回从停用变为启用的规则
+    pub fn set_scheduled_inactive(&mut self, inactive: HashSet<String>) -> Vec<String> {
+        let activated = self.scheduled_inactive.difference(&inactive).cloned().collect();
+        if self.scheduled_inactive != inactive {
+            self.scheduled_inactive = inactive;
+            self.revision += 1;
+        }
+        activated
+    }
+
+    /// 检查可能匹配同一进程但设置相互矛盾的启用规则
+    ///
+    /// 规则按顺序匹配，第一条匹配的规则生效（用户规则在 profiles.d 之前），
+    /// 因此不会出现两条规则反复争夺同一进程；这里只是提示后定义的规则对这些进程不生效。
+    /// 受定时控制的规则也参与检查，时间段不重叠的同名规则需要自行忽略提示
+    pub fn detect_conflicts(&self) -> Vec<RuleOverlap> {
+        let rules: Vec<&AutoRule> = self.all_rules().filter(|r| r.enabled).collect();
+        let mut overlaps = Vec::new();
+        for (i, winner) in rules.iter().enumerate() {
+            let winner_changes = preset_changes(&winner.preset);
+            for shadowed in &rules[i + 1..] {
+                if !patterns_overlap(&winner.pattern, &shadowed.pattern) {
+                    continue;
+                }
+                let labels = differing_changes(&winner_changes, &preset_changes(&shadowed.preset));
+                if !labels.is_empty() {
+                    overlaps.push(RuleOverlap {
+                        winner: winner.name.clone(),
+                        shadowed: shadowed.name.clone(),
+                        labels,
+                    });
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// 快照版本：规则、覆盖记录或延后动作变化后与之前的值不同
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// 当前规则的快照（用于手动应用前检查冲突）
+    pub fn guard(&self) -> RuleGuard {
+        RuleGuard {
+            rules: self
+                .all_rules()
+                .filter(|r| r.enabled && !self.scheduled_inactive.contains(&r.name))
+                .map(|r| (r.clone(), self.rules.iter().any(|user| user.name == r.name)))
+                .collect(),
+            exempt: self.exempt.clone(),
+        }
+    }
+
+    /// 执行冲突处理，修改了规则时调用方负责保存
+    pub fn resolve(&mut self, resolution: RuleResolution) -> Result<(), String> {
+        self.revision += 1;
+        match resolution {
+            RuleResolution::Exempt(pid) => {
+                self.exempt.insert(pid);
+                Ok(())
+            }
+            RuleResolution::UpdateRule { rule_name, changes } => {
+                let Some(rule) = self.rules.iter_mut().find(|r| r.name == rule_name) else {
+                    return Err(format!("规则 '{}' 不存在或来自 profiles.d，无法修改", rule_name));
+                };
+                merge_into_preset(&mut rule.preset, &changes);
+                Ok(())
+            }
+        }
+    }
+
+    /// 把刚启用或修改的规则应用到已在运行的匹配进程（定时生效、profiles.d 重新加载时调用），手动覆盖过的进程除外；
+    /// 返回（成功, 失败）的进程数
+    pub fn apply_to_running<'a>(
+        &mut self,
+        rule_names: &[String],
+        processes: impl Iterator<Item = (u32, &'a str)>,
+    ) -> (usize, usize) {
+        let (mut applied, mut failed) = (0, 0);
+        for (pid, process_name) in processes {
+            if self.exempt.contains(&pid) {
+                continue;
+            }
+            let Some(rule) = self.matching_rule(process_name).filter(|r| rule_names.contains(&r.name)) else {
+                continue;
+            };
+            let rule_name = rule.name.clone();
+            self.handled.insert(pid);
+            match self.try_apply(pid, process_name, &rule_name) {
+                Some(true) => applied += 1,
+                Some(false) => failed += 1,
+                None => {}
+            }
+        }
+        (applied, failed)
+    }
+
+    /// 新进程出现时应用匹配的规则
+    pub fn process_appeared(&mut self, process: &ProcessInfo) {
+        self.process_appeared_at(process.pid, &process.name, process.start_time, Instant::now());
+    }
+
+    /// 新进程出现时应用匹配的规则（每个进程只处理一次），有延迟的规则进入等待队列，
+    /// 延迟从进程启动时算起（首次扫描到的已运行足够久的进程立即应用）
+    ///
+    /// 匹配的一次性延后动作优先于规则：触发后不再对该进程应用规则。`started_at` 为进程启动时间 (Unix 秒)，
+    /// 只有不早于动作创建时间启动的进程才触发动作（首次扫描到的已在运行的进程不触发）
+    pub fn process_appeared_at(&mut self, pid: u32, process_name: &str, started_at: u64, now: Instant) {
+        if !self.handled.insert(pid) {
+            return;
+        }
+        let wall = unix_timestamp();
+        if self.fire_deferred(pid, process_name, started_at, wall) {
+            return;
+        }
+        let Some(rule) = self.matching_rule(process_name) else {
+            return;
+        };
+        let rule_name = rule.name.clone();
+        let age = Duration::from_secs(wall.saturating_sub(started_at));
+        let remaining = Duration::from_secs(rule.delay_secs).saturating_sub(age);
+        if remaining.is_zero() {
+            self.try_apply(pid, process_name, &rule_name);
+        } else {
+            self.pending.push(PendingApplication {
+                pid,
+                process_name: process_name.to_string(),
+                start_time: self.source.start_time(pid),
+                rule_name,
+                deadline: now + remaining,
+            });
+        }
+    }
+
+    /// 处理已到期的延迟应用（每次刷新调用）
+    pub fn process_pending(&mut self, now: Instant) {
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|p| p.deadline <= now);
+        self.pending = waiting;
+
+        for pending in due {
+            // 延迟期间退出（或 PID 被复用）的进程不再处理
+            if pending.start_time.is_none() || self.source.start_time(pending.pid) != pending.start_time {
+                continue;
+            }
+            self.try_apply(pending.pid, &pending.process_name, &pending.rule_name);
+        }
+    }
+
+    /// 在 Once 和实例数限制允许时应用规则，返回是否成功（未应用时为 None）
+    fn try_apply(&mut self, pid: u32, process_name: &str, rule_name: &str) -> Option<bool> {
+        let rule = self.rules.iter().chain(&self.dropin_rules).find(|r| r.name == rule_name)?;
+        if rule.match_mode == RuleMatchMode::Once && self.fired_once.contains(rule_name) {
+            return None;
+        }
+        if let Some(max) = rule.max_instances {
+            if self.active.values().filter(|name| *name == rule_name).count() >= max {
+                return None;
+            }
+        }
+
+        let changes = preset_changes(&rule.preset);
+        let report = self.source.apply(pid, &changes, self.logical_cores);
+        let success = report.is_success();
+        if success {
+            self.active.insert(pid, rule_name.to_string());
+            if rule.match_mode == RuleMatchMode::Once {
+                self.fired_once.insert(rule_name.to_string());
+            }
+        }
+        self.applications.push(RuleApplication {
+            pid,
+            process_name: process_name.to_string(),
+            rule_name: rule_name.to_string(),
+            deferred: false,
+            changes,
+            report,
+        });
+        Some(success)
+    }
+
+    /// 进程退出时清除处理记录和等待中的应用
+    pub fn process_disappeared(&mut self, pid: u32) {
+        self.handled.remove(&pid);
+        self.active.remove(&pid);
+        if self.exempt.remove(&pid) {
+            self.revision += 1;
+        }
+        self.pending.retain(|p| p.pid != pid);
+    }
+
+    /// 进程执行 exec（名称变化）后按新名称重新匹配规则
+    pub fn process_exec(&mut self, process: &ProcessInfo) {
+        self.process_exec_at(process.pid, &process.name, Instant::now());
+    }
+
+    /// 进程执行 exec 后清除按旧名称的处理记录，再当作此刻启动的新进程匹配规则
+    pub fn process_exec_at(&mut self, pid: u32, process_name: &str, now: Instant) {
+        self.process_disappeared(pid);
+        self.process_appeared_at(pid, process_name, unix_timestamp(), now);
+    }
+
+    /// 取走自上次调用以来的应用结果
+    pub fn take_applications(&mut self) -> Vec<RuleApplication> {
+        std::mem::take(&mut self.applications)
+    }
+
+    /// 等待中的一次性延后动作
+    pub fn deferred_actions(&self) -> &[DeferredAction] {
+        &self.deferred
+    }
+
+    /// 加入一次性延后动作，返回分配的编号
+    pub fn add_deferred(&mut self, mut action: DeferredAction) -> u64 {
+        action.id = self.deferred.iter().map(|a| a.id).max().map_or(1, |id| id + 1);
+        self.deferred.push(action);
+        self.revision += 1;
+        self.deferred.last().map_or(0, |a| a.id)
+    }
+
+    /// 删除一次性延后动作，不存在时返回 false
+    pub fn remove_deferred(&mut self, id: u64) -> bool {
+        let before = self.deferred.len();
+        self.deferred.retain(|a| a.id != id);
+        let removed = self.deferred.len() != before;
+        if removed {
+            self.revision += 1;
+        }
+        removed
+    }
+
+    /// 删除并返回在 `now` (Unix 秒) 时已过期的动作
+    pub fn expire_deferred(&mut self, now: u64) -> Vec<DeferredAction> {
+        let (expired, waiting): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.deferred).into_iter().partition(|a| a.is_expired(now));
+        self.deferred = waiting;
+        if !expired.is_empty() {
+            self.revision += 1;
+        }
+        expired
+    }
+
+    /// 应用第一个匹配、未过期且不晚于进程启动创建的延后动作并将其删除，无论应用是否成功都只触发一次
+    fn fire_deferred(&mut self, pid: u32, process_name: &str, started_at: u64, now: u64) -> bool {
+        let Some(index) = self
+            .deferred
+            .iter()
+            .position(|a| !a.is_expired(now) && started_at >= a.created_at && a.matches(process_name))
+        else {
+            return false;
+        };
+        let action = self.deferred.remove(index);
+        self.revision += 1;
+        let changes = preset_changes(&action.preset);
+        let report = self.source.apply(pid, &changes, self.logical_cores);
+        self.applications.push(RuleApplication {
+            pid,
+            process_name: process_name.to_string(),
+            rule_name: format!("一次性: {}", action.preset.name),
+            deferred: true,
+            changes,
+            report,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::SchedulePolicy;
+    use std::sync::{Arc, Mutex};
+
+    /// 测试用系统：进程表和应用记录可在外部查看
+    #[derive(Clone, Default)]
+    struct FakeSystem {
+        start_times: Arc<Mutex<HashMap<u32, u64>>>,
+        applied: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl FakeSystem {
+        fn spawn(&self, pid: u32, start_time: u64) {
+            self.start_times.lock().unwrap().insert(pid, start_time);
+        }
+
+        fn exit(&self, pid: u32) {
+            self.start_times.lock().unwrap().remove(&pid);
+        }
+
+        fn applied(&self) -> Vec<u32> {
+            self.applied.lock().unwrap().clone()
+        }
+    }
+
+    impl SystemSource for FakeSystem {
+        fn start_time(&self, pid: u32) -> Option<u64> {
+            self.start_times.lock().unwrap().get(&pid).copied()
+        }
+
+        fn apply(&mut self, pid: u32, _changes: &[SchedChange], _logical_cores: usize) -> ApplyReport {
+            self.applied.lock().unwrap().push(pid);
+            ApplyReport::default()
+        }
+    }
+
+    fn engine_with(rules: Vec<AutoRule>) -> (RuleEngine, FakeSystem) {
+        let system = FakeSystem::default();
+        (RuleEngine::with_source(rules, 4, Box::new(system.clone())), system)
+    }
+
+    fn rule(name: &str, pattern: &str, enabled: bool) -> AutoRule {
+        AutoRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            preset: SchedulePreset {
+                name: name.to_string(),
+                description: String::new(),
+                policy: SchedulePolicy::Other,
+                priority: 0,
+                affinity_cores: None,
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: false,
+            },
+            enabled,
+            delay_secs: 0,
+            match_mode: RuleMatchMode::EveryInstance,
+            max_instances: None,
+        }
+    }
+
+    #[test]
+    fn test_affinity_rule() {
+        let template = AutoRule::affinity_template("blender", &[0, 1, 2, 3], SchedulePolicy::Batch, 5);
+        assert_eq!(template.name, "blender 亲和性");
+        assert!(template.matches("Blender") && !template.matches("blender-bin"));
+        assert_eq!(template.preset.description, "绑定 CPU 0-3");
+        assert_eq!((template.preset.policy, template.preset.priority), (SchedulePolicy::Batch, 5));
+
+        // 没有匹配的规则：追加
+        let (mut engine, _) = engine_with(vec![rule("编译", "make", true)]);
+        assert_eq!(engine.add_affinity_rule(template.clone()), "已创建规则 'blender 亲和性'");
+        assert_eq!(engine.rules().len(), 2);
+        assert_eq!(engine.matching_rule("blender").unwrap().preset.affinity_cores, Some(vec![0, 1, 2, 3]));
+
+        // 已有匹配的用户规则：只更新亲和性，保留其他设置
+        let mut existing = rule("渲染", "blend*", true);
+        existing.preset.policy = SchedulePolicy::Idle;
+        let (mut engine, _) = engine_with(vec![existing]);
+        assert_eq!(engine.add_affinity_rule(template), "已更新规则 '渲染' 的亲和性");
+        assert_eq!(engine.rules().len(), 1);
+        let updated = &engine.rules()[0].preset;
+        assert_eq!((updated.policy, updated.affinity_cores.clone()), (SchedulePolicy::Idle, Some(vec![0, 1, 2, 3])));
+    }
+
+    #[test]
+    fn test_deferred_action() {
+        let (mut engine, system) = engine_with(vec![rule("游戏规则", "eldenring*", true)]);
+        let now = unix_timestamp();
+        let game = engine.add_deferred(DeferredAction::new("EldenRing.exe", rule("游戏", "", true).preset, now, 24));
+        let other = engine.add_deferred(DeferredAction::new("editor", rule("编辑", "", true).preset, now, 24));
+        assert_eq!((game, other), (1, 2));
+
+        // 创建动作之前已在运行的进程（如首次扫描）不触发，按规则处理
+        system.spawn(99, 1);
+        engine.process_appeared_at(99, "eldenring.exe", now - 60, Instant::now());
+        let applications = engine.take_applications();
+        assert_eq!((applications[0].rule_name.as_str(), applications[0].deferred), ("游戏规则", false));
+        assert_eq!(engine.deferred_actions().len(), 2);
+
+        // 首次出现时应用一次并删除，同时不再应用匹配的规则
+        system.spawn(100, 1);
+        engine.process_appeared_at(100, "eldenring.exe", now + 1, Instant::now());
+        assert_eq!(system.applied(), vec![99, 100]);
+        let applications = engine.take_applications();
+        assert_eq!(applications.len(), 1);
+        assert!(applications[0].deferred);
+        assert_eq!(applications[0].rule_name, "一次性: 游戏");
+        assert_eq!(engine.deferred_actions().iter().map(|a| a.id).collect::<Vec<_>>(), vec![other]);
+
+        // 之后的实例按规则处理
+        system.spawn(101, 2);
+        engine.process_appeared_at(101, "eldenring.exe", now + 1, Instant::now());
+        let applications = engine.take_applications();
+        assert_eq!((applications[0].rule_name.as_str(), applications[0].deferred), ("游戏规则", false));
+
+        assert!(engine.remove_deferred(other));
+        assert!(!engine.remove_deferred(other));
+        assert!(engine.deferred_actions().is_empty());
+    }
+
+    #[test]
+    fn test_deferred_expiry() {
+        let (mut engine, system) = engine_with(Vec::new());
+        let now = unix_timestamp();
+        let expired = DeferredAction::new("game", rule("游戏", "", true).preset, now - 7200, 1);
+        assert!(expired.is_expired(now));
+        engine.add_deferred(expired);
+        let soon = engine.add_deferred(DeferredAction::new("game", rule("游戏", "", true).preset, now, 2));
+
+        // 过期的动作不会触发，未过期的同名动作触发
+        system.spawn(7, 1);
+        engine.process_appeared_at(7, "game", now + 1, Instant::now());
+        assert_eq!(system.applied(), vec![7]);
+        assert!(engine.deferred_actions().iter().all(|a| a.id != soon));
+
+        let removed = engine.expire_deferred(now);
+        assert_eq!(removed.len(), 1);
+        assert!(engine.deferred_actions().is_empty());
+
+        let action = DeferredAction::new("x", rule("x", "", true).preset, 1000, 2);
+        assert_eq!(action.expires_at, 1000 + 7200);
+        assert!(!action.is_expired(1000 + 7199));
+        assert!(action.is_expired(1000 + 7200));
+        assert_eq!(action.remaining(1000), "2 小时 0 分钟");
+        assert_eq!(action.remaining(1000 + 7170), "1 分钟");
+        assert_eq!(DeferredAction::new("x", action.preset.clone(), 0, 49).remaining(0), "2 天 1 小时");
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("firefox", "firefox"));
+        assert!(!wildcard_match("firefox", "firefox-bin"));
+        assert!(wildcard_match("firefox*", "firefox-bin"));
+        assert!(wildcard_match("*.exe", "game.exe"));
+        assert!(wildcard_match("steam*game*", "steam_my_game_x"));
+        assert!(!wildcard_match("steam*game*", "steam_my_gam"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_patterns_overlap() {
+        assert!(patterns_overlap("firefox*", "*fox"));
+        assert!(patterns_overlap("a*c", "ab*"));
+        assert!(patterns_overlap("Steam", "steam"));
+        assert!(patterns_overlap("*", "anything"));
+        assert!(patterns_overlap("*game*", "steam*"));
+        assert!(!patterns_overlap("abc", "abd"));
+        assert!(!patterns_overlap("*x*", "y"));
+        assert!(!patterns_overlap("steam*", "x*"));
+        assert!(!patterns_overlap("bash", "bash*z"));
+    }
+
+    #[test]
+    fn test_detect_conflicts() {
+        let pinned = |name: &str, pattern: &str, cores: Vec<usize>| {
+            let mut r = rule(name, pattern, true);
+            r.preset.affinity_cores = Some(cores);
+            r
+        };
+        let mut disabled = pinned("停用", "firefox", vec![5]);
+        disabled.enabled = false;
+        let (mut engine, _) = engine_with(vec![
+            pinned("P 核心", "firefox*", vec![0, 1, 2, 3]),
+            pinned("E 核心", "*fox", vec![4, 5, 6, 7]),
+            disabled,
+            // 模式重叠但设置相同
+            pinned("同样的 P 核心", "firefox", vec![0, 1, 2, 3]),
+            pinned("终端", "bash", vec![7]),
+        ]);
+        engine.set_dropin_rules(vec![pinned("profiles.d/firefox", "firefox-esr", vec![6])]);
+
+        let conflicts = engine.detect_conflicts();
+        assert_eq!(
+            conflicts,
+            vec![
+                RuleOverlap { winner: "P 核心".to_string(), shadowed: "E 核心".to_string(), labels: vec!["亲和性"] },
+                RuleOverlap {
+                    winner: "P 核心".to_string(),
+                    shadowed: "profiles.d/firefox".to_string(),
+                    labels: vec!["亲和性"],
+                },
+                RuleOverlap {
+                    winner: "E 核心".to_string(),
+                    shadowed: "同样的 P 核心".to_string(),
+                    labels: vec!["亲和性"],
+                },
+            ]
+        );
+        // 报告与实际匹配顺序一致：两者都匹配时靠前的规则生效
+        assert_eq!(engine.matching_rule("firefox").unwrap().name, conflicts[0].winner);
+        assert!(conflicts[0].describe().contains("'P 核心' 生效"));
+    }
+
+    #[test]
+    fn test_matching_rule_order_and_enabled() {
+        let engine = RuleEngine::new(
+            vec![
+                rule("禁用", "firefox", false),
+                rule("浏览器", "Firefox*", true),
+                rule("兜底", "*", true),
+            ],
+            4,
+        );
+        assert_eq!(engine.matching_rule("firefox").map(|r| r.name.as_str()), Some("浏览器"));
+        assert_eq!(engine.matching_rule("bash").map(|r| r.name.as_str()), Some("兜底"));
+    }
+
+    #[test]
+    fn test_dropin_rules_precedence() {
+        let (mut engine, system) = engine_with(vec![rule("mine", "factorio", true)]);
+        let mut dropin = rule("profiles.d/factorio", "factorio*", true);
+        dropin.delay_secs = 10;
+        engine.set_dropin_rules(vec![dropin, rule("profiles.d/blender", "blender", true)]);
+
+        // 用户规则优先于 profiles.d
+        assert_eq!(engine.matching_rule("factorio").unwrap().name, "mine");
+        assert_eq!(engine.matching_rule("factorio-server").unwrap().name, "profiles.d/factorio");
+
+        system.spawn(10, 1);
+        engine.process_appeared_at(10, "blender", 0, Instant::now());
+        assert_eq!(system.applied(), vec![10]);
+        assert_eq!(engine.take_applications()[0].rule_name, "profiles.d/blender");
+
+        // 重新加载后删除的 profiles.d 规则不再应用
+        let now = Instant::now();
+        system.spawn(11, 1);
+        engine.process_appeared_at(11, "factorio-server", unix_timestamp(), now);
+        engine.set_dropin_rules(Vec::new());
+        engine.process_pending(now + Duration::from_secs(20));
+        assert_eq!(system.applied(), vec![10]);
+        assert!(engine.matching_rule("blender").is_none());
+
+        // 重新加载：只返回新增或修改的规则，并重新评估已在运行的进程
+        let blender = rule("profiles.d/blender", "blender", true);
+        assert_eq!(engine.set_dropin_rules(vec![blender.clone()]), vec!["profiles.d/blender".to_string()]);
+        assert!(engine.set_dropin_rules(vec![blender.clone()]).is_empty());
+        let mut changed = blender;
+        changed.preset.priority = 5;
+        let names = engine.set_dropin_rules(vec![changed]);
+        assert_eq!(names, vec!["profiles.d/blender".to_string()]);
+        assert_eq!(engine.apply_to_running(&names, [(10, "blender"), (12, "bash")].into_iter()), (1, 0));
+        assert_eq!(system.applied(), vec![10, 10]);
+    }
+
+    #[test]
+    fn test_scheduled_rules() {
+        let (mut engine, system) = engine_with(vec![rule("白天", "make", true), rule("夜间", "make", true)]);
+        // 白天：夜间规则不在时间段内
+        assert!(engine.set_scheduled_inactive(HashSet::from(["夜间".to_string()])).is_empty());
+        assert_eq!(engine.matching_rule("make").unwrap().name, "白天");
+
+        // 到了晚上：白天规则停用，夜间规则生效并应用到已在运行的进程
+        let activated = engine.set_scheduled_inactive(HashSet::from(["白天".to_string()]));
+        assert_eq!(activated, vec!["夜间".to_string()]);
+        assert_eq!(engine.matching_rule("make").unwrap().name, "夜间");
+        assert_eq!(engine.apply_to_running(&activated, [(10, "make"), (11, "bash")].into_iter()), (1, 0));
+        assert_eq!(system.applied(), vec![10]);
+        assert_eq!(engine.take_applications()[0].rule_name, "夜间");
+
+        // 两条规则都停用
+        engine.set_scheduled_inactive(HashSet::from(["白天".to_string(), "夜间".to_string()]));
+        assert!(engine.matching_rule("make").is_none());
+    }
+
+    #[test]
+    fn test_manual_conflict_resolution() {
+        let manual = vec![SchedChange::Affinity(vec![0, 1, 2, 3])];
+        let mut browser = rule("浏览器", "firefox", true);
+        browser.preset.affinity_cores = Some(vec![8, 9, 10, 11]);
+        let (mut engine, system) = engine_with(vec![browser]);
+        let mut dropin = rule("profiles.d/make", "make", true);
+        dropin.preset.affinity_cores = Some(vec![4]);
+        engine.set_dropin_rules(vec![dropin]);
+
+        let guard = engine.guard();
+        assert_eq!(
+            guard.conflict(10, "firefox", &manual),
+            Some(RuleConflict { rule_name: "浏览器".to_string(), labels: vec!["亲和性"], editable: true })
+        );
+        // 取值相同、规则未设置的项或没有匹配规则时不冲突
+        assert!(guard.conflict(10, "firefox", &[SchedChange::Affinity(vec![8, 9, 10, 11])]).is_none());
+        assert!(guard.conflict(10, "firefox", &[SchedChange::Nice(5)]).is_none());
+        assert!(guard.conflict(10, "bash", &manual).is_none());
+
+        // 取消：规则保持不变，定时生效时仍会应用
+        engine.apply_to_running(&["浏览器".to_string()], [(10, "firefox")].into_iter());
+        assert_eq!(system.applied(), vec![10]);
+        engine.take_applications();
+
+        // 覆盖一次：之后不再对该进程应用规则，其他实例不受影响
+        let revision = engine.revision();
+        engine.resolve(RuleResolution::Exempt(10)).unwrap();
+        assert_ne!(engine.revision(), revision);
+        assert!(engine.guard().conflict(10, "firefox", &manual).is_none());
+        assert!(engine.guard().conflict(11, "firefox", &manual).is_some());
+        engine.apply_to_running(&["浏览器".to_string()], [(10, "firefox"), (11, "firefox")].into_iter());
+        assert_eq!(system.applied(), vec![10, 11]);
+        engine.take_applications();
+        // 进程退出后 PID 可能被复用，不再豁免
+        let revision = engine.revision();
+        engine.process_disappeared(11);
+        assert_eq!(engine.revision(), revision);
+        engine.process_disappeared(10);
+        assert_ne!(engine.revision(), revision);
+        assert!(engine.guard().conflict(10, "firefox", &manual).is_some());
+
+        // 更新规则：新进程使用新的值
+        let update = RuleResolution::UpdateRule { rule_name: "浏览器".to_string(), changes: manual.clone() };
+        engine.resolve(update).unwrap();
+        assert_eq!(engine.rules()[0].preset.affinity_cores, Some(vec![0, 1, 2, 3]));
+        assert!(engine.guard().conflict(12, "firefox", &manual).is_none());
+        system.spawn(12, 1);
+        engine.process_appeared_at(12, "firefox", 0, Instant::now());
+        assert!(engine.take_applications()[0].changes.contains(&SchedChange::Affinity(vec![0, 1, 2, 3])));
+
+        let realtime = vec![SchedChange::Policy { policy: SchedulePolicy::Fifo, priority: 50, reset_on_fork: true }];
+        engine.resolve(RuleResolution::UpdateRule { rule_name: "浏览器".to_string(), changes: realtime }).unwrap();
+        assert_eq!((engine.rules()[0].preset.policy, engine.rules()[0].preset.priority), (SchedulePolicy::Fifo, 50));
+
+        // profiles.d 规则只读
+        let conflict = engine.guard().conflict(20, "make", &manual).unwrap();
+        assert!(!conflict.editable);
+        let update = RuleResolution::UpdateRule { rule_name: conflict.rule_name, changes: manual };
+        assert!(engine.resolve(update).is_err());
+    }
+
+    #[test]
+    fn test_delayed_application() {
+        let mut game = rule("游戏", "game", true);
+        game.delay_secs = 5;
+        let (mut engine, system) = engine_with(vec![game]);
+        let t0 = Instant::now();
+        let started = unix_timestamp();
+
+        system.spawn(100, 1);
+        engine.process_appeared_at(100, "game", started, t0);
+        assert!(system.applied().is_empty());
+
+        engine.process_pending(t0 + Duration::from_secs(3));
+        assert!(system.applied().is_empty());
+
+        engine.process_pending(t0 + Duration::from_secs(5));
+        assert_eq!(system.applied(), vec![100]);
+        assert_eq!(engine.take_applications().len(), 1);
+
+        // 已应用的进程不再入队
+        engine.process_pending(t0 + Duration::from_secs(10));
+        assert_eq!(system.applied(), vec![100]);
+
+        // 延迟从进程启动时算起：启动 2 秒后才首次扫描到的进程只再等剩下的时间，早已启动的进程立即应用
+        system.spawn(101, 1);
+        engine.process_appeared_at(101, "game", started - 2, t0);
+        engine.process_pending(t0 + Duration::from_secs(1));
+        assert_eq!(system.applied(), vec![100]);
+        engine.process_pending(t0 + Duration::from_secs(3));
+        assert_eq!(system.applied(), vec![100, 101]);
+
+        system.spawn(102, 1);
+        engine.process_appeared_at(102, "game", started - 60, t0);
+        assert_eq!(system.applied(), vec![100, 101, 102]);
+    }
+
+    #[test]
+    fn test_exit_before_delay_elapses() {
+        let mut game = rule("游戏", "game", true);
+        game.delay_secs = 5;
+        let (mut engine, system) = engine_with(vec![game]);
+        let t0 = Instant::now();
+        let started = unix_timestamp();
+
+        // 进程退出但尚未收到退出通知
+        system.spawn(100, 1);
+        engine.process_appeared_at(100, "game", started, t0);
+        system.exit(100);
+        engine.process_pending(t0 + Duration::from_secs(5));
+
+        // PID 在延迟期间被复用
+        system.spawn(200, 1);
+        engine.process_appeared_at(200, "game", started, t0);
+        system.spawn(200, 2);
+        engine.process_pending(t0 + Duration::from_secs(5));
+
+        // 收到退出通知
+        system.spawn(300, 1);
+        engine.process_appeared_at(300, "game", started, t0);
+        engine.process_disappeared(300);
+        engine.process_pending(t0 + Duration::from_secs(5));
+
+        assert!(system.applied().is_empty());
+    }
+
+    #[test]
+    fn test_instance_cap_and_once() {
+        let mut worker = rule("构建", "cc1*", true);
+        worker.max_instances = Some(2);
+        let mut launcher = rule("启动器", "launcher", true);
+        launcher.match_mode = RuleMatchMode::Once;
+        let (mut engine, system) = engine_with(vec![worker, launcher]);
+        let now = Instant::now();
+
+        for pid in 1..=3 {
+            system.spawn(pid, 1);
+            engine.process_appeared_at(pid, "cc1plus", 0, now);
+        }
+        assert_eq!(system.applied(), vec![1, 2]);
+
+        // 一个实例退出后，新实例可以应用
+        engine.process_disappeared(1);
+        system.spawn(4, 1);
+        engine.process_appeared_at(4, "cc1plus", 0, now);
+        assert_eq!(system.applied(), vec![1, 2, 4]);
+
+        system.spawn(10, 1);
+        system.spawn(11, 1);
+        engine.process_appeared_at(10, "launcher", 0, now);
+        engine.process_disappeared(10);
+        engine.process_appeared_at(11, "launcher", 0, now);
+        assert_eq!(system.applied(), vec![1, 2, 4, 10]);
+    }
+
+    #[test]
+    fn test_exec_reevaluates_rules() {
+        let mut game = rule("游戏", "eldenring.exe", true);
+        game.max_instances = Some(1);
+        let mut delayed = rule("延迟", "worker", true);
+        delayed.delay_secs = 5;
+        let (mut engine, system) = engine_with(vec![game, rule("启动器", "launcher", true), delayed]);
+        let t0 = Instant::now();
+
+        // 启动器按旧名称应用规则；同一 PID 再次出现不会重新匹配
+        system.spawn(100, 1);
+        engine.process_appeared_at(100, "launcher", 0, t0);
+        engine.process_appeared_at(100, "eldenring.exe", 0, t0);
+        assert_eq!(system.applied(), vec![100]);
+
+        // exec 后启动时间不变，按新名称重新匹配
+        engine.process_exec_at(100, "eldenring.exe", t0);
+        assert_eq!(system.applied(), vec![100, 100]);
+        assert_eq!(engine.take_applications().last().map(|a| a.rule_name.clone()), Some("游戏".to_string()));
+        // 实例数按新规则计算
+        system.spawn(101, 1);
+        engine.process_appeared_at(101, "eldenring.exe", 0, t0);
+        assert_eq!(system.applied(), vec![100, 100]);
+
+        // exec 前排队的延迟应用被丢弃
+        system.spawn(200, 1);
+        engine.process_appeared_at(200, "worker", unix_timestamp(), t0);
+        engine.process_exec_at(200, "other", t0);
+        engine.process_pending(t0 + Duration::from_secs(5));
+        assert_eq!(system.applied(), vec![100, 100]);
+    }
+}
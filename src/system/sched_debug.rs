@@ -0,0 +1,258 @@
+//! 内核调度实体统计 - 解析 debugfs 的 sched/debug 中的逐任务行
+//! 文件很大（每个线程一行），只在用户请求时读取；需要挂载 debugfs 且通常需要 root
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// sched/debug 路径（5.13 之前的内核为 /proc/sched_debug）
+const SCHED_DEBUG_PATHS: &[&str] = &["/sys/kernel/debug/sched/debug", "/proc/sched_debug"];
+
+/// 一个线程的调度实体统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedDebugTask {
+    /// 线程 ID
+    pub tid: u32,
+    /// 线程名
+    pub comm: String,
+    /// 所在运行队列的 CPU
+    pub cpu: Option<usize>,
+    /// 虚拟运行时间 (ms)，旧内核为 tree-key
+    pub vruntime_ms: Option<f64>,
+    /// 累计运行时间 (ms)
+    pub sum_exec_ms: Option<f64>,
+    /// 上下文切换次数
+    pub switches: Option<u64>,
+    /// 内核优先级 (100 + 20 + nice)
+    pub prio: Option<i32>,
+    /// 跨 CPU 迁移次数（来自 /proc/[pid]/task/[tid]/sched）
+    pub nr_migrations: Option<u64>,
+}
+
+/// 解析 "cpu#3, 3600.000 MHz" 形式的运行队列标题
+fn parse_cpu_header(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix("cpu#")?;
+    let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// 在任务行中定位 PID 列：线程名可能含空格，取后面紧跟带小数点数值的第一个整数
+fn find_pid_column(tokens: &[&str]) -> Option<usize> {
+    (1..tokens.len().saturating_sub(1)).find(|&i| {
+        tokens[i].parse::<u32>().is_ok() && tokens[i + 1].contains('.') && tokens[i + 1].parse::<f64>().is_ok()
+    })
+}
+
+/// 解析 sched/debug 内容，只保留 `tids` 中的线程
+///
+/// 列的含义取自 "runnable tasks:" 下方的表头，因此兼容不同内核版本的列顺序；
+/// 无法识别的行直接跳过
+pub fn parse_sched_debug(content: &str, tids: &HashSet<u32>) -> Vec<SchedDebugTask> {
+    let mut tasks = Vec::new();
+    let mut cpu = None;
+    let mut columns: Vec<&str> = Vec::new();
+
+    for line in content.lines() {
+        if let Some(id) = parse_cpu_header(line) {
+            cpu = Some(id);
+            continue;
+        }
+        let trimmed = line.trim_start_matches(['>', ' ']);
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        // 表头：" S  task  PID  tree-key ..." 或 " S  task  PID  vruntime ..."
+        if tokens.len() > 3 && tokens[0] == "S" && tokens[1] == "task" && tokens[2] == "PID" {
+            columns = tokens[3..].to_vec();
+            continue;
+        }
+        if columns.is_empty() || tokens.len() < 3 || tokens[0].len() != 1 {
+            continue;
+        }
+        let Some(pid_index) = find_pid_column(&tokens) else {
+            continue;
+        };
+        let Ok(tid) = tokens[pid_index].parse::<u32>() else {
+            continue;
+        };
+        if !tids.contains(&tid) {
+            continue;
+        }
+        let values = &tokens[pid_index + 1..];
+        let column = |names: &[&str]| {
+            let index = columns.iter().position(|c| names.contains(c))?;
+            values.get(index).copied()
+        };
+        tasks.push(SchedDebugTask {
+            tid,
+            comm: tokens[1..pid_index].join(" "),
+            cpu,
+            vruntime_ms: column(&["vruntime", "tree-key"]).and_then(|v| v.parse().ok()),
+            sum_exec_ms: column(&["sum-exec"]).and_then(|v| v.parse().ok()),
+            switches: column(&["switches"]).and_then(|v| v.parse().ok()),
+            prio: column(&["prio"]).and_then(|v| v.parse().ok()),
+            nr_migrations: None,
+        });
+    }
+    tasks.sort_by_key(|t| t.tid);
+    tasks
+}
+
+/// 从 /proc/[pid]/task/[tid]/sched 中解析 se.nr_migrations
+pub fn parse_nr_migrations(content: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "se.nr_migrations").then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+/// 进程的所有线程 ID
+fn read_thread_ids(pid: u32) -> HashSet<u32> {
+    fs::read_dir(format!("/proc/{}/task", pid))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 读取 sched/debug 原文
+fn read_sched_debug_file() -> Result<String, String> {
+    let mut last_error = String::new();
+    for path in SCHED_DEBUG_PATHS {
+        match fs::read_to_string(path) {
+            Ok(content) => return Ok(content),
+            Err(e) => last_error = format!("无法读取 {}: {}", path, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// sched/debug 是否可读（不可读时隐藏相关界面）
+pub fn is_sched_debug_readable() -> bool {
+    SCHED_DEBUG_PATHS.iter().any(|path| fs::File::open(path).is_ok())
+}
+
+/// 读取进程所有线程的调度实体统计
+pub fn read_sched_debug(pid: u32) -> Result<Vec<SchedDebugTask>, String> {
+    let tids = read_thread_ids(pid);
+    if tids.is_empty() {
+        return Err("进程已退出".to_string());
+    }
+    let content = read_sched_debug_file()?;
+    let mut tasks = parse_sched_debug(&content, &tids);
+    for task in &mut tasks {
+        task.nr_migrations = fs::read_to_string(format!("/proc/{}/task/{}/sched", pid, task.tid))
+            .ok()
+            .and_then(|s| parse_nr_migrations(&s));
+    }
+    Ok(tasks)
+}
+
+/// 相对上一次读取的迁移次数增量（按线程 ID），新出现的线程没有增量
+pub fn migration_deltas(previous: &[SchedDebugTask], current: &[SchedDebugTask]) -> HashMap<u32, u64> {
+    let before: HashMap<u32, u64> = previous.iter().filter_map(|t| Some((t.tid, t.nr_migrations?))).collect();
+    current
+        .iter()
+        .filter_map(|t| {
+            let now = t.nr_migrations?;
+            let then = before.get(&t.tid)?;
+            Some((t.tid, now.saturating_sub(*then)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5.x 内核（CFS，tree-key 列）
+    const SCHED_DEBUG_CFS: &str = "\
+Sched Debug Version: v0.11, 5.15.0-91-generic #101-Ubuntu
+ktime                                   : 1234567.890123
+
+cpu#0, 3600.000 MHz
+  .nr_running                    : 1
+
+runnable tasks:
+ S            task   PID         tree-key  switches  prio     wait-time             sum-exec        sum-sleep
+-------------------------------------------------------------------------------------------------------------
+ S        kthreadd     2        49.392789       175   120         0.000000         1.096473         0.000000 0 0 /
+>R         factorio  4242    812345.123456     90210   115         3.500000     65432.100000      1200.000000 0 0 /user.slice
+
+cpu#5, 3600.000 MHz
+runnable tasks:
+ S            task   PID         tree-key  switches  prio     wait-time             sum-exec        sum-sleep
+-------------------------------------------------------------------------------------------------------------
+ S  Render Thread  4250    812000.000000      4321   115         0.100000      9876.500000       300.000000 0 0 /user.slice
+ S        garbage
+";
+
+    /// 6.6+ 内核（EEVDF，vruntime / eligible / deadline 列）
+    const SCHED_DEBUG_EEVDF: &str = "\
+cpu#2, 4200.000 MHz
+runnable tasks:
+ S            task   PID       vruntime   eligible    deadline             slice          sum-exec      switches  prio         wait-time        sum-sleep       sum-block  node   group-id  group-path
+-------------------------------------------------------------------------------------------------------------------------------------
+ S         blender  7001     51234.500000   E       51237.500000     3.000000      1500.250000           88   120         0.000000         0.000000         0.000000   0      0        /
+";
+
+    #[test]
+    fn test_parse_sched_debug_cfs() {
+        let tasks = parse_sched_debug(SCHED_DEBUG_CFS, &HashSet::from([4242, 4250, 9999]));
+        assert_eq!(tasks.len(), 2);
+
+        assert_eq!(tasks[0].tid, 4242);
+        assert_eq!(tasks[0].comm, "factorio");
+        assert_eq!(tasks[0].cpu, Some(0));
+        assert_eq!(tasks[0].vruntime_ms, Some(812345.123456));
+        assert_eq!(tasks[0].sum_exec_ms, Some(65432.1));
+        assert_eq!(tasks[0].switches, Some(90210));
+        assert_eq!(tasks[0].prio, Some(115));
+        assert_eq!(tasks[0].nr_migrations, None);
+
+        // 线程名含空格
+        assert_eq!(tasks[1].comm, "Render Thread");
+        assert_eq!(tasks[1].cpu, Some(5));
+    }
+
+    #[test]
+    fn test_parse_sched_debug_eevdf() {
+        let tasks = parse_sched_debug(SCHED_DEBUG_EEVDF, &HashSet::from([7001]));
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].cpu, Some(2));
+        assert_eq!(tasks[0].vruntime_ms, Some(51234.5));
+        assert_eq!(tasks[0].sum_exec_ms, Some(1500.25));
+        assert_eq!(tasks[0].switches, Some(88));
+        assert_eq!(tasks[0].prio, Some(120));
+
+        // 没有表头时不解析任何行
+        assert!(parse_sched_debug("", &HashSet::from([7001])).is_empty());
+        let headless = SCHED_DEBUG_EEVDF.lines().filter(|l| !l.contains("PID")).collect::<Vec<_>>().join("\n");
+        assert!(parse_sched_debug(&headless, &HashSet::from([7001])).is_empty());
+    }
+
+    #[test]
+    fn test_migrations() {
+        let sched = "factorio (4242, #threads: 12)\n\
+---------------------------------------------------------\n\
+se.exec_start                                :      12345678.901234\n\
+se.nr_migrations                             :                 1523\n\
+nr_switches                                  :                90210\n";
+        assert_eq!(parse_nr_migrations(sched), Some(1523));
+        assert_eq!(parse_nr_migrations("se.nr_migrations : x"), None);
+
+        let task = |tid, nr_migrations| SchedDebugTask {
+            tid,
+            comm: String::new(),
+            cpu: None,
+            vruntime_ms: None,
+            sum_exec_ms: None,
+            switches: None,
+            prio: None,
+            nr_migrations,
+        };
+        let previous = [task(1, Some(100)), task(2, Some(5)), task(3, None)];
+        let current = [task(1, Some(130)), task(2, Some(5)), task(3, Some(9)), task(4, Some(2))];
+        assert_eq!(migration_deltas(&previous, &current), HashMap::from([(1, 30), (2, 0)]));
+    }
+}
@@ -1,8 +1,11 @@
 //! Linux 调度策略 API 封装
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 
+use super::{AffinityTarget, CoreTopology, IoClass, IoPriority};
+
 // Linux 调度策略常量
 #[cfg(target_os = "linux")]
 mod linux_sched {
@@ -11,6 +14,8 @@ mod linux_sched {
     pub const SCHED_RR: i32 = 2;
     pub const SCHED_BATCH: i32 = 3;
     pub const SCHED_IDLE: i32 = 5;
+    /// 与策略按位或：fork 出的子进程恢复为 SCHED_OTHER / nice 0
+    pub const SCHED_RESET_ON_FORK: i32 = 0x4000_0000;
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -20,10 +25,15 @@ mod linux_sched {
     pub const SCHED_RR: i32 = 2;
     pub const SCHED_BATCH: i32 = 3;
     pub const SCHED_IDLE: i32 = 5;
+    /// 与策略按位或：fork 出的子进程恢复为 SCHED_OTHER / nice 0
+    pub const SCHED_RESET_ON_FORK: i32 = 0x4000_0000;
 }
 
 use linux_sched::*;
 
+/// sched_attr.sched_flags 中的 SCHED_FLAG_RESET_ON_FORK
+pub const SCHED_FLAG_RESET_ON_FORK: u32 = 0x01;
+
 /// 调度策略
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SchedulePolicy {
@@ -66,30 +76,35 @@ impl SchedulePolicy {
         }
     }
 
-    /// 显示名称
-    pub fn display_name(&self) -> &'static str {
+    /// 显示名称（未知策略附带原始值）
+    pub fn display_name(&self) -> String {
         match self {
-            SchedulePolicy::Other => "SCHED_OTHER (默认)",
-            SchedulePolicy::Fifo => "SCHED_FIFO (实时先进先出)",
-            SchedulePolicy::RoundRobin => "SCHED_RR (实时轮转)",
-            SchedulePolicy::Batch => "SCHED_BATCH (批处理)",
-            SchedulePolicy::Idle => "SCHED_IDLE (空闲)",
-            SchedulePolicy::Unknown(_) => "未知",
+            SchedulePolicy::Other => "SCHED_OTHER (默认)".to_string(),
+            SchedulePolicy::Fifo => "SCHED_FIFO (实时先进先出)".to_string(),
+            SchedulePolicy::RoundRobin => "SCHED_RR (实时轮转)".to_string(),
+            SchedulePolicy::Batch => "SCHED_BATCH (批处理)".to_string(),
+            SchedulePolicy::Idle => "SCHED_IDLE (空闲)".to_string(),
+            SchedulePolicy::Unknown(v) => format!("未知 ({})", v),
         }
     }
 
     /// 短名称
-    pub fn short_name(&self) -> &'static str {
+    pub fn short_name(&self) -> String {
         match self {
-            SchedulePolicy::Other => "OTHER",
-            SchedulePolicy::Fifo => "FIFO",
-            SchedulePolicy::RoundRobin => "RR",
-            SchedulePolicy::Batch => "BATCH",
-            SchedulePolicy::Idle => "IDLE",
-            SchedulePolicy::Unknown(_) => "???",
+            SchedulePolicy::Other => "OTHER".to_string(),
+            SchedulePolicy::Fifo => "FIFO".to_string(),
+            SchedulePolicy::RoundRobin => "RR".to_string(),
+            SchedulePolicy::Batch => "BATCH".to_string(),
+            SchedulePolicy::Idle => "IDLE".to_string(),
+            SchedulePolicy::Unknown(v) => format!("?({})", v),
         }
     }
 
+    /// 是否为 hexin 无法识别的策略（如 SCHED_DEADLINE），-1 表示读取失败
+    pub fn is_unrecognized(&self) -> bool {
+        matches!(self, SchedulePolicy::Unknown(v) if *v >= 0)
+    }
+
     /// 是否为实时策略
     pub fn is_realtime(&self) -> bool {
         matches!(self, SchedulePolicy::Fifo | SchedulePolicy::RoundRobin)
@@ -107,37 +122,140 @@ impl SchedulePolicy {
     }
 }
 
-/// 获取进程的调度策略和优先级 (Linux only)
+/// sched_attr 扩展调度属性（非 SCHED_DEADLINE 进程均为 0）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedAttr {
+    /// 每周期运行时间 (纳秒)
+    pub runtime_ns: u64,
+    /// 相对截止时间 (纳秒)
+    pub deadline_ns: u64,
+    /// 周期 (纳秒)
+    pub period_ns: u64,
+    /// 调度标志 (SCHED_FLAG_*)
+    pub flags: u32,
+}
+
+impl SchedAttr {
+    /// 子进程是否不继承调度策略 (SCHED_RESET_ON_FORK)
+    pub fn reset_on_fork(&self) -> bool {
+        self.flags & SCHED_FLAG_RESET_ON_FORK != 0
+    }
+
+    /// 是否设置了 deadline 参数
+    pub fn has_deadline_params(&self) -> bool {
+        self.runtime_ns != 0 || self.deadline_ns != 0 || self.period_ns != 0
+    }
+
+    /// 格式化 deadline 参数显示
+    pub fn format_deadline(&self) -> String {
+        format!(
+            "DL: runtime={}μs / deadline={}μs / period={}μs",
+            self.runtime_ns / 1000,
+            self.deadline_ns / 1000,
+            self.period_ns / 1000
+        )
+    }
+}
+
+/// 内核 struct sched_attr 布局 (SCHED_ATTR_SIZE_VER1)
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+struct RawSchedAttr {
+    size: u32,
+    sched_policy: u32,
+    sched_flags: u64,
+    sched_nice: i32,
+    sched_priority: u32,
+    sched_runtime: u64,
+    sched_deadline: u64,
+    sched_period: u64,
+    sched_util_min: u32,
+    sched_util_max: u32,
+}
+
+/// 通过 sched_getattr 系统调用读取扩展调度属性 (Linux 3.14+)
+#[cfg(target_os = "linux")]
+pub fn get_sched_attr(pid: i32) -> Option<SchedAttr> {
+    let mut attr = RawSchedAttr::default();
+    let size = std::mem::size_of::<RawSchedAttr>() as u32;
+
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_sched_getattr,
+            pid,
+            &mut attr as *mut RawSchedAttr,
+            size,
+            0u32,
+        )
+    };
+
+    if result == 0 {
+        Some(SchedAttr {
+            runtime_ns: attr.sched_runtime,
+            deadline_ns: attr.sched_deadline,
+            period_ns: attr.sched_period,
+            flags: attr.sched_flags as u32,
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_sched_attr(_pid: i32) -> Option<SchedAttr> {
+    None
+}
+
+/// 获取进程的调度策略、优先级和扩展属性 (Linux only)
 #[cfg(target_os = "linux")]
-pub fn get_scheduler_info(pid: i32) -> (SchedulePolicy, i32) {
+pub fn get_scheduler_info(pid: i32) -> (SchedulePolicy, i32, SchedAttr) {
     use libc::sched_getscheduler;
 
     unsafe {
         let policy = sched_getscheduler(pid);
         if policy < 0 {
-            return (SchedulePolicy::Unknown(-1), 0);
+            return (SchedulePolicy::Unknown(-1), 0, SchedAttr::default());
         }
+        // 设置了 reset-on-fork 的进程返回值带有该标志位
+        let policy = policy & !SCHED_RESET_ON_FORK;
 
         let priority = get_process_nice(pid);
-        (SchedulePolicy::from_raw(policy), priority)
+        let attr = get_sched_attr(pid).unwrap_or_default();
+        (SchedulePolicy::from_raw(policy), priority, attr)
     }
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn get_scheduler_info(_pid: i32) -> (SchedulePolicy, i32) {
-    (SchedulePolicy::Other, 0)
+pub fn get_scheduler_info(_pid: i32) -> (SchedulePolicy, i32, SchedAttr) {
+    (SchedulePolicy::Other, 0, SchedAttr::default())
+}
+
+/// 获取进程的实时优先级 (sched_priority，非实时策略为 0)
+#[cfg(target_os = "linux")]
+pub fn get_rt_priority(pid: i32) -> Option<i32> {
+    let mut param = libc::sched_param { sched_priority: 0 };
+    let result = unsafe { libc::sched_getparam(pid, &mut param) };
+    (result == 0).then_some(param.sched_priority)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_rt_priority(_pid: i32) -> Option<i32> {
+    None
 }
 
 /// 设置进程的调度策略 (Linux only)
+/// `reset_on_fork` 为 true 时子进程不继承实时策略和负 nice 值
 #[cfg(target_os = "linux")]
-pub fn set_scheduler(pid: i32, policy: SchedulePolicy, priority: i32) -> Result<(), String> {
+pub fn set_scheduler(pid: i32, policy: SchedulePolicy, priority: i32, reset_on_fork: bool) -> Result<(), String> {
     use libc::{sched_param, sched_setscheduler};
 
     let param = sched_param {
         sched_priority: if policy.is_realtime() { priority } else { 0 },
     };
+    let raw_policy = if reset_on_fork { policy.to_raw() | SCHED_RESET_ON_FORK } else { policy.to_raw() };
 
-    let result = unsafe { sched_setscheduler(pid, policy.to_raw(), &param) };
+    let result = unsafe { sched_setscheduler(pid, raw_policy, &param) };
 
     if result == 0 {
         Ok(())
@@ -148,7 +266,7 @@ pub fn set_scheduler(pid: i32, policy: SchedulePolicy, priority: i32) -> Result<
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn set_scheduler(_pid: i32, _policy: SchedulePolicy, _priority: i32) -> Result<(), String> {
+pub fn set_scheduler(_pid: i32, _policy: SchedulePolicy, _priority: i32, _reset_on_fork: bool) -> Result<(), String> {
     Err("调度策略设置仅支持 Linux".to_string())
 }
 
@@ -165,6 +283,34 @@ pub fn get_process_nice(pid: i32) -> i32 {
     0
 }
 
+/// /proc/[pid]/schedstat 调度统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedStats {
+    /// 在 CPU 上运行的累计时间 (纳秒)
+    pub run_time_ns: u64,
+    /// 可运行但等待 CPU 的累计时间 (纳秒)
+    pub run_delay_ns: u64,
+    /// 运行过的时间片数
+    pub timeslices: u64,
+}
+
+/// 解析 schedstat 内容 (如 "123456 7890 12")
+pub fn parse_schedstat(content: &str) -> Option<SchedStats> {
+    let mut fields = content.split_whitespace().map(|f| f.parse::<u64>().ok());
+    Some(SchedStats {
+        run_time_ns: fields.next()??,
+        run_delay_ns: fields.next()??,
+        timeslices: fields.next()??,
+    })
+}
+
+/// 读取进程的调度统计（需要内核开启 schedstats 或 sched_info）
+pub fn read_schedstat(pid: u32) -> Option<SchedStats> {
+    fs::read_to_string(format!("/proc/{}/schedstat", pid))
+        .ok()
+        .and_then(|s| parse_schedstat(&s))
+}
+
 /// 设置进程的 nice 值 (Linux only)
 #[cfg(target_os = "linux")]
 pub fn set_process_nice(pid: i32, nice: i32) -> Result<(), String> {
@@ -204,19 +350,134 @@ pub fn get_rt_priority_range(_policy: SchedulePolicy) -> (i32, i32) {
     (1, 99)
 }
 
+/// 实时进程限流的 sysctl 文件
+const RT_RUNTIME_PATH: &str = "/proc/sys/kernel/sched_rt_runtime_us";
+const RT_PERIOD_PATH: &str = "/proc/sys/kernel/sched_rt_period_us";
+
+/// 实时进程限流参数 (sched_rt_runtime_us / sched_rt_period_us)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RtThrottling {
+    /// 每个周期内实时进程最多可运行的时间 (微秒)，-1 表示不限流
+    pub runtime_us: i64,
+    /// 周期 (微秒)
+    pub period_us: i64,
+}
+
+impl RtThrottling {
+    /// 是否已禁用限流
+    pub fn is_disabled(&self) -> bool {
+        self.runtime_us < 0
+    }
+
+    /// 实时进程最多可占用的 CPU 比例 (0-1)，不限流时为 None
+    pub fn fraction(&self) -> Option<f64> {
+        if self.is_disabled() || self.period_us <= 0 {
+            return None;
+        }
+        Some(self.runtime_us as f64 / self.period_us as f64)
+    }
+
+    /// 检查是否为内核接受的值
+    pub fn validate(&self) -> Result<(), String> {
+        if self.period_us <= 0 || self.period_us > i32::MAX as i64 {
+            return Err(format!("周期必须在 1 到 {} 微秒之间", i32::MAX));
+        }
+        if self.runtime_us < -1 || self.runtime_us > self.period_us {
+            return Err("运行时间必须为 -1 或不超过周期".to_string());
+        }
+        Ok(())
+    }
+
+    /// 说明文字
+    pub fn describe(&self) -> String {
+        match self.fraction() {
+            Some(fraction) => format!(
+                "每 {} 微秒周期内实时进程最多运行 {} 微秒 ({:.0}%)",
+                self.period_us,
+                self.runtime_us,
+                fraction * 100.0
+            ),
+            None => "已禁用限流，失控的实时进程可以独占 CPU".to_string(),
+        }
+    }
+}
+
+/// 解析 sched_rt_runtime_us 和 sched_rt_period_us 的内容
+pub fn parse_rt_throttling(runtime: &str, period: &str) -> Option<RtThrottling> {
+    Some(RtThrottling {
+        runtime_us: runtime.trim().parse().ok()?,
+        period_us: period.trim().parse().ok()?,
+    })
+}
+
+/// 读取实时进程限流参数
+pub fn get_rt_throttling() -> Option<RtThrottling> {
+    let runtime = fs::read_to_string(RT_RUNTIME_PATH).ok()?;
+    let period = fs::read_to_string(RT_PERIOD_PATH).ok()?;
+    parse_rt_throttling(&runtime, &period)
+}
+
+/// 设置实时进程限流参数（需要 root）
+pub fn set_rt_throttling(value: RtThrottling) -> Result<(), String> {
+    value.validate()?;
+    let write = |path: &str, v: i64| {
+        fs::write(path, v.to_string()).map_err(|e| format!("写入 {} 失败: {}", path, e))
+    };
+    // 内核在每次写入时都检查 runtime <= period，按顺序写入避免中间状态被拒绝
+    let current_period = get_rt_throttling().map_or(0, |t| t.period_us);
+    if value.runtime_us > current_period {
+        write(RT_PERIOD_PATH, value.period_us)?;
+        write(RT_RUNTIME_PATH, value.runtime_us)
+    } else {
+        write(RT_RUNTIME_PATH, value.runtime_us)?;
+        write(RT_PERIOD_PATH, value.period_us)
+    }
+}
+
+/// 切换到某个策略时预填的优先级（按策略短名称查找，缺失时为 0，并限制在该策略的有效范围内）
+pub fn policy_default_priority(defaults: &HashMap<String, i32>, policy: SchedulePolicy) -> i32 {
+    let priority = defaults.get(&policy.short_name()).copied().unwrap_or(0);
+    if policy.is_realtime() {
+        let (min, max) = get_rt_priority_range(policy);
+        priority.clamp(min, max.max(min))
+    } else {
+        priority.clamp(-20, 19)
+    }
+}
+
 /// 预设配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SchedulePreset {
     pub name: String,
     pub description: String,
     pub policy: SchedulePolicy,
     pub priority: i32,
     pub affinity_cores: Option<Vec<usize>>,
+    /// I/O 优先级（None 表示不修改）
+    #[serde(default)]
+    pub io_priority: Option<IoPriority>,
+    /// OOM 分数调整值（None 表示不修改）
+    #[serde(default)]
+    pub oom_score_adj: Option<i32>,
+    /// 是否为内置预设（内置预设不可编辑或删除）
+    #[serde(default)]
+    pub is_builtin: bool,
 }
 
 impl SchedulePreset {
+    /// 复制为可编辑的自定义预设
+    pub fn duplicate(&self) -> SchedulePreset {
+        SchedulePreset {
+            name: format!("{} (副本)", self.name),
+            is_builtin: false,
+            ..self.clone()
+        }
+    }
+
     /// 内置预设
-    pub fn builtin_presets(vcache_cores: &[usize], all_cores: usize) -> Vec<SchedulePreset> {
+    /// 绑核预设经 AffinityTarget 解析，因此不包含保留核心
+    pub fn builtin_presets(topology: &CoreTopology) -> Vec<SchedulePreset> {
+        let resolve = |target: AffinityTarget| target.resolve(topology).ok().flatten();
         let mut presets = vec![
             SchedulePreset {
                 name: "默认".to_string(),
@@ -224,6 +485,9 @@ impl SchedulePreset {
                 policy: SchedulePolicy::Other,
                 priority: 0,
                 affinity_cores: None,
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: true,
             },
             SchedulePreset {
                 name: "高优先级".to_string(),
@@ -231,13 +495,29 @@ impl SchedulePreset {
                 policy: SchedulePolicy::Other,
                 priority: -10,
                 affinity_cores: None,
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: true,
+            },
+            SchedulePreset {
+                name: "前台".to_string(),
+                description: "前台加速时应用于焦点窗口所属进程".to_string(),
+                policy: SchedulePolicy::Other,
+                priority: -5,
+                affinity_cores: None,
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: true,
             },
             SchedulePreset {
                 name: "后台任务".to_string(),
-                description: "低优先级，仅在空闲时运行".to_string(),
+                description: "低优先级，CPU 和 I/O 仅在空闲时运行".to_string(),
                 policy: SchedulePolicy::Idle,
                 priority: 0,
                 affinity_cores: None,
+                io_priority: Some(IoPriority { class: IoClass::Idle, level: 0 }),
+                oom_score_adj: Some(500),
+                is_builtin: true,
             },
             SchedulePreset {
                 name: "实时 (FIFO)".to_string(),
@@ -245,23 +525,27 @@ impl SchedulePreset {
                 policy: SchedulePolicy::Fifo,
                 priority: 50,
                 affinity_cores: None,
+                io_priority: None,
+                oom_score_adj: None,
+                is_builtin: true,
             },
         ];
 
         // 如果有 V-Cache 核心，添加游戏模式预设
-        if !vcache_cores.is_empty() {
+        if let Some(vcache_cores) = resolve(AffinityTarget::VCache) {
             presets.push(SchedulePreset {
                 name: "游戏模式 (V-Cache)".to_string(),
                 description: "绑定到 3D V-Cache 核心".to_string(),
                 policy: SchedulePolicy::Other,
                 priority: -5,
-                affinity_cores: Some(vcache_cores.to_vec()),
+                affinity_cores: Some(vcache_cores),
+                io_priority: Some(IoPriority { class: IoClass::BestEffort, level: 0 }),
+                oom_score_adj: Some(-300),
+                is_builtin: true,
             });
 
             // 非 V-Cache 核心
-            let non_vcache: Vec<usize> = (0..all_cores)
-                .filter(|c| !vcache_cores.contains(c))
-                .collect();
+            let non_vcache = resolve(AffinityTarget::Frequency).unwrap_or_default();
 
             if !non_vcache.is_empty() {
                 presets.push(SchedulePreset {
@@ -270,6 +554,28 @@ impl SchedulePreset {
                     policy: SchedulePolicy::Other,
                     priority: 0,
                     affinity_cores: Some(non_vcache),
+                    io_priority: None,
+                    oom_score_adj: None,
+                    is_builtin: true,
+                });
+            }
+        }
+
+        // 多路系统：绑定到单个插槽，避免跨插槽访问内存
+        if topology.is_multi_package() {
+            for &package_id in topology.packages.keys() {
+                let Some(cpus) = resolve(AffinityTarget::Package(package_id as u32)) else {
+                    continue;
+                };
+                presets.push(SchedulePreset {
+                    name: format!("绑定插槽 {}", package_id),
+                    description: format!("绑定到插槽 {} 的 {} 个逻辑核心", package_id, cpus.len()),
+                    policy: SchedulePolicy::Other,
+                    priority: 0,
+                    affinity_cores: Some(cpus),
+                    io_priority: None,
+                    oom_score_adj: None,
+                    is_builtin: true,
                 });
             }
         }
@@ -277,3 +583,92 @@ impl SchedulePreset {
         presets
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rt_throttling() {
+        let default = parse_rt_throttling("950000\n", "1000000\n").unwrap();
+        assert_eq!(default, RtThrottling { runtime_us: 950_000, period_us: 1_000_000 });
+        assert_eq!(default.fraction(), Some(0.95));
+        assert!(default.validate().is_ok());
+
+        let disabled = parse_rt_throttling("-1\n", "1000000\n").unwrap();
+        assert!(disabled.is_disabled());
+        assert_eq!(disabled.fraction(), None);
+        assert!(disabled.validate().is_ok());
+
+        assert!(RtThrottling { runtime_us: 2_000_000, period_us: 1_000_000 }.validate().is_err());
+        assert!(RtThrottling { runtime_us: -2, period_us: 1_000_000 }.validate().is_err());
+        assert!(RtThrottling { runtime_us: 0, period_us: 0 }.validate().is_err());
+        assert_eq!(parse_rt_throttling("abc", "1000000"), None);
+    }
+
+    #[test]
+    fn test_package_presets() {
+        // 单插槽不提供插槽预设
+        let single = SchedulePreset::builtin_presets(&CoreTopology::single_package(8, &[]));
+        assert!(!single.iter().any(|p| p.name.starts_with("绑定插槽")));
+
+        let topology = CoreTopology {
+            logical_cores: 8,
+            vcache_cores: Vec::new(),
+            packages: [(0, vec![0, 1, 4, 5]), (1, vec![2, 3, 6, 7])].into(),
+            reserved_cores: Vec::new(),
+        };
+        let presets = SchedulePreset::builtin_presets(&topology);
+        let socket1 = presets.iter().find(|p| p.name == "绑定插槽 1").unwrap();
+        assert_eq!(socket1.affinity_cores, Some(vec![2, 3, 6, 7]));
+        assert!(socket1.is_builtin);
+        assert!(presets.iter().any(|p| p.name == "绑定插槽 0"));
+
+        // 保留核心不出现在内置预设中
+        let presets = SchedulePreset::builtin_presets(&topology.with_reserved(&[0, 4]));
+        let socket0 = presets.iter().find(|p| p.name == "绑定插槽 0").unwrap();
+        assert_eq!(socket0.affinity_cores, Some(vec![1, 5]));
+        assert!(socket0.description.contains("2 个逻辑核心"));
+        let presets = SchedulePreset::builtin_presets(&CoreTopology::single_package(8, &[0, 1, 2, 3]).with_reserved(&[0]));
+        let game = presets.iter().find(|p| p.name == "游戏模式 (V-Cache)").unwrap();
+        assert_eq!(game.affinity_cores, Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_policy_default_priority() {
+        let defaults: HashMap<String, i32> =
+            [("BATCH".to_string(), 5), ("FIFO".to_string(), 50), ("OTHER".to_string(), -40)].into();
+        assert_eq!(policy_default_priority(&defaults, SchedulePolicy::Batch), 5);
+        assert_eq!(policy_default_priority(&defaults, SchedulePolicy::Fifo), 50);
+        assert_eq!(policy_default_priority(&defaults, SchedulePolicy::Other), -20);
+        assert_eq!(policy_default_priority(&defaults, SchedulePolicy::Idle), 0);
+
+        // 实时策略缺少默认值时取有效范围内的最小值，而不是无效的 0
+        let (min, _) = get_rt_priority_range(SchedulePolicy::RoundRobin);
+        assert_eq!(policy_default_priority(&defaults, SchedulePolicy::RoundRobin), min);
+    }
+
+    #[test]
+    fn test_parse_schedstat() {
+        assert_eq!(
+            parse_schedstat("2147483648 1048576 42\n"),
+            Some(SchedStats {
+                run_time_ns: 2147483648,
+                run_delay_ns: 1048576,
+                timeslices: 42,
+            })
+        );
+        assert_eq!(parse_schedstat("1 2"), None);
+        assert_eq!(parse_schedstat(""), None);
+    }
+
+    #[test]
+    fn test_unknown_policy_display() {
+        assert_eq!(SchedulePolicy::from_raw(6), SchedulePolicy::Unknown(6));
+        assert_eq!(SchedulePolicy::Unknown(6).display_name(), "未知 (6)");
+        assert_eq!(SchedulePolicy::Unknown(6).short_name(), "?(6)");
+        assert!(SchedulePolicy::Unknown(6).is_unrecognized());
+        assert!(!SchedulePolicy::Unknown(-1).is_unrecognized());
+        assert!(!SchedulePolicy::Other.is_unrecognized());
+    }
+}
@@ -0,0 +1,199 @@
+//! 调整录制 - 把界面中手动应用的调度更改录制为可重放的 shell 脚本
+//! 脚本使用 chrt / renice / ionice / taskset，按进程名 (pgrep -x) 匹配目标，可以之后或在另一台机器上重新运行
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{format_cpu_list, ApplySource, IoClass, SchedChange, SchedulePolicy};
+
+/// 录制的一步：对同名进程应用的一组更改
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStep {
+    /// 目标进程名 (/proc/[pid]/comm，与 pgrep -x 匹配的名称相同)
+    pub name: String,
+    /// 来源说明（如 "手动"、"预设 '游戏'"）
+    pub source: String,
+    /// 成功应用的更改
+    pub changes: Vec<SchedChange>,
+}
+
+/// 调整录制器：录制期间记录手动修改和手动应用的预设（自动规则、前台加速等自动操作不录制）
+#[derive(Debug, Default)]
+pub struct ScriptRecorder {
+    recording: bool,
+    steps: Vec<ScriptStep>,
+}
+
+impl ScriptRecorder {
+    /// 是否正在录制
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// 开始或停止录制（已录制的步骤保留，继续录制时追加）
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// 已录制的步骤
+    pub fn steps(&self) -> &[ScriptStep] {
+        &self.steps
+    }
+
+    /// 清空已录制的步骤
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    /// 录制一次成功的应用（未在录制或不是用户操作时忽略）
+    pub fn record(&mut self, pid: u32, changes: &[SchedChange], source: &ApplySource) {
+        if !self.recording || !matches!(source, ApplySource::Manual | ApplySource::Preset(_)) || changes.is_empty() {
+            return;
+        }
+        let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) else {
+            return;
+        };
+        self.push(ScriptStep {
+            name: comm.trim_end_matches('\n').to_string(),
+            source: source.describe(),
+            changes: changes.to_vec(),
+        });
+    }
+
+    fn push(&mut self, step: ScriptStep) {
+        self.steps.push(step);
+    }
+
+    /// 生成 shell 脚本
+    pub fn to_shell_script(&self) -> String {
+        let mut script = String::from("#!/bin/sh\n");
+        script.push_str(&format!("# hexin 录制的调度调整（{} 步）\n", self.steps.len()));
+        script.push_str("# 每一步按进程名 (pgrep -x) 匹配，对所有同名进程执行；实时策略、降低 nice 等操作需要 root 权限\n");
+        for (index, step) in self.steps.iter().enumerate() {
+            script.push_str(&format!("\n# {}. {} · {}\n", index + 1, step.source, step.name));
+            let name = shell_quote(&step.name);
+            script.push_str(&format!("pids=$(pgrep -x {}) || echo \"未找到进程 {}\" >&2\n", name, escape_double_quoted(&step.name)));
+            script.push_str("for pid in $pids; do\n");
+            for change in &step.changes {
+                script.push_str(&format!("    {}\n", change_command(change)));
+            }
+            script.push_str("done\n");
+        }
+        script
+    }
+
+    /// 保存为可执行的脚本文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_shell_script()).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("设置 {} 的执行权限失败: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// 对 `$pid` 应用一项更改的命令
+fn change_command(change: &SchedChange) -> String {
+    match change {
+        SchedChange::Policy { policy, priority, reset_on_fork } => {
+            let (flag, priority) = match policy {
+                SchedulePolicy::Other => ("--other", 0),
+                SchedulePolicy::Batch => ("--batch", 0),
+                SchedulePolicy::Idle => ("--idle", 0),
+                SchedulePolicy::Fifo => ("--fifo", *priority),
+                SchedulePolicy::RoundRobin => ("--rr", *priority),
+                SchedulePolicy::Unknown(raw) => return format!("# 跳过未识别的调度策略 {}", raw),
+            };
+            let reset = if *reset_on_fork { " --reset-on-fork" } else { "" };
+            format!("chrt {}{} -p {} \"$pid\"", flag, reset, priority)
+        }
+        SchedChange::Nice(nice) => format!("renice {} -p \"$pid\"", nice),
+        SchedChange::IoPriority(prio) => match prio.class {
+            IoClass::None => "ionice -c 0 -p \"$pid\"".to_string(),
+            IoClass::RealTime => format!("ionice -c 1 -n {} -p \"$pid\"", prio.level),
+            IoClass::BestEffort => format!("ionice -c 2 -n {} -p \"$pid\"", prio.level),
+            IoClass::Idle => "ionice -c 3 -p \"$pid\"".to_string(),
+        },
+        SchedChange::OomScoreAdj(value) => format!("echo {} > \"/proc/$pid/oom_score_adj\"", value),
+        SchedChange::Affinity(cores) => format!("taskset -pc {} \"$pid\"", format_cpu_list(cores)),
+    }
+}
+
+/// 单引号转义（进程名可能包含空格等特殊字符）
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// 双引号字符串内的转义
+fn escape_double_quoted(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '"' | '\\' | '$' | '`' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// 默认保存路径（主目录，取不到时为当前目录）
+pub fn default_script_path(timestamp: u64) -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(format!("hexin_script_{}.sh", timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::IoPriority;
+
+    #[test]
+    fn test_shell_script() {
+        let mut recorder = ScriptRecorder::default();
+        recorder.push(ScriptStep {
+            name: "game".to_string(),
+            source: "预设 '游戏'".to_string(),
+            changes: vec![
+                SchedChange::Policy { policy: SchedulePolicy::Fifo, priority: 50, reset_on_fork: true },
+                SchedChange::Affinity(vec![0, 1, 2, 3, 8]),
+            ],
+        });
+        recorder.push(ScriptStep {
+            name: "it's $x".to_string(),
+            source: "手动".to_string(),
+            changes: vec![
+                SchedChange::Nice(10),
+                SchedChange::IoPriority(IoPriority { class: IoClass::Idle, level: 0 }),
+                SchedChange::OomScoreAdj(500),
+            ],
+        });
+
+        let script = recorder.to_shell_script();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("# 1. 预设 '游戏' · game\npids=$(pgrep -x 'game')"));
+        assert!(script.contains("    chrt --fifo --reset-on-fork -p 50 \"$pid\"\n    taskset -pc 0-3,8 \"$pid\"\ndone\n"));
+        assert!(script.contains("pgrep -x 'it'\\''s $x') || echo \"未找到进程 it's \\$x\" >&2"));
+        assert!(script.contains("    renice 10 -p \"$pid\"\n    ionice -c 3 -p \"$pid\"\n"));
+        assert!(script.contains("echo 500 > \"/proc/$pid/oom_score_adj\""));
+        assert_eq!(change_command(&SchedChange::Nice(-5)), "renice -5 -p \"$pid\"");
+    }
+
+    #[test]
+    fn test_record_only_user_actions() {
+        let mut recorder = ScriptRecorder::default();
+        let pid = std::process::id();
+        let changes = [SchedChange::Nice(5)];
+        recorder.record(pid, &changes, &ApplySource::Manual);
+        assert!(recorder.steps().is_empty());
+
+        recorder.set_recording(true);
+        recorder.record(pid, &changes, &ApplySource::Rule("build".to_string()));
+        recorder.record(pid, &changes, &ApplySource::FocusBoost);
+        recorder.record(pid, &changes, &ApplySource::Preset("后台".to_string()));
+        assert_eq!(recorder.steps().len(), 1);
+        assert_eq!(recorder.steps()[0].source, "预设 '后台'");
+        assert!(!recorder.steps()[0].name.is_empty());
+    }
+}
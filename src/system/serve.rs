@@ -0,0 +1,244 @@
+//! 网页快照 - 在指定端口提供只读的状态页面（拓扑、各核心使用率和占用最高的进程），便于从局域网其他设备查看
+//! 服务需要 `serve` feature；页面定期轮询 /snapshot.json，服务只响应 GET，不提供任何修改操作
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{unix_timestamp, CpuInfo, ProcessInfo, ProcessManager};
+
+/// 快照中的进程数（按 CPU 使用率降序）
+pub const WEB_TOP_PROCESSES: usize = 25;
+/// 两次生成快照的最短间隔
+const WEB_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+/// 超过此时间没有页面请求快照时停止生成
+const WEB_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 服务线程与界面（或无界面循环）共享的快照状态
+#[derive(Debug, Default)]
+pub struct SnapshotState {
+    /// 最新的快照 JSON（尚未生成时为空）
+    json: String,
+    /// 最近一次收到快照请求的时间
+    requested_at: Option<Instant>,
+    /// 最近一次生成快照的时间
+    built_at: Option<Instant>,
+}
+
+impl SnapshotState {
+    /// 是否需要重新生成：最近有页面在轮询，且距上次生成已超过最短间隔
+    fn wants_rebuild(&self, now: Instant) -> bool {
+        let polled = self.requested_at.is_some_and(|at| now.duration_since(at) < WEB_IDLE_TIMEOUT);
+        polled && self.built_at.is_none_or(|at| now.duration_since(at) >= WEB_SNAPSHOT_INTERVAL)
+    }
+}
+
+/// 快照状态，由界面或无界面循环写入，服务线程读取
+pub type SnapshotSlot = Arc<Mutex<SnapshotState>>;
+
+/// 页面中的进程：只包含名称、PID、CPU 和内存（服务没有认证，不公开命令行等信息）
+#[derive(Debug, Serialize)]
+pub struct WebProcess<'a> {
+    pub pid: u32,
+    pub name: &'a str,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+/// 占用 CPU 最高的进程
+#[derive(Debug, Serialize)]
+pub struct WebProcesses<'a> {
+    /// 生成时间 (Unix 秒)
+    pub timestamp: u64,
+    /// 进程总数
+    pub total: usize,
+    /// 按 CPU 使用率降序（相同时按 PID 升序）的前若干个进程
+    pub processes: Vec<WebProcess<'a>>,
+}
+
+impl<'a> WebProcesses<'a> {
+    /// 从全部进程中取 CPU 使用率最高的 `limit` 个，与界面当前的排序和过滤无关
+    pub fn top_by_cpu(processes: impl Iterator<Item = &'a ProcessInfo>, limit: usize, timestamp: u64) -> Self {
+        let mut all: Vec<&ProcessInfo> = processes.collect();
+        let total = all.len();
+        all.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(a.pid.cmp(&b.pid)));
+        all.truncate(limit);
+        let processes = all
+            .into_iter()
+            .map(|p| WebProcess { pid: p.pid, name: &p.name, cpu_usage: p.cpu_usage, memory: p.memory })
+            .collect();
+        Self { timestamp, total, processes }
+    }
+}
+
+/// 页面轮询的快照
+#[derive(Debug, Serialize)]
+pub struct WebSnapshot<'a> {
+    /// CPU 拓扑和各核心使用率
+    pub cpu: &'a CpuInfo,
+    /// 占用最高的进程
+    pub processes: WebProcesses<'a>,
+}
+
+/// 序列化当前状态为页面使用的 JSON
+pub fn web_snapshot_json(cpu_info: &CpuInfo, process_manager: &ProcessManager) -> Result<String, String> {
+    let snapshot = WebSnapshot {
+        cpu: cpu_info,
+        processes: WebProcesses::top_by_cpu(process_manager.processes(), WEB_TOP_PROCESSES, unix_timestamp()),
+    };
+    serde_json::to_string(&snapshot).map_err(|e| format!("序列化网页快照失败: {}", e))
+}
+
+/// 有页面在轮询时更新服务提供的快照（限制生成频率，没有访问时不生成）
+pub fn publish_snapshot(slot: &SnapshotSlot, cpu_info: &CpuInfo, process_manager: &ProcessManager) {
+    let now = Instant::now();
+    if !slot.lock().is_ok_and(|state| state.wants_rebuild(now)) {
+        return;
+    }
+    match web_snapshot_json(cpu_info, process_manager) {
+        Ok(json) => {
+            if let Ok(mut state) = slot.lock() {
+                state.json = json;
+                state.built_at = Some(now);
+            }
+        }
+        Err(e) => tracing::warn!("{}", e),
+    }
+}
+
+/// 在 `addr`（如 127.0.0.1:8080）启动快照服务，返回写入快照的位置
+#[cfg(feature = "serve")]
+pub fn serve_snapshots(addr: &str) -> Result<SnapshotSlot, String> {
+    http::spawn(addr)
+}
+
+#[cfg(not(feature = "serve"))]
+pub fn serve_snapshots(_addr: &str) -> Result<SnapshotSlot, String> {
+    Err("此版本编译时未启用网页快照 (serve feature)".to_string())
+}
+
+/// 基于 tiny_http 的只读服务
+#[cfg(feature = "serve")]
+mod http {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Instant;
+    use tiny_http::{Header, Method, Response, Server};
+
+    use super::{SnapshotSlot, SnapshotState};
+
+    /// 自包含的状态页面（无外部资源）
+    const PAGE: &str = include_str!("../../assets/snapshot.html");
+
+    /// 按请求路径生成响应（状态码, Content-Type, 内容）
+    fn respond(method: &Method, url: &str, slot: &SnapshotSlot) -> (u16, &'static str, String) {
+        if *method != Method::Get && *method != Method::Head {
+            return (405, "text/plain; charset=utf-8", "只读服务，仅支持 GET".to_string());
+        }
+        // 忽略查询参数（页面轮询时附加时间戳避免缓存）
+        match url.split('?').next().unwrap_or("") {
+            "/" | "/index.html" => (200, "text/html; charset=utf-8", PAGE.to_string()),
+            "/snapshot.json" => {
+                // 记录请求：界面只在有页面轮询时生成快照
+                let json = slot
+                    .lock()
+                    .map(|mut state| {
+                        state.requested_at = Some(Instant::now());
+                        state.json.clone()
+                    })
+                    .unwrap_or_default();
+                if json.is_empty() {
+                    (503, "text/plain; charset=utf-8", "正在采集数据".to_string())
+                } else {
+                    (200, "application/json", json)
+                }
+            }
+            _ => (404, "text/plain; charset=utf-8", "未找到".to_string()),
+        }
+    }
+
+    /// 绑定地址并在后台线程处理请求（运行到进程退出）
+    pub fn spawn(addr: &str) -> Result<SnapshotSlot, String> {
+        let server = Server::http(addr).map_err(|e| format!("无法在 {} 启动网页快照服务: {}", addr, e))?;
+        let slot: SnapshotSlot = Arc::new(Mutex::new(SnapshotState::default()));
+        let latest = slot.clone();
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let (status, content_type, body) = respond(request.method(), request.url(), &latest);
+                let mut response = Response::from_string(body).with_status_code(status);
+                for (name, value) in [("Content-Type", content_type), ("Cache-Control", "no-store")] {
+                    if let Ok(header) = Header::from_bytes(name, value) {
+                        response.add_header(header);
+                    }
+                }
+                // 客户端提前断开时忽略
+                let _ = request.respond(response);
+            }
+        });
+        tracing::info!("网页快照: http://{}/", addr);
+        Ok(slot)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_respond() {
+            let slot: SnapshotSlot = Arc::new(Mutex::new(SnapshotState::default()));
+            assert_eq!(respond(&Method::Get, "/", &slot).0, 200);
+            assert_eq!(respond(&Method::Get, "/snapshot.json", &slot).0, 503);
+            assert!(slot.lock().unwrap().requested_at.is_some());
+            slot.lock().unwrap().json = "{}".to_string();
+            let (status, content_type, body) = respond(&Method::Get, "/snapshot.json?t=1", &slot);
+            assert_eq!((status, content_type, body.as_str()), (200, "application/json", "{}"));
+            assert_eq!(respond(&Method::Post, "/snapshot.json", &slot).0, 405);
+            assert_eq!(respond(&Method::Get, "/config", &slot).0, 404);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::process::tests::process;
+    use crate::system::SortField;
+
+    #[test]
+    fn test_publish_snapshot() {
+        let mut manager = ProcessManager::new(1);
+        manager.insert_process(process(10, "game", 80.0, 0));
+        manager.insert_process(process(11, "bash", 1.0, 0));
+
+        // 界面按 PID 排序时网页仍按 CPU 降序
+        manager.set_sort(SortField::Pid);
+
+        // 没有页面请求过快照时不生成
+        let slot: SnapshotSlot = Arc::new(Mutex::new(SnapshotState::default()));
+        publish_snapshot(&slot, &CpuInfo::empty(), &manager);
+        assert!(slot.lock().unwrap().json.is_empty());
+
+        slot.lock().unwrap().requested_at = Some(Instant::now());
+        publish_snapshot(&slot, &CpuInfo::empty(), &manager);
+        let json = slot.lock().unwrap().json.clone();
+        assert!(json.starts_with("{\"cpu\":{"));
+        assert!(json.contains("\"total\":2"));
+        assert!(json.find("\"name\":\"game\"").unwrap() < json.find("\"name\":\"bash\"").unwrap());
+        // 只公开名称、PID、CPU 和内存
+        assert!(!json.contains("\"cmd\"") && !json.contains("\"affinity\""));
+    }
+
+    #[test]
+    fn test_snapshot_throttle() {
+        let now = Instant::now();
+        let mut state = SnapshotState::default();
+        assert!(!state.wants_rebuild(now));
+        state.requested_at = Some(now);
+        assert!(state.wants_rebuild(now));
+        state.built_at = Some(now);
+        assert!(!state.wants_rebuild(now + Duration::from_millis(500)));
+        assert!(state.wants_rebuild(now + WEB_SNAPSHOT_INTERVAL));
+        // 页面不再轮询
+        assert!(!state.wants_rebuild(now + WEB_IDLE_TIMEOUT));
+    }
+}
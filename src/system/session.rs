@@ -0,0 +1,747 @@
+//! 本次会话中 hexin 所做调度更改的记录
+//! 每个进程只保留首次修改前的原始值，用于一键还原
+//! 进程当前配置的来源记录保存在 ~/.config/hexin/applied_by.toml，重启后仍可显示
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::mem::discriminant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{
+    apply_each, protected_reason, read_process_security, read_process_start_time, rollback, AffinityDiff, ApplyReport,
+    ProcessInfo, SchedChange, SchedulePolicy, ScriptRecorder,
+};
+
+/// 进程当前配置的来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApplySource {
+    /// 在界面中手动修改
+    Manual,
+    /// 手动应用的预设
+    Preset(String),
+    /// 自动规则
+    Rule(String),
+    /// 高温降温动作
+    Thermal,
+    /// 前台加速
+    FocusBoost,
+}
+
+impl ApplySource {
+    /// 显示文本
+    pub fn describe(&self) -> String {
+        match self {
+            ApplySource::Manual => "手动".to_string(),
+            ApplySource::Preset(name) => format!("预设 '{}'", name),
+            ApplySource::Rule(name) => format!("规则 '{}'", name),
+            ApplySource::Thermal => "高温降温".to_string(),
+            ApplySource::FocusBoost => "前台加速".to_string(),
+        }
+    }
+}
+
+/// 进程当前配置的来源记录：同一进程多次应用时合并设置项，来源和时间取最近一次
+#[derive(Debug, Clone)]
+pub struct AppliedBy {
+    /// 来源
+    pub source: ApplySource,
+    /// 应用时间
+    pub at: Instant,
+    /// 进程启动时间（用于识别 PID 复用）
+    pub start_time: Option<u64>,
+    /// 应用的设置项
+    pub changes: Vec<SchedChange>,
+    /// 应用之后进程执行了 exec 时，应用时的进程名称
+    pub exec_from: Option<String>,
+}
+
+impl AppliedBy {
+    /// 显示文本（如 "规则 'factorio' · 2 分钟前"）
+    pub fn describe(&self, now: Instant) -> String {
+        let mut text = format!("{} · {}", self.source.describe(), format_ago(now.saturating_duration_since(self.at)));
+        if let Some(name) = &self.exec_from {
+            text.push_str(&format!(" · 应用于 exec 前的 {}", name));
+        }
+        text
+    }
+
+    /// 进程当前的设置是否仍与应用的一致
+    /// 只比较进程列表中可见的策略、nice 和亲和性，I/O 优先级和 OOM 调整不参与判断
+    pub fn matches(&self, process: &ProcessInfo, logical_cores: usize) -> bool {
+        self.changes.iter().all(|change| match change {
+            SchedChange::Policy { policy, .. } => process.sched_policy == *policy,
+            SchedChange::Nice(nice) => process.sched_policy.is_realtime() || process.priority == *nice,
+            SchedChange::Affinity(cores) => {
+                let mut expected: Vec<usize> = cores.iter().copied().filter(|&c| c < logical_cores).collect();
+                expected.sort_unstable();
+                expected.dedup();
+                let mut current = process.affinity.clone();
+                current.sort_unstable();
+                expected == current
+            }
+            SchedChange::IoPriority(_) | SchedChange::OomScoreAdj(_) => true,
+        })
+    }
+}
+
+/// 保存到磁盘的来源记录，重启后按 PID 和启动时间恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredAppliedBy {
+    pid: u32,
+    source: ApplySource,
+    /// 应用时间（Unix 秒）
+    applied_at: u64,
+    start_time: u64,
+    changes: Vec<SchedChange>,
+    #[serde(default)]
+    exec_from: Option<String>,
+}
+
+/// 来源记录文件
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AppliedByFile {
+    #[serde(default)]
+    processes: Vec<StoredAppliedBy>,
+}
+
+/// 相对时间（如 "2 分钟前"）
+fn format_ago(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        "刚刚".to_string()
+    } else if secs < 3600 {
+        format!("{} 分钟前", secs / 60)
+    } else if secs < 86400 {
+        format!("{} 小时前", secs / 3600)
+    } else {
+        format!("{} 天前", secs / 86400)
+    }
+}
+
+/// 单个进程的更改记录
+#[derive(Debug, Clone)]
+pub struct SessionEntry {
+    /// 进程 ID
+    pub pid: u32,
+    /// 进程名称
+    pub name: String,
+    /// 进程启动时间（用于识别 PID 复用）
+    pub start_time: Option<u64>,
+    /// 各设置项在首次修改前的原始值
+    pub originals: Vec<SchedChange>,
+}
+
+impl SessionEntry {
+    /// 记录的进程是否仍在运行（PID 未被复用）
+    pub fn is_alive(&self) -> bool {
+        match read_process_start_time(self.pid) {
+            Some(start_time) => self.start_time.is_none() || self.start_time == Some(start_time),
+            None => false,
+        }
+    }
+}
+
+/// 恢复默认时应用的设置：已记录原始值的设置项恢复原值，调度策略、nice 和亲和性没有记录时
+/// 使用默认值（SCHED_OTHER、nice 0、全部核心）
+///
+/// 先恢复调度策略（离开实时策略后 nice 才生效），亲和性最后恢复
+pub fn default_changes(originals: &[SchedChange], logical_cores: usize) -> Vec<SchedChange> {
+    let original = |kind: &SchedChange| originals.iter().find(|c| discriminant(*c) == discriminant(kind)).cloned();
+    let mut changes = Vec::new();
+    for default in [
+        SchedChange::Policy { policy: SchedulePolicy::Other, priority: 0, reset_on_fork: false },
+        SchedChange::Nice(0),
+    ] {
+        changes.push(original(&default).unwrap_or(default));
+    }
+    changes.extend(
+        originals
+            .iter()
+            .filter(|c| matches!(c, SchedChange::IoPriority(_) | SchedChange::OomScoreAdj(_)))
+            .cloned(),
+    );
+    let all_cores = SchedChange::Affinity((0..logical_cores).collect());
+    changes.push(original(&all_cores).unwrap_or(all_cores));
+    changes
+}
+
+/// 还原结果汇总
+#[derive(Debug, Clone, Default)]
+pub struct RevertSummary {
+    /// 已还原的进程数
+    pub reverted: usize,
+    /// 已退出而跳过的进程
+    pub skipped: Vec<u32>,
+    /// 受保护（hexin 自身、init、内核线程）而跳过的进程及原因
+    pub protected: Vec<(u32, &'static str)>,
+    /// 还原失败的设置项
+    pub errors: Vec<String>,
+}
+
+impl RevertSummary {
+    /// 结果摘要
+    pub fn summary(&self) -> String {
+        let mut msg = format!("已还原 {} 个进程", self.reverted);
+        if !self.skipped.is_empty() {
+            msg.push_str(&format!("，跳过 {} 个已退出进程", self.skipped.len()));
+        }
+        if !self.protected.is_empty() {
+            msg.push_str(&format!("，跳过 {} 个受保护进程", self.protected.len()));
+        }
+        if !self.errors.is_empty() {
+            msg.push_str(&format!("；失败: {}", self.errors.join("; ")));
+        }
+        msg
+    }
+}
+
+/// 会话更改记录
+#[derive(Debug, Default)]
+pub struct SessionJournal {
+    entries: Vec<SessionEntry>,
+    /// 各进程当前配置的来源（按 PID 索引，详情面板直接查找）
+    applied_by: HashMap<u32, AppliedBy>,
+    /// 刚被修改或还原、需要立即刷新显示的进程
+    touched: Vec<u32>,
+    /// 刚应用的亲和性变化（核心网格据此闪烁提示）
+    affinity_diffs: Vec<AffinityDiff>,
+    /// 调整录制（录制期间手动应用的更改）
+    recorder: ScriptRecorder,
+    /// 刚失败的设置项及错误（汇总到权限与限制清单）
+    failures: Vec<(SchedChange, String)>,
+    /// 来源记录自上次保存后是否变化
+    applied_by_dirty: bool,
+}
+
+impl SessionJournal {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            applied_by: HashMap::new(),
+            touched: Vec::new(),
+            affinity_diffs: Vec::new(),
+            recorder: ScriptRecorder::default(),
+            failures: Vec::new(),
+            applied_by_dirty: false,
+        }
+    }
+
+    /// 创建记录并恢复上次运行保存的、进程仍在运行的来源记录
+    pub fn load() -> Self {
+        let mut journal = Self::new();
+        if let Some(path) = Self::applied_by_path() {
+            journal.load_applied_by_from(&path);
+        }
+        journal
+    }
+
+    /// 获取来源记录文件路径
+    fn applied_by_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("hexin").join("applied_by.toml"))
+    }
+
+    /// 来源记录变化时写入磁盘
+    pub fn save_applied_by(&mut self) {
+        if !self.applied_by_dirty {
+            return;
+        }
+        if let Some(path) = Self::applied_by_path() {
+            self.save_applied_by_to(&path);
+        }
+        self.applied_by_dirty = false;
+    }
+
+    /// 写入来源记录；没有启动时间的记录重启后无法识别 PID 复用，不保存
+    fn save_applied_by_to(&self, path: &Path) {
+        let now = Instant::now();
+        let wall = SystemTime::now();
+        let processes = self
+            .applied_by
+            .iter()
+            .filter_map(|(&pid, applied)| {
+                let applied_at = wall.checked_sub(now.saturating_duration_since(applied.at)).unwrap_or(wall);
+                Some(StoredAppliedBy {
+                    pid,
+                    source: applied.source.clone(),
+                    applied_at: applied_at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+                    start_time: applied.start_time?,
+                    changes: applied.changes.clone(),
+                    exec_from: applied.exec_from.clone(),
+                })
+            })
+            .collect();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(content) = toml::to_string_pretty(&AppliedByFile { processes }) {
+            let _ = fs::write(path, content);
+        }
+    }
+
+    /// 读取来源记录，跳过已退出或 PID 已被复用的进程
+    fn load_applied_by_from(&mut self, path: &Path) {
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+        let file: AppliedByFile = toml::from_str(&content).unwrap_or_default();
+        let now = Instant::now();
+        let wall = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        for stored in file.processes {
+            if read_process_start_time(stored.pid) != Some(stored.start_time) {
+                continue;
+            }
+            let ago = Duration::from_secs(wall.saturating_sub(stored.applied_at));
+            self.applied_by.insert(
+                stored.pid,
+                AppliedBy {
+                    source: stored.source,
+                    at: now.checked_sub(ago).unwrap_or(now),
+                    start_time: Some(stored.start_time),
+                    changes: stored.changes,
+                    exec_from: stored.exec_from,
+                },
+            );
+        }
+    }
+
+    /// 记录一次应用：保存原始值，成功时同时记录配置来源
+    pub fn record_application(&mut self, pid: u32, changes: &[SchedChange], report: &ApplyReport, source: ApplySource) {
+        self.record(pid, &report.previous);
+        self.touch(pid);
+        if let Some((label, error)) = &report.failure {
+            if let Some(change) = changes.iter().find(|c| c.label() == *label) {
+                self.failures.push((change.clone(), error.clone()));
+            }
+        }
+        if !report.is_success() || report.applied.is_empty() {
+            return;
+        }
+        if let Some(diff) = report.affinity_diff(changes) {
+            self.affinity_diffs.push(diff);
+        }
+        let changes: Vec<SchedChange> = changes
+            .iter()
+            .filter(|c| report.applied.contains(&c.label()))
+            .cloned()
+            .collect();
+        self.recorder.record(pid, &changes, &source);
+        self.merge_applied_by(pid, read_process_start_time(pid), source, changes);
+    }
+
+    /// 把一次成功的应用合并进进程的来源记录：同类设置项替换，其余保留；PID 被复用时替换整条记录
+    fn merge_applied_by(&mut self, pid: u32, start_time: Option<u64>, source: ApplySource, changes: Vec<SchedChange>) {
+        self.applied_by_dirty = true;
+        let applied = self.applied_by.entry(pid).or_insert_with(|| AppliedBy {
+            source: source.clone(),
+            at: Instant::now(),
+            start_time,
+            changes: Vec::new(),
+            exec_from: None,
+        });
+        if applied.start_time != start_time {
+            applied.changes.clear();
+        }
+        for change in changes {
+            match applied.changes.iter_mut().find(|c| discriminant(*c) == discriminant(&change)) {
+                Some(existing) => *existing = change,
+                None => applied.changes.push(change),
+            }
+        }
+        applied.source = source;
+        applied.at = Instant::now();
+        applied.start_time = start_time;
+        applied.exec_from = None;
+    }
+
+    /// 进程执行 exec（名称变化）后更新记录中的名称；已有的配置来源注明是应用在 exec 之前的程序上
+    pub fn process_exec(&mut self, pid: u32, old_name: &str, new_name: &str) {
+        for entry in self.entries.iter_mut().filter(|e| e.pid == pid) {
+            entry.name = new_name.to_string();
+        }
+        if let Some(applied) = self.applied_by.get_mut(&pid) {
+            if applied.exec_from.is_none() {
+                applied.exec_from = Some(old_name.to_string());
+                self.applied_by_dirty = true;
+            }
+        }
+    }
+
+    fn touch(&mut self, pid: u32) {
+        if !self.touched.contains(&pid) {
+            self.touched.push(pid);
+        }
+    }
+
+    /// 取出自上次调用以来被修改或还原的进程
+    pub fn take_touched(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.touched)
+    }
+
+    /// 取出自上次调用以来应用的亲和性变化
+    pub fn take_affinity_diffs(&mut self) -> Vec<AffinityDiff> {
+        std::mem::take(&mut self.affinity_diffs)
+    }
+
+    /// 取出自上次调用以来失败的设置项及错误
+    pub fn take_failures(&mut self) -> Vec<(SchedChange, String)> {
+        std::mem::take(&mut self.failures)
+    }
+
+    /// 调整录制
+    pub fn recorder(&self) -> &ScriptRecorder {
+        &self.recorder
+    }
+
+    pub fn recorder_mut(&mut self) -> &mut ScriptRecorder {
+        &mut self.recorder
+    }
+
+    /// 进程当前配置的来源
+    pub fn applied_by(&self, pid: u32) -> Option<&AppliedBy> {
+        self.applied_by.get(&pid)
+    }
+
+    /// 丢弃已退出、PID 被复用或设置已被外部修改的进程的来源记录
+    /// `lookup` 应返回刚完整刷新过的进程信息
+    pub fn forget_modified<'a>(&mut self, lookup: impl Fn(u32) -> Option<&'a ProcessInfo>, logical_cores: usize) {
+        let count = self.applied_by.len();
+        self.applied_by.retain(|&pid, applied| {
+            let Some(process) = lookup(pid) else {
+                return false;
+            };
+            let same_process = match read_process_start_time(pid) {
+                Some(start_time) => applied.start_time.is_none() || applied.start_time == Some(start_time),
+                None => false,
+            };
+            same_process && applied.matches(process, logical_cores)
+        });
+        if self.applied_by.len() != count {
+            self.applied_by_dirty = true;
+        }
+    }
+
+    /// 记录一次更改前的原始值（同一进程的同类设置只保留第一次）
+    pub fn record(&mut self, pid: u32, previous: &[SchedChange]) {
+        if previous.is_empty() {
+            return;
+        }
+        let start_time = read_process_start_time(pid);
+
+        // PID 被复用时旧记录已无意义
+        self.entries.retain(|e| e.pid != pid || e.start_time == start_time);
+
+        let index = match self.entries.iter().position(|e| e.pid == pid) {
+            Some(index) => index,
+            None => {
+                self.entries.push(SessionEntry {
+                    pid,
+                    name: std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                        .map(|s| s.trim().to_string())
+                        .unwrap_or_default(),
+                    start_time,
+                    originals: Vec::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+
+        let entry = &mut self.entries[index];
+        for change in previous {
+            if !entry.originals.iter().any(|c| discriminant(c) == discriminant(change)) {
+                entry.originals.push(change.clone());
+            }
+        }
+    }
+
+    /// 已修改的进程记录
+    pub fn entries(&self) -> &[SessionEntry] {
+        &self.entries
+    }
+
+    /// 是否没有任何更改
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 还原本次会话的所有更改并清空记录，跳过已退出的进程
+    pub fn revert_all_session_changes(&mut self) -> RevertSummary {
+        self.restore_all(|entry| rollback(entry.pid as i32, &entry.originals))
+    }
+
+    /// 把本次会话修改过的所有进程恢复为默认调度设置（见 [`default_changes`]）并清空记录，跳过已退出的进程
+    /// 某项设置失败（如无权修改的进程）时继续恢复其余各项
+    pub fn restore_all_defaults(&mut self, logical_cores: usize) -> RevertSummary {
+        self.restore_all(|entry| apply_each(entry.pid as i32, &default_changes(&entry.originals, logical_cores)))
+    }
+
+    /// 对每个仍在运行的记录执行 `restore`（返回已恢复项和错误）并清空记录，受保护的进程不修改
+    fn restore_all(&mut self, restore: impl Fn(&SessionEntry) -> (Vec<&'static str>, Vec<String>)) -> RevertSummary {
+        let mut summary = RevertSummary::default();
+        let mut touched = Vec::new();
+        self.applied_by.clear();
+        self.applied_by_dirty = true;
+
+        for entry in self.entries.drain(..) {
+            if !entry.is_alive() {
+                summary.skipped.push(entry.pid);
+                continue;
+            }
+            if let Some(reason) = protected_reason(entry.pid, read_process_security(entry.pid).1) {
+                summary.protected.push((entry.pid, reason));
+                continue;
+            }
+            let (_, errors) = restore(&entry);
+            touched.push(entry.pid);
+            if errors.is_empty() {
+                summary.reverted += 1;
+            } else {
+                summary.errors.extend(
+                    errors
+                        .into_iter()
+                        .map(|e| format!("{} ({}): {}", entry.name, entry.pid, e)),
+                );
+            }
+        }
+        for pid in touched {
+            self.touch(pid);
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(changes: Vec<SchedChange>) -> AppliedBy {
+        AppliedBy {
+            source: ApplySource::Rule("factorio".to_string()),
+            at: Instant::now(),
+            start_time: None,
+            changes,
+            exec_from: None,
+        }
+    }
+
+    #[test]
+    fn test_applied_by_describe() {
+        let entry = applied(Vec::new());
+        assert_eq!(entry.describe(entry.at), "规则 'factorio' · 刚刚");
+        assert_eq!(entry.describe(entry.at + Duration::from_secs(150)), "规则 'factorio' · 2 分钟前");
+        assert_eq!(format_ago(Duration::from_secs(7200)), "2 小时前");
+        assert_eq!(ApplySource::Manual.describe(), "手动");
+    }
+
+    #[test]
+    fn test_process_exec() {
+        let mut journal = SessionJournal::new();
+        let pid = std::process::id();
+        journal.record(pid, &[SchedChange::Nice(0)]);
+        journal.applied_by.insert(pid, applied(Vec::new()));
+
+        journal.process_exec(pid, "launcher", "game");
+        journal.process_exec(pid, "game", "game-bin");
+        assert_eq!(journal.entries()[0].name, "game-bin");
+        let entry = journal.applied_by(pid).unwrap();
+        assert_eq!(entry.exec_from.as_deref(), Some("launcher"));
+        assert_eq!(entry.describe(entry.at), "规则 'factorio' · 刚刚 · 应用于 exec 前的 launcher");
+    }
+
+    #[test]
+    fn test_merge_applied_by() {
+        let mut journal = SessionJournal::new();
+        let rule = ApplySource::Rule("factorio".to_string());
+        journal.merge_applied_by(100, Some(1), rule, vec![SchedChange::Nice(-5), SchedChange::Affinity(vec![0, 1])]);
+        journal.merge_applied_by(100, Some(1), ApplySource::Manual, vec![SchedChange::Nice(0)]);
+        let entry = journal.applied_by(100).unwrap();
+        assert_eq!(entry.source, ApplySource::Manual);
+        assert_eq!(entry.changes, vec![SchedChange::Nice(0), SchedChange::Affinity(vec![0, 1])]);
+
+        // PID 被复用：替换整条记录
+        journal.merge_applied_by(100, Some(2), ApplySource::Manual, vec![SchedChange::OomScoreAdj(100)]);
+        assert_eq!(journal.applied_by(100).unwrap().changes, vec![SchedChange::OomScoreAdj(100)]);
+    }
+
+    #[test]
+    fn test_applied_by_persistence() {
+        let dir = std::env::temp_dir().join(format!("hexin-applied-by-{}", std::process::id()));
+        let path = dir.join("applied_by.toml");
+        let pid = std::process::id();
+        let start_time = read_process_start_time(pid);
+
+        let mut journal = SessionJournal::new();
+        let changes = vec![
+            SchedChange::Policy { policy: SchedulePolicy::Batch, priority: 0, reset_on_fork: false },
+            SchedChange::Affinity(vec![0, 2]),
+        ];
+        journal.merge_applied_by(pid, start_time, ApplySource::Preset("游戏".to_string()), changes.clone());
+        journal.applied_by.get_mut(&pid).unwrap().exec_from = Some("launcher".to_string());
+        // PID 复用后的记录和没有启动时间的记录都不恢复
+        journal.merge_applied_by(u32::MAX - 1, Some(1), ApplySource::Manual, vec![SchedChange::Nice(1)]);
+        journal.merge_applied_by(1, None, ApplySource::Manual, vec![SchedChange::Nice(1)]);
+        journal.save_applied_by_to(&path);
+
+        let mut restored = SessionJournal::new();
+        restored.load_applied_by_from(&path);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let entry = restored.applied_by(pid).unwrap();
+        assert_eq!(entry.source, ApplySource::Preset("游戏".to_string()));
+        assert_eq!(entry.changes, changes);
+        assert_eq!(entry.start_time, start_time);
+        assert_eq!(entry.describe(Instant::now()), "预设 '游戏' · 刚刚 · 应用于 exec 前的 launcher");
+        assert!(restored.applied_by(u32::MAX - 1).is_none());
+        assert!(restored.applied_by(1).is_none());
+    }
+
+    #[test]
+    fn test_applied_by_matches() {
+        let mut process = crate::system::process::tests::process(100, "factorio", 0.0, 0);
+        process.priority = -5;
+        process.affinity = vec![0, 1, 2, 3];
+
+        let entry = applied(vec![
+            SchedChange::Policy { policy: SchedulePolicy::Other, priority: 0, reset_on_fork: false },
+            SchedChange::Nice(-5),
+            SchedChange::Affinity(vec![3, 2, 1, 0, 9]),
+            SchedChange::OomScoreAdj(100),
+        ]);
+        assert!(entry.matches(&process, 8));
+
+        // 外部修改了 nice 值
+        process.priority = 0;
+        assert!(!entry.matches(&process, 8));
+        process.priority = -5;
+
+        // 外部修改了亲和性
+        process.affinity = vec![0, 1];
+        assert!(!entry.matches(&process, 8));
+    }
+
+    #[test]
+    fn test_record_keeps_first_original() {
+        let mut journal = SessionJournal::new();
+        let pid = std::process::id();
+        journal.record(pid, &[SchedChange::Nice(0)]);
+        journal.record(pid, &[SchedChange::Nice(5), SchedChange::OomScoreAdj(0)]);
+        journal.record(pid, &[]);
+
+        assert_eq!(journal.entries().len(), 1);
+        assert_eq!(
+            journal.entries()[0].originals,
+            vec![SchedChange::Nice(0), SchedChange::OomScoreAdj(0)]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_revert_all_session_changes() {
+        use crate::system::{apply_changes, get_oom_score_adj};
+        use std::process::Command;
+
+        let mut alive = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut exited = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut journal = SessionJournal::new();
+        let original = get_oom_score_adj(alive.id() as i32).unwrap();
+
+        for child in [&alive, &exited] {
+            let pid = child.id();
+            let report = apply_changes(pid as i32, &[SchedChange::OomScoreAdj(original + 100)], 1);
+            assert!(report.is_success());
+            journal.record(pid, &report.previous);
+        }
+        let _ = exited.kill();
+        let _ = exited.wait();
+
+        let summary = journal.revert_all_session_changes();
+        assert_eq!(summary.reverted, 1);
+        assert_eq!(summary.skipped, vec![exited.id()]);
+        assert!(summary.errors.is_empty());
+        assert!(journal.is_empty());
+        assert_eq!(get_oom_score_adj(alive.id() as i32), Ok(original));
+
+        let _ = alive.kill();
+        let _ = alive.wait();
+    }
+
+    #[test]
+    fn test_default_changes() {
+        use crate::system::{IoClass, IoPriority};
+
+        let io = SchedChange::IoPriority(IoPriority { class: IoClass::BestEffort, level: 4 });
+        let originals = [SchedChange::Affinity(vec![2, 3]), SchedChange::OomScoreAdj(100), SchedChange::Nice(5), io.clone()];
+        assert_eq!(
+            default_changes(&originals, 4),
+            vec![
+                SchedChange::Policy { policy: SchedulePolicy::Other, priority: 0, reset_on_fork: false },
+                SchedChange::Nice(5),
+                SchedChange::OomScoreAdj(100),
+                io,
+                SchedChange::Affinity(vec![2, 3]),
+            ]
+        );
+        let batch = SchedChange::Policy { policy: SchedulePolicy::Batch, priority: 0, reset_on_fork: false };
+        assert_eq!(
+            default_changes(std::slice::from_ref(&batch), 2),
+            vec![batch, SchedChange::Nice(0), SchedChange::Affinity(vec![0, 1])]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_restore_all_defaults() {
+        use crate::system::{get_process_affinity, get_scheduler_info, set_process_affinity, set_scheduler};
+        use std::process::Command;
+
+        let logical_cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let unrestorable = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut exited = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id() as i32;
+        let allowed = get_process_affinity(pid, logical_cores);
+
+        // hexin 只修改过 OOM 调整；调度策略和亲和性是外部修改的，恢复为默认值
+        let mut journal = SessionJournal::new();
+        journal.record(child.id(), &[SchedChange::OomScoreAdj(0)]);
+        set_scheduler(pid, SchedulePolicy::Batch, 0, false).unwrap();
+        set_process_affinity(pid, &allowed[..1]).unwrap();
+        crate::system::set_oom_score_adj(pid, 300).unwrap();
+
+        // 无法恢复的设置项（空亲和性）不影响其余各项，错误被汇总
+        journal.record(unrestorable.id(), &[SchedChange::Affinity(Vec::new())]);
+        set_scheduler(unrestorable.id() as i32, SchedulePolicy::Idle, 0, false).unwrap();
+
+        // 受保护的进程（这里是测试进程自身，相当于 hexin 自身）不修改
+        let own_pid = std::process::id();
+        let own_policy = get_scheduler_info(own_pid as i32);
+        let own_affinity = get_process_affinity(own_pid as i32, logical_cores);
+        journal.record(own_pid, &[SchedChange::Affinity(Vec::new())]);
+
+        journal.record(exited.id(), &[SchedChange::Nice(0)]);
+        let _ = exited.kill();
+        let _ = exited.wait();
+
+        let summary = journal.restore_all_defaults(logical_cores);
+        assert_eq!(summary.reverted, 1);
+        assert_eq!(summary.skipped, vec![exited.id()]);
+        assert_eq!(summary.protected, vec![(own_pid, "hexin 自身")]);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].contains(&unrestorable.id().to_string()) && summary.errors[0].contains("亲和性"));
+        assert!(journal.is_empty());
+        assert_eq!(get_scheduler_info(own_pid as i32), own_policy);
+        assert_eq!(get_process_affinity(own_pid as i32, logical_cores), own_affinity);
+
+        assert_eq!(get_scheduler_info(pid).0, SchedulePolicy::Other);
+        assert_eq!(get_process_affinity(pid, logical_cores), allowed);
+        assert_eq!(crate::system::get_oom_score_adj(pid), Ok(0));
+        assert_eq!(get_scheduler_info(unrestorable.id() as i32).0, SchedulePolicy::Other);
+
+        for mut c in [child, unrestorable] {
+            let _ = c.kill();
+            let _ = c.wait();
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//! 延迟统计 (delay accounting)
+//! 通过 generic netlink 的 TASKSTATS 接口读取进程等待 CPU、块 I/O 和换入的累计延迟
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+/// generic netlink 控制器的固定 family ID
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const TASKSTATS_GENL_NAME: &str = "TASKSTATS";
+const TASKSTATS_CMD_GET: u8 = 1;
+const TASKSTATS_CMD_ATTR_TGID: u16 = 2;
+const TASKSTATS_TYPE_STATS: u16 = 3;
+const TASKSTATS_TYPE_AGGR_TGID: u16 = 5;
+
+const NLMSG_ERROR: u16 = 2;
+const NLM_F_REQUEST: u16 = 1;
+const NLMSG_HDRLEN: usize = 16;
+const GENL_HDRLEN: usize = 4;
+/// nlattr 类型字段中嵌套 / 字节序标志之外的部分
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+/// struct taskstats 中延迟字段的偏移（自第 1 版起固定）
+const CPU_COUNT_OFFSET: usize = 16;
+const TASKSTATS_MIN_LEN: usize = 64;
+
+/// 进程（线程组）的累计延迟统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DelayStats {
+    /// 在运行队列上等待的次数
+    pub cpu_count: u64,
+    /// 等待 CPU 的累计时间 (ns)
+    pub cpu_delay_ns: u64,
+    /// 等待块 I/O 的次数
+    pub blkio_count: u64,
+    /// 等待块 I/O 的累计时间 (ns)
+    pub blkio_delay_ns: u64,
+    /// 等待换入的次数
+    pub swapin_count: u64,
+    /// 等待换入的累计时间 (ns)
+    pub swapin_delay_ns: u64,
+}
+
+impl DelayStats {
+    /// 平均每次等待 CPU 的时间 (ns)
+    pub fn avg_cpu_delay_ns(&self) -> Option<f64> {
+        (self.cpu_count > 0).then(|| self.cpu_delay_ns as f64 / self.cpu_count as f64)
+    }
+
+    /// 所有计数均为 0（内核未开启延迟统计时的表现）
+    pub fn is_empty(&self) -> bool {
+        *self == DelayStats::default()
+    }
+}
+
+/// 一次延迟统计读取结果
+#[derive(Debug, Clone, Copy)]
+pub struct DelaySample {
+    /// 累计统计
+    pub stats: DelayStats,
+    /// 自上次读取以来等待 CPU 的时间占比 (%)
+    pub cpu_wait_percent: Option<f32>,
+    /// 读取时内核是否开启了延迟统计（见 `is_delay_accounting_enabled`）
+    pub delay_accounting: Option<bool>,
+}
+
+/// 内核是否开启了延迟统计（读取 kernel.task_delayacct，旧内核无此项时返回 None）
+pub fn is_delay_accounting_enabled() -> Option<bool> {
+    std::fs::read_to_string("/proc/sys/kernel/task_delayacct")
+        .ok()
+        .map(|s| s.trim() != "0")
+}
+
+/// 追加一个 netlink 属性（按 4 字节对齐）
+fn put_attr(buf: &mut Vec<u8>, attr_type: u16, payload: &[u8]) {
+    let len = 4 + payload.len();
+    buf.extend_from_slice(&(len as u16).to_ne_bytes());
+    buf.extend_from_slice(&attr_type.to_ne_bytes());
+    buf.extend_from_slice(payload);
+    buf.resize(buf.len() + (4 - len % 4) % 4, 0);
+}
+
+/// 构造 generic netlink 请求消息
+fn build_message(family: u16, seq: u32, cmd: u8, attrs: &[u8]) -> Vec<u8> {
+    let len = NLMSG_HDRLEN + GENL_HDRLEN + attrs.len();
+    let mut msg = Vec::with_capacity(len);
+    msg.extend_from_slice(&(len as u32).to_ne_bytes());
+    msg.extend_from_slice(&family.to_ne_bytes());
+    msg.extend_from_slice(&NLM_F_REQUEST.to_ne_bytes());
+    msg.extend_from_slice(&seq.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(&[cmd, 1, 0, 0]);
+    msg.extend_from_slice(attrs);
+    msg
+}
+
+/// 解析属性列表 (类型, 负载)
+fn parse_attrs(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attrs = Vec::new();
+    while data.len() >= 4 {
+        let len = u16::from_ne_bytes([data[0], data[1]]) as usize;
+        let attr_type = u16::from_ne_bytes([data[2], data[3]]) & NLA_TYPE_MASK;
+        if len < 4 || len > data.len() {
+            break;
+        }
+        attrs.push((attr_type, &data[4..len]));
+        let aligned = (len + 3) & !3;
+        data = &data[aligned.min(data.len())..];
+    }
+    attrs
+}
+
+/// 校验回复消息并返回其中的属性区，NLMSG_ERROR 转为错误
+fn reply_attrs(reply: &[u8]) -> Result<&[u8], String> {
+    if reply.len() < NLMSG_HDRLEN {
+        return Err("netlink 回复过短".to_string());
+    }
+    let len = (u32::from_ne_bytes([reply[0], reply[1], reply[2], reply[3]]) as usize).min(reply.len());
+    let msg_type = u16::from_ne_bytes([reply[4], reply[5]]);
+    if msg_type == NLMSG_ERROR {
+        let errno = reply
+            .get(NLMSG_HDRLEN..NLMSG_HDRLEN + 4)
+            .map(|b| -i32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .unwrap_or(0);
+        let err = std::io::Error::from_raw_os_error(errno);
+        return Err(match errno {
+            libc::EPERM | libc::EACCES => format!("{} (部分内核需要 CAP_NET_ADMIN)", err),
+            libc::ESRCH => "进程已退出".to_string(),
+            _ => err.to_string(),
+        });
+    }
+    reply
+        .get(NLMSG_HDRLEN + GENL_HDRLEN..len)
+        .ok_or_else(|| "netlink 回复过短".to_string())
+}
+
+/// 从 CTRL_CMD_GETFAMILY 回复中解析 family ID
+fn parse_family_id(reply: &[u8]) -> Result<u16, String> {
+    parse_attrs(reply_attrs(reply)?)
+        .into_iter()
+        .find(|(attr_type, payload)| *attr_type == CTRL_ATTR_FAMILY_ID && payload.len() >= 2)
+        .map(|(_, payload)| u16::from_ne_bytes([payload[0], payload[1]]))
+        .ok_or_else(|| "内核未提供 TASKSTATS 接口".to_string())
+}
+
+/// 从 struct taskstats 中读取延迟字段
+fn parse_taskstats_struct(data: &[u8]) -> Option<DelayStats> {
+    if data.len() < TASKSTATS_MIN_LEN {
+        return None;
+    }
+    let field = |index: usize| {
+        let start = CPU_COUNT_OFFSET + index * 8;
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[start..start + 8]);
+        u64::from_ne_bytes(bytes)
+    };
+    Some(DelayStats {
+        cpu_count: field(0),
+        cpu_delay_ns: field(1),
+        blkio_count: field(2),
+        blkio_delay_ns: field(3),
+        swapin_count: field(4),
+        swapin_delay_ns: field(5),
+    })
+}
+
+/// 从 TASKSTATS_CMD_GET (TGID) 回复中解析延迟统计
+fn parse_taskstats_reply(reply: &[u8]) -> Result<DelayStats, String> {
+    parse_attrs(reply_attrs(reply)?)
+        .into_iter()
+        .filter(|(attr_type, _)| *attr_type == TASKSTATS_TYPE_AGGR_TGID)
+        .flat_map(|(_, nested)| parse_attrs(nested))
+        .find(|(attr_type, _)| *attr_type == TASKSTATS_TYPE_STATS)
+        .and_then(|(_, payload)| parse_taskstats_struct(payload))
+        .ok_or_else(|| "TASKSTATS 回复中没有统计数据".to_string())
+}
+
+/// TASKSTATS netlink 客户端（连接后复用套接字）
+#[cfg(target_os = "linux")]
+pub struct TaskstatsClient {
+    fd: std::os::fd::OwnedFd,
+    family_id: u16,
+    seq: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl TaskstatsClient {
+    /// 打开 netlink 套接字并解析 TASKSTATS family ID
+    pub fn connect() -> Result<Self, String> {
+        use std::os::fd::FromRawFd;
+
+        let raw = unsafe {
+            libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_GENERIC)
+        };
+        if raw < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(format!("创建 netlink 套接字失败: {}", err));
+        }
+        let fd = unsafe { std::os::fd::OwnedFd::from_raw_fd(raw) };
+
+        unsafe {
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            let result = libc::bind(
+                raw,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+            if result != 0 {
+                let err = std::io::Error::last_os_error();
+                return Err(format!("绑定 netlink 套接字失败: {}", err));
+            }
+
+            // 避免内核无响应时阻塞界面
+            let timeout = libc::timeval { tv_sec: 1, tv_usec: 0 };
+            libc::setsockopt(
+                raw,
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+            );
+        }
+
+        let mut client = TaskstatsClient { fd, family_id: GENL_ID_CTRL, seq: 0 };
+        let mut name = TASKSTATS_GENL_NAME.as_bytes().to_vec();
+        name.push(0);
+        let mut attrs = Vec::new();
+        put_attr(&mut attrs, CTRL_ATTR_FAMILY_NAME, &name);
+        let reply = client.request(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, &attrs)?;
+        client.family_id = parse_family_id(&reply)?;
+        Ok(client)
+    }
+
+    /// 查询线程组（进程）的累计延迟统计
+    pub fn query_tgid(&mut self, tgid: u32) -> Result<DelayStats, String> {
+        let mut attrs = Vec::new();
+        put_attr(&mut attrs, TASKSTATS_CMD_ATTR_TGID, &tgid.to_ne_bytes());
+        let reply = self.request(self.family_id, TASKSTATS_CMD_GET, &attrs)?;
+        parse_taskstats_reply(&reply)
+    }
+
+    /// 发送请求并等待序号匹配的回复
+    fn request(&mut self, family: u16, cmd: u8, attrs: &[u8]) -> Result<Vec<u8>, String> {
+        use std::os::fd::AsRawFd;
+
+        self.seq = self.seq.wrapping_add(1);
+        let msg = build_message(family, self.seq, cmd, attrs);
+        let fd = self.fd.as_raw_fd();
+
+        let sent = unsafe { libc::send(fd, msg.as_ptr() as *const libc::c_void, msg.len(), 0) };
+        if sent < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(format!("发送 netlink 请求失败: {}", err));
+        }
+
+        let mut buf = vec![0u8; 8192];
+        loop {
+            let received = unsafe { libc::recv(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+            if received < 0 {
+                let err = std::io::Error::last_os_error();
+                return Err(format!("接收 netlink 回复失败: {}", err));
+            }
+            let reply = &buf[..received as usize];
+            // 跳过之前超时请求遗留的回复
+            if reply.len() >= 12 && u32::from_ne_bytes([reply[8], reply[9], reply[10], reply[11]]) == self.seq {
+                return Ok(reply.to_vec());
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub struct TaskstatsClient;
+
+#[cfg(not(target_os = "linux"))]
+impl TaskstatsClient {
+    pub fn connect() -> Result<Self, String> {
+        Err("延迟统计仅支持 Linux".to_string())
+    }
+
+    pub fn query_tgid(&mut self, _tgid: u32) -> Result<DelayStats, String> {
+        Err("延迟统计仅支持 Linux".to_string())
+    }
+}
+
+/// 延迟统计读取器：缓存 netlink 连接，并计算两次读取之间的 CPU 等待占比
+#[derive(Default)]
+pub struct DelayAccountingReader {
+    client: Option<TaskstatsClient>,
+    /// 各进程上一次读取的 (时间, 统计)
+    last: HashMap<u32, (Instant, DelayStats)>,
+}
+
+impl DelayAccountingReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只保留仍在显示的进程的上一次读取
+    pub fn retain(&mut self, mut keep: impl FnMut(u32) -> bool) {
+        self.last.retain(|&pid, _| keep(pid));
+    }
+
+    /// 读取进程延迟统计
+    pub fn read(&mut self, pid: u32) -> Result<DelaySample, String> {
+        let client = match &mut self.client {
+            Some(client) => client,
+            None => self.client.insert(TaskstatsClient::connect()?),
+        };
+        let stats = match client.query_tgid(pid) {
+            Ok(stats) => stats,
+            Err(e) => {
+                // 套接字出错时下次重新连接
+                self.client = None;
+                self.last.remove(&pid);
+                return Err(e);
+            }
+        };
+
+        let sample = self.record(pid, Instant::now(), stats);
+        Ok(DelaySample { delay_accounting: is_delay_accounting_enabled(), ..sample })
+    }
+
+    /// 记录一次读取，并与该进程上一次读取比较得出 CPU 等待占比
+    fn record(&mut self, pid: u32, now: Instant, stats: DelayStats) -> DelaySample {
+        let cpu_wait_percent = self.last.insert(pid, (now, stats)).and_then(|(last_time, last_stats)| {
+            let elapsed_ns = now.duration_since(last_time).as_nanos() as f64;
+            let delta = stats.cpu_delay_ns.checked_sub(last_stats.cpu_delay_ns)?;
+            (elapsed_ns > 0.0).then(|| (delta as f64 / elapsed_ns * 100.0) as f32)
+        });
+        DelaySample { stats, cpu_wait_percent, delay_accounting: None }
+    }
+}
+
+/// 延迟统计工作线程的请求
+enum DelayRequest {
+    /// 读取进程延迟统计
+    Read(u32),
+    /// 丢弃进程的上一次读取
+    Forget(u32),
+}
+
+/// 在后台线程读取延迟统计，避免 netlink 请求阻塞界面；丢弃时线程退出
+pub struct DelayAccountingWorker {
+    tx: Sender<DelayRequest>,
+    rx: Receiver<(u32, Result<DelaySample, String>)>,
+    /// 已请求过的进程
+    known: HashSet<u32>,
+    /// 已发出请求、尚未收到结果的进程
+    in_flight: HashSet<u32>,
+}
+
+impl DelayAccountingWorker {
+    /// 启动工作线程
+    pub fn spawn() -> Self {
+        let (tx, request_rx) = channel();
+        let (result_tx, rx) = channel();
+        thread::spawn(move || {
+            let mut reader = DelayAccountingReader::new();
+            for request in request_rx {
+                match request {
+                    DelayRequest::Read(pid) => {
+                        if result_tx.send((pid, reader.read(pid))).is_err() {
+                            break;
+                        }
+                    }
+                    DelayRequest::Forget(pid) => reader.retain(|p| p != pid),
+                }
+            }
+        });
+        Self {
+            tx,
+            rx,
+            known: HashSet::new(),
+            in_flight: HashSet::new(),
+        }
+    }
+
+    /// 请求读取进程延迟统计（同一进程的请求未完成时忽略）
+    pub fn request(&mut self, pid: u32) {
+        if self.in_flight.insert(pid) {
+            self.known.insert(pid);
+            // 工作线程只在接收端销毁后退出，忽略发送失败
+            let _ = self.tx.send(DelayRequest::Read(pid));
+        }
+    }
+
+    /// 取出已完成的读取结果
+    pub fn poll(&mut self) -> Vec<(u32, Result<DelaySample, String>)> {
+        let results: Vec<_> = self.rx.try_iter().collect();
+        for (pid, _) in &results {
+            self.in_flight.remove(pid);
+        }
+        results
+    }
+
+    /// 只保留仍在显示的进程的上一次读取
+    pub fn retain(&mut self, mut keep: impl FnMut(u32) -> bool) {
+        let tx = &self.tx;
+        self.known.retain(|&pid| {
+            let kept = keep(pid);
+            if !kept {
+                let _ = tx.send(DelayRequest::Forget(pid));
+            }
+            kept
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(msg_type: u16, seq: u32, attrs: &[u8]) -> Vec<u8> {
+        let mut msg = build_message(msg_type, seq, 0, attrs);
+        msg[6..8].copy_from_slice(&0u16.to_ne_bytes());
+        msg
+    }
+
+    #[test]
+    fn test_parse_family_id() {
+        let mut attrs = Vec::new();
+        put_attr(&mut attrs, CTRL_ATTR_FAMILY_NAME, b"TASKSTATS\0");
+        put_attr(&mut attrs, CTRL_ATTR_FAMILY_ID, &0x17u16.to_ne_bytes());
+        assert_eq!(parse_family_id(&reply(GENL_ID_CTRL, 1, &attrs)), Ok(0x17));
+
+        let mut error = reply(NLMSG_ERROR, 1, &[]);
+        error.truncate(NLMSG_HDRLEN);
+        error.extend_from_slice(&(-libc::ENOENT).to_ne_bytes());
+        assert!(parse_family_id(&error).is_err());
+    }
+
+    #[test]
+    fn test_parse_taskstats_reply() {
+        let mut stats = vec![0u8; 328];
+        for (i, value) in [10u64, 5_000, 2, 700, 0, 0].iter().enumerate() {
+            let start = CPU_COUNT_OFFSET + i * 8;
+            stats[start..start + 8].copy_from_slice(&value.to_ne_bytes());
+        }
+        let mut nested = Vec::new();
+        put_attr(&mut nested, 2, &1234u32.to_ne_bytes());
+        put_attr(&mut nested, TASKSTATS_TYPE_STATS, &stats);
+        let mut attrs = Vec::new();
+        // 嵌套属性可能带 NLA_F_NESTED 标志
+        put_attr(&mut attrs, TASKSTATS_TYPE_AGGR_TGID | 0x8000, &nested);
+
+        let parsed = parse_taskstats_reply(&reply(0x17, 2, &attrs)).unwrap();
+        assert_eq!(parsed.cpu_count, 10);
+        assert_eq!(parsed.cpu_delay_ns, 5_000);
+        assert_eq!(parsed.blkio_delay_ns, 700);
+        assert_eq!(parsed.avg_cpu_delay_ns(), Some(500.0));
+        assert!(!parsed.is_empty());
+
+        assert!(parse_taskstats_reply(&reply(0x17, 2, &[])).is_err());
+    }
+
+    #[test]
+    fn test_cpu_wait_per_pid() {
+        let stats = |cpu_delay_ns| DelayStats { cpu_delay_ns, ..Default::default() };
+        let start = Instant::now();
+        let at = |ms| start + std::time::Duration::from_millis(ms);
+        let mut reader = DelayAccountingReader::new();
+
+        // 两个详情窗口交替读取，各自与自己的上一次读取比较
+        assert_eq!(reader.record(10, at(0), stats(0)).cpu_wait_percent, None);
+        assert_eq!(reader.record(20, at(100), stats(0)).cpu_wait_percent, None);
+        assert_eq!(reader.record(10, at(1000), stats(250_000_000)).cpu_wait_percent, Some(25.0));
+        assert_eq!(reader.record(20, at(1100), stats(500_000_000)).cpu_wait_percent, Some(50.0));
+
+        // 关闭窗口后丢弃基准
+        reader.retain(|pid| pid == 20);
+        assert_eq!(reader.record(10, at(2000), stats(300_000_000)).cpu_wait_percent, None);
+    }
+
+    #[test]
+    fn test_worker_exited_process() {
+        let mut worker = DelayAccountingWorker::spawn();
+        // PID 上限之外的进程不存在（没有 netlink 权限时连接失败，同样是错误）
+        let pid = u32::MAX - 1;
+        worker.request(pid);
+        worker.request(pid);
+        let mut results = Vec::new();
+        for _ in 0..300 {
+            results.extend(worker.poll());
+            if !results.is_empty() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, pid);
+        assert!(results[0].1.is_err());
+    }
+}
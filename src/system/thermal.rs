@@ -0,0 +1,623 @@
+//! 封装温度监控与降温自动化
+//! 封装温度持续高于上限时应用"降温"动作，持续低于下限后自动撤销
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::{
+    apply_changes, read_process_start_time, rollback, wildcard_match, ApplySource, ProcessInfo, SchedChange,
+    SessionJournal,
+};
+
+/// 可选的 EPP (energy_performance_preference) 取值
+pub const EPP_VALUES: &[&str] = &["performance", "balance_performance", "balance_power", "power"];
+
+/// 保留的降温日志条数
+const THERMAL_LOG_LENGTH: usize = 20;
+/// 没有找到温度传感器或传感器文件消失后，重新查找的间隔
+const SENSOR_REDISCOVER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 降温动作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThermalAction {
+    /// 目标进程名匹配模式（不区分大小写，支持 * 通配符，留空表示不调整进程）
+    #[serde(default)]
+    pub process_pattern: String,
+    /// 目标进程改为的 nice 值
+    #[serde(default)]
+    pub nice: Option<i32>,
+    /// 所有 cpufreq 策略改为的 EPP
+    #[serde(default)]
+    pub epp: Option<String>,
+}
+
+impl Default for ThermalAction {
+    fn default() -> Self {
+        Self {
+            process_pattern: String::new(),
+            nice: Some(0),
+            epp: None,
+        }
+    }
+}
+
+/// 降温自动化配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThermalConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 上限温度 (°C)
+    pub high_celsius: f32,
+    /// 高于上限持续多少秒后应用降温动作
+    pub high_secs: u64,
+    /// 下限温度 (°C)
+    pub low_celsius: f32,
+    /// 低于下限持续多少秒后撤销
+    pub low_secs: u64,
+    /// 降温动作
+    pub action: ThermalAction,
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_celsius: 90.0,
+            high_secs: 10,
+            low_celsius: 80.0,
+            low_secs: 30,
+            action: ThermalAction::default(),
+        }
+    }
+}
+
+/// 降温状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThermalState {
+    /// 温度正常
+    Normal,
+    /// 温度高于上限，开始于 `since`（秒）
+    Heating { since: f64 },
+    /// 降温动作已生效
+    Throttled,
+    /// 降温动作生效中，温度低于下限，开始于 `since`（秒）
+    Cooling { since: f64 },
+}
+
+/// 需要执行的状态转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThermalTransition {
+    /// 应用降温动作
+    Engage,
+    /// 撤销降温动作
+    Release,
+}
+
+/// 带滞回的降温状态机
+#[derive(Debug, Clone)]
+pub struct ThermalGuard {
+    state: ThermalState,
+}
+
+impl ThermalGuard {
+    pub fn new() -> Self {
+        Self { state: ThermalState::Normal }
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> ThermalState {
+        self.state
+    }
+
+    /// 降温动作是否生效中
+    pub fn is_throttled(&self) -> bool {
+        matches!(self.state, ThermalState::Throttled | ThermalState::Cooling { .. })
+    }
+
+    /// 输入一个温度样本（`now` 为单调递增的秒数），返回需要执行的转换
+    pub fn update(&mut self, config: &ThermalConfig, celsius: f32, now: f64) -> Option<ThermalTransition> {
+        let hot = celsius >= config.high_celsius;
+        let cool = celsius <= config.low_celsius;
+        match self.state {
+            ThermalState::Normal if hot => {
+                self.state = ThermalState::Heating { since: now };
+                self.update(config, celsius, now)
+            }
+            ThermalState::Heating { .. } if !hot => {
+                self.state = ThermalState::Normal;
+                None
+            }
+            ThermalState::Heating { since } if now - since >= config.high_secs as f64 => {
+                self.state = ThermalState::Throttled;
+                Some(ThermalTransition::Engage)
+            }
+            ThermalState::Throttled if cool => {
+                self.state = ThermalState::Cooling { since: now };
+                self.update(config, celsius, now)
+            }
+            ThermalState::Cooling { .. } if !cool => {
+                self.state = ThermalState::Throttled;
+                None
+            }
+            ThermalState::Cooling { since } if now - since >= config.low_secs as f64 => {
+                self.state = ThermalState::Normal;
+                Some(ThermalTransition::Release)
+            }
+            _ => None,
+        }
+    }
+
+    /// 回到初始状态（不执行撤销）
+    pub fn reset(&mut self) {
+        self.state = ThermalState::Normal;
+    }
+}
+
+impl Default for ThermalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 降温自动化：状态机 + 已应用动作的记录
+pub struct ThermalController {
+    guard: ThermalGuard,
+    /// 降温时修改过的进程、启动时间及其原值
+    applied: Vec<(u32, Option<u64>, Vec<SchedChange>)>,
+    /// 降温前各 cpufreq 策略的 EPP
+    previous_epp: Vec<(PathBuf, String)>,
+    /// 最近一次读取的封装温度
+    last_temperature: Option<f32>,
+    /// 封装温度传感器
+    sensors: TemperatureSensors,
+    /// 最近的动作日志
+    log: Vec<String>,
+}
+
+impl ThermalController {
+    pub fn new() -> Self {
+        Self {
+            guard: ThermalGuard::new(),
+            applied: Vec::new(),
+            previous_epp: Vec::new(),
+            last_temperature: None,
+            sensors: TemperatureSensors::default(),
+            log: Vec::new(),
+        }
+    }
+
+    /// 最近一次读取的封装温度
+    pub fn last_temperature(&self) -> Option<f32> {
+        self.last_temperature
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> ThermalState {
+        self.guard.state()
+    }
+
+    /// 最近的动作日志（旧的在前）
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    /// 读取温度并推进状态机，需要时应用或撤销降温动作（在刷新循环中调用）
+    pub fn tick(
+        &mut self,
+        config: &ThermalConfig,
+        processes: &[&ProcessInfo],
+        session: &mut SessionJournal,
+        logical_cores: usize,
+        now: f64,
+    ) {
+        if !config.enabled {
+            // 关闭时撤销仍在生效的动作，且不再遍历 hwmon
+            self.restore_all();
+            self.last_temperature = None;
+            return;
+        }
+        self.last_temperature = self.sensors.read_package();
+        let Some(celsius) = self.last_temperature else {
+            return;
+        };
+        match self.guard.update(config, celsius, now) {
+            Some(ThermalTransition::Engage) => self.engage(config, celsius, processes, session, logical_cores),
+            Some(ThermalTransition::Release) => self.release(),
+            None => {}
+        }
+    }
+
+    /// 应用降温动作
+    fn engage(
+        &mut self,
+        config: &ThermalConfig,
+        celsius: f32,
+        processes: &[&ProcessInfo],
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        self.push_log(format!("封装温度 {:.0}°C，应用降温动作", celsius));
+        let action = &config.action;
+        if let Some(nice) = action.nice.filter(|_| !action.process_pattern.is_empty()) {
+            let pattern = action.process_pattern.to_lowercase();
+            for process in processes
+                .iter()
+                .filter(|p| wildcard_match(&pattern, &p.name.to_lowercase()))
+            {
+                let changes = [SchedChange::Nice(nice)];
+                let report = apply_changes(process.pid as i32, &changes, logical_cores);
+                session.record_application(process.pid, &changes, &report, ApplySource::Thermal);
+                self.push_log(format!("{} ({}) nice → {}: {}", process.name, process.pid, nice, report.summary()));
+                if report.is_success() {
+                    let start_time = read_process_start_time(process.pid);
+                    self.applied.push((process.pid, start_time, report.previous));
+                }
+            }
+        }
+        if let Some(epp) = &action.epp {
+            self.previous_epp = read_epp_all();
+            match set_epp_all(epp) {
+                Ok(()) => self.push_log(format!("EPP → {}", epp)),
+                Err(e) => self.push_log(e),
+            }
+        }
+    }
+
+    /// 撤销仍在生效的降温动作并回到初始状态（关闭自动化或退出程序时调用）
+    pub fn restore_all(&mut self) {
+        if self.guard.is_throttled() {
+            self.release();
+        }
+        self.guard.reset();
+    }
+
+    /// 撤销降温动作
+    fn release(&mut self) {
+        let temperature = self
+            .last_temperature
+            .map_or_else(|| "未知".to_string(), |t| format!("{:.0}°C", t));
+        self.push_log(format!("封装温度 {}，撤销降温动作", temperature));
+        for (pid, start_time, previous) in std::mem::take(&mut self.applied) {
+            // 进程已退出或 PID 被复用时无需恢复
+            if start_time.is_none() || read_process_start_time(pid) != start_time {
+                continue;
+            }
+            let (_, errors) = rollback(pid as i32, &previous);
+            if !errors.is_empty() {
+                self.push_log(format!("恢复进程 {} 失败: {}", pid, errors.join("; ")));
+            }
+        }
+        for (path, value) in std::mem::take(&mut self.previous_epp) {
+            if let Err(e) = write_epp(&path, &value) {
+                self.push_log(e);
+            }
+        }
+    }
+
+    fn push_log(&mut self, entry: String) {
+        tracing::info!("降温自动化: {}", entry);
+        let time = chrono::Local::now().format("%H:%M:%S");
+        self.log.push(format!("{} {}", time, entry));
+        if self.log.len() > THERMAL_LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+}
+
+impl Default for ThermalController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// hwmon 传感器是否是 CPU 封装温度（Intel coretemp 的 Package，AMD k10temp / zenpower 的 Tctl / Tdie）
+pub fn is_package_sensor(hwmon_name: &str, label: &str) -> bool {
+    match hwmon_name {
+        "coretemp" => label.starts_with("Package id"),
+        "k10temp" | "zenpower" => label == "Tctl" || label == "Tdie",
+        _ => false,
+    }
+}
+
+/// 解析 coretemp 的单核传感器标签 "Core N"，返回物理核心 ID
+pub fn parse_core_label(label: &str) -> Option<usize> {
+    label.strip_prefix("Core ")?.trim().parse().ok()
+}
+
+/// 解析 coretemp 的封装传感器标签 "Package id N"
+fn parse_package_label(label: &str) -> Option<usize> {
+    label.strip_prefix("Package id ")?.trim().parse().ok()
+}
+
+/// 解析 sysfs 中的毫摄氏度
+pub fn parse_millidegrees(content: &str) -> Option<f32> {
+    content.trim().parse::<i64>().ok().map(|m| m as f32 / 1000.0)
+}
+
+/// 温度传感器文件：查找一次后每次只读取找到的文件，而不是每次探测 tempN_label
+/// 没有找到传感器或有文件消失（驱动重新加载）时，每隔 `SENSOR_REDISCOVER_INTERVAL` 重新查找
+#[derive(Debug, Clone, Default)]
+pub struct TemperatureSensors {
+    /// coretemp 单核传感器 ((封装 ID, 核心 ID), tempN_input)
+    cores: Vec<((usize, usize), PathBuf)>,
+    /// 封装温度文件：hwmon 的 Package / Tctl / Tdie，其次 x86_pkg_temp 温区
+    package: Option<PathBuf>,
+    /// 上次查找的时间（None 表示尚未查找）
+    discovered_at: Option<Instant>,
+    /// 读取时有文件消失
+    lost: bool,
+}
+
+impl TemperatureSensors {
+    /// 查找 /sys/class 下的温度传感器
+    pub fn discover() -> Self {
+        Self::discover_in(Path::new("/sys/class"))
+    }
+
+    /// 在 `root`（/sys/class）下查找：hwmon 设备中列出的 tempN_label，以及 thermal 温区
+    fn discover_in(root: &Path) -> Self {
+        let read = |path: PathBuf| fs::read_to_string(path).ok();
+        let list = |dir: &Path| -> Vec<PathBuf> {
+            let mut paths: Vec<PathBuf> = fs::read_dir(dir).into_iter().flatten().flatten().map(|e| e.path()).collect();
+            paths.sort();
+            paths
+        };
+        let mut sensors = Self { discovered_at: Some(Instant::now()), ..Self::default() };
+
+        for dir in list(&root.join("hwmon")) {
+            let Some(name) = read(dir.join("name")) else {
+                continue;
+            };
+            let mut labels: Vec<(u32, String)> = list(&dir)
+                .into_iter()
+                .filter_map(|path| {
+                    let file = path.file_name()?.to_str()?;
+                    let index = file.strip_prefix("temp")?.strip_suffix("_label")?.parse().ok()?;
+                    Some((index, read(path)?.trim().to_string()))
+                })
+                .collect();
+            labels.sort_by_key(|(index, _)| *index);
+
+            let mut package_id = 0;
+            let mut cores = Vec::new();
+            for (index, label) in labels {
+                let input = dir.join(format!("temp{}_input", index));
+                if sensors.package.is_none() && is_package_sensor(name.trim(), &label) {
+                    sensors.package = Some(input.clone());
+                }
+                if name.trim() != "coretemp" {
+                    continue;
+                }
+                if let Some(id) = parse_package_label(&label) {
+                    package_id = id;
+                } else if let Some(core_id) = parse_core_label(&label) {
+                    cores.push((core_id, input));
+                }
+            }
+            sensors.cores.extend(cores.into_iter().map(|(core_id, input)| ((package_id, core_id), input)));
+        }
+
+        if sensors.package.is_none() {
+            sensors.package = list(&root.join("thermal"))
+                .into_iter()
+                .find(|dir| read(dir.join("type")).is_some_and(|t| t.trim() == "x86_pkg_temp"))
+                .map(|dir| dir.join("temp"));
+        }
+        sensors
+    }
+
+    /// 是否没有任何传感器
+    pub fn is_empty(&self) -> bool {
+        self.cores.is_empty() && self.package.is_none()
+    }
+
+    /// 首次使用时查找；没有传感器或有文件消失时按间隔重新查找
+    fn refresh(&mut self) {
+        let due = match self.discovered_at {
+            None => true,
+            Some(at) => (self.lost || self.is_empty()) && at.elapsed() >= SENSOR_REDISCOVER_INTERVAL,
+        };
+        if due {
+            *self = Self::discover();
+        }
+    }
+
+    /// 读取一个传感器文件，文件消失时记下以便重新查找
+    fn read_file(path: &Path, lost: &mut bool) -> Option<f32> {
+        match fs::read_to_string(path) {
+            Ok(content) => parse_millidegrees(&content),
+            Err(_) => {
+                *lost = true;
+                None
+            }
+        }
+    }
+
+    /// 读取各物理核心温度 (°C)，按 (封装 ID, 核心 ID) 索引
+    /// 只有 Intel coretemp 提供单核传感器（每个封装一个 hwmon 设备），其他平台返回空表
+    pub fn read_cores(&mut self) -> HashMap<(usize, usize), f32> {
+        self.refresh();
+        let mut lost = false;
+        let temperatures = self
+            .cores
+            .iter()
+            .filter_map(|(key, path)| Some((*key, Self::read_file(path, &mut lost)?)))
+            .collect();
+        self.lost |= lost;
+        temperatures
+    }
+
+    /// 读取 CPU 封装温度 (°C)
+    pub fn read_package(&mut self) -> Option<f32> {
+        self.refresh();
+        let path = self.package.clone()?;
+        Self::read_file(&path, &mut self.lost)
+    }
+}
+
+/// 各 cpufreq 策略的 EPP 文件
+fn epp_paths() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir("/sys/devices/system/cpu/cpufreq") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path().join("energy_performance_preference"))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// 读取所有 cpufreq 策略的 EPP
+pub fn read_epp_all() -> Vec<(PathBuf, String)> {
+    epp_paths()
+        .into_iter()
+        .filter_map(|path| {
+            let value = fs::read_to_string(&path).ok()?;
+            Some((path, value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn write_epp(path: &Path, value: &str) -> Result<(), String> {
+    fs::write(path, value).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+/// 将所有 cpufreq 策略的 EPP 设为指定值
+pub fn set_epp_all(value: &str) -> Result<(), String> {
+    let paths = epp_paths();
+    if paths.is_empty() {
+        return Err("当前 cpufreq 驱动不支持 EPP".to_string());
+    }
+    paths.iter().try_for_each(|path| write_epp(path, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ThermalConfig {
+        ThermalConfig {
+            enabled: true,
+            high_celsius: 90.0,
+            high_secs: 5,
+            low_celsius: 80.0,
+            low_secs: 10,
+            action: ThermalAction::default(),
+        }
+    }
+
+    /// 按 1 秒间隔输入温度序列，返回发生的转换及其时间
+    fn run(guard: &mut ThermalGuard, series: &[f32], start: f64) -> Vec<(f64, ThermalTransition)> {
+        let config = config();
+        series
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &t)| {
+                let now = start + i as f64;
+                guard.update(&config, t, now).map(|tr| (now, tr))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_engage_after_sustained_heat() {
+        let mut guard = ThermalGuard::new();
+        let transitions = run(&mut guard, &[85.0, 91.0, 92.0, 93.0, 92.0, 91.0, 95.0, 95.0], 0.0);
+        assert_eq!(transitions, vec![(6.0, ThermalTransition::Engage)]);
+        assert_eq!(guard.state(), ThermalState::Throttled);
+    }
+
+    #[test]
+    fn test_short_spike_does_not_engage() {
+        let mut guard = ThermalGuard::new();
+        let transitions = run(&mut guard, &[95.0, 95.0, 95.0, 89.0, 95.0, 95.0, 95.0, 95.0], 0.0);
+        assert!(transitions.is_empty());
+        assert_eq!(guard.state(), ThermalState::Heating { since: 4.0 });
+    }
+
+    #[test]
+    fn test_hysteresis_and_release() {
+        let mut guard = ThermalGuard::new();
+        run(&mut guard, &[95.0; 6], 0.0);
+        assert!(guard.is_throttled());
+
+        // 介于上下限之间不撤销
+        assert!(run(&mut guard, &[85.0; 30], 10.0).is_empty());
+        assert_eq!(guard.state(), ThermalState::Throttled);
+
+        // 低于下限但未持续足够时间后回升，计时重新开始
+        assert!(run(&mut guard, &[79.0, 79.0, 79.0, 81.0], 40.0).is_empty());
+        assert_eq!(guard.state(), ThermalState::Throttled);
+
+        let transitions = run(&mut guard, &[75.0; 12], 50.0);
+        assert_eq!(transitions, vec![(60.0, ThermalTransition::Release)]);
+        assert_eq!(guard.state(), ThermalState::Normal);
+    }
+
+    #[test]
+    fn test_zero_duration_engages_immediately() {
+        let mut guard = ThermalGuard::new();
+        let config = ThermalConfig { high_secs: 0, ..config() };
+        assert_eq!(guard.update(&config, 90.0, 0.0), Some(ThermalTransition::Engage));
+    }
+
+    #[test]
+    fn test_package_sensor() {
+        assert!(is_package_sensor("coretemp", "Package id 0"));
+        assert!(!is_package_sensor("coretemp", "Core 0"));
+        assert!(is_package_sensor("k10temp", "Tctl"));
+        assert!(!is_package_sensor("nvme", "Composite"));
+        assert_eq!(parse_millidegrees("54250\n"), Some(54.25));
+        assert_eq!(parse_millidegrees("n/a"), None);
+        assert_eq!(parse_core_label("Core 12\n"), Some(12));
+        assert_eq!(parse_core_label("Package id 0"), None);
+        assert_eq!(parse_package_label("Package id 1\n"), Some(1));
+    }
+
+    #[test]
+    fn test_discover_sensors() {
+        let root = std::env::temp_dir().join(format!("hexin-sensors-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("hwmon/hwmon0/name", "nvme\n");
+        write("hwmon/hwmon0/temp1_label", "Composite\n");
+        write("hwmon/hwmon0/temp1_input", "38000\n");
+        write("hwmon/hwmon1/name", "coretemp\n");
+        write("hwmon/hwmon1/temp1_label", "Package id 0\n");
+        write("hwmon/hwmon1/temp1_input", "61000\n");
+        write("hwmon/hwmon1/temp2_label", "Core 0\n");
+        write("hwmon/hwmon1/temp2_input", "55000\n");
+        write("hwmon/hwmon1/temp10_label", "Core 8\n");
+        write("hwmon/hwmon1/temp10_input", "57500\n");
+
+        let mut sensors = TemperatureSensors::discover_in(&root);
+        assert_eq!(sensors.read_package(), Some(61.0));
+        let cores = sensors.read_cores();
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores.get(&(0, 8)), Some(&57.5));
+        assert!(!sensors.lost);
+
+        // 传感器文件消失：读不到该核心，并记下需要重新查找
+        fs::remove_file(root.join("hwmon/hwmon1/temp10_input")).unwrap();
+        assert_eq!(sensors.read_cores().len(), 1);
+        assert!(sensors.lost);
+
+        // 没有 hwmon 封装传感器时使用 x86_pkg_temp 温区
+        fs::remove_dir_all(root.join("hwmon")).unwrap();
+        write("thermal/thermal_zone0/type", "acpitz\n");
+        write("thermal/thermal_zone0/temp", "40000\n");
+        write("thermal/thermal_zone1/type", "x86_pkg_temp\n");
+        write("thermal/thermal_zone1/temp", "66000\n");
+        let mut sensors = TemperatureSensors::discover_in(&root);
+        assert_eq!(sensors.read_package(), Some(66.0));
+        assert!(sensors.read_cores().is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,275 @@
+//! 定时规则 - 在指定的星期和时间段内启用一组自动规则
+//! 按本地墙上时间判断（夏令时切换和时区变化由系统时区处理），
+//! 每次刷新都根据当前时间重新计算，因此 hexin 未运行时错过的启用会在下次启动时补上
+
+use chrono::{Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 本地时间：星期几和当天的分钟数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    /// 1 = 周一 … 7 = 周日
+    pub weekday: u8,
+    /// 自午夜起的分钟数 (0-1439)
+    pub minutes: u16,
+}
+
+impl LocalTime {
+    /// 当前本地时间
+    pub fn now() -> Self {
+        let now = Local::now();
+        Self {
+            weekday: now.weekday().number_from_monday() as u8,
+            minutes: (now.hour() * 60 + now.minute()) as u16,
+        }
+    }
+}
+
+/// 星期名称（1 = 周一）
+pub const WEEKDAY_NAMES: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+
+/// 解析 "HH:MM" 为自午夜起的分钟数
+pub fn parse_hhmm(s: &str) -> Option<u16> {
+    let (hour, minute) = s.trim().split_once(':')?;
+    let hour: u16 = hour.parse().ok()?;
+    let minute: u16 = minute.parse().ok()?;
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+/// 一条定时规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSchedule {
+    /// 名称
+    pub name: String,
+    /// 是否启用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 生效的星期（1 = 周一 … 7 = 周日），为空表示每天
+    #[serde(default)]
+    pub days: Vec<u8>,
+    /// 开始时间 "HH:MM"
+    pub start: String,
+    /// 结束时间 "HH:MM"，早于开始时间表示跨过午夜；与开始时间相同表示全天
+    pub end: String,
+    /// 时间段内启用的自动规则名称
+    #[serde(default)]
+    pub rules: Vec<String>,
+}
+
+impl Default for RuleSchedule {
+    fn default() -> Self {
+        Self {
+            name: "新定时".to_string(),
+            enabled: true,
+            days: vec![1, 2, 3, 4, 5],
+            start: "09:00".to_string(),
+            end: "18:00".to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl RuleSchedule {
+    /// 检查时间格式和星期
+    pub fn validate(&self) -> Result<(), String> {
+        if parse_hhmm(&self.start).is_none() {
+            return Err(format!("开始时间 '{}' 无效，应为 HH:MM", self.start));
+        }
+        if parse_hhmm(&self.end).is_none() {
+            return Err(format!("结束时间 '{}' 无效，应为 HH:MM", self.end));
+        }
+        if let Some(day) = self.days.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(format!("星期 {} 无效，应为 1-7", day));
+        }
+        Ok(())
+    }
+
+    fn on_day(&self, weekday: u8) -> bool {
+        self.days.is_empty() || self.days.contains(&weekday)
+    }
+
+    /// 给定时间是否处于时间段内（跨午夜的时间段属于开始的那一天），无效的定时始终不生效
+    pub fn is_active(&self, now: LocalTime) -> bool {
+        let (Some(start), Some(end)) = (parse_hhmm(&self.start), parse_hhmm(&self.end)) else {
+            return false;
+        };
+        let yesterday = if now.weekday == 1 { 7 } else { now.weekday - 1 };
+        if start < end {
+            self.on_day(now.weekday) && (start..end).contains(&now.minutes)
+        } else if start > end {
+            (self.on_day(now.weekday) && now.minutes >= start) || (self.on_day(yesterday) && now.minutes < end)
+        } else {
+            self.on_day(now.weekday)
+        }
+    }
+}
+
+/// 受定时控制、当前不在任何所属时间段内的规则
+/// 一条规则出现在多个定时中时，任一定时生效即启用；停用或无效的定时不控制规则
+pub fn inactive_scheduled_rules(schedules: &[RuleSchedule], now: LocalTime) -> HashSet<String> {
+    let controlling = || schedules.iter().filter(|s| s.enabled && s.validate().is_ok());
+    let active: HashSet<&String> = controlling().filter(|s| s.is_active(now)).flat_map(|s| &s.rules).collect();
+    controlling()
+        .flat_map(|s| &s.rules)
+        .filter(|rule| !active.contains(rule))
+        .cloned()
+        .collect()
+}
+
+/// 免打扰时间段：每天在此时间段内不弹出后台提示（仍写入日志）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// 开始时间 "HH:MM"
+    pub start: String,
+    /// 结束时间 "HH:MM"，早于开始时间表示跨过午夜；与开始时间相同表示全天
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self { start: "23:00".to_string(), end: "07:00".to_string() }
+    }
+}
+
+impl QuietHours {
+    /// 检查时间格式
+    pub fn validate(&self) -> Result<(), String> {
+        if parse_hhmm(&self.start).is_none() {
+            return Err(format!("开始时间 '{}' 无效，应为 HH:MM", self.start));
+        }
+        if parse_hhmm(&self.end).is_none() {
+            return Err(format!("结束时间 '{}' 无效，应为 HH:MM", self.end));
+        }
+        Ok(())
+    }
+
+    /// 给定时间是否处于时间段内，无效的时间段始终不生效
+    pub fn contains(&self, now: LocalTime) -> bool {
+        let (Some(start), Some(end)) = (parse_hhmm(&self.start), parse_hhmm(&self.end)) else {
+            return false;
+        };
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => (start..end).contains(&now.minutes),
+            std::cmp::Ordering::Greater => now.minutes >= start || now.minutes < end,
+            std::cmp::Ordering::Equal => true,
+        }
+    }
+}
+
+/// 给定时间是否处于任一免打扰时间段内
+pub fn in_quiet_hours(ranges: &[QuietHours], now: LocalTime) -> bool {
+    ranges.iter().any(|range| range.contains(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(days: &[u8], start: &str, end: &str, rules: &[&str]) -> RuleSchedule {
+        RuleSchedule {
+            name: "test".to_string(),
+            enabled: true,
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+            rules: rules.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    fn at(weekday: u8, hhmm: &str) -> LocalTime {
+        LocalTime { weekday, minutes: parse_hhmm(hhmm).unwrap() }
+    }
+
+    #[test]
+    fn test_parse_hhmm() {
+        assert_eq!(parse_hhmm("09:30"), Some(570));
+        assert_eq!(parse_hhmm(" 0:00 "), Some(0));
+        assert_eq!(parse_hhmm("23:59"), Some(1439));
+        assert_eq!(parse_hhmm("24:00"), None);
+        assert_eq!(parse_hhmm("9"), None);
+        assert_eq!(parse_hhmm("09:60"), None);
+    }
+
+    #[test]
+    fn test_schedule_windows() {
+        // 工作日 09:00-18:00
+        let work = schedule(&[1, 2, 3, 4, 5], "09:00", "18:00", &["build-work"]);
+        assert!(work.is_active(at(1, "09:00")));
+        assert!(work.is_active(at(5, "17:59")));
+        assert!(!work.is_active(at(5, "18:00")));
+        assert!(!work.is_active(at(6, "12:00")));
+
+        // 周五晚上跨到周六早上
+        let night = schedule(&[5], "22:00", "06:00", &["build-night"]);
+        assert!(night.is_active(at(5, "23:00")));
+        assert!(night.is_active(at(6, "05:59")));
+        assert!(!night.is_active(at(6, "22:30")));
+        assert!(!night.is_active(at(5, "05:00")));
+
+        // 周日开始的时间段延续到周一凌晨
+        let sunday_night = schedule(&[7], "22:00", "06:00", &[]);
+        assert!(sunday_night.is_active(at(1, "01:00")));
+
+        // 开始等于结束表示全天；没有星期表示每天
+        assert!(schedule(&[], "00:00", "00:00", &[]).is_active(at(3, "12:34")));
+        assert!(!schedule(&[2], "08:00", "08:00", &[]).is_active(at(3, "12:34")));
+
+        assert!(schedule(&[8], "09:00", "18:00", &[]).validate().is_err());
+        assert!(!schedule(&[], "9", "18:00", &[]).is_active(at(1, "12:00")));
+    }
+
+    #[test]
+    fn test_inactive_scheduled_rules() {
+        let mut disabled = schedule(&[], "00:00", "00:00", &["manual"]);
+        disabled.enabled = false;
+        let schedules = vec![
+            schedule(&[1, 2, 3, 4, 5], "09:00", "18:00", &["build-work", "shared"]),
+            schedule(&[1, 2, 3, 4, 5], "18:00", "09:00", &["build-night", "shared"]),
+            disabled,
+        ];
+
+        let inactive = inactive_scheduled_rules(&schedules, at(2, "10:00"));
+        assert_eq!(inactive, HashSet::from(["build-night".to_string()]));
+
+        let inactive = inactive_scheduled_rules(&schedules, at(2, "20:00"));
+        assert_eq!(inactive, HashSet::from(["build-work".to_string()]));
+
+        // 周六白天两个定时都不生效，未受任何启用定时控制的规则不受影响
+        let inactive = inactive_scheduled_rules(&schedules, at(6, "12:00"));
+        assert_eq!(inactive.len(), 3);
+        assert!(!inactive.contains("manual"));
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let quiet = |start: &str, end: &str| QuietHours { start: start.to_string(), end: end.to_string() };
+
+        // 跨过午夜：23:00 到次日 07:00，不区分星期
+        let night = quiet("23:00", "07:00");
+        assert!(night.contains(at(3, "23:00")));
+        assert!(night.contains(at(4, "00:30")));
+        assert!(night.contains(at(7, "06:59")));
+        assert!(!night.contains(at(3, "07:00")));
+        assert!(!night.contains(at(3, "22:59")));
+
+        // 当天的时间段不包含结束时刻
+        let lunch = quiet("12:00", "13:00");
+        assert!(lunch.contains(at(1, "12:30")));
+        assert!(!lunch.contains(at(1, "13:00")));
+        assert!(!lunch.contains(at(1, "11:59")));
+
+        // 开始等于结束表示全天，无效时间始终不生效
+        assert!(quiet("08:00", "08:00").contains(at(2, "03:00")));
+        assert!(!quiet("25:00", "07:00").contains(at(2, "03:00")));
+        assert!(quiet("25:00", "07:00").validate().is_err());
+
+        assert!(in_quiet_hours(&[lunch.clone(), night], at(5, "02:00")));
+        assert!(!in_quiet_hours(&[lunch], at(5, "02:00")));
+        assert!(!in_quiet_hours(&[], at(5, "02:00")));
+    }
+}
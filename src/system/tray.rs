@@ -0,0 +1,157 @@
+//! 系统托盘图标 - 关闭窗口时可以改为最小化，托盘图标用于恢复窗口和退出
+//! 定期任务（自动规则、前台加速、监视列表）随界面刷新运行，窗口不能隐藏，否则界面停止刷新
+//! 托盘图标需要 `tray` feature（StatusNotifierItem：KDE、带 AppIndicator 扩展的 GNOME 和大多数 Wayland 桌面）
+
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+/// 托盘发出的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayEvent {
+    /// 恢复窗口
+    Show,
+    /// 退出 hexin
+    Quit,
+    /// 托盘服务已结束（窗口应恢复，之后关闭窗口直接退出）
+    Closed,
+}
+
+/// 托盘菜单项（名称, 事件），托盘线程发送被点击项的序号；左键点击图标等同第一项
+pub const TRAY_MENU: [(&str, TrayEvent); 2] = [("显示窗口", TrayEvent::Show), ("退出", TrayEvent::Quit)];
+
+/// 窗口关闭请求的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    /// 正常关闭（正在退出、没有托盘，或用户选择了关闭即退出）
+    Close,
+    /// 取消关闭并询问用户（首次关闭）
+    Prompt,
+    /// 取消关闭并最小化窗口
+    Minimize,
+}
+
+/// 决定窗口关闭请求的处理方式
+pub fn close_action(quitting: bool, has_tray: bool, confirmed: bool, close_to_tray: bool) -> CloseAction {
+    if quitting || !has_tray {
+        CloseAction::Close
+    } else if !confirmed {
+        CloseAction::Prompt
+    } else if close_to_tray {
+        CloseAction::Minimize
+    } else {
+        CloseAction::Close
+    }
+}
+
+/// 显示托盘图标，返回托盘事件的接收端；`wake` 在事件到达时调用（用于唤醒最小化的窗口处理事件）
+#[cfg(feature = "tray")]
+pub fn spawn_tray(wake: impl Fn() + Send + 'static) -> Result<Receiver<usize>, String> {
+    sni::spawn(Box::new(wake))
+}
+
+#[cfg(not(feature = "tray"))]
+pub fn spawn_tray(_wake: impl Fn() + Send + 'static) -> Result<Receiver<usize>, String> {
+    Err("此版本编译时未启用托盘图标 (tray feature)".to_string())
+}
+
+/// 取出一个待处理的托盘事件
+pub fn poll_tray(events: &Receiver<usize>) -> Option<TrayEvent> {
+    match events.try_recv() {
+        Ok(index) => TRAY_MENU.get(index).map(|(_, event)| *event),
+        Err(TryRecvError::Empty) => None,
+        Err(TryRecvError::Disconnected) => Some(TrayEvent::Closed),
+    }
+}
+
+/// 通过 D-Bus StatusNotifierItem 显示托盘图标
+#[cfg(feature = "tray")]
+mod sni {
+    use ksni::blocking::TrayMethods;
+    use ksni::menu::StandardItem;
+    use ksni::MenuItem;
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    use super::TRAY_MENU;
+
+    struct HexinTray {
+        sender: Sender<usize>,
+        wake: Box<dyn Fn() + Send>,
+    }
+
+    impl HexinTray {
+        fn send(&self, index: usize) {
+            if self.sender.send(index).is_ok() {
+                (self.wake)();
+            }
+        }
+    }
+
+    impl ksni::Tray for HexinTray {
+        fn id(&self) -> String {
+            "hexin".to_string()
+        }
+
+        fn title(&self) -> String {
+            "hexin".to_string()
+        }
+
+        fn icon_name(&self) -> String {
+            "utilities-system-monitor".to_string()
+        }
+
+        fn activate(&mut self, _x: i32, _y: i32) {
+            self.send(0);
+        }
+
+        fn menu(&self) -> Vec<MenuItem<Self>> {
+            TRAY_MENU
+                .iter()
+                .enumerate()
+                .map(|(index, (label, _))| {
+                    StandardItem {
+                        label: label.to_string(),
+                        activate: Box::new(move |tray: &mut Self| tray.send(index)),
+                        ..Default::default()
+                    }
+                    .into()
+                })
+                .collect()
+        }
+    }
+
+    /// 托盘服务在后台线程运行到进程退出
+    pub fn spawn(wake: Box<dyn Fn() + Send>) -> Result<Receiver<usize>, String> {
+        let (sender, receiver) = channel();
+        HexinTray { sender, wake }.spawn().map_err(|e| format!("无法显示托盘图标: {}", e))?;
+        Ok(receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_poll_tray() {
+        let (sender, receiver) = channel();
+        assert_eq!(poll_tray(&receiver), None);
+        sender.send(1).unwrap();
+        sender.send(9).unwrap();
+        assert_eq!(poll_tray(&receiver), Some(TrayEvent::Quit));
+        // 未知的序号忽略
+        assert_eq!(poll_tray(&receiver), None);
+        drop(sender);
+        assert_eq!(poll_tray(&receiver), Some(TrayEvent::Closed));
+    }
+
+    #[test]
+    fn test_close_action() {
+        // 没有托盘或正在退出：直接关闭
+        assert_eq!(close_action(false, false, false, true), CloseAction::Close);
+        assert_eq!(close_action(true, true, true, true), CloseAction::Close);
+        // 首次关闭先询问，之后按保存的选择
+        assert_eq!(close_action(false, true, false, true), CloseAction::Prompt);
+        assert_eq!(close_action(false, true, true, true), CloseAction::Minimize);
+        assert_eq!(close_action(false, true, true, false), CloseAction::Close);
+    }
+}
@@ -0,0 +1,142 @@
+//! 低于标称频率检测
+//! 有负载的核心持续运行在远低于标称频率时，通常说明触发了功耗墙 (PL1 / PPT) 或温度墙、
+//! 调速器 / EPP 偏向节能，或 cpufreq 驱动卡在低频
+//!
+//! 规则：最近连续的样本都满足"使用率高于 `min_usage_percent` 且频率比标称频率低 `below_percent`% 以上"，
+//! 并且持续至少 `duration_secs` 秒。空闲核心降频是正常的，不满足条件的样本会中断计时；
+//! 频率为 0（无法读取）的样本同样中断计时
+
+use serde::{Deserialize, Serialize};
+
+use super::{CpuCore, CpuInfo};
+use crate::utils::CpuHistory;
+
+/// 检测规则
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnderclockRule {
+    /// 是否启用
+    pub enabled: bool,
+    /// 频率低于标称频率的比例 (%)
+    pub below_percent: f32,
+    /// 持续多少秒后标记
+    pub duration_secs: u64,
+    /// 使用率高于此值 (%) 的样本才计入
+    pub min_usage_percent: f32,
+}
+
+impl Default for UnderclockRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            below_percent: 30.0,
+            duration_secs: 10,
+            min_usage_percent: 50.0,
+        }
+    }
+}
+
+/// 持续低于标称频率的核心
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnderclockedCore {
+    /// 逻辑 CPU ID
+    pub cpu_id: usize,
+    /// 标称频率 (MHz)
+    pub nominal_mhz: u64,
+    /// 低频期间的平均频率 (MHz)
+    pub average_mhz: u64,
+    /// 已持续的时长（秒）
+    pub secs: f64,
+}
+
+/// 对一个核心的频率历史（时间戳, 使用率 %, 频率 MHz，从旧到新）应用规则，
+/// 最近的样本持续满足条件时返回（持续秒数, 平均频率 MHz）
+pub fn below_nominal_run<'a, I>(samples: I, nominal_mhz: u64, rule: &UnderclockRule) -> Option<(f64, u64)>
+where
+    I: IntoIterator<Item = &'a [f64; 3]>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    if nominal_mhz == 0 {
+        return None;
+    }
+    let threshold = nominal_mhz as f64 * (1.0 - rule.below_percent as f64 / 100.0);
+    let mut samples = samples.into_iter().rev().peekable();
+    let latest = samples.peek()?[0];
+    // 从最新的样本往回累计满足条件的一段：(最早时间, 频率之和, 样本数)
+    let (earliest, sum, count) = samples
+        .take_while(|[_, usage, mhz]| *usage > rule.min_usage_percent as f64 && *mhz > 0.0 && *mhz < threshold)
+        .fold((latest, 0.0, 0usize), |(_, sum, count), [t, _, mhz]| (*t, sum + mhz, count + 1));
+    let secs = latest - earliest;
+    if count == 0 || secs < rule.duration_secs as f64 {
+        return None;
+    }
+    Some((secs, (sum / count as f64).round() as u64))
+}
+
+/// 核心的标称频率：优先使用 CPPC nominal_freq，否则使用 cpufreq 报告的基础频率
+/// 两者都没有时为 None（不能拿最低频率代替，否则永远不会判定为低频）
+pub fn nominal_mhz(cpu_info: &CpuInfo, core: &CpuCore) -> Option<u64> {
+    core.cppc
+        .map(|cppc| cppc.nominal_mhz)
+        .or(cpu_info.nominal_frequency_mhz)
+        .filter(|&mhz| mhz > 0)
+}
+
+/// 根据频率历史找出持续低于标称频率的核心
+pub fn underclocked_cores(cpu_info: &CpuInfo, history: &CpuHistory, rule: &UnderclockRule) -> Vec<UnderclockedCore> {
+    if !rule.enabled {
+        return Vec::new();
+    }
+    cpu_info
+        .cores
+        .iter()
+        .enumerate()
+        .filter_map(|(index, core)| {
+            let nominal_mhz = nominal_mhz(cpu_info, core)?;
+            let samples = history.core_frequency_samples(index)?;
+            let (secs, average_mhz) = below_nominal_run(samples, nominal_mhz, rule)?;
+            Some(UnderclockedCore { cpu_id: core.cpu_id, nominal_mhz, average_mhz, secs })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每秒一个样本
+    fn samples(points: &[(f64, u64)]) -> Vec<[f64; 3]> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(t, &(usage, mhz))| [t as f64, usage, mhz as f64])
+            .collect()
+    }
+
+    #[test]
+    fn test_below_nominal_run() {
+        let rule = UnderclockRule::default();
+        // 标称 3000 MHz，阈值为 2100 MHz
+        let stuck = samples(&[(90.0, 1200); 12]);
+        assert_eq!(below_nominal_run(&stuck, 3000, &rule), Some((11.0, 1200)));
+        // 持续时间不足
+        assert_eq!(below_nominal_run(&stuck[..8], 3000, &rule), None);
+        // 空闲时降频不计入
+        assert_eq!(below_nominal_run(&samples(&[(10.0, 800); 12]), 3000, &rule), None);
+        // 频率正常
+        assert_eq!(below_nominal_run(&samples(&[(90.0, 2500); 12]), 3000, &rule), None);
+        // 只看最近连续的一段：中途恢复过正常频率则从恢复之后重新计时
+        let mut recovered = samples(&[(90.0, 1000); 20]);
+        recovered[14][2] = 3500.0;
+        assert_eq!(below_nominal_run(&recovered, 3000, &rule), None);
+        recovered[14][2] = 1600.0;
+        assert_eq!(below_nominal_run(&recovered, 3000, &rule), Some((19.0, 1030)));
+        // 无法读取频率的样本中断计时
+        assert_eq!(below_nominal_run(&samples(&[(90.0, 0); 12]), 3000, &rule), None);
+        assert_eq!(below_nominal_run(&stuck, 0, &rule), None);
+        assert_eq!(below_nominal_run(&[], 3000, &rule), None);
+
+        let strict = UnderclockRule { below_percent: 70.0, ..rule };
+        assert_eq!(below_nominal_run(&stuck, 3000, &strict), None);
+    }
+}
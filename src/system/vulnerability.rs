@@ -0,0 +1,133 @@
+//! CPU 漏洞缓解状态
+//! 读取 /sys/devices/system/cpu/vulnerabilities/*，并找出与 SMT 相关的状态（如 L1TF / MDS 的 "SMT vulnerable"）
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 漏洞状态目录
+const VULNERABILITIES_DIR: &str = "/sys/devices/system/cpu/vulnerabilities";
+
+/// 漏洞的缓解状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MitigationState {
+    /// 不受影响
+    NotAffected,
+    /// 已缓解
+    Mitigated,
+    /// 存在漏洞
+    Vulnerable,
+    /// 无法识别的状态
+    Unknown,
+}
+
+impl MitigationState {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            MitigationState::NotAffected => "不受影响",
+            MitigationState::Mitigated => "已缓解",
+            MitigationState::Vulnerable => "存在漏洞",
+            MitigationState::Unknown => "未知",
+        }
+    }
+}
+
+/// 单个漏洞的状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vulnerability {
+    /// 名称（文件名，如 "l1tf"）
+    pub name: String,
+    /// 内核给出的原始状态
+    pub status: String,
+    /// 缓解状态
+    pub state: MitigationState,
+    /// 与 SMT 相关的子句，如 "SMT vulnerable"、"SMT disabled"
+    pub smt: Option<String>,
+}
+
+impl Vulnerability {
+    /// 开启 SMT 时该漏洞仍未完全缓解
+    pub fn smt_vulnerable(&self) -> bool {
+        self.smt.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("SMT vulnerable"))
+    }
+}
+
+/// 解析单个漏洞文件的内容
+pub fn parse_vulnerability(name: &str, content: &str) -> Vulnerability {
+    let status = content.trim().to_string();
+    let state = if status.starts_with("Not affected") {
+        MitigationState::NotAffected
+    } else if status.starts_with("Mitigation") {
+        MitigationState::Mitigated
+    } else if status.starts_with("Vulnerable") {
+        MitigationState::Vulnerable
+    } else {
+        MitigationState::Unknown
+    };
+    // 子句以 ";" 或 "," 分隔，如 "Mitigation: PTE Inversion; VMX: conditional cache flushes, SMT vulnerable"
+    let smt = status
+        .split([';', ','])
+        .map(str::trim)
+        .find(|clause| clause.starts_with("SMT "))
+        .map(str::to_string);
+    Vulnerability { name: name.to_string(), status, state, smt }
+}
+
+/// 读取所有漏洞状态（按名称排序），旧内核没有该目录时返回空列表
+pub fn read_vulnerabilities() -> Vec<Vulnerability> {
+    read_vulnerabilities_in(Path::new(VULNERABILITIES_DIR))
+}
+
+fn read_vulnerabilities_in(dir: &Path) -> Vec<Vulnerability> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut vulnerabilities: Vec<Vulnerability> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let content = fs::read_to_string(entry.path()).ok()?;
+            Some(parse_vulnerability(&name, &content))
+        })
+        .collect();
+    vulnerabilities.sort_by(|a, b| a.name.cmp(&b.name));
+    vulnerabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vulnerability() {
+        let l1tf = parse_vulnerability("l1tf", "Mitigation: PTE Inversion; VMX: conditional cache flushes, SMT vulnerable\n");
+        assert_eq!(l1tf.state, MitigationState::Mitigated);
+        assert_eq!(l1tf.smt.as_deref(), Some("SMT vulnerable"));
+        assert!(l1tf.smt_vulnerable());
+
+        let mds = parse_vulnerability("mds", "Mitigation: Clear CPU buffers; SMT disabled\n");
+        assert_eq!(mds.smt.as_deref(), Some("SMT disabled"));
+        assert!(!mds.smt_vulnerable());
+
+        let meltdown = parse_vulnerability("meltdown", "Not affected\n");
+        assert_eq!((meltdown.state, meltdown.smt), (MitigationState::NotAffected, None));
+        assert_eq!(parse_vulnerability("spectre_v1", "Vulnerable: __user pointer sanitization").state, MitigationState::Vulnerable);
+        assert_eq!(parse_vulnerability("x", "").state, MitigationState::Unknown);
+    }
+
+    #[test]
+    fn test_read_vulnerabilities() {
+        let dir = std::env::temp_dir().join(format!("hexin-vulnerabilities-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(read_vulnerabilities_in(&dir).is_empty());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("mds"), "Mitigation: Clear CPU buffers; SMT vulnerable\n").unwrap();
+        fs::write(dir.join("l1tf"), "Not affected\n").unwrap();
+        let vulnerabilities = read_vulnerabilities_in(&dir);
+        assert_eq!(vulnerabilities.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), ["l1tf", "mds"]);
+        assert!(vulnerabilities[1].smt_vulnerable());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,23 +1,29 @@
 //! 图表组件
 
 use eframe::egui::{Color32, Ui};
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Line, LineStyle, Plot, PlotPoints, PlotUi, VLine};
 
 use crate::utils::CpuHistory;
 
+/// 休眠等间断处的标记颜色
+const GAP_MARKER_COLOR: Color32 = Color32::from_gray(110);
+
+/// 在图表中标出间断（休眠或长时间停顿）的横轴范围
+pub fn draw_gap_markers(plot_ui: &mut PlotUi, gaps: &[[f64; 2]]) {
+    for &[start, end] in gaps {
+        plot_ui.vline(VLine::new(start).color(GAP_MARKER_COLOR).style(LineStyle::dashed_loose()));
+        plot_ui.vline(VLine::new(end).color(GAP_MARKER_COLOR).style(LineStyle::dashed_loose()));
+    }
+}
+
 /// 绘制 CPU 使用率折线图
 pub fn draw_cpu_line_chart(ui: &mut Ui, history: &CpuHistory, title: &str) {
-    let data = history.plot_data();
-    if data.is_empty() {
+    let plot = history.plot_segments();
+    if plot.segments.is_empty() {
         ui.label("等待数据...");
         return;
     }
 
-    let line = Line::new(PlotPoints::new(data))
-        .color(Color32::from_rgb(100, 150, 255))
-        .width(2.0)
-        .name(title);
-
     Plot::new(title)
         .height(150.0)
         .include_y(0.0)
@@ -27,21 +33,39 @@ pub fn draw_cpu_line_chart(ui: &mut Ui, history: &CpuHistory, title: &str) {
         .allow_scroll(false)
         .show_axes([true, true])
         .show(ui, |plot_ui| {
-            plot_ui.line(line);
+            for segment in plot.segments {
+                let line = Line::new(PlotPoints::new(segment))
+                    .color(Color32::from_rgb(100, 150, 255))
+                    .width(2.0)
+                    .name(title);
+                plot_ui.line(line);
+            }
+            draw_gap_markers(plot_ui, &plot.gaps);
         });
 }
 
+/// 对比曲线的调色板
+const SERIES_PALETTE: [Color32; 8] = [
+    Color32::from_rgb(255, 100, 100),
+    Color32::from_rgb(100, 220, 100),
+    Color32::from_rgb(100, 150, 255),
+    Color32::from_rgb(255, 220, 80),
+    Color32::from_rgb(230, 100, 230),
+    Color32::from_rgb(80, 220, 220),
+    Color32::from_rgb(255, 160, 60),
+    Color32::from_rgb(200, 200, 200),
+];
+
+/// 第 index 条对比曲线的颜色：超出调色板后循环，每一轮调暗一些以便区分
+pub fn series_color(index: usize) -> Color32 {
+    let base = SERIES_PALETTE[index % SERIES_PALETTE.len()];
+    let factor = 0.65f32.powi((index / SERIES_PALETTE.len()) as i32);
+    let scale = |c: u8| (c as f32 * factor) as u8;
+    Color32::from_rgb(scale(base.r()), scale(base.g()), scale(base.b()))
+}
+
 /// 绘制多核心使用率对比图
 pub fn draw_multi_core_chart(ui: &mut Ui, history: &CpuHistory, core_ids: &[usize]) {
-    let colors = [
-        Color32::from_rgb(255, 100, 100),
-        Color32::from_rgb(100, 255, 100),
-        Color32::from_rgb(100, 100, 255),
-        Color32::from_rgb(255, 255, 100),
-        Color32::from_rgb(255, 100, 255),
-        Color32::from_rgb(100, 255, 255),
-    ];
-
     Plot::new("multi_core_chart")
         .height(200.0)
         .include_y(0.0)
@@ -50,16 +74,19 @@ pub fn draw_multi_core_chart(ui: &mut Ui, history: &CpuHistory, core_ids: &[usiz
         .allow_zoom(false)
         .legend(egui_plot::Legend::default())
         .show(ui, |plot_ui| {
+            let mut gaps = Vec::new();
             for (i, &core_id) in core_ids.iter().enumerate() {
-                let data = history.core_plot_data(core_id);
-                if !data.is_empty() {
-                    let color = colors[i % colors.len()];
-                    let line = Line::new(PlotPoints::new(data))
-                        .color(color)
+                let plot = history.core_plot_segments(core_id);
+                // 同名的曲线段在图例中合并为一项
+                for segment in plot.segments {
+                    let line = Line::new(PlotPoints::new(segment))
+                        .color(series_color(i))
                         .width(1.5)
                         .name(format!("CPU {}", core_id));
                     plot_ui.line(line);
                 }
+                gaps = plot.gaps;
             }
+            draw_gap_markers(plot_ui, &gaps);
         });
 }
@@ -0,0 +1,327 @@
+//! 手动应用前的检查与确认提示
+//! 拒绝修改 hexin 自身、init 和内核线程；亲和性排除 V-Cache 核心或包含保留核心时先提醒，
+//! 与自动规则冲突时询问如何处理
+
+use eframe::egui::{Button, Color32, Frame, Margin, RichText, Rounding, Ui};
+
+use crate::system::{
+    apply_changes, excludes_vcache, format_cpu_list, reserved_among, ApplySource, GameList, ProcessInfo,
+    RuleConflict, RuleGuard, RuleResolution, SchedChange, SchedulePreset, SessionJournal,
+};
+
+/// 手动应用前检查所需的状态
+#[derive(Clone, Default)]
+pub struct ApplyGuard {
+    /// 自动规则快照
+    pub rules: RuleGuard,
+    /// V-Cache 核心（没有 V-Cache 时为空）
+    pub vcache_cores: Vec<usize>,
+    pub game_list: GameList,
+    /// 保留给系统的核心
+    pub reserved_cores: Vec<usize>,
+}
+
+impl ApplyGuard {
+    /// 受保护的进程返回 Err，否则返回应用前需要用户确认的提醒
+    pub fn warnings(&self, process: &ProcessInfo, changes: &[SchedChange]) -> Result<Vec<String>, String> {
+        self.check(process, changes).map(|(warnings, _)| warnings)
+    }
+
+    /// 同 [`Self::warnings`]，另外返回提醒中是否包含 V-Cache 提醒（可以选择不再提示）
+    fn check(&self, process: &ProcessInfo, changes: &[SchedChange]) -> Result<(Vec<String>, bool), String> {
+        if let Some(reason) = process.protected_reason() {
+            return Err(format!("不能修改 {} ({}): {}", process.name, process.pid, reason));
+        }
+        let mut warnings = Vec::new();
+        let mut vcache = false;
+        let affinity = changes.iter().find_map(|change| match change {
+            SchedChange::Affinity(cores) => Some(cores),
+            _ => None,
+        });
+        if let Some(cores) = affinity {
+            if self.game_list.warn_vcache
+                && excludes_vcache(cores, &self.vcache_cores)
+                && self.game_list.is_game(process.pid)
+            {
+                warnings.push(format!(
+                    "亲和性不包含任何 V-Cache 核心 ({})，而 {} 看起来是游戏",
+                    format_cpu_list(&self.vcache_cores),
+                    process.name
+                ));
+                vcache = true;
+            }
+            let reserved = reserved_among(cores, &self.reserved_cores);
+            if !reserved.is_empty() {
+                warnings.push(format!("亲和性包含保留给系统的核心 {}", format_cpu_list(&reserved)));
+            }
+        }
+        Ok((warnings, vcache))
+    }
+
+    /// 检查对一组进程的应用：受保护的进程跳过，其余进程的提醒和规则冲突汇总后一起确认
+    pub fn check_group(&self, processes: &[&ProcessInfo], changes: &[SchedChange]) -> GroupCheck {
+        let mut check = GroupCheck::default();
+        for process in processes {
+            let warnings = match self.warnings(process, changes) {
+                Ok(warnings) => warnings,
+                Err(reason) => {
+                    check.skipped.push(reason);
+                    continue;
+                }
+            };
+            check.warnings.extend(warnings.into_iter().map(|w| format!("{} ({}): {}", process.name, process.pid, w)));
+            if let Some(conflict) = self.rules.conflict(process.pid, &process.name, changes) {
+                check.warnings.push(format!(
+                    "{} ({}): 规则 '{}' 设置了不同的{}，确认后此进程不再受该规则影响",
+                    process.name,
+                    process.pid,
+                    conflict.rule_name,
+                    conflict.labels.join("、")
+                ));
+                check.conflicted.push(process.pid);
+            }
+            check.pids.push(process.pid);
+        }
+        check
+    }
+}
+
+/// 对一组进程应用前的检查结果
+#[derive(Debug, Clone, Default)]
+pub struct GroupCheck {
+    /// 可以修改的进程
+    pub pids: Vec<u32>,
+    /// 跳过的受保护进程（说明）
+    pub skipped: Vec<String>,
+    /// 应用前需要确认的提醒（包括与自动规则的冲突）
+    pub warnings: Vec<String>,
+    /// 与自动规则冲突的进程，确认后让规则不再作用于它们
+    pub conflicted: Vec<u32>,
+}
+
+impl GroupCheck {
+    /// 与自动规则冲突的进程对应的规则处理（覆盖一次）
+    pub fn resolutions(&self) -> Vec<RuleResolution> {
+        self.conflicted.iter().map(|&pid| RuleResolution::Exempt(pid)).collect()
+    }
+}
+
+/// 通过检查、可以执行的手动应用
+#[derive(Debug, Clone)]
+pub struct ManualApply {
+    pub pid: u32,
+    pub changes: Vec<SchedChange>,
+    /// 从预设应用时的预设（用于记录来源）
+    pub preset: Option<SchedulePreset>,
+}
+
+impl ManualApply {
+    /// 应用并记录到会话，返回结果说明
+    pub fn run(&self, session: &mut SessionJournal, logical_cores: usize) -> Result<String, String> {
+        let report = apply_changes(self.pid as i32, &self.changes, logical_cores);
+        let source = match &self.preset {
+            Some(preset) => ApplySource::Preset(preset.name.clone()),
+            None => ApplySource::Manual,
+        };
+        session.record_application(self.pid, &self.changes, &report, source);
+        match (&self.preset, report.is_success()) {
+            (Some(preset), true) => Ok(format!("预设 '{}' 已应用到 {}", preset.name, self.pid)),
+            (Some(preset), false) => Err(format!("预设 '{}' 应用失败: {}", preset.name, report.summary())),
+            (None, true) => Ok(report.summary()),
+            (None, false) => Err(report.summary()),
+        }
+    }
+}
+
+/// 等待确认提醒的应用
+struct PendingWarning {
+    process_name: String,
+    apply: ManualApply,
+    warnings: Vec<String>,
+    /// 提醒中包含 V-Cache 提醒
+    vcache: bool,
+    /// "不再提示" V-Cache 提醒的勾选状态
+    opt_out: bool,
+}
+
+/// 手动应用的确认流程：先确认提醒，再处理与自动规则的冲突
+#[derive(Default)]
+pub struct CheckedApply {
+    warning: Option<PendingWarning>,
+    conflict: Option<ConflictPrompt>,
+    /// 尚未被取走的规则处理
+    resolutions: Vec<RuleResolution>,
+    /// 用户选择了不再提示 V-Cache 提醒，尚未被取走
+    vcache_opt_out: bool,
+}
+
+impl CheckedApply {
+    /// 开始一次手动应用：可以直接执行时返回 Ok(Some)，需要确认时返回 Ok(None)，受保护的进程返回 Err
+    pub fn request(
+        &mut self,
+        guard: &ApplyGuard,
+        process: &ProcessInfo,
+        changes: Vec<SchedChange>,
+        preset: Option<SchedulePreset>,
+    ) -> Result<Option<ManualApply>, String> {
+        let (warnings, vcache) = guard.check(process, &changes)?;
+        let apply = ManualApply { pid: process.pid, changes, preset };
+        self.conflict = None;
+        if warnings.is_empty() {
+            return Ok(self.check_conflict(guard, &process.name, apply));
+        }
+        self.warning = Some(PendingWarning { process_name: process.name.clone(), apply, warnings, vcache, opt_out: false });
+        Ok(None)
+    }
+
+    /// 与自动规则冲突时等待确认，否则返回可以执行的应用
+    fn check_conflict(&mut self, guard: &ApplyGuard, process_name: &str, apply: ManualApply) -> Option<ManualApply> {
+        let Some(conflict) = guard.rules.conflict(apply.pid, process_name, &apply.changes) else {
+            return Some(apply);
+        };
+        self.conflict = Some(ConflictPrompt {
+            pid: apply.pid,
+            process_name: process_name.to_string(),
+            changes: apply.changes,
+            preset: apply.preset,
+            conflict,
+        });
+        None
+    }
+
+    /// 绘制等待中的确认提示，用户确认后返回要执行的应用
+    pub fn ui(&mut self, ui: &mut Ui, guard: &ApplyGuard) -> Option<ManualApply> {
+        if let Some(pending) = &mut self.warning {
+            let confirmed = draw_warnings(ui, pending)?;
+            let pending = self.warning.take()?;
+            self.vcache_opt_out |= pending.opt_out;
+            return confirmed.then(|| self.check_conflict(guard, &pending.process_name, pending.apply)).flatten();
+        }
+        let choice = self.conflict.as_ref().and_then(|prompt| prompt.ui(ui))?;
+        let prompt = self.conflict.take()?;
+        let resolution = prompt.resolution(choice)?;
+        self.resolutions.push(resolution);
+        Some(ManualApply { pid: prompt.pid, changes: prompt.changes, preset: prompt.preset })
+    }
+
+    /// 取走用户在冲突提示中选择的规则处理
+    pub fn take_resolutions(&mut self) -> Vec<RuleResolution> {
+        std::mem::take(&mut self.resolutions)
+    }
+
+    /// 取走用户是否在提醒中选择了不再提示 V-Cache 提醒
+    pub fn take_vcache_opt_out(&mut self) -> bool {
+        std::mem::take(&mut self.vcache_opt_out)
+    }
+}
+
+/// 绘制应用前的提醒，返回 Some(true) 表示仍然应用，Some(false) 表示取消
+fn draw_warnings(ui: &mut Ui, pending: &mut PendingWarning) -> Option<bool> {
+    let mut choice = None;
+    Frame::none()
+        .fill(Color32::from_rgb(70, 60, 30))
+        .inner_margin(Margin::same(10.0))
+        .rounding(Rounding::same(6.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("⚠").size(14.0).color(Color32::from_rgb(255, 200, 100)));
+                ui.label(RichText::new(format!("应用到 {} ({}) 前请确认", pending.process_name, pending.apply.pid))
+                    .strong().color(Color32::from_rgb(255, 220, 150)));
+            });
+            for warning in &pending.warnings {
+                ui.label(RichText::new(warning).size(12.0).color(Color32::from_gray(200)));
+            }
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.button("仍然应用").clicked() {
+                    choice = Some(true);
+                }
+                if ui.button("取消").clicked() {
+                    choice = Some(false);
+                }
+                if pending.vcache {
+                    ui.add_space(12.0);
+                    ui.checkbox(&mut pending.opt_out, "不再提示 V-Cache")
+                        .on_hover_text("游戏列表和此开关保存在 ~/.config/hexin/games.toml");
+                }
+            });
+        });
+    ui.add_space(8.0);
+    choice
+}
+
+/// 等待确认的手动应用
+pub struct ConflictPrompt {
+    pub pid: u32,
+    pub process_name: String,
+    pub changes: Vec<SchedChange>,
+    /// 从预设应用时的预设（用于记录来源）
+    pub preset: Option<SchedulePreset>,
+    pub conflict: RuleConflict,
+}
+
+/// 用户的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictChoice {
+    /// 应用并让规则不再作用于此进程
+    OverrideOnce,
+    /// 应用并把规则改为新值
+    UpdateRule,
+    Cancel,
+}
+
+impl ConflictPrompt {
+    /// 选择对应的规则处理（取消时为 None）
+    pub fn resolution(&self, choice: ConflictChoice) -> Option<RuleResolution> {
+        match choice {
+            ConflictChoice::OverrideOnce => Some(RuleResolution::Exempt(self.pid)),
+            ConflictChoice::UpdateRule => Some(RuleResolution::UpdateRule {
+                rule_name: self.conflict.rule_name.clone(),
+                changes: self.changes.clone(),
+            }),
+            ConflictChoice::Cancel => None,
+        }
+    }
+
+    /// 绘制提示，返回用户的选择
+    pub fn ui(&self, ui: &mut Ui) -> Option<ConflictChoice> {
+        let mut choice = None;
+        Frame::none()
+            .fill(Color32::from_rgb(70, 60, 30))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("⚠").size(14.0).color(Color32::from_rgb(255, 200, 100)));
+                    ui.label(RichText::new("与自动规则冲突").strong().color(Color32::from_rgb(255, 220, 150)));
+                });
+                ui.label(RichText::new(format!(
+                    "规则 '{}' 为 {} ({}) 设置了不同的{}，规则再次生效时会覆盖这次修改",
+                    self.conflict.rule_name,
+                    self.process_name,
+                    self.pid,
+                    self.conflict.labels.join("、")
+                )).size(12.0).color(Color32::from_gray(200)));
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("覆盖一次").on_hover_text("应用，并且规则不再作用于此进程").clicked() {
+                        choice = Some(ConflictChoice::OverrideOnce);
+                    }
+                    let update = ui.add_enabled(self.conflict.editable, Button::new("更新规则"));
+                    let update = if self.conflict.editable {
+                        update.on_hover_text("应用，并把规则改为这次的值")
+                    } else {
+                        update.on_disabled_hover_text("profiles.d 中的规则只读")
+                    };
+                    if update.clicked() {
+                        choice = Some(ConflictChoice::UpdateRule);
+                    }
+                    if ui.button("取消").clicked() {
+                        choice = Some(ConflictChoice::Cancel);
+                    }
+                });
+            });
+        ui.add_space(8.0);
+        choice
+    }
+}
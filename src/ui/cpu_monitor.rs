@@ -1,28 +1,395 @@
 //! CPU 监控面板
 
 use eframe::egui::{self, Color32, Frame, Margin, RichText, Rounding, Stroke, Ui, Vec2};
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_plot::{Line, LineStyle, Plot, PlotPoints};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use crate::system::{CoreType, CpuInfo};
-use crate::utils::CpuHistory;
+use crate::system::{
+    cores_by_l3, format_cpu_list, format_mhz_range, governor_mismatch, khz_to_mhz, limit_max_frequency, mean_usage, policy_for_cpu,
+    read_cpufreq_policies, recommend_consolidation, reserved_among, set_core_governor, set_scaling_max_freq,
+    set_scaling_min_freq, smt_contention, split_idle_distribution, set_epp_all, underclocked_cores,
+    AffinityDiff, FreqLimits, MitigationState, UnderclockRule, UnderclockedCore, Vulnerability, EPP_VALUES,
+    CoreType, CpuCore, CpuInfo, CpufreqPolicy, SchedulePolicy, SchedulePreset, SmtContention, IDLE_P95_PERCENT,
+    SMT_CONTENTION_THRESHOLD, SMT_SHARED_THROUGHPUT,
+};
+use crate::ui::charts::{draw_gap_markers, draw_multi_core_chart, series_color};
+use crate::ui::theme::{CcdPalette, RESERVED_CORE_COLOR};
+use crate::ui::RangeSlider;
+use crate::utils::ring_buffer::split_at_gaps;
+use crate::utils::{CpuHistory, ProcessHistory};
+
+/// 默认动画平滑系数
+const DEFAULT_ANIMATION_SMOOTHING: f32 = 0.3;
+/// 动画值与目标差距小于此值 (%) 时停止重绘
+const ANIMATION_SETTLE_THRESHOLD: f32 = 0.5;
+/// 跟踪进程高亮框在核心间移动的时长（秒）
+const RUNNING_HIGHLIGHT_SECS: f32 = 0.25;
+/// 跟踪进程高亮颜色
+pub const RUNNING_HIGHLIGHT_COLOR: Color32 = Color32::from_rgb(80, 230, 230);
+/// 默认同时对比的最多核心数
+pub const DEFAULT_MAX_COMPARED_CORES: usize = 8;
+/// 重新读取 cpufreq 频率限制的间隔
+const CPUFREQ_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// 核心停放建议的重新计算间隔（基于整个历史窗口，变化缓慢）
+const PARKING_HINTS_INTERVAL: Duration = Duration::from_secs(5);
+/// 持续低于标称频率的核心标记颜色
+const UNDERCLOCK_COLOR: Color32 = Color32::from_rgb(255, 100, 100);
+/// 频率上限低于硬件最高频率的核心标记颜色
+const FREQ_CAPPED_COLOR: Color32 = Color32::from_rgb(255, 200, 100);
+/// 调速器角标颜色：performance / powersave / schedutil / 其他
+const GOVERNOR_PERFORMANCE_COLOR: Color32 = Color32::from_rgb(255, 150, 80);
+const GOVERNOR_POWERSAVE_COLOR: Color32 = Color32::from_rgb(120, 170, 255);
+const GOVERNOR_SCHEDUTIL_COLOR: Color32 = Color32::from_rgb(120, 210, 140);
+const GOVERNOR_OTHER_COLOR: Color32 = Color32::from_gray(170);
+/// 亲和性变化后核心闪烁的时长（秒）
+const CORE_FLASH_SECS: f32 = 1.0;
+/// SMT 争用的斜线颜色
+const SMT_HATCH_COLOR: Color32 = Color32::from_rgba_premultiplied(90, 63, 35, 90);
+/// 使用率填充中内核态部分的斜线颜色
+const SYSTEM_HATCH_COLOR: Color32 = Color32::from_rgba_premultiplied(50, 50, 50, 50);
+/// 使用率填充之上的 iowait 部分
+const IOWAIT_COLOR: Color32 = Color32::from_rgba_premultiplied(33, 38, 55, 70);
+/// iowait 超过此比例 (%) 时在提示中说明核心繁忙但不是计算密集
+const HIGH_IOWAIT_PERCENT: f32 = 20.0;
+
+/// 核心单元格上显示的数值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellDisplay {
+    /// 是否显示数值（关闭时只用颜色表示使用率，适合核心很多时的紧凑视图）
+    pub numbers: bool,
+    /// 使用率
+    pub usage: bool,
+    /// 频率
+    pub frequency: bool,
+    /// 温度
+    pub temperature: bool,
+}
+
+impl Default for CellDisplay {
+    fn default() -> Self {
+        Self {
+            numbers: true,
+            usage: true,
+            frequency: true,
+            temperature: false,
+        }
+    }
+}
+
+impl CellDisplay {
+    /// 是否需要读取核心温度
+    pub fn needs_temperature(&self) -> bool {
+        self.numbers && self.temperature
+    }
+
+    /// 单元格上的数值文本，按 使用率 / 频率 / 温度 的顺序
+    fn texts(&self, core: &CpuCore) -> Vec<String> {
+        if !self.numbers {
+            return Vec::new();
+        }
+        let mut texts = Vec::new();
+        if self.usage {
+            texts.push(format!("{:.0}%", core.usage_percent));
+        }
+        if self.frequency {
+            texts.push(format!("{:.1}G", core.frequency_mhz as f64 / 1000.0));
+        }
+        if self.temperature {
+            let stale = if core.temperature_stale { "~" } else { "" };
+            texts.push(core.temperature_c.map_or_else(|| "--°".to_string(), |t| format!("{}{:.0}°", stale, t)));
+        }
+        texts
+    }
+}
+
+/// 选择模式下选中核心的边框颜色
+pub const SELECTION_COLOR: Color32 = Color32::from_rgb(255, 220, 80);
+
+/// 从核心选择创建预设的状态（选择模式）
+struct CoreSelection {
+    /// 选中的核心
+    cores: BTreeSet<usize>,
+    /// 新预设名称
+    name: String,
+    /// 新预设的调度策略（只提供非实时策略）
+    policy: SchedulePolicy,
+    /// 新预设的 nice 值
+    nice: i32,
+    /// 最近一次创建失败的原因
+    error: Option<String>,
+}
+
+impl CoreSelection {
+    fn new() -> Self {
+        Self {
+            cores: BTreeSet::new(),
+            name: String::new(),
+            policy: SchedulePolicy::Other,
+            nice: 0,
+            error: None,
+        }
+    }
+
+    /// 由当前选择生成预设
+    fn preset(&self) -> SchedulePreset {
+        let cores: Vec<usize> = self.cores.iter().copied().collect();
+        SchedulePreset {
+            name: self.name.trim().to_string(),
+            description: format!("绑定 CPU {}", format_cpu_list(&cores)),
+            policy: self.policy,
+            priority: self.nice,
+            affinity_cores: Some(cores),
+            io_priority: None,
+            oom_score_adj: None,
+            is_builtin: false,
+        }
+    }
+}
 
 /// CPU 监控面板
 pub struct CpuMonitorPanel {
     /// 选中的核心（用于显示详情）
     selected_core: Option<usize>,
+    /// 核心使用率的动画值（按 CPU ID 索引）
+    core_anim_usage: Vec<f32>,
+    /// 每帧向目标值靠近的比例 (0-1)
+    animation_smoothing: f32,
+    /// 已关闭的节能建议
+    dismissed_hints: Vec<String>,
+    /// 最近一次计算的节能建议及计算时间
+    parking_hints: Option<(Instant, Vec<String>)>,
+    /// 跟踪进程当前所在的核心
+    running_cpu: Option<usize>,
+    /// 本帧各核心单元格的位置（用于绘制移动高亮）
+    cell_rects: HashMap<usize, egui::Rect>,
+    /// 历史图表横轴使用系统时间（否则为启动以来的秒数）
+    wall_clock_axis: bool,
+    /// 加入对比图的核心（按加入顺序）
+    compared_cores: Vec<usize>,
+    /// 同时对比的最多核心数
+    max_compared_cores: usize,
+    /// 核心单元格显示的数值
+    cell_display: CellDisplay,
+    /// 选择模式（点击核心切换选中，而不是查看详情）
+    core_selection: Option<CoreSelection>,
+    /// 待加入预设库的新预设
+    created_preset: Option<SchedulePreset>,
+    /// 各 CCD 的强调色
+    ccd_palette: CcdPalette,
+    /// 保留给系统的核心
+    reserved_cores: Vec<usize>,
+    /// 只显示使用率不低于阈值的核心
+    hide_idle_cores: bool,
+    /// 空闲阈值 (%)
+    idle_threshold: f32,
+    /// cpufreq 策略（频率限制）
+    cpufreq_policies: Vec<CpufreqPolicy>,
+    /// 上次读取 cpufreq 策略的时间
+    cpufreq_read_at: Option<Instant>,
+    /// 选中核心所在策略正在拖动的范围（策略目录, 下限 MHz, 上限 MHz）
+    freq_draft: Option<(PathBuf, u64, u64)>,
+    /// 全局最大频率限制的草稿 (MHz)
+    max_freq_draft: Option<u64>,
+    /// 右键菜单中正在编辑的频率范围（CPU, 下限 MHz, 上限 MHz）
+    menu_freq_draft: Option<(usize, u64, u64)>,
+    /// 最近一次写入频率限制的结果
+    freq_message: Option<Result<String, String>>,
+    /// 尚未取走的 cpufreq / EPP 写入错误
+    sysfs_failures: Vec<String>,
+    /// 亲和性变化涉及的核心（是否为新允许的核心, 开始时间）
+    core_flashes: HashMap<usize, (bool, Instant)>,
+    /// 调度健康评分和各项扣分（说明, 扣分），尚未计算时为 None
+    health: Option<(u8, Vec<(String, f32)>)>,
+    /// 低于标称频率的检测规则
+    underclock_rule: UnderclockRule,
+    /// 持续低于标称频率的核心（每个新样本重新判断）
+    underclocked: Vec<UnderclockedCore>,
+    /// 上次判断低频时最新样本的时间戳
+    underclock_checked_at: Option<f64>,
+    /// 当前 EPP（与 cpufreq 策略一起定期读取，驱动不支持时为 None）
+    epp: Option<String>,
+    /// 各核心调速器不一致时的说明（与 cpufreq 策略一起更新）
+    governor_mismatch: Option<String>,
 }
 
 impl CpuMonitorPanel {
-    pub fn new() -> Self {
+    pub fn new(animation_smoothing: f32) -> Self {
         Self {
             selected_core: None,
+            core_anim_usage: Vec::new(),
+            animation_smoothing: animation_smoothing.clamp(0.01, 1.0),
+            dismissed_hints: Vec::new(),
+            parking_hints: None,
+            running_cpu: None,
+            cell_rects: HashMap::new(),
+            wall_clock_axis: false,
+            compared_cores: Vec::new(),
+            max_compared_cores: DEFAULT_MAX_COMPARED_CORES,
+            cell_display: CellDisplay::default(),
+            core_selection: None,
+            created_preset: None,
+            ccd_palette: CcdPalette::default(),
+            reserved_cores: Vec::new(),
+            hide_idle_cores: false,
+            idle_threshold: IDLE_P95_PERCENT,
+            cpufreq_policies: Vec::new(),
+            cpufreq_read_at: None,
+            freq_draft: None,
+            max_freq_draft: None,
+            menu_freq_draft: None,
+            freq_message: None,
+            sysfs_failures: Vec::new(),
+            core_flashes: HashMap::new(),
+            health: None,
+            underclock_rule: UnderclockRule::default(),
+            underclocked: Vec::new(),
+            underclock_checked_at: None,
+            epp: None,
+            governor_mismatch: None,
+        }
+    }
+
+    /// 定期重新读取 cpufreq 策略（其他工具也可能修改频率限制）
+    fn refresh_cpufreq(&mut self, force: bool) {
+        if force || self.cpufreq_read_at.is_none_or(|t| t.elapsed() >= CPUFREQ_REFRESH_INTERVAL) {
+            self.cpufreq_policies = read_cpufreq_policies();
+            self.epp = self.cpufreq_policies.iter().find_map(|p| p.epp.clone());
+            self.governor_mismatch = governor_mismatch(&self.cpufreq_policies);
+            self.cpufreq_read_at = Some(Instant::now());
+        }
+    }
+
+    /// 写入频率限制后重新读取并记录结果
+    fn finish_frequency_write(&mut self, result: Result<(), String>, success: String) {
+        self.finish_sysfs_write(result.map(|()| success));
+    }
+
+    /// 写入 cpufreq / EPP 后重新读取并记录结果，失败时留给权限与限制清单
+    fn finish_sysfs_write(&mut self, result: Result<String, String>) {
+        self.refresh_cpufreq(true);
+        if let Err(error) = &result {
+            self.sysfs_failures.push(error.clone());
+        }
+        self.freq_message = Some(result);
+    }
+
+    /// 取出自上次调用以来 cpufreq / EPP 写入失败的错误
+    pub fn take_sysfs_failures(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.sysfs_failures)
+    }
+
+    /// 亲和性变化后闪烁涉及的核心（颜色在绘制时取自当前主题）
+    pub fn flash_affinity_change(&mut self, diff: &AffinityDiff) {
+        let now = Instant::now();
+        for &core in &diff.added {
+            self.core_flashes.insert(core, (true, now));
+        }
+        for &core in &diff.removed {
+            self.core_flashes.insert(core, (false, now));
+        }
+    }
+
+    /// 设置调度健康评分（每次采样后更新）
+    pub fn set_scheduling_health(&mut self, score: u8, factors: Vec<(String, f32)>) {
+        self.health = Some((score, factors));
+    }
+
+    /// 设置"只显示繁忙核心"的空闲阈值
+    pub fn set_idle_threshold(&mut self, threshold: f32) {
+        self.idle_threshold = threshold;
+    }
+
+    /// 设置低于标称频率的检测规则
+    pub fn set_underclock_rule(&mut self, rule: UnderclockRule) {
+        self.underclock_rule = rule;
+        self.underclock_checked_at = None;
+    }
+
+    /// 设置保留核心（在网格中标记，选择时提醒）
+    pub fn set_reserved_cores(&mut self, reserved_cores: Vec<usize>) {
+        self.reserved_cores = reserved_cores;
+    }
+
+    /// 设置各 CCD 的强调色
+    pub fn set_ccd_palette(&mut self, palette: CcdPalette) {
+        self.ccd_palette = palette;
+    }
+
+    /// 取出从核心选择创建的预设
+    pub fn take_created_preset(&mut self) -> Option<SchedulePreset> {
+        self.created_preset.take()
+    }
+
+    /// 显示创建预设的结果：成功时退出选择模式，失败时保留选择并显示原因
+    pub fn set_preset_result(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.core_selection = None,
+            Err(e) => {
+                if let Some(selection) = self.core_selection.as_mut() {
+                    selection.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// 设置核心单元格显示的数值
+    pub fn set_cell_display(&mut self, display: CellDisplay) {
+        self.cell_display = display;
+    }
+
+    /// 设置同时对比的最多核心数，超出时丢弃最早加入的核心
+    pub fn set_max_compared_cores(&mut self, max: usize) {
+        self.max_compared_cores = max.max(1);
+        let excess = self.compared_cores.len().saturating_sub(self.max_compared_cores);
+        self.compared_cores.drain(..excess);
+    }
+
+    /// 切换核心是否加入对比，已满时替换最早加入的核心
+    fn toggle_compared(&mut self, cpu_id: usize) {
+        if let Some(index) = self.compared_cores.iter().position(|&id| id == cpu_id) {
+            self.compared_cores.remove(index);
+            return;
         }
+        if self.compared_cores.len() >= self.max_compared_cores {
+            self.compared_cores.remove(0);
+        }
+        self.compared_cores.push(cpu_id);
     }
 
     /// 绘制面板
-    pub fn ui(&mut self, ui: &mut Ui, cpu_info: &CpuInfo, history: &CpuHistory) {
+    /// - `start_wall_time`: 启动时的系统时间，用于历史图表的系统时间轴
+    /// - `tracked`: 进程管理中选中的进程历史，用于在核心网格上标出其所在核心
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        cpu_info: &CpuInfo,
+        history: &mut CpuHistory,
+        start_wall_time: SystemTime,
+        tracked: Option<&ProcessHistory>,
+    ) {
+        self.running_cpu = tracked.and_then(|t| t.current_cpu());
+        self.refresh_cpufreq(false);
+        if history.latest_timestamp() != self.underclock_checked_at {
+            self.underclock_checked_at = history.latest_timestamp();
+            self.underclocked = underclocked_cores(cpu_info, history, &self.underclock_rule);
+        }
+        if self.advance_animation(cpu_info) {
+            ui.ctx().request_repaint();
+        }
+
         ui.add_space(8.0);
 
+        // 节能建议
+        self.draw_parking_hints(ui, cpu_info, history);
+        self.draw_underclock_warning(ui);
+        self.draw_governor_mismatch(ui);
+
+        let contention = smt_contention(&cpu_info.cores);
+
         // 上半部分：核心网格 + CPU 信息
         ui.horizontal(|ui| {
             // 左侧：核心网格
@@ -33,9 +400,31 @@ impl CpuMonitorPanel {
                 .show(ui, |ui| {
                     ui.set_min_width(280.0);
                     ui.vertical(|ui| {
-                        ui.label(RichText::new("CPU 核心使用率").size(16.0).strong());
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("CPU 核心使用率").size(16.0).strong());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let label = if self.core_selection.is_some() { "退出选择" } else { "从选择创建预设" };
+                                if ui.small_button(label)
+                                    .on_hover_text("进入选择模式后点击核心切换选中，再用选中的核心创建绑定预设")
+                                    .clicked()
+                                {
+                                    self.core_selection = match self.core_selection {
+                                        Some(_) => None,
+                                        None => Some(CoreSelection::new()),
+                                    };
+                                }
+                                ui.checkbox(&mut self.hide_idle_cores, "只显示繁忙核心")
+                                    .on_hover_text(format!("隐藏使用率低于 {:.0}% 的核心（阈值可在设置中调整）", self.idle_threshold));
+                            });
+                        });
                         ui.add_space(12.0);
-                        self.draw_core_grid(ui, cpu_info);
+                        self.draw_core_grid(ui, cpu_info, &contention);
+                        self.draw_running_highlight(ui);
+                        self.draw_core_flashes(ui);
+                        draw_ccd_core_seconds(ui, cpu_info, history, &self.ccd_palette);
+                        if let Some(tracked) = tracked {
+                            draw_tracked_process(ui, tracked);
+                        }
                     });
                 });
 
@@ -49,13 +438,17 @@ impl CpuMonitorPanel {
                 .show(ui, |ui| {
                     ui.set_min_width(300.0);
                     ui.vertical(|ui| {
-                        self.draw_cpu_summary(ui, cpu_info);
+                        self.draw_cpu_summary(ui, cpu_info, contention.len());
+                        self.draw_frequency_limit(ui);
                         ui.add_space(20.0);
                         self.draw_cache_info(ui, cpu_info);
+                        self.draw_selected_core(ui, cpu_info);
                     });
                 });
         });
 
+        self.draw_selection_bar(ui, cpu_info);
+
         ui.add_space(16.0);
 
         // 下半部分：历史曲线图
@@ -64,64 +457,338 @@ impl CpuMonitorPanel {
             .rounding(Rounding::same(8.0))
             .fill(Color32::from_gray(35))
             .show(ui, |ui| {
-                self.draw_history_chart(ui, history, cpu_info);
+                self.draw_history_chart(ui, history, cpu_info, start_wall_time);
             });
+
+        if !self.compared_cores.is_empty() {
+            ui.add_space(16.0);
+            Frame::none()
+                .inner_margin(Margin::same(12.0))
+                .rounding(Rounding::same(8.0))
+                .fill(Color32::from_gray(35))
+                .show(ui, |ui| {
+                    self.draw_core_comparison(ui, history);
+                });
+        }
+    }
+
+    /// 选择模式的浮动操作条：当前选择、平均使用率和创建预设
+    fn draw_selection_bar(&mut self, ui: &mut Ui, cpu_info: &CpuInfo) {
+        let Some(selection) = self.core_selection.as_mut() else {
+            return;
+        };
+        let mut create = false;
+        let mut exit = false;
+        egui::Area::new(egui::Id::new("core_selection_bar"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -16.0))
+            .show(ui.ctx(), |ui| {
+                Frame::none()
+                    .fill(Color32::from_gray(45))
+                    .stroke(Stroke::new(1.0, SELECTION_COLOR))
+                    .inner_margin(Margin::same(10.0))
+                    .rounding(Rounding::same(8.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if selection.cores.is_empty() {
+                                ui.label(RichText::new("点击核心进行选择").color(Color32::from_gray(160)));
+                            } else {
+                                let cores: Vec<usize> = selection.cores.iter().copied().collect();
+                                ui.label(RichText::new(format!("CPU {}", format_cpu_list(&cores)))
+                                    .monospace().color(SELECTION_COLOR));
+                                let usage = mean_usage(cpu_info.cores.iter().filter(|c| selection.cores.contains(&c.cpu_id)));
+                                ui.label(RichText::new(format!(
+                                    "{} 个核心 · 平均 {:.0}%",
+                                    cores.len(),
+                                    usage.unwrap_or(0.0)
+                                )).color(Color32::from_gray(180)));
+                            }
+                            ui.separator();
+                            ui.add(egui::TextEdit::singleline(&mut selection.name).hint_text("预设名称").desired_width(120.0));
+                            egui::ComboBox::from_id_salt("selection_policy")
+                                .selected_text(selection.policy.short_name())
+                                .show_ui(ui, |ui| {
+                                    for policy in [SchedulePolicy::Other, SchedulePolicy::Batch, SchedulePolicy::Idle] {
+                                        ui.selectable_value(&mut selection.policy, policy, policy.display_name());
+                                    }
+                                });
+                            ui.label(RichText::new("nice").color(Color32::from_gray(160)));
+                            ui.add(egui::DragValue::new(&mut selection.nice).range(-20..=19));
+                            let ready = !selection.cores.is_empty() && !selection.name.trim().is_empty();
+                            if ui.add_enabled(ready, egui::Button::new("创建预设"))
+                                .on_disabled_hover_text("需要选择核心并填写名称")
+                                .clicked()
+                            {
+                                create = true;
+                            }
+                            if ui.button("取消").clicked() {
+                                exit = true;
+                            }
+                        });
+                        let cores: Vec<usize> = selection.cores.iter().copied().collect();
+                        let reserved = reserved_among(&cores, &self.reserved_cores);
+                        if !reserved.is_empty() {
+                            ui.label(RichText::new(format!("⚠ 选择包含保留核心 {}", format_cpu_list(&reserved)))
+                                .size(12.0).color(Color32::from_rgb(255, 200, 100)));
+                        }
+                        if let Some(error) = &selection.error {
+                            ui.label(RichText::new(error).size(12.0).color(Color32::from_rgb(255, 100, 100)));
+                        }
+                    });
+            });
+        if create {
+            selection.error = None;
+            self.created_preset = Some(selection.preset());
+        }
+        if exit {
+            self.core_selection = None;
+        }
+    }
+
+    /// 绘制核心对比图
+    fn draw_core_comparison(&mut self, ui: &mut Ui, history: &CpuHistory) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("核心对比").size(16.0).strong());
+            ui.add_space(12.0);
+            if self.compared_cores.len() >= self.max_compared_cores {
+                ui.label(RichText::new(format!("最多对比 {} 个核心", self.max_compared_cores))
+                    .size(12.0).color(Color32::from_rgb(255, 200, 100)))
+                    .on_hover_text("继续添加会替换最早加入的核心，可在设置中调整上限");
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("清除").clicked() {
+                    self.compared_cores.clear();
+                }
+            });
+        });
+        ui.add_space(8.0);
+        draw_multi_core_chart(ui, history, &self.compared_cores);
+    }
+
+    /// 绘制可关闭的核心停放 / 收拢建议
+    fn draw_parking_hints(&mut self, ui: &mut Ui, cpu_info: &CpuInfo, history: &CpuHistory) {
+        if self.parking_hints.as_ref().is_none_or(|(at, _)| at.elapsed() >= PARKING_HINTS_INTERVAL) {
+            self.parking_hints = Some((Instant::now(), recommend_consolidation(cpu_info, history)));
+        }
+        let Some((_, hints)) = &self.parking_hints else {
+            return;
+        };
+        let mut dismissed = None;
+        for hint in hints.iter().filter(|h| !self.dismissed_hints.contains(h)) {
+            Frame::none()
+                .fill(Color32::from_rgb(30, 55, 70))
+                .inner_margin(Margin::same(8.0))
+                .rounding(Rounding::same(4.0))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("💡").color(Color32::from_rgb(255, 220, 100)));
+                        ui.label(RichText::new(hint.as_str()).color(Color32::from_rgb(180, 220, 255)))
+                            .on_hover_text("基于历史窗口内的持续负载给出的节能建议，仅供参考");
+                        if ui.small_button("✕").clicked() {
+                            dismissed = Some(hint.clone());
+                        }
+                    });
+                });
+            ui.add_space(8.0);
+        }
+        if let Some(hint) = dismissed {
+            self.dismissed_hints.push(hint);
+        }
+    }
+
+    /// 持续低于标称频率的核心：汇总警告、可能原因，以及调速器 / EPP 的入口
+    fn draw_underclock_warning(&mut self, ui: &mut Ui) {
+        if self.underclocked.is_empty() {
+            return;
+        }
+        let cpus: Vec<usize> = self.underclocked.iter().map(|u| u.cpu_id).collect();
+        let average = self.underclocked.iter().map(|u| u.average_mhz).sum::<u64>() / self.underclocked.len() as u64;
+        let nominal = self.underclocked.iter().map(|u| u.nominal_mhz).max().unwrap_or(0);
+
+        let mut causes = Vec::new();
+        let policies: Vec<&CpufreqPolicy> = self
+            .cpufreq_policies
+            .iter()
+            .filter(|p| p.cpus.iter().any(|cpu| cpus.contains(cpu)))
+            .collect();
+        if let Some(policy) = policies.iter().find(|p| p.limits().is_capped()) {
+            causes.push(format!("{} 的 scaling_max_freq 被限制在 {} MHz", policy.name(), khz_to_mhz(policy.max_khz)));
+        }
+        if let Some(governor) = policies.iter().filter_map(|p| p.governor.as_deref()).find(|g| *g != "performance") {
+            causes.push(format!("调速器为 {}，负载上升时可能升频不及时", governor));
+        }
+        if let Some(epp) = self.epp.as_deref().filter(|epp| epp.contains("power") && !epp.contains("performance")) {
+            causes.push(format!("EPP 为 {}，偏向节能", epp));
+        }
+        causes.push("功耗墙 (PL1 / PPT) 或温度墙：检查散热和 BIOS 中的功耗限制".to_string());
+        causes.push("cpufreq 驱动卡在低频：尝试切换调速器".to_string());
+
+        Frame::none()
+            .fill(Color32::from_rgb(70, 40, 40))
+            .inner_margin(Margin::same(8.0))
+            .rounding(Rounding::same(4.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new(format!(
+                    "↓ CPU {} 有负载但持续低于标称频率（平均 {} MHz / 标称 {} MHz）",
+                    format_cpu_list(&cpus),
+                    average,
+                    nominal
+                )).color(Color32::from_rgb(255, 200, 100)));
+                ui.label(RichText::new("可能的原因：").size(12.0).color(Color32::from_gray(180)));
+                for cause in &causes {
+                    ui.label(RichText::new(format!("  • {}", cause)).size(12.0).color(Color32::from_gray(180)));
+                }
+                ui.horizontal(|ui| {
+                    if ui.small_button(format!("查看 CPU {}", cpus[0]))
+                        .on_hover_text("在详情中查看调速器和频率限制；右键点击核心可切换调速器")
+                        .clicked()
+                    {
+                        self.selected_core = Some(cpus[0]);
+                    }
+                    if let Some(current) = self.epp.clone() {
+                        ui.label(RichText::new("EPP").size(12.0).color(Color32::from_gray(160)));
+                        egui::ComboBox::from_id_salt("underclock_epp")
+                            .selected_text(current.as_str())
+                            .show_ui(ui, |ui| {
+                                for value in EPP_VALUES {
+                                    if ui.selectable_label(current == *value, *value).clicked() && current != *value {
+                                        let result = set_epp_all(value).map(|()| format!("EPP 已设为 {}", value));
+                                        self.finish_sysfs_write(result);
+                                    }
+                                }
+                            });
+                    }
+                });
+            });
+        ui.add_space(8.0);
+    }
+
+    /// 各核心调速器不一致的提示（常见于只改了部分策略，或电源管理守护进程只接管了部分核心）
+    fn draw_governor_mismatch(&self, ui: &mut Ui) {
+        let Some(mismatch) = &self.governor_mismatch else {
+            return;
+        };
+        ui.label(RichText::new(format!("⚠ 调速器不一致：{}", mismatch)).color(Color32::from_rgb(255, 200, 100)))
+            .on_hover_text("核心左上角的字母为所在策略的调速器；右键点击核心可切换");
+        ui.add_space(8.0);
+    }
+
+    /// 动画值向实际使用率靠近一步，返回是否仍需继续动画
+    fn advance_animation(&mut self, cpu_info: &CpuInfo) -> bool {
+        let len = cpu_info.cores.iter().map(|c| c.cpu_id + 1).max().unwrap_or(0);
+        self.core_anim_usage.resize(len, 0.0);
+
+        let mut animating = false;
+        for core in &cpu_info.cores {
+            let current = &mut self.core_anim_usage[core.cpu_id];
+            let delta = core.usage_percent - *current;
+            if delta.abs() > ANIMATION_SETTLE_THRESHOLD {
+                *current += delta * self.animation_smoothing;
+                animating = true;
+            } else {
+                *current = core.usage_percent;
+            }
+        }
+        animating
+    }
+
+    /// 核心是否在网格中显示（"只显示繁忙核心"开启时隐藏空闲核心；选择模式下全部显示）
+    fn is_core_visible(&self, core: &CpuCore) -> bool {
+        !self.hide_idle_cores || self.core_selection.is_some() || core.usage_percent >= self.idle_threshold
     }
 
     /// 绘制核心网格
-    fn draw_core_grid(&mut self, ui: &mut Ui, cpu_info: &CpuInfo) {
+    fn draw_core_grid(&mut self, ui: &mut Ui, cpu_info: &CpuInfo, contention: &[SmtContention]) {
+        self.cell_rects.clear();
+        let packages = cpu_info.packages();
+
+        let hidden = cpu_info.cores.iter().filter(|c| !self.is_core_visible(c)).count();
+        if hidden > 0 {
+            ui.label(RichText::new(format!("隐藏 {} 个空闲核心", hidden)).size(12.0).color(Color32::from_gray(140)))
+                .on_hover_text(format!("使用率低于 {:.0}%", self.idle_threshold));
+            ui.add_space(4.0);
+        }
+
+        if packages.len() <= 1 {
+            let cores: Vec<&CpuCore> = cpu_info.cores.iter().filter(|c| self.is_core_visible(c)).collect();
+            self.draw_core_group(ui, cpu_info, &cores, contention, 0);
+            return;
+        }
+
+        // 多路系统：每个插槽一个可折叠分组，标题显示插槽平均使用率；没有可见核心的插槽不显示
+        for (&package_id, cpus) in &packages {
+            let cores: Vec<&CpuCore> = cpu_info
+                .cores
+                .iter()
+                .filter(|c| c.package_id == package_id && self.is_core_visible(c))
+                .collect();
+            if cores.is_empty() {
+                continue;
+            }
+            let usage = cpu_info.package_usage(package_id).unwrap_or(0.0);
+            let title = RichText::new(format!("插槽 {} · {} 个逻辑核心 · 平均 {:.0}%", package_id, cpus.len(), usage))
+                .size(13.0)
+                .strong()
+                .color(usage_to_color(usage));
+            egui::CollapsingHeader::new(title)
+                .id_salt(("cpu_package", package_id))
+                .default_open(true)
+                .show(ui, |ui| self.draw_core_group(ui, cpu_info, &cores, contention, package_id));
+            ui.add_space(8.0);
+        }
+    }
+
+    /// 绘制一组核心（单插槽系统为全部核心），组内按 L3 缓存分组
+    fn draw_core_group(
+        &mut self,
+        ui: &mut Ui,
+        cpu_info: &CpuInfo,
+        cores: &[&CpuCore],
+        contention: &[SmtContention],
+        package_id: usize,
+    ) {
+        let contended = |cpu_id: usize| contention.iter().find(|c| c.cpu_ids.contains(&cpu_id));
         let columns = cpu_info.grid_columns().min(8);
         let core_size = Vec2::new(52.0, 52.0);
         let spacing = 6.0;
 
         // 按 L3 缓存分组绘制
-        let cores_by_l3 = cpu_info.cores_by_l3();
+        let cores_by_l3 = cores_by_l3(cores.iter().copied());
 
         if cores_by_l3.is_empty() {
             // 没有 L3 分组信息，直接绘制所有核心
-            egui::Grid::new("cpu_grid")
+            egui::Grid::new(("cpu_grid", package_id))
                 .num_columns(columns)
                 .spacing([spacing, spacing])
                 .show(ui, |ui| {
-                    for (i, core) in cpu_info.cores.iter().enumerate() {
-                        self.draw_core_cell(ui, core.cpu_id, core.usage_percent, core.frequency_mhz,
-                            core.core_type, false, core_size);
+                    for (i, core) in cores.iter().enumerate() {
+                        self.draw_core_cell(ui, core, None, contended(core.cpu_id), core_size);
                         if (i + 1) % columns == 0 {
                             ui.end_row();
                         }
                     }
                 });
         } else {
-            // 按 L3 缓存分组绘制
-            let mut l3_ids: Vec<_> = cores_by_l3.keys().copied().collect();
-            l3_ids.sort();
-
-            for l3_id in l3_ids {
-                if let (Some(cores), Some(cache_info)) = (
-                    cores_by_l3.get(&l3_id),
-                    cpu_info.l3_caches.iter().find(|c| c.id == l3_id),
-                ) {
+            // 只有一个普通 CCD 时保留按核心类型着色的边框（Intel 混合架构的 P / E 核心）
+            let multiple = cores_by_l3.len() > 1;
+            for (l3_id, cores) in &cores_by_l3 {
+                if let Some(cache_info) = cpu_info.l3_caches.iter().find(|c| c.id == *l3_id) {
                     let is_vcache = cache_info.is_vcache;
                     let label = if is_vcache {
                         format!("CCD {} (3D V-Cache: {} MB)", l3_id, cache_info.size_kb / 1024)
                     } else {
                         format!("CCD {} (L3: {} MB)", l3_id, cache_info.size_kb / 1024)
                     };
+                    let accent = self.ccd_palette.color(*l3_id).filter(|_| multiple || is_vcache);
 
-                    ui.label(RichText::new(label).size(12.0).color(
-                        if is_vcache { Color32::from_rgb(100, 200, 100) } else { Color32::from_gray(160) }
-                    ));
+                    ui.label(RichText::new(label).size(12.0).color(accent.unwrap_or(Color32::from_gray(160))));
                     ui.add_space(4.0);
 
-                    egui::Grid::new(format!("cpu_grid_{}", l3_id))
+                    egui::Grid::new(("cpu_grid", package_id, *l3_id))
                         .num_columns(columns.min(cores.len()))
                         .spacing([spacing, spacing])
                         .show(ui, |ui| {
                             for (i, core) in cores.iter().enumerate() {
-                                self.draw_core_cell(
-                                    ui, core.cpu_id, core.usage_percent, core.frequency_mhz,
-                                    core.core_type, is_vcache, core_size,
-                                );
+                                self.draw_core_cell(ui, core, accent, contended(core.cpu_id), core_size);
                                 if (i + 1) % columns == 0 {
                                     ui.end_row();
                                 }
@@ -134,81 +801,355 @@ impl CpuMonitorPanel {
         }
     }
 
-    /// 绘制单个核心单元格
+    /// 绘制单个核心单元格，存在 SMT 争用时叠加斜线；`accent` 为所属 CCD 的强调色
     fn draw_core_cell(
         &mut self,
         ui: &mut Ui,
-        cpu_id: usize,
-        usage: f32,
-        freq_mhz: u64,
-        core_type: CoreType,
-        is_vcache: bool,
+        core: &CpuCore,
+        accent: Option<Color32>,
+        contention: Option<&SmtContention>,
         size: Vec2,
     ) {
-        let usage_color = usage_to_color(usage);
-        let border_color = if is_vcache {
-            Color32::from_rgb(100, 200, 100)
-        } else {
-            match core_type {
-                CoreType::Performance => Color32::from_rgb(100, 150, 255),
-                CoreType::Efficiency => Color32::from_rgb(255, 180, 100),
-                CoreType::Unknown => Color32::from_gray(80),
-            }
-        };
+        let (cpu_id, usage, freq_mhz, core_type) = (core.cpu_id, core.usage_percent, core.frequency_mhz, core.core_type);
+        let anim_usage = self.core_anim_usage.get(cpu_id).copied().unwrap_or(usage);
+        let usage_color = usage_to_color(anim_usage);
+        let border_color = accent.unwrap_or(match core_type {
+            CoreType::Performance => Color32::from_rgb(100, 150, 255),
+            CoreType::Efficiency => Color32::from_rgb(255, 180, 100),
+            CoreType::Unknown => Color32::from_gray(80),
+        });
 
         let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+        self.cell_rects.insert(cpu_id, rect);
+        let policy = policy_for_cpu(&self.cpufreq_policies, cpu_id);
+        let (governor, epp) = policy.map_or((None, None), |p| (p.governor.clone(), p.epp.clone()));
+        let freq_limits = policy.map(CpufreqPolicy::limits);
 
         if ui.is_rect_visible(rect) {
             let painter = ui.painter();
 
-            // 背景渐变效果
-            painter.rect_filled(rect, 6.0, usage_color);
+            // 背景 + 自底向上的使用率填充
+            painter.rect_filled(rect, 6.0, Color32::from_gray(45));
+            let fill_height = rect.height() * (anim_usage / 100.0).clamp(0.0, 1.0);
+            if fill_height > 0.0 {
+                let fill_rect = egui::Rect::from_min_max(
+                    egui::pos2(rect.left(), rect.bottom() - fill_height),
+                    rect.right_bottom(),
+                );
+                painter.rect_filled(fill_rect, 6.0, usage_color);
+            }
 
-            // 边框
-            painter.rect_stroke(rect, 6.0, Stroke::new(2.0, border_color));
+            // 分项：用户态实心，内核态斜线，iowait 以半透明色叠在使用率之上
+            if let Some(breakdown) = core.usage_breakdown {
+                let unit = rect.height() / 100.0;
+                let user_top = rect.bottom() - (breakdown.user * unit).min(fill_height);
+                let system_top = (user_top - breakdown.system * unit).max(rect.bottom() - fill_height);
+                if user_top - system_top > 1.0 {
+                    let band = egui::Rect::from_x_y_ranges(rect.x_range(), system_top..=user_top);
+                    draw_hatching(painter, band, SYSTEM_HATCH_COLOR);
+                }
+                let iowait_height = (breakdown.iowait * unit).min(rect.height() - fill_height);
+                if iowait_height > 1.0 {
+                    let top = rect.bottom() - fill_height;
+                    let band = egui::Rect::from_x_y_ranges(rect.x_range(), top - iowait_height..=top);
+                    painter.rect_filled(band, 0.0, IOWAIT_COLOR);
+                }
+            }
 
-            // 核心编号
-            painter.text(
-                rect.center_top() + egui::vec2(0.0, 10.0),
-                egui::Align2::CENTER_TOP,
-                format!("{:02}", cpu_id),
-                egui::FontId::proportional(12.0),
-                Color32::WHITE,
-            );
+            if contention.is_some() {
+                draw_hatching(painter, rect, SMT_HATCH_COLOR);
+            }
 
-            // 使用率
-            painter.text(
-                rect.center(),
-                egui::Align2::CENTER_CENTER,
-                format!("{:.0}%", usage),
-                egui::FontId::proportional(14.0),
-                Color32::WHITE,
-            );
+            // 保留核心：右上角三角标记
+            if self.reserved_cores.contains(&cpu_id) {
+                let corner = rect.right_top();
+                painter.add(egui::Shape::convex_polygon(
+                    vec![corner + egui::vec2(-14.0, 0.0), corner, corner + egui::vec2(0.0, 14.0)],
+                    RESERVED_CORE_COLOR,
+                    Stroke::NONE,
+                ));
+            }
 
-            // 频率
-            let freq_ghz = freq_mhz as f64 / 1000.0;
-            painter.text(
-                rect.center_bottom() - egui::vec2(0.0, 8.0),
-                egui::Align2::CENTER_BOTTOM,
-                format!("{:.1}G", freq_ghz),
-                egui::FontId::proportional(10.0),
-                Color32::from_gray(220),
-            );
+            // 频率上限被限制：左下角三角标记
+            if freq_limits.is_some_and(|l| l.is_capped()) {
+                let corner = rect.left_bottom();
+                painter.add(egui::Shape::convex_polygon(
+                    vec![corner + egui::vec2(0.0, -12.0), corner + egui::vec2(12.0, 0.0), corner],
+                    FREQ_CAPPED_COLOR,
+                    Stroke::NONE,
+                ));
+            }
+
+            // 持续低于标称频率：右下角向下箭头
+            if self.underclocked.iter().any(|u| u.cpu_id == cpu_id) {
+                painter.text(
+                    rect.right_bottom() + egui::vec2(-4.0, -2.0),
+                    egui::Align2::RIGHT_BOTTOM,
+                    "↓",
+                    egui::FontId::proportional(13.0),
+                    UNDERCLOCK_COLOR,
+                );
+            }
+
+            // 对比图中的曲线颜色
+            if let Some(index) = self.compared_cores.iter().position(|&id| id == cpu_id) {
+                painter.circle_filled(rect.left_top() + egui::vec2(8.0, 8.0), 4.0, series_color(index));
+            }
+
+            // 调速器角标：左上角（有对比圆点时右移）
+            if let Some(governor) = governor.as_deref() {
+                let (letter, color) = governor_badge(governor);
+                let offset = if self.compared_cores.contains(&cpu_id) { 15.0 } else { 4.0 };
+                painter.text(
+                    rect.left_top() + egui::vec2(offset, 2.0),
+                    egui::Align2::LEFT_TOP,
+                    letter,
+                    egui::FontId::monospace(9.0),
+                    color,
+                );
+            }
+
+            // 边框（选择模式下选中的核心用高亮边框）
+            let selected = self.core_selection.as_ref().is_some_and(|s| s.cores.contains(&cpu_id));
+            if selected {
+                painter.rect_stroke(rect, 6.0, Stroke::new(3.0, SELECTION_COLOR));
+            } else {
+                painter.rect_stroke(rect, 6.0, Stroke::new(2.0, border_color));
+            }
+
+            if self.cell_display.numbers {
+                // 核心编号
+                painter.text(
+                    rect.center_top() + egui::vec2(0.0, 10.0),
+                    egui::Align2::CENTER_TOP,
+                    format!("{:02}", cpu_id),
+                    egui::FontId::proportional(12.0),
+                    Color32::WHITE,
+                );
+
+                // 第一项居中，其余合并到底部一行；只显示一项时用大字号
+                let texts = self.cell_display.texts(core);
+                if let Some((primary, rest)) = texts.split_first() {
+                    let (size, offset) = if rest.is_empty() { (18.0, 4.0) } else { (14.0, 0.0) };
+                    painter.text(
+                        rect.center() + egui::vec2(0.0, offset),
+                        egui::Align2::CENTER_CENTER,
+                        primary,
+                        egui::FontId::proportional(size),
+                        Color32::WHITE,
+                    );
+                    if !rest.is_empty() {
+                        painter.text(
+                            rect.center_bottom() - egui::vec2(0.0, 8.0),
+                            egui::Align2::CENTER_BOTTOM,
+                            rest.join(" "),
+                            egui::FontId::proportional(10.0),
+                            Color32::from_gray(220),
+                        );
+                    }
+                }
+            }
         }
 
         if response.clicked() {
-            self.selected_core = Some(cpu_id);
+            if let Some(selection) = self.core_selection.as_mut() {
+                if !selection.cores.remove(&cpu_id) {
+                    selection.cores.insert(cpu_id);
+                }
+            } else if ui.input(|i| i.modifiers.shift) {
+                self.toggle_compared(cpu_id);
+            } else {
+                self.selected_core = Some(cpu_id);
+            }
+        }
+
+        let stale = if core.temperature_stale { "（上次读取的值）" } else { "" };
+        let temperature = core.temperature_c.map_or_else(String::new, |t| format!("\n温度: {:.0}°C{}", t, stale));
+        let mut hover = format!(
+            "CPU {}\n使用率: {:.1}%\n频率: {} MHz{}\n类型: {:?}\nShift 点击加入 / 移出对比",
+            cpu_id, usage, freq_mhz, temperature, core_type
+        );
+        if let Some(breakdown) = core.usage_breakdown {
+            hover.push_str(&format!(
+                "\n\n用户 {:.1}% · 内核 {:.1}% · iowait {:.1}%\n（填充中实心为用户态，斜线为内核态，上方半透明为 iowait）",
+                breakdown.user, breakdown.system, breakdown.iowait
+            ));
+            if breakdown.iowait >= HIGH_IOWAIT_PERCENT {
+                hover.push_str("\niowait 较高：核心主要在等待 I/O 而不是计算，绑定核心不会提升性能");
+            }
+        }
+        if governor.is_some() || epp.is_some() {
+            hover.push_str(&format!(
+                "\n调速器: {} · EPP: {}",
+                governor.as_deref().unwrap_or("未知"),
+                epp.as_deref().unwrap_or("不支持")
+            ));
+        }
+        if self.reserved_cores.contains(&cpu_id) {
+            hover.push_str("\n保留给系统");
+        }
+        if let Some(underclocked) = self.underclocked.iter().find(|u| u.cpu_id == cpu_id) {
+            hover.push_str(&format!(
+                "\n\n↓ 有负载但持续 {:.0} 秒低于标称频率：平均 {} MHz / 标称 {} MHz",
+                underclocked.secs, underclocked.average_mhz, underclocked.nominal_mhz
+            ));
+        }
+        if let Some(limits) = freq_limits.filter(FreqLimits::is_capped) {
+            hover.push_str(&format!(
+                "\n\n频率上限 {} MHz，低于硬件最高 {} MHz\n（scaling_max_freq 被用户或散热守护进程限制，右键点击可调整）",
+                limits.max_mhz, limits.hw_max_mhz
+            ));
+        }
+        if let Some(contention) = contention {
+            let siblings: Vec<String> = contention
+                .cpu_ids
+                .iter()
+                .filter(|&&id| id != cpu_id)
+                .map(|id| id.to_string())
+                .collect();
+            hover.push_str(&format!(
+                "\n\nSMT 争用: 与 CPU {} 共用物理核心且都繁忙\n每个线程约为独占核心时的 {:.0}% 性能",
+                siblings.join(", "),
+                SMT_SHARED_THROUGHPUT * 100.0
+            ));
         }
+        response.on_hover_text(hover).context_menu(|ui| self.draw_core_menu(ui, cpu_id));
+    }
+
+    /// 核心右键菜单：切换所在策略的调速器、调整频率范围，同策略的核心一并修改
+    fn draw_core_menu(&mut self, ui: &mut Ui, cpu_id: usize) {
+        let Some(policy) = policy_for_cpu(&self.cpufreq_policies, cpu_id).cloned() else {
+            ui.label(RichText::new("没有 cpufreq 驱动").color(Color32::from_gray(140)));
+            return;
+        };
+        ui.label(RichText::new(policy.name()).strong());
+        if policy.cpus.len() > 1 {
+            ui.label(RichText::new(format!("同时作用于 CPU {}", format_cpu_list(&policy.cpus)))
+                .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+        }
+        ui.separator();
+        self.draw_governor_menu(ui, cpu_id, &policy);
+        ui.separator();
+        self.draw_scaling_menu(ui, cpu_id, &policy);
+    }
 
-        response.on_hover_text(format!(
-            "CPU {}\n使用率: {:.1}%\n频率: {} MHz\n类型: {:?}",
-            cpu_id, usage, freq_mhz, core_type
-        ));
+    /// 右键菜单中的调速器列表
+    fn draw_governor_menu(&mut self, ui: &mut Ui, cpu_id: usize, policy: &CpufreqPolicy) {
+        if policy.available_governors.is_empty() {
+            ui.label(RichText::new("驱动未提供可用的调速器").color(Color32::from_gray(140)));
+            return;
+        }
+        ui.label(RichText::new("调速器").color(Color32::from_gray(160)));
+        for governor in &policy.available_governors {
+            let current = policy.governor.as_deref() == Some(governor.as_str());
+            if ui.selectable_label(current, governor).clicked() {
+                ui.close_menu();
+                if !current {
+                    let result = set_core_governor(cpu_id, governor).map(|cpus| {
+                        format!("CPU {}: 调速器已切换为 {}", format_cpu_list(&cpus), governor)
+                    });
+                    self.finish_sysfs_write(result);
+                }
+            }
+        }
+    }
+
+    /// 右键菜单中的频率范围：分别写入 scaling_max_freq / scaling_min_freq 并读回确认
+    fn draw_scaling_menu(&mut self, ui: &mut Ui, cpu_id: usize, policy: &CpufreqPolicy) {
+        let limits = policy.limits();
+        let (mut low, mut high) = match self.menu_freq_draft {
+            Some((cpu, low, high)) if cpu == cpu_id => (low, high),
+            _ => (limits.min_mhz, limits.max_mhz),
+        };
+        ui.label(RichText::new("频率范围").color(Color32::from_gray(160)));
+        ui.label(RichText::new(format!("硬件 {} - {} MHz", limits.hw_min_mhz, limits.hw_max_mhz))
+            .size(11.0).color(Color32::from_gray(140)));
+        if limits.is_capped() {
+            ui.label(RichText::new(format!("上限被限制在 {} MHz", limits.max_mhz)).size(11.0).color(FREQ_CAPPED_COLOR));
+        }
+
+        let range = limits.hw_min_mhz..=limits.hw_max_mhz;
+        let mut result = None;
+        egui::Grid::new(("core_menu_limits", cpu_id)).num_columns(3).show(ui, |ui| {
+            ui.label("上限");
+            let max_changed = ui.add(egui::DragValue::new(&mut high).range(range.clone()).speed(10.0).suffix(" MHz")).changed();
+            if ui.button("应用").clicked() {
+                result = Some(set_scaling_max_freq(cpu_id, high));
+            }
+            ui.end_row();
+
+            ui.label("下限");
+            let min_changed = ui.add(egui::DragValue::new(&mut low).range(range).speed(10.0).suffix(" MHz")).changed();
+            if ui.button("应用").clicked() {
+                result = Some(set_scaling_min_freq(cpu_id, low));
+            }
+            ui.end_row();
+
+            if max_changed || min_changed {
+                self.menu_freq_draft = Some((cpu_id, low, high));
+            }
+        });
+        if (limits.min_mhz, limits.max_mhz) != (limits.hw_min_mhz, limits.hw_max_mhz) && ui.button("恢复硬件范围").clicked() {
+            result = Some(
+                set_scaling_max_freq(cpu_id, limits.hw_max_mhz).and_then(|_| set_scaling_min_freq(cpu_id, limits.hw_min_mhz)),
+            );
+        }
+
+        if let Some(result) = result {
+            ui.close_menu();
+            self.menu_freq_draft = None;
+            self.finish_sysfs_write(result.map(|limits| {
+                format!("CPU {}: {} - {} MHz", format_cpu_list(&policy.cpus), limits.min_mhz, limits.max_mhz)
+            }));
+        }
+    }
+
+    /// 在跟踪进程所在的核心上绘制高亮框，核心切换时平滑移动过去
+    fn draw_running_highlight(&self, ui: &mut Ui) {
+        let Some(rect) = self.running_cpu.and_then(|cpu| self.cell_rects.get(&cpu)) else {
+            return;
+        };
+        let ctx = ui.ctx();
+        let id = egui::Id::new("running_cpu_highlight");
+        let x = ctx.animate_value_with_time(id.with("x"), rect.left(), RUNNING_HIGHLIGHT_SECS);
+        let y = ctx.animate_value_with_time(id.with("y"), rect.top(), RUNNING_HIGHLIGHT_SECS);
+        let highlight = egui::Rect::from_min_size(egui::pos2(x, y), rect.size()).expand(2.0);
+        ui.painter().rect_stroke(highlight, 8.0, Stroke::new(3.0, RUNNING_HIGHLIGHT_COLOR));
+    }
+
+    /// 在亲和性刚变化的核心上叠加逐渐淡出的颜色，全部淡出后不再请求重绘
+    /// 新允许的核心使用主题的选中色，不再允许的使用错误色
+    fn draw_core_flashes(&mut self, ui: &mut Ui) {
+        self.core_flashes.retain(|_, (_, start)| start.elapsed().as_secs_f32() < CORE_FLASH_SECS);
+        if self.core_flashes.is_empty() {
+            return;
+        }
+        let visuals = ui.visuals();
+        let (added_color, removed_color) = (visuals.selection.bg_fill, visuals.error_fg_color);
+        let painter = ui.painter();
+        for (cpu_id, (added, start)) in &self.core_flashes {
+            let color = if *added { added_color } else { removed_color };
+            // 隐藏的核心没有单元格
+            let Some(rect) = self.cell_rects.get(cpu_id) else {
+                continue;
+            };
+            let strength = 1.0 - start.elapsed().as_secs_f32() / CORE_FLASH_SECS;
+            painter.rect_filled(*rect, 6.0, color.gamma_multiply(0.5 * strength));
+            painter.rect_stroke(*rect, 6.0, Stroke::new(3.0, color.gamma_multiply(strength)));
+        }
+        ui.ctx().request_repaint();
     }
 
     /// 绘制 CPU 总体信息
-    fn draw_cpu_summary(&self, ui: &mut Ui, cpu_info: &CpuInfo) {
-        ui.label(RichText::new("CPU 信息").size(16.0).strong());
+    fn draw_cpu_summary(&self, ui: &mut Ui, cpu_info: &CpuInfo, contended_cores: usize) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("CPU 信息").size(16.0).strong());
+            if let Some((score, factors)) = &self.health {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    draw_health_score(ui, *score, factors);
+                });
+            }
+        });
         ui.add_space(12.0);
 
         let row_height = 24.0;
@@ -225,6 +1166,25 @@ impl CpuMonitorPanel {
                 ui.label(format!("{:?}", cpu_info.vendor));
                 ui.end_row();
 
+                if let (Some(family), Some(model), Some(stepping)) = (cpu_info.family, cpu_info.model, cpu_info.stepping) {
+                    ui.label(RichText::new("标识").color(Color32::from_gray(160)));
+                    ui.label(format!("系列 {} · 型号 {} · 步进 {}", family, model, stepping))
+                        .on_hover_text(format!("Family {:#x}, Model {:#x}, Stepping {:#x}", family, model, stepping));
+                    ui.end_row();
+                }
+
+                ui.label(RichText::new("微码").color(Color32::from_gray(160)));
+                match &cpu_info.microcode {
+                    Some(version) => {
+                        ui.label(RichText::new(version).monospace())
+                            .on_hover_text("部分调度和加速行为与微码版本有关，报告问题时请附上");
+                    }
+                    None => {
+                        ui.label(RichText::new("不可用").color(Color32::from_gray(120)));
+                    }
+                }
+                ui.end_row();
+
                 ui.label(RichText::new("核心").color(Color32::from_gray(160)));
                 ui.label(format!(
                     "{} 物理 / {} 逻辑",
@@ -233,7 +1193,18 @@ impl CpuMonitorPanel {
                 ui.end_row();
 
                 ui.label(RichText::new("SMT").color(Color32::from_gray(160)));
-                ui.label(if cpu_info.smt_enabled { "启用" } else { "禁用" });
+                ui.horizontal(|ui| {
+                    ui.label(if cpu_info.smt_enabled { "启用" } else { "禁用" });
+                    if contended_cores > 0 {
+                        ui.label(RichText::new(format!("{} 个物理核心存在 SMT 争用", contended_cores))
+                            .color(Color32::from_rgb(255, 180, 100)))
+                            .on_hover_text(format!(
+                                "同一物理核心的兄弟线程使用率都超过 {:.0}%，核心网格中以斜线标出",
+                                SMT_CONTENTION_THRESHOLD
+                            ));
+                    }
+                    draw_smt_security_note(ui, cpu_info);
+                });
                 ui.end_row();
 
                 ui.label(RichText::new("总使用率").color(Color32::from_gray(160)));
@@ -241,6 +1212,10 @@ impl CpuMonitorPanel {
                 ui.label(RichText::new(usage_text).size(18.0).strong().color(usage_to_color(cpu_info.total_usage_percent)));
                 ui.end_row();
 
+                ui.label(RichText::new("空闲分布").color(Color32::from_gray(160)));
+                draw_idle_distribution(ui, cpu_info);
+                ui.end_row();
+
                 if cpu_info.max_frequency_mhz > 0 {
                     ui.label(RichText::new("频率范围").color(Color32::from_gray(160)));
                     ui.label(format!(
@@ -251,6 +1226,180 @@ impl CpuMonitorPanel {
                     ui.end_row();
                 }
             });
+
+        if !cpu_info.vulnerabilities.is_empty() {
+            ui.add_space(8.0);
+            draw_vulnerabilities(ui, &cpu_info.vulnerabilities);
+        }
+    }
+
+    /// 绘制选中核心的详情
+    fn draw_selected_core(&mut self, ui: &mut Ui, cpu_info: &CpuInfo) {
+        let Some(core) = self.selected_core.and_then(|id| cpu_info.cores.get(id)) else {
+            return;
+        };
+
+        ui.add_space(20.0);
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("CPU {}", core.cpu_id)).size(14.0).strong());
+            if ui.small_button("✕").clicked() {
+                close = true;
+            }
+        });
+        ui.add_space(8.0);
+
+        let governor = policy_for_cpu(&self.cpufreq_policies, core.cpu_id).and_then(|p| p.governor.clone());
+        egui::Grid::new("selected_core")
+            .num_columns(2)
+            .spacing([16.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("物理核心").color(Color32::from_gray(160)));
+                ui.label(format!("{} (封装 {}, NUMA {})", core.core_id, core.package_id, core.numa_node));
+                ui.end_row();
+
+                ui.label(RichText::new("类型").color(Color32::from_gray(160)));
+                ui.label(format!("{:?}", core.core_type));
+                ui.end_row();
+
+                ui.label(RichText::new("使用率").color(Color32::from_gray(160)));
+                ui.label(RichText::new(format!("{:.1}%", core.usage_percent)).color(usage_to_color(core.usage_percent)));
+                ui.end_row();
+
+                if let Some(breakdown) = core.usage_breakdown {
+                    ui.label(RichText::new("分项").color(Color32::from_gray(160)));
+                    ui.label(format!(
+                        "用户 {:.1}% · 内核 {:.1}% · 中断 {:.1}% · iowait {:.1}% · steal {:.1}%",
+                        breakdown.user, breakdown.system, breakdown.irq, breakdown.iowait, breakdown.steal
+                    ));
+                    ui.end_row();
+                }
+
+                ui.label(RichText::new("频率").color(Color32::from_gray(160)));
+                ui.label(format!("{} MHz", core.frequency_mhz));
+                ui.end_row();
+
+                if let Some(cppc) = core.cppc {
+                    ui.label(RichText::new("标称 / 最高").color(Color32::from_gray(160)));
+                    let max = cppc.max_mhz.map_or_else(|| "-".to_string(), |mhz| mhz.to_string());
+                    ui.label(format!("{} / {} MHz", cppc.nominal_mhz, max))
+                        .on_hover_text("ACPI CPPC 报告的标称频率（持续负载下应能保持）和最高频率");
+                    ui.end_row();
+                }
+
+                if let Some(turbo) = core.turbo_max_mhz {
+                    ui.label(RichText::new("最大睿频").color(Color32::from_gray(160)));
+                    ui.label(format!("{} MHz", turbo));
+                    ui.end_row();
+                }
+
+                if let Some(limits) = policy_for_cpu(&self.cpufreq_policies, core.cpu_id).map(CpufreqPolicy::limits) {
+                    ui.label(RichText::new("频率范围").color(Color32::from_gray(160)));
+                    let text = format!(
+                        "{} - {} MHz（硬件 {} - {} MHz）",
+                        limits.min_mhz, limits.max_mhz, limits.hw_min_mhz, limits.hw_max_mhz
+                    );
+                    if limits.is_capped() {
+                        ui.label(RichText::new(text).color(FREQ_CAPPED_COLOR))
+                            .on_hover_text("scaling_max_freq 低于硬件最高频率：被用户或散热守护进程限制");
+                    } else {
+                        ui.label(text);
+                    }
+                    ui.end_row();
+                }
+
+                if let Some(governor) = &governor {
+                    ui.label(RichText::new("调速器").color(Color32::from_gray(160)));
+                    ui.label(governor).on_hover_text("右键点击核心可切换");
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(8.0);
+        draw_frequency_gauge(ui, core.frequency_mhz, cpu_info.base_frequency_mhz, core.turbo_max_mhz, cpu_info.max_frequency_mhz);
+
+        let cpu_id = core.cpu_id;
+        if let Some(policy) = policy_for_cpu(&self.cpufreq_policies, cpu_id).cloned() {
+            ui.add_space(8.0);
+            self.draw_policy_limits(ui, &policy);
+        }
+
+        if close {
+            self.selected_core = None;
+        }
+    }
+
+    /// 选中核心所在策略的频率范围：双端滑块，松开时写入
+    fn draw_policy_limits(&mut self, ui: &mut Ui, policy: &CpufreqPolicy) {
+        let (mut low, mut high) = match &self.freq_draft {
+            Some((path, low, high)) if *path == policy.path => (*low, *high),
+            _ => (khz_to_mhz(policy.min_khz), khz_to_mhz(policy.max_khz)),
+        };
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("频率限制").color(Color32::from_gray(160)));
+            ui.label(RichText::new(format!("{} - {} MHz", low, high)).monospace());
+        });
+        let range = khz_to_mhz(policy.hw_min_khz)..=khz_to_mhz(policy.hw_max_khz);
+        let response = ui.add(RangeSlider::new(&mut low, &mut high, range))
+            .on_hover_text(format!(
+                "{} 控制 CPU {}，修改会同时作用于这些核心",
+                policy.name(),
+                format_cpu_list(&policy.cpus)
+            ));
+        if response.changed() {
+            self.freq_draft = Some((policy.path.clone(), low, high));
+        }
+        if response.drag_stopped() {
+            self.freq_draft = None;
+            let result = policy.set_limits(low, high);
+            self.finish_frequency_write(result, format!("CPU {}: {} - {} MHz", format_cpu_list(&policy.cpus), low, high));
+        }
+        if policy.cpus.len() > 1 {
+            ui.label(RichText::new(format!("与 CPU {} 共享 {}", format_cpu_list(&policy.cpus), policy.name()))
+                .size(11.0).color(Color32::from_gray(140)));
+        }
+    }
+
+    /// 全局最大频率限制（快速降温 / 降噪）
+    fn draw_frequency_limit(&mut self, ui: &mut Ui) {
+        if self.cpufreq_policies.is_empty() {
+            return;
+        }
+        let hw_min = self.cpufreq_policies.iter().map(|p| khz_to_mhz(p.hw_min_khz)).min().unwrap_or(0);
+        let hw_max = self.cpufreq_policies.iter().map(|p| khz_to_mhz(p.hw_max_khz)).max().unwrap_or(0);
+        let current_max = self.cpufreq_policies.iter().map(|p| khz_to_mhz(p.max_khz)).max().unwrap_or(hw_max);
+
+        ui.add_space(12.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("限制最大频率").color(Color32::from_gray(160)));
+            ui.label(RichText::new(format_mhz_range(self.cpufreq_policies.iter().map(|p| p.max_khz))).monospace())
+                .on_hover_text("各策略当前的最高频率，不一致时显示范围");
+        });
+        ui.horizontal(|ui| {
+            let mut draft = self.max_freq_draft.unwrap_or(current_max);
+            if ui.add(egui::Slider::new(&mut draft, hw_min..=hw_max).suffix(" MHz")).changed() {
+                self.max_freq_draft = Some(draft);
+            }
+            if ui.add_enabled(self.max_freq_draft.is_some(), egui::Button::new("应用")).clicked() {
+                self.max_freq_draft = None;
+                let result = limit_max_frequency(&self.cpufreq_policies, draft);
+                self.finish_frequency_write(result, format!("最高频率已限制为 {} MHz", draft));
+            }
+            if ui.button("恢复").on_hover_text("把最高频率恢复为硬件上限").clicked() {
+                self.max_freq_draft = None;
+                let result = limit_max_frequency(&self.cpufreq_policies, hw_max);
+                self.finish_frequency_write(result, "已恢复硬件最高频率".to_string());
+            }
+        });
+        match &self.freq_message {
+            Some(Ok(message)) => {
+                ui.label(RichText::new(message).size(12.0).color(Color32::from_rgb(100, 200, 100)));
+            }
+            Some(Err(error)) => {
+                ui.label(RichText::new(error).size(12.0).color(Color32::from_rgb(255, 100, 100)));
+            }
+            None => {}
+        }
     }
 
     /// 绘制缓存信息
@@ -263,17 +1412,12 @@ impl CpuMonitorPanel {
         ui.add_space(8.0);
 
         for cache in &cpu_info.l3_caches {
-            let (label, color) = if cache.is_vcache {
-                (
-                    format!("CCD {}: {} MB (3D V-Cache)", cache.id, cache.size_kb / 1024),
-                    Color32::from_rgb(100, 200, 100),
-                )
+            let label = if cache.is_vcache {
+                format!("CCD {}: {} MB (3D V-Cache)", cache.id, cache.size_kb / 1024)
             } else {
-                (
-                    format!("CCD {}: {} MB", cache.id, cache.size_kb / 1024),
-                    Color32::from_gray(180),
-                )
+                format!("CCD {}: {} MB", cache.id, cache.size_kb / 1024)
             };
+            let color = self.ccd_palette.color(cache.id).unwrap_or(Color32::from_gray(180));
 
             ui.horizontal(|ui| {
                 ui.add_space(8.0);
@@ -284,26 +1428,83 @@ impl CpuMonitorPanel {
     }
 
     /// 绘制历史曲线图
-    fn draw_history_chart(&self, ui: &mut Ui, history: &CpuHistory, cpu_info: &CpuInfo) {
+    fn draw_history_chart(&mut self, ui: &mut Ui, history: &CpuHistory, cpu_info: &CpuInfo, start_wall_time: SystemTime) {
         ui.horizontal(|ui| {
             ui.label(RichText::new("使用率历史").size(16.0).strong());
             ui.add_space(20.0);
             ui.label(RichText::new(format!("当前: {:.1}%", cpu_info.total_usage_percent))
                 .color(usage_to_color(cpu_info.total_usage_percent)));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.selectable_value(&mut self.wall_clock_axis, true, "系统时间");
+                ui.selectable_value(&mut self.wall_clock_axis, false, "相对时间");
+            });
         });
         ui.add_space(8.0);
 
-        let plot_data = history.plot_data();
-        if plot_data.is_empty() {
+        let (plot, time_offset) = if self.wall_clock_axis {
+            let offset = start_wall_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+            (history.wall_clock_plot_segments(start_wall_time), offset)
+        } else {
+            (history.plot_segments(), 0.0)
+        };
+        if plot.segments.is_empty() {
             ui.label("收集数据中...");
             return;
         }
 
-        let line = Line::new(PlotPoints::new(plot_data))
-            .color(Color32::from_rgb(100, 180, 255))
-            .width(2.0)
-            .fill(0.0);
+        // 休眠等间断处断开曲线，避免画出一段并不存在的连续数据
+        let lines: Vec<Line> = plot
+            .segments
+            .into_iter()
+            .map(|segment| {
+                Line::new(PlotPoints::new(segment))
+                    .color(Color32::from_rgb(100, 180, 255))
+                    .width(2.0)
+                    .fill(0.0)
+                    .name("使用率")
+            })
+            .collect();
 
+        // CPU 压力曲线（内核支持 PSI 时）
+        let pressure_data: Vec<[f64; 2]> = history
+            .pressure_plot_data()
+            .into_iter()
+            .map(|[t, p]| [t + time_offset, p])
+            .collect();
+        let pressure_lines: Vec<Line> = split_at_gaps(pressure_data, &plot.gaps)
+            .into_iter()
+            .map(|segment| {
+                Line::new(PlotPoints::new(segment))
+                    .color(Color32::from_rgb(255, 150, 80))
+                    .width(1.5)
+                    .name("压力 (PSI)")
+            })
+            .collect();
+        // 内核态和 iowait 比例
+        let (system_data, iowait_data) = history.breakdown_plot_data();
+        let shift = |data: Vec<[f64; 2]>| -> Vec<[f64; 2]> { data.into_iter().map(|[t, v]| [t + time_offset, v]).collect() };
+        let breakdown_lines: Vec<Line> = split_at_gaps(shift(system_data), &plot.gaps)
+            .into_iter()
+            .map(|segment| {
+                Line::new(PlotPoints::new(segment))
+                    .color(Color32::from_rgb(200, 130, 255))
+                    .width(1.5)
+                    .name("内核态")
+            })
+            .chain(split_at_gaps(shift(iowait_data), &plot.gaps).into_iter().map(|segment| {
+                Line::new(PlotPoints::new(segment))
+                    .color(Color32::from_rgb(140, 160, 220))
+                    .width(1.0)
+                    .style(LineStyle::dashed_dense())
+                    .name("iowait")
+            }))
+            .collect();
+        if !plot.gaps.is_empty() {
+            ui.label(RichText::new(format!("{} 处间断（系统休眠或 hexin 停顿），以虚线标出", plot.gaps.len()))
+                .size(11.0).color(Color32::from_gray(130)));
+        }
+
+        let wall_clock_axis = self.wall_clock_axis;
         Plot::new("cpu_history_plot")
             .height(160.0)
             .include_y(0.0)
@@ -311,23 +1512,278 @@ impl CpuMonitorPanel {
             .allow_drag(false)
             .allow_zoom(false)
             .allow_scroll(false)
-            .show_axes([false, true])
+            .show_axes([true, true])
+            .x_axis_formatter(move |mark, _| format_time_axis(mark.value, wall_clock_axis))
             .y_axis_label("使用率 %")
             .show_grid(true)
             .show(ui, |plot_ui| {
-                plot_ui.line(line);
+                for line in lines.into_iter().chain(pressure_lines).chain(breakdown_lines) {
+                    plot_ui.line(line);
+                }
+                draw_gap_markers(plot_ui, &plot.gaps);
             });
     }
 }
 
 impl Default for CpuMonitorPanel {
     fn default() -> Self {
-        Self::new()
+        Self::new(DEFAULT_ANIMATION_SMOOTHING)
+    }
+}
+
+/// 格式化历史图表横轴：系统时间显示为本地 HH:MM:SS，相对时间显示为秒
+fn format_time_axis(value: f64, wall_clock: bool) -> String {
+    if !wall_clock {
+        return format!("{:.0}s", value);
+    }
+    Local
+        .timestamp_opt(value.floor() as i64, 0)
+        .single()
+        .map(|time| time.format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// 绘制频率条：当前频率填充，基础频率和最大睿频以刻度标出
+fn draw_frequency_gauge(ui: &mut Ui, freq_mhz: u64, base_mhz: u64, turbo_mhz: Option<u64>, max_mhz: u64) {
+    let scale = max_mhz.max(turbo_mhz.unwrap_or(0)).max(freq_mhz).max(1) as f32;
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(260.0, 12.0), egui::Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let painter = ui.painter();
+    painter.rect_filled(rect, 3.0, Color32::from_gray(45));
+    let fill = egui::Rect::from_min_size(
+        rect.left_top(),
+        Vec2::new(rect.width() * (freq_mhz as f32 / scale).min(1.0), rect.height()),
+    );
+    painter.rect_filled(fill, 3.0, Color32::from_rgb(100, 150, 255));
+
+    let tick = |mhz: u64, color: Color32| {
+        let x = rect.left() + rect.width() * (mhz as f32 / scale).min(1.0);
+        painter.line_segment(
+            [egui::pos2(x, rect.top() - 3.0), egui::pos2(x, rect.bottom() + 3.0)],
+            Stroke::new(2.0, color),
+        );
+    };
+    let mut hover = format!("当前: {} MHz", freq_mhz);
+    if base_mhz > 0 {
+        tick(base_mhz, Color32::from_gray(200));
+        hover.push_str(&format!("\n基础频率: {} MHz", base_mhz));
+    }
+    match turbo_mhz {
+        Some(turbo) => {
+            tick(turbo, Color32::from_rgb(255, 160, 60));
+            hover.push_str(&format!("\n最大睿频 (MSR): {} MHz", turbo));
+        }
+        None => hover.push_str("\n最大睿频: 不可用（需要 Intel CPU、msr 驱动和 CAP_SYS_RAWIO）"),
+    }
+    response.on_hover_text(hover);
+}
+
+/// 绘制各 CCD 自启动（或重置）以来累计消耗的 CPU 时间
+fn draw_ccd_core_seconds(ui: &mut Ui, cpu_info: &CpuInfo, history: &mut CpuHistory, palette: &CcdPalette) {
+    let cores_by_l3 = cores_by_l3(&cpu_info.cores);
+    if cores_by_l3.len() < 2 {
+        return;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for (id, cores) in &cores_by_l3 {
+            let seconds = history.core_seconds(cores.iter().map(|c| c.cpu_id));
+            ui.label(RichText::new(format!("CCD{}: {} 核·秒", id, format_thousands(seconds.round() as u64)))
+                .size(12.0).color(palette.color(*id).unwrap_or(Color32::from_gray(180))))
+                .on_hover_text("自启动或上次重置以来，各 CCD 上所有核心消耗的 CPU 时间之和");
+        }
+        if ui.small_button("重置").clicked() {
+            history.reset_core_seconds();
+        }
+    });
+}
+
+/// 千位分隔（空格），如 1380 -> "1 380"
+fn format_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(' ');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 绘制跟踪进程的所在核心和迁移次数曲线
+fn draw_tracked_process(ui: &mut Ui, tracked: &ProcessHistory) {
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("■").color(RUNNING_HIGHLIGHT_COLOR));
+        let location = match tracked.current_cpu() {
+            Some(cpu) => format!("PID {} 正在 CPU {}", tracked.pid(), cpu),
+            None => format!("PID {} 已退出或无法读取", tracked.pid()),
+        };
+        ui.label(RichText::new(location).size(12.0));
+        ui.label(RichText::new(format!("迁移 {} 次", tracked.migration_count()))
+            .size(12.0)
+            .color(Color32::from_gray(160)))
+            .on_hover_text("主线程在两次采样之间换了核心即计一次；迁移频繁说明调度器在来回搬动该线程，可考虑绑定亲和性");
+    });
+
+    let data = tracked.migration_plot_data();
+    if data.len() < 2 {
+        return;
+    }
+    let line = Line::new(PlotPoints::new(data))
+        .color(RUNNING_HIGHLIGHT_COLOR)
+        .width(1.5)
+        .name("累计迁移");
+    Plot::new("tracked_migration_plot")
+        .height(60.0)
+        .width(260.0)
+        .include_y(0.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show_axes([false, true])
+        .show(ui, |plot_ui| plot_ui.line(line));
+}
+
+/// 调度健康评分：分数按高低着色，悬停显示各项扣分
+fn draw_health_score(ui: &mut Ui, score: u8, factors: &[(String, f32)]) {
+    let color = if score >= 80 {
+        Color32::from_rgb(100, 200, 100)
+    } else if score >= 50 {
+        Color32::from_rgb(255, 200, 100)
+    } else {
+        Color32::from_rgb(255, 100, 100)
+    };
+    let mut tooltip = String::from("调度健康评分：满分 100，按下列各项扣分");
+    for (factor, penalty) in factors {
+        tooltip.push_str(&format!("\n{}: -{:.1}", factor, penalty));
+    }
+    ui.label(RichText::new(score.to_string()).size(22.0).strong().color(color))
+        .on_hover_text(tooltip);
+    ui.label(RichText::new("调度健康").color(Color32::from_gray(160)));
+}
+
+/// 在 SMT 状态旁说明与 SMT 有关的漏洞缓解，开关 SMT 前应了解其安全影响
+fn draw_smt_security_note(ui: &mut Ui, cpu_info: &CpuInfo) {
+    let names = |filter: fn(&Vulnerability) -> bool| -> Vec<String> {
+        cpu_info.vulnerabilities.iter().filter(|v| filter(v)).map(|v| v.name.to_uppercase()).collect()
+    };
+    let vulnerable = names(Vulnerability::smt_vulnerable);
+    if cpu_info.smt_enabled && !vulnerable.is_empty() {
+        ui.label(RichText::new(format!("⚠ SMT 下未完全缓解: {}", vulnerable.join(", ")))
+            .color(Color32::from_rgb(255, 200, 100)))
+            .on_hover_text(
+                "内核报告这些漏洞在启用 SMT 时仍可被同一物理核心的兄弟线程利用\n\
+                 为性能保留 SMT 时请了解这一风险；禁用 SMT（nosmt）可以完全缓解",
+            );
+        return;
+    }
+    let disabled = names(|v| v.smt.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("SMT disabled")));
+    if !disabled.is_empty() {
+        ui.label(RichText::new(format!("为缓解 {} 而禁用", disabled.join(", "))).color(Color32::from_gray(140)))
+            .on_hover_text("内核报告 SMT 已禁用，相关漏洞因此得到完全缓解；重新启用 SMT 会失去这部分保护");
+    }
+}
+
+/// 绘制可折叠的漏洞缓解列表
+fn draw_vulnerabilities(ui: &mut Ui, vulnerabilities: &[Vulnerability]) {
+    let affected = vulnerabilities.iter().filter(|v| v.state != MitigationState::NotAffected).count();
+    egui::CollapsingHeader::new(RichText::new(format!("安全缓解 · {} 项受影响", affected)).color(Color32::from_gray(180)))
+        .id_salt("cpu_vulnerabilities")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new("cpu_vulnerabilities_grid")
+                .num_columns(2)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    for vulnerability in vulnerabilities {
+                        let color = match vulnerability.state {
+                            MitigationState::NotAffected => Color32::from_gray(120),
+                            MitigationState::Mitigated => Color32::from_rgb(100, 200, 100),
+                            MitigationState::Vulnerable => Color32::from_rgb(255, 100, 100),
+                            MitigationState::Unknown => Color32::from_gray(160),
+                        };
+                        ui.label(RichText::new(&vulnerability.name).monospace().color(Color32::from_gray(160)));
+                        let label = if vulnerability.smt_vulnerable() {
+                            RichText::new(format!("{} · SMT 下存在漏洞", vulnerability.state.display_name()))
+                                .color(Color32::from_rgb(255, 200, 100))
+                        } else {
+                            RichText::new(vulnerability.state.display_name()).color(color)
+                        };
+                        ui.label(label).on_hover_text(&vulnerability.status);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// 绘制空闲分布堆叠条：C0 (运行) / C1 (浅睡眠) / C2+ (深睡眠)
+fn draw_idle_distribution(ui: &mut Ui, cpu_info: &CpuInfo) {
+    let distribution = cpu_info.idle_distribution();
+    if distribution.is_empty() {
+        ui.label(
+            RichText::new(format!("空闲 {:.1}% (无 C-state 数据)", cpu_info.mean_idle_percent()))
+                .color(Color32::from_gray(140)),
+        );
+        return;
+    }
+
+    let (light, deep) = split_idle_distribution(&distribution);
+    let segments = [
+        ("C0 运行", (100.0 - light - deep).max(0.0), Color32::from_rgb(230, 150, 60)),
+        ("C1 浅睡眠", light, Color32::from_rgb(100, 160, 220)),
+        ("C2+ 深睡眠", deep, Color32::from_rgb(60, 90, 160)),
+    ];
+
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(200.0, 14.0), egui::Sense::hover());
+    if ui.is_rect_visible(rect) {
+        let painter = ui.painter();
+        painter.rect_filled(rect, 3.0, Color32::from_gray(45));
+        let mut x = rect.left();
+        for (_, pct, color) in segments {
+            let width = rect.width() * (pct / 100.0).clamp(0.0, 1.0);
+            let segment = egui::Rect::from_min_size(egui::pos2(x, rect.top()), Vec2::new(width, rect.height()));
+            painter.rect_filled(segment.intersect(rect), 0.0, color);
+            x += width;
+        }
+    }
+
+    let mut states: Vec<(&String, &f32)> = distribution.iter().collect();
+    states.sort_by(|a, b| a.0.cmp(b.0));
+    response.on_hover_ui(|ui| {
+        for (label, pct, color) in segments {
+            ui.label(RichText::new(format!("{}: {:.1}%", label, pct)).color(color));
+        }
+        ui.separator();
+        for (name, pct) in states {
+            ui.label(format!("{}: {:.1}%", name, pct));
+        }
+    });
+}
+
+/// 在单元格上叠加半透明斜线
+fn draw_hatching(painter: &egui::Painter, rect: egui::Rect, color: Color32) {
+    let painter = painter.with_clip_rect(rect.shrink(1.0));
+    let stroke = Stroke::new(1.5, color);
+    let mut offset = -rect.height();
+    while offset < rect.width() {
+        painter.line_segment(
+            [
+                egui::pos2(rect.left() + offset, rect.bottom()),
+                egui::pos2(rect.left() + offset + rect.height(), rect.top()),
+            ],
+            stroke,
+        );
+        offset += 8.0;
     }
 }
 
 /// 使用率转颜色（渐变）
-fn usage_to_color(usage: f32) -> Color32 {
+pub fn usage_to_color(usage: f32) -> Color32 {
     let t = (usage / 100.0).clamp(0.0, 1.0);
 
     if t < 0.5 {
@@ -348,3 +1804,48 @@ fn usage_to_color(usage: f32) -> Color32 {
         )
     }
 }
+
+/// 调速器角标：performance 为 P，powersave / schedutil 都为 S 但颜色不同，其他取首字母
+fn governor_badge(governor: &str) -> (String, Color32) {
+    match governor {
+        "performance" => ("P".to_string(), GOVERNOR_PERFORMANCE_COLOR),
+        "powersave" => ("S".to_string(), GOVERNOR_POWERSAVE_COLOR),
+        "schedutil" => ("S".to_string(), GOVERNOR_SCHEDUTIL_COLOR),
+        other => (other.chars().next().map_or_else(String::new, |c| c.to_ascii_uppercase().to_string()), GOVERNOR_OTHER_COLOR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compared_cores_eviction() {
+        let mut panel = CpuMonitorPanel::new(0.0);
+        panel.set_max_compared_cores(3);
+        for cpu_id in [4, 1, 7] {
+            panel.toggle_compared(cpu_id);
+        }
+        assert_eq!(panel.compared_cores, vec![4, 1, 7]);
+
+        // 已满时替换最早加入的核心
+        panel.toggle_compared(2);
+        assert_eq!(panel.compared_cores, vec![1, 7, 2]);
+        panel.toggle_compared(9);
+        assert_eq!(panel.compared_cores, vec![7, 2, 9]);
+
+        // 再次点击移出对比，空出的位置不淘汰其他核心
+        panel.toggle_compared(2);
+        assert_eq!(panel.compared_cores, vec![7, 9]);
+        panel.toggle_compared(3);
+        assert_eq!(panel.compared_cores, vec![7, 9, 3]);
+
+        // 调低上限时淘汰最早加入的核心，上限至少为 1
+        panel.set_max_compared_cores(2);
+        assert_eq!(panel.compared_cores, vec![9, 3]);
+        panel.set_max_compared_cores(0);
+        assert_eq!(panel.compared_cores, vec![3]);
+        panel.toggle_compared(5);
+        assert_eq!(panel.compared_cores, vec![5]);
+    }
+}
@@ -0,0 +1,156 @@
+//! 调试面板 - 负载生成器，在选中的核心上产生已知负载
+
+use eframe::egui::{self, Color32, Frame, Margin, RichText, Rounding, Slider, Ui};
+use std::time::Duration;
+
+use crate::system::{format_cpu_list, CpuInfo, LoadGenerator};
+use crate::ui::theme::RESERVED_CORE_COLOR;
+
+/// 调试面板
+pub struct DebugPanel {
+    /// 负载生成器
+    generator: LoadGenerator,
+    /// 选中的核心（按 CPU ID 索引）
+    selected: Vec<bool>,
+    /// 启动失败的原因
+    error: Option<String>,
+    /// 保留给系统的核心
+    reserved_cores: Vec<usize>,
+}
+
+impl DebugPanel {
+    pub fn new() -> Self {
+        Self {
+            generator: LoadGenerator::new(),
+            selected: Vec::new(),
+            error: None,
+            reserved_cores: Vec::new(),
+        }
+    }
+
+    /// 设置保留核心（在核心选择中标记）
+    pub fn set_reserved_cores(&mut self, reserved_cores: Vec<usize>) {
+        self.reserved_cores = reserved_cores;
+    }
+
+    /// 停止所有负载线程（关闭调试工具或退出时）
+    pub fn stop(&mut self) {
+        self.generator.stop();
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, cpu_info: &CpuInfo) {
+        self.selected.resize(cpu_info.logical_cores, false);
+
+        Frame::none()
+            .fill(Color32::from_rgb(70, 60, 30))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                ui.label(RichText::new("⚠ 调试工具").strong().color(Color32::from_rgb(255, 220, 150)));
+                ui.label(RichText::new("负载生成器会在选中的核心上运行真实的忙循环，用于验证核心网格、图表和亲和性，不要在需要性能的场合使用")
+                    .size(12.0).color(Color32::from_gray(200)));
+            });
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                ui.label(RichText::new("负载生成器").size(16.0).strong());
+                ui.add_space(4.0);
+                ui.label(RichText::new("每个选中的核心一个绑定的线程，按占空比在 100ms 周期内忙等和睡眠")
+                    .size(11.0).color(Color32::from_gray(140)));
+                ui.add_space(12.0);
+
+                let running = self.generator.is_running();
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for (cpu, selected) in self.selected.iter_mut().enumerate() {
+                            if self.reserved_cores.contains(&cpu) {
+                                ui.checkbox(selected, RichText::new(cpu.to_string()).color(RESERVED_CORE_COLOR))
+                                    .on_hover_text("保留给系统的核心");
+                            } else {
+                                ui.checkbox(selected, cpu.to_string());
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.small_button("全选").clicked() {
+                            self.selected.fill(true);
+                        }
+                        if ui.small_button("清除").clicked() {
+                            self.selected.fill(false);
+                        }
+                    });
+                });
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("占空比").color(Color32::from_gray(160)));
+                    let mut duty = self.generator.duty_percent();
+                    if ui.add(Slider::new(&mut duty, 0..=100).suffix("%")).changed() {
+                        self.generator.set_duty_percent(duty);
+                    }
+                });
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    if running {
+                        if ui.button("停止").clicked() {
+                            self.generator.stop();
+                        }
+                    } else if ui.button("启动").clicked() {
+                        let cores: Vec<usize> = self
+                            .selected
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, &selected)| selected)
+                            .map(|(cpu, _)| cpu)
+                            .collect();
+                        self.error = self.generator.start(&cores).err();
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.label(RichText::new(error).size(12.0).color(Color32::from_rgb(255, 100, 100)));
+                }
+
+                let status = self.generator.status();
+                if !status.is_empty() {
+                    ui.add_space(12.0);
+                    let cores: Vec<usize> = status.iter().map(|s| s.cpu).collect();
+                    ui.label(RichText::new(format!("运行中: CPU {}", format_cpu_list(&cores)))
+                        .color(Color32::from_rgb(100, 180, 255)));
+                    ui.add_space(4.0);
+                    egui::Grid::new("load_workers").num_columns(4).spacing([16.0, 4.0]).show(ui, |ui| {
+                        for header in ["绑定核心", "TID", "最近运行于", "越界采样"] {
+                            ui.label(RichText::new(header).size(12.0).color(Color32::from_gray(140)));
+                        }
+                        ui.end_row();
+                        for worker in &status {
+                            ui.label(worker.cpu.to_string());
+                            ui.label(worker.tid.to_string());
+                            ui.label(worker.last_cpu.map_or_else(|| "-".to_string(), |cpu| cpu.to_string()));
+                            let color = if worker.off_target == 0 {
+                                Color32::from_rgb(100, 200, 100)
+                            } else {
+                                Color32::from_rgb(255, 100, 100)
+                            };
+                            ui.label(RichText::new(format!("{} / {}", worker.off_target, worker.samples)).color(color))
+                                .on_hover_text("在绑定核心之外运行的采样次数，亲和性生效时应为 0");
+                            ui.end_row();
+                        }
+                    });
+                    ui.ctx().request_repaint_after(Duration::from_millis(500));
+                }
+            });
+    }
+}
+
+impl Default for DebugPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,90 @@
+//! 权限与限制窗口 - 把检查发现和操作失败时遇到的权限、功能可用性问题列成一份清单，附带解决办法和命令
+
+use eframe::egui::{self, Color32, Context, Frame, Margin, RichText, Rounding, ScrollArea};
+
+use crate::system::Limitations;
+use crate::ui::CommandLine;
+
+/// 权限与限制窗口
+#[derive(Default)]
+pub struct LimitationsWindow {
+    open: bool,
+}
+
+impl LimitationsWindow {
+    /// 打开或关闭窗口
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// 绘制窗口，用户点击"重新检查"时返回 true
+    pub fn ui(&mut self, ctx: &Context, limitations: &mut Limitations) -> bool {
+        if !self.open {
+            return false;
+        }
+        let mut open = true;
+        let mut recheck = false;
+        let mut dismissed = None;
+        egui::Window::new("权限与限制")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("被阻止的操作、原因和解决办法；解决后点击重新检查")
+                        .size(12.0).color(Color32::from_gray(140)));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("重新检查").clicked() {
+                            recheck = true;
+                        }
+                    });
+                });
+                ui.add_space(8.0);
+
+                if limitations.entries().is_empty() {
+                    ui.label(RichText::new("✓ 没有发现权限或功能限制").color(Color32::from_rgb(100, 200, 100)));
+                    return;
+                }
+
+                ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+                    for entry in limitations.entries() {
+                        Frame::none()
+                            .fill(Color32::from_gray(40))
+                            .inner_margin(Margin::same(10.0))
+                            .rounding(Rounding::same(6.0))
+                            .show(ui, |ui| {
+                                ui.set_min_width(ui.available_width());
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new("⚠").color(Color32::from_rgb(255, 200, 100)));
+                                    ui.label(RichText::new(entry.kind.title()).strong().color(Color32::from_rgb(255, 220, 150)));
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        if ui.small_button("忽略")
+                                            .on_hover_text("从清单中移除，重新检查时仍存在的问题会再次出现")
+                                            .clicked()
+                                        {
+                                            dismissed = Some(entry.kind);
+                                        }
+                                    });
+                                });
+                                ui.label(RichText::new(entry.kind.cause()).size(12.0).color(Color32::from_gray(200)));
+                                if let Some(error) = &entry.last_error {
+                                    ui.label(RichText::new(format!("已失败 {} 次，最近一次: {}", entry.failures, error))
+                                        .size(11.0).color(Color32::from_rgb(255, 100, 100)));
+                                }
+                                ui.add_space(4.0);
+                                ui.label(RichText::new(format!("解决办法: {}", entry.kind.remedy()))
+                                    .size(12.0).color(Color32::from_rgb(100, 200, 100)));
+                                ui.add(CommandLine::new(&limitations.command(entry.kind), entry.kind));
+                            });
+                        ui.add_space(6.0);
+                    }
+                });
+            });
+
+        if let Some(kind) = dismissed {
+            limitations.dismiss(kind);
+        }
+        self.open = open;
+        recheck
+    }
+}
@@ -1,8 +1,22 @@
+pub mod conflict;
 pub mod cpu_monitor;
+pub mod debug;
+pub mod limitations;
+pub mod onboarding;
 pub mod process_list;
 pub mod scheduler;
+pub mod settings;
 pub mod charts;
+pub mod theme;
+pub mod watchlist;
+pub mod widgets;
 
 pub use cpu_monitor::CpuMonitorPanel;
+pub use debug::DebugPanel;
+pub use limitations::LimitationsWindow;
+pub use onboarding::OnboardingOverlay;
 pub use process_list::ProcessListPanel;
-pub use scheduler::SchedulerPanel;
+pub use scheduler::{DeferredEdit, SchedulerPanel};
+pub use settings::{ProfileAction, SettingsPanel};
+pub use watchlist::WatchlistPanel;
+pub use widgets::{CommandLine, HoldButton, RangeSlider};
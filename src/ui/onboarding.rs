@@ -0,0 +1,197 @@
+//! 首次运行的使用说明 - 介绍标签页、核心颜色和修改调度设置所需的权限
+//! 首次启动时自动显示一次，之后可以通过标签栏的 "?" 重新打开
+
+use eframe::egui::{self, Align2, Color32, Context, Frame, Id, LayerId, Margin, Order, RichText, Rounding, Sense};
+
+use crate::system::PrivilegeInfo;
+use crate::ui::cpu_monitor::{usage_to_color, RUNNING_HIGHLIGHT_COLOR, SELECTION_COLOR};
+use crate::ui::theme::RESERVED_CORE_COLOR;
+
+/// 说明页正文之后的附加内容
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PageExtra {
+    None,
+    /// 核心颜色图例
+    CoreLegend,
+    /// hexin 当前的权限
+    Privilege,
+}
+
+/// 说明页
+struct HelpPage {
+    title: &'static str,
+    paragraphs: &'static [&'static str],
+    extra: PageExtra,
+}
+
+/// 说明页内容（顺序即翻页顺序）
+const PAGES: &[HelpPage] = &[
+    HelpPage {
+        title: "标签页",
+        paragraphs: &[
+            "CPU 监控：核心网格、CPU 信息和历史曲线。点击核心查看详情，可以把核心加入对比图或从选择创建预设。",
+            "进程管理：搜索、筛选和排序进程，修改单个进程或整组进程的亲和性、调度策略和优先级。",
+            "调度策略：管理预设、自动规则和调优档案，自动规则会在匹配的进程启动时生效。",
+            "设置：刷新间隔、核心网格显示、CCD 颜色、保留核心等。",
+        ],
+        extra: PageExtra::None,
+    },
+    HelpPage {
+        title: "核心颜色",
+        paragraphs: &[
+            "单元格自底向上的填充表示使用率，颜色从绿色经黄色到红色。",
+            "多 CCD 时边框为所属 CCD 的颜色（3D V-Cache CCD 默认为绿色），Intel 混合架构上蓝色边框为性能核心、橙色为能效核心。",
+            "斜线表示同一物理核心的两个 SMT 线程都很繁忙，它们在争用同一个核心。",
+        ],
+        extra: PageExtra::CoreLegend,
+    },
+    HelpPage {
+        title: "权限",
+        paragraphs: &[
+            "普通用户只能修改自己的进程，并且不能降低 nice 值或使用实时调度策略。",
+            "修改其他用户的进程、设置负 nice 值或 FIFO / RR 策略需要 root 或 CAP_SYS_NICE；频率限制等 sysfs 设置需要 root。",
+            "权限不足时操作会失败并显示原因。本次会话的所有修改都可以用标签栏的\"还原全部更改\"撤销。",
+        ],
+        extra: PageExtra::Privilege,
+    },
+];
+
+/// 核心颜色页的图例
+fn core_legend() -> [(Color32, &'static str); 6] {
+    [
+        (usage_to_color(10.0), "低使用率"),
+        (usage_to_color(50.0), "中等使用率"),
+        (usage_to_color(95.0), "高使用率"),
+        (RESERVED_CORE_COLOR, "右上角三角：保留给系统的核心"),
+        (SELECTION_COLOR, "选择模式下选中的核心"),
+        (RUNNING_HIGHLIGHT_COLOR, "进程管理中选中的进程当前所在的核心"),
+    ]
+}
+
+/// 使用说明浮层
+pub struct OnboardingOverlay {
+    /// 是否显示
+    open: bool,
+    /// 当前页
+    page: usize,
+    /// 打开时检测的权限（用于权限页）
+    privilege: Option<PrivilegeInfo>,
+}
+
+impl OnboardingOverlay {
+    /// `open` 为 true 时首帧即显示（首次运行）
+    pub fn new(open: bool) -> Self {
+        Self {
+            open,
+            page: 0,
+            privilege: open.then(PrivilegeInfo::detect),
+        }
+    }
+
+    /// 从第一页重新打开
+    pub fn open(&mut self) {
+        self.open = true;
+        self.page = 0;
+        self.privilege = Some(PrivilegeInfo::detect());
+    }
+
+    /// 绘制浮层，用户关闭时返回 true
+    pub fn ui(&mut self, ctx: &Context) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        // 半透明遮罩，挡住下面面板的点击
+        let screen = ctx.screen_rect();
+        egui::Area::new(Id::new("onboarding_backdrop"))
+            .order(Order::PanelResizeLine)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                ui.allocate_rect(screen, Sense::click_and_drag());
+            });
+        ctx.layer_painter(LayerId::new(Order::PanelResizeLine, Id::new("onboarding_backdrop")))
+            .rect_filled(screen, 0.0, Color32::from_black_alpha(160));
+
+        let mut close = false;
+        let page = &PAGES[self.page];
+        egui::Area::new(Id::new("onboarding"))
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                Frame::none()
+                    .fill(Color32::from_gray(35))
+                    .inner_margin(Margin::same(16.0))
+                    .rounding(Rounding::same(8.0))
+                    .show(ui, |ui| {
+                        ui.set_width(480.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("欢迎使用 hexin").size(16.0).strong());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("✕").clicked() {
+                                    close = true;
+                                }
+                                ui.label(RichText::new(format!("{} / {}", self.page + 1, PAGES.len()))
+                                    .size(12.0).color(Color32::from_gray(140)));
+                            });
+                        });
+                        ui.add_space(8.0);
+                        ui.label(RichText::new(page.title).size(14.0).strong().color(Color32::from_rgb(100, 180, 255)));
+                        ui.add_space(6.0);
+                        for paragraph in page.paragraphs {
+                            ui.label(RichText::new(*paragraph).color(Color32::from_gray(210)));
+                            ui.add_space(4.0);
+                        }
+
+                        if page.extra == PageExtra::CoreLegend {
+                            ui.add_space(4.0);
+                            for (color, text) in core_legend() {
+                                ui.horizontal(|ui| {
+                                    let (rect, _) = ui.allocate_exact_size(egui::vec2(14.0, 14.0), Sense::hover());
+                                    ui.painter().rect_filled(rect, 3.0, color);
+                                    ui.label(RichText::new(text).size(12.0).color(Color32::from_gray(180)));
+                                });
+                            }
+                        }
+                        if let Some(privilege) = self.privilege.filter(|_| page.extra == PageExtra::Privilege) {
+                            ui.add_space(4.0);
+                            let (text, color) = if privilege.euid == 0 {
+                                ("当前以 root 运行，可以修改所有进程", Color32::from_rgb(100, 200, 100))
+                            } else if privilege.cap_sys_nice {
+                                ("当前拥有 CAP_SYS_NICE，可以修改所有进程的调度设置", Color32::from_rgb(100, 200, 100))
+                            } else {
+                                ("当前以普通用户运行，只能修改自己的进程", Color32::from_rgb(255, 200, 100))
+                            };
+                            ui.label(RichText::new(text).size(12.0).color(color));
+                        }
+
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(self.page > 0, egui::Button::new("上一页")).clicked() {
+                                self.page -= 1;
+                            }
+                            if self.page + 1 < PAGES.len() {
+                                if ui.button("下一页").clicked() {
+                                    self.page += 1;
+                                }
+                            } else if ui.button("开始使用").clicked() {
+                                close = true;
+                            }
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(RichText::new("可随时点击标签栏的 \"?\" 再次查看").size(11.0).color(Color32::from_gray(120)));
+                            });
+                        });
+                    });
+            });
+
+        if close {
+            self.open = false;
+        }
+        close
+    }
+}
+
+impl Default for OnboardingOverlay {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
@@ -1,35 +1,349 @@
 //! 进程列表面板
 
-use eframe::egui::{self, Color32, Frame, Margin, RichText, Rounding, ScrollArea, Stroke, TextEdit, Ui};
+use eframe::egui::{self, Color32, ComboBox, Frame, Margin, RichText, Rounding, ScrollArea, Stroke, TextEdit, TextStyle, Ui};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use crate::system::{
-    format_memory, set_process_affinity, ProcessInfo, ProcessManager, SortField,
+    affinity_diff, format_cpu_list, get_rt_priority, format_memory, read_process_memory_features, read_thp_setting,
+    set_thp_mode, terminate_process, is_sched_debug_readable, migration_deltas,
+    read_sched_debug, DelayAccountingWorker, DelaySample, SchedDebugTask,
+    AppliedBy, DetailsLoader, DetailsState, ProcessGroup, ProcessInfo, ProcessManager, ProcessMemoryFeatures, ThpSetting,
+    ContainerInfo, CpuInfo, ProcessOrigin, ProcessReport, SchedChange, SchedulePreset, SessionJournal, SortField,
+    default_report_path, l3_containing, preset_changes, reserved_among, unix_timestamp, L3CacheInfo,
+    RuleResolution, CgroupSampler, AutoRule,
 };
+use crate::app::TableDensity;
+use crate::ui::conflict::{ApplyGuard, CheckedApply, GroupCheck, ManualApply};
+use crate::ui::theme::{CcdPalette, RESERVED_CORE_COLOR};
+use crate::ui::widgets::truncate_chars;
+use crate::ui::{CommandLine, HoldButton};
+use crate::utils::ProcessHistory;
 
 /// 进程列表面板
 pub struct ProcessListPanel {
     /// 选中的进程 PID
     selected_pid: Option<u32>,
+    /// 多选的进程（Ctrl 点击 PID 切换），用于导出报告
+    selected_pids: HashSet<u32>,
+    /// 导出报告的保存路径（正在输入时为 Some）
+    export_path: Option<String>,
     /// 亲和性编辑模式
     editing_affinity: Option<u32>,
     /// 亲和性选择状态
     affinity_selection: Vec<bool>,
     /// 错误消息
     error_message: Option<String>,
+    /// 成功消息
+    success_message: Option<String>,
+    /// 按名称分组显示
+    grouped: bool,
+    /// 显示缺页速率列
+    show_faults: bool,
+    /// 已展开的分组（按进程名）
+    expanded_groups: HashSet<String>,
+    /// 内存特性缓存（按 PID：读取时间, 结果）
+    memory_features: HashMap<u32, (Instant, Option<ProcessMemoryFeatures>)>,
+    /// 系统 THP 设置缓存（与内存特性一起刷新）
+    thp_setting: Option<ThpSetting>,
+    /// 待应用的搜索过滤器（防抖）
+    filter_pending: Option<(String, Instant)>,
+    /// 下一帧让搜索框获得焦点
+    focus_search: bool,
+    /// 调度详情异步加载器（按 PID，每个显示详情的进程一个）
+    details_loaders: HashMap<u32, DetailsLoader>,
+    /// 延迟统计工作线程（未启用时为 None）
+    delay_worker: Option<DelayAccountingWorker>,
+    /// 延迟统计缓存（按 PID：读取时间, 结果）
+    delay_stats: HashMap<u32, (Instant, Result<DelaySample, String>)>,
+    /// 独立详情窗口（true 表示窗口打开）
+    detail_windows: HashMap<u32, bool>,
+    /// 待立即刷新的进程（打开详情或从右键菜单请求，绘制完列表后处理）
+    refresh_requests: Vec<u32>,
+    /// sched/debug 读取结果（按 PID，只在点击"读取"时更新）
+    sched_debug: HashMap<u32, SchedDebugView>,
+    /// sched/debug 是否可读（首次显示时检查）
+    sched_debug_readable: Option<bool>,
+    /// 监视列表中的 PID（每帧开始时从进程管理器复制）
+    watched: HashSet<u32>,
+    /// 待加入或移出监视列表的 PID
+    watch_toggles: Vec<u32>,
+    /// 手动应用前的检查状态（规则或拓扑变化时由应用更新）
+    apply_guard: ApplyGuard,
+    /// 等待确认提醒或规则冲突的亲和性修改
+    checked: CheckedApply,
+    /// 待规则引擎执行的冲突处理
+    rule_resolutions: Vec<RuleResolution>,
+    /// 右键菜单请求对同名进程应用的亲和性（模板 PID, 核心），绘制完列表后生成确认提示
+    bulk_affinity_request: Option<(u32, Vec<usize>)>,
+    /// 等待确认的同名进程亲和性批量应用
+    pending_bulk_affinity: Option<BulkAffinityPrompt>,
+    /// 分组菜单请求应用的预设（分组名, PID, 预设），绘制完列表后检查
+    group_preset_request: Option<(String, Vec<u32>, SchedulePreset)>,
+    /// 有提醒或规则冲突、等待确认的分组预设应用
+    pending_group_preset: Option<GroupPresetPrompt>,
+    /// 待规则引擎加入的亲和性模板规则
+    affinity_rules: Vec<AutoRule>,
+    /// 各 CCD 的强调色
+    ccd_palette: CcdPalette,
+    /// 多 CCD 时的 L3 缓存，用于标示限定在单个 CCD 上的进程（单 CCD 时为空）
+    ccd_caches: Vec<L3CacheInfo>,
+    /// 保留给系统的核心
+    reserved_cores: Vec<usize>,
+    /// 显示按 cgroup 汇总的 CPU 占用
+    show_cgroups: bool,
+    /// cgroup CPU 占用的后台采样
+    cgroups: CgroupSampler,
+    /// 表格密度
+    density: TableDensity,
+}
+
+/// 一个进程的 sched/debug 读取结果
+struct SchedDebugView {
+    /// 读取时间
+    read_at: Instant,
+    /// 各线程的调度实体统计
+    result: Result<Vec<SchedDebugTask>, String>,
+    /// 相对上一次读取的迁移次数增量（按线程 ID）
+    migration_deltas: HashMap<u32, u64>,
+}
+
+/// 等待确认的同名进程亲和性批量应用
+struct BulkAffinityPrompt {
+    process_name: String,
+    cores: Vec<usize>,
+    /// 应用前的检查（可修改的进程、跳过的受保护进程、提醒和规则冲突）
+    check: GroupCheck,
+    /// 为以后启动的同名进程准备的规则
+    rule: AutoRule,
+    /// 是否同时创建规则
+    create_rule: bool,
+}
+
+/// 等待确认的分组预设应用
+struct GroupPresetPrompt {
+    group_name: String,
+    preset: SchedulePreset,
+    check: GroupCheck,
+}
+
+/// 搜索输入防抖间隔
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(150);
+/// 新进程高亮的淡出时间（秒）
+const NEW_PROCESS_FADE_SECS: f32 = 2.0;
+/// 每秒主要缺页数达到此值时视为缺页风暴
+const MAJOR_FAULT_STORM_RATE: f64 = 100.0;
+/// 可执行文件已被替换的提示
+const EXE_DELETED_HINT: &str = "二进制已更新，建议重启";
+/// 核心匹配列的说明
+const CORE_FIT_HINT: &str = "进程运行的核心类型是否与优先级相符\n高优先级（实时策略或负 nice）在能效核上，或后台任务（Batch / Idle 策略或 nice ≥ 10）占用性能核时标记\n可用\"核心错配\"分类只显示这些进程";
+/// 窗口高度低于此值时选中进程的详情改为浮动窗口
+const SHORT_WINDOW_HEIGHT: f32 = 820.0;
+/// 进程表的最小高度
+const MIN_TABLE_HEIGHT: f32 = 160.0;
+/// 内嵌显示详情时进程表占剩余高度的比例
+const DETAILS_TABLE_SHARE: f32 = 0.5;
+/// cgroup 视图的刷新间隔
+const CGROUP_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// 等待后台遍历结果时的重绘间隔
+const CGROUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// cgroup 视图显示的最多组数
+const CGROUP_VIEW_LIMIT: usize = 20;
+/// 名称悬停提示中命令行显示的最多字符数
+const TOOLTIP_COMMAND_CHARS: usize = 600;
+/// 名称悬停提示的最大宽度
+const TOOLTIP_COMMAND_WIDTH: f32 = 480.0;
+
+/// 可选列
+#[derive(Clone, Copy)]
+struct OptionalColumns {
+    /// GPU 使用率（启用 GPU 监控时）
+    gpu: bool,
+    /// 每秒缺页数
+    faults: bool,
+    /// 核心匹配（混合架构时）
+    core_fit: bool,
+}
+
+/// 某一密度下的行尺寸
+fn row_style(density: TableDensity) -> RowStyle {
+    match density {
+        TableDensity::Comfortable => RowStyle { margin: Margin::symmetric(8.0, 6.0), height: 18.0, font_scale: 1.0 },
+        TableDensity::Compact => RowStyle { margin: Margin::symmetric(6.0, 2.0), height: 16.0, font_scale: 0.88 },
+    }
+}
+
+/// 表格行的尺寸（由密度决定，所有行绘制函数共用）
+#[derive(Clone, Copy)]
+struct RowStyle {
+    /// 行内边距
+    margin: Margin,
+    /// 单元格高度
+    height: f32,
+    /// 正文字号相对默认值的比例
+    font_scale: f32,
+}
+
+impl RowStyle {
+    /// 按比例缩放行内的正文、按钮和等宽字号（未单独指定字号的文本）
+    fn apply_font(&self, ui: &mut Ui) {
+        if self.font_scale == 1.0 {
+            return;
+        }
+        for text_style in [TextStyle::Body, TextStyle::Button, TextStyle::Monospace] {
+            if let Some(font) = ui.style_mut().text_styles.get_mut(&text_style) {
+                font.size *= self.font_scale;
+            }
+        }
+    }
+}
+
+/// 进程行的绘制选项
+#[derive(Clone, Copy)]
+struct RowOptions {
+    /// 行序号（斑马纹）
+    idx: usize,
+    /// 显示的可选列
+    columns: OptionalColumns,
+    /// 新进程高亮强度 (0-1)
+    new_highlight: f32,
+    /// 行尺寸
+    style: RowStyle,
 }
 
 impl ProcessListPanel {
     pub fn new() -> Self {
         Self {
             selected_pid: None,
+            selected_pids: HashSet::new(),
+            export_path: None,
             editing_affinity: None,
             affinity_selection: Vec::new(),
             error_message: None,
+            success_message: None,
+            grouped: false,
+            show_faults: false,
+            expanded_groups: HashSet::new(),
+            memory_features: HashMap::new(),
+            thp_setting: None,
+            filter_pending: None,
+            focus_search: false,
+            details_loaders: HashMap::new(),
+            delay_worker: None,
+            delay_stats: HashMap::new(),
+            detail_windows: HashMap::new(),
+            refresh_requests: Vec::new(),
+            sched_debug: HashMap::new(),
+            sched_debug_readable: None,
+            watched: HashSet::new(),
+            watch_toggles: Vec::new(),
+            apply_guard: ApplyGuard::default(),
+            checked: CheckedApply::default(),
+            bulk_affinity_request: None,
+            pending_bulk_affinity: None,
+            group_preset_request: None,
+            pending_group_preset: None,
+            affinity_rules: Vec::new(),
+            rule_resolutions: Vec::new(),
+            ccd_palette: CcdPalette::default(),
+            ccd_caches: Vec::new(),
+            reserved_cores: Vec::new(),
+            show_cgroups: false,
+            cgroups: CgroupSampler::new(CGROUP_VIEW_LIMIT),
+            density: TableDensity::default(),
         }
     }
 
+    /// 更新自动规则快照
+    pub fn set_apply_guard(&mut self, guard: ApplyGuard) {
+        self.apply_guard = guard;
+    }
+
+    /// 取出待规则引擎执行的冲突处理
+    pub fn take_rule_resolutions(&mut self) -> Vec<RuleResolution> {
+        std::mem::take(&mut self.rule_resolutions)
+    }
+
+    /// 取出用户是否选择了不再提示 V-Cache 提醒
+    pub fn take_vcache_opt_out(&mut self) -> bool {
+        self.checked.take_vcache_opt_out()
+    }
+
+    /// 取出待加入规则引擎的亲和性模板规则
+    pub fn take_affinity_rules(&mut self) -> Vec<AutoRule> {
+        std::mem::take(&mut self.affinity_rules)
+    }
+
+    /// 设置各 CCD 的强调色
+    pub fn set_ccd_palette(&mut self, palette: CcdPalette, l3_caches: &[L3CacheInfo]) {
+        self.ccd_palette = palette;
+        self.ccd_caches = if l3_caches.len() > 1 { l3_caches.to_vec() } else { Vec::new() };
+    }
+
+    /// 设置表格密度
+    pub fn set_density(&mut self, density: TableDensity) {
+        self.density = density;
+    }
+
+    /// 设置保留核心（亲和性编辑器中标记并提醒）
+    pub fn set_reserved_cores(&mut self, reserved_cores: Vec<usize>) {
+        self.reserved_cores = reserved_cores;
+    }
+
+    /// 是否显示缺页列
+    pub fn shows_faults(&self) -> bool {
+        self.show_faults
+    }
+
+    /// 当前显示的可选列
+    fn optional_columns(&self, process_manager: &ProcessManager) -> OptionalColumns {
+        OptionalColumns {
+            gpu: process_manager.gpu_monitor_name().is_some(),
+            faults: self.show_faults,
+            core_fit: process_manager.has_core_types(),
+        }
+    }
+
+    /// 选中的进程
+    pub fn selected_pid(&self) -> Option<u32> {
+        self.selected_pid
+    }
+
+    /// 打开独立详情窗口的进程
+    pub fn detail_window_pids(&self) -> Vec<u32> {
+        self.detail_windows.iter().filter(|(_, &open)| open).map(|(&pid, _)| pid).collect()
+    }
+
+    /// 让搜索框获得焦点（快捷键）
+    pub fn focus_search(&mut self) {
+        self.focus_search = true;
+    }
+
+    /// 启用或禁用延迟统计显示
+    pub fn set_delay_accounting(&mut self, enabled: bool) {
+        self.delay_worker = enabled.then(DelayAccountingWorker::spawn);
+        self.delay_stats.clear();
+    }
+
     /// 绘制面板
-    pub fn ui(&mut self, ui: &mut Ui, process_manager: &mut ProcessManager, logical_cores: usize) {
+    /// viewport_height 是外层滚动区域的可见高度，进程表按它分配高度，不随外层滚动变化
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &mut ProcessManager,
+        session: &mut SessionJournal,
+        presets: &[SchedulePreset],
+        cpu_info: &CpuInfo,
+        viewport_height: f32,
+    ) {
+        let panel_top = ui.cursor().top();
+        let logical_cores = cpu_info.logical_cores;
+        let details_overlay = ui.ctx().screen_rect().height() < SHORT_WINDOW_HEIGHT;
+        self.watched = process_manager.watched().iter().copied().collect();
+        self.selected_pids.retain(|&pid| process_manager.process(pid).is_some());
+        // 分组视图下工具栏的组数和表格共用一次分组结果
+        let mut groups: Option<Vec<ProcessGroup>> = None;
         ui.add_space(8.0);
 
         // 错误消息显示
@@ -54,6 +368,42 @@ impl ProcessListPanel {
             self.error_message = None;
         }
 
+        let mut clear_success = false;
+        if let Some(ref msg) = self.success_message {
+            Frame::none()
+                .fill(Color32::from_rgb(30, 70, 40))
+                .inner_margin(Margin::same(8.0))
+                .rounding(Rounding::same(4.0))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("✔").color(Color32::from_rgb(100, 220, 120)));
+                        ui.label(RichText::new(msg.as_str()).color(Color32::from_rgb(170, 230, 180)));
+                        if ui.small_button("✕").clicked() {
+                            clear_success = true;
+                        }
+                    });
+                });
+            ui.add_space(8.0);
+        }
+        if clear_success {
+            self.success_message = None;
+        }
+
+        self.draw_checked_apply(ui, session, logical_cores);
+        self.draw_bulk_affinity_prompt(ui, session, logical_cores);
+        self.draw_group_preset_prompt(ui, session, logical_cores);
+        self.draw_export_prompt(ui, process_manager, cpu_info);
+
+        // 输入停顿后再应用过滤器，避免每次按键都重新过滤
+        if let Some((filter, changed_at)) = &self.filter_pending {
+            if changed_at.elapsed() >= FILTER_DEBOUNCE {
+                process_manager.set_filter(filter.clone());
+                self.filter_pending = None;
+            } else {
+                ui.ctx().request_repaint_after(FILTER_DEBOUNCE - changed_at.elapsed());
+            }
+        }
+
         // 搜索框
         Frame::none()
             .fill(Color32::from_gray(35))
@@ -63,24 +413,135 @@ impl ProcessListPanel {
                 ui.horizontal(|ui| {
                     ui.label(RichText::new("🔍").size(16.0));
                     ui.add_space(8.0);
-                    let mut filter = process_manager.filter().to_string();
+                    let mut filter = match &self.filter_pending {
+                        Some((pending, _)) => pending.clone(),
+                        None => process_manager.filter().to_string(),
+                    };
                     let response = ui.add(
                         TextEdit::singleline(&mut filter)
                             .desired_width(300.0)
                             .hint_text("搜索进程名称、命令或 PID...")
                     );
+                    if std::mem::take(&mut self.focus_search) {
+                        response.request_focus();
+                    }
                     if response.changed() {
-                        process_manager.set_filter(filter);
+                        self.filter_pending = Some((filter, Instant::now()));
+                        ui.ctx().request_repaint_after(FILTER_DEBOUNCE);
+                    }
+
+                    ui.add_space(12.0);
+                    let mut containers_only = process_manager.containers_only();
+                    if ui.checkbox(&mut containers_only, "仅容器进程").changed() {
+                        process_manager.set_containers_only(containers_only);
+                    }
+
+                    ui.add_space(12.0);
+                    ui.checkbox(&mut self.grouped, "按名称分组");
+                    ui.checkbox(&mut self.show_faults, "缺页列");
+                    if ui.checkbox(&mut self.show_cgroups, "cgroup 视图")
+                        .on_hover_text("按 cgroup v2 的 slice / service 汇总 CPU 占用")
+                        .changed()
+                    {
+                        // 重新打开时丢弃过期的基准
+                        self.cgroups.reset();
+                    }
+                    let mut secondary = process_manager.secondary_sort();
+                    ComboBox::from_id_salt("secondary_sort")
+                        .width(70.0)
+                        .selected_text(secondary.map_or("无", |f| f.display_name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut secondary, None, "无");
+                            let core_fit = process_manager.has_core_types();
+                            for field in SortField::ALL.into_iter().filter(|&f| f != SortField::CoreFit || core_fit) {
+                                ui.selectable_value(&mut secondary, Some(field), field.display_name());
+                            }
+                        })
+                        .response
+                        .on_hover_text("次要排序：主排序值相同时使用，最后总是按 PID 升序");
+                    if secondary != process_manager.secondary_sort() {
+                        process_manager.set_secondary_sort(secondary);
+                    }
+                    if ui.button("复制 JSON").on_hover_text("复制当前过滤后的进程列表快照，供外部工具使用").clicked() {
+                        match process_manager.snapshot_json(true) {
+                            Ok(json) => ui.ctx().copy_text(json),
+                            Err(e) => self.error_message = Some(e),
+                        }
+                    }
+
+                    if !self.selected_pids.is_empty() {
+                        ui.add_space(12.0);
+                        if ui.button(format!("导出所选为 JSON ({})", self.selected_pids.len()))
+                            .on_hover_text("Ctrl 点击 PID 可多选")
+                            .clicked()
+                        {
+                            self.open_export_prompt();
+                        }
                     }
 
                     ui.add_space(20.0);
-                    ui.label(RichText::new(format!("共 {} 个进程", process_manager.filtered_processes().len()))
-                        .color(Color32::from_gray(160)));
+                    let count = process_manager.filtered_processes().len();
+                    let count_text = if self.grouped {
+                        let groups = groups.get_or_insert_with(|| process_manager.grouped_by_name());
+                        format!("共 {} 个进程 / {} 组", count, groups.len())
+                    } else {
+                        format!("共 {} 个进程", count)
+                    };
+                    ui.label(RichText::new(count_text).color(Color32::from_gray(160)));
+
+                    let diff = process_manager.last_diff();
+                    ui.add_space(12.0);
+                    let mut diff_text = format!("新增 {} / 退出 {}", diff.appeared, diff.exited);
+                    if diff.execed > 0 {
+                        diff_text.push_str(&format!(" / exec {}", diff.execed));
+                    }
+                    ui.label(RichText::new(diff_text)
+                        .size(12.0).color(Color32::from_gray(140)))
+                        .on_hover_text("最近一次刷新");
+                });
+
+                // 来源筛选条
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("来源").size(12.0).color(Color32::from_gray(140)));
+                    for origin in ProcessOrigin::ALL {
+                        let visible = process_manager.is_origin_visible(origin);
+                        let text = RichText::new(format!("● {}", origin.display_name())).size(12.0);
+                        let text = if visible { text.color(origin_color(origin)) } else { text.color(Color32::from_gray(90)) };
+                        if ui.add(egui::SelectableLabel::new(visible, text)).clicked() {
+                            process_manager.set_origin_visible(origin, !visible);
+                        }
+                    }
                 });
+
+                // 分类快速过滤
+                if !process_manager.categories().is_empty() {
+                    ui.add_space(4.0);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(RichText::new("分类").size(12.0).color(Color32::from_gray(140)))
+                            .on_hover_text("选中的分类同时生效，并与搜索文本同时生效；可在 config.toml 的 custom_categories 中添加");
+                        let mut toggled = None;
+                        for category in process_manager.categories() {
+                            let active = process_manager.active_categories().contains(&category.name);
+                            let text = RichText::new(&category.name).size(12.0);
+                            if ui.add(egui::SelectableLabel::new(active, text)).clicked() {
+                                toggled = Some((category.name.clone(), !active));
+                            }
+                        }
+                        if let Some((name, active)) = toggled {
+                            process_manager.set_category_active(&name, active);
+                        }
+                    });
+                }
             });
 
         ui.add_space(12.0);
 
+        if self.show_cgroups {
+            self.draw_cgroup_view(ui);
+            ui.add_space(12.0);
+        }
+
         // 进程表格
         Frame::none()
             .fill(Color32::from_gray(35))
@@ -95,29 +556,416 @@ impl ProcessListPanel {
                 // 分隔线
                 ui.add(egui::Separator::default().spacing(0.0));
 
-                // 进程列表
+                // 进程列表：占满窗口剩余高度，内嵌详情时留出一部分给详情
+                let remaining = viewport_height - (ui.cursor().top() - panel_top) - 12.0;
+                let table_height = if self.selected_pid.is_some() && !details_overlay {
+                    remaining * DETAILS_TABLE_SHARE
+                } else {
+                    remaining
+                };
                 ScrollArea::vertical()
-                    .max_height(350.0)
+                    .max_height(table_height.max(MIN_TABLE_HEIGHT))
                     .show(ui, |ui| {
+                        let columns = self.optional_columns(process_manager);
+                        let style = row_style(self.density);
+
+                        // 刚退出的进程（删除线，短暂保留）
+                        for (process, _) in process_manager.recently_exited() {
+                            draw_exited_row(ui, process, style);
+                        }
+
+                        if self.grouped {
+                            let groups = groups.take().unwrap_or_else(|| process_manager.grouped_by_name());
+                            self.draw_groups(ui, &groups, process_manager, session, presets, logical_cores);
+                            return;
+                        }
+
                         let processes = process_manager.filtered_processes();
 
                         for (idx, process) in processes.iter().take(100).enumerate() {
-                            self.draw_process_row(ui, process, logical_cores, idx);
+                            // 刚出现时目标为 1，随后以动画淡出到 0
+                            let is_new = process_manager
+                                .process_age(process.pid)
+                                .is_some_and(|age| age < Duration::from_millis(300));
+                            let new_highlight = ui.ctx().animate_value_with_time(
+                                egui::Id::new(("new_process", process.pid)),
+                                if is_new { 1.0 } else { 0.0 },
+                                NEW_PROCESS_FADE_SECS,
+                            );
+                            let options = RowOptions { idx, columns, new_highlight, style };
+                            self.draw_process_row(ui, process, session, logical_cores, options);
                         }
                     });
             });
 
-        // 选中进程的详情
-        if let Some(pid) = self.selected_pid {
-            if let Some(process) = process_manager
+        for pid in std::mem::take(&mut self.refresh_requests) {
+            process_manager.refresh_pid(pid);
+        }
+        for pid in std::mem::take(&mut self.watch_toggles) {
+            process_manager.toggle_watch(pid);
+        }
+        if let Some((pid, cores)) = self.bulk_affinity_request.take() {
+            self.open_bulk_affinity(pid, cores, process_manager);
+            ui.ctx().request_repaint();
+        }
+        if let Some((group_name, pids, preset)) = self.group_preset_request.take() {
+            self.open_group_preset(group_name, &pids, preset, process_manager, session, logical_cores);
+            ui.ctx().request_repaint();
+        }
+
+        // 选中进程的详情（窗口较矮时以浮动窗口显示，不占用表格高度）
+        process_manager.track_pid(self.selected_pid);
+        if let Some(pid) = self.selected_pid.filter(|pid| !self.detail_windows.contains_key(pid)) {
+            if details_overlay {
+                self.draw_details_overlay(ui, pid, process_manager, session, logical_cores);
+            } else if let Some(process) = process_manager
                 .filtered_processes()
                 .iter()
                 .find(|p| p.pid == pid)
             {
                 ui.add_space(12.0);
-                self.draw_process_details(ui, process);
+                let applied_by = session.applied_by(pid);
+                self.draw_process_details(ui, process, process_manager.tracked_history(), applied_by, logical_cores);
+            }
+        }
+
+        self.draw_detail_windows(ui, process_manager, session, logical_cores);
+        self.prune_detail_caches();
+    }
+
+    /// 按 cgroup 汇总的 CPU 占用（在后台定期遍历 cgroup 树）
+    fn draw_cgroup_view(&mut self, ui: &mut Ui) {
+        self.cgroups.refresh(CGROUP_REFRESH_INTERVAL);
+        ui.ctx().request_repaint_after(CGROUP_POLL_INTERVAL);
+        let snapshot = self.cgroups.snapshot();
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(12.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("cgroup CPU 占用").size(14.0).strong());
+                    ui.label(RichText::new("父组包含其子组，100% 为一个核心").size(11.0).color(Color32::from_gray(140)));
+                });
+                ui.add_space(6.0);
+
+                if self.cgroups.usage().is_empty() {
+                    let text = if snapshot.age().is_some() && snapshot.is_empty() {
+                        "没有可读的 cgroup v2 统计（系统未使用 cgroup v2 统一层级？）"
+                    } else {
+                        "正在采样…"
+                    };
+                    ui.label(RichText::new(text).color(Color32::from_gray(140)));
+                } else {
+                    let max = self.cgroups.usage().iter().map(|u| u.cpu_percent).fold(1.0, f32::max);
+                    egui::Grid::new("cgroup_usage").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+                        for usage in self.cgroups.usage() {
+                            ui.horizontal(|ui| {
+                                ui.add_space((usage.depth.saturating_sub(1)) as f32 * 12.0);
+                                ui.label(RichText::new(usage.name()).monospace()).on_hover_text(&usage.path);
+                            });
+                            ui.horizontal(|ui| {
+                                let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 8.0), egui::Sense::hover());
+                                ui.painter().rect_filled(rect, 2.0, Color32::from_gray(50));
+                                let mut filled = rect;
+                                filled.set_width(rect.width() * (usage.cpu_percent / max).clamp(0.0, 1.0));
+                                ui.painter().rect_filled(filled, 2.0, cpu_usage_color(usage.cpu_percent));
+                                ui.label(RichText::new(format!("{:.1}%", usage.cpu_percent)).color(cpu_usage_color(usage.cpu_percent)));
+                            });
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                let (missing, denied) = (snapshot.missing, snapshot.denied);
+                if missing > 0 || denied > 0 {
+                    ui.add_space(4.0);
+                    ui.label(RichText::new(format!("{} 个组没有 CPU 统计，{} 个组无权读取", missing, denied))
+                        .size(11.0).color(Color32::from_gray(120)))
+                        .on_hover_text("没有 cpu.stat 的组未启用统计；无权读取的组需要 root 权限");
+                }
+            });
+    }
+
+    /// 绘制独立的进程详情窗口（双击进程行打开，可同时打开多个用于对比）
+    fn draw_detail_windows(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &ProcessManager,
+        session: &SessionJournal,
+        logical_cores: usize,
+    ) {
+        let mut pids = self.detail_window_pids();
+        pids.sort_unstable();
+        for pid in pids {
+            let mut open = true;
+            egui::Window::new(format!("进程 {}", pid))
+                .id(egui::Id::new(("process_detail_window", pid)))
+                .open(&mut open)
+                .default_width(560.0)
+                .resizable(true)
+                .vscroll(true)
+                .show(ui.ctx(), |ui| match process_manager.process(pid) {
+                    Some(process) => {
+                        let applied_by = session.applied_by(pid);
+                        self.draw_process_details(ui, process, process_manager.tracked_history(), applied_by, logical_cores);
+                    }
+                    None => {
+                        ui.label(RichText::new("进程已退出").color(Color32::from_rgb(255, 150, 100)));
+                    }
+                });
+            if !open {
+                self.detail_windows.remove(&pid);
+            }
+        }
+    }
+
+    /// 以浮动窗口显示选中进程的详情，关闭时取消选中
+    fn draw_details_overlay(
+        &mut self,
+        ui: &mut Ui,
+        pid: u32,
+        process_manager: &ProcessManager,
+        session: &SessionJournal,
+        logical_cores: usize,
+    ) {
+        let Some(process) = process_manager.process(pid) else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("进程 {} ({})", process.name, pid))
+            .id(egui::Id::new("selected_process_overlay"))
+            .open(&mut open)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+            .default_width(520.0)
+            .default_height(ui.ctx().screen_rect().height() * 0.6)
+            .collapsible(true)
+            .vscroll(true)
+            .show(ui.ctx(), |ui| {
+                let applied_by = session.applied_by(pid);
+                self.draw_process_details(ui, process, process_manager.tracked_history(), applied_by, logical_cores);
+            });
+        if !open {
+            self.selected_pid = None;
+        }
+    }
+
+    /// 打开独立详情窗口，并立即刷新该进程的调度信息
+    fn open_detail_window(&mut self, pid: u32) {
+        self.detail_windows.insert(pid, true);
+        self.refresh_requests.push(pid);
+    }
+
+    /// 丢弃不再显示详情的进程的缓存
+    fn prune_detail_caches(&mut self) {
+        let shown = |pid: &u32| self.selected_pid == Some(*pid) || self.detail_windows.contains_key(pid);
+        self.details_loaders.retain(|pid, _| shown(pid));
+        self.memory_features.retain(|pid, _| shown(pid));
+        self.delay_stats.retain(|pid, _| shown(pid));
+        if let Some(worker) = &mut self.delay_worker {
+            worker.retain(|pid| shown(&pid));
+        }
+        self.sched_debug.retain(|pid, _| shown(pid));
+    }
+
+    /// 绘制按名称分组的列表，单实例分组直接显示为进程行
+    fn draw_groups(
+        &mut self,
+        ui: &mut Ui,
+        groups: &[ProcessGroup],
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        presets: &[SchedulePreset],
+        logical_cores: usize,
+    ) {
+        let columns = self.optional_columns(process_manager);
+        let style = row_style(self.density);
+        for (idx, group) in groups.iter().take(100).enumerate() {
+            let options = RowOptions { idx, columns, new_highlight: 0.0, style };
+            if group.count() == 1 {
+                if let Some(process) = process_manager.process(group.pids[0]) {
+                    self.draw_process_row(ui, process, session, logical_cores, options);
+                }
+                continue;
+            }
+
+            self.draw_group_row(ui, group, options, presets);
+            if self.expanded_groups.contains(&group.name) {
+                for (i, pid) in group.pids.iter().enumerate() {
+                    if let Some(process) = process_manager.process(*pid) {
+                        ui.horizontal(|ui| {
+                            ui.add_space(16.0);
+                            ui.vertical(|ui| {
+                                let options = RowOptions { idx: i, ..options };
+                                self.draw_process_row(ui, process, session, logical_cores, options);
+                            });
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// 绘制分组汇总行（可展开，可对整组应用预设）
+    fn draw_group_row(&mut self, ui: &mut Ui, group: &ProcessGroup, options: RowOptions, presets: &[SchedulePreset]) {
+        let RowOptions { columns, style, .. } = options;
+        Frame::none()
+            .fill(Color32::from_gray(44))
+            .inner_margin(style.margin)
+            .rounding(Rounding::same(4.0))
+            .show(ui, |ui| {
+                style.apply_font(ui);
+                ui.horizontal(|ui| {
+                    let expanded = self.expanded_groups.contains(&group.name);
+                    let arrow = if expanded { "▼" } else { "▶" };
+                    let toggle = ui.add_sized(
+                        [70.0, style.height],
+                        egui::Button::new(RichText::new(format!("{} ×{}", arrow, group.count())).monospace())
+                            .fill(Color32::TRANSPARENT)
+                            .stroke(Stroke::NONE),
+                    );
+                    if toggle.clicked() {
+                        if expanded {
+                            self.expanded_groups.remove(&group.name);
+                        } else {
+                            self.expanded_groups.insert(group.name.clone());
+                        }
+                    }
+
+                    ui.add_sized([180.0, style.height], egui::Label::new(
+                        RichText::new(&group.name).color(Color32::WHITE).strong()
+                    ).truncate());
+
+                    ui.add_sized([70.0, style.height], egui::Label::new(
+                        RichText::new(format!("{:>5.1}%", group.cpu_usage)).color(cpu_usage_color(group.cpu_usage))
+                    ));
+
+                    ui.add_sized([90.0, style.height], egui::Label::new(
+                        format!("{:>8}", format_memory(group.memory))
+                    ));
+
+                    if columns.gpu {
+                        ui.add_sized([70.0, style.height], egui::Label::new(""));
+                    }
+                    if columns.faults {
+                        ui.add_sized([90.0, style.height], egui::Label::new(""));
+                    }
+                    if columns.core_fit {
+                        ui.add_sized([100.0, style.height], egui::Label::new(""));
+                    }
+                    ui.add_sized([70.0, style.height], egui::Label::new(""));
+
+                    ui.menu_button(RichText::new("应用预设").size(11.0), |ui| {
+                        for preset in presets {
+                            if ui.button(&preset.name).on_hover_text(&preset.description).clicked() {
+                                self.group_preset_request = Some((group.name.clone(), group.pids.clone(), preset.clone()));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
+            });
+    }
+
+    /// 检查分组内的进程：没有提醒和规则冲突时直接应用预设，否则等待确认
+    fn open_group_preset(
+        &mut self,
+        group_name: String,
+        pids: &[u32],
+        preset: SchedulePreset,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        let processes: Vec<&ProcessInfo> = pids.iter().filter_map(|&pid| process_manager.process(pid)).collect();
+        let check = self.apply_guard.check_group(&processes, &preset_changes(&preset));
+        let prompt = GroupPresetPrompt { group_name, preset, check };
+        if prompt.check.warnings.is_empty() {
+            self.apply_group_preset(prompt, session, logical_cores);
+        } else {
+            self.pending_group_preset = Some(prompt);
+        }
+    }
+
+    /// 分组预设应用的确认提示
+    fn draw_group_preset_prompt(&mut self, ui: &mut Ui, session: &mut SessionJournal, logical_cores: usize) {
+        let Some(prompt) = self.pending_group_preset.as_ref() else {
+            return;
+        };
+
+        let mut apply = false;
+        let mut cancel = false;
+        Frame::none()
+            .fill(Color32::from_rgb(70, 60, 30))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("⚠").size(14.0).color(Color32::from_rgb(255, 200, 100)));
+                    ui.label(RichText::new(format!(
+                        "对 {} 的 {} 个进程应用预设 '{}' 前请确认",
+                        prompt.group_name,
+                        prompt.check.pids.len(),
+                        prompt.preset.name
+                    )).strong().color(Color32::from_rgb(255, 220, 150)));
+                });
+                for warning in &prompt.check.warnings {
+                    ui.label(RichText::new(warning).size(12.0).color(Color32::from_gray(200)));
+                }
+                if !prompt.check.skipped.is_empty() {
+                    ui.label(RichText::new(format!("跳过受保护的进程: {}", prompt.check.skipped.join("; ")))
+                        .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                }
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    apply = ui.add_enabled(!prompt.check.pids.is_empty(), egui::Button::new("仍然应用"))
+                        .on_disabled_hover_text("没有可修改的进程")
+                        .clicked();
+                    cancel = ui.button("取消").clicked();
+                });
+            });
+        ui.add_space(8.0);
+
+        if cancel {
+            self.pending_group_preset = None;
+        } else if apply {
+            if let Some(prompt) = self.pending_group_preset.take() {
+                self.apply_group_preset(prompt, session, logical_cores);
+            }
+        }
+    }
+
+    /// 对通过检查的分组进程应用预设，与规则冲突的进程让规则不再作用于它们
+    fn apply_group_preset(&mut self, prompt: GroupPresetPrompt, session: &mut SessionJournal, logical_cores: usize) {
+        let GroupPresetPrompt { group_name, preset, check } = prompt;
+        self.rule_resolutions.extend(check.resolutions());
+        let changes = preset_changes(&preset);
+        let mut errors = Vec::new();
+        for &pid in &check.pids {
+            let apply = ManualApply { pid, changes: changes.clone(), preset: Some(preset.clone()) };
+            if let Err(e) = apply.run(session, logical_cores) {
+                errors.push(format!("{}: {}", pid, e));
             }
         }
+
+        let mut summary = format!(
+            "预设 '{}' 已应用到 {} 的 {} 个进程",
+            preset.name,
+            group_name,
+            check.pids.len() - errors.len()
+        );
+        if !check.skipped.is_empty() {
+            summary.push_str(&format!("，跳过 {} 个受保护进程", check.skipped.len()));
+        }
+        if errors.is_empty() {
+            self.success_message = Some(summary);
+            self.error_message = None;
+        } else {
+            self.error_message = Some(format!("{}；失败 {} 个 ({})", summary, errors.len(), errors.join("; ")));
+            self.success_message = None;
+        }
     }
 
     /// 绘制表头
@@ -128,22 +976,43 @@ impl ProcessListPanel {
         ui.horizontal(|ui| {
             ui.add_space(8.0);
 
-            if self.sort_header_button(ui, "PID", SortField::Pid, sort_field, is_desc, 70.0) {
+            if self.sort_header_button(ui, "PID", SortField::Pid, sort_field, is_desc, 70.0).clicked() {
                 process_manager.set_sort(SortField::Pid);
             }
 
-            if self.sort_header_button(ui, "名称", SortField::Name, sort_field, is_desc, 180.0) {
+            if self.sort_header_button(ui, "名称", SortField::Name, sort_field, is_desc, 180.0).clicked() {
                 process_manager.set_sort(SortField::Name);
             }
 
-            if self.sort_header_button(ui, "CPU%", SortField::CpuUsage, sort_field, is_desc, 70.0) {
+            if self.sort_header_button(ui, "CPU%", SortField::CpuUsage, sort_field, is_desc, 70.0).clicked() {
                 process_manager.set_sort(SortField::CpuUsage);
             }
 
-            if self.sort_header_button(ui, "内存", SortField::Memory, sort_field, is_desc, 90.0) {
+            if self.sort_header_button(ui, "内存", SortField::Memory, sort_field, is_desc, 90.0).clicked() {
                 process_manager.set_sort(SortField::Memory);
             }
 
+            if let Some(backend) = process_manager.gpu_monitor_name() {
+                ui.add_sized([70.0, 20.0], egui::Label::new(
+                    RichText::new("GPU%").color(Color32::from_gray(180))
+                )).on_hover_text(format!("GPU 使用率 ({})", backend));
+            }
+
+            if self.show_faults {
+                ui.add_sized([90.0, 20.0], egui::Label::new(
+                    RichText::new("缺页/s").color(Color32::from_gray(180))
+                )).on_hover_text("每秒主要缺页 / 次要缺页\n主要缺页需要从磁盘读入，频繁时进程在等待 I/O");
+            }
+
+            if process_manager.has_core_types()
+                && self
+                    .sort_header_button(ui, "核心", SortField::CoreFit, sort_field, is_desc, 100.0)
+                    .on_hover_text(CORE_FIT_HINT)
+                    .clicked()
+            {
+                process_manager.set_sort(SortField::CoreFit);
+            }
+
             ui.add_sized([70.0, 20.0], egui::Label::new(
                 RichText::new("策略").color(Color32::from_gray(180))
             ));
@@ -163,7 +1032,7 @@ impl ProcessListPanel {
         current_field: SortField,
         is_desc: bool,
         width: f32,
-    ) -> bool {
+    ) -> egui::Response {
         let is_active = field == current_field;
         let arrow = if is_active {
             if is_desc { " ▼" } else { " ▲" }
@@ -178,22 +1047,201 @@ impl ProcessListPanel {
             Color32::from_gray(180)
         };
 
-        let response = ui.add_sized(
+        ui.add_sized(
             [width, 20.0],
             egui::Button::new(RichText::new(text).color(color))
                 .fill(Color32::TRANSPARENT)
                 .stroke(Stroke::NONE)
+        )
+    }
+
+    /// 为同名进程生成亲和性批量应用的确认提示，检查与分组预设应用相同，受保护的进程单独列出并跳过
+    fn open_bulk_affinity(&mut self, pid: u32, cores: Vec<usize>, process_manager: &ProcessManager) {
+        let Some(template) = process_manager.process(pid) else {
+            self.error_message = Some(format!("进程 {} 已退出", pid));
+            return;
+        };
+        if cores.is_empty() {
+            self.error_message = Some("至少选择一个核心".to_string());
+            return;
+        }
+        let mut same_name: Vec<&ProcessInfo> = process_manager.processes().filter(|p| p.name == template.name).collect();
+        same_name.sort_by_key(|p| p.pid);
+
+        // 规则沿用模板进程的调度策略，实时策略取实时优先级而不是 nice
+        let priority = if template.sched_policy.is_realtime() {
+            get_rt_priority(pid as i32).unwrap_or(1)
+        } else {
+            template.priority
+        };
+        let changes = vec![SchedChange::Affinity(cores.clone())];
+        self.pending_bulk_affinity = Some(BulkAffinityPrompt {
+            rule: AutoRule::affinity_template(&template.name, &cores, template.sched_policy, priority),
+            check: self.apply_guard.check_group(&same_name, &changes),
+            process_name: template.name.clone(),
+            cores,
+            create_rule: true,
+        });
+    }
+
+    /// 同名进程亲和性批量应用的确认提示
+    fn draw_bulk_affinity_prompt(&mut self, ui: &mut Ui, session: &mut SessionJournal, logical_cores: usize) {
+        let Some(prompt) = self.pending_bulk_affinity.as_mut() else {
+            return;
+        };
+
+        let mut apply = false;
+        let mut cancel = false;
+        Frame::none()
+            .fill(Color32::from_gray(40))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new("对所有同名进程应用亲和性").strong());
+                ui.label(RichText::new(format!(
+                    "将 {} 的 {} 个进程绑定到 CPU {}",
+                    prompt.process_name,
+                    prompt.check.pids.len(),
+                    format_cpu_list(&prompt.cores)
+                )).size(12.0).color(Color32::from_gray(200)));
+                let pids: Vec<String> = prompt.check.pids.iter().map(|pid| pid.to_string()).collect();
+                ui.label(RichText::new(format!("PID: {}", pids.join(", "))).size(11.0).monospace().color(Color32::from_gray(160)));
+                for warning in &prompt.check.warnings {
+                    ui.label(RichText::new(warning).size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                }
+                if !prompt.check.skipped.is_empty() {
+                    ui.label(RichText::new(format!("跳过受保护的进程: {}", prompt.check.skipped.join("; ")))
+                        .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                }
+                ui.checkbox(&mut prompt.create_rule, format!("同时为以后启动的 {} 创建规则", prompt.process_name))
+                    .on_hover_text("已有匹配的规则时只更新其亲和性");
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    apply = ui.add_enabled(!prompt.check.pids.is_empty(), egui::Button::new("应用"))
+                        .on_disabled_hover_text("没有可修改的进程")
+                        .clicked();
+                    cancel = ui.button("取消").clicked();
+                });
+            });
+        ui.add_space(8.0);
+
+        if cancel {
+            self.pending_bulk_affinity = None;
+        } else if apply {
+            if let Some(prompt) = self.pending_bulk_affinity.take() {
+                self.apply_bulk_affinity(prompt, session, logical_cores);
+            }
+        }
+    }
+
+    /// 对确认提示中列出的进程应用亲和性，与规则冲突的进程让规则不再作用于它们，需要时把模板规则交给规则引擎
+    fn apply_bulk_affinity(&mut self, prompt: BulkAffinityPrompt, session: &mut SessionJournal, logical_cores: usize) {
+        self.rule_resolutions.extend(prompt.check.resolutions());
+        let changes = vec![SchedChange::Affinity(prompt.cores.clone())];
+        let mut errors = Vec::new();
+        for &pid in &prompt.check.pids {
+            let apply = ManualApply { pid, changes: changes.clone(), preset: None };
+            if let Err(e) = apply.run(session, logical_cores) {
+                errors.push(format!("{}: {}", pid, e));
+            }
+        }
+        if self.editing_affinity.is_some_and(|pid| prompt.check.pids.contains(&pid)) {
+            self.editing_affinity = None;
+        }
+
+        let mut summary = format!(
+            "亲和性 CPU {} 已应用到 {} 的 {} 个进程",
+            format_cpu_list(&prompt.cores),
+            prompt.process_name,
+            prompt.check.pids.len() - errors.len()
         );
+        if !prompt.check.skipped.is_empty() {
+            summary.push_str(&format!("，跳过 {} 个受保护进程", prompt.check.skipped.len()));
+        }
+        if prompt.create_rule {
+            summary.push_str("，以后启动的实例由规则处理");
+            self.affinity_rules.push(prompt.rule);
+        }
+        if errors.is_empty() {
+            self.success_message = Some(summary);
+            self.error_message = None;
+        } else {
+            self.error_message = Some(format!("{}；失败 {} 个 ({})", summary, errors.len(), errors.join("; ")));
+            self.success_message = None;
+        }
+    }
+
+    /// 打开导出路径输入
+    fn open_export_prompt(&mut self) {
+        let path = default_report_path(unix_timestamp());
+        self.export_path = Some(path.to_string_lossy().into_owned());
+    }
+
+    /// 导出报告的保存路径输入框
+    fn draw_export_prompt(&mut self, ui: &mut Ui, process_manager: &ProcessManager, cpu_info: &CpuInfo) {
+        let Some(path) = self.export_path.as_mut() else {
+            return;
+        };
+
+        let mut save = false;
+        let mut cancel = false;
+        Frame::none()
+            .fill(Color32::from_gray(40))
+            .inner_margin(Margin::same(8.0))
+            .rounding(Rounding::same(4.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("导出 {} 个进程到:", self.selected_pids.len()));
+                    let response = ui.add(TextEdit::singleline(path).desired_width(360.0));
+                    save = ui.button("保存").clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                    cancel = ui.button("取消").clicked();
+                });
+            });
+        ui.add_space(8.0);
 
-        response.clicked()
+        if save {
+            let processes: Vec<&ProcessInfo> = self
+                .selected_pids
+                .iter()
+                .filter_map(|&pid| process_manager.process(pid))
+                .collect();
+            if processes.is_empty() {
+                self.error_message = Some("所选进程均已退出".to_string());
+                return;
+            }
+            let path = std::path::PathBuf::from(path.trim());
+            let report = ProcessReport::new(&processes, cpu_info, unix_timestamp());
+            match report.save(&path) {
+                Ok(()) => {
+                    self.success_message = Some(format!(
+                        "已导出 {} 个进程到 {}",
+                        report.processes.len(),
+                        path.display()
+                    ));
+                    self.export_path = None;
+                }
+                Err(e) => self.error_message = Some(e),
+            }
+        } else if cancel {
+            self.export_path = None;
+        }
     }
 
     /// 绘制进程行
-    fn draw_process_row(&mut self, ui: &mut Ui, process: &ProcessInfo, logical_cores: usize, idx: usize) {
-        let is_selected = self.selected_pid == Some(process.pid);
+    fn draw_process_row(
+        &mut self,
+        ui: &mut Ui,
+        process: &ProcessInfo,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+        options: RowOptions,
+    ) {
+        let RowOptions { idx, columns, new_highlight, style } = options;
+        let is_selected = self.selected_pid == Some(process.pid) || self.selected_pids.contains(&process.pid);
         let is_editing = self.editing_affinity == Some(process.pid);
 
-        // 斑马纹背景
+        // 斑马纹背景，新进程叠加淡出的绿色
         let bg_color = if is_selected {
             Color32::from_rgb(50, 70, 90)
         } else if idx % 2 == 0 {
@@ -201,52 +1249,161 @@ impl ProcessListPanel {
         } else {
             Color32::from_gray(38)
         };
+        let bg_color = if new_highlight > 0.0 {
+            lerp_color(bg_color, Color32::from_rgb(40, 110, 60), new_highlight)
+        } else {
+            bg_color
+        };
 
-        Frame::none()
+        let frame_response = Frame::none()
             .fill(bg_color)
-            .inner_margin(Margin::symmetric(8.0, 6.0))
+            .inner_margin(style.margin)
             .rounding(Rounding::same(4.0))
             .show(ui, |ui| {
+                style.apply_font(ui);
                 ui.horizontal(|ui| {
                     // PID
                     let pid_response = ui.add_sized(
-                        [70.0, 18.0],
+                        [70.0, style.height],
                         egui::SelectableLabel::new(
                             is_selected,
                             RichText::new(format!("{:>6}", process.pid)).monospace(),
                         )
                     );
+                    if pid_response.double_clicked() {
+                        self.open_detail_window(process.pid);
+                    }
                     if pid_response.clicked() {
+                        if ui.input(|i| i.modifiers.command) {
+                            if !self.selected_pids.remove(&process.pid) {
+                                self.selected_pids.insert(process.pid);
+                            }
+                        } else {
+                            self.selected_pids = HashSet::from([process.pid]);
+                        }
+                        if self.selected_pid != Some(process.pid) {
+                            self.refresh_requests.push(process.pid);
+                        }
                         self.selected_pid = Some(process.pid);
                     }
+                    pid_response.context_menu(|ui| {
+                        if ui.button("刷新此进程").clicked() {
+                            self.refresh_requests.push(process.pid);
+                            ui.close_menu();
+                        }
+                        let watch_label = if self.watched.contains(&process.pid) { "移出监视列表" } else { "加入监视列表" };
+                        if ui.button(watch_label).clicked() {
+                            self.watch_toggles.push(process.pid);
+                            ui.close_menu();
+                        }
+                        if ui.button("对所有同名进程应用当前亲和性")
+                            .on_hover_text("正在编辑亲和性时使用编辑中的核心，确认前会列出受影响的进程")
+                            .clicked()
+                        {
+                            let cores = if self.editing_affinity == Some(process.pid) {
+                                selected_cores(&self.affinity_selection)
+                            } else {
+                                process.affinity.clone()
+                            };
+                            self.bulk_affinity_request = Some((process.pid, cores));
+                            ui.close_menu();
+                        }
+                        if !self.selected_pids.is_empty()
+                            && ui.button(format!("导出所选为 JSON ({})", self.selected_pids.len())).clicked()
+                        {
+                            self.open_export_prompt();
+                            ui.close_menu();
+                        }
+                    });
 
-                    // 名称
-                    ui.add_sized([180.0, 18.0], egui::Label::new(
-                        RichText::new(&process.name).color(Color32::WHITE)
-                    ).truncate());
+                    // 名称（容器进程附带徽标，可执行文件已替换时附带标记）
+                    let stale_width = if process.exe_deleted { 18.0 } else { 0.0 };
+                    match &process.container {
+                        Some(container) => {
+                            ui.add_sized([126.0 - stale_width, style.height], egui::Label::new(
+                                RichText::new(&process.name).color(Color32::WHITE)
+                            ).truncate()).on_hover_ui(|ui| command_tooltip(ui, process));
+                            container_badge(ui, container);
+                        }
+                        None => {
+                            let name_response = ui.add_sized([180.0 - stale_width, style.height], egui::Label::new(
+                                RichText::new(&process.name).color(Color32::WHITE)
+                            ).truncate().sense(egui::Sense::click()))
+                                .on_hover_ui(|ui| command_tooltip(ui, process));
+                            if name_response.double_clicked() {
+                                self.open_detail_window(process.pid);
+                            }
+                        }
+                    }
+                    if process.exe_deleted {
+                        ui.add_sized([14.0, style.height], egui::Label::new(
+                            RichText::new("⟳").color(Color32::from_rgb(255, 200, 100))
+                        )).on_hover_text(EXE_DELETED_HINT);
+                    }
 
                     // CPU 使用率
                     let cpu_color = cpu_usage_color(process.cpu_usage);
-                    ui.add_sized([70.0, 18.0], egui::Label::new(
+                    ui.add_sized([70.0, style.height], egui::Label::new(
                         RichText::new(format!("{:>5.1}%", process.cpu_usage)).color(cpu_color)
                     ));
 
                     // 内存
-                    ui.add_sized([90.0, 18.0], egui::Label::new(
+                    ui.add_sized([90.0, style.height], egui::Label::new(
                         format!("{:>8}", format_memory(process.memory))
                     ));
 
+                    // GPU 使用率
+                    if columns.gpu {
+                        let gpu_text = match process.gpu_usage_percent {
+                            Some(usage) => RichText::new(format!("{:>5.1}%", usage)).color(cpu_usage_color(usage)),
+                            None => RichText::new("-").color(Color32::from_gray(100)),
+                        };
+                        ui.add_sized([70.0, style.height], egui::Label::new(gpu_text));
+                    }
+
+                    // 缺页速率（主要 / 次要）
+                    if columns.faults {
+                        let color = if process.major_fault_rate.is_some_and(|r| r >= MAJOR_FAULT_STORM_RATE) {
+                            Color32::from_rgb(255, 120, 100)
+                        } else {
+                            Color32::from_gray(180)
+                        };
+                        ui.add_sized([90.0, style.height], egui::Label::new(
+                            RichText::new(format!(
+                                "{} / {}",
+                                format_rate(process.major_fault_rate),
+                                format_rate(process.minor_fault_rate)
+                            )).monospace().color(color)
+                        ));
+                    }
+
+                    // 核心匹配（混合架构）
+                    if columns.core_fit {
+                        match process.core_mismatch {
+                            Some(mismatch) => {
+                                ui.add_sized([100.0, style.height], egui::Label::new(
+                                    RichText::new(mismatch.short_label()).size(11.0).color(Color32::from_rgb(255, 200, 100))
+                                )).on_hover_text(mismatch.label());
+                            }
+                            None => {
+                                ui.add_sized([100.0, style.height], egui::Label::new(
+                                    RichText::new("-").color(Color32::from_gray(100))
+                                ));
+                            }
+                        }
+                    }
+
                     // 调度策略
-                    ui.add_sized([70.0, 18.0], egui::Label::new(
+                    ui.add_sized([70.0, style.height], egui::Label::new(
                         RichText::new(process.sched_policy.short_name()).color(Color32::from_gray(180))
                     ));
 
                     // 亲和性
                     if is_editing {
-                        self.draw_affinity_editor(ui, process, logical_cores);
+                        self.draw_affinity_editor(ui, process, session, logical_cores);
                     } else {
                         let affinity_str = self.format_affinity(&process.affinity, logical_cores);
-                        if ui.add_sized([70.0, 18.0], egui::Button::new(
+                        if ui.add_sized([70.0, style.height], egui::Button::new(
                             RichText::new(&affinity_str).size(11.0)
                         ).rounding(Rounding::same(4.0))).clicked() {
                             self.editing_affinity = Some(process.pid);
@@ -257,9 +1414,24 @@ impl ProcessListPanel {
                                 }
                             }
                         }
+                        // 亲和性限定在单个 CCD 内时显示该 CCD
+                        if let Some(l3_id) = l3_containing(&process.affinity, &self.ccd_caches) {
+                            let color = self.ccd_palette.color(l3_id).unwrap_or(Color32::from_gray(180));
+                            ui.label(RichText::new(format!("CCD {}", l3_id)).size(10.0).color(color))
+                                .on_hover_text("进程的亲和性限定在此 CCD 内");
+                        }
                     }
                 });
-            });
+            })
+            .response;
+
+        // 左侧细条标示进程来源
+        let rect = frame_response.rect;
+        ui.painter().rect_filled(
+            egui::Rect::from_min_size(rect.min, egui::vec2(3.0, rect.height())),
+            Rounding::same(1.5),
+            origin_color(process.origin),
+        );
     }
 
     /// 格式化亲和性显示
@@ -277,14 +1449,45 @@ impl ProcessListPanel {
         }
     }
 
+    /// 应用通过检查的手动修改并记录到会话
+    fn run_manual_apply(&mut self, apply: ManualApply, session: &mut SessionJournal, logical_cores: usize) {
+        match apply.run(session, logical_cores) {
+            Ok(_) => {
+                self.editing_affinity = None;
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// 应用前的提醒和与自动规则冲突时的确认提示
+    fn draw_checked_apply(&mut self, ui: &mut Ui, session: &mut SessionJournal, logical_cores: usize) {
+        if let Some(apply) = self.checked.ui(ui, &self.apply_guard) {
+            self.run_manual_apply(apply, session, logical_cores);
+        }
+        self.rule_resolutions.extend(self.checked.take_resolutions());
+    }
+
     /// 绘制亲和性编辑器
-    fn draw_affinity_editor(&mut self, ui: &mut Ui, process: &ProcessInfo, logical_cores: usize) {
+    fn draw_affinity_editor(
+        &mut self,
+        ui: &mut Ui,
+        process: &ProcessInfo,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
         ui.horizontal(|ui| {
             // 核心复选框（简化显示）
             let show_count = logical_cores.min(8);
             for (i, selected) in self.affinity_selection.iter_mut().enumerate().take(show_count) {
-                let label = format!("{}", i);
-                ui.checkbox(selected, label);
+                if self.reserved_cores.contains(&i) {
+                    ui.checkbox(selected, RichText::new(format!("{}", i)).color(RESERVED_CORE_COLOR))
+                        .on_hover_text("保留给系统的核心");
+                } else {
+                    ui.checkbox(selected, format!("{}", i));
+                }
             }
 
             if logical_cores > 8 {
@@ -292,25 +1495,16 @@ impl ProcessListPanel {
             }
 
             if ui.small_button("✓").clicked() {
-                let cores: Vec<usize> = self
-                    .affinity_selection
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, &selected)| selected)
-                    .map(|(i, _)| i)
-                    .collect();
+                let cores = selected_cores(&self.affinity_selection);
 
                 if cores.is_empty() {
                     self.error_message = Some("至少选择一个核心".to_string());
                 } else {
-                    match set_process_affinity(process.pid as i32, &cores) {
-                        Ok(_) => {
-                            self.editing_affinity = None;
-                            self.error_message = None;
-                        }
-                        Err(e) => {
-                            self.error_message = Some(e);
-                        }
+                    let changes = vec![SchedChange::Affinity(cores)];
+                    match self.checked.request(&self.apply_guard, process, changes, None) {
+                        Ok(Some(apply)) => self.run_manual_apply(apply, session, logical_cores),
+                        Ok(None) => self.editing_affinity = None,
+                        Err(e) => self.error_message = Some(e),
                     }
                 }
             }
@@ -318,19 +1512,54 @@ impl ProcessListPanel {
             if ui.small_button("✕").clicked() {
                 self.editing_affinity = None;
             }
+
+            if process.container.is_some() {
+                ui.label(RichText::new("⚠").color(Color32::from_rgb(255, 200, 100)))
+                    .on_hover_text("该进程运行在容器中，容器运行时的 cpuset 可能会限制此更改");
+            }
+
+            let selected = selected_cores(&self.affinity_selection);
+            let reserved = reserved_among(&selected, &self.reserved_cores);
+            if !reserved.is_empty() {
+                ui.label(RichText::new(format!("⚠ 包含保留核心 {}", format_cpu_list(&reserved)))
+                    .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+            }
+
+            // 与当前亲和性的差异（颜色与应用后核心网格的闪烁一致）
+            let diff = affinity_diff(&process.affinity, &selected);
+            if !diff.added.is_empty() {
+                ui.label(RichText::new(format!("+{}", format_cpu_list(&diff.added)))
+                    .size(11.0).color(Color32::from_rgb(100, 200, 100)));
+            }
+            if !diff.removed.is_empty() {
+                ui.label(RichText::new(format!("-{}", format_cpu_list(&diff.removed)))
+                    .size(11.0).color(Color32::from_rgb(255, 100, 100)));
+            }
         });
     }
 
     /// 绘制进程详情
-    fn draw_process_details(&self, ui: &mut Ui, process: &ProcessInfo) {
+    fn draw_process_details(
+        &mut self,
+        ui: &mut Ui,
+        process: &ProcessInfo,
+        history: Option<&ProcessHistory>,
+        applied_by: Option<&AppliedBy>,
+        logical_cores: usize,
+    ) {
         Frame::none()
             .fill(Color32::from_gray(35))
             .inner_margin(Margin::same(16.0))
             .rounding(Rounding::same(8.0))
             .stroke(Stroke::new(1.0, Color32::from_gray(60)))
             .show(ui, |ui| {
-                ui.label(RichText::new(format!("进程详情: {} (PID: {})", process.name, process.pid))
-                    .size(16.0).strong());
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("进程详情: {} (PID: {})", process.name, process.pid))
+                        .size(16.0).strong());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        self.draw_kill_buttons(ui, process);
+                    });
+                });
                 ui.add_space(12.0);
 
                 egui::Grid::new("process_details")
@@ -338,13 +1567,45 @@ impl ProcessListPanel {
                     .spacing([20.0, 8.0])
                     .show(ui, |ui| {
                         ui.label(RichText::new("命令行").color(Color32::from_gray(160)));
-                        ui.label(&process.cmd);
+                        // 参数随调度详情在后台读取，尚未加载时只显示拼接后的命令行
+                        let args = match self.details_loaders.get(&process.pid).map(|l| l.state(process.pid)) {
+                            Some(DetailsState::Loaded(details)) => details.args.as_slice(),
+                            _ => &[],
+                        };
+                        ui.add(CommandLine::new(&process.display_command(), process.pid).args(args));
                         ui.end_row();
 
                         ui.label(RichText::new("状态").color(Color32::from_gray(160)));
                         ui.label(&process.status);
                         ui.end_row();
 
+                        ui.label(RichText::new("内存").color(Color32::from_gray(160)));
+                        ui.horizontal(|ui| {
+                            ui.label(format_memory(process.memory));
+                            if process.memory_delta != 0 {
+                                let (sign, color) = if process.memory_delta > 0 {
+                                    ("+", Color32::from_rgb(255, 150, 100))
+                                } else {
+                                    ("-", Color32::from_rgb(100, 200, 100))
+                                };
+                                ui.label(RichText::new(format!("{}{}", sign, format_memory(process.memory_delta.unsigned_abs())))
+                                    .size(11.0).color(color));
+                            }
+                        });
+                        ui.end_row();
+
+                        if let Some(container) = &process.container {
+                            ui.label(RichText::new("容器").color(Color32::from_gray(160)));
+                            ui.horizontal(|ui| {
+                                ui.label(container.display());
+                                if container.pid_ns_differs {
+                                    ui.label(RichText::new("独立 PID 命名空间").size(11.0).color(Color32::from_rgb(255, 200, 100)))
+                                        .on_hover_text("容器内的工具看到的 PID 与此处显示的宿主机 PID 不同");
+                                }
+                            });
+                            ui.end_row();
+                        }
+
                         ui.label(RichText::new("调度策略").color(Color32::from_gray(160)));
                         ui.label(process.sched_policy.display_name());
                         ui.end_row();
@@ -353,12 +1614,471 @@ impl ProcessListPanel {
                         ui.label(format!("{}", process.priority));
                         ui.end_row();
 
+                        if process.sched_attr.has_deadline_params() {
+                            ui.label(RichText::new("Deadline 参数").color(Color32::from_gray(160)));
+                            ui.label(process.sched_attr.format_deadline());
+                            ui.end_row();
+                        }
+
                         ui.label(RichText::new("CPU 亲和性").color(Color32::from_gray(160)));
                         ui.label(format!("{:?}", process.affinity));
                         ui.end_row();
+
+                        if let Some(cpu) = process.last_cpu {
+                            ui.label(RichText::new("最近运行核心").color(Color32::from_gray(160)));
+                            ui.label(format!("CPU {}", cpu));
+                            ui.end_row();
+                        }
+
+                        if let Some(applied_by) = applied_by {
+                            ui.label(RichText::new("当前配置来源").color(Color32::from_gray(160)));
+                            ui.label(RichText::new(applied_by.describe(Instant::now())).color(Color32::from_rgb(150, 200, 255)))
+                                .on_hover_text("hexin 最近一次成功应用的设置，外部修改后不再显示");
+                            ui.end_row();
+                        }
+
+                        ui.label(RichText::new("缺页").color(Color32::from_gray(160)));
+                        ui.vertical(|ui| {
+                            ui.label(format!(
+                                "次要 {} ({}/s)，主要 {} ({}/s)",
+                                process.minor_faults,
+                                format_rate(process.minor_fault_rate),
+                                process.major_faults,
+                                format_rate(process.major_fault_rate)
+                            ));
+                            if process.major_fault_rate.is_some_and(|r| r >= MAJOR_FAULT_STORM_RATE) {
+                                ui.label(RichText::new("⚠ 主要缺页频繁：进程在等待从磁盘读入内存，调整调度或绑定核心不会改善")
+                                    .size(11.0).color(Color32::from_rgb(255, 150, 100)));
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(RichText::new("Seccomp").color(Color32::from_gray(160)));
+                        let (seccomp, color) = match process.seccomp_mode {
+                            0 => ("禁用", Color32::from_gray(200)),
+                            1 => ("严格", Color32::from_rgb(100, 200, 100)),
+                            _ => ("过滤器", Color32::from_rgb(100, 200, 100)),
+                        };
+                        ui.label(RichText::new(seccomp).color(color));
+                        ui.end_row();
+
+                        if process.exe_deleted {
+                            ui.label(RichText::new("可执行文件").color(Color32::from_gray(160)));
+                            ui.label(RichText::new(format!("⚠ {}", EXE_DELETED_HINT)).color(Color32::from_rgb(255, 200, 100)))
+                                .on_hover_text("可执行文件在进程启动后被删除或替换（如软件包升级），进程仍在运行旧代码");
+                            ui.end_row();
+                        }
                     });
+
+                ui.add_space(12.0);
+                self.draw_sched_details(ui, process.pid, logical_cores);
+
+                if self.delay_worker.is_some() {
+                    ui.add_space(12.0);
+                    self.draw_delay_accounting(ui, process.pid);
+                }
+
+                ui.add_space(12.0);
+                self.draw_sched_debug(ui, process.pid);
+
+                if let Some(history) = history.filter(|h| h.pid() == process.pid) {
+                    ui.add_space(12.0);
+                    self.draw_process_history(ui, history);
+                }
+
+                ui.add_space(12.0);
+                self.draw_memory_features(ui, process.pid);
             });
     }
+
+    /// 绘制结束进程按钮（按住确认）
+    fn draw_kill_buttons(&mut self, ui: &mut Ui, process: &ProcessInfo) {
+        // 不允许结束 hexin 自身
+        let enabled = process.pid != std::process::id();
+        if ui.add(HoldButton::new("强制结束").enabled(enabled)).changed() {
+            if let Err(e) = terminate_process(process.pid as i32, true) {
+                self.error_message = Some(e);
+            }
+        }
+        if ui
+            .add(HoldButton::new("结束").color(Color32::from_rgb(255, 180, 100)).enabled(enabled))
+            .changed()
+        {
+            if let Err(e) = terminate_process(process.pid as i32, false) {
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// 绘制异步加载的调度详情
+    fn draw_sched_details(&mut self, ui: &mut Ui, pid: u32, logical_cores: usize) {
+        let loader = self.details_loaders.entry(pid).or_default();
+        loader.request(pid, logical_cores);
+
+        let mut reload = false;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("调度详情").size(14.0).strong());
+            if ui.small_button("刷新").clicked() {
+                reload = true;
+            }
+        });
+        ui.add_space(8.0);
+
+        match loader.state(pid) {
+            DetailsState::Loading => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new("正在读取...").color(Color32::from_gray(140)));
+                });
+            }
+            DetailsState::Exited => {
+                ui.label(RichText::new("进程已退出").color(Color32::from_rgb(255, 150, 100)));
+            }
+            DetailsState::Loaded(details) => {
+                egui::Grid::new("process_sched_details")
+                    .num_columns(2)
+                    .spacing([20.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label(RichText::new("调度策略").color(Color32::from_gray(160)));
+                        ui.label(match details.rt_priority {
+                            Some(prio) if details.policy.is_realtime() => {
+                                format!("{} (优先级 {})", details.policy.display_name(), prio)
+                            }
+                            _ => format!("{} (nice {})", details.policy.display_name(), details.nice),
+                        });
+                        ui.end_row();
+
+                        ui.label(RichText::new("I/O 优先级").color(Color32::from_gray(160)));
+                        ui.label(details.io_priority.map_or("-".to_string(), |p| p.display()));
+                        ui.end_row();
+
+                        ui.label(RichText::new("OOM 调整").color(Color32::from_gray(160)));
+                        ui.label(details.oom_score_adj.map_or("-".to_string(), |v| v.to_string()));
+                        ui.end_row();
+
+                        ui.label(RichText::new("CPU 亲和性").color(Color32::from_gray(160)));
+                        ui.label(format_cpu_list(&details.affinity));
+                        ui.end_row();
+
+                        ui.label(RichText::new("cgroup").color(Color32::from_gray(160)));
+                        ui.label(details.cgroup.as_deref().unwrap_or("-"));
+                        ui.end_row();
+
+                        if let Some(limits) = details.limits {
+                            ui.label(RichText::new("资源限制").color(Color32::from_gray(160)));
+                            ui.label(format!(
+                                "Max nice {} / {} (nice ≥ {}) · Max RT 优先级 {} / {}",
+                                limits.nice.soft,
+                                limits.nice.hard,
+                                limits.min_nice(),
+                                limits.rtprio.soft,
+                                limits.rtprio.hard
+                            ))
+                            .on_hover_text("软限制 / 硬限制。没有 CAP_SYS_NICE 时，降低 nice 或设置实时优先级受目标进程的软限制约束");
+                            ui.end_row();
+                        }
+
+                        ui.label(RichText::new("文件描述符").color(Color32::from_gray(160)));
+                        match &details.open_fds {
+                            Ok(count) => {
+                                let open_files = details.limits.and_then(|l| l.open_files);
+                                let usage = details.limits.and_then(|l| l.open_files_usage(*count));
+                                let text = match open_files {
+                                    Some(limit) => format!("{} / {}", count, limit.soft),
+                                    None => count.to_string(),
+                                };
+                                if usage.is_some_and(|u| u > 0.8) {
+                                    ui.label(RichText::new(format!("⚠ {}", text)).color(Color32::from_rgb(255, 200, 100)))
+                                        .on_hover_text("已超过软限制（RLIMIT_NOFILE）的 80%，达到上限后打开文件或建立连接会失败");
+                                } else {
+                                    ui.label(text).on_hover_text("已打开的文件描述符数 / 软限制（RLIMIT_NOFILE）");
+                                }
+                            }
+                            Err(e) => {
+                                ui.label(RichText::new(e).color(Color32::from_gray(140)));
+                            }
+                        }
+                        ui.end_row();
+                    });
+            }
+        }
+
+        if reload {
+            loader.reload();
+        }
+    }
+
+    /// 绘制内核调度实体统计（按需读取 sched/debug，显示各线程的迁移增量）
+    fn draw_sched_debug(&mut self, ui: &mut Ui, pid: u32) {
+        if !*self.sched_debug_readable.get_or_insert_with(is_sched_debug_readable) {
+            ui.label(RichText::new("内核调度实体统计不可用：需要挂载 debugfs (mount -t debugfs none /sys/kernel/debug) 并以 root 运行")
+                .size(11.0).color(Color32::from_gray(120)));
+            return;
+        }
+
+        egui::CollapsingHeader::new(RichText::new("内核调度实体 (sched/debug)").size(14.0).strong())
+            .id_salt(("sched_debug", pid))
+            .show(ui, |ui| {
+                let mut read = false;
+                ui.horizontal(|ui| {
+                    if ui.small_button("读取").clicked() {
+                        read = true;
+                    }
+                    match self.sched_debug.get(&pid) {
+                        Some(view) => ui.label(RichText::new(format!("{} 秒前读取", view.read_at.elapsed().as_secs()))
+                            .size(11.0).color(Color32::from_gray(140))),
+                        None => ui.label(RichText::new("文件较大，只在点击时读取").size(11.0).color(Color32::from_gray(140))),
+                    };
+                });
+                if read {
+                    let result = read_sched_debug(pid);
+                    let deltas = match (self.sched_debug.get(&pid).map(|v| &v.result), &result) {
+                        (Some(Ok(previous)), Ok(current)) => migration_deltas(previous, current),
+                        _ => HashMap::new(),
+                    };
+                    self.sched_debug.insert(pid, SchedDebugView { read_at: Instant::now(), result, migration_deltas: deltas });
+                }
+
+                let Some(view) = self.sched_debug.get(&pid) else {
+                    return;
+                };
+                let tasks = match &view.result {
+                    Ok(tasks) => tasks,
+                    Err(e) => {
+                        ui.label(RichText::new(e).color(Color32::from_rgb(255, 150, 100)));
+                        return;
+                    }
+                };
+                if tasks.is_empty() {
+                    ui.label(RichText::new("sched/debug 中没有找到该进程的线程").color(Color32::from_gray(140)));
+                    return;
+                }
+                ui.add_space(4.0);
+                let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+                ScrollArea::vertical().id_salt(("sched_debug_tasks", pid)).max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new(("sched_debug_grid", pid))
+                        .num_columns(8)
+                        .striped(true)
+                        .spacing([12.0, 4.0])
+                        .show(ui, |ui| {
+                            for header in ["TID", "线程", "CPU", "vruntime", "运行时间", "切换", "prio", "迁移"] {
+                                ui.label(RichText::new(header).size(12.0).color(Color32::from_gray(160)));
+                            }
+                            ui.end_row();
+                            for task in tasks {
+                                ui.label(RichText::new(task.tid.to_string()).monospace());
+                                ui.label(&task.comm);
+                                ui.label(optional(task.cpu.map(|c| c.to_string())));
+                                ui.label(optional(task.vruntime_ms.map(|v| format!("{:.1} ms", v))));
+                                ui.label(optional(task.sum_exec_ms.map(|v| format!("{:.1} ms", v))));
+                                ui.label(optional(task.switches.map(|v| v.to_string())));
+                                ui.label(optional(task.prio.map(|v| v.to_string())));
+                                let migrations = optional(task.nr_migrations.map(|v| v.to_string()));
+                                match view.migration_deltas.get(&task.tid) {
+                                    Some(&delta) if delta > 0 => {
+                                        ui.label(RichText::new(format!("{} (+{})", migrations, delta))
+                                            .color(Color32::from_rgb(255, 200, 100)))
+                                            .on_hover_text("上次读取以来的迁移次数：频繁迁移时可考虑绑定核心");
+                                    }
+                                    _ => {
+                                        ui.label(migrations);
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+
+    /// 绘制延迟统计 (delay accounting)
+    fn draw_delay_accounting(&mut self, ui: &mut Ui, pid: u32) {
+        // 在工作线程中查询，每秒最多一次
+        if let Some(worker) = &mut self.delay_worker {
+            for (pid, result) in worker.poll() {
+                self.delay_stats.insert(pid, (Instant::now(), result));
+            }
+            let stale = self
+                .delay_stats
+                .get(&pid)
+                .is_none_or(|(read_at, _)| read_at.elapsed() > Duration::from_secs(1));
+            if stale {
+                worker.request(pid);
+            }
+        }
+
+        ui.label(RichText::new("延迟统计").size(14.0).strong());
+        ui.add_space(8.0);
+
+        let Some((_, result)) = self.delay_stats.get(&pid) else {
+            return;
+        };
+        let DelaySample { stats, cpu_wait_percent, delay_accounting } = match result {
+            Ok(sample) => sample,
+            Err(e) => {
+                ui.label(RichText::new(format!("无法读取: {}", e)).color(Color32::from_gray(140)));
+                return;
+            }
+        };
+        const ENABLE_HINT: &str = "可执行 sysctl -w kernel.task_delayacct=1 或添加启动参数 delayacct";
+        if stats.is_empty() {
+            ui.label(RichText::new(format!("内核未开启延迟统计，{}", ENABLE_HINT)).color(Color32::from_gray(140)));
+            return;
+        }
+        // 关闭 task_delayacct 时较新的内核仍会提供 CPU 等待（来自 sched_info），但 I/O 和换入为 0
+        let delayacct_off = *delay_accounting == Some(false);
+
+        let ms = |ns: u64| ns as f64 / 1_000_000.0;
+        egui::Grid::new("process_delay_accounting")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("CPU 等待").color(Color32::from_gray(160)))
+                    .on_hover_text("在运行队列上等待 CPU 的时间，反映调度饥饿程度");
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:.1} ms", ms(stats.cpu_delay_ns)));
+                    if let Some(avg) = stats.avg_cpu_delay_ns() {
+                        ui.label(RichText::new(format!("平均 {:.1} µs/次", avg / 1000.0)).color(Color32::from_gray(160)));
+                    }
+                    if let Some(percent) = cpu_wait_percent {
+                        ui.label(RichText::new(format!("最近 {:.1}%", percent)).color(cpu_usage_color(*percent)));
+                    }
+                });
+                ui.end_row();
+
+                if !delayacct_off {
+                    ui.label(RichText::new("块 I/O 等待").color(Color32::from_gray(160)));
+                    ui.label(format!("{:.1} ms ({} 次)", ms(stats.blkio_delay_ns), stats.blkio_count));
+                    ui.end_row();
+
+                    ui.label(RichText::new("换入等待").color(Color32::from_gray(160)));
+                    ui.label(format!("{:.1} ms ({} 次)", ms(stats.swapin_delay_ns), stats.swapin_count));
+                    ui.end_row();
+                }
+            });
+
+        if delayacct_off {
+            ui.label(
+                RichText::new(format!("块 I/O 和换入等待需要开启延迟统计，{}", ENABLE_HINT))
+                    .size(11.0)
+                    .color(Color32::from_gray(140)),
+            );
+        }
+    }
+
+    /// 绘制进程 CPU 使用率和运行延迟曲线
+    fn draw_process_history(&self, ui: &mut Ui, history: &ProcessHistory) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("调度历史").size(14.0).strong());
+            if let Some(delay) = history.latest_run_delay() {
+                ui.add_space(12.0);
+                ui.label(RichText::new(format!("运行延迟: {:.1} ms/s", delay)).color(Color32::from_rgb(255, 150, 80)))
+                    .on_hover_text("每秒处于可运行状态但在等待 CPU 的时间 (/proc/<pid>/schedstat)");
+            }
+        });
+        ui.add_space(8.0);
+
+        let cpu_line = Line::new(PlotPoints::new(history.cpu_plot_data()))
+            .color(Color32::from_rgb(100, 180, 255))
+            .width(1.5)
+            .name("CPU %");
+        let delay_data = history.run_delay_plot_data();
+        let delay_line = (!delay_data.is_empty()).then(|| {
+            Line::new(PlotPoints::new(delay_data))
+                .color(Color32::from_rgb(255, 150, 80))
+                .width(1.5)
+                .name("运行延迟 ms/s")
+        });
+
+        Plot::new("process_history_plot")
+            .height(120.0)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .show_axes([false, true])
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.line(cpu_line);
+                if let Some(delay_line) = delay_line {
+                    plot_ui.line(delay_line);
+                }
+            });
+    }
+
+    /// 绘制内存特性（高级）
+    fn draw_memory_features(&mut self, ui: &mut Ui, pid: u32) {
+        // smaps_rollup 读取代价较高，每秒最多读取一次
+        let stale = self
+            .memory_features
+            .get(&pid)
+            .is_none_or(|(read_at, _)| read_at.elapsed() > Duration::from_secs(1));
+        if stale {
+            self.memory_features.insert(pid, (Instant::now(), read_process_memory_features(pid)));
+            self.thp_setting = read_thp_setting();
+        }
+        let features = self.memory_features.get(&pid).and_then(|(_, f)| f.clone());
+        let thp_setting = self.thp_setting.clone();
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("内存特性").size(14.0).strong());
+            ui.label(RichText::new("高级").size(11.0).color(Color32::from_rgb(255, 200, 100)));
+        });
+        ui.add_space(8.0);
+
+        egui::Grid::new("process_memory_features")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                match &features {
+                    Some(features) => {
+                        ui.label(RichText::new("匿名大页").color(Color32::from_gray(160)));
+                        ui.label(format_memory(features.anon_huge_pages_kb * 1024));
+                        ui.end_row();
+
+                        if let Some(eligible) = features.thp_eligible {
+                            ui.label(RichText::new("THP 适用").color(Color32::from_gray(160)));
+                            ui.label(if eligible { "是" } else { "否" });
+                            ui.end_row();
+                        }
+
+                        if let Some(pages) = features.ksm_merging_pages {
+                            ui.label(RichText::new("KSM 合并页").color(Color32::from_gray(160)));
+                            ui.label(format!("{}", pages));
+                            ui.end_row();
+                        }
+                    }
+                    None => {
+                        ui.label(RichText::new("进程特性").color(Color32::from_gray(160)));
+                        ui.label(RichText::new("无法读取 (可能需要 root 权限)").color(Color32::from_gray(140)));
+                        ui.end_row();
+                    }
+                }
+
+                // 系统级 THP 设置（可修改）
+                if let Some(setting) = thp_setting {
+                    ui.label(RichText::new("系统 THP 模式").color(Color32::from_gray(160)));
+                    let mut selected = setting.current;
+                    ComboBox::from_id_salt(("thp_mode", pid))
+                        .width(160.0)
+                        .selected_text(selected.display_name())
+                        .show_ui(ui, |ui| {
+                            for mode in &setting.available {
+                                ui.selectable_value(&mut selected, *mode, mode.display_name());
+                            }
+                        });
+                    if selected != setting.current {
+                        match set_thp_mode(selected) {
+                            Ok(()) => self.thp_setting = read_thp_setting(),
+                            Err(e) => self.error_message = Some(e),
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.label(RichText::new("系统 THP 模式影响所有进程，进程级设置仅供查看").size(11.0).color(Color32::from_gray(140)));
+    }
 }
 
 impl Default for ProcessListPanel {
@@ -367,6 +2087,86 @@ impl Default for ProcessListPanel {
     }
 }
 
+/// 亲和性编辑器中勾选的核心
+fn selected_cores(selection: &[bool]) -> Vec<usize> {
+    selection.iter().enumerate().filter(|(_, &selected)| selected).map(|(i, _)| i).collect()
+}
+
+/// 绘制刚退出的进程行
+fn draw_exited_row(ui: &mut Ui, process: &ProcessInfo, style: RowStyle) {
+    Frame::none()
+        .fill(Color32::from_rgb(45, 30, 30))
+        .inner_margin(style.margin)
+        .rounding(Rounding::same(4.0))
+        .show(ui, |ui| {
+            style.apply_font(ui);
+            ui.horizontal(|ui| {
+                ui.add_sized([70.0, style.height], egui::Label::new(
+                    RichText::new(format!("{:>6}", process.pid)).monospace().strikethrough().color(Color32::from_gray(120))
+                ));
+                ui.add_sized([180.0, style.height], egui::Label::new(
+                    RichText::new(&process.name).strikethrough().color(Color32::from_gray(140))
+                ).truncate());
+                ui.label(RichText::new("已退出").size(11.0).color(Color32::from_rgb(255, 120, 120)));
+            });
+        });
+}
+
+/// 格式化每秒速率（无数据时为 "-"）
+fn format_rate(rate: Option<f64>) -> String {
+    match rate {
+        None => "-".to_string(),
+        Some(r) if r >= 1000.0 => format!("{:.1}k", r / 1000.0),
+        Some(r) => format!("{:.0}", r),
+    }
+}
+
+/// 颜色线性插值
+fn lerp_color(from: Color32, to: Color32, t: f32) -> Color32 {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color32::from_rgb(mix(from.r(), to.r()), mix(from.g(), to.g()), mix(from.b(), to.b()))
+}
+
+/// 名称单元格的悬停提示：完整命令行（换行显示，过长时截断）和复制按钮
+fn command_tooltip(ui: &mut Ui, process: &ProcessInfo) {
+    ui.set_max_width(TOOLTIP_COMMAND_WIDTH);
+    let command = process.display_command();
+    let shown = match truncate_chars(&command, TOOLTIP_COMMAND_CHARS) {
+        Some(preview) => format!("{}…", preview),
+        None => command.to_string(),
+    };
+    ui.add(egui::Label::new(RichText::new(shown).monospace()).wrap());
+    if ui.small_button("复制").on_hover_text("复制完整命令行").clicked() {
+        ui.ctx().copy_text(command.into_owned());
+    }
+}
+
+/// 容器徽标
+fn container_badge(ui: &mut Ui, container: &ContainerInfo) {
+    Frame::none()
+        .fill(Color32::from_rgb(40, 70, 100))
+        .rounding(Rounding::same(3.0))
+        .inner_margin(Margin::symmetric(4.0, 0.0))
+        .show(ui, |ui| {
+            ui.set_width(38.0);
+            ui.label(RichText::new(container.runtime.display_name()).size(10.0).color(Color32::from_rgb(150, 200, 255)));
+        })
+        .response
+        .on_hover_text(container.display());
+}
+
+/// 进程来源对应的颜色
+fn origin_color(origin: ProcessOrigin) -> Color32 {
+    match origin {
+        ProcessOrigin::UserApp => Color32::from_rgb(100, 200, 120),
+        ProcessOrigin::UserService => Color32::from_rgb(100, 170, 255),
+        ProcessOrigin::SystemService => Color32::from_rgb(230, 170, 60),
+        ProcessOrigin::Kernel => Color32::from_rgb(180, 120, 220),
+        ProcessOrigin::Unknown => Color32::from_gray(90),
+    }
+}
+
 /// CPU 使用率转颜色
 fn cpu_usage_color(usage: f32) -> Color32 {
     if usage < 10.0 {
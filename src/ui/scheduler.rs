@@ -1,11 +1,65 @@
 //! 调度策略配置面板
 
-use eframe::egui::{self, Color32, ComboBox, Frame, Margin, RichText, Rounding, ScrollArea, Slider, Stroke, TextEdit, Ui};
+use eframe::egui::{
+    self, Color32, ComboBox, DragValue, Frame, Margin, RichText, Rounding, ScrollArea, Slider, Stroke, TextEdit, Ui,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use crate::system::{
-    get_rt_priority_range, set_process_affinity, set_process_nice, set_scheduler,
-    ProcessManager, SchedulePolicy, SchedulePreset,
+    check_pid, child_count, format_cpu_list, get_rt_priority_range, get_rt_throttling, inheritance_risk,
+    parse_cpu_list, policy_default_priority, preset_changes, read_process_limits, reserved_among, set_reset_on_fork,
+    set_rt_throttling, suspected_rt_inheritance, unix_timestamp, CoreTopology, DeferredAction, IoClass, IoPriority,
+    PidCheck, ProcessInfo, ProcessLimits, PresetStore, PrivilegeInfo, ProcessManager, RtThrottling, RuleResolution,
+    SchedChange, SchedulePolicy, SchedulePreset, SessionJournal, DEFAULT_DEFERRED_EXPIRY_HOURS,
 };
+use crate::ui::conflict::{ApplyGuard, CheckedApply, ManualApply};
+
+/// PID 输入停顿多久后再检查
+const PID_CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+/// 快速选择在未搜索时显示的进程数（CPU 使用率最高的）
+const SELECTOR_TOP_PROCESSES: usize = 30;
+/// 快速选择搜索结果最多显示的进程数
+const SELECTOR_MAX_MATCHES: usize = 200;
+
+/// 一次性延后动作的修改（由应用交给规则引擎执行）
+pub enum DeferredEdit {
+    /// 加入新动作
+    Add(DeferredAction),
+    /// 按编号删除
+    Remove(u64),
+}
+
+/// 预设编辑草稿
+struct PresetDraft {
+    /// 预设在存储中的索引
+    index: usize,
+    /// 编辑中的预设
+    preset: SchedulePreset,
+    /// 亲和性输入（如 "0-7,16-23"，留空表示不限制）
+    affinity_text: String,
+}
+
+/// 等待确认的实时策略应用（目标进程的子进程会继承实时策略）
+struct PendingRtApply {
+    pid: i32,
+    /// 风险说明
+    warning: String,
+    /// 待应用的设置
+    changes: Vec<SchedChange>,
+    /// 来自预设时为该预设
+    preset: Option<SchedulePreset>,
+    /// 是否设置 reset-on-fork（默认开启）
+    reset_on_fork: bool,
+}
+
+/// 预设列表中的操作
+enum PresetAction {
+    Apply(i32, SchedulePreset),
+    Duplicate(usize),
+    Edit(usize),
+    Remove(usize),
+}
 
 /// 调度策略面板
 pub struct SchedulerPanel {
@@ -15,44 +69,255 @@ pub struct SchedulerPanel {
     editing_policy: SchedulePolicy,
     /// 编辑中的优先级
     editing_priority: i32,
-    /// 预设列表
-    presets: Vec<SchedulePreset>,
+    /// 预设存储
+    presets: PresetStore,
+    /// 编辑中的预设
+    editing_preset: Option<PresetDraft>,
     /// PID 输入框
     pid_input: String,
+    /// 预设搜索
+    preset_search: String,
+    /// 快速选择进程的搜索
+    process_search: String,
+    /// 快速选择的搜索结果缓存（搜索文本, 进程数据版本, 按 CPU 排序的 PID）
+    process_matches: Option<(String, u64, Vec<u32>)>,
+    /// 下一帧让快速选择的搜索框获得焦点
+    focus_search: bool,
     /// 错误消息
     error_message: Option<String>,
     /// 成功消息
     success_message: Option<String>,
+    /// hexin 自身的权限
+    privilege: PrivilegeInfo,
+    /// 输入 PID 的预检查结果
+    pid_check: Option<(u32, PidCheck)>,
+    /// 输入 PID 的 RLIMIT_NICE / RLIMIT_RTPRIO
+    pid_limits: Option<ProcessLimits>,
+    /// 待检查的 PID 输入时间（防抖）
+    pid_check_pending: Option<Instant>,
+    /// 核心拓扑（含保留核心，用于生成内置预设）
+    topology: CoreTopology,
+    /// 内置预设包含保留核心（显式覆盖）
+    include_reserved: bool,
+    /// 实时进程限流参数（None 表示无法读取）
+    rt_throttling: Option<RtThrottling>,
+    /// 编辑中的限流参数
+    rt_throttling_draft: Option<RtThrottling>,
+    /// 等待确认的禁用限流
+    rt_disable_pending: bool,
+    /// 等待确认的实时策略应用
+    pending_rt_apply: Option<PendingRtApply>,
+    /// 待加入或移出监视列表的 PID
+    watch_toggle: Option<u32>,
+    /// 手动应用前检查所需的状态（规则变化时由应用更新）
+    apply_guard: ApplyGuard,
+    /// 等待确认的应用（V-Cache / 保留核心提醒、与自动规则冲突）
+    checked: CheckedApply,
+    /// 待规则引擎执行的冲突处理
+    rule_resolutions: Vec<RuleResolution>,
+    /// 一次性延后动作快照（每帧由应用更新）
+    deferred_actions: Vec<DeferredAction>,
+    /// 新延后动作的草稿（进程名, 预设名称, 有效期小时）
+    deferred_draft: (String, String, u64),
+    /// 待规则引擎执行的延后动作修改
+    deferred_edits: Vec<DeferredEdit>,
 }
 
 impl SchedulerPanel {
-    pub fn new(vcache_cores: &[usize], all_cores: usize) -> Self {
+    pub fn new(topology: &CoreTopology) -> Self {
         Self {
             selected_pid: None,
             editing_policy: SchedulePolicy::Other,
             editing_priority: 0,
-            presets: SchedulePreset::builtin_presets(vcache_cores, all_cores),
+            presets: PresetStore::load(topology),
+            editing_preset: None,
             pid_input: String::new(),
+            preset_search: String::new(),
+            process_search: String::new(),
+            process_matches: None,
+            focus_search: false,
             error_message: None,
             success_message: None,
+            privilege: PrivilegeInfo::detect(),
+            pid_check: None,
+            pid_limits: None,
+            pid_check_pending: None,
+            topology: topology.clone(),
+            include_reserved: false,
+            rt_throttling: get_rt_throttling(),
+            rt_throttling_draft: None,
+            rt_disable_pending: false,
+            pending_rt_apply: None,
+            watch_toggle: None,
+            apply_guard: ApplyGuard::default(),
+            checked: CheckedApply::default(),
+            rule_resolutions: Vec::new(),
+            deferred_actions: Vec::new(),
+            deferred_draft: (String::new(), String::new(), DEFAULT_DEFERRED_EXPIRY_HOURS),
+            deferred_edits: Vec::new(),
+        }
+    }
+
+    /// 更新核心拓扑（启动检测完成、保留核心变化时），有变化时重新生成内置预设并返回 true
+    pub fn set_topology(&mut self, topology: &CoreTopology) -> bool {
+        if self.topology == *topology {
+            return false;
+        }
+        self.topology = topology.clone();
+        self.rebuild_builtin_presets();
+        true
+    }
+
+    /// 按当前拓扑和覆盖选项重新生成内置预设
+    fn rebuild_builtin_presets(&mut self) {
+        let topology = if self.include_reserved {
+            self.topology.including_reserved()
+        } else {
+            self.topology.clone()
+        };
+        self.presets.set_builtin(SchedulePreset::builtin_presets(&topology));
+    }
+
+    /// 更新手动应用前的检查状态
+    pub fn set_apply_guard(&mut self, guard: ApplyGuard) {
+        self.apply_guard = guard;
+    }
+
+    /// 取出待规则引擎执行的冲突处理
+    pub fn take_rule_resolutions(&mut self) -> Vec<RuleResolution> {
+        std::mem::take(&mut self.rule_resolutions)
+    }
+
+    /// 取出用户是否选择了不再提示 V-Cache 提醒
+    pub fn take_vcache_opt_out(&mut self) -> bool {
+        self.checked.take_vcache_opt_out()
+    }
+
+    /// 更新一次性延后动作快照
+    pub fn set_deferred_actions(&mut self, actions: Vec<DeferredAction>) {
+        self.deferred_actions = actions;
+    }
+
+    /// 取出待规则引擎执行的延后动作修改
+    pub fn take_deferred_edits(&mut self) -> Vec<DeferredEdit> {
+        std::mem::take(&mut self.deferred_edits)
+    }
+
+    /// 选中的进程
+    pub fn selected_pid(&self) -> Option<u32> {
+        self.selected_pid
+    }
+
+    /// 设置快速选择的搜索文本（启动参数或其他实例转发的 --filter）
+    pub fn set_process_search(&mut self, text: String) {
+        self.process_search = text;
+    }
+
+    /// 让快速选择的搜索框获得焦点（快捷键）
+    pub fn focus_search(&mut self) {
+        self.focus_search = true;
+    }
+
+    /// 取出待加入或移出监视列表的 PID（由应用处理）
+    pub fn take_watch_toggle(&mut self) -> Option<u32> {
+        self.watch_toggle.take()
+    }
+
+    /// 所有预设（供其他面板批量应用）
+    pub fn presets(&self) -> &[SchedulePreset] {
+        self.presets.presets()
+    }
+
+    /// 自定义预设（用于导出调优档案）
+    pub fn custom_presets(&self) -> Vec<SchedulePreset> {
+        self.presets.custom()
+    }
+
+    /// 替换并保存自定义预设（启用调优档案时）
+    pub fn set_custom_presets(&mut self, presets: Vec<SchedulePreset>) {
+        self.presets.set_custom(presets);
+        self.presets.save();
+        self.editing_preset = None;
+    }
+
+    /// 添加一个自定义预设并保存
+    pub fn add_preset(&mut self, preset: SchedulePreset) -> Result<(), String> {
+        self.presets.add(preset)?;
+        self.presets.save();
+        Ok(())
+    }
+
+    /// 立即检查 PID
+    fn check_selected_pid(&mut self, pid: u32) {
+        self.pid_check = Some((pid, check_pid(pid, &self.privilege)));
+        self.pid_limits = read_process_limits(pid);
+        self.pid_check_pending = None;
+    }
+
+    /// 请求的优先级超出目标进程 rlimit 时的提示
+    fn limit_warning(&self, current_nice: Option<i32>) -> Option<String> {
+        if self.privilege.cap_sys_nice {
+            return None;
+        }
+        let limits = self.pid_limits?;
+        if self.editing_policy.is_realtime() {
+            limits.rtprio_warning(self.editing_priority)
+        } else {
+            limits.nice_warning(self.editing_priority, current_nice.unwrap_or(0))
         }
     }
 
+    /// 绘制 PID 检查状态行
+    fn draw_pid_status(&self, ui: &mut Ui) {
+        let (text, color) = match &self.pid_check {
+            _ if self.pid_check_pending.is_some() => {
+                ("检查中...".to_string(), Color32::from_gray(140))
+            }
+            Some((_, PidCheck::Modifiable { name, owner })) => (
+                format!("✔ {} · 所有者 {} · 可修改", name, owner),
+                Color32::from_rgb(100, 200, 100),
+            ),
+            Some((_, PidCheck::NeedsPrivilege { name, owner })) => (
+                format!("⚠ {} · 所有者 {} · 需要 root 或 CAP_SYS_NICE", name, owner),
+                Color32::from_rgb(255, 200, 100),
+            ),
+            Some((pid, PidCheck::NotFound)) => {
+                (format!("✖ 进程 {} 不存在", pid), Color32::from_rgb(255, 100, 100))
+            }
+            None if !self.pid_input.is_empty() => {
+                ("✖ PID 无效".to_string(), Color32::from_rgb(255, 100, 100))
+            }
+            None => return,
+        };
+        ui.label(RichText::new(text).size(11.0).color(color));
+    }
+
     /// 绘制面板
-    pub fn ui(&mut self, ui: &mut Ui, process_manager: &ProcessManager, logical_cores: usize) {
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+        policy_defaults: &HashMap<String, i32>,
+    ) {
         ui.add_space(8.0);
 
         // 消息显示
         self.draw_messages(ui);
+        self.draw_rt_inheritance_warning(ui, process_manager, session, logical_cores);
+        self.draw_checked_apply(ui, session, logical_cores);
 
         // 主布局：左右分栏
         ui.horizontal(|ui| {
             // 左侧：调度配置
             ui.vertical(|ui| {
                 ui.set_min_width(380.0);
-                self.draw_scheduler_config(ui, process_manager);
+                self.draw_scheduler_config(ui, process_manager, session, logical_cores, policy_defaults);
+                ui.add_space(16.0);
+                self.draw_presets(ui, process_manager, session, logical_cores);
                 ui.add_space(16.0);
-                self.draw_presets(ui, logical_cores);
+                self.draw_deferred_actions(ui);
             });
 
             ui.add_space(16.0);
@@ -61,6 +326,7 @@ impl SchedulerPanel {
             ui.vertical(|ui| {
                 ui.set_min_width(280.0);
                 self.draw_process_selector(ui, process_manager);
+                self.draw_rt_inheritance_scan(ui, process_manager);
             });
         });
     }
@@ -116,8 +382,193 @@ impl SchedulerPanel {
         }
     }
 
+    /// 实时策略会被子进程继承时的确认提示
+    fn draw_rt_inheritance_warning(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        let Some(pending) = &mut self.pending_rt_apply else {
+            return;
+        };
+        let mut apply = false;
+        let mut cancel = false;
+        Frame::none()
+            .fill(Color32::from_rgb(90, 35, 35))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("⚠").size(14.0).color(Color32::from_rgb(255, 120, 100)));
+                    ui.label(RichText::new("实时策略会被子进程继承").strong().color(Color32::from_rgb(255, 170, 150)));
+                });
+                ui.label(RichText::new(format!(
+                    "{}。这些程序一旦失控（如死循环），可能锁死整个桌面",
+                    pending.warning
+                )).size(12.0).color(Color32::from_gray(210)));
+                ui.add_space(4.0);
+                ui.checkbox(&mut pending.reset_on_fork, "子进程不继承 (SCHED_RESET_ON_FORK)")
+                    .on_hover_text(format!(
+                        "只有进程 {} 本身使用实时策略，它启动的程序恢复为 SCHED_OTHER 和非负 nice 值",
+                        pending.pid
+                    ));
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    apply = ui.button("应用").clicked();
+                    cancel = ui.button("取消").clicked();
+                });
+            });
+        ui.add_space(8.0);
+
+        if apply || cancel {
+            if let Some(mut pending) = self.pending_rt_apply.take() {
+                if apply {
+                    set_reset_on_fork(&mut pending.changes, pending.reset_on_fork);
+                    self.apply_checked(pending.pid, pending.changes, pending.preset, process_manager, session, logical_cores);
+                }
+            }
+        }
+    }
+
+    /// 对目标进程设置实时策略的继承风险说明
+    fn rt_inheritance_warning(&self, pid: i32, process_manager: &ProcessManager) -> Option<String> {
+        let process = process_manager.process(pid as u32)?;
+        let children = child_count(process_manager.processes(), process.pid);
+        inheritance_risk(&process.name, children).map(|risk| risk.describe(&process.name))
+    }
+
+    /// 列出父进程不是实时策略的实时进程（疑似意外继承）
+    fn draw_rt_inheritance_scan(&mut self, ui: &mut Ui, process_manager: &ProcessManager) {
+        let processes: Vec<_> = process_manager.processes().collect();
+        let flagged = suspected_rt_inheritance(&processes);
+        if flagged.is_empty() {
+            return;
+        }
+        ui.add_space(16.0);
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new("疑似继承的实时进程").size(16.0).strong());
+                ui.label(RichText::new("这些进程使用实时策略，但父进程不是，通常是启动器曾被设为实时策略")
+                    .size(11.0).color(Color32::from_gray(140)));
+                ui.add_space(8.0);
+                for entry in &flagged {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("{} ({})", entry.name, entry.pid)).color(Color32::from_rgb(255, 180, 100)));
+                        ui.label(RichText::new(format!("← {} ({})", entry.parent_name, entry.parent_pid))
+                            .size(11.0).color(Color32::from_gray(140)));
+                        if ui.small_button("选择").clicked() {
+                            if let Some(process) = process_manager.process(entry.pid) {
+                                self.select_process(process);
+                            }
+                        }
+                    });
+                }
+            });
+    }
+
+    /// 实时进程限流说明和调整（选择实时策略时显示）
+    fn draw_rt_throttling(&mut self, ui: &mut Ui) {
+        let Some(current) = self.rt_throttling else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("RT 限流").color(Color32::from_gray(160)));
+            ui.add_space(8.0);
+            let color = if current.is_disabled() { Color32::from_rgb(255, 120, 100) } else { Color32::from_gray(200) };
+            ui.label(RichText::new(current.describe()).size(12.0).color(color));
+        });
+        ui.label(RichText::new("内核限制实时进程在每个周期内的运行时间，剩余时间留给普通进程，防止失控的实时进程锁死系统。\
+            实时进程周期性地停顿时，通常是触发了限流")
+            .size(11.0).color(Color32::from_gray(140)));
+
+        if self.privilege.euid != 0 {
+            ui.label(RichText::new("修改限流参数需要 root 权限").size(11.0).color(Color32::from_gray(140)));
+            return;
+        }
+
+        let Some(draft) = self.rt_throttling_draft.as_mut() else {
+            if ui.small_button("调整限流...").clicked() {
+                self.rt_throttling_draft = Some(current);
+            }
+            return;
+        };
+
+        ui.add_space(4.0);
+        let mut apply = None;
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("运行时间 (µs)").color(Color32::from_gray(160)));
+            ui.add(egui::DragValue::new(&mut draft.runtime_us).range(0..=draft.period_us).speed(1000.0));
+            ui.label(RichText::new("周期 (µs)").color(Color32::from_gray(160)));
+            ui.add(egui::DragValue::new(&mut draft.period_us).range(1..=i32::MAX as i64).speed(1000.0));
+        });
+        ui.horizontal(|ui| {
+            if ui.button("应用").clicked() {
+                apply = Some(*draft);
+            }
+            if ui.button("恢复默认 (950000 / 1000000)").clicked() {
+                apply = Some(RtThrottling { runtime_us: 950_000, period_us: 1_000_000 });
+            }
+            if ui.button(RichText::new("禁用限流...").color(Color32::from_rgb(255, 120, 100))).clicked() {
+                self.rt_disable_pending = true;
+            }
+            close = ui.button("关闭").clicked();
+        });
+
+        if self.rt_disable_pending {
+            let period_us = draft.period_us;
+            Frame::none()
+                .fill(Color32::from_rgb(80, 40, 40))
+                .inner_margin(Margin::same(10.0))
+                .rounding(Rounding::same(6.0))
+                .show(ui, |ui| {
+                    ui.label(RichText::new("⚠ 禁用实时进程限流").strong().color(Color32::from_rgb(255, 150, 130)));
+                    ui.label(RichText::new("禁用后实时进程不再为普通进程保留 CPU 时间。\
+                        一个陷入死循环的 SCHED_FIFO 进程会让对应核心完全无响应，可能导致整个系统卡死，只能强制重启")
+                        .size(12.0).color(Color32::from_gray(200)));
+                    ui.add_space(4.0);
+                    ui.horizontal(|ui| {
+                        if ui.button(RichText::new("确认禁用").color(Color32::from_rgb(255, 120, 100))).clicked() {
+                            apply = Some(RtThrottling { runtime_us: -1, period_us });
+                            self.rt_disable_pending = false;
+                        }
+                        if ui.button("取消").clicked() {
+                            self.rt_disable_pending = false;
+                        }
+                    });
+                });
+        }
+
+        if let Some(value) = apply {
+            match set_rt_throttling(value) {
+                Ok(()) => {
+                    self.success_message = Some(format!("RT 限流已更新: {}", value.describe()));
+                    close = true;
+                }
+                Err(e) => self.error_message = Some(e),
+            }
+            self.rt_throttling = get_rt_throttling();
+        }
+        if close {
+            self.rt_throttling_draft = None;
+            self.rt_disable_pending = false;
+        }
+    }
+
     /// 绘制调度配置区域
-    fn draw_scheduler_config(&mut self, ui: &mut Ui, process_manager: &ProcessManager) {
+    fn draw_scheduler_config(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+        policy_defaults: &HashMap<String, i32>,
+    ) {
         Frame::none()
             .fill(Color32::from_gray(35))
             .inner_margin(Margin::same(16.0))
@@ -136,8 +587,10 @@ impl SchedulerPanel {
                             .hint_text("输入 PID")
                     );
                     if response.changed() {
-                        if let Ok(pid) = self.pid_input.parse::<u32>() {
+                        if let Ok(pid) = self.pid_input.trim().parse::<u32>() {
                             self.selected_pid = Some(pid);
+                            self.pid_check_pending = Some(Instant::now());
+                            ui.ctx().request_repaint_after(PID_CHECK_DEBOUNCE);
                             if let Some(process) = process_manager
                                 .filtered_processes()
                                 .iter()
@@ -146,24 +599,54 @@ impl SchedulerPanel {
                                 self.editing_policy = process.sched_policy;
                                 self.editing_priority = process.priority;
                             }
+                        } else {
+                            self.selected_pid = None;
+                            self.pid_check = None;
+                            self.pid_limits = None;
+                            self.pid_check_pending = None;
                         }
                     }
-
-                    // 显示选中的进程名
-                    if let Some(pid) = self.selected_pid {
-                        if let Some(process) = process_manager
-                            .filtered_processes()
-                            .iter()
-                            .find(|p| p.pid == pid)
-                        {
-                            ui.add_space(12.0);
-                            ui.label(RichText::new(&process.name).color(Color32::from_rgb(100, 180, 255)));
+                    if let Some(pid) = self.selected_pid.filter(|&pid| process_manager.process(pid).is_some()) {
+                        let label = if process_manager.is_watched(pid) { "移出监视" } else { "加入监视" };
+                        if ui.button(label).on_hover_text("在窗口底部持续显示该进程的状态").clicked() {
+                            self.watch_toggle = Some(pid);
                         }
                     }
                 });
 
+                // 输入停顿后再检查，避免逐个按键探测 "1"、"12"、"123"...
+                if let Some(changed_at) = self.pid_check_pending {
+                    if changed_at.elapsed() >= PID_CHECK_DEBOUNCE {
+                        if let Some(pid) = self.selected_pid {
+                            self.check_selected_pid(pid);
+                        }
+                    } else {
+                        ui.ctx().request_repaint_after(PID_CHECK_DEBOUNCE - changed_at.elapsed());
+                    }
+                }
+                self.draw_pid_status(ui);
+
                 ui.add_space(16.0);
 
+                // 当前策略无法识别时只读显示，避免误降级
+                let unrecognized = self
+                    .selected_pid
+                    .and_then(|pid| process_manager.filtered_processes().into_iter().find(|p| p.pid == pid))
+                    .map(|p| p.sched_policy)
+                    .filter(|policy| policy.is_unrecognized());
+                if let Some(policy) = unrecognized {
+                    Frame::none()
+                        .fill(Color32::from_rgb(70, 60, 30))
+                        .inner_margin(Margin::same(10.0))
+                        .rounding(Rounding::same(6.0))
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(format!("当前策略: {}", policy.display_name())).strong());
+                            ui.label(RichText::new("hexin 无法识别该调度策略 (如 SCHED_DEADLINE)，为避免误降级，此处仅显示不可修改")
+                                .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                        });
+                    return;
+                }
+
                 // 策略选择
                 ui.horizontal(|ui| {
                     ui.label(RichText::new("调度策略").color(Color32::from_gray(160)));
@@ -173,11 +656,12 @@ impl SchedulerPanel {
                         .selected_text(self.editing_policy.display_name())
                         .show_ui(ui, |ui| {
                             for policy in SchedulePolicy::all() {
-                                ui.selectable_value(
-                                    &mut self.editing_policy,
-                                    *policy,
-                                    policy.display_name(),
-                                );
+                                if ui
+                                    .selectable_value(&mut self.editing_policy, *policy, policy.display_name())
+                                    .clicked()
+                                {
+                                    self.editing_priority = policy_default_priority(policy_defaults, *policy);
+                                }
                             }
                         });
                 });
@@ -194,6 +678,8 @@ impl SchedulerPanel {
                     });
                     ui.add_space(4.0);
                     ui.label(RichText::new("⚠ 实时调度可能影响系统稳定性").size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                    ui.add_space(8.0);
+                    self.draw_rt_throttling(ui);
                 } else {
                     ui.horizontal(|ui| {
                         ui.label(RichText::new("Nice 值").color(Color32::from_gray(160)));
@@ -204,6 +690,18 @@ impl SchedulerPanel {
                     ui.label(RichText::new("-20 最高优先级，19 最低优先级").size(11.0).color(Color32::from_gray(140)));
                 }
 
+                // 目标进程的 rlimit 会拒绝的值（hexin 有 CAP_SYS_NICE 时不受限制）
+                let current_nice = self
+                    .selected_pid
+                    .and_then(|pid| process_manager.process(pid))
+                    .filter(|p| !p.sched_policy.is_realtime())
+                    .map(|p| p.priority);
+                if let Some(warning) = self.limit_warning(current_nice) {
+                    ui.add_space(4.0);
+                    ui.label(RichText::new(format!("⚠ {}，修改将被拒绝", warning))
+                        .size(11.0).color(Color32::from_rgb(255, 150, 100)));
+                }
+
                 ui.add_space(16.0);
 
                 // 应用按钮
@@ -211,9 +709,10 @@ impl SchedulerPanel {
                     .fill(Color32::from_rgb(60, 100, 140))
                     .rounding(Rounding::same(6.0));
 
-                if ui.add_sized([160.0, 32.0], button).clicked() {
+                let pid_missing = matches!(self.pid_check, Some((_, PidCheck::NotFound)));
+                if ui.add_enabled_ui(!pid_missing, |ui| ui.add_sized([160.0, 32.0], button)).inner.clicked() {
                     if let Some(pid) = self.selected_pid {
-                        self.apply_scheduler(pid as i32);
+                        self.apply_scheduler(pid as i32, process_manager, session, logical_cores);
                     } else {
                         self.error_message = Some("请输入有效的 PID".to_string());
                     }
@@ -221,8 +720,69 @@ impl SchedulerPanel {
             });
     }
 
+    /// 绘制一次性延后动作（下次启动时应用）区域
+    fn draw_deferred_actions(&mut self, ui: &mut Ui) {
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new("下次启动时应用").size(16.0).strong());
+                ui.add_space(4.0);
+                ui.label(RichText::new("指定名称的进程首次出现时应用一次预设并通知，触发或过期后自动删除，不会创建自动规则")
+                    .size(11.0).color(Color32::from_gray(140)));
+                ui.add_space(12.0);
+
+                let (pattern, preset_name, hours) = &mut self.deferred_draft;
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(pattern).desired_width(140.0).hint_text("进程名，支持 *"));
+                    ComboBox::from_id_salt("deferred_preset")
+                        .width(120.0)
+                        .selected_text(if preset_name.is_empty() { "选择预设" } else { preset_name.as_str() })
+                        .show_ui(ui, |ui| {
+                            for preset in self.presets.presets() {
+                                ui.selectable_value(preset_name, preset.name.clone(), &preset.name);
+                            }
+                        });
+                    ui.add(DragValue::new(hours).range(1..=720).prefix("有效 ").suffix(" 小时"));
+                    let preset = self.presets.presets().iter().find(|p| p.name == *preset_name);
+                    let ready = !pattern.trim().is_empty() && preset.is_some();
+                    if ui.add_enabled(ready, egui::Button::new("添加")).clicked() {
+                        if let Some(preset) = preset {
+                            let action = DeferredAction::new(pattern, preset.clone(), unix_timestamp(), *hours);
+                            self.deferred_edits.push(DeferredEdit::Add(action));
+                            pattern.clear();
+                        }
+                    }
+                });
+
+                if self.deferred_actions.is_empty() {
+                    return;
+                }
+                ui.add_space(8.0);
+                let now = unix_timestamp();
+                for action in &self.deferred_actions {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&action.pattern).monospace());
+                        ui.label(RichText::new(format!("→ {}", action.preset.name)).color(Color32::from_gray(200)));
+                        ui.label(RichText::new(format!("剩余 {}", action.remaining(now)))
+                            .size(11.0).color(Color32::from_gray(140)));
+                        if ui.small_button("删除").clicked() {
+                            self.deferred_edits.push(DeferredEdit::Remove(action.id));
+                        }
+                    });
+                }
+            });
+    }
+
     /// 绘制预设配置区域
-    fn draw_presets(&mut self, ui: &mut Ui, logical_cores: usize) {
+    fn draw_presets(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
         Frame::none()
             .fill(Color32::from_gray(35))
             .inner_margin(Margin::same(16.0))
@@ -231,13 +791,51 @@ impl SchedulerPanel {
                 ui.label(RichText::new("快速预设").size(16.0).strong());
                 ui.add_space(12.0);
 
-                let presets_clone: Vec<SchedulePreset> = self.presets.clone();
-                let mut apply_preset: Option<(i32, SchedulePreset)> = None;
+                let presets_clone: Vec<SchedulePreset> = self.presets.presets().to_vec();
+                let search_lower = self.preset_search.to_lowercase();
+                let matching: Vec<(usize, &SchedulePreset)> = presets_clone
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, preset)| {
+                        preset.name.to_lowercase().contains(&search_lower)
+                            || preset.description.to_lowercase().contains(&search_lower)
+                    })
+                    .collect();
+                let mut action: Option<PresetAction> = None;
+
+                // 搜索框
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("🔍").size(14.0));
+                    ui.add(
+                        TextEdit::singleline(&mut self.preset_search)
+                            .desired_width(200.0)
+                            .hint_text("搜索预设名称或描述...")
+                    );
+                    ui.add_space(12.0);
+                    ui.label(RichText::new(format!("{} / {} 预设", matching.len(), presets_clone.len()))
+                        .color(Color32::from_gray(160)));
+                    if !self.topology.reserved_cores.is_empty() {
+                        ui.add_space(12.0);
+                        let reserved = format_cpu_list(&self.topology.reserved_cores);
+                        if ui
+                            .checkbox(&mut self.include_reserved, "预设包含保留核心")
+                            .on_hover_text(format!("内置预设默认不使用保留核心 {}", reserved))
+                            .changed()
+                        {
+                            self.rebuild_builtin_presets();
+                        }
+                    }
+                });
+                ui.add_space(8.0);
+
+                if matching.is_empty() {
+                    ui.label(RichText::new("没有匹配的预设").color(Color32::from_gray(140)));
+                }
 
                 ScrollArea::vertical()
                     .max_height(200.0)
                     .show(ui, |ui| {
-                        for preset in &presets_clone {
+                        for (index, preset) in matching.iter().copied() {
                             Frame::none()
                                 .fill(Color32::from_gray(45))
                                 .inner_margin(Margin::same(12.0))
@@ -284,14 +882,49 @@ impl SchedulerPanel {
                                             }
                                         }
 
+                                        if let Some(io_priority) = preset.io_priority {
+                                            Frame::none()
+                                                .fill(Color32::from_rgb(60, 50, 80))
+                                                .inner_margin(Margin::symmetric(8.0, 4.0))
+                                                .rounding(Rounding::same(4.0))
+                                                .show(ui, |ui| {
+                                                    ui.label(RichText::new(format!("I/O: {}", io_priority.display())).size(11.0));
+                                                });
+                                        }
+
+                                        if let Some(oom) = preset.oom_score_adj {
+                                            Frame::none()
+                                                .fill(Color32::from_rgb(80, 50, 50))
+                                                .inner_margin(Margin::symmetric(8.0, 4.0))
+                                                .rounding(Rounding::same(4.0))
+                                                .show(ui, |ui| {
+                                                    ui.label(RichText::new(format!("OOM: {:+}", oom)).size(11.0));
+                                                });
+                                        }
+
+                                        if !preset.is_builtin {
+                                            ui.label(RichText::new("自定义").size(11.0).color(Color32::from_rgb(100, 180, 255)));
+                                        }
+
                                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                             if ui.small_button("应用").clicked() {
                                                 if let Some(pid) = self.selected_pid {
-                                                    apply_preset = Some((pid as i32, preset.clone()));
+                                                    action = Some(PresetAction::Apply(pid as i32, preset.clone()));
                                                 } else {
                                                     self.error_message = Some("请先选择进程".to_string());
                                                 }
                                             }
+                                            if ui.small_button("复制").on_hover_text("复制为可编辑的自定义预设").clicked() {
+                                                action = Some(PresetAction::Duplicate(index));
+                                            }
+                                            if !preset.is_builtin {
+                                                if ui.small_button("编辑").clicked() {
+                                                    action = Some(PresetAction::Edit(index));
+                                                }
+                                                if ui.small_button("删除").clicked() {
+                                                    action = Some(PresetAction::Remove(index));
+                                                }
+                                            }
                                         });
                                     });
                                 });
@@ -299,12 +932,190 @@ impl SchedulerPanel {
                         }
                     });
 
-                if let Some((pid, preset)) = apply_preset {
-                    self.apply_preset(pid, &preset, logical_cores);
+                match action {
+                    Some(PresetAction::Apply(pid, preset)) => {
+                        self.apply_preset(pid, &preset, process_manager, session, logical_cores);
+                    }
+                    Some(PresetAction::Duplicate(index)) => {
+                        if let Some(new_index) = self.presets.duplicate(index) {
+                            self.presets.save();
+                            self.start_editing_preset(new_index);
+                        }
+                    }
+                    Some(PresetAction::Edit(index)) => {
+                        self.start_editing_preset(index);
+                    }
+                    Some(PresetAction::Remove(index)) if self.presets.remove(index) => {
+                        self.presets.save();
+                        self.editing_preset = None;
+                    }
+                    Some(PresetAction::Remove(_)) | None => {}
+                }
+
+                if self.editing_preset.is_some() {
+                    ui.add_space(12.0);
+                    self.draw_preset_editor(ui, logical_cores);
                 }
             });
     }
 
+    /// 开始编辑指定预设
+    fn start_editing_preset(&mut self, index: usize) {
+        if let Some(preset) = self.presets.presets().get(index) {
+            self.editing_preset = Some(PresetDraft {
+                index,
+                preset: preset.clone(),
+                affinity_text: preset.affinity_cores.as_deref().map(format_cpu_list).unwrap_or_default(),
+            });
+        }
+    }
+
+    /// 绘制预设编辑器
+    fn draw_preset_editor(&mut self, ui: &mut Ui, logical_cores: usize) {
+        let mut save = false;
+        let mut cancel = false;
+
+        let Some(draft) = self.editing_preset.as_mut() else {
+            return;
+        };
+
+        Frame::none()
+            .fill(Color32::from_gray(45))
+            .inner_margin(Margin::same(12.0))
+            .rounding(Rounding::same(6.0))
+            .stroke(Stroke::new(1.0, Color32::from_rgb(60, 100, 140)))
+            .show(ui, |ui| {
+                ui.label(RichText::new("编辑预设").strong());
+                ui.add_space(8.0);
+
+                egui::Grid::new("preset_editor")
+                    .num_columns(2)
+                    .spacing([12.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label(RichText::new("名称").color(Color32::from_gray(160)));
+                        ui.add(TextEdit::singleline(&mut draft.preset.name).desired_width(200.0));
+                        ui.end_row();
+
+                        ui.label(RichText::new("描述").color(Color32::from_gray(160)));
+                        ui.add(TextEdit::singleline(&mut draft.preset.description).desired_width(200.0));
+                        ui.end_row();
+
+                        ui.label(RichText::new("调度策略").color(Color32::from_gray(160)));
+                        ComboBox::from_id_salt("preset_policy")
+                            .width(180.0)
+                            .selected_text(draft.preset.policy.display_name())
+                            .show_ui(ui, |ui| {
+                                for policy in SchedulePolicy::all() {
+                                    ui.selectable_value(&mut draft.preset.policy, *policy, policy.display_name());
+                                }
+                            });
+                        ui.end_row();
+
+                        if draft.preset.policy.is_realtime() {
+                            let (min, max) = get_rt_priority_range(draft.preset.policy);
+                            ui.label(RichText::new("实时优先级").color(Color32::from_gray(160)));
+                            ui.add(Slider::new(&mut draft.preset.priority, min..=max));
+                        } else {
+                            ui.label(RichText::new("Nice 值").color(Color32::from_gray(160)));
+                            ui.add(Slider::new(&mut draft.preset.priority, -20..=19));
+                        }
+                        ui.end_row();
+
+                        ui.label(RichText::new("I/O 优先级").color(Color32::from_gray(160)));
+                        ui.horizontal(|ui| {
+                            let selected_text = draft.preset.io_priority.map(|p| p.class.display_name()).unwrap_or("不修改");
+                            ComboBox::from_id_salt("preset_io_class")
+                                .width(100.0)
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut draft.preset.io_priority, None, "不修改");
+                                    for class in IoClass::all() {
+                                        let level = draft.preset.io_priority.map(|p| p.level).unwrap_or(4);
+                                        let value = Some(IoPriority { class: *class, level });
+                                        let is_selected = draft.preset.io_priority.map(|p| p.class) == Some(*class);
+                                        if ui.selectable_label(is_selected, class.display_name()).clicked() {
+                                            draft.preset.io_priority = value;
+                                        }
+                                    }
+                                });
+                            if let Some(ref mut io_priority) = draft.preset.io_priority {
+                                if io_priority.class != IoClass::Idle {
+                                    ui.add(Slider::new(&mut io_priority.level, 0..=7).text("级别"));
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(RichText::new("OOM 调整").color(Color32::from_gray(160)));
+                        ui.horizontal(|ui| {
+                            let mut enabled = draft.preset.oom_score_adj.is_some();
+                            if ui.checkbox(&mut enabled, "").changed() {
+                                draft.preset.oom_score_adj = enabled.then_some(0);
+                            }
+                            if let Some(ref mut oom) = draft.preset.oom_score_adj {
+                                ui.add(Slider::new(oom, -1000..=1000));
+                            } else {
+                                ui.label(RichText::new("不修改").color(Color32::from_gray(140)));
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(RichText::new("亲和性").color(Color32::from_gray(160)));
+                        ui.vertical(|ui| {
+                            ui.add(
+                                TextEdit::singleline(&mut draft.affinity_text)
+                                    .desired_width(200.0)
+                                    .hint_text("如 0-7,16-23，留空为全部核心"),
+                            );
+                            let reserved = parse_cpu_list(&draft.affinity_text)
+                                .map(|cores| reserved_among(&cores, &self.topology.reserved_cores))
+                                .unwrap_or_default();
+                            if !reserved.is_empty() {
+                                ui.label(RichText::new(format!("⚠ 包含保留核心 {}", format_cpu_list(&reserved)))
+                                    .size(12.0).color(Color32::from_rgb(255, 200, 100)));
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() {
+                        save = true;
+                    }
+                    if ui.button("取消").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.editing_preset = None;
+        } else if save {
+            let Some(mut draft) = self.editing_preset.take() else {
+                return;
+            };
+            let affinity_text = draft.affinity_text.trim();
+            if affinity_text.is_empty() {
+                draft.preset.affinity_cores = None;
+            } else {
+                match parse_cpu_list(affinity_text) {
+                    Some(cores) if !cores.is_empty() && cores.iter().all(|&c| c < logical_cores) => {
+                        draft.preset.affinity_cores = Some(cores);
+                    }
+                    _ => {
+                        self.error_message = Some(format!("无效的核心列表: {}", affinity_text));
+                        self.editing_preset = Some(draft);
+                        return;
+                    }
+                }
+            }
+            if self.presets.update(draft.index, draft.preset) {
+                self.presets.save();
+            }
+        }
+    }
+
     /// 绘制进程选择器
     fn draw_process_selector(&mut self, ui: &mut Ui, process_manager: &ProcessManager) {
         Frame::none()
@@ -313,16 +1124,58 @@ impl SchedulerPanel {
             .rounding(Rounding::same(8.0))
             .show(ui, |ui| {
                 ui.label(RichText::new("快速选择进程").size(16.0).strong());
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("🔍").size(14.0));
+                    let response = ui.add(
+                        TextEdit::singleline(&mut self.process_search)
+                            .desired_width(200.0)
+                            .hint_text("搜索名称、命令行或 PID...")
+                    );
+                    if std::mem::take(&mut self.focus_search) {
+                        response.request_focus();
+                    }
+                    if !self.process_search.is_empty() && ui.small_button("✕").clicked() {
+                        self.process_search.clear();
+                    }
+                });
                 ui.add_space(4.0);
-                ui.label(RichText::new("按 CPU 使用率排序").size(11.0).color(Color32::from_gray(140)));
-                ui.add_space(12.0);
+
+                // 未搜索时只显示 CPU 使用率最高的进程，搜索时显示所有匹配的进程
+                // 搜索结果只在搜索文本变化或进程刷新后重新计算
+                let generation = process_manager.generation();
+                let stale = match &self.process_matches {
+                    Some((text, cached, _)) => *text != self.process_search || *cached != generation,
+                    None => true,
+                };
+                if stale {
+                    let pids = process_manager.search(&self.process_search).iter().map(|p| p.pid).collect();
+                    self.process_matches = Some((self.process_search.clone(), generation, pids));
+                }
+                let matches = self.process_matches.as_ref().map_or(&[][..], |(_, _, pids)| pids.as_slice());
+                let searching = !self.process_search.trim().is_empty();
+                let limit = if searching { SELECTOR_MAX_MATCHES } else { SELECTOR_TOP_PROCESSES };
+                let processes: Vec<_> =
+                    matches.iter().take(limit).filter_map(|&pid| process_manager.process(pid)).collect();
+                let summary = if !searching {
+                    format!("CPU 使用率最高的 {} 个，搜索可找到所有进程", processes.len())
+                } else if matches.len() > limit {
+                    format!("{} 个匹配，显示前 {} 个，请输入更具体的关键词", matches.len(), limit)
+                } else {
+                    format!("{} 个匹配，按 CPU 使用率排序", matches.len())
+                };
+                ui.label(RichText::new(summary).size(11.0).color(Color32::from_gray(140)));
+                ui.add_space(8.0);
 
                 ScrollArea::vertical()
                     .max_height(400.0)
                     .id_salt("process_select")
                     .show(ui, |ui| {
-                        let processes = process_manager.filtered_processes();
-                        for (idx, process) in processes.iter().take(30).enumerate() {
+                        if processes.is_empty() {
+                            ui.label(RichText::new("没有匹配的进程").color(Color32::from_gray(140)));
+                        }
+                        for (idx, process) in processes.iter().enumerate() {
                             let is_selected = self.selected_pid == Some(process.pid);
 
                             let bg_color = if is_selected {
@@ -358,10 +1211,7 @@ impl SchedulerPanel {
                                     }).response;
 
                                     if response.interact(egui::Sense::click()).clicked() {
-                                        self.selected_pid = Some(process.pid);
-                                        self.pid_input = process.pid.to_string();
-                                        self.editing_policy = process.sched_policy;
-                                        self.editing_priority = process.priority;
+                                        self.select_process(process);
                                     }
                                 });
                         }
@@ -369,64 +1219,86 @@ impl SchedulerPanel {
             });
     }
 
-    /// 应用调度策略
-    fn apply_scheduler(&mut self, pid: i32) {
-        if self.editing_policy.is_realtime() {
-            match set_scheduler(pid, self.editing_policy, self.editing_priority) {
-                Ok(_) => {
-                    self.success_message = Some("调度策略已应用".to_string());
-                    self.error_message = None;
-                }
-                Err(e) => {
-                    self.error_message = Some(e);
-                    self.success_message = None;
-                }
-            }
-        } else {
-            match set_scheduler(pid, self.editing_policy, 0) {
-                Ok(_) => {
-                    if self.editing_priority != 0 {
-                        if let Err(e) = set_process_nice(pid, self.editing_priority) {
-                            self.error_message = Some(e);
-                            return;
-                        }
-                    }
-                    self.success_message = Some("调度策略已应用".to_string());
-                    self.error_message = None;
-                }
-                Err(e) => {
-                    self.error_message = Some(e);
-                    self.success_message = None;
-                }
+    /// 选中进程并载入其当前策略
+    fn select_process(&mut self, process: &ProcessInfo) {
+        self.selected_pid = Some(process.pid);
+        self.pid_input = process.pid.to_string();
+        self.editing_policy = process.sched_policy;
+        self.editing_priority = process.priority;
+        self.check_selected_pid(process.pid);
+    }
+
+    /// 应用调度策略（只修改策略和优先级，不涉及亲和性，因此无需 V-Cache 提示）
+    fn apply_scheduler(
+        &mut self,
+        pid: i32,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        let is_realtime = self.editing_policy.is_realtime();
+        let mut changes = vec![SchedChange::Policy {
+            policy: self.editing_policy,
+            priority: if is_realtime { self.editing_priority } else { 0 },
+            reset_on_fork: false,
+        }];
+        if !is_realtime && self.editing_priority != 0 {
+            changes.push(SchedChange::Nice(self.editing_priority));
+        }
+
+        if is_realtime {
+            if let Some(warning) = self.rt_inheritance_warning(pid, process_manager) {
+                self.pending_rt_apply = Some(PendingRtApply {
+                    pid,
+                    warning,
+                    changes,
+                    preset: None,
+                    reset_on_fork: true,
+                });
+                return;
             }
         }
+        self.apply_checked(pid, changes, None, process_manager, session, logical_cores);
     }
 
-    /// 应用预设
-    fn apply_preset(&mut self, pid: i32, preset: &SchedulePreset, _logical_cores: usize) {
-        let priority = if preset.policy.is_realtime() {
-            preset.priority
-        } else {
-            0
+    /// 经过与进程列表相同的检查（受保护的进程、V-Cache / 保留核心提醒、与自动规则冲突）后应用，
+    /// 需要确认时等待用户选择；进程还不在进程列表中时直接应用
+    fn apply_checked(
+        &mut self,
+        pid: i32,
+        changes: Vec<SchedChange>,
+        preset: Option<SchedulePreset>,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        let Some(process) = process_manager.process(pid as u32) else {
+            self.run_manual_apply(ManualApply { pid: pid as u32, changes, preset }, session, logical_cores);
+            return;
         };
+        match self.checked.request(&self.apply_guard, process, changes, preset) {
+            Ok(Some(apply)) => self.run_manual_apply(apply, session, logical_cores),
+            Ok(None) => {}
+            Err(e) => {
+                self.error_message = Some(e);
+                self.success_message = None;
+            }
+        }
+    }
 
-        match set_scheduler(pid, preset.policy, priority) {
-            Ok(_) => {
-                if !preset.policy.is_realtime() && preset.priority != 0 {
-                    if let Err(e) = set_process_nice(pid, preset.priority) {
-                        self.error_message = Some(format!("设置 nice 值失败: {}", e));
-                        return;
-                    }
-                }
-
-                if let Some(ref cores) = preset.affinity_cores {
-                    if let Err(e) = set_process_affinity(pid, cores) {
-                        self.error_message = Some(format!("设置亲和性失败: {}", e));
-                        return;
-                    }
-                }
+    /// 应用前的提醒和与自动规则冲突时的确认提示
+    fn draw_checked_apply(&mut self, ui: &mut Ui, session: &mut SessionJournal, logical_cores: usize) {
+        if let Some(apply) = self.checked.ui(ui, &self.apply_guard) {
+            self.run_manual_apply(apply, session, logical_cores);
+        }
+        self.rule_resolutions.extend(self.checked.take_resolutions());
+    }
 
-                self.success_message = Some(format!("预设 '{}' 已应用", preset.name));
+    /// 应用通过检查的设置并记录到会话
+    fn run_manual_apply(&mut self, apply: ManualApply, session: &mut SessionJournal, logical_cores: usize) {
+        match apply.run(session, logical_cores) {
+            Ok(message) => {
+                self.success_message = Some(message);
                 self.error_message = None;
             }
             Err(e) => {
@@ -435,4 +1307,28 @@ impl SchedulerPanel {
             }
         }
     }
+
+    /// 应用预设，实时策略会被子进程继承时先提示确认，其余检查见 [`Self::apply_checked`]
+    fn apply_preset(
+        &mut self,
+        pid: i32,
+        preset: &SchedulePreset,
+        process_manager: &ProcessManager,
+        session: &mut SessionJournal,
+        logical_cores: usize,
+    ) {
+        if preset.policy.is_realtime() {
+            if let Some(warning) = self.rt_inheritance_warning(pid, process_manager) {
+                self.pending_rt_apply = Some(PendingRtApply {
+                    pid,
+                    warning,
+                    changes: preset_changes(preset),
+                    preset: Some(preset.clone()),
+                    reset_on_fork: true,
+                });
+                return;
+            }
+        }
+        self.apply_checked(pid, preset_changes(preset), Some(preset.clone()), process_manager, session, logical_cores);
+    }
 }
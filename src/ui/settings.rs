@@ -0,0 +1,1130 @@
+//! 设置面板
+
+use eframe::egui::{self, Color32, ComboBox, DragValue, Frame, Margin, RichText, Rounding, Slider, TextEdit, Ui};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::app::{AppConfig, TableDensity, Tab};
+use crate::ui::theme::CcdPalette;
+use crate::utils::args::TAB_NAMES;
+use crate::system::{
+    format_cpu_list, get_rt_priority_range, hotkey_conflicts, parse_cpu_list, parse_reserved_cores, policy_default_priority,
+    CpuInfo, FocusBooster, HotkeyAction, HotkeyBinding, KeyChord, LocalTime, Profile, ProfileMetadata, QuietHours, RuleOverlap, RuleSchedule, SchedulePolicy, SchedulePreset,
+    ThermalController, ThermalState, UsageSource, EPP_VALUES, HOT_PROCESS_CPU_PERCENT, WEEKDAY_NAMES,
+};
+
+/// 需要由应用处理的档案操作（涉及调度面板和规则引擎）
+pub enum ProfileAction {
+    /// 启用档案，附带兼容性提示
+    Activate { profile: Profile, warnings: Vec<String> },
+    /// 将当前设置导出为档案
+    Export { metadata: ProfileMetadata, path: PathBuf },
+}
+
+/// 设置面板
+pub struct SettingsPanel {
+    /// 档案库（None 表示尚未加载）
+    gallery: Option<Vec<(PathBuf, Profile)>>,
+    /// 导出档案的元数据
+    export_metadata: ProfileMetadata,
+    /// 导出路径
+    export_path: String,
+    /// 导入路径
+    import_path: String,
+    /// 档案操作结果
+    profile_message: Option<Result<String, String>>,
+    /// 待应用处理的档案操作
+    profile_action: Option<ProfileAction>,
+    /// 可供定时规则选择的自动规则名称
+    rule_names: Vec<String>,
+    /// 相互矛盾的自动规则
+    rule_overlaps: Vec<RuleOverlap>,
+    /// 编辑中的保留核心（None 表示显示已保存的值）
+    reserved_draft: Option<String>,
+    /// 保留核心输入错误
+    reserved_error: Option<String>,
+    /// 正在录制按键的快捷键绑定
+    capturing_hotkey: Option<usize>,
+    /// 全局快捷键无法注册的原因
+    global_hotkey_error: Option<String>,
+    /// 后台提示暂停的剩余时间（None 表示未暂停）
+    alert_pause: Option<Duration>,
+    /// 待应用处理的暂停切换（true 表示暂停）
+    alert_pause_request: Option<bool>,
+}
+
+impl SettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            gallery: None,
+            export_metadata: ProfileMetadata::default(),
+            export_path: String::new(),
+            import_path: String::new(),
+            profile_message: None,
+            profile_action: None,
+            rule_names: Vec::new(),
+            rule_overlaps: Vec::new(),
+            reserved_draft: None,
+            reserved_error: None,
+            capturing_hotkey: None,
+            global_hotkey_error: None,
+            alert_pause: None,
+            alert_pause_request: None,
+        }
+    }
+
+    /// 更新全局快捷键的注册错误
+    pub fn set_global_hotkey_error(&mut self, error: Option<String>) {
+        self.global_hotkey_error = error;
+    }
+
+    /// 更新后台提示暂停的剩余时间
+    pub fn set_alert_pause(&mut self, remaining: Option<Duration>) {
+        self.alert_pause = remaining;
+    }
+
+    /// 取出待处理的暂停切换
+    pub fn take_alert_pause_request(&mut self) -> Option<bool> {
+        self.alert_pause_request.take()
+    }
+
+    /// 是否正在录制快捷键（此时不触发快捷键）
+    pub fn is_capturing_hotkey(&self) -> bool {
+        self.capturing_hotkey.is_some()
+    }
+
+    /// 取出待处理的档案操作
+    pub fn take_profile_action(&mut self) -> Option<ProfileAction> {
+        self.profile_action.take()
+    }
+
+    /// 更新可供定时规则选择的自动规则名称
+    pub fn set_rule_names(&mut self, names: Vec<String>) {
+        self.rule_names = names;
+    }
+
+    /// 更新相互矛盾的自动规则
+    pub fn set_rule_overlaps(&mut self, overlaps: Vec<RuleOverlap>) {
+        self.rule_overlaps = overlaps;
+    }
+
+    /// 显示档案操作结果，并重新加载档案库
+    pub fn set_profile_result(&mut self, result: Result<String, String>) {
+        self.profile_message = Some(result);
+        self.gallery = None;
+    }
+
+    /// 绘制面板，配置有修改时返回 true
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        config: &mut AppConfig,
+        thermal: &ThermalController,
+        focus: &FocusBooster,
+        presets: &[SchedulePreset],
+        cpu_info: &CpuInfo,
+    ) -> bool {
+        ui.add_space(8.0);
+        let mut changed = false;
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= draw_usage_source(ui, config);
+                ui.add_space(16.0);
+                changed |= draw_core_comparison(ui, config);
+                ui.add_space(16.0);
+                changed |= draw_cell_display(ui, config);
+                ui.add_space(16.0);
+                changed |= draw_underclock_rule(ui, config);
+                ui.add_space(16.0);
+                changed |= draw_table_density(ui, config);
+                if cpu_info.l3_caches.len() > 1 {
+                    ui.add_space(16.0);
+                    changed |= draw_ccd_colors(ui, config, cpu_info);
+                }
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= self.draw_thermal(ui, config, thermal);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= self.draw_reserved_cores(ui, config, cpu_info);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                self.draw_profiles(ui, config, cpu_info);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= draw_policy_defaults(ui, config);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= draw_focus_boost(ui, config, focus, presets);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                draw_rule_overlaps(ui, &self.rule_overlaps);
+                changed |= draw_rule_schedules(ui, config, &self.rule_names);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= self.draw_quiet_hours(ui, config);
+            });
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                changed |= self.draw_hotkeys(ui, config, presets);
+            });
+
+        if cfg!(feature = "tray") {
+            ui.add_space(16.0);
+
+            Frame::none()
+                .fill(Color32::from_gray(35))
+                .inner_margin(Margin::same(16.0))
+                .rounding(Rounding::same(8.0))
+                .show(ui, |ui| {
+                    ui.set_min_width(480.0);
+                    ui.label(RichText::new("窗口").size(16.0).strong());
+                    ui.add_space(8.0);
+                    if ui.checkbox(&mut config.close_to_tray, "关闭窗口时最小化")
+                        .on_hover_text("窗口最小化后自动规则和监视仍随界面刷新运行（部分桌面会降低刷新频率），在托盘菜单中选择\"退出\"完全退出；托盘图标不可用时关闭窗口直接退出")
+                        .changed()
+                    {
+                        config.close_to_tray_confirmed = true;
+                        changed = true;
+                    }
+                });
+        }
+
+        ui.add_space(16.0);
+
+        Frame::none()
+            .fill(Color32::from_gray(35))
+            .inner_margin(Margin::same(16.0))
+            .rounding(Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(480.0);
+                ui.label(RichText::new("调试").size(16.0).strong());
+                ui.add_space(8.0);
+                changed |= ui.checkbox(&mut config.debug_tools, "显示调试工具")
+                    .on_hover_text("在标签栏中显示\"调试\"页（负载生成器），关闭时停止所有负载线程")
+                    .changed();
+            });
+
+        changed
+    }
+
+    /// 免打扰时间段和临时暂停，时间段有修改时返回 true
+    fn draw_quiet_hours(&mut self, ui: &mut Ui, config: &mut AppConfig) -> bool {
+        ui.label(RichText::new("免打扰").size(16.0).strong());
+        ui.add_space(4.0);
+        ui.label(RichText::new("时间段内或暂停期间不显示自动规则和一次性动作的提示，提示仍写入日志，应用记录仍写入会话")
+            .size(11.0).color(Color32::from_gray(140)));
+        ui.add_space(12.0);
+
+        let mut paused = self.alert_pause.is_some();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut paused, "暂停告警 1 小时").changed() {
+                self.alert_pause_request = Some(paused);
+            }
+            if let Some(remaining) = self.alert_pause {
+                ui.label(RichText::new(format!("剩余 {} 分钟", remaining.as_secs().div_ceil(60)))
+                    .size(11.0).color(Color32::from_gray(140)));
+            }
+        });
+        ui.add_space(8.0);
+
+        let now = LocalTime::now();
+        let mut changed = false;
+        let mut remove = None;
+        for (index, range) in config.quiet_hours.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(TextEdit::singleline(&mut range.start).desired_width(48.0).hint_text("23:00")).changed();
+                    ui.label("至");
+                    changed |= ui.add(TextEdit::singleline(&mut range.end).desired_width(48.0).hint_text("07:00")).changed();
+                    let (status, color) = match range.validate() {
+                        Err(e) => (e, Color32::from_rgb(255, 120, 100)),
+                        Ok(()) if range.contains(now) => ("生效中".to_string(), Color32::from_rgb(100, 200, 100)),
+                        Ok(()) => ("未到时间".to_string(), Color32::from_gray(140)),
+                    };
+                    ui.label(RichText::new(status).size(11.0).color(color));
+                    if ui.small_button("删除").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            });
+        }
+        if let Some(index) = remove {
+            config.quiet_hours.remove(index);
+            changed = true;
+        }
+        ui.horizontal(|ui| {
+            if ui.button("添加时间段").clicked() {
+                config.quiet_hours.push(QuietHours::default());
+                changed = true;
+            }
+            ui.label(RichText::new("结束早于开始表示跨过午夜").size(11.0).color(Color32::from_gray(120)));
+        });
+        changed
+    }
+
+    /// 快捷键绑定：点击按键按钮后按下组合键录制，Esc 取消；绑定有修改时返回 true
+    fn draw_hotkeys(&mut self, ui: &mut Ui, config: &mut AppConfig, presets: &[SchedulePreset]) -> bool {
+        ui.label(RichText::new("快捷键").size(16.0).strong());
+        ui.add_space(4.0);
+        ui.label(RichText::new("应用预设作用于进程管理或调度策略中选中的进程，全局快捷键作用于焦点窗口所属进程；输入框获得焦点时只响应带 Ctrl / Alt 的组合")
+            .size(11.0).color(Color32::from_gray(140)));
+        ui.add_space(12.0);
+
+        let mut changed = false;
+        if let Some(index) = self.capturing_hotkey {
+            if let Some(keys) = capture_chord(ui) {
+                if let Some(binding) = config.hotkeys.get_mut(index) {
+                    binding.keys = keys;
+                    changed = true;
+                }
+                self.capturing_hotkey = None;
+            }
+        }
+
+        let conflicts = hotkey_conflicts(&config.hotkeys);
+        let mut remove = None;
+        for (index, binding) in config.hotkeys.iter_mut().enumerate() {
+            ui.push_id(("hotkey", index), |ui| {
+                ui.horizontal(|ui| {
+                    let capturing = self.capturing_hotkey == Some(index);
+                    let label = if capturing {
+                        "按下组合键…".to_string()
+                    } else if binding.keys.is_empty() {
+                        "点击设置".to_string()
+                    } else {
+                        binding.keys.clone()
+                    };
+                    if ui.add_sized([120.0, 20.0], egui::Button::new(RichText::new(label).monospace()).selected(capturing))
+                        .on_hover_text("点击后按下组合键，Esc 取消")
+                        .clicked()
+                    {
+                        self.capturing_hotkey = if capturing { None } else { Some(index) };
+                    }
+                    changed |= draw_hotkey_action(ui, &mut binding.action, presets, &self.rule_names);
+                    changed |= ui.add_enabled(cfg!(feature = "global-hotkey"), egui::Checkbox::new(&mut binding.global, "全局"))
+                        .on_hover_text("hexin 窗口不在前台时也生效（X11），应用预设时作用于焦点窗口所属进程，没有焦点窗口时不应用")
+                        .on_disabled_hover_text("此版本编译时未启用全局快捷键 (global-hotkey feature)")
+                        .changed();
+                    if ui.small_button("删除").clicked() {
+                        remove = Some(index);
+                    }
+                });
+                if let Some(other) = conflicts[index] {
+                    ui.label(RichText::new(format!("⚠ 与第 {} 项使用相同的按键，只有第一项生效", other + 1))
+                        .size(11.0).color(Color32::from_rgb(255, 100, 100)));
+                }
+            });
+        }
+        if let Some(index) = remove {
+            config.hotkeys.remove(index);
+            self.capturing_hotkey = None;
+            changed = true;
+        }
+        if ui.button("添加快捷键").clicked() {
+            config.hotkeys.push(HotkeyBinding::default());
+            self.capturing_hotkey = Some(config.hotkeys.len() - 1);
+            changed = true;
+        }
+        if let Some(error) = &self.global_hotkey_error {
+            ui.add_space(8.0);
+            ui.label(RichText::new(error).size(11.0).color(Color32::from_rgb(255, 150, 100)));
+        }
+        changed
+    }
+
+    /// 保留给系统的核心，应用后返回 true
+    fn draw_reserved_cores(&mut self, ui: &mut Ui, config: &mut AppConfig, cpu_info: &CpuInfo) -> bool {
+        ui.label(RichText::new("保留核心").size(16.0).strong());
+        ui.add_space(4.0);
+        ui.label(RichText::new("预设和自动规则中的\"全部\"、V-Cache、插槽等目标不再包含这些核心，明确的 CPU 列表不受影响")
+            .size(11.0).color(Color32::from_gray(140)));
+        ui.add_space(12.0);
+
+        let draft = self.reserved_draft.get_or_insert_with(|| format_cpu_list(&config.reserved_cores));
+        let mut apply = false;
+        ui.horizontal(|ui| {
+            let response = ui.add(TextEdit::singleline(draft).desired_width(160.0).hint_text("如 0,8，留空为不保留"));
+            if response.changed() {
+                self.reserved_error = None;
+            }
+            if ui.button("加入 SMT 兄弟").on_hover_text("把与已填核心共用物理核心的线程一起保留").clicked() {
+                if let Some(cores) = parse_cpu_list(draft) {
+                    let physical: Vec<(usize, usize)> = cpu_info
+                        .cores
+                        .iter()
+                        .filter(|c| cores.contains(&c.cpu_id))
+                        .map(|c| (c.package_id, c.core_id))
+                        .collect();
+                    let siblings: Vec<usize> = cpu_info
+                        .cores
+                        .iter()
+                        .filter(|c| cores.contains(&c.cpu_id) || physical.contains(&(c.package_id, c.core_id)))
+                        .map(|c| c.cpu_id)
+                        .collect();
+                    *draft = format_cpu_list(&siblings);
+                }
+            }
+            if ui.button("应用").clicked() {
+                apply = true;
+            }
+        });
+        let mut changed = false;
+        if apply {
+            match parse_reserved_cores(draft, cpu_info.logical_cores) {
+                Ok(cores) => {
+                    changed = cores != config.reserved_cores;
+                    config.reserved_cores = cores;
+                    self.reserved_draft = None;
+                }
+                Err(e) => self.reserved_error = Some(e),
+            }
+        }
+        if let Some(error) = &self.reserved_error {
+            ui.label(RichText::new(error).size(12.0).color(Color32::from_rgb(255, 100, 100)));
+        }
+        changed
+    }
+
+    /// 调优档案库
+    fn draw_profiles(&mut self, ui: &mut Ui, config: &AppConfig, cpu_info: &CpuInfo) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("调优档案").size(16.0).strong());
+            ui.add_space(12.0);
+            if let Some(active) = &config.active_profile {
+                ui.label(RichText::new(format!("当前: {}", active)).size(12.0).color(Color32::from_rgb(100, 180, 255)));
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("刷新").clicked() {
+                    self.gallery = None;
+                }
+            });
+        });
+        ui.add_space(4.0);
+        ui.label(RichText::new("档案包含自定义预设和自动规则，启用时替换当前的自定义预设和规则")
+            .size(11.0).color(Color32::from_gray(140)));
+        ui.add_space(8.0);
+
+        let mut clear_message = false;
+        if let Some(result) = &self.profile_message {
+            let (text, color) = match result {
+                Ok(msg) => (msg.as_str(), Color32::from_rgb(150, 255, 150)),
+                Err(msg) => (msg.as_str(), Color32::from_rgb(255, 150, 150)),
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(text).size(12.0).color(color));
+                if ui.small_button("✕").clicked() {
+                    clear_message = true;
+                }
+            });
+            ui.add_space(8.0);
+        }
+        if clear_message {
+            self.profile_message = None;
+        }
+
+        // 档案库列表
+        let gallery = self.gallery.get_or_insert_with(Profile::gallery);
+        let mut activate = None;
+        if gallery.is_empty() {
+            ui.label(RichText::new("档案库为空，可导出当前设置或导入他人分享的档案").color(Color32::from_gray(140)));
+        }
+        for (path, profile) in gallery.iter() {
+            let metadata = &profile.metadata;
+            Frame::none()
+                .fill(Color32::from_gray(45))
+                .inner_margin(Margin::same(10.0))
+                .rounding(Rounding::same(6.0))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&metadata.name).strong().color(Color32::WHITE));
+                        if !metadata.author.is_empty() {
+                            ui.label(RichText::new(format!("by {}", metadata.author)).size(11.0).color(Color32::from_gray(160)));
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("启用").on_hover_text(path.display().to_string()).clicked() {
+                                activate = Some(profile.clone());
+                            }
+                        });
+                    });
+                    if !metadata.target_cpu.is_empty() {
+                        ui.label(RichText::new(&metadata.target_cpu).size(11.0).color(Color32::from_gray(160)));
+                    }
+                    if !metadata.description.is_empty() {
+                        ui.label(RichText::new(&metadata.description).size(12.0).color(Color32::from_gray(200)));
+                    }
+                    ui.label(RichText::new(format!("{} 个预设 · {} 条规则", profile.presets.len(), profile.rules.len()))
+                        .size(11.0).color(Color32::from_gray(140)));
+                });
+            ui.add_space(6.0);
+        }
+        if let Some(profile) = activate {
+            let check = profile.check(&cpu_info.model_name, cpu_info.logical_cores);
+            if check.is_ok() {
+                self.profile_action = Some(ProfileAction::Activate { profile, warnings: check.warnings });
+            } else {
+                self.profile_message = Some(Err(check.errors.join("；")));
+            }
+        }
+
+        ui.add_space(8.0);
+        ui.collapsing("导出当前设置", |ui| {
+            if self.export_metadata.target_cpu.is_empty() {
+                self.export_metadata.target_cpu = cpu_info.model_name.trim().to_string();
+            }
+            egui::Grid::new("profile_export")
+                .num_columns(2)
+                .spacing([16.0, 6.0])
+                .show(ui, |ui| {
+                    let metadata = &mut self.export_metadata;
+                    ui.label("名称");
+                    ui.add(TextEdit::singleline(&mut metadata.name).hint_text("如 7950X3D 游戏档案"));
+                    ui.end_row();
+                    ui.label("作者");
+                    ui.text_edit_singleline(&mut metadata.author);
+                    ui.end_row();
+                    ui.label("目标 CPU");
+                    ui.text_edit_singleline(&mut metadata.target_cpu);
+                    ui.end_row();
+                    ui.label("说明");
+                    ui.text_edit_multiline(&mut metadata.description);
+                    ui.end_row();
+                    ui.label("导出到");
+                    ui.add(TextEdit::singleline(&mut self.export_path).hint_text("留空则只保存到档案库"));
+                    ui.end_row();
+                });
+            let can_export = !self.export_metadata.name.trim().is_empty();
+            if ui.add_enabled(can_export, egui::Button::new("导出")).clicked() {
+                self.profile_action = Some(ProfileAction::Export {
+                    metadata: self.export_metadata.clone(),
+                    path: PathBuf::from(self.export_path.trim()),
+                });
+            }
+        });
+
+        ui.collapsing("导入档案", |ui| {
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.import_path).desired_width(300.0).hint_text("档案文件路径 (.toml)"));
+                if ui.button("导入").clicked() {
+                    self.import_profile(cpu_info);
+                }
+            });
+        });
+    }
+
+    /// 导入档案到档案库（核心编号超出范围时拒绝）
+    fn import_profile(&mut self, cpu_info: &CpuInfo) {
+        let result = Profile::import(&PathBuf::from(self.import_path.trim())).and_then(|profile| {
+            let check = profile.check(&cpu_info.model_name, cpu_info.logical_cores);
+            if !check.is_ok() {
+                return Err(check.errors.join("；"));
+            }
+            profile.save_to_gallery()?;
+            let mut msg = format!("已导入档案 '{}'", profile.metadata.name);
+            if !check.warnings.is_empty() {
+                msg.push_str(&format!("（注意: {}）", check.warnings.join("；")));
+            }
+            Ok(msg)
+        });
+        if result.is_ok() {
+            self.import_path.clear();
+        }
+        self.set_profile_result(result);
+    }
+
+    /// 高温降温自动化设置，有修改时返回 true
+    fn draw_thermal(&mut self, ui: &mut Ui, config: &mut AppConfig, thermal: &ThermalController) -> bool {
+        let thermal_config = &mut config.thermal;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("高温降温").size(16.0).strong());
+            ui.add_space(12.0);
+            let (status, color) = match thermal.state() {
+                _ if !thermal_config.enabled => ("未启用", Color32::from_gray(140)),
+                ThermalState::Normal => ("正常", Color32::from_rgb(100, 200, 100)),
+                ThermalState::Heating { .. } => ("高温计时中", Color32::from_rgb(255, 200, 100)),
+                ThermalState::Throttled => ("降温动作生效中", Color32::from_rgb(255, 120, 100)),
+                ThermalState::Cooling { .. } => ("回落计时中", Color32::from_rgb(255, 200, 100)),
+            };
+            ui.label(RichText::new(status).size(12.0).color(color));
+            // 未启用时不读取温度
+            if thermal_config.enabled {
+                let temperature = thermal
+                    .last_temperature()
+                    .map_or_else(|| "封装温度不可用".to_string(), |t| format!("封装温度 {:.0}°C", t));
+                ui.label(RichText::new(temperature).size(12.0).color(Color32::from_gray(160)));
+            }
+        });
+        ui.add_space(4.0);
+        ui.label(RichText::new("封装温度持续高于上限时应用降温动作，持续低于下限后自动撤销")
+            .size(11.0).color(Color32::from_gray(140)));
+        ui.add_space(12.0);
+
+        let mut changed = ui.checkbox(&mut thermal_config.enabled, "启用").changed();
+        ui.add_space(8.0);
+
+        egui::Grid::new("thermal_settings")
+            .num_columns(2)
+            .spacing([16.0, 8.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("上限").color(Color32::from_gray(160)));
+                ui.horizontal(|ui| {
+                    changed |= ui.add(Slider::new(&mut thermal_config.high_celsius, 50.0..=105.0).suffix("°C").integer()).changed();
+                    ui.label("持续");
+                    changed |= ui.add(DragValue::new(&mut thermal_config.high_secs).range(0..=600).suffix(" 秒")).changed();
+                });
+                ui.end_row();
+
+                ui.label(RichText::new("下限").color(Color32::from_gray(160)));
+                ui.horizontal(|ui| {
+                    let max_low = thermal_config.high_celsius - 1.0;
+                    changed |= ui.add(Slider::new(&mut thermal_config.low_celsius, 40.0..=max_low).suffix("°C").integer()).changed();
+                    ui.label("持续");
+                    changed |= ui.add(DragValue::new(&mut thermal_config.low_secs).range(0..=3600).suffix(" 秒")).changed();
+                });
+                ui.end_row();
+
+                let action = &mut thermal_config.action;
+                ui.label(RichText::new("目标进程").color(Color32::from_gray(160)));
+                changed |= ui.add(TextEdit::singleline(&mut action.process_pattern)
+                    .desired_width(200.0)
+                    .hint_text("进程名，支持 * 通配符"))
+                    .changed();
+                ui.end_row();
+
+                ui.label(RichText::new("改为 nice").color(Color32::from_gray(160)));
+                ui.horizontal(|ui| {
+                    let mut set_nice = action.nice.is_some();
+                    if ui.checkbox(&mut set_nice, "").changed() {
+                        action.nice = set_nice.then_some(0);
+                        changed = true;
+                    }
+                    if let Some(nice) = action.nice.as_mut() {
+                        changed |= ui.add(Slider::new(nice, -20..=19)).changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(RichText::new("改为 EPP").color(Color32::from_gray(160)));
+                ComboBox::from_id_salt("thermal_epp")
+                    .selected_text(action.epp.as_deref().unwrap_or("不修改"))
+                    .show_ui(ui, |ui| {
+                        changed |= ui.selectable_value(&mut action.epp, None, "不修改").changed();
+                        for value in EPP_VALUES {
+                            changed |= ui.selectable_value(&mut action.epp, Some(value.to_string()), *value).changed();
+                        }
+                    });
+                ui.end_row();
+            });
+        thermal_config.low_celsius = thermal_config.low_celsius.min(thermal_config.high_celsius - 1.0);
+
+        if !thermal.log().is_empty() {
+            ui.add_space(12.0);
+            ui.label(RichText::new("最近动作").color(Color32::from_gray(160)));
+            for entry in thermal.log().iter().rev() {
+                ui.label(RichText::new(entry).size(11.0).monospace().color(Color32::from_gray(200)));
+            }
+        }
+        changed
+    }
+}
+
+impl Default for SettingsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 各调度策略的默认优先级（在调度面板切换策略时预填），有修改时返回 true
+fn draw_policy_defaults(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("调度策略默认值").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("在调度面板中切换策略时，优先级滑块自动设为此值")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+
+    let mut changed = false;
+    egui::Grid::new("policy_defaults")
+        .num_columns(2)
+        .spacing([16.0, 8.0])
+        .show(ui, |ui| {
+            for &policy in SchedulePolicy::all() {
+                let (min, max) = if policy.is_realtime() { get_rt_priority_range(policy) } else { (-20, 19) };
+                let mut priority = policy_default_priority(&config.policy_defaults, policy);
+                ui.label(RichText::new(policy.display_name()).color(Color32::from_gray(160)));
+                if ui.add(Slider::new(&mut priority, min..=max)).changed() {
+                    config.policy_defaults.insert(policy.short_name(), priority);
+                    changed = true;
+                }
+                ui.end_row();
+            }
+        });
+    changed
+}
+
+/// 快捷键操作的种类
+const HOTKEY_ACTION_KINDS: [&str; 5] = ["应用预设", "应用规则", "切换标签页", "暂停 / 继续刷新", "跳转到搜索"];
+
+/// 读取本帧按下的组合键，Esc 取消录制（返回空文本）
+fn capture_chord(ui: &Ui) -> Option<String> {
+    ui.input(|i| {
+        i.events.iter().find_map(|event| match event {
+            egui::Event::Key { key: egui::Key::Escape, pressed: true, modifiers, .. } if modifiers.is_none() => {
+                Some(String::new())
+            }
+            egui::Event::Key { key, pressed: true, modifiers, .. } => Some(
+                KeyChord { ctrl: modifiers.ctrl, alt: modifiers.alt, shift: modifiers.shift, key: key.name().to_string() }
+                    .to_string(),
+            ),
+            _ => None,
+        })
+    })
+}
+
+/// 快捷键操作：种类和参数（预设 / 规则 / 标签页）
+fn draw_hotkey_action(ui: &mut Ui, action: &mut HotkeyAction, presets: &[SchedulePreset], rule_names: &[String]) -> bool {
+    let kind = match action {
+        HotkeyAction::ApplyPreset(_) => 0,
+        HotkeyAction::ApplyRule(_) => 1,
+        HotkeyAction::SwitchTab(_) => 2,
+        HotkeyAction::TogglePause => 3,
+        HotkeyAction::FocusSearch => 4,
+    };
+    let mut selected = kind;
+    ComboBox::from_id_salt("hotkey_kind")
+        .selected_text(HOTKEY_ACTION_KINDS[kind])
+        .show_ui(ui, |ui| {
+            for (index, name) in HOTKEY_ACTION_KINDS.iter().enumerate() {
+                ui.selectable_value(&mut selected, index, *name);
+            }
+        });
+    if selected != kind {
+        *action = match selected {
+            0 => HotkeyAction::ApplyPreset(presets.first().map(|p| p.name.clone()).unwrap_or_default()),
+            1 => HotkeyAction::ApplyRule(rule_names.first().cloned().unwrap_or_default()),
+            2 => HotkeyAction::SwitchTab(TAB_NAMES[0].to_string()),
+            3 => HotkeyAction::TogglePause,
+            _ => HotkeyAction::FocusSearch,
+        };
+    }
+
+    let changed = match action {
+        HotkeyAction::ApplyPreset(name) => {
+            let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+            draw_name_choice(ui, "hotkey_preset", name, &names, |n| n.to_string())
+        }
+        HotkeyAction::ApplyRule(name) => {
+            let names: Vec<&str> = rule_names.iter().map(String::as_str).collect();
+            draw_name_choice(ui, "hotkey_rule", name, &names, |n| n.to_string())
+        }
+        HotkeyAction::SwitchTab(name) => {
+            let label = |n: &str| Tab::from_name(n).map_or(n.to_string(), |tab| tab.label().to_string());
+            draw_name_choice(ui, "hotkey_tab", name, &TAB_NAMES, label)
+        }
+        HotkeyAction::TogglePause | HotkeyAction::FocusSearch => false,
+    };
+    changed || selected != kind
+}
+
+/// 从名称列表中选择，当前值不在列表中时标红；选择有变化时返回 true
+fn draw_name_choice(ui: &mut Ui, id: &str, value: &mut String, names: &[&str], label: impl Fn(&str) -> String) -> bool {
+    let known = names.contains(&value.as_str());
+    let text = if value.is_empty() { "未选择".to_string() } else { label(value) };
+    let color = if known { Color32::from_gray(220) } else { Color32::from_rgb(255, 150, 100) };
+    let mut changed = false;
+    ComboBox::from_id_salt(id)
+        .selected_text(RichText::new(text).color(color))
+        .show_ui(ui, |ui| {
+            for name in names {
+                changed |= ui.selectable_value(value, name.to_string(), label(name)).changed();
+            }
+        });
+    changed
+}
+
+/// 前台加速：焦点窗口所属进程临时应用预设，有修改时返回 true
+fn draw_focus_boost(ui: &mut Ui, config: &mut AppConfig, focus: &FocusBooster, presets: &[SchedulePreset]) -> bool {
+    let focus_config = &mut config.focus_boost;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("前台加速").size(16.0).strong());
+        ui.add_space(12.0);
+        if let Some(pid) = focus.boosted_pid().filter(|_| focus_config.enabled) {
+            ui.label(RichText::new(format!("正在加速 PID {}", pid)).size(12.0).color(Color32::from_rgb(100, 200, 100)));
+        }
+    });
+    ui.add_space(4.0);
+    ui.label(RichText::new("焦点窗口所属进程临时应用所选预设，切换焦点或退出 hexin 时恢复原设置")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+
+    let mut changed = ui.checkbox(&mut focus_config.enabled, "启用").changed();
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("预设").color(Color32::from_gray(160)));
+        ComboBox::from_id_salt("focus_boost_preset")
+            .selected_text(focus_config.preset.as_str())
+            .show_ui(ui, |ui| {
+                for preset in presets {
+                    changed |= ui.selectable_value(&mut focus_config.preset, preset.name.clone(), &preset.name).changed();
+                }
+            });
+    });
+
+    if let Some(message) = focus.message().filter(|_| focus_config.enabled) {
+        ui.add_space(8.0);
+        ui.label(RichText::new(message).size(11.0).color(Color32::from_rgb(255, 150, 100)));
+    }
+    changed
+}
+
+/// 可能匹配同一进程但设置相互矛盾的自动规则
+fn draw_rule_overlaps(ui: &mut Ui, overlaps: &[RuleOverlap]) {
+    if overlaps.is_empty() {
+        return;
+    }
+    Frame::none()
+        .fill(Color32::from_rgb(70, 60, 30))
+        .inner_margin(Margin::same(10.0))
+        .rounding(Rounding::same(6.0))
+        .show(ui, |ui| {
+            ui.label(RichText::new(format!("⚠ {} 对自动规则相互矛盾", overlaps.len()))
+                .strong().color(Color32::from_rgb(255, 220, 150)))
+                .on_hover_text("规则按定义顺序匹配，第一条匹配的规则生效，profiles.d 中的规则排在最后");
+            for overlap in overlaps {
+                ui.label(RichText::new(overlap.describe()).size(12.0).color(Color32::from_gray(200)));
+            }
+        });
+    ui.add_space(12.0);
+}
+
+/// 定时规则：在指定的星期和时间段内启用自动规则，有修改时返回 true
+fn draw_rule_schedules(ui: &mut Ui, config: &mut AppConfig, rule_names: &[String]) -> bool {
+    ui.label(RichText::new("定时规则").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("时间段内启用所选自动规则，时间段外停用；生效时也会应用到已在运行的匹配进程")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+
+    let now = LocalTime::now();
+    let mut changed = false;
+    let mut remove = None;
+    for (index, schedule) in config.rule_schedules.iter_mut().enumerate() {
+        Frame::none()
+            .fill(Color32::from_gray(40))
+            .inner_margin(Margin::same(10.0))
+            .rounding(Rounding::same(6.0))
+            .show(ui, |ui| {
+                changed |= ui
+                    .push_id(index, |ui| draw_rule_schedule(ui, schedule, rule_names, now, || remove = Some(index)))
+                    .inner;
+            });
+        ui.add_space(8.0);
+    }
+    if let Some(index) = remove {
+        config.rule_schedules.remove(index);
+        changed = true;
+    }
+    if ui.button("添加定时").clicked() {
+        config.rule_schedules.push(RuleSchedule::default());
+        changed = true;
+    }
+    changed
+}
+
+/// 单条定时规则的编辑行，点击删除时调用 `on_remove`；有修改时返回 true
+fn draw_rule_schedule(
+    ui: &mut Ui,
+    schedule: &mut RuleSchedule,
+    rule_names: &[String],
+    now: LocalTime,
+    mut on_remove: impl FnMut(),
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut schedule.enabled, "").changed();
+        changed |= ui.add(TextEdit::singleline(&mut schedule.name).desired_width(140.0)).changed();
+        let (status, color) = match schedule.validate() {
+            Err(e) => (e, Color32::from_rgb(255, 120, 100)),
+            Ok(()) if !schedule.enabled => ("已停用".to_string(), Color32::from_gray(120)),
+            Ok(()) if schedule.is_active(now) => ("生效中".to_string(), Color32::from_rgb(100, 200, 100)),
+            Ok(()) => ("未到时间".to_string(), Color32::from_gray(140)),
+        };
+        ui.label(RichText::new(status).size(11.0).color(color));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.small_button("删除").clicked() {
+                on_remove();
+            }
+        });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("星期").color(Color32::from_gray(160)));
+        for (day, name) in (1..=7u8).zip(WEEKDAY_NAMES) {
+            let selected = schedule.days.contains(&day);
+            if ui.selectable_label(selected, name).clicked() {
+                changed = true;
+                if selected {
+                    schedule.days.retain(|d| *d != day);
+                } else {
+                    schedule.days.push(day);
+                    schedule.days.sort_unstable();
+                }
+            }
+        }
+        if schedule.days.is_empty() {
+            ui.label(RichText::new("每天").size(11.0).color(Color32::from_gray(140)));
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("时间").color(Color32::from_gray(160)));
+        changed |= ui.add(TextEdit::singleline(&mut schedule.start).desired_width(48.0).hint_text("09:00")).changed();
+        ui.label("至");
+        changed |= ui.add(TextEdit::singleline(&mut schedule.end).desired_width(48.0).hint_text("18:00")).changed();
+        ui.label(RichText::new("结束早于开始表示跨过午夜").size(11.0).color(Color32::from_gray(120)));
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(RichText::new("规则").color(Color32::from_gray(160)));
+        let mut remove_rule = None;
+        for (i, rule) in schedule.rules.iter().enumerate() {
+            let known = rule_names.contains(rule);
+            let color = if known { Color32::from_gray(220) } else { Color32::from_rgb(255, 150, 100) };
+            let mut response = ui.button(RichText::new(format!("{} ✖", rule)).size(11.0).color(color));
+            if !known {
+                response = response.on_hover_text("找不到此规则");
+            }
+            if response.clicked() {
+                remove_rule = Some(i);
+            }
+        }
+        if let Some(i) = remove_rule {
+            schedule.rules.remove(i);
+            changed = true;
+        }
+        let mut add_rule = None;
+        ComboBox::from_id_salt("schedule_add_rule")
+            .selected_text("添加规则")
+            .show_ui(ui, |ui| {
+                for name in rule_names.iter().filter(|name| !schedule.rules.contains(name)) {
+                    if ui.selectable_label(false, name).clicked() {
+                        add_rule = Some(name.clone());
+                    }
+                }
+            });
+        changed |= add_rule.is_some();
+        schedule.rules.extend(add_rule);
+    });
+    changed
+}
+
+/// 核心对比图的上限，有修改时返回 true
+fn draw_core_comparison(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("核心对比").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("在 CPU 监控中 Shift 点击核心加入对比图，超过上限时替换最早加入的核心")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("最多对比").color(Color32::from_gray(160)));
+        ui.add(Slider::new(&mut config.max_compared_cores, 2..=16).suffix(" 个核心")).changed()
+    })
+    .inner
+}
+
+/// 核心网格单元格显示的数值，有修改时返回 true
+fn draw_cell_display(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("核心网格").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("关闭数值后只用颜色表示使用率，核心很多时更紧凑；只选一项时以大字号显示")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    let display = &mut config.cell_display;
+    let mut changed = ui.checkbox(&mut display.numbers, "显示数值").changed();
+    ui.add_enabled_ui(display.numbers, |ui| {
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut display.usage, "使用率").changed();
+            changed |= ui.checkbox(&mut display.frequency, "频率").changed();
+            changed |= ui.checkbox(&mut display.temperature, "温度")
+                .on_hover_text("Intel 读取单核传感器，其他平台显示封装温度")
+                .changed();
+        });
+    });
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("空闲阈值").color(Color32::from_gray(160)));
+        changed |= ui.add(Slider::new(&mut config.idle_core_threshold, 1.0..=50.0).suffix("%").fixed_decimals(0))
+            .on_hover_text("开启\"只显示繁忙核心\"时，使用率低于此值的核心会被隐藏")
+            .changed();
+    });
+    changed
+}
+
+/// 低于标称频率检测规则，有修改时返回 true
+fn draw_underclock_rule(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("低频检测").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("有负载的核心持续低于标称频率时在网格中标记 ↓（可能是功耗墙、调速器 / EPP 偏节能或驱动卡住）")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    let rule = &mut config.underclock;
+    let mut changed = ui.checkbox(&mut rule.enabled, "启用").changed();
+    ui.add_enabled_ui(rule.enabled, |ui| {
+        egui::Grid::new("underclock_rule")
+            .num_columns(2)
+            .spacing([16.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("低于标称").color(Color32::from_gray(160)));
+                changed |= ui.add(Slider::new(&mut rule.below_percent, 5.0..=80.0).suffix("%").fixed_decimals(0)).changed();
+                ui.end_row();
+
+                ui.label(RichText::new("持续").color(Color32::from_gray(160)));
+                changed |= ui.add(DragValue::new(&mut rule.duration_secs).range(1..=600).suffix(" 秒"))
+                    .on_hover_text("受历史记录长度限制，超过历史窗口的时长永远不会触发")
+                    .changed();
+                ui.end_row();
+
+                ui.label(RichText::new("使用率高于").color(Color32::from_gray(160)));
+                changed |= ui.add(Slider::new(&mut rule.min_usage_percent, 0.0..=100.0).suffix("%").fixed_decimals(0)).changed();
+                ui.end_row();
+            });
+    });
+    changed
+}
+
+/// 进程表密度，有修改时返回 true
+fn draw_table_density(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("进程表").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("紧凑模式减小行距和字号，在较矮的屏幕上显示更多进程")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("密度").color(Color32::from_gray(160)));
+        for density in TableDensity::ALL {
+            changed |= ui.radio_value(&mut config.table_density, density, density.label()).changed();
+        }
+    });
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("完整刷新").color(Color32::from_gray(160)));
+        changed |= ui.add(DragValue::new(&mut config.full_process_refresh_cycles).range(1..=30).prefix("每 ").suffix(" 个周期"))
+            .on_hover_text(format!(
+                "其余周期只刷新 CPU 使用率高于 {}% 的、选中的、监视中的和匹配搜索的进程\n\
+                 新进程在下一次完整刷新时出现；设为 1 时每个周期都完整刷新",
+                HOT_PROCESS_CPU_PERCENT
+            ))
+            .changed();
+    });
+    changed
+}
+
+/// 各 CCD 的强调色，有修改时返回 true
+fn draw_ccd_colors(ui: &mut Ui, config: &mut AppConfig, cpu_info: &CpuInfo) -> bool {
+    ui.label(RichText::new("CCD 颜色").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("用于核心网格边框、缓存信息和进程列表中的 CCD 标记")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    let mut colors = CcdPalette::resolve(&config.ccd_colors, &cpu_info.l3_caches).to_config();
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        for entry in &mut colors {
+            ui.label(format!("CCD {}", entry.l3_id));
+            changed |= ui.color_edit_button_srgb(&mut entry.rgb).changed();
+            ui.add_space(8.0);
+        }
+    });
+    if changed {
+        config.ccd_colors = colors;
+    }
+    if !config.ccd_colors.is_empty() && ui.button("恢复默认").clicked() {
+        config.ccd_colors.clear();
+        changed = true;
+    }
+    changed
+}
+
+/// 核心使用率的数据来源，有修改时返回 true
+fn draw_usage_source(ui: &mut Ui, config: &mut AppConfig) -> bool {
+    ui.label(RichText::new("使用率数据源").size(16.0).strong());
+    ui.add_space(4.0);
+    ui.label(RichText::new("/proc/stat 与内核统计口径一致（用户态 / 内核态 / iowait 分项总是来自 /proc/stat）")
+        .size(11.0).color(Color32::from_gray(140)));
+    ui.add_space(12.0);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        for source in [UsageSource::Sysinfo, UsageSource::ProcStat] {
+            changed |= ui.radio_value(&mut config.usage_source, source, source.display_name()).changed();
+        }
+    });
+    changed
+}
@@ -0,0 +1,152 @@
+//! 界面配色 - 各 CCD 的强调色在核心网格、缓存信息和进程列表中保持一致
+
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::system::L3CacheInfo;
+
+/// 3D V-Cache CCD 的默认颜色
+const VCACHE_COLOR: Color32 = Color32::from_rgb(100, 200, 100);
+
+/// 其他 CCD 的默认调色板（按 L3 ID 顺序分配，避开 V-Cache 的绿色）
+const CCD_PALETTE: [Color32; 6] = [
+    Color32::from_rgb(100, 150, 255),
+    Color32::from_rgb(255, 180, 100),
+    Color32::from_rgb(220, 120, 220),
+    Color32::from_rgb(80, 210, 220),
+    Color32::from_rgb(255, 220, 80),
+    Color32::from_rgb(255, 120, 120),
+];
+
+/// 保留核心的标记颜色
+pub const RESERVED_CORE_COLOR: Color32 = Color32::from_rgb(200, 110, 230);
+
+/// 用户为某个 CCD 指定的颜色（按 L3 缓存 ID）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CcdColor {
+    pub l3_id: u32,
+    pub rgb: [u8; 3],
+}
+
+impl CcdColor {
+    pub fn new(l3_id: u32, color: Color32) -> Self {
+        Self {
+            l3_id,
+            rgb: [color.r(), color.g(), color.b()],
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        Color32::from_rgb(self.rgb[0], self.rgb[1], self.rgb[2])
+    }
+}
+
+/// 当前拓扑下各 CCD 的强调色
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CcdPalette {
+    colors: BTreeMap<u32, Color32>,
+}
+
+impl CcdPalette {
+    /// 默认颜色：V-Cache CCD 为绿色，其余依次取调色板
+    pub fn defaults(l3_caches: &[L3CacheInfo]) -> Self {
+        let mut next = 0;
+        let colors = l3_caches
+            .iter()
+            .map(|cache| {
+                let color = if cache.is_vcache {
+                    VCACHE_COLOR
+                } else {
+                    next += 1;
+                    CCD_PALETTE[(next - 1) % CCD_PALETTE.len()]
+                };
+                (cache.id, color)
+            })
+            .collect();
+        Self { colors }
+    }
+
+    /// 应用保存的颜色；保存的 CCD 与当前检测到的不一致时（如更换了 CPU）全部使用默认颜色
+    pub fn resolve(saved: &[CcdColor], l3_caches: &[L3CacheInfo]) -> Self {
+        let mut palette = Self::defaults(l3_caches);
+        if matches_topology(saved, l3_caches) {
+            for entry in saved {
+                palette.colors.insert(entry.l3_id, entry.color());
+            }
+        }
+        palette
+    }
+
+    /// CCD 的强调色
+    pub fn color(&self, l3_id: u32) -> Option<Color32> {
+        self.colors.get(&l3_id).copied()
+    }
+
+    /// 转换为保存格式
+    pub fn to_config(&self) -> Vec<CcdColor> {
+        self.colors.iter().map(|(&id, &color)| CcdColor::new(id, color)).collect()
+    }
+}
+
+/// 保存的颜色是否正好覆盖当前检测到的所有 CCD
+pub fn matches_topology(saved: &[CcdColor], l3_caches: &[L3CacheInfo]) -> bool {
+    let mut saved_ids: Vec<u32> = saved.iter().map(|c| c.l3_id).collect();
+    let mut detected: Vec<u32> = l3_caches.iter().map(|c| c.id).collect();
+    saved_ids.sort_unstable();
+    detected.sort_unstable();
+    saved_ids == detected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(id: u32, is_vcache: bool) -> L3CacheInfo {
+        let size_kb = if is_vcache { 98304 } else { 32768 };
+        L3CacheInfo { id, size_kb, shared_cpus: Vec::new(), is_vcache }
+    }
+
+    #[test]
+    fn test_default_palette() {
+        let caches = [cache(0, true), cache(1, false), cache(2, false)];
+        let palette = CcdPalette::defaults(&caches);
+        // V-Cache CCD 固定为绿色，不占用调色板位置
+        assert_eq!(palette.color(0), Some(VCACHE_COLOR));
+        assert_eq!(palette.color(1), Some(CCD_PALETTE[0]));
+        assert_eq!(palette.color(2), Some(CCD_PALETTE[1]));
+        assert_eq!(palette.color(3), None);
+
+        // CCD 数多于调色板时循环使用
+        let many: Vec<L3CacheInfo> = (0..8).map(|id| cache(id, false)).collect();
+        let palette = CcdPalette::defaults(&many);
+        assert_eq!(palette.color(6), Some(CCD_PALETTE[0]));
+        assert_eq!(palette.color(7), Some(CCD_PALETTE[1]));
+        assert_eq!(CcdPalette::defaults(&[]), CcdPalette::default());
+    }
+
+    #[test]
+    fn test_resolve_saved_colors() {
+        let caches = [cache(0, true), cache(1, false)];
+        let custom = Color32::from_rgb(10, 20, 30);
+        let saved = [CcdColor::new(1, custom), CcdColor::new(0, VCACHE_COLOR)];
+        assert!(matches_topology(&saved, &caches));
+        let palette = CcdPalette::resolve(&saved, &caches);
+        assert_eq!(palette.color(1), Some(custom));
+        assert_eq!(palette.color(0), Some(VCACHE_COLOR));
+
+        // 保存格式按 L3 ID 排序，读回后颜色不变
+        let config = palette.to_config();
+        assert_eq!(config, vec![CcdColor::new(0, VCACHE_COLOR), CcdColor::new(1, custom)]);
+        assert_eq!(config[1].color(), custom);
+        assert_eq!(CcdPalette::resolve(&config, &caches), palette);
+
+        // 拓扑变化（缺少或多出 CCD）时全部回到默认颜色
+        let defaults = CcdPalette::defaults(&caches);
+        assert!(!matches_topology(&saved[..1], &caches));
+        assert_eq!(CcdPalette::resolve(&saved[..1], &caches), defaults);
+        let extra = [saved[0], saved[1], CcdColor::new(2, custom)];
+        assert_eq!(CcdPalette::resolve(&extra, &caches), defaults);
+        assert_eq!(CcdPalette::resolve(&[], &caches), defaults);
+    }
+}
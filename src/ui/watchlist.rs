@@ -0,0 +1,341 @@
+//! 监视列表 - 在所有标签页底部以小卡片显示固定的进程
+//! 固定的进程保存在配置中，重启 hexin 后按进程名和启动时间重新找到
+
+use eframe::egui::{self, Color32, Frame, Margin, RichText, Rounding, Ui};
+use std::time::{Duration, Instant};
+
+use crate::system::{
+    format_cpu_list, format_memory, l3_containing, preset_changes, L3CacheInfo, PinState, PinnedProcess,
+    PinnedWatchlist, ProcessInfo, ProcessManager, RuleResolution, SchedChange, SchedulePreset, SessionJournal,
+};
+use crate::ui::conflict::{ApplyGuard, CheckedApply};
+use crate::ui::theme::CcdPalette;
+
+/// 操作结果提示的显示时长
+const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+/// "已退出"提示的显示时长
+const EXITED_NOTE_DURATION: Duration = Duration::from_secs(5);
+/// 卡片宽度
+const CARD_WIDTH: f32 = 190.0;
+
+/// 卡片上的操作
+enum CardAction {
+    Unpin(usize),
+    ConfirmRebind(usize),
+    DeclineRebind(usize),
+    ApplyPreset(u32, SchedulePreset),
+    ApplyAffinity(u32, Vec<usize>),
+}
+
+/// 监视列表条
+pub struct WatchlistPanel {
+    /// 固定的进程
+    pinned: PinnedWatchlist,
+    /// 各 CCD 的强调色
+    ccd_palette: CcdPalette,
+    /// 多 CCD 时的 L3 缓存，用于显示进程当前所在的 CCD（单 CCD 时为空）
+    ccd_caches: Vec<L3CacheInfo>,
+    /// 最近一次操作的结果
+    message: Option<(Result<String, String>, Instant)>,
+    /// 最近退出的监视进程（PID, 名称, 退出时间）
+    exited: Vec<(u32, String, Instant)>,
+    /// 等待确认的预设 / 亲和性应用
+    checked: CheckedApply,
+}
+
+impl WatchlistPanel {
+    /// `pins` 为配置中保存的固定进程
+    pub fn new(pins: Vec<PinnedProcess>) -> Self {
+        Self {
+            pinned: PinnedWatchlist::new(pins),
+            ccd_palette: CcdPalette::default(),
+            ccd_caches: Vec::new(),
+            message: None,
+            exited: Vec::new(),
+            checked: CheckedApply::default(),
+        }
+    }
+
+    /// 取走用户在冲突提示中选择的规则处理
+    pub fn take_rule_resolutions(&mut self) -> Vec<RuleResolution> {
+        self.checked.take_resolutions()
+    }
+
+    /// 取走用户是否选择了不再提示 V-Cache 提醒
+    pub fn take_vcache_opt_out(&mut self) -> bool {
+        self.checked.take_vcache_opt_out()
+    }
+
+    /// 设置各 CCD 的强调色
+    pub fn set_ccd_palette(&mut self, palette: CcdPalette, l3_caches: &[L3CacheInfo]) {
+        self.ccd_palette = palette;
+        self.ccd_caches = if l3_caches.len() > 1 { l3_caches.to_vec() } else { Vec::new() };
+    }
+
+    /// 记录从监视列表中移除的已退出进程（保留其固定项）
+    pub fn add_exited(&mut self, exited: Vec<(u32, String)>) {
+        let now = Instant::now();
+        self.pinned.mark_exited(exited.iter().map(|(pid, _)| *pid));
+        self.exited.extend(exited.into_iter().map(|(pid, name)| (pid, name, now)));
+    }
+
+    /// 与进程管理器的监视列表同步；`rescan` 为 true 时为未运行的固定项查找进程（完整刷新后调用）
+    pub fn sync(&mut self, process_manager: &mut ProcessManager, rescan: bool) {
+        self.pinned.sync(process_manager, rescan);
+    }
+
+    /// 固定的进程有变化时取出要保存的列表
+    pub fn take_pins_changed(&mut self) -> Option<Vec<PinnedProcess>> {
+        self.pinned.take_changed()
+    }
+
+    /// 是否有需要显示的内容
+    pub fn is_visible(&self, process_manager: &ProcessManager) -> bool {
+        !self.pinned.is_empty() || !process_manager.watched().is_empty() || !self.exited.is_empty()
+    }
+
+    /// 绘制监视列表条，预设和亲和性经过 `guard` 检查后应用
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        process_manager: &mut ProcessManager,
+        session: &mut SessionJournal,
+        guard: &ApplyGuard,
+        presets: &[SchedulePreset],
+        logical_cores: usize,
+    ) {
+        if let Some(apply) = self.checked.ui(ui, guard) {
+            self.message = Some((apply.run(session, logical_cores), Instant::now()));
+        }
+        self.exited.retain(|(_, _, at)| at.elapsed() < EXITED_NOTE_DURATION);
+
+        let mut action = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(RichText::new("监视").size(12.0).color(Color32::from_gray(140)));
+            ui.add_space(4.0);
+            for (index, entry) in self.pinned.entries().iter().enumerate() {
+                let card = |ui: &mut Ui, contents: &mut dyn FnMut(&mut Ui)| {
+                    Frame::none()
+                        .fill(Color32::from_gray(45))
+                        .inner_margin(Margin::symmetric(8.0, 4.0))
+                        .rounding(Rounding::same(4.0))
+                        .show(ui, |ui| {
+                            ui.set_width(CARD_WIDTH);
+                            ui.vertical(|ui| contents(ui));
+                        })
+                        .response
+                };
+                match entry.state {
+                    PinState::Bound(pid) => {
+                        let Some(process) = process_manager.process(pid) else {
+                            continue;
+                        };
+                        let response = card(ui, &mut |ui| {
+                            if let Some(a) = self.draw_process_card(ui, index, process, presets, logical_cores) {
+                                action = Some(a);
+                            }
+                        });
+                        response.on_hover_text(format!(
+                            "{}\n亲和性: {}",
+                            process.display_command(),
+                            format_cpu_list(&process.affinity)
+                        ));
+                    }
+                    PinState::Restarted(pid) => {
+                        card(ui, &mut |ui| {
+                            ui.label(RichText::new(&entry.pin.name).size(12.0).color(Color32::WHITE));
+                            ui.label(RichText::new(format!("已重新启动 (PID {})，继续监视新进程？", pid))
+                                .size(11.0).color(Color32::from_rgb(255, 200, 100)));
+                            ui.horizontal(|ui| {
+                                if ui.small_button("继续监视").clicked() {
+                                    action = Some(CardAction::ConfirmRebind(index));
+                                }
+                                if ui.small_button("忽略").clicked() {
+                                    action = Some(CardAction::DeclineRebind(index));
+                                }
+                            });
+                        });
+                    }
+                    PinState::Missing => {
+                        card(ui, &mut |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&entry.pin.name).size(12.0).color(Color32::from_gray(150)));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("✕").on_hover_text("取消固定").clicked() {
+                                        action = Some(CardAction::Unpin(index));
+                                    }
+                                });
+                            });
+                            ui.label(RichText::new("未运行，启动后提示继续监视").size(11.0).color(Color32::from_gray(120)));
+                        });
+                    }
+                }
+            }
+            for (pid, name, _) in &self.exited {
+                ui.label(RichText::new(format!("{} ({}) 已退出", name, pid))
+                    .size(12.0).color(Color32::from_rgb(255, 180, 100)));
+            }
+            if let Some((result, _)) = &self.message {
+                let (text, color) = match result {
+                    Ok(msg) => (msg, Color32::from_rgb(100, 200, 100)),
+                    Err(msg) => (msg, Color32::from_rgb(255, 100, 100)),
+                };
+                ui.label(RichText::new(text).size(12.0).color(color));
+            }
+        });
+
+        if let Some(action) = action {
+            self.handle_action(action, process_manager, session, guard, logical_cores);
+        }
+        // 让结果提示和"已退出"提示按时消失
+        if self.message.as_ref().is_some_and(|(_, at)| at.elapsed() >= MESSAGE_DURATION) {
+            self.message = None;
+        }
+        if self.message.is_some() || !self.exited.is_empty() {
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    /// 已绑定进程的卡片：名称、CPU、内存、调度策略、当前 CCD 和预设 / 亲和性菜单
+    fn draw_process_card(
+        &self,
+        ui: &mut Ui,
+        index: usize,
+        process: &ProcessInfo,
+        presets: &[SchedulePreset],
+        logical_cores: usize,
+    ) -> Option<CardAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.add(egui::Label::new(RichText::new(format!("{} ({})", process.name, process.pid))
+                .size(12.0).color(Color32::WHITE)).truncate());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✕").on_hover_text("移出监视列表").clicked() {
+                    action = Some(CardAction::Unpin(index));
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{:.1}%", process.cpu_usage)).size(12.0).color(Color32::from_rgb(100, 180, 255)));
+            ui.label(RichText::new(format_memory(process.memory)).size(12.0).color(Color32::from_gray(180)));
+            let policy_color = if process.sched_policy.is_realtime() {
+                Color32::from_rgb(255, 200, 100)
+            } else {
+                Color32::from_gray(180)
+            };
+            Frame::none()
+                .fill(Color32::from_gray(60))
+                .inner_margin(Margin::symmetric(4.0, 0.0))
+                .rounding(Rounding::same(3.0))
+                .show(ui, |ui| {
+                    ui.label(RichText::new(process.sched_policy.short_name()).size(11.0).color(policy_color));
+                });
+            let ccd = process.last_cpu.and_then(|cpu| l3_containing(&[cpu], &self.ccd_caches));
+            if let Some(l3_id) = ccd {
+                let color = self.ccd_palette.color(l3_id).unwrap_or(Color32::from_gray(180));
+                ui.label(RichText::new(format!("CCD {}", l3_id)).size(11.0).color(color))
+                    .on_hover_text("进程最近运行所在的 CCD");
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.menu_button(RichText::new("预设").size(11.0), |ui| {
+                for preset in presets {
+                    if ui.button(&preset.name).on_hover_text(&preset.description).clicked() {
+                        action = Some(CardAction::ApplyPreset(process.pid, preset.clone()));
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.menu_button(RichText::new(format!("亲和性 {}", format_cpu_list(&process.affinity))).size(11.0), |ui| {
+                if let Some(cores) = self.draw_affinity_menu(ui, process, logical_cores) {
+                    action = Some(CardAction::ApplyAffinity(process.pid, cores));
+                    ui.close_menu();
+                }
+            });
+        });
+        action
+    }
+
+    /// 亲和性菜单：各核心的复选框，点击"应用"时返回选中的核心
+    fn draw_affinity_menu(&self, ui: &mut Ui, process: &ProcessInfo, logical_cores: usize) -> Option<Vec<usize>> {
+        // 菜单打开期间的选择保存在 egui 临时数据中
+        let id = egui::Id::new(("watchlist_affinity", process.pid));
+        let mut selection: Vec<bool> = ui.data_mut(|d| d.get_temp(id)).unwrap_or_else(|| {
+            (0..logical_cores).map(|core| process.affinity.contains(&core)).collect()
+        });
+        let mut result = None;
+        egui::Grid::new(id.with("grid")).spacing([4.0, 2.0]).show(ui, |ui| {
+            for (core, selected) in selection.iter_mut().enumerate() {
+                ui.checkbox(selected, core.to_string());
+                if core % 8 == 7 {
+                    ui.end_row();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            let cores: Vec<usize> = selection.iter().enumerate().filter(|(_, &s)| s).map(|(i, _)| i).collect();
+            if ui.add_enabled(!cores.is_empty(), egui::Button::new("应用")).clicked() {
+                result = Some(cores);
+            }
+        });
+        if result.is_some() {
+            ui.data_mut(|d| d.remove::<Vec<bool>>(id));
+        } else {
+            ui.data_mut(|d| d.insert_temp(id, selection));
+        }
+        result
+    }
+
+    fn handle_action(
+        &mut self,
+        action: CardAction,
+        process_manager: &mut ProcessManager,
+        session: &mut SessionJournal,
+        guard: &ApplyGuard,
+        logical_cores: usize,
+    ) {
+        match action {
+            CardAction::Unpin(index) => self.pinned.unpin(index, process_manager),
+            CardAction::ConfirmRebind(index) => self.pinned.confirm_rebind(index, process_manager),
+            CardAction::DeclineRebind(index) => self.pinned.decline_rebind(index),
+            CardAction::ApplyPreset(pid, preset) => match process_manager.process(pid) {
+                Some(process) => {
+                    let changes = preset_changes(&preset);
+                    self.request_apply(process, changes, Some(preset), session, guard, logical_cores);
+                }
+                None => self.message = Some((Err(format!("进程 {} 已退出", pid)), Instant::now())),
+            },
+            CardAction::ApplyAffinity(pid, cores) => match process_manager.process(pid) {
+                Some(process) => {
+                    let changes = vec![SchedChange::Affinity(cores)];
+                    self.request_apply(process, changes, None, session, guard, logical_cores);
+                }
+                None => self.message = Some((Err(format!("进程 {} 已退出", pid)), Instant::now())),
+            },
+        }
+    }
+
+    /// 经过与进程列表相同的检查后应用，需要确认时等待用户选择
+    fn request_apply(
+        &mut self,
+        process: &ProcessInfo,
+        changes: Vec<SchedChange>,
+        preset: Option<SchedulePreset>,
+        session: &mut SessionJournal,
+        guard: &ApplyGuard,
+        logical_cores: usize,
+    ) {
+        let result = match self.checked.request(guard, process, changes, preset) {
+            Ok(Some(apply)) => apply.run(session, logical_cores),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        self.message = Some((result, Instant::now()));
+    }
+}
+
+impl Default for WatchlistPanel {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
@@ -0,0 +1,314 @@
+//! 通用控件
+
+use eframe::egui::{
+    self, Color32, Key, Response, RichText, ScrollArea, Sense, Shape, Stroke, TextEdit, TextStyle, Ui, Vec2, Widget,
+    WidgetInfo, WidgetText, WidgetType,
+};
+use std::f32::consts::TAU;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// 按住确认时长
+const HOLD_DURATION: Duration = Duration::from_millis(600);
+
+/// 进度环半径
+const RING_RADIUS: f32 = 6.0;
+
+/// 范围滑块的手柄半径
+const HANDLE_RADIUS: f32 = 7.0;
+
+/// 命令行折叠时显示的最多字符数
+const COMMAND_PREVIEW_CHARS: usize = 160;
+
+/// 命令行区域的最大宽度
+const COMMAND_MAX_WIDTH: f32 = 420.0;
+
+/// 命令行展开后的最大高度，超出时滚动
+const COMMAND_MAX_HEIGHT: f32 = 160.0;
+
+/// 命令行显示：自动换行，过长时截断并提供"展开"，展开后在有限高度内滚动
+///
+/// 展开后的文本可选中，"复制"按钮始终复制完整命令行。提供参数时可切换为每行一个参数。
+pub struct CommandLine<'a> {
+    text: &'a str,
+    args: &'a [String],
+    id: egui::Id,
+}
+
+impl<'a> CommandLine<'a> {
+    /// `id_salt` 区分同时显示的多个命令行（如 PID）
+    pub fn new(text: &'a str, id_salt: impl Hash) -> Self {
+        Self {
+            text,
+            args: &[],
+            id: egui::Id::new(("command_line", id_salt)),
+        }
+    }
+
+    /// 提供拆分后的参数，多于一个时可切换为每行一个参数显示
+    pub fn args(mut self, args: &'a [String]) -> Self {
+        self.args = args;
+        self
+    }
+}
+
+/// 按字符截断，未超出时返回 None
+pub fn truncate_chars(text: &str, max_chars: usize) -> Option<&str> {
+    text.char_indices().nth(max_chars).map(|(end, _)| &text[..end])
+}
+
+impl Widget for CommandLine<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let preview = truncate_chars(self.text, COMMAND_PREVIEW_CHARS);
+        let mut expanded = ui.data(|d| d.get_temp::<bool>(self.id)).unwrap_or(false);
+        let lines_id = self.id.with("lines");
+        let mut one_per_line = self.args.len() > 1 && ui.data(|d| d.get_temp::<bool>(lines_id)).unwrap_or(false);
+
+        let response = ui
+            .vertical(|ui| {
+                ui.set_max_width(COMMAND_MAX_WIDTH);
+                match preview {
+                    _ if one_per_line => {
+                        ScrollArea::vertical().id_salt(lines_id).max_height(COMMAND_MAX_HEIGHT).show(ui, |ui| {
+                            for arg in self.args {
+                                ui.add(egui::Label::new(RichText::new(arg).monospace()).wrap());
+                            }
+                        });
+                    }
+                    Some(preview) if !expanded => {
+                        ui.add(egui::Label::new(RichText::new(format!("{}…", preview)).monospace()).wrap());
+                    }
+                    Some(_) => {
+                        ScrollArea::vertical().id_salt(self.id).max_height(COMMAND_MAX_HEIGHT).show(ui, |ui| {
+                            let mut text = self.text;
+                            ui.add(
+                                TextEdit::multiline(&mut text)
+                                    .font(TextStyle::Monospace)
+                                    .desired_rows(1)
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                    }
+                    None => {
+                        ui.add(egui::Label::new(RichText::new(self.text).monospace()).wrap());
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if preview.is_some() && !one_per_line {
+                        let label = if expanded {
+                            "收起".to_string()
+                        } else {
+                            format!("展开 ({} 字符)", self.text.chars().count())
+                        };
+                        if ui.small_button(label).clicked() {
+                            expanded = !expanded;
+                        }
+                    }
+                    if self.args.len() > 1 {
+                        ui.toggle_value(&mut one_per_line, "逐行显示参数")
+                            .on_hover_text(format!("每行一个参数（共 {} 个）", self.args.len()));
+                    }
+                    if ui.small_button("复制").on_hover_text("复制完整命令行").clicked() {
+                        ui.ctx().copy_text(self.text.to_string());
+                    }
+                });
+            })
+            .response;
+
+        ui.data_mut(|d| {
+            d.insert_temp(self.id, expanded);
+            d.insert_temp(lines_id, one_per_line);
+        });
+        response
+    }
+}
+
+/// 按住确认按钮：用于危险操作，按住（鼠标或空格键）直到进度环填满才触发
+///
+/// 触发当帧 `response.changed()` 为 true；提前松开、移出按钮或禁用时进度清零。
+/// 按住期间只触发一次，需松开后才能再次触发。
+pub struct HoldButton {
+    text: String,
+    color: Color32,
+    enabled: bool,
+}
+
+/// 按住状态：(开始时间, 本次按住是否已触发)
+#[derive(Clone, Copy)]
+struct HoldState {
+    start: f64,
+    fired: bool,
+}
+
+impl HoldButton {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: Color32::from_rgb(255, 100, 100),
+            enabled: true,
+        }
+    }
+
+    /// 文字和进度环颜色
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// 是否可用
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+impl Widget for HoldButton {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let padding = ui.spacing().button_padding;
+        let text_color = if self.enabled { self.color } else { Color32::from_gray(100) };
+        let galley = WidgetText::from(RichText::new(&self.text).color(text_color)).into_galley(
+            ui,
+            Some(egui::TextWrapMode::Extend),
+            f32::INFINITY,
+            egui::TextStyle::Button,
+        );
+        let ring_space = RING_RADIUS * 2.0 + ui.spacing().item_spacing.x;
+        let size = Vec2::new(
+            galley.size().x + ring_space + padding.x * 2.0,
+            (galley.size().y.max(RING_RADIUS * 2.0) + padding.y * 2.0).max(ui.spacing().interact_size.y),
+        );
+
+        let sense = if self.enabled { Sense::click() } else { Sense::hover() };
+        let (rect, mut response) = ui.allocate_exact_size(size, sense);
+        let enabled = self.enabled;
+        let label = self.text.clone();
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, enabled, &label));
+
+        // 按住判定：鼠标在按钮上按下且未移出，或获得焦点时按住空格
+        let pointer_hold = response.is_pointer_button_down_on() && response.contains_pointer();
+        let key_hold = response.has_focus() && ui.input(|i| i.key_down(Key::Space));
+        let holding = self.enabled && (pointer_hold || key_hold);
+
+        let id = response.id;
+        let now = ui.input(|i| i.time);
+        let mut progress = 0.0;
+        if holding {
+            let mut state = ui
+                .data(|d| d.get_temp::<HoldState>(id))
+                .unwrap_or(HoldState { start: now, fired: false });
+            progress = ((now - state.start) / HOLD_DURATION.as_secs_f64()).clamp(0.0, 1.0) as f32;
+            if progress >= 1.0 && !state.fired {
+                state.fired = true;
+                response.mark_changed();
+            }
+            if !state.fired {
+                ui.ctx().request_repaint();
+            }
+            ui.data_mut(|d| d.insert_temp(id, state));
+        } else {
+            ui.data_mut(|d| d.remove::<HoldState>(id));
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            painter.rect(rect, visuals.rounding, visuals.weak_bg_fill, visuals.bg_stroke);
+
+            let center = egui::pos2(rect.left() + padding.x + RING_RADIUS, rect.center().y);
+            let ring_color = if self.enabled { self.color } else { Color32::from_gray(90) };
+            painter.circle_stroke(center, RING_RADIUS, Stroke::new(1.5, Color32::from_gray(70)));
+            if progress > 0.0 {
+                let segments = ((progress * 32.0).ceil() as usize).max(2);
+                let points: Vec<egui::Pos2> = (0..=segments)
+                    .map(|i| {
+                        let angle = -TAU / 4.0 + TAU * progress * i as f32 / segments as f32;
+                        center + RING_RADIUS * Vec2::angled(angle)
+                    })
+                    .collect();
+                painter.add(Shape::line(points, Stroke::new(2.0, ring_color)));
+            }
+
+            let text_pos = egui::pos2(rect.left() + padding.x + ring_space, rect.center().y - galley.size().y / 2.0);
+            painter.galley(text_pos, galley, text_color);
+        }
+
+        let seconds = HOLD_DURATION.as_secs_f32();
+        if self.enabled {
+            response.on_hover_text(format!("按住 {:.1} 秒确认（键盘：聚焦后按住空格）", seconds))
+        } else {
+            response
+        }
+    }
+}
+
+/// 双滑块范围选择：拖动靠近指针的一端调整下限或上限
+///
+/// 拖动过程中 `response.changed()` 为 true，松开时 `response.drag_stopped()` 为 true（适合在此时才应用）。
+pub struct RangeSlider<'a> {
+    low: &'a mut u64,
+    high: &'a mut u64,
+    range: RangeInclusive<u64>,
+    width: f32,
+}
+
+impl<'a> RangeSlider<'a> {
+    pub fn new(low: &'a mut u64, high: &'a mut u64, range: RangeInclusive<u64>) -> Self {
+        Self {
+            low,
+            high,
+            range,
+            width: 220.0,
+        }
+    }
+}
+
+impl Widget for RangeSlider<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let height = ui.spacing().interact_size.y;
+        let (rect, mut response) = ui.allocate_exact_size(Vec2::new(self.width, height), Sense::drag());
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let span = max.saturating_sub(min).max(1) as f32;
+        let track = rect.shrink2(Vec2::new(HANDLE_RADIUS, 0.0));
+        let to_x = |value: u64| track.left() + track.width() * (value.saturating_sub(min) as f32 / span).clamp(0.0, 1.0);
+        let to_value = |x: f32| min + (((x - track.left()) / track.width()).clamp(0.0, 1.0) * span).round() as u64;
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            // 按下时选定拖动哪一端，之后保持不变
+            let id = response.id;
+            let drag_high = if response.drag_started() {
+                let (low_x, high_x) = (to_x(*self.low), to_x(*self.high));
+                let high = (pos.x - high_x).abs() < (pos.x - low_x).abs() || (low_x == high_x && pos.x > high_x);
+                ui.data_mut(|d| d.insert_temp(id, high));
+                high
+            } else {
+                ui.data(|d| d.get_temp(id)).unwrap_or(true)
+            };
+            let value = to_value(pos.x);
+            let (target, value) = if drag_high {
+                (&mut *self.high, value.max(*self.low))
+            } else {
+                (&mut *self.low, value.min(*self.high))
+            };
+            if *target != value {
+                *target = value;
+                response.mark_changed();
+            }
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            let y = rect.center().y;
+            painter.line_segment([egui::pos2(track.left(), y), egui::pos2(track.right(), y)], Stroke::new(4.0, Color32::from_gray(60)));
+            let (low_x, high_x) = (to_x(*self.low), to_x(*self.high));
+            painter.line_segment([egui::pos2(low_x, y), egui::pos2(high_x, y)], Stroke::new(4.0, Color32::from_rgb(100, 150, 255)));
+            for x in [low_x, high_x] {
+                painter.circle(egui::pos2(x, y), HANDLE_RADIUS, visuals.bg_fill, visuals.fg_stroke);
+            }
+        }
+
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::Slider, ui.is_enabled(), format!("{}-{}", self.low, self.high)));
+        response
+    }
+}
@@ -0,0 +1,112 @@
+//! 命令行参数
+
+/// 网页快照服务的默认绑定地址（仅本机可访问，局域网访问需要 --bind 0.0.0.0）
+pub const DEFAULT_SERVE_BIND: &str = "127.0.0.1";
+
+/// --tab 可用的标签页名称
+pub const TAB_NAMES: [&str; 5] = ["cpu", "processes", "scheduler", "settings", "debug"];
+
+/// 用法说明
+pub const USAGE: &str = "用法: hexin [选项]
+
+选项:
+  --serve <端口>   启动只读的网页快照服务（需要 serve feature）
+  --bind <地址>    网页快照服务的绑定地址，默认 127.0.0.1（局域网访问使用 0.0.0.0）
+  --no-gui         不显示窗口，只运行网页快照服务
+  --tab <名称>     打开指定标签页：cpu、processes、scheduler、settings、debug
+  --filter <文本>  设置进程过滤
+  已有实例在运行时，不会启动新窗口，而是显示已有窗口并应用 --tab / --filter
+  -h, --help       显示此说明";
+
+/// 解析后的命令行参数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliArgs {
+    /// 网页快照服务的监听地址（地址:端口），未指定 --serve 时为 None
+    pub serve: Option<String>,
+    /// 不显示窗口
+    pub no_gui: bool,
+    /// 打开的标签页（`TAB_NAMES` 之一）
+    pub tab: Option<String>,
+    /// 进程过滤文本
+    pub filter: Option<String>,
+    /// 只显示用法
+    pub help: bool,
+}
+
+/// 解析命令行参数（不含程序名）
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+    let mut parsed = CliArgs::default();
+    let mut port = None;
+    let mut bind = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--serve" => {
+                let value = args.next().ok_or("--serve 需要端口号")?;
+                port = Some(value.parse::<u16>().map_err(|_| format!("无效的端口号: {}", value))?);
+            }
+            "--bind" => bind = Some(args.next().ok_or("--bind 需要地址")?),
+            "--no-gui" => parsed.no_gui = true,
+            "--tab" => {
+                let tab = args.next().ok_or("--tab 需要标签页名称")?;
+                if !TAB_NAMES.contains(&tab.as_str()) {
+                    return Err(format!("未知标签页: {}（可用: {}）", tab, TAB_NAMES.join(", ")));
+                }
+                parsed.tab = Some(tab);
+            }
+            "--filter" => parsed.filter = Some(args.next().ok_or("--filter 需要过滤文本")?),
+            "-h" | "--help" => parsed.help = true,
+            _ => return Err(format!("未知参数: {}", arg)),
+        }
+    }
+
+    parsed.serve = match (port, bind) {
+        (Some(port), bind) => {
+            let bind = bind.unwrap_or_else(|| DEFAULT_SERVE_BIND.to_string());
+            // IPv6 地址需要加方括号
+            Some(if bind.contains(':') && !bind.starts_with('[') {
+                format!("[{}]:{}", bind, port)
+            } else {
+                format!("{}:{}", bind, port)
+            })
+        }
+        (None, Some(_)) => return Err("--bind 需要与 --serve 一起使用".to_string()),
+        (None, None) => None,
+    };
+    if parsed.no_gui && parsed.serve.is_none() {
+        return Err("--no-gui 需要与 --serve 一起使用".to_string());
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(CliArgs::default()));
+        assert_eq!(parse(&["--serve", "8080"]).unwrap().serve.as_deref(), Some("127.0.0.1:8080"));
+
+        let headless = parse(&["--no-gui", "--serve", "9000", "--bind", "0.0.0.0"]).unwrap();
+        assert_eq!(headless.serve.as_deref(), Some("0.0.0.0:9000"));
+        assert!(headless.no_gui);
+        assert_eq!(parse(&["--serve", "80", "--bind", "::"]).unwrap().serve.as_deref(), Some("[::]:80"));
+        assert!(parse(&["--help"]).unwrap().help);
+
+        let focus = parse(&["--tab", "scheduler", "--filter", "game"]).unwrap();
+        assert_eq!((focus.tab.as_deref(), focus.filter.as_deref()), (Some("scheduler"), Some("game")));
+        assert!(parse(&["--tab", "graphs"]).is_err());
+        assert!(parse(&["--filter"]).is_err());
+
+        assert!(parse(&["--serve"]).is_err());
+        assert!(parse(&["--serve", "99999"]).is_err());
+        assert!(parse(&["--bind", "0.0.0.0"]).is_err());
+        assert!(parse(&["--no-gui"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! 时钟抽象 - 生成历史时间戳的时间来源，测试中可替换为可控时钟
+//! 单调时钟在系统休眠期间不前进，因此同时记录累计休眠时间，用于发现休眠造成的间断
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use std::cell::Cell;
+
+/// 一个数据点的采样时间
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleTime {
+    /// 自启动以来的单调时间（秒，不包含休眠）
+    pub monotonic: f64,
+    /// 系统时间（Unix 时间戳秒）
+    pub wall: f64,
+    /// 开机以来的累计休眠时间（秒，CLOCK_BOOTTIME − CLOCK_MONOTONIC，不受系统时间调整影响）
+    pub suspended: f64,
+}
+
+/// 时间来源
+pub trait Clock {
+    /// 当前时刻
+    fn now(&self) -> Instant;
+
+    /// 当前系统时间
+    fn wall_now(&self) -> SystemTime;
+
+    /// 开机以来的累计休眠时间
+    fn suspended(&self) -> Duration;
+
+    /// 自 `start` 以来经过的秒数（用作历史时间戳）
+    fn seconds_since(&self, start: Instant) -> f64 {
+        self.now().saturating_duration_since(start).as_secs_f64()
+    }
+
+    /// 当前的采样时间（单调时间相对 `start`）
+    fn sample_time(&self, start: Instant) -> SampleTime {
+        SampleTime {
+            monotonic: self.seconds_since(start),
+            wall: self.wall_now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64(),
+            suspended: self.suspended().as_secs_f64(),
+        }
+    }
+}
+
+/// 系统单调时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn suspended(&self) -> Duration {
+        let read = |clock| {
+            let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+            // 两个时钟在 Linux 上总是可用
+            unsafe { libc::clock_gettime(clock, &mut ts) };
+            Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+        };
+        // 两次读取之间也在走时，用前后两次单调时间的平均值与启动时间对齐
+        let before = read(libc::CLOCK_MONOTONIC);
+        let boottime = read(libc::CLOCK_BOOTTIME);
+        let after = read(libc::CLOCK_MONOTONIC);
+        boottime.saturating_sub(before + (after - before) / 2)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn suspended(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// 测试用时钟：只在调用 `advance` 时前进
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    now: Cell<Instant>,
+    wall: Cell<SystemTime>,
+    suspended: Cell<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            now: Cell::new(Instant::now()),
+            wall: Cell::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            suspended: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// 让时间前进
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+        self.wall.set(self.wall.get() + duration);
+    }
+
+    /// 模拟系统休眠：单调时间不前进
+    pub fn suspend(&self, duration: Duration) {
+        self.wall.set(self.wall.get() + duration);
+        self.suspended.set(self.suspended.get() + duration);
+    }
+
+    /// 模拟系统时间被调整（如 NTP 校时）：只有系统时间变化
+    pub fn set_wall(&self, wall: SystemTime) {
+        self.wall.set(wall);
+    }
+
+    /// 以秒为单位让时间前进
+    pub fn advance_secs(&self, secs: f64) {
+        self.advance(Duration::from_secs_f64(secs));
+    }
+}
+
+#[cfg(test)]
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        self.wall.get()
+    }
+
+    fn suspended(&self) -> Duration {
+        self.suspended.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        assert_eq!(clock.seconds_since(start), 0.0);
+
+        clock.advance(Duration::from_millis(500));
+        clock.advance_secs(1.25);
+        assert!((clock.seconds_since(start) - 1.75).abs() < 1e-9);
+
+        // 起点晚于当前时刻时不出现负时间戳
+        assert_eq!(clock.seconds_since(clock.now() + Duration::from_secs(1)), 0.0);
+
+        // 休眠期间单调时间不前进
+        let before = clock.sample_time(start);
+        clock.suspend(Duration::from_secs(600));
+        let after = clock.sample_time(start);
+        assert_eq!(after.monotonic, before.monotonic);
+        assert_eq!(after.wall - before.wall, 600.0);
+        assert_eq!(after.suspended - before.suspended, 600.0);
+    }
+
+    #[test]
+    fn test_system_clock_suspended() {
+        let clock = SystemClock;
+        let before = clock.suspended();
+        // 两个时钟不是同时读取的，允许微小抖动，但不会明显倒退或增长
+        let after = clock.suspended();
+        let tolerance = Duration::from_millis(1);
+        assert!(after + tolerance >= before && after <= before + tolerance);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod args;
+pub mod clock;
+pub mod process_history;
+pub mod rate;
 pub mod ring_buffer;
 
+pub use args::{parse_args, USAGE};
+pub use clock::{Clock, SampleTime, SystemClock};
+pub use process_history::ProcessHistory;
 pub use ring_buffer::CpuHistory;
@@ -0,0 +1,134 @@
+//! 单个进程的历史数据 - 用于详情面板的曲线图
+
+use super::rate::CounterRate;
+use super::ring_buffer::RingBuffer;
+
+/// 单个进程的 CPU 使用率和调度延迟历史
+#[derive(Debug, Clone)]
+pub struct ProcessHistory {
+    /// 进程 ID
+    pid: u32,
+    /// CPU 使用率历史（时间戳，百分比）
+    cpu_usage: RingBuffer<[f64; 2]>,
+    /// 运行延迟历史（时间戳，每秒等待毫秒数）
+    run_delay: RingBuffer<[f64; 2]>,
+    /// 运行延迟速率计算
+    run_delay_rate: CounterRate,
+    /// 所在核心历史（时间戳，CPU 编号）
+    running_cpu: RingBuffer<[f64; 2]>,
+    /// 累计核心迁移次数历史（时间戳，次数）
+    migrations: RingBuffer<[f64; 2]>,
+    /// 累计核心迁移次数
+    migration_count: u64,
+}
+
+impl ProcessHistory {
+    pub fn new(pid: u32, history_size: usize) -> Self {
+        Self {
+            pid,
+            cpu_usage: RingBuffer::new(history_size),
+            run_delay: RingBuffer::new(history_size),
+            run_delay_rate: CounterRate::new(),
+            running_cpu: RingBuffer::new(history_size),
+            migrations: RingBuffer::new(history_size),
+            migration_count: 0,
+        }
+    }
+
+    /// 进程 ID
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// 添加数据点
+    /// - `run_delay_ns`: schedstat 中累计的运行延迟（纳秒），不可读时为 None
+    pub fn push(&mut self, cpu_usage: f32, run_delay_ns: Option<u64>, timestamp: f64) {
+        self.cpu_usage.push([timestamp, cpu_usage as f64]);
+
+        match run_delay_ns {
+            Some(delay) => {
+                if let Some(ns_per_sec) = self.run_delay_rate.update(delay, timestamp) {
+                    self.run_delay.push([timestamp, ns_per_sec / 1_000_000.0]);
+                }
+            }
+            None => self.run_delay_rate.reset(),
+        }
+    }
+
+    /// 记录当前所在核心，与上一次采样不同时计为一次迁移
+    pub fn push_cpu(&mut self, cpu: usize, timestamp: f64) {
+        if self.current_cpu().is_some_and(|last| last != cpu) {
+            self.migration_count += 1;
+        }
+        self.running_cpu.push([timestamp, cpu as f64]);
+        self.migrations.push([timestamp, self.migration_count as f64]);
+    }
+
+    /// 最近一次采样时所在的核心
+    pub fn current_cpu(&self) -> Option<usize> {
+        self.running_cpu.latest().map(|p| p[1] as usize)
+    }
+
+    /// 开始跟踪以来的核心迁移次数
+    pub fn migration_count(&self) -> u64 {
+        self.migration_count
+    }
+
+    /// 累计迁移次数绘图数据
+    pub fn migration_plot_data(&self) -> Vec<[f64; 2]> {
+        self.migrations.to_vec()
+    }
+
+    /// CPU 使用率绘图数据
+    pub fn cpu_plot_data(&self) -> Vec<[f64; 2]> {
+        self.cpu_usage.to_vec()
+    }
+
+    /// 运行延迟绘图数据 (ms/s)
+    pub fn run_delay_plot_data(&self) -> Vec<[f64; 2]> {
+        self.run_delay.to_vec()
+    }
+
+    /// 最新的运行延迟 (ms/s)
+    pub fn latest_run_delay(&self) -> Option<f64> {
+        self.run_delay.latest().map(|p| p[1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_history_run_delay() {
+        let mut history = ProcessHistory::new(42, 10);
+        history.push(10.0, Some(0), 0.0);
+        history.push(20.0, Some(5_000_000), 1.0);
+        history.push(30.0, Some(15_000_000), 2.0);
+
+        assert_eq!(history.cpu_plot_data().len(), 3);
+        assert_eq!(history.run_delay_plot_data(), vec![[1.0, 5.0], [2.0, 10.0]]);
+        assert_eq!(history.latest_run_delay(), Some(10.0));
+
+        // 计数器重置（re-exec）不产生负值
+        history.push(30.0, Some(1_000), 3.0);
+        assert_eq!(history.run_delay_plot_data().len(), 2);
+    }
+
+    #[test]
+    fn test_process_history_migrations() {
+        let mut history = ProcessHistory::new(42, 10);
+        assert_eq!(history.current_cpu(), None);
+        history.push_cpu(3, 0.0);
+        history.push_cpu(3, 0.5);
+        history.push_cpu(5, 1.0);
+        history.push_cpu(3, 1.5);
+
+        assert_eq!(history.current_cpu(), Some(3));
+        assert_eq!(history.migration_count(), 2);
+        assert_eq!(
+            history.migration_plot_data(),
+            vec![[0.0, 0.0], [0.5, 0.0], [1.0, 1.0], [1.5, 2.0]]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! 计数器速率计算 - 用于从累计计数器推导每秒增量
+
+/// 单调递增计数器的速率计算器
+#[derive(Debug, Clone, Default)]
+pub struct CounterRate {
+    /// 上一次的计数值和时间戳（秒）
+    last: Option<(u64, f64)>,
+}
+
+impl CounterRate {
+    pub fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// 输入新的计数值和时间戳（秒），返回每秒增量
+    ///
+    /// 首个样本、时间未前进或计数器回退（进程 re-exec / PID 复用）时返回 None，
+    /// 并以当前值重新建立基准
+    pub fn update(&mut self, value: u64, timestamp: f64) -> Option<f64> {
+        let rate = match self.last {
+            Some((last_value, last_time)) if value >= last_value && timestamp > last_time => {
+                Some((value - last_value) as f64 / (timestamp - last_time))
+            }
+            _ => None,
+        };
+        self.last = Some((value, timestamp));
+        rate
+    }
+
+    /// 清除基准
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_rate() {
+        let mut rate = CounterRate::new();
+        assert_eq!(rate.update(1000, 1.0), None);
+        assert_eq!(rate.update(3000, 2.0), Some(2000.0));
+        assert_eq!(rate.update(4000, 4.0), Some(500.0));
+
+        // 计数器回退视为重置
+        assert_eq!(rate.update(100, 5.0), None);
+        assert_eq!(rate.update(600, 6.0), Some(500.0));
+
+        // 时间未前进
+        assert_eq!(rate.update(700, 6.0), None);
+
+        rate.reset();
+        assert_eq!(rate.update(800, 7.0), None);
+    }
+}
@@ -1,6 +1,12 @@
 //! 环形缓冲区 - 用于存储历史数据
 
 use std::collections::VecDeque;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use super::{Clock, SampleTime};
+
+/// 相邻数据点之间休眠或停顿超过此时长 (秒) 时，图表在此处断开
+const GAP_THRESHOLD_SECS: f64 = 5.0;
 
 /// 固定大小的环形缓冲区
 #[derive(Debug, Clone)]
@@ -67,11 +73,40 @@ impl<T: Clone> RingBuffer<T> {
     }
 
     /// 迭代器
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
         self.data.iter()
     }
 }
 
+/// 按间断拆分的绘图数据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlotSegments {
+    /// 连续的曲线段
+    pub segments: Vec<Vec<[f64; 2]>>,
+    /// 间断的横轴范围（间断前最后一个点, 间断后第一个点）
+    pub gaps: Vec<[f64; 2]>,
+}
+
+/// 在间断处拆分一组数据点（相邻两点跨过某个间断的中点时断开）
+pub fn split_at_gaps(points: Vec<[f64; 2]>, gaps: &[[f64; 2]]) -> Vec<Vec<[f64; 2]>> {
+    let mut segments: Vec<Vec<[f64; 2]>> = Vec::new();
+    let mut previous: Option<f64> = None;
+    for point in points {
+        let crosses_gap = previous.is_some_and(|prev| {
+            gaps.iter().any(|&[start, end]| {
+                let middle = (start + end) / 2.0;
+                prev < middle && point[0] > middle
+            })
+        });
+        match segments.last_mut() {
+            Some(segment) if !crosses_gap => segment.push(point),
+            _ => segments.push(vec![point]),
+        }
+        previous = Some(point[0]);
+    }
+    segments
+}
+
 /// CPU 使用率历史记录
 #[derive(Debug, Clone)]
 pub struct CpuHistory {
@@ -79,8 +114,26 @@ pub struct CpuHistory {
     core_history: Vec<RingBuffer<f32>>,
     /// 总体使用率历史
     total_history: RingBuffer<f32>,
-    /// 时间戳
+    /// 时间戳：单调时间加上此前检测到的休眠时长，休眠后相对时间仍与实际经过的时间一致
     timestamps: RingBuffer<f64>,
+    /// 各数据点的系统时间（Unix 时间戳秒，未记录时为 None）
+    wall_times: RingBuffer<Option<f64>>,
+    /// 数据点与前一个点之间是否有间断（休眠或长时间停顿）
+    gap_before: RingBuffer<bool>,
+    /// CPU 压力 (PSI some avg10) 历史（时间戳，百分比）
+    pressure_history: RingBuffer<[f64; 2]>,
+    /// 总体内核态和 iowait 比例历史（时间戳，内核态 %，iowait %）
+    breakdown_history: RingBuffer<[f64; 3]>,
+    /// 每个核心的频率历史（时间戳，使用率 %，频率 MHz）
+    frequency_history: Vec<RingBuffer<[f64; 3]>>,
+    /// 每个核心累计消耗的 CPU 时间（核·秒）
+    core_seconds: Vec<f64>,
+    /// 上一个数据点的单调时间（用于按实际间隔积分）
+    last_timestamp: Option<f64>,
+    /// 上一个数据点时的累计休眠时间
+    last_suspended: Option<f64>,
+    /// 累计检测到的休眠时长（秒）
+    suspended_secs: f64,
 }
 
 impl CpuHistory {
@@ -97,11 +150,55 @@ impl CpuHistory {
             core_history,
             total_history: RingBuffer::new(history_size),
             timestamps: RingBuffer::new(history_size),
+            wall_times: RingBuffer::new(history_size),
+            gap_before: RingBuffer::new(history_size),
+            pressure_history: RingBuffer::new(history_size),
+            breakdown_history: RingBuffer::new(history_size),
+            frequency_history: (0..core_count).map(|_| RingBuffer::new(history_size)).collect(),
+            core_seconds: vec![0.0; core_count],
+            last_timestamp: None,
+            last_suspended: None,
+            suspended_secs: 0.0,
         }
     }
 
-    /// 添加新的数据点
+    /// 添加只有单调时间的数据点
+    #[cfg(test)]
     pub fn push(&mut self, core_usages: &[f32], total_usage: f32, timestamp: f64) {
+        self.push_sample(core_usages, total_usage, timestamp, None, None);
+    }
+
+    /// 按 `clock` 的当前时间（相对 `start`）记录一次刷新：各核心 (使用率, 频率) 和总使用率
+    /// 返回采样时间和它在历史时间轴上的时间戳（其他同时记录的数据应使用此时间戳）
+    pub fn record(
+        &mut self,
+        clock: &dyn Clock,
+        start: Instant,
+        core_samples: &[(f32, u64)],
+        total_usage: f32,
+    ) -> (SampleTime, f64) {
+        let time = clock.sample_time(start);
+        let core_usages: Vec<f32> = core_samples.iter().map(|&(usage, _)| usage).collect();
+        let timestamp = self.push_at(&core_usages, total_usage, time);
+        self.push_core_frequencies(core_samples, timestamp);
+        (time, timestamp)
+    }
+
+    /// 添加新的数据点，返回它在历史时间轴上的时间戳（其他同时记录的数据应使用此时间戳）
+    pub fn push_at(&mut self, core_usages: &[f32], total_usage: f32, time: SampleTime) -> f64 {
+        self.push_sample(core_usages, total_usage, time.monotonic, Some(time.wall), Some(time.suspended))
+    }
+
+    fn push_sample(
+        &mut self,
+        core_usages: &[f32],
+        total_usage: f32,
+        monotonic: f64,
+        wall: Option<f64>,
+        suspended: Option<f64>,
+    ) -> f64 {
+        let gap = self.detect_gap(monotonic, suspended);
+        let timestamp = monotonic + self.suspended_secs;
         for (i, &usage) in core_usages.iter().enumerate() {
             if i < self.core_history.len() {
                 self.core_history[i].push(usage);
@@ -109,6 +206,108 @@ impl CpuHistory {
         }
         self.total_history.push(total_usage);
         self.timestamps.push(timestamp);
+        self.wall_times.push(wall);
+        self.gap_before.push(gap);
+        self.accumulate_core_seconds(core_usages, monotonic);
+        self.last_suspended = suspended;
+        timestamp
+    }
+
+    /// 与上一个数据点之间是否有间断：累计休眠时间增加了很多（休眠），或单调时间本身间隔过长
+    /// 休眠按 CLOCK_BOOTTIME − CLOCK_MONOTONIC 判断，系统时间被调整（NTP、手动修改）不会误判
+    fn detect_gap(&mut self, monotonic: f64, suspended: Option<f64>) -> bool {
+        let Some(last) = self.last_timestamp else {
+            return false;
+        };
+        let elapsed = monotonic - last;
+        let suspended = match (self.last_suspended, suspended) {
+            (Some(before), Some(now)) => now - before,
+            _ => 0.0,
+        };
+        if suspended > GAP_THRESHOLD_SECS {
+            self.suspended_secs += suspended;
+            return true;
+        }
+        elapsed > GAP_THRESHOLD_SECS
+    }
+
+    /// 累加核心时间：使用率是上次刷新以来的平均值，乘以实际经过的时间，
+    /// 因此刷新间隔不规则或漏掉刷新时结果仍然准确；按单调时间计算，休眠时间不计入
+    fn accumulate_core_seconds(&mut self, core_usages: &[f32], timestamp: f64) {
+        if let Some(last) = self.last_timestamp {
+            let elapsed = timestamp - last;
+            if elapsed > 0.0 {
+                for (total, &usage) in self.core_seconds.iter_mut().zip(core_usages) {
+                    *total += usage.clamp(0.0, 100.0) as f64 / 100.0 * elapsed;
+                }
+            }
+        }
+        self.last_timestamp = Some(timestamp);
+    }
+
+    /// 一组核心累计消耗的 CPU 时间（核·秒）
+    pub fn core_seconds(&self, core_ids: impl IntoIterator<Item = usize>) -> f64 {
+        core_ids
+            .into_iter()
+            .filter_map(|id| self.core_seconds.get(id))
+            .sum()
+    }
+
+    /// 清零累计 CPU 时间
+    pub fn reset_core_seconds(&mut self) {
+        self.core_seconds.iter_mut().for_each(|t| *t = 0.0);
+    }
+
+    /// 添加 CPU 压力数据点
+    pub fn push_pressure(&mut self, pressure: f32, timestamp: f64) {
+        self.pressure_history.push([timestamp, pressure as f64]);
+    }
+
+    /// 获取 CPU 压力用于绘图的数据点
+    pub fn pressure_plot_data(&self) -> Vec<[f64; 2]> {
+        self.pressure_history.to_vec()
+    }
+
+    /// 添加总体内核态和 iowait 比例 (%)
+    pub fn push_breakdown(&mut self, system: f32, iowait: f32, timestamp: f64) {
+        self.breakdown_history.push([timestamp, system as f64, iowait as f64]);
+    }
+
+    /// 获取内核态和 iowait 用于绘图的数据点
+    pub fn breakdown_plot_data(&self) -> (Vec<[f64; 2]>, Vec<[f64; 2]>) {
+        self.breakdown_history
+            .iter()
+            .map(|&[t, system, iowait]| ([t, system], [t, iowait]))
+            .unzip()
+    }
+
+    /// 添加各核心的（使用率 %, 频率 MHz），与使用率历史相同按核心索引
+    pub fn push_core_frequencies(&mut self, samples: &[(f32, u64)], timestamp: f64) {
+        for (history, &(usage, mhz)) in self.frequency_history.iter_mut().zip(samples) {
+            history.push([timestamp, usage as f64, mhz as f64]);
+        }
+    }
+
+    /// 指定核心的频率历史（时间戳，使用率 %，频率 MHz）
+    pub fn core_frequency_samples(&self, core_id: usize) -> Option<impl DoubleEndedIterator<Item = &[f64; 3]>> {
+        self.frequency_history.get(core_id).map(|h| h.iter())
+    }
+
+    /// 最新数据点的时间戳，无数据时为 None
+    pub fn latest_timestamp(&self) -> Option<f64> {
+        self.timestamps.latest().copied()
+    }
+
+    /// 最近 `window_secs` 秒内总体使用率的平均值（以最新数据点的时间为准），无数据时为 None
+    pub fn average_over(&self, window_secs: f64) -> Option<f32> {
+        let latest = *self.timestamps.latest()?;
+        let (sum, count) = self
+            .timestamps
+            .iter()
+            .zip(self.total_history.iter())
+            .filter(|(&t, _)| latest - t <= window_secs)
+            .fold((0.0, 0), |(sum, count), (_, &u)| (sum + u, count + 1));
+        Some(sum / count as f32)
     }
 
     /// 获取指定核心的历史数据
@@ -138,6 +337,49 @@ impl CpuHistory {
             .collect()
     }
 
+    /// 获取以系统时间为横轴的绘图数据点（Unix 时间戳秒，使用率）
+    /// - `base_wall_time`: 时间戳为 0 时对应的系统时间（即启动时间），用于没有记录系统时间的数据点
+    pub fn wall_clock_plot_data(&self, base_wall_time: SystemTime) -> Vec<[f64; 2]> {
+        let base = base_wall_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.plot_data()
+            .into_iter()
+            .zip(self.wall_times.iter())
+            .map(|([t, u], wall)| [wall.unwrap_or(base + t), u])
+            .collect()
+    }
+
+    /// 按间断拆分绘图数据；data 与数据点一一对应（横轴可以是相对时间或系统时间）
+    fn segments(&self, data: Vec<[f64; 2]>) -> PlotSegments {
+        let gaps: Vec<[f64; 2]> = data
+            .windows(2)
+            .zip(self.gap_before.iter().skip(1))
+            .filter(|(_, &gap)| gap)
+            .map(|(pair, _)| [pair[0][0], pair[1][0]])
+            .collect();
+        PlotSegments {
+            segments: split_at_gaps(data, &gaps),
+            gaps,
+        }
+    }
+
+    /// 总体使用率的绘图数据，在休眠等间断处断开
+    pub fn plot_segments(&self) -> PlotSegments {
+        self.segments(self.plot_data())
+    }
+
+    /// 以系统时间为横轴的总体使用率绘图数据，在间断处断开
+    pub fn wall_clock_plot_segments(&self, base_wall_time: SystemTime) -> PlotSegments {
+        self.segments(self.wall_clock_plot_data(base_wall_time))
+    }
+
+    /// 指定核心的绘图数据，在间断处断开
+    pub fn core_plot_segments(&self, core_id: usize) -> PlotSegments {
+        self.segments(self.core_plot_data(core_id))
+    }
+
     /// 获取指定核心用于绘图的数据点
     pub fn core_plot_data(&self, core_id: usize) -> Vec<[f64; 2]> {
         let times = self.timestamps.to_vec();
@@ -168,6 +410,149 @@ impl CpuHistory {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_core_seconds_irregular_intervals() {
+        let mut history = CpuHistory::new(2, 4);
+        // 第一个点没有间隔，不计入
+        history.push(&[100.0, 50.0], 75.0, 10.0);
+        assert_eq!(history.core_seconds([0, 1]), 0.0);
+
+        // 正常间隔 0.5 秒
+        history.push(&[100.0, 50.0], 75.0, 10.5);
+        // 漏掉刷新：间隔 3 秒
+        history.push(&[50.0, 0.0], 25.0, 13.5);
+        // 时间戳未前进时忽略
+        history.push(&[100.0, 100.0], 100.0, 13.5);
+
+        assert!((history.core_seconds([0]) - 2.0).abs() < 1e-9);
+        assert!((history.core_seconds([1]) - 0.25).abs() < 1e-9);
+        assert!((history.core_seconds([0, 1, 7]) - 2.25).abs() < 1e-9);
+
+        // 超过历史长度后累计值不受影响
+        history.push(&[100.0, 0.0], 50.0, 14.5);
+        history.push(&[100.0, 0.0], 50.0, 15.5);
+        assert!((history.core_seconds([0]) - 4.0).abs() < 1e-9);
+
+        history.reset_core_seconds();
+        assert_eq!(history.core_seconds([0, 1]), 0.0);
+        history.push(&[100.0, 0.0], 50.0, 16.5);
+        assert!((history.core_seconds([0]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_history_with_mock_clock() {
+        use crate::utils::clock::{Clock, MockClock};
+
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut history = CpuHistory::new(1, 4);
+
+        // 刷新间隔不规则：0.5s、0.5s、2s（漏掉刷新）、0.5s、0.5s
+        for (step, usage) in [(0.0, 20.0), (0.5, 40.0), (0.5, 60.0), (2.0, 80.0), (0.5, 100.0), (0.5, 0.0)] {
+            clock.advance_secs(step);
+            let (time, timestamp) = history.record(&clock, start, &[(usage, 3000)], usage);
+            assert_eq!(timestamp, time.monotonic);
+        }
+
+        // 超出容量的旧数据被淘汰
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.timestamps(), vec![1.0, 3.0, 3.5, 4.0]);
+        assert_eq!(history.plot_data()[0], [1.0, 60.0]);
+
+        // 时间窗口统计
+        assert_eq!(history.average_over(1.0), Some((80.0 + 100.0 + 0.0) / 3.0));
+        assert_eq!(history.average_over(0.0), Some(0.0));
+        assert_eq!(history.average_over(60.0), Some(60.0));
+        assert_eq!(CpuHistory::new(1, 4).average_over(60.0), None);
+
+        // 按实际间隔积分：0.5*0.4 + 0.5*0.6 + 2*0.8 + 0.5*1.0 + 0.5*0
+        assert!((history.core_seconds([0]) - 2.6).abs() < 1e-9);
+        assert_eq!(history.core_frequency_samples(0).unwrap().last(), Some(&[4.0, 0.0, 3000.0]));
+    }
+
+    #[test]
+    fn test_wall_clock_plot_data() {
+        let mut history = CpuHistory::new(1, 4);
+        history.push(&[10.0], 10.0, 0.0);
+        history.push(&[20.0], 20.0, 1.5);
+
+        let base = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            history.wall_clock_plot_data(base),
+            vec![[1_700_000_000.0, 10.0], [1_700_000_001.5, 20.0]]
+        );
+    }
+
+    #[test]
+    fn test_suspend_gaps() {
+        use crate::utils::clock::{Clock, MockClock};
+        use std::time::Duration;
+
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut history = CpuHistory::new(1, 8);
+        let push = |history: &mut CpuHistory, usage: f32| history.record(&clock, start, &[(usage, 0)], usage).1;
+
+        push(&mut history, 10.0);
+        clock.advance_secs(1.0);
+        push(&mut history, 20.0);
+        // 休眠 10 分钟：单调时间不前进，时间轴加上休眠时长
+        clock.advance_secs(0.5);
+        clock.suspend(Duration::from_secs(600));
+        clock.advance_secs(0.5);
+        assert_eq!(push(&mut history, 30.0), 602.0);
+        clock.advance_secs(1.0);
+        push(&mut history, 40.0);
+
+        let plot = history.plot_segments();
+        assert_eq!(plot.segments, vec![vec![[0.0, 10.0], [1.0, 20.0]], vec![[602.0, 30.0], [603.0, 40.0]]]);
+        assert_eq!(plot.gaps, vec![[1.0, 602.0]]);
+        // 休眠时间不计入核心时间：1*0.2 + 1*0.3 + 1*0.4
+        assert!((history.core_seconds([0]) - 0.9).abs() < 1e-9);
+        assert_eq!(history.average_over(2.0), Some(35.0));
+
+        // 系统时间轴直接使用记录的系统时间
+        let wall = history.wall_clock_plot_segments(UNIX_EPOCH);
+        assert_eq!(wall.gaps, vec![[1_700_000_001.0, 1_700_000_602.0]]);
+        assert_eq!(wall.segments[1][0], [1_700_000_602.0, 30.0]);
+        assert_eq!(history.core_plot_segments(0).segments.len(), 2);
+
+        // hexin 长时间停顿（单调时间间隔过长）也视为间断，短的不规则间隔不算
+        clock.advance_secs(8.0);
+        push(&mut history, 50.0);
+        clock.advance_secs(3.0);
+        push(&mut history, 60.0);
+        let plot = history.plot_segments();
+        assert_eq!(plot.gaps, vec![[1.0, 602.0], [603.0, 611.0]]);
+        assert_eq!(plot.segments.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 2]);
+
+        // 只有单调时间的数据点不会被判为休眠
+        let mut plain = CpuHistory::new(1, 4);
+        plain.push(&[1.0], 1.0, 0.0);
+        plain.push(&[1.0], 1.0, 0.5);
+        assert_eq!(plain.plot_segments().segments.len(), 1);
+
+        // 系统时间被向前调整一小时不是休眠
+        let mut adjusted = CpuHistory::new(1, 4);
+        push(&mut adjusted, 1.0);
+        clock.advance_secs(1.0);
+        clock.set_wall(clock.wall_now() + Duration::from_secs(3600));
+        push(&mut adjusted, 2.0);
+        assert!(adjusted.plot_segments().gaps.is_empty());
+    }
+
+    #[test]
+    fn test_split_at_gaps() {
+        let points = vec![[0.0, 1.0], [1.0, 2.0], [10.0, 3.0], [11.0, 4.0]];
+        // 压力曲线等其他数据按同一组间断拆分
+        assert_eq!(
+            split_at_gaps(points.clone(), &[[1.0, 10.0]]),
+            vec![vec![[0.0, 1.0], [1.0, 2.0]], vec![[10.0, 3.0], [11.0, 4.0]]]
+        );
+        assert_eq!(split_at_gaps(points.clone(), &[]).len(), 1);
+        assert!(split_at_gaps(Vec::new(), &[[1.0, 10.0]]).is_empty());
+    }
+
     #[test]
     fn test_ring_buffer() {
         let mut buf: RingBuffer<i32> = RingBuffer::new(3);
@@ -194,5 +579,16 @@ mod tests {
         assert_eq!(history.len(), 2);
         assert_eq!(history.core_history(0), Some(vec![10.0, 30.0]));
         assert_eq!(history.total_history(), vec![15.0, 35.0]);
+
+        history.push_pressure(2.5, 2.0);
+        assert_eq!(history.pressure_plot_data(), vec![[2.0, 2.5]]);
+
+        history.push_breakdown(12.0, 3.0, 2.0);
+        assert_eq!(history.breakdown_plot_data(), (vec![[2.0, 12.0]], vec![[2.0, 3.0]]));
+
+        history.push_core_frequencies(&[(30.0, 3200), (40.0, 1800)], 2.0);
+        let samples: Option<Vec<[f64; 3]>> = history.core_frequency_samples(1).map(|s| s.copied().collect());
+        assert_eq!(samples, Some(vec![[2.0, 40.0, 1800.0]]));
+        assert!(history.core_frequency_samples(2).is_none());
     }
 }